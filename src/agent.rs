@@ -4,17 +4,19 @@ use std::path::Path;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use futures_util::future::join_all;
 use tokio::sync::mpsc;
 
 use crate::agent::session::{Session, SessionError};
 use crate::agent::subagent_manager::{SubagentManager, SubagentStatus};
-use crate::llm::{HttpProvider, Message, Role};
+use crate::llm::usage::{self, UsageScope};
+use crate::llm::{GenParams, LlmProvider, Message, Role, ToolCall};
 use crate::memory::db::BrainDb;
 use crate::skills::{self, SkillsError};
-use crate::telegram::OutboundMsg;
+use crate::telegram::{OutboundKind, OutboundMsg};
 use crate::tools::context::ToolCtx;
 use crate::tools::registry::ToolRegistry;
-use context::build_messages;
+use context::{build_messages, trim_to_budget};
 
 pub mod context;
 pub mod session;
@@ -22,6 +24,33 @@ pub mod subagent_manager;
 pub mod summarize;
 
 const MAX_ITERATIONS: u32 = 20;
+/// Saved facts (memory tool) added to the system prompt each turn.
+const PROMPT_MEMORIES: usize = 10;
+/// Mentioned entities (entity tool) added to the system prompt each turn.
+const PROMPT_ENTITIES: usize = 5;
+/// Earlier sessions recalled in the system prompt while a conversation is new.
+const PROMPT_EARLIER_SESSIONS: usize = 3;
+
+/// Per-turn settings for [`process_message_streaming`], from `[llm]` in config.
+#[derive(Debug, Clone)]
+pub struct TurnOptions {
+    /// Approximate prompt size history is trimmed to (`context-budget`).
+    pub context_budget: usize,
+    /// Sampling for the agent's own calls (`[llm.agent]`).
+    pub agent: GenParams,
+    /// Sampling for session summaries (`[llm.summarize]`).
+    pub summarize: GenParams,
+}
+
+impl Default for TurnOptions {
+    fn default() -> Self {
+        Self {
+            context_budget: context::DEFAULT_CONTEXT_BUDGET,
+            agent: GenParams::default(),
+            summarize: GenParams::default(),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum AgentError {
@@ -75,20 +104,64 @@ impl From<SkillsError> for AgentError {
 // Inner agent loop (shared by main agent and subagent)
 // ---------------------------------------------------------------------------
 
+/// Execute one tool call and return its output for the LLM. A `for_user` result is sent to
+/// the chat right away.
+async fn run_tool_call(registry: &ToolRegistry, tool_ctx: &ToolCtx, tc: &ToolCall) -> String {
+    let args = match serde_json::from_str::<serde_json::Value>(&tc.function.arguments) {
+        Ok(v) => v,
+        Err(e) => return format!("Invalid JSON arguments: {}", e),
+    };
+
+    let result = registry.execute(tool_ctx, &tc.function.name, &args).await;
+
+    if let Some(ref text) = result.for_user {
+        if !result.silent {
+            if let (Some(tx), Some(cid)) = (tool_ctx.outbound_tx.as_ref(), tool_ctx.chat_id) {
+                let _ = tx.try_send(OutboundMsg {
+                    chat_id: cid,
+                    text: text.clone(),
+                    channel: tool_ctx
+                        .channel
+                        .clone()
+                        .unwrap_or_else(|| "telegram".to_string()),
+                    kind: OutboundKind::Text,
+                    thread_id: tool_ctx.thread_id,
+                    disable_notification: false,
+                });
+                tool_ctx.delivered.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+    result.for_llm
+}
+
 /// Pure agent loop: given messages and tools, call LLM repeatedly until no
 /// tool_calls remain.  Returns final assistant content.  No session I/O.
+/// With `deltas`, each LLM call is streamed and its content forwarded as it arrives.
+#[allow(clippy::too_many_arguments)]
 pub async fn run_agent_loop(
-    llm: &HttpProvider,
+    llm: &dyn LlmProvider,
     registry: &ToolRegistry,
     mut messages: Vec<Message>,
     tool_ctx: &ToolCtx,
     model: &str,
+    params: &GenParams,
     max_iterations: u32,
+    deltas: Option<&mpsc::UnboundedSender<String>>,
 ) -> Result<String, AgentError> {
     let tool_defs = registry.to_tool_defs();
 
     for _iter in 1..=max_iterations {
-        let response = llm.chat(&messages, &tool_defs, model).await?;
+        let response = match deltas {
+            Some(tx) => {
+                llm.chat_stream(&messages, &tool_defs, model, params, tx)
+                    .await?
+            }
+            None => {
+                llm.chat_with_params(&messages, &tool_defs, model, params)
+                    .await?
+            }
+        };
 
         if response.tool_calls.is_empty() {
             let content = response.content.trim().to_string();
@@ -104,47 +177,36 @@ pub async fn run_agent_loop(
             content: response.content,
             tool_call_id: None,
             tool_calls: Some(response.tool_calls.clone()),
+            images: Vec::new(),
         });
 
-        for tc in &response.tool_calls {
-            let args = match serde_json::from_str::<serde_json::Value>(&tc.function.arguments) {
-                Ok(v) => v,
-                Err(e) => {
-                    messages.push(Message {
-                        role: Role::Tool,
-                        content: format!("Invalid JSON arguments: {}", e),
-                        tool_call_id: Some(tc.id.clone()),
-                        tool_calls: None,
-                    });
-                    continue;
-                }
-            };
-
-            let result = registry.execute(tool_ctx, &tc.function.name, &args).await;
-
-            if let Some(ref text) = result.for_user {
-                if !result.silent {
-                    if let (Some(tx), Some(cid)) = (tool_ctx.outbound_tx.as_ref(), tool_ctx.chat_id)
-                    {
-                        let _ = tx.try_send(OutboundMsg {
-                            chat_id: cid,
-                            text: text.clone(),
-                            channel: tool_ctx
-                                .channel
-                                .clone()
-                                .unwrap_or_else(|| "telegram".to_string()),
-                        });
-                        tool_ctx.delivered.store(true, Ordering::Relaxed);
-                    }
+        // Runs of consecutive read-only calls execute concurrently; anything with side
+        // effects runs on its own, in order. Results keep the order of the calls.
+        let calls = &response.tool_calls;
+        let mut start = 0;
+        while start < calls.len() {
+            let mut end = start + 1;
+            if registry.is_read_only(&calls[start].function.name) {
+                while end < calls.len() && registry.is_read_only(&calls[end].function.name) {
+                    end += 1;
                 }
             }
-
-            messages.push(Message {
-                role: Role::Tool,
-                content: result.for_llm,
-                tool_call_id: Some(tc.id.clone()),
-                tool_calls: None,
-            });
+            let outputs = join_all(
+                calls[start..end]
+                    .iter()
+                    .map(|tc| run_tool_call(registry, tool_ctx, tc)),
+            )
+            .await;
+            for (tc, content) in calls[start..end].iter().zip(outputs) {
+                messages.push(Message {
+                    role: Role::Tool,
+                    content,
+                    tool_call_id: Some(tc.id.clone()),
+                    tool_calls: None,
+                    images: Vec::new(),
+                });
+            }
+            start = end;
         }
     }
 
@@ -158,7 +220,38 @@ pub async fn run_agent_loop(
 /// Process one user message: load session, build context, run LLM loop until
 /// no tool_calls, persist session and return reply.
 pub async fn process_message(
-    llm: &HttpProvider,
+    llm: &dyn LlmProvider,
+    registry: &ToolRegistry,
+    workspace_path: &Path,
+    model: &str,
+    timezone: &str,
+    chat_id: &str,
+    user_message: &str,
+    tool_ctx: &ToolCtx,
+    db: &Arc<BrainDb>,
+) -> Result<String, AgentError> {
+    process_message_streaming(
+        llm,
+        registry,
+        workspace_path,
+        model,
+        timezone,
+        chat_id,
+        user_message,
+        tool_ctx,
+        db,
+        &TurnOptions::default(),
+        None,
+    )
+    .await
+}
+
+/// [`process_message`] that streams reply text to `deltas` as the LLM generates it
+/// (used for live-edited Telegram replies). The returned reply is the final content.
+/// History is trimmed so the prompt stays within `opts.context_budget` tokens.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_message_streaming(
+    llm: &dyn LlmProvider,
     registry: &ToolRegistry,
     workspace_path: &Path,
     model: &str,
@@ -167,12 +260,16 @@ pub async fn process_message(
     user_message: &str,
     tool_ctx: &ToolCtx,
     db: &Arc<BrainDb>,
+    opts: &TurnOptions,
+    deltas: Option<&mpsc::UnboundedSender<String>>,
 ) -> Result<String, AgentError> {
     let mut session = Session::load(Arc::clone(db), chat_id).await?;
 
     // Check if summarization is needed (before building context so summary is included)
     if session.history().len() > summarize::SUMMARIZE_THRESHOLD {
-        if let Err(e) = summarize::summarize_if_needed(llm, &mut session, model).await {
+        if let Err(e) =
+            summarize::summarize_if_needed(llm, &mut session, model, &opts.summarize).await
+        {
             eprintln!("Warning: summarization failed: {}", e);
             // Continue anyway — summarization is optimization
         }
@@ -181,8 +278,17 @@ pub async fn process_message(
     let skills_summary = skills::build_skills_summary(workspace_path)?;
     let tool_summaries = registry.summaries();
 
+    // Until this conversation has a summary of its own, recall what earlier ones were about.
+    let recall = if session.summary().is_empty() {
+        earlier_sessions(db, chat_id).await
+    } else {
+        String::new()
+    };
+    let memories = relevant_memories(db, user_message).await;
+    let entities = mentioned_entities(db, user_message).await;
+
     let today = crate::workspace::today_yyyymmdd();
-    let messages = build_messages(
+    let mut messages = build_messages(
         workspace_path,
         timezone,
         session.history(),
@@ -192,25 +298,97 @@ pub async fn process_message(
         &skills_summary,
         &tool_summaries,
         Some(&today),
+        &recall,
+        &memories,
+        &entities,
     );
+    trim_to_budget(&mut messages, opts.context_budget);
     session.add_user_message(user_message);
 
-    let final_content =
-        run_agent_loop(llm, registry, messages, tool_ctx, model, MAX_ITERATIONS).await?;
+    let final_content = run_agent_loop(
+        llm,
+        registry,
+        messages,
+        tool_ctx,
+        model,
+        &opts.agent,
+        MAX_ITERATIONS,
+        deltas,
+    )
+    .await?;
 
     session.add_assistant_message(&final_content, None);
     session.save().await?;
     Ok(final_content)
 }
 
+/// The chat's most recently archived sessions, formatted for the system prompt. Lookup
+/// failures are logged and yield none.
+async fn earlier_sessions(db: &Arc<BrainDb>, chat_id: &str) -> String {
+    let db = Arc::clone(db);
+    let chat_id = chat_id.to_string();
+    match tokio::task::spawn_blocking(move || {
+        db.recent_archived_sessions(&chat_id, PROMPT_EARLIER_SESSIONS)
+    })
+    .await
+    {
+        Ok(Ok(sessions)) => crate::tools::session::format_recall(&sessions),
+        Ok(Err(e)) => {
+            eprintln!("Warning: session recall failed: {}", e);
+            String::new()
+        }
+        Err(e) => {
+            eprintln!("Warning: session recall task failed: {}", e);
+            String::new()
+        }
+    }
+}
+
+/// Saved facts relevant to `user_message`, formatted for the system prompt. Lookup
+/// failures are logged and yield no memories.
+async fn relevant_memories(db: &Arc<BrainDb>, user_message: &str) -> String {
+    let db = Arc::clone(db);
+    let text = user_message.to_string();
+    match tokio::task::spawn_blocking(move || db.relevant_memories(&text, PROMPT_MEMORIES)).await {
+        Ok(Ok(facts)) => crate::tools::memory::format_facts(&facts),
+        Ok(Err(e)) => {
+            eprintln!("Warning: memory lookup failed: {}", e);
+            String::new()
+        }
+        Err(e) => {
+            eprintln!("Warning: memory lookup task failed: {}", e);
+            String::new()
+        }
+    }
+}
+
+/// Entities named in `user_message`, formatted for the system prompt. Lookup failures are
+/// logged and yield none.
+async fn mentioned_entities(db: &Arc<BrainDb>, user_message: &str) -> String {
+    let db = Arc::clone(db);
+    let text = user_message.to_string();
+    match tokio::task::spawn_blocking(move || db.mentioned_entities(&text, PROMPT_ENTITIES)).await {
+        Ok(Ok(entities)) => crate::tools::entity::format_entities(&entities),
+        Ok(Err(e)) => {
+            eprintln!("Warning: entity lookup failed: {}", e);
+            String::new()
+        }
+        Err(e) => {
+            eprintln!("Warning: entity lookup task failed: {}", e);
+            String::new()
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Heartbeat agent entry point (one-shot, no session)
 // ---------------------------------------------------------------------------
 
 /// One-shot run for heartbeat: same context as `process_message` but with empty
-/// history and summary.  No session load or save.
+/// history and summary.  No session load or save.  `params` are the main agent's.
+#[allow(clippy::too_many_arguments)]
 pub async fn process_heartbeat_message(
-    llm: &HttpProvider,
+    llm: &dyn LlmProvider,
     registry: &ToolRegistry,
     workspace_path: &Path,
     model: &str,
@@ -218,6 +396,7 @@ pub async fn process_heartbeat_message(
     chat_id: &str,
     user_message: &str,
     tool_ctx: &ToolCtx,
+    params: &GenParams,
 ) -> Result<String, AgentError> {
     let skills_summary = skills::build_skills_summary(workspace_path)?;
     let tool_summaries = registry.summaries();
@@ -232,8 +411,21 @@ pub async fn process_heartbeat_message(
         &skills_summary,
         &tool_summaries,
         Some(&today),
+        "",
+        "",
+        "",
     );
-    run_agent_loop(llm, registry, messages, tool_ctx, model, MAX_ITERATIONS).await
+    run_agent_loop(
+        llm,
+        registry,
+        messages,
+        tool_ctx,
+        model,
+        params,
+        MAX_ITERATIONS,
+        None,
+    )
+    .await
 }
 
 // ---------------------------------------------------------------------------
@@ -243,12 +435,14 @@ pub async fn process_heartbeat_message(
 /// Run a subagent to completion.  Builds a minimal system prompt (with skills
 /// and tool summaries), runs `run_agent_loop`, then updates the manager task
 /// state.  Called inside `tokio::spawn` — must not panic.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn run_subagent(
     manager: Arc<SubagentManager>,
     task_id: String,
     task: String,
-    _label: Option<String>,
+    label: Option<String>,
     chat_id: i64,
+    thread_id: Option<i64>,
     outbound_tx: Arc<mpsc::Sender<OutboundMsg>>,
     channel: String,
 ) {
@@ -289,12 +483,14 @@ pub(crate) async fn run_subagent(
             content: system,
             tool_call_id: None,
             tool_calls: None,
+            images: Vec::new(),
         },
         Message {
             role: Role::User,
             content: task,
             tool_call_id: None,
             tool_calls: None,
+            images: Vec::new(),
         },
     ];
 
@@ -305,15 +501,26 @@ pub(crate) async fn run_subagent(
         channel: Some(channel),
         outbound_tx: Some(outbound_tx),
         delivered: Default::default(),
+        location: None,
+        thread_id,
     };
 
-    match run_agent_loop(
-        manager.llm(),
-        manager.registry(),
-        messages,
-        &tool_ctx,
-        manager.model(),
-        manager.max_iterations(),
+    let usage_scope = UsageScope {
+        chat_id: crate::telegram::session_key(chat_id, thread_id),
+        source: format!("subagent:{}", label.as_deref().unwrap_or(&task_id)),
+    };
+    match usage::scoped(
+        usage_scope,
+        run_agent_loop(
+            manager.llm(),
+            manager.registry(),
+            messages,
+            &tool_ctx,
+            manager.model(),
+            manager.params(),
+            manager.max_iterations(),
+            None,
+        ),
     )
     .await
     {
@@ -1,4 +1,5 @@
 //! Build system prompt: identity, bootstrap files, memory snippet, skills summary, tool list.
+//! Trim the assembled context to a token budget.
 
 use std::path::Path;
 
@@ -7,9 +8,83 @@ use chrono::Offset as _;
 use crate::llm::{Message, Role};
 use crate::workspace;
 
-/// Build full message list for the LLM: [system, …history…, user].
+/// Prompt token budget when `[llm] context-budget` is unset.
+pub const DEFAULT_CONTEXT_BUDGET: usize = 32_000;
+/// Per-message framing (role, separators) in chat templates.
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Stands in for a tool output dropped by [`trim_to_budget`].
+const ELIDED_TOOL_OUTPUT: &str = "[tool output omitted to fit context]";
+
+/// Rough token count in the spirit of tiktoken: about four ASCII characters per token,
+/// one token per non-ASCII character (CJK, emoji).
+pub fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.chars().filter(char::is_ascii).count();
+    let other = text.chars().count() - ascii;
+    ascii.div_ceil(4) + other
+}
+
+/// Estimated tokens for one message, including tool call names and arguments.
+pub fn estimate_message_tokens(msg: &Message) -> usize {
+    let calls: usize = msg
+        .tool_calls
+        .iter()
+        .flatten()
+        .map(|tc| estimate_tokens(&tc.function.name) + estimate_tokens(&tc.function.arguments))
+        .sum();
+    MESSAGE_OVERHEAD_TOKENS + estimate_tokens(&msg.content) + calls
+}
+
+/// Shrink `[system…, …history…, user]` (from [`build_messages`]) to about `budget` tokens.
+///
+/// Old tool outputs go first: they are replaced with a short placeholder, oldest first, so
+/// the tool call/result pairing stays intact. If that is not enough, whole turns (a user
+/// message up to the next one) are dropped from the front. The leading system messages and the
+/// current user message are always kept, even when they alone exceed the budget.
+pub fn trim_to_budget(messages: &mut Vec<Message>, budget: usize) {
+    let mut total: usize = messages.iter().map(estimate_message_tokens).sum();
+    let history_start = messages
+        .iter()
+        .take_while(|m| m.role == Role::System)
+        .count()
+        .max(1);
+    if total <= budget || messages.len() <= history_start + 1 {
+        return;
+    }
+    let history_end = messages.len() - 1;
+
+    let elided = estimate_tokens(ELIDED_TOOL_OUTPUT);
+    for msg in &mut messages[history_start..history_end] {
+        if total <= budget {
+            return;
+        }
+        let tokens = estimate_tokens(&msg.content);
+        if msg.role == Role::Tool && tokens > elided {
+            total = total - tokens + elided;
+            msg.content = ELIDED_TOOL_OUTPUT.to_string();
+        }
+    }
+
+    let mut drop_end = history_start;
+    while total > budget && drop_end < history_end {
+        total -= estimate_message_tokens(&messages[drop_end]);
+        drop_end += 1;
+        // Never start the kept history mid-turn (e.g. on an orphaned tool result).
+        while drop_end < history_end && messages[drop_end].role != Role::User {
+            total -= estimate_message_tokens(&messages[drop_end]);
+            drop_end += 1;
+        }
+    }
+    messages.drain(history_start..drop_end);
+}
+
+/// Build full message list for the LLM: [system, session, …history…, user].
 /// System prompt order: identity → bootstrap (AGENT.md, USER.md, IDENTITY.md) → memory snippet →
-/// skills → tool list → current session (chat_id). Then history and current user message.
+/// skills → tool list. It only changes when those files do, so it is a stable prefix that
+/// providers can cache. The second system message holds what changes per request: current
+/// time, chat id, session summary, what earlier conversations of this chat were about
+/// (`recall`, preformatted lines), the remembered facts relevant to this message
+/// (`memories`, likewise) and the entities it mentions (`entities`, likewise).
+/// Then history and current user message.
 #[allow(clippy::too_many_arguments)]
 pub fn build_messages(
     workspace_path: &Path,
@@ -21,33 +96,14 @@ pub fn build_messages(
     skills_summary: &str,
     tool_summaries: &[String],
     today_yyyymmdd: Option<&str>,
+    recall: &str,
+    memories: &str,
+    entities: &str,
 ) -> Vec<Message> {
     let mut system = String::new();
 
-    // Identity: current local date/time with UTC offset, Unix timestamp, workspace
-    let tz: chrono_tz::Tz = timezone
-        .parse()
-        .expect("timezone was validated at startup; parse cannot fail here");
-    let now_utc = chrono::Utc::now();
-    let now_local = now_utc.with_timezone(&tz);
-    let offset_secs = now_local.offset().fix().local_minus_utc();
-    let offset_hours = offset_secs / 3600;
-    let offset_str = if offset_hours >= 0 {
-        format!("UTC+{}", offset_hours)
-    } else {
-        format!("UTC{}", offset_hours)
-    };
-    let now_unix = now_utc.timestamp();
-    let time_line = format!(
-        "Current time ({}, local): {} ({}). Unix: {}.",
-        offset_str,
-        now_local.format("%A %-d %b %Y %H:%M"),
-        timezone,
-        now_unix,
-    );
-    system.push_str("You are iCrab, a minimal personal AI assistant. ");
-    system.push_str(&time_line);
-    system.push_str(" Workspace: ");
+    // Identity: workspace
+    system.push_str("You are iCrab, a minimal personal AI assistant. Workspace: ");
     system.push_str(workspace_path.to_string_lossy().as_ref());
     system.push_str(".\n\n");
 
@@ -99,16 +155,51 @@ pub fn build_messages(
         }
     }
 
-    // Current session
+    // Current session: local date/time with UTC offset, Unix timestamp, chat, summary
+    let tz: chrono_tz::Tz = timezone
+        .parse()
+        .expect("timezone was validated at startup; parse cannot fail here");
+    let now_utc = chrono::Utc::now();
+    let now_local = now_utc.with_timezone(&tz);
+    let offset_secs = now_local.offset().fix().local_minus_utc();
+    let offset_hours = offset_secs / 3600;
+    let offset_str = if offset_hours >= 0 {
+        format!("UTC+{}", offset_hours)
+    } else {
+        format!("UTC{}", offset_hours)
+    };
+    let now_unix = now_utc.timestamp();
+    let time_line = format!(
+        "Current time ({}, local): {} ({}). Unix: {}.",
+        offset_str,
+        now_local.format("%A %-d %b %Y %H:%M"),
+        timezone,
+        now_unix,
+    );
+    let mut session = time_line;
     if let Some(cid) = chat_id {
-        system.push_str("\nCurrent chat: ");
-        system.push_str(cid);
-        system.push_str(".\n");
+        session.push_str("\nCurrent chat: ");
+        session.push_str(cid);
+        session.push('.');
     }
     if !summary.is_empty() {
-        system.push_str("\nSession summary: ");
-        system.push_str(summary);
-        system.push('\n');
+        session.push_str("\nSession summary: ");
+        session.push_str(summary);
+    }
+    if !recall.is_empty() {
+        session.push_str(
+            "\nWhat you might need to remember from earlier conversations (session tool \
+             can resume them):\n",
+        );
+        session.push_str(recall);
+    }
+    if !memories.is_empty() {
+        session.push_str("\nRemembered about the user (memory tool):\n");
+        session.push_str(memories);
+    }
+    if !entities.is_empty() {
+        session.push_str("\nPeople, places and projects mentioned (entity tool):\n");
+        session.push_str(entities);
     }
 
     let system_msg = Message {
@@ -116,16 +207,25 @@ pub fn build_messages(
         content: system.trim().to_string(),
         tool_call_id: None,
         tool_calls: None,
+        images: Vec::new(),
     };
 
-    let mut messages = Vec::with_capacity(2 + history.len());
+    let mut messages = Vec::with_capacity(3 + history.len());
     messages.push(system_msg);
+    messages.push(Message {
+        role: Role::System,
+        content: session,
+        tool_call_id: None,
+        tool_calls: None,
+        images: Vec::new(),
+    });
     messages.extend(history.iter().cloned());
     messages.push(Message {
         role: Role::User,
         content: user_message.to_string(),
         tool_call_id: None,
         tool_calls: None,
+        images: Vec::new(),
     });
     messages
 }
@@ -144,6 +244,96 @@ mod tests {
         "Sunday",
     ];
 
+    fn msg(role: Role, content: &str) -> Message {
+        Message {
+            role,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn estimate_tokens_counts_ascii_and_wide_chars() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("hello"), 2);
+        assert_eq!(estimate_tokens("hello world!"), 3);
+        assert_eq!(estimate_tokens("你好"), 2);
+    }
+
+    #[test]
+    fn trim_drops_tool_outputs_before_turns() {
+        let big = "x".repeat(4_000); // ~1000 tokens
+        let mut messages = vec![
+            msg(Role::System, "sys"),
+            msg(Role::User, "read it"),
+            msg(Role::Assistant, ""),
+            msg(Role::Tool, &big),
+            msg(Role::Assistant, "done"),
+            msg(Role::User, "thanks"),
+        ];
+        trim_to_budget(&mut messages, 200);
+        assert_eq!(
+            messages.len(),
+            6,
+            "turns survive once tool output is elided"
+        );
+        assert_eq!(messages[3].content, ELIDED_TOOL_OUTPUT);
+        assert_eq!(messages[4].content, "done");
+    }
+
+    #[test]
+    fn trim_drops_oldest_whole_turns() {
+        let turn = "y".repeat(400); // ~100 tokens
+        let mut messages = vec![msg(Role::System, "sys")];
+        for _ in 0..5 {
+            messages.push(msg(Role::User, &turn));
+            messages.push(msg(Role::Assistant, ""));
+            messages.push(msg(Role::Tool, "ok"));
+            messages.push(msg(Role::Assistant, &turn));
+        }
+        messages.push(msg(Role::User, "now"));
+        trim_to_budget(&mut messages, 450);
+
+        let total: usize = messages.iter().map(estimate_message_tokens).sum();
+        assert!(total <= 450, "total {total}");
+        assert_eq!(messages[0].role, Role::System);
+        assert_eq!(
+            messages[1].role,
+            Role::User,
+            "kept history starts on a turn"
+        );
+        assert_eq!(messages.last().unwrap().content, "now");
+        assert_eq!(messages.len(), 1 + 2 * 4 + 1);
+    }
+
+    #[test]
+    fn trim_keeps_system_and_user_over_budget() {
+        let mut messages = vec![
+            msg(Role::System, &"s".repeat(1_000)),
+            msg(Role::User, "old"),
+            msg(Role::User, "new"),
+        ];
+        trim_to_budget(&mut messages, 10);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].content, "new");
+    }
+
+    #[test]
+    fn trim_keeps_leading_system_messages() {
+        let mut messages = vec![
+            msg(Role::System, "stable"),
+            msg(Role::System, "session"),
+            msg(Role::User, &"z".repeat(400)),
+            msg(Role::Assistant, "ok"),
+            msg(Role::User, "now"),
+        ];
+        trim_to_budget(&mut messages, 30);
+        let contents: Vec<&str> = messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["stable", "session", "now"]);
+    }
+
     #[test]
     fn system_prompt_includes_human_readable_time_and_unix() {
         let workspace = std::env::temp_dir();
@@ -157,8 +347,15 @@ mod tests {
             "",
             &[],
             None,
+            "",
+            "",
+            "",
+        );
+        assert!(
+            !messages[0].content.contains("Unix: "),
+            "the stable system prompt stays free of per-request values"
         );
-        let system = &messages[0].content;
+        let system = &messages[1].content;
         assert!(
             system.contains("Current time (UTC"),
             "system prompt should include 'Current time (UTC'"
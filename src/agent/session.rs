@@ -9,7 +9,9 @@ use std::sync::Arc;
 use crate::llm::{Message, Role, ToolCall};
 use crate::memory::db::{BrainDb, DbError, StoredMessage};
 
-const MAX_HISTORY: usize = 50;
+/// Hard bound on in-memory history. What reaches the LLM is trimmed by token budget
+/// (`context::trim_to_budget`), not by message count.
+const MAX_HISTORY: usize = 200;
 
 // ---------------------------------------------------------------------------
 // Error
@@ -47,10 +49,16 @@ impl From<DbError> for SessionError {
 ///
 /// `pending_inserts` tracks messages added since the last `save()`. Only those
 /// are written to the database on the next save (append-only storage).
+///
+/// Messages pushed out by the cap go to `evicted` until summarization folds them
+/// into the summary; `folded` counts messages folded since the last `save()`, which
+/// then stops them from being loaded again.
 #[derive(Debug, Clone)]
 pub struct Session {
     history: Vec<Message>,
     pending_inserts: Vec<Message>,
+    evicted: Vec<Message>,
+    folded: usize,
     summary: String,
     chat_id: String,
     session_id: String,
@@ -82,6 +90,8 @@ impl Session {
         let mut session = Self {
             history,
             pending_inserts: Vec::new(),
+            evicted: Vec::new(),
+            folded: 0,
             summary,
             chat_id,
             session_id,
@@ -93,9 +103,10 @@ impl Session {
     }
 
     /// Persist only the new messages (since the last save) to the database, then
-    /// clear the pending queue.  Append-only: previous messages are never deleted.
+    /// clear the pending queue.  Append-only: previous messages are never deleted;
+    /// those folded into the summary are only marked as such.
     pub async fn save(&mut self) -> Result<(), SessionError> {
-        if self.pending_inserts.is_empty() && self.summary.is_empty() {
+        if self.pending_inserts.is_empty() && self.summary.is_empty() && self.folded == 0 {
             return Ok(());
         }
 
@@ -109,13 +120,18 @@ impl Session {
         let session_id = self.session_id.clone();
         let summary = self.summary.clone();
         let db = Arc::clone(&self.db);
+        let folded = self.folded;
 
-        tokio::task::spawn_blocking(move || db.append_session(&chat_id, &session_id, &stored, &summary))
-            .await
-            .map_err(|e| SessionError::Db(format!("spawn_blocking: {e}")))?
-            .map_err(SessionError::from)?;
+        tokio::task::spawn_blocking(move || {
+            db.append_session(&chat_id, &session_id, &stored, &summary)?;
+            db.fold_session_messages(&chat_id, &session_id, folded)
+        })
+        .await
+        .map_err(|e| SessionError::Db(format!("spawn_blocking: {e}")))?
+        .map_err(SessionError::from)?;
 
         self.pending_inserts.clear();
+        self.folded = 0;
         Ok(())
     }
 
@@ -126,7 +142,7 @@ impl Session {
     /// `chat_id` will start with an empty history and a new `session_id`.
     pub async fn reset(db: Arc<BrainDb>, chat_id: &str) -> Result<(), SessionError> {
         let chat_id = chat_id.to_string();
-        tokio::task::spawn_blocking(move || db.reset_session_id(&chat_id))
+        tokio::task::spawn_blocking(move || db.start_new_session(&chat_id))
             .await
             .map_err(|e| SessionError::Db(format!("spawn_blocking: {e}")))?
             .map_err(SessionError::from)?;
@@ -143,6 +159,7 @@ impl Session {
             content: content.to_string(),
             tool_call_id: None,
             tool_calls: None,
+            images: Vec::new(),
         };
         self.pending_inserts.push(msg.clone());
         self.history.push(msg);
@@ -155,6 +172,7 @@ impl Session {
             content: content.to_string(),
             tool_call_id: None,
             tool_calls,
+            images: Vec::new(),
         };
         self.pending_inserts.push(msg.clone());
         self.history.push(msg);
@@ -167,15 +185,18 @@ impl Session {
             content: content.to_string(),
             tool_call_id: Some(tool_call_id.to_string()),
             tool_calls: None,
+            images: Vec::new(),
         };
         self.pending_inserts.push(msg.clone());
         self.history.push(msg);
         self.cap_history();
     }
 
+    /// Move messages beyond MAX_HISTORY to `evicted`, oldest first, for summarization.
     fn cap_history(&mut self) {
         if self.history.len() > MAX_HISTORY {
-            self.history.drain(..self.history.len() - MAX_HISTORY);
+            let excess = self.history.len() - MAX_HISTORY;
+            self.evicted.extend(self.history.drain(..excess));
         }
     }
 
@@ -198,15 +219,25 @@ impl Session {
         &self.session_id
     }
 
+    /// Messages pushed out by the history cap that are not yet in the summary.
+    #[inline]
+    pub fn evicted(&self) -> &[Message] {
+        &self.evicted
+    }
+
     pub fn set_summary(&mut self, s: String) {
         self.summary = s;
     }
 
-    /// Truncate history to the last `keep` messages. No-op if already shorter.
+    /// Truncate history to the last `keep` messages and drop evicted ones; all of these
+    /// count as folded into the summary and are not loaded again after the next save.
     pub fn truncate_history(&mut self, keep: usize) {
+        self.folded += self.evicted.len();
+        self.evicted.clear();
         if self.history.len() > keep {
             let start = self.history.len() - keep;
             self.history.drain(..start);
+            self.folded += start;
         }
     }
 }
@@ -248,7 +279,8 @@ fn message_to_stored(msg: &Message) -> Result<StoredMessage, SessionError> {
     })
 }
 
-fn stored_to_message(stored: StoredMessage) -> Result<Message, SessionError> {
+/// Rebuild an LLM message from its `chat_history` row.
+pub fn stored_to_message(stored: StoredMessage) -> Result<Message, SessionError> {
     let tool_calls = stored
         .tool_calls
         .as_deref()
@@ -264,6 +296,7 @@ fn stored_to_message(stored: StoredMessage) -> Result<Message, SessionError> {
         content: stored.content,
         tool_call_id: stored.tool_call_id,
         tool_calls,
+        images: Vec::new(),
     })
 }
 
@@ -340,11 +373,39 @@ mod tests {
     async fn session_add_messages_caps_history() {
         let (_tmp, db) = temp_db();
         let mut session = Session::load(Arc::clone(&db), "cap").await.unwrap();
-        for i in 0..55 {
+        for i in 0..MAX_HISTORY + 5 {
             session.add_user_message(&format!("msg {}", i));
         }
         assert_eq!(session.history().len(), MAX_HISTORY);
         assert_eq!(session.history().first().unwrap().content, "msg 5");
+        // Capped messages wait for summarization instead of vanishing.
+        assert_eq!(session.evicted().len(), 5);
+        assert_eq!(session.evicted()[0].content, "msg 0");
+    }
+
+    #[tokio::test]
+    async fn folded_messages_stay_out_of_reloaded_history() {
+        let (_tmp, db) = temp_db();
+        let mut session = Session::load(Arc::clone(&db), "fold").await.unwrap();
+        for i in 0..10 {
+            session.add_user_message(&format!("msg {}", i));
+        }
+        session.save().await.unwrap();
+
+        let mut session = Session::load(Arc::clone(&db), "fold").await.unwrap();
+        session.set_summary("- talked about msgs 0-5".to_string());
+        session.truncate_history(4);
+        session.add_user_message("msg 10");
+        session.save().await.unwrap();
+
+        let reloaded = Session::load(Arc::clone(&db), "fold").await.unwrap();
+        let contents: Vec<&str> = reloaded
+            .history()
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(contents, ["msg 6", "msg 7", "msg 8", "msg 9", "msg 10"]);
+        assert_eq!(reloaded.summary(), "- talked about msgs 0-5");
     }
 
     // ── Session::reset archives old session and starts fresh ──────────────────
@@ -402,16 +463,16 @@ mod tests {
     async fn session_all_pending_inserts_saved_to_db() {
         let (_tmp, db) = temp_db();
         let mut session = Session::load(Arc::clone(&db), "cap2").await.unwrap();
-        for i in 0..55 {
+        for i in 0..MAX_HISTORY + 5 {
             session.add_user_message(&format!("msg {}", i));
         }
-        // In-memory history is capped at MAX_HISTORY (50)
+        // In-memory history is capped at MAX_HISTORY
         assert_eq!(session.history().len(), MAX_HISTORY);
 
-        // Save — all 55 pending inserts must go to the DB
+        // Save — all MAX_HISTORY + 5 pending inserts must go to the DB
         session.save().await.unwrap();
 
-        // Reload: DB has 55 rows, memory caps to 50; oldest in memory is msg 5
+        // Reload: DB has MAX_HISTORY + 5 rows, memory caps; oldest in memory is msg 5
         let reloaded = Session::load(Arc::clone(&db), "cap2").await.unwrap();
         assert_eq!(reloaded.history().len(), MAX_HISTORY);
         assert_eq!(reloaded.history().first().unwrap().content, "msg 5");
@@ -426,6 +487,8 @@ mod tests {
         let mut session = Session {
             history: Vec::new(),
             pending_inserts: Vec::new(),
+            evicted: Vec::new(),
+            folded: 0,
             summary: String::new(),
             chat_id: "truncate".to_string(),
             session_id: "test-session".to_string(),
@@ -12,7 +12,7 @@ use std::time::Instant;
 use tokio::sync::mpsc;
 use tokio::task::AbortHandle;
 
-use crate::llm::HttpProvider;
+use crate::llm::{GenParams, LlmProvider};
 use crate::telegram::OutboundMsg;
 use crate::tools::registry::ToolRegistry;
 
@@ -70,9 +70,10 @@ struct ManagerState {
 
 /// Owns subagent config and task map.  Cheap to clone via `Arc`.
 pub struct SubagentManager {
-    llm: Arc<HttpProvider>,
+    llm: Arc<dyn LlmProvider>,
     registry: Arc<ToolRegistry>,
     model: String,
+    params: GenParams,
     workspace: PathBuf,
     restrict_to_workspace: bool,
     max_iterations: u32,
@@ -82,9 +83,10 @@ pub struct SubagentManager {
 
 impl SubagentManager {
     pub fn new(
-        llm: Arc<HttpProvider>,
+        llm: Arc<dyn LlmProvider>,
         registry: Arc<ToolRegistry>,
         model: String,
+        params: GenParams,
         workspace: PathBuf,
         restrict_to_workspace: bool,
         max_iterations: u32,
@@ -93,6 +95,7 @@ impl SubagentManager {
             llm,
             registry,
             model,
+            params,
             workspace,
             restrict_to_workspace,
             max_iterations,
@@ -106,8 +109,8 @@ impl SubagentManager {
     // -- config accessors (immutable after construction) --
 
     #[inline]
-    pub fn llm(&self) -> &HttpProvider {
-        &self.llm
+    pub fn llm(&self) -> &dyn LlmProvider {
+        self.llm.as_ref()
     }
 
     #[inline]
@@ -120,6 +123,12 @@ impl SubagentManager {
         &self.model
     }
 
+    /// Sampling for subagent calls (`[llm.subagent]`).
+    #[inline]
+    pub fn params(&self) -> &GenParams {
+        &self.params
+    }
+
     #[inline]
     pub fn workspace(&self) -> &PathBuf {
         &self.workspace
@@ -144,6 +153,7 @@ impl SubagentManager {
         task: String,
         label: Option<String>,
         chat_id: i64,
+        thread_id: Option<i64>,
         outbound_tx: Arc<mpsc::Sender<OutboundMsg>>,
         channel: String,
     ) -> String {
@@ -172,7 +182,17 @@ impl SubagentManager {
         let manager = Arc::clone(self);
         let tid = task_id.clone();
         let handle = tokio::spawn(async move {
-            super::run_subagent(manager, tid, task, label, chat_id, outbound_tx, channel).await;
+            super::run_subagent(
+                manager,
+                tid,
+                task,
+                label,
+                chat_id,
+                thread_id,
+                outbound_tx,
+                channel,
+            )
+            .await;
         });
 
         // Store abort handle so we can cancel later.
@@ -299,6 +319,7 @@ mod tests {
             Arc::new(stub_provider()),
             Arc::new(crate::tools::registry::ToolRegistry::new()),
             "m".into(),
+            Default::default(),
             std::path::PathBuf::from("/tmp"),
             true,
             5,
@@ -312,6 +333,7 @@ mod tests {
             Arc::new(stub_provider()),
             Arc::new(crate::tools::registry::ToolRegistry::new()),
             "m".into(),
+            Default::default(),
             std::path::PathBuf::from("/tmp"),
             true,
             5,
@@ -342,7 +364,7 @@ mod tests {
     }
 
     /// Minimal provider stub for tests that never call chat().
-    fn stub_provider() -> HttpProvider {
+    fn stub_provider() -> crate::llm::HttpProvider {
         // HttpProvider::from_config requires a real config; we construct one
         // with dummy values.  The provider is never used in these unit tests.
         let cfg = crate::config::Config {
@@ -354,11 +376,18 @@ mod tests {
                 api_base: Some("http://localhost:1".into()),
                 api_key: Some("test".into()),
                 model: Some("test".into()),
+                ..Default::default()
             }),
             tools: None,
             heartbeat: None,
+            cron: None,
             timezone: None,
+            voice: None,
+            backup: None,
+            database: None,
+            retention: None,
+            index: None,
         };
-        HttpProvider::from_config(&cfg).expect("stub provider")
+        crate::llm::HttpProvider::from_config(&cfg).expect("stub provider")
     }
 }
@@ -1,7 +1,12 @@
 //! Session history summarization: compress old messages into concise summaries.
+//!
+//! The summary is rolling: each pass folds the messages leaving the history (older turns and
+//! anything evicted by the session's history cap) into the existing summary, and folded
+//! messages are not loaded again, so earlier decisions survive in compressed form.
 
+use crate::agent::context::estimate_tokens;
 use crate::agent::session::Session;
-use crate::llm::{HttpProvider, LlmError, Message, Role};
+use crate::llm::{GenParams, LlmError, LlmProvider, Message, Role};
 
 // --- Constants ---
 
@@ -10,7 +15,7 @@ pub const SUMMARIZE_THRESHOLD: usize = 20;
 const MAX_MESSAGE_TOKENS_RATIO: f64 = 0.5; // 50% of context window
 const DEFAULT_CONTEXT_WINDOW: usize = 128_000; // tokens
 const SUMMARY_MAX_TOKENS: usize = 1024;
-const SUMMARY_TEMPERATURE: f64 = 0.2;
+const SUMMARY_TEMPERATURE: f64 = 0.0; // deterministic, so repeats hit the LLM cache
 const MULTI_PASS_THRESHOLD: usize = 10; // messages
 
 // --- Error Type ---
@@ -49,35 +54,69 @@ impl From<LlmError> for SummarizeError {
 
 /// Summarize session history if it exceeds threshold.
 /// Returns true if summarization occurred, false otherwise.
+/// `params` (`[llm.summarize]`) override the default temperature 0 and 1024 max tokens.
 pub async fn summarize_if_needed(
-    llm: &HttpProvider,
+    llm: &dyn LlmProvider,
     session: &mut Session,
     model: &str,
+    params: &GenParams,
 ) -> Result<bool, SummarizeError> {
-    if !should_summarize(session.history()) {
+    if !should_summarize(session.history()) && session.evicted().is_empty() {
         return Ok(false);
     }
-
-    let to_summarize = &session.history()[..session.history().len() - KEEP_RECENT_MESSAGES];
-    let max_tokens = (DEFAULT_CONTEXT_WINDOW as f64 * MAX_MESSAGE_TOKENS_RATIO) as usize;
-    let (valid_messages, omitted) = filter_valid_messages(to_summarize, max_tokens);
-
-    if valid_messages.is_empty() {
+    let history = session.history();
+    let leaving = &history[..history.len().saturating_sub(KEEP_RECENT_MESSAGES)];
+    let to_summarize: Vec<Message> = session.evicted().iter().chain(leaving).cloned().collect();
+    let existing_summary = session.summary().to_string();
+    let Some(final_summary) =
+        fold_into_summary(llm, &to_summarize, &existing_summary, model, params).await?
+    else {
         // Fallback: truncate to keep recent + some buffer
         session.truncate_history(KEEP_RECENT_MESSAGES + 10);
         return Ok(false);
+    };
+
+    // Update session: the new summary already folds in the existing one. Folded messages
+    // leave the history (and are not reloaded after the next save).
+    if !final_summary.is_empty() {
+        session.set_summary(final_summary);
+    }
+    session.truncate_history(KEEP_RECENT_MESSAGES);
+
+    Ok(true)
+}
+
+/// Fold `messages` into `existing_summary` and return the updated summary. Tool messages and
+/// oversized messages are skipped; None when nothing is left to summarize.
+/// `params` (`[llm.summarize]`) override the default temperature 0 and 1024 max tokens.
+pub async fn fold_into_summary(
+    llm: &dyn LlmProvider,
+    messages: &[Message],
+    existing_summary: &str,
+    model: &str,
+    params: &GenParams,
+) -> Result<Option<String>, SummarizeError> {
+    let params = params.or(&GenParams {
+        temperature: Some(SUMMARY_TEMPERATURE),
+        max_tokens: Some(SUMMARY_MAX_TOKENS),
+        ..GenParams::default()
+    });
+
+    let max_tokens = (DEFAULT_CONTEXT_WINDOW as f64 * MAX_MESSAGE_TOKENS_RATIO) as usize;
+    let (valid_messages, omitted) = filter_valid_messages(messages, max_tokens);
+    if valid_messages.is_empty() {
+        return Ok(None);
     }
 
-    let existing_summary = session.summary().to_string();
     let new_summary = if valid_messages.len() > MULTI_PASS_THRESHOLD {
         // Multi-pass: split, summarize each half, then merge
         let mid = valid_messages.len() / 2;
         let part1 = &valid_messages[..mid];
         let part2 = &valid_messages[mid..];
 
-        let s1 = summarize_batch(llm, part1, "", model).await?;
-        let s2 = summarize_batch(llm, part2, "", model).await?;
-        merge_summaries(llm, &s1, &s2, model)
+        let s1 = summarize_batch(llm, part1, existing_summary, model, &params).await?;
+        let s2 = summarize_batch(llm, part2, "", model, &params).await?;
+        merge_summaries(llm, &s1, &s2, model, &params)
             .await
             .unwrap_or_else(|_| {
                 // Fallback: concatenate
@@ -85,7 +124,7 @@ pub async fn summarize_if_needed(
             })
     } else {
         // Single-pass
-        summarize_batch(llm, &valid_messages, &existing_summary, model).await?
+        summarize_batch(llm, &valid_messages, existing_summary, model, &params).await?
     };
 
     // Append note if messages were omitted
@@ -97,19 +136,7 @@ pub async fn summarize_if_needed(
     } else {
         new_summary
     };
-
-    // Update session: set summary and truncate history
-    if !final_summary.is_empty() {
-        let updated_summary = if existing_summary.is_empty() {
-            final_summary
-        } else {
-            format!("{}\n\n{}", existing_summary, final_summary)
-        };
-        session.set_summary(updated_summary);
-    }
-    session.truncate_history(KEEP_RECENT_MESSAGES);
-
-    Ok(true)
+    Ok(Some(final_summary))
 }
 
 // --- Helper Functions ---
@@ -118,11 +145,6 @@ fn should_summarize(history: &[Message]) -> bool {
     history.len() > SUMMARIZE_THRESHOLD
 }
 
-fn estimate_tokens(text: &str) -> usize {
-    // Fast approximation: char_count / 3 (accounts for CJK and multi-byte)
-    text.chars().count() / 3
-}
-
 fn filter_valid_messages(messages: &[Message], max_tokens: usize) -> (Vec<Message>, bool) {
     let mut valid = Vec::new();
     let mut omitted = false;
@@ -164,10 +186,11 @@ fn format_messages_for_summary(messages: &[Message]) -> String {
 }
 
 async fn summarize_batch(
-    llm: &HttpProvider,
+    llm: &dyn LlmProvider,
     messages: &[Message],
     existing_summary: &str,
     model: &str,
+    params: &GenParams,
 ) -> Result<String, SummarizeError> {
     if messages.is_empty() {
         return Err(SummarizeError::EmptyBatch);
@@ -183,7 +206,7 @@ async fn summarize_batch(
         )
     } else {
         format!(
-            "Summarize the following conversation history for context preservation. Keep it short (max 12 bullet points).\n\nExisting context: {}\n\nCONVERSATION:\n{}",
+            "Update the existing summary with the following conversation history for context preservation. Keep everything from the existing summary that still matters and keep it short (max 12 bullet points).\n\nEXISTING SUMMARY:\n{}\n\nCONVERSATION:\n{}",
             existing_summary, formatted
         )
     };
@@ -194,33 +217,28 @@ async fn summarize_batch(
             content: system_prompt.to_string(),
             tool_call_id: None,
             tool_calls: None,
+            images: Vec::new(),
         },
         Message {
             role: Role::User,
             content: user_prompt,
             tool_call_id: None,
             tool_calls: None,
+            images: Vec::new(),
         },
     ];
 
-    let response = llm
-        .chat_with_params(
-            &msgs,
-            &[],
-            model,
-            Some(SUMMARY_TEMPERATURE),
-            Some(SUMMARY_MAX_TOKENS),
-        )
-        .await?;
+    let response = llm.chat_with_params(&msgs, &[], model, params).await?;
 
     Ok(response.content.trim().to_string())
 }
 
 async fn merge_summaries(
-    llm: &HttpProvider,
+    llm: &dyn LlmProvider,
     s1: &str,
     s2: &str,
     model: &str,
+    params: &GenParams,
 ) -> Result<String, SummarizeError> {
     let merge_prompt = format!(
         "Merge these two conversation summaries into one cohesive summary:\n\n1: {}\n\n2: {}",
@@ -232,17 +250,10 @@ async fn merge_summaries(
         content: merge_prompt,
         tool_call_id: None,
         tool_calls: None,
+        images: Vec::new(),
     }];
 
-    let response = llm
-        .chat_with_params(
-            &msgs,
-            &[],
-            model,
-            Some(SUMMARY_TEMPERATURE),
-            Some(SUMMARY_MAX_TOKENS),
-        )
-        .await?;
+    let response = llm.chat_with_params(&msgs, &[], model, params).await?;
 
     Ok(response.content.trim().to_string())
 }
@@ -259,6 +270,7 @@ mod tests {
                 content: "test".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                images: Vec::new(),
             };
             SUMMARIZE_THRESHOLD
         ];
@@ -273,20 +285,13 @@ mod tests {
                 content: "test".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                images: Vec::new(),
             };
             SUMMARIZE_THRESHOLD + 1
         ];
         assert!(should_summarize(&history));
     }
 
-    #[test]
-    fn estimate_tokens_basic() {
-        // "hello" = 5 chars / 3 ≈ 1 token
-        assert_eq!(estimate_tokens("hello"), 1);
-        // "hello world" = 11 chars / 3 ≈ 3 tokens
-        assert_eq!(estimate_tokens("hello world"), 3);
-    }
-
     #[test]
     fn filter_valid_messages_skips_tool_messages() {
         let messages = vec![
@@ -295,18 +300,21 @@ mod tests {
                 content: "test".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                images: Vec::new(),
             },
             Message {
                 role: Role::Tool,
                 content: "tool result".to_string(),
                 tool_call_id: Some("call_1".to_string()),
                 tool_calls: None,
+                images: Vec::new(),
             },
             Message {
                 role: Role::Assistant,
                 content: "response".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                images: Vec::new(),
             },
         ];
 
@@ -319,11 +327,10 @@ mod tests {
     fn filter_valid_messages_skips_oversized() {
         // Create a message that will estimate to more than max_tokens
         // max_tokens = 128000 * 0.5 = 64000
-        // To exceed this, we need chars / 3 > 64000
-        // With integer division, chars / 3 = 64000 when chars = 192000
-        // So we need chars >= 192003 to get 64001 tokens
+        // ASCII text counts one token per 4 chars (rounded up), so
+        // 256001 chars estimate to 64001 tokens
         let max_tokens = (DEFAULT_CONTEXT_WINDOW as f64 * MAX_MESSAGE_TOKENS_RATIO) as usize;
-        let min_chars_to_exceed = max_tokens * 3 + 3; // Ensure it exceeds (64001 tokens)
+        let min_chars_to_exceed = max_tokens * 4 + 1; // Ensure it exceeds (64001 tokens)
         let large_content = "x".repeat(min_chars_to_exceed);
         let messages = vec![
             Message {
@@ -331,12 +338,14 @@ mod tests {
                 content: "normal".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                images: Vec::new(),
             },
             Message {
                 role: Role::User,
                 content: large_content,
                 tool_call_id: None,
                 tool_calls: None,
+                images: Vec::new(),
             },
         ];
 
@@ -353,12 +362,14 @@ mod tests {
                 content: "Hello".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                images: Vec::new(),
             },
             Message {
                 role: Role::Assistant,
                 content: "Hi there".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                images: Vec::new(),
             },
         ];
 
@@ -366,4 +377,76 @@ mod tests {
         assert!(formatted.contains("User: Hello"));
         assert!(formatted.contains("Assistant: Hi there"));
     }
+
+    /// Records every prompt and replies with a numbered summary.
+    struct Recorder(std::sync::Mutex<Vec<String>>);
+
+    impl LlmProvider for Recorder {
+        fn chat_with_params<'a>(
+            &'a self,
+            messages: &'a [Message],
+            _tools: &'a [crate::llm::ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> crate::tools::registry::BoxFuture<'a, Result<crate::llm::LlmResponse, LlmError>>
+        {
+            let prompt = messages.last().unwrap().content.clone();
+            let mut prompts = self.0.lock().unwrap();
+            prompts.push(prompt);
+            let content = format!("- summary {}", prompts.len());
+            Box::pin(async move {
+                Ok(crate::llm::LlmResponse {
+                    content,
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".into(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn rolling_summary_folds_old_messages_once() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = std::sync::Arc::new(crate::memory::db::BrainDb::open(tmp.path()).unwrap());
+        let llm = Recorder(Default::default());
+        let mut session = Session::load(db.clone(), "roll").await.unwrap();
+        for i in 0..8 {
+            session.add_user_message(&format!("q{i}"));
+        }
+        session.set_summary("- earlier: rent is due on the 1st".into());
+        session.save().await.unwrap();
+
+        let mut session = Session::load(db.clone(), "roll").await.unwrap();
+        for i in 8..=SUMMARIZE_THRESHOLD {
+            session.add_user_message(&format!("q{i}"));
+        }
+        assert!(
+            summarize_if_needed(&llm, &mut session, "m", &GenParams::default())
+                .await
+                .unwrap()
+        );
+        session.save().await.unwrap();
+        {
+            let prompts = llm.0.lock().unwrap();
+            assert!(
+                prompts.iter().any(|p| p.contains("rent is due")),
+                "existing summary is folded in"
+            );
+            assert!(prompts.iter().any(|p| p.contains("User: q0")));
+        }
+
+        // The summary replaces the old one and folded messages are not reloaded.
+        let session = Session::load(db, "roll").await.unwrap();
+        assert!(
+            session.summary().starts_with("- summary"),
+            "{}",
+            session.summary()
+        );
+        assert_eq!(session.history().len(), KEEP_RECENT_MESSAGES);
+        assert_eq!(
+            session.history()[0].content,
+            format!("q{}", SUMMARIZE_THRESHOLD + 1 - KEEP_RECENT_MESSAGES)
+        );
+    }
 }
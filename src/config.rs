@@ -3,12 +3,16 @@
 //! Single file: `~/.icrab/config.toml`. Override path with `ICRAB_CONFIG`.
 //! Env overrides (optional): `TELEGRAM_BOT_TOKEN` or `ICRAB_TELEGRAM_BOT_TOKEN`,
 //! `ICRAB_LLM_API_KEY`, `ICRAB_LLM_API_BASE`, `ICRAB_LLM_MODEL`, `ICRAB_WORKSPACE`,
-//! `ICRAB_TOOLS_WEB_BRAVE_API_KEY`, `ICRAB_TIMEZONE`.
+//! `ICRAB_TOOLS_WEB_BRAVE_API_KEY`, `ICRAB_TIMEZONE`, `ICRAB_VOICE_API_KEY`,
+//! `ICRAB_DATABASE_ENCRYPTION_KEY`.
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use serde::Deserialize;
 
+use crate::llm::GenParams;
+
 /// Root config: workspace, telegram, llm, optional tools.web, heartbeat, restrict_to_workspace.
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -19,25 +23,252 @@ pub struct Config {
     #[serde(default)]
     pub tools: Option<ToolsConfig>,
     pub heartbeat: Option<HeartbeatConfig>,
+    /// Optional cron runner settings (agent job timeout and retries).
+    pub cron: Option<CronConfig>,
     pub restrict_to_workspace: Option<bool>,
     /// IANA timezone name (e.g. "Europe/London"). Default when absent: "Europe/London".
     pub timezone: Option<String>,
+    /// Optional speech API for voice notes (transcription in, text-to-speech out).
+    pub voice: Option<VoiceConfig>,
+    /// Optional scheduled brain.db backups (the `backup` tool works without it).
+    pub backup: Option<BackupConfig>,
+    /// Optional brain.db settings (encryption at rest).
+    pub database: Option<DatabaseConfig>,
+    /// Optional limits on stored chat history, applied periodically.
+    pub retention: Option<RetentionConfig>,
+    /// Optional vault index settings (which file types are searchable).
+    pub index: Option<IndexConfig>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct ToolsConfig {
     pub web: Option<WebConfig>,
+    pub exec: Option<ExecConfig>,
+    pub http: Option<HttpConfig>,
+    pub feeds: Option<FeedsConfig>,
+    pub calendar: Option<CalendarConfig>,
+    pub email: Option<EmailConfig>,
+    pub ocr: Option<OcrConfig>,
+    pub translate: Option<TranslateConfig>,
+    pub summarize: Option<SummarizeConfig>,
+    pub daily_note: Option<DailyNoteConfig>,
+    pub habits: Option<HabitsConfig>,
+    pub workout: Option<WorkoutConfig>,
+    pub expense: Option<ExpenseConfig>,
+    pub bookmark: Option<BookmarkConfig>,
+    pub time: Option<TimeConfig>,
+    pub github: Option<GithubConfig>,
+    pub image: Option<ImageConfig>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct WebConfig {
+    /// "brave", "searxng", "google" or "duckduckgo"; default brave when `brave_api_key` is
+    /// set, else duckduckgo.
+    pub search_provider: Option<String>,
     pub brave_api_key: Option<String>,
-    /// Max results for Brave/DDG search (1–10); default 5.
+    /// Max results for web search (1–10), whichever provider; default 5.
     pub brave_max_results: Option<u8>,
+    /// SearXNG instance for `search_provider = "searxng"`, e.g. "https://searx.example.org";
+    /// its settings.yml must list `json` in `search.formats`.
+    pub searxng_url: Option<String>,
+    /// Google Custom Search JSON API key, for `search_provider = "google"`.
+    pub google_api_key: Option<String>,
+    /// Programmable Search Engine id (`cx`) used with `google_api_key`.
+    pub google_cx: Option<String>,
     /// Max chars for web_fetch body; default 50_000.
     pub web_fetch_max_chars: Option<u32>,
+    /// Seconds web_fetch reuses a cached page without asking the server; after that the page
+    /// is revalidated with its ETag/Last-Modified. Default 900; 0 revalidates every time.
+    pub web_fetch_cache_ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExecConfig {
+    /// Register the `exec` shell tool; default false.
+    pub enabled: Option<bool>,
+    /// Programs commands may run; unset or empty allows any program not denied.
+    pub allow: Option<Vec<String>>,
+    /// Programs commands may never run; unset uses a built-in list (rm, sudo, dd, kill, …).
+    pub deny: Option<Vec<String>>,
+    /// Seconds before a command is killed; default 30.
+    pub timeout_secs: Option<u64>,
+    /// Max chars returned of stdout and of stderr; default 10_000.
+    pub max_output_chars: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HttpConfig {
+    /// Named basic-auth logins the `http_request` tool may use (`credential = "<name>"`);
+    /// the agent sees only the names.
+    pub credentials: Option<BTreeMap<String, HttpCredential>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FeedsConfig {
+    /// RSS/Atom feeds the `feeds` tool reads, by name (`bbc = "https://…/rss.xml"`).
+    pub urls: Option<BTreeMap<String, String>>,
+    /// Max new items returned per feed; default 10.
+    pub max_items: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CalendarConfig {
+    /// Calendars the `calendar` tool reads, by name.
+    pub calendars: Option<BTreeMap<String, CalendarSource>>,
+    /// Hours of upcoming events appended to heartbeat and cron messages; default 24, 0 = off.
+    pub briefing_hours: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CalendarSource {
+    /// ICS feed (http, https or webcal), or a CalDAV calendar collection when `caldav`.
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Query `url` as a CalDAV collection (REPORT calendar-query); default false (GET an ICS).
+    pub caldav: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EmailConfig {
+    /// Mailboxes the `email` tool can use, by name.
+    pub accounts: Option<BTreeMap<String, EmailAccount>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EmailAccount {
+    /// IMAP server, reached over TLS (e.g. "imap.fastmail.com").
+    pub imap_host: String,
+    /// Default 993.
+    pub imap_port: Option<u16>,
+    /// SMTP server for `send`; without it the account is read-only.
+    pub smtp_host: Option<String>,
+    /// Default 465 (TLS from the start); any other port upgrades with STARTTLS.
+    pub smtp_port: Option<u16>,
+    pub username: String,
+    pub password: String,
+    /// Address mail is sent from; default `username`.
+    pub from: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GithubConfig {
+    /// Personal access token; the `github` tool is registered only when set.
+    pub token: Option<String>,
+    /// Repositories ("owner/name") listed by default and the only ones issues may be created
+    /// in.
+    pub repos: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ImageConfig {
+    /// "openai" (default; the Images API) or "stability" (Stability AI Stable Image).
+    pub provider: Option<String>,
+    /// Required; the `generate_image` tool is registered only when set.
+    pub api_key: Option<String>,
+    /// Default `https://api.openai.com/v1` or `https://api.stability.ai`.
+    pub api_base: Option<String>,
+    /// OpenAI model (default "gpt-image-1") or Stability service ("core" (default), "ultra",
+    /// "sd3").
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OcrConfig {
+    /// "tesseract", "vision" (an LLM call with the image) or "auto" (default): tesseract when
+    /// installed, otherwise vision.
+    pub backend: Option<String>,
+    /// Model for the vision backend; default `[llm] model` (it must accept images).
+    pub model: Option<String>,
+    /// Tesseract languages, e.g. "eng+deu"; default "eng".
+    pub languages: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TranslateConfig {
+    /// Model for the `translate` tool; default `[llm] model`. A small, cheap model is usually
+    /// enough.
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SummarizeConfig {
+    /// Model for the `summarize_url` tool's chunk and merge calls; default `[llm] model`. A
+    /// small, cheap model is usually enough.
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DailyNoteConfig {
+    /// Vault folder of daily notes, one `<YYYY-MM-DD>.md` per day; default "Daily log".
+    pub dir: Option<String>,
+    /// Vault path of the template for new notes; default "Templates/Daily log.md", and a
+    /// built-in template when that file does not exist.
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HabitsConfig {
+    /// Vault note the `habits` tool keeps a dashboard table in; default "Habits.md".
+    pub dashboard: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WorkoutConfig {
+    /// Vault folder the `workout` tool appends logged sets to, one `<YYYY-MM-DD>.md` per day;
+    /// default "Workouts".
+    pub dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExpenseConfig {
+    /// ISO code of amounts given without a currency; default "GBP".
+    pub currency: Option<String>,
+    /// Vault folder of the monthly reports (`<YYYY-MM>.md`); default "Expenses".
+    pub dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BookmarkConfig {
+    /// Vault note listing every bookmark; default "Bookmarks.md".
+    pub note: Option<String>,
+    /// Model that titles, summarizes and tags saved pages; default `[llm] model`.
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TimeConfig {
+    /// Places the `time` tool shows besides the user's own zone when asked for the time
+    /// without naming any (cities, IANA names or offsets).
+    pub clocks: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HttpCredential {
+    pub username: String,
+    pub password: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -47,6 +278,43 @@ pub struct TelegramConfig {
     pub allowed_user_ids: Option<Vec<i64>>,
     /// Optional API base URL for testing or custom endpoints. Defaults to `https://api.telegram.org/bot{token}`.
     pub api_base: Option<String>,
+    /// Local-time window ("23:00-07:00") in which cron and heartbeat messages are sent
+    /// without a notification sound. Uses `timezone`.
+    pub quiet_hours: Option<String>,
+}
+
+impl TelegramConfig {
+    /// Parsed `quiet_hours`; None when unset or invalid (validate() rejects invalid values).
+    pub fn quiet_hours(&self) -> Option<QuietHours> {
+        self.quiet_hours.as_deref().and_then(QuietHours::parse)
+    }
+}
+
+/// Daily time window `[start, end)`; wraps past midnight when `start > end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: chrono::NaiveTime,
+    pub end: chrono::NaiveTime,
+}
+
+impl QuietHours {
+    /// Parse "HH:MM-HH:MM" (24h clock).
+    pub fn parse(s: &str) -> Option<Self> {
+        let (start, end) = s.split_once('-')?;
+        let t = |v: &str| chrono::NaiveTime::parse_from_str(v.trim(), "%H:%M").ok();
+        Some(Self {
+            start: t(start)?,
+            end: t(end)?,
+        })
+    }
+
+    pub fn contains(&self, time: chrono::NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -56,6 +324,80 @@ pub struct LlmConfig {
     pub api_base: Option<String>,
     pub api_key: Option<String>,
     pub model: Option<String>,
+    /// Stream replies (SSE) and show them as a Telegram message edited in place; default false.
+    pub stream: Option<bool>,
+    /// Prompt token price in USD per million tokens, for the usage cost estimate.
+    pub input_price: Option<f64>,
+    /// Completion token price in USD per million tokens.
+    pub output_price: Option<f64>,
+    /// Approximate prompt size (tokens) chat history is trimmed to; default 32000.
+    pub context_budget: Option<usize>,
+    /// How long temperature-0 responses (summaries) are reused from `llm_cache`; 0 disables.
+    /// Default 3600.
+    pub cache_ttl_secs: Option<u64>,
+    /// Tries per request on 429/500/502/503 or connection failures (OpenAI-compatible
+    /// provider); default 3.
+    pub max_attempts: Option<u32>,
+    /// Cap on LLM requests per minute across agent, heartbeat, cron and subagents; callers
+    /// over the limit wait. Unset means no limit.
+    pub max_requests_per_minute: Option<u32>,
+    /// Sampling (temperature, max-tokens, top-p, stop) for the main agent: `[llm.agent]`.
+    pub agent: Option<GenParams>,
+    /// Sampling for subagents: `[llm.subagent]`.
+    pub subagent: Option<GenParams>,
+    /// Sampling for session summaries: `[llm.summarize]` (default temperature 0).
+    pub summarize: Option<GenParams>,
+    /// Write every request and response (secrets redacted) to `workspace/.icrab/llm-log/`;
+    /// default false.
+    pub debug_log: Option<bool>,
+    /// Size at which the debug log is rotated (one old file is kept); default 10 MiB.
+    pub debug_log_max_bytes: Option<u64>,
+    /// Extra headers sent with every LLM request: `[llm.headers]` (e.g. OpenRouter's
+    /// `HTTP-Referer` / `X-Title`, or a gateway's auth header).
+    pub headers: Option<BTreeMap<String, String>>,
+    /// Proxy for LLM requests: `http://`, `https://` or `socks5://` URL, with optional
+    /// `user:pass@`.
+    pub proxy: Option<String>,
+    /// "record" appends every LLM exchange to `replay-file`; "replay" answers from it offline.
+    pub replay: Option<String>,
+    /// Recording for `replay`; default `workspace/.icrab/llm-replay.jsonl`.
+    pub replay_file: Option<String>,
+    /// Embedding model for vault semantic search (e.g. "text-embedding-3-small" or
+    /// "nomic-embed-text"). Unset disables embeddings and `semantic_search_vault`.
+    pub embedding_model: Option<String>,
+}
+
+impl LlmConfig {
+    /// `provider`, trimmed and lowercased; empty when unset.
+    pub fn provider_name(&self) -> String {
+        self.provider
+            .as_deref()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase()
+    }
+
+    /// Local servers (Ollama) run without an API key.
+    pub fn requires_api_key(&self) -> bool {
+        self.provider_name() != "ollama"
+    }
+}
+
+/// OpenAI-compatible speech endpoints (`/audio/transcriptions`, `/audio/speech`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct VoiceConfig {
+    /// Defaults to `https://api.openai.com/v1`.
+    pub api_base: Option<String>,
+    pub api_key: Option<String>,
+    /// Transcription model; default "whisper-1".
+    pub stt_model: Option<String>,
+    /// Speech model; default "gpt-4o-mini-tts".
+    pub tts_model: Option<String>,
+    /// Speech voice; default "alloy".
+    pub tts_voice: Option<String>,
+    /// Answer voice notes with a voice note (sendVoice); default true. False replies in text.
+    pub voice_replies: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -64,6 +406,60 @@ pub struct HeartbeatConfig {
     pub interval_minutes: Option<u64>,
 }
 
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CronConfig {
+    /// Seconds an agent job may run before it is cancelled and recorded as failed; default
+    /// 300. A job's own `timeout_seconds` overrides it.
+    pub agent_timeout_seconds: Option<u64>,
+    /// Retries of a failed agent job before the chat is told; default 3, 0 = none.
+    pub agent_retries: Option<u32>,
+    /// Seconds before the first retry, doubling for each one after; default 60.
+    pub retry_backoff_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BackupConfig {
+    /// Hours between automatic backups; absent or 0 disables the schedule.
+    pub interval_hours: Option<u64>,
+    /// Backups kept after rotation; default 7.
+    pub keep: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DatabaseConfig {
+    /// SQLCipher key for brain.db and its backups (requires the `encryption` build feature).
+    /// Losing it makes the database unreadable.
+    pub encryption_key: Option<String>,
+    /// Minutes between WAL checkpoints and `PRAGMA optimize` (default 30).
+    pub maintenance_minutes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RetentionConfig {
+    /// Messages kept per chat (newest first); unset keeps all.
+    pub max_messages: Option<usize>,
+    /// Days of history kept per chat; unset keeps all.
+    pub max_age_days: Option<u64>,
+    /// Fold pruned messages into their session's summary before deleting them; default false.
+    pub summarize: Option<bool>,
+    /// Hours between pruning passes; default 24.
+    pub interval_hours: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IndexConfig {
+    /// File extensions indexed for vault search (e.g. ["md", "txt"]); default md, txt, org,
+    /// csv and canvas.
+    pub extensions: Option<Vec<String>>,
+    /// Re-index files a couple of seconds after they change; default true.
+    pub watch: Option<bool>,
+}
+
 /// Config load/validation errors.
 #[derive(Debug, Clone)]
 pub enum ConfigError {
@@ -135,6 +531,11 @@ pub fn load(path: &std::path::Path) -> Result<Config, ConfigError> {
     } else if let Some(ref w) = cfg.workspace {
         cfg.workspace = Some(expand_home(w));
     }
+    if let Some(ref mut l) = cfg.llm
+        && let Some(ref f) = l.replay_file
+    {
+        l.replay_file = Some(expand_home(f));
+    }
     if let Ok(v) = std::env::var("ICRAB_LLM_API_KEY") {
         cfg.llm.get_or_insert_with(LlmConfig::default).api_key = Some(v);
     }
@@ -152,12 +553,104 @@ pub fn load(path: &std::path::Path) -> Result<Config, ConfigError> {
     if let Ok(v) = std::env::var("ICRAB_TIMEZONE") {
         cfg.timezone = Some(v);
     }
+    if let Ok(v) = std::env::var("ICRAB_VOICE_API_KEY") {
+        cfg.voice.get_or_insert_with(VoiceConfig::default).api_key = Some(v);
+    }
+    if let Ok(v) = std::env::var("ICRAB_DATABASE_ENCRYPTION_KEY") {
+        cfg.database
+            .get_or_insert_with(DatabaseConfig::default)
+            .encryption_key = Some(v);
+    }
 
     cfg.validate()?;
     Ok(cfg)
 }
 
 impl Config {
+    /// Configured values that must never be written to logs: API keys, the database key, the
+    /// bot token, `http_request` passwords and allowed Telegram user ids.
+    pub fn secrets(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(t) = &self.telegram {
+            out.extend(t.bot_token.clone());
+            for id in t.allowed_user_ids.iter().flatten() {
+                out.push(id.to_string());
+            }
+        }
+        if let Some(l) = &self.llm {
+            out.extend(l.api_key.clone());
+            // Auth-like extra headers; ranking headers such as X-Title stay readable.
+            for (name, value) in l.headers.iter().flatten() {
+                let name = name.to_ascii_lowercase();
+                if ["auth", "key", "token", "secret"]
+                    .iter()
+                    .any(|s| name.contains(s))
+                {
+                    out.push(value.clone());
+                }
+            }
+            out.extend(
+                l.proxy
+                    .as_deref()
+                    .and_then(|p| p.split_once("://"))
+                    .and_then(|(_, rest)| rest.split_once('@'))
+                    .map(|(userinfo, _)| userinfo.to_string()),
+            );
+        }
+        out.extend(self.voice.as_ref().and_then(|v| v.api_key.clone()));
+        out.extend(
+            self.database
+                .as_ref()
+                .and_then(|d| d.encryption_key.clone()),
+        );
+        if let Some(web) = self.tools.as_ref().and_then(|t| t.web.as_ref()) {
+            out.extend(web.brave_api_key.clone());
+            out.extend(web.google_api_key.clone());
+        }
+        if let Some(creds) = self
+            .tools
+            .as_ref()
+            .and_then(|t| t.http.as_ref())
+            .and_then(|h| h.credentials.as_ref())
+        {
+            out.extend(creds.values().filter_map(|c| c.password.clone()));
+        }
+        // Private ICS addresses are secrets in themselves.
+        if let Some(cals) = self
+            .tools
+            .as_ref()
+            .and_then(|t| t.calendar.as_ref())
+            .and_then(|c| c.calendars.as_ref())
+        {
+            for c in cals.values() {
+                out.push(c.url.clone());
+                out.extend(c.password.clone());
+            }
+        }
+        if let Some(accounts) = self
+            .tools
+            .as_ref()
+            .and_then(|t| t.email.as_ref())
+            .and_then(|e| e.accounts.as_ref())
+        {
+            out.extend(accounts.values().map(|a| a.password.clone()));
+        }
+        out.extend(
+            self.tools
+                .as_ref()
+                .and_then(|t| t.github.as_ref())
+                .and_then(|g| g.token.clone()),
+        );
+        out.extend(
+            self.tools
+                .as_ref()
+                .and_then(|t| t.image.as_ref())
+                .and_then(|i| i.api_key.clone()),
+        );
+        out.retain(|s| !s.trim().is_empty());
+        out
+    }
+
     /// Validate required fields for running the gateway (Telegram + agent).
     pub fn validate(&self) -> Result<(), ConfigError> {
         if self.workspace.as_deref().unwrap_or("").trim().is_empty() {
@@ -171,17 +664,33 @@ impl Config {
                     "telegram.bot_token is required (or TELEGRAM_BOT_TOKEN)".to_string(),
                 ));
             }
+            if let Some(ref q) = t.quiet_hours
+                && QuietHours::parse(q).is_none()
+            {
+                return Err(ConfigError::Validation(format!(
+                    "[telegram] quiet-hours '{}' must look like \"23:00-07:00\"",
+                    q
+                )));
+            }
         } else {
             return Err(ConfigError::Validation(
                 "telegram section is required".to_string(),
             ));
         }
         if let Some(ref l) = self.llm {
-            if l.api_key.as_deref().unwrap_or("").trim().is_empty() {
+            if l.requires_api_key() && l.api_key.as_deref().unwrap_or("").trim().is_empty() {
                 return Err(ConfigError::Validation(
                     "llm.api_key is required (or ICRAB_LLM_API_KEY)".to_string(),
                 ));
             }
+            if let Some(ref mode) = l.replay
+                && !matches!(mode.trim(), "" | "record" | "replay")
+            {
+                return Err(ConfigError::Validation(format!(
+                    "llm.replay '{}' must be \"record\" or \"replay\"",
+                    mode
+                )));
+            }
             if l.model.as_deref().unwrap_or("").trim().is_empty() {
                 return Err(ConfigError::Validation(
                     "llm.model is required (or ICRAB_LLM_MODEL)".to_string(),
@@ -192,6 +701,53 @@ impl Config {
                 "llm section is required".to_string(),
             ));
         }
+        if let Some(ref v) = self.voice
+            && v.api_key.as_deref().unwrap_or("").trim().is_empty()
+        {
+            return Err(ConfigError::Validation(
+                "voice.api_key is required when [voice] is set (or ICRAB_VOICE_API_KEY)"
+                    .to_string(),
+            ));
+        }
+        if let Some(web) = self.tools.as_ref().and_then(|t| t.web.as_ref()) {
+            let set = |v: &Option<String>| !v.as_deref().unwrap_or("").trim().is_empty();
+            let missing = match web.search_provider.as_deref().map(str::trim) {
+                None | Some("" | "duckduckgo") => None,
+                Some("brave") => (!set(&web.brave_api_key))
+                    .then_some("tools.web.brave_api_key (or ICRAB_TOOLS_WEB_BRAVE_API_KEY)"),
+                Some("searxng") => (!set(&web.searxng_url)).then_some("tools.web.searxng_url"),
+                Some("google") => (!set(&web.google_api_key) || !set(&web.google_cx))
+                    .then_some("tools.web.google_api_key and tools.web.google_cx"),
+                Some(other) => {
+                    return Err(ConfigError::Validation(format!(
+                        "tools.web.search_provider '{}' must be brave, searxng, google or duckduckgo",
+                        other
+                    )));
+                }
+            };
+            if let Some(missing) = missing {
+                return Err(ConfigError::Validation(format!(
+                    "{} is required for search_provider \"{}\"",
+                    missing,
+                    web.search_provider.as_deref().unwrap_or("").trim()
+                )));
+            }
+        }
+        if let Some(image) = self.tools.as_ref().and_then(|t| t.image.as_ref()) {
+            if image.api_key.as_deref().unwrap_or("").trim().is_empty() {
+                return Err(ConfigError::Validation(
+                    "tools.image.api_key is required when [tools.image] is set".to_string(),
+                ));
+            }
+            if let Some(ref p) = image.provider
+                && !matches!(p.trim(), "" | "openai" | "stability")
+            {
+                return Err(ConfigError::Validation(format!(
+                    "tools.image.provider '{}' must be openai or stability",
+                    p
+                )));
+            }
+        }
         if let Some(ref tz) = self.timezone {
             tz.parse::<chrono_tz::Tz>().map_err(|_| {
                 ConfigError::Validation(format!(
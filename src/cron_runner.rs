@@ -1,13 +1,28 @@
 //! Tick loop: load jobs.json, find due jobs, execute (inbound to agent or direct sendMessage).
+//! Direct runs are recorded here; agent runs once the main loop has the agent's reply.
 
 use std::sync::Arc;
 
 use tokio::sync::mpsc;
 
-use crate::telegram::{InboundMsg, OutboundMsg};
-use crate::tools::cron::{CronStore, JobAction};
+use crate::telegram::{Allowlist, InboundMsg, OutboundKind, OutboundMsg};
+use crate::tools::cron::{CronStore, JobAction, RunStart};
 
-fn unix_now() -> u64 {
+/// Seconds between checks for due jobs: short so timers ring on time (a check only scans
+/// the in-memory job list).
+pub const TICK_SECS: u64 = 1;
+
+/// Seconds an agent job may run when neither the job nor `[cron]` sets a timeout.
+pub const DEFAULT_AGENT_TIMEOUT_SECS: u64 = 300;
+
+/// Retries of a failed agent job when `[cron]` doesn't say.
+pub const DEFAULT_AGENT_RETRIES: u32 = 3;
+
+/// Seconds before the first retry of a failed agent job; each further retry waits twice as long.
+pub const DEFAULT_RETRY_BACKOFF_SECS: u64 = 60;
+
+/// Current Unix time in seconds.
+pub fn unix_now() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|d| d.as_secs())
@@ -15,8 +30,10 @@ fn unix_now() -> u64 {
 }
 
 /// Run one tick: find due jobs, send to channels, mark fired. Used by runner and tests.
+/// `allowlist` resolves jobs sent to all allowed users.
 pub async fn tick_once(
     store: &CronStore,
+    allowlist: Option<&Allowlist>,
     inbound_tx: &mpsc::Sender<InboundMsg>,
     outbound_tx: &mpsc::Sender<OutboundMsg>,
     now: u64,
@@ -25,31 +42,74 @@ pub async fn tick_once(
     for job in due {
         match job.action {
             JobAction::Agent => {
-                let msg = InboundMsg {
-                    chat_id: job.chat_id,
-                    user_id: 0,
-                    text: job.message.clone(),
-                    channel: "cron".to_string(),
-                };
-                if inbound_tx.try_send(msg).is_err() {
-                    eprintln!(
-                        "cron runner: inbound channel full, dropping agent job {}",
-                        job.id
-                    );
+                // One run per recipient chat, each in that chat's session. A retry runs
+                // again only in the chats whose run failed.
+                let recipients = job.due_chats(now, allowlist);
+                match store.start_run(&job.id, recipients.len() as u32) {
+                    RunStart::Started => {}
+                    RunStart::Skipped => {
+                        store.record_run(
+                            &job.id,
+                            now,
+                            job.action,
+                            false,
+                            "skipped: the previous run is still going",
+                        );
+                        store.mark_fired(&job.id, now);
+                        continue;
+                    }
+                    RunStart::Queued => {
+                        store.mark_fired(&job.id, now);
+                        continue;
+                    }
+                }
+                for chat_id in recipients {
+                    let msg = InboundMsg {
+                        chat_id,
+                        user_id: 0,
+                        text: job.message.clone(),
+                        channel: "cron".to_string(),
+                        location: None,
+                        thread_id: None,
+                        voice: None,
+                        message_id: None,
+                        cron_job: Some(job.id.clone()),
+                    };
+                    if inbound_tx.try_send(msg).is_err() {
+                        eprintln!(
+                            "cron runner: inbound channel full, dropping agent job {} for chat {}",
+                            job.id, chat_id
+                        );
+                        store.record_run(&job.id, now, job.action, false, "inbound channel full");
+                        store.finish_run(&job.id);
+                    }
                 }
             }
             JobAction::Direct => {
-                let msg = OutboundMsg {
-                    chat_id: job.chat_id,
-                    text: job.message.clone(),
-                    channel: "cron".to_string(),
-                };
-                if outbound_tx.try_send(msg).is_err() {
-                    eprintln!(
-                        "cron runner: outbound channel full, dropping direct job {}",
-                        job.id
-                    );
+                let mut dropped = Vec::new();
+                for chat_id in job.recipients(allowlist) {
+                    let msg = OutboundMsg {
+                        chat_id,
+                        text: job.message.clone(),
+                        channel: "cron".to_string(),
+                        kind: OutboundKind::Text,
+                        thread_id: None,
+                        disable_notification: false,
+                    };
+                    if outbound_tx.try_send(msg).is_err() {
+                        eprintln!(
+                            "cron runner: outbound channel full, dropping direct job {} for chat {}",
+                            job.id, chat_id
+                        );
+                        dropped.push(chat_id.to_string());
+                    }
                 }
+                let detail = if dropped.is_empty() {
+                    String::new()
+                } else {
+                    format!("outbound channel full; not sent to {}", dropped.join(", "))
+                };
+                store.record_run(&job.id, now, job.action, dropped.is_empty(), &detail);
             }
         }
         store.mark_fired(&job.id, now);
@@ -58,6 +118,7 @@ pub async fn tick_once(
 
 async fn tick_loop(
     store: Arc<CronStore>,
+    allowlist: Option<Arc<Allowlist>>,
     inbound_tx: mpsc::Sender<InboundMsg>,
     outbound_tx: mpsc::Sender<OutboundMsg>,
     tick_secs: u64,
@@ -67,26 +128,34 @@ async fn tick_loop(
     loop {
         interval.tick().await;
         let now = unix_now();
-        tick_once(&store, &inbound_tx, &outbound_tx, now).await;
+        tick_once(&store, allowlist.as_deref(), &inbound_tx, &outbound_tx, now).await;
     }
 }
 
 /// Spawns the cron runner task. Returns the join handle (caller may ignore).
 pub fn spawn_cron_runner(
     store: Arc<CronStore>,
+    allowlist: Option<Arc<Allowlist>>,
     inbound_tx: mpsc::Sender<InboundMsg>,
     outbound_tx: mpsc::Sender<OutboundMsg>,
     tick_interval_secs: u64,
 ) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
-        tick_loop(store, inbound_tx, outbound_tx, tick_interval_secs).await;
+        tick_loop(
+            store,
+            allowlist,
+            inbound_tx,
+            outbound_tx,
+            tick_interval_secs,
+        )
+        .await;
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tools::cron::{CronStore, Schedule};
+    use crate::tools::cron::{CronStore, JobUpdate, Overlap, Schedule, Targets};
 
     fn unix_now() -> u64 {
         std::time::SystemTime::now()
@@ -113,7 +182,7 @@ mod tests {
             .unwrap();
         let (inbound_tx, _inbound_rx) = mpsc::channel(8);
         let (outbound_tx, mut outbound_rx) = mpsc::channel(8);
-        tick_once(&store, &inbound_tx, &outbound_tx, base + 61).await;
+        tick_once(&store, None, &inbound_tx, &outbound_tx, base + 61).await;
         let msg = outbound_rx.try_recv().unwrap();
         assert_eq!(msg.chat_id, 12345);
         assert_eq!(msg.text, "Reminder");
@@ -142,7 +211,7 @@ mod tests {
             .unwrap();
         let (inbound_tx, mut inbound_rx) = mpsc::channel(8);
         let (outbound_tx, _outbound_rx) = mpsc::channel(8);
-        tick_once(&store, &inbound_tx, &outbound_tx, base + 61).await;
+        tick_once(&store, None, &inbound_tx, &outbound_tx, base + 61).await;
         let msg = inbound_rx.try_recv().unwrap();
         assert_eq!(msg.chat_id, 999);
         assert_eq!(msg.text, "Agent task");
@@ -171,8 +240,195 @@ mod tests {
             .unwrap();
         let (inbound_tx, _inbound_rx) = mpsc::channel(8);
         let (outbound_tx, mut outbound_rx) = mpsc::channel(8);
-        tick_once(&store, &inbound_tx, &outbound_tx, base + 500).await;
+        tick_once(&store, None, &inbound_tx, &outbound_tx, base + 500).await;
         assert!(outbound_rx.try_recv().is_err());
         let _ = std::fs::remove_dir_all(&dir);
     }
+
+    #[tokio::test]
+    async fn tick_records_direct_runs_and_tags_agent_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(crate::memory::db::BrainDb::open(dir.path()).unwrap());
+        let store = CronStore::empty(dir.path()).with_history(Arc::clone(&db));
+        let base = unix_now();
+        for action in [JobAction::Direct, JobAction::Agent] {
+            store
+                .add(
+                    None,
+                    "Backup".to_string(),
+                    action,
+                    Schedule::Once { at_unix: base + 60 },
+                    7,
+                )
+                .unwrap();
+        }
+        let (inbound_tx, mut inbound_rx) = mpsc::channel(8);
+        let (outbound_tx, _outbound_rx) = mpsc::channel(8);
+        tick_once(&store, None, &inbound_tx, &outbound_tx, base + 61).await;
+        let runs = store.history("job-1", 10).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].ok);
+        assert_eq!(runs[0].action, "direct");
+        assert_eq!(runs[0].ran_at, (base + 61) as i64);
+        // Agent runs are recorded by the main loop once the reply is in.
+        assert_eq!(
+            inbound_rx.try_recv().unwrap().cron_job.as_deref(),
+            Some("job-2")
+        );
+        assert!(store.history("job-2", 10).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn overlapping_agent_runs_skip_or_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(crate::memory::db::BrainDb::open(dir.path()).unwrap());
+        let store = CronStore::empty(dir.path()).with_history(db);
+        let base = unix_now();
+        for _ in 0..2 {
+            store
+                .add(
+                    None,
+                    "Check feeds".to_string(),
+                    JobAction::Agent,
+                    Schedule::Interval {
+                        every_seconds: 300,
+                        jitter_seconds: None,
+                    },
+                    7,
+                )
+                .unwrap();
+        }
+        let queue = JobUpdate {
+            overlap: Some(Overlap::Queue),
+            ..Default::default()
+        };
+        store.update("job-2", queue).unwrap();
+        let (inbound_tx, mut inbound_rx) = mpsc::channel(8);
+        let (outbound_tx, _outbound_rx) = mpsc::channel(8);
+
+        tick_once(&store, None, &inbound_tx, &outbound_tx, base + 301).await;
+        assert_eq!(
+            inbound_rx.try_recv().unwrap().cron_job.as_deref(),
+            Some("job-1")
+        );
+        assert_eq!(
+            inbound_rx.try_recv().unwrap().cron_job.as_deref(),
+            Some("job-2")
+        );
+        // Both runs are still going when the jobs fire again.
+        tick_once(&store, None, &inbound_tx, &outbound_tx, base + 602).await;
+        assert!(inbound_rx.try_recv().is_err());
+        let skipped = store.history("job-1", 10).unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].detail.starts_with("skipped"));
+        assert!(store.get("job-2").unwrap().queued);
+
+        // The queued firing runs as soon as job-2 is idle, without moving its schedule.
+        let next_run = store.get("job-2").unwrap().next_run;
+        store.finish_run("job-2");
+        tick_once(&store, None, &inbound_tx, &outbound_tx, base + 610).await;
+        assert_eq!(
+            inbound_rx.try_recv().unwrap().cron_job.as_deref(),
+            Some("job-2")
+        );
+        let job = store.get("job-2").unwrap();
+        assert!(!job.queued);
+        assert_eq!(job.running, 1);
+        assert_eq!(job.next_run, next_run);
+    }
+
+    #[tokio::test]
+    async fn tick_sends_to_every_target_chat() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CronStore::empty(dir.path());
+        let base = unix_now();
+        for targets in [Targets::Chats(vec![11, 22, 55, 11]), Targets::AllAllowed] {
+            let job = store
+                .add(
+                    None,
+                    "Bins out".to_string(),
+                    JobAction::Direct,
+                    Schedule::Once { at_unix: base + 60 },
+                    11,
+                )
+                .unwrap();
+            let changes = JobUpdate {
+                targets: Some(targets),
+                ..Default::default()
+            };
+            store.update(&job.id, changes).unwrap();
+        }
+        let allowlist = Allowlist::from_config(&crate::config::TelegramConfig {
+            allowed_user_ids: Some(vec![11, 33]),
+            ..Default::default()
+        });
+        allowlist.add(22);
+        allowlist.add(44);
+        let (inbound_tx, _inbound_rx) = mpsc::channel(8);
+        let (outbound_tx, mut outbound_rx) = mpsc::channel(8);
+        tick_once(
+            &store,
+            Some(&allowlist),
+            &inbound_tx,
+            &outbound_tx,
+            base + 61,
+        )
+        .await;
+        let mut chats = Vec::new();
+        while let Ok(msg) = outbound_rx.try_recv() {
+            chats.push(msg.chat_id);
+        }
+        // 55 left the allow-list, so it no longer gets the job.
+        assert_eq!(chats, vec![11, 22, 11, 33, 22, 44]);
+    }
+
+    #[tokio::test]
+    async fn failed_target_chat_is_retried_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CronStore::empty(dir.path());
+        let base = unix_now();
+        let job = store
+            .add(
+                None,
+                "Summarise the news".to_string(),
+                JobAction::Agent,
+                Schedule::Interval {
+                    every_seconds: 86_400,
+                    jitter_seconds: None,
+                },
+                11,
+            )
+            .unwrap();
+        let changes = JobUpdate {
+            targets: Some(Targets::Chats(vec![11, 22])),
+            ..Default::default()
+        };
+        store.update(&job.id, changes).unwrap();
+        let (inbound_tx, mut inbound_rx) = mpsc::channel(8);
+        let (outbound_tx, _outbound_rx) = mpsc::channel(8);
+
+        let fired = base + 86_401;
+        tick_once(&store, None, &inbound_tx, &outbound_tx, fired).await;
+        let chats: Vec<i64> = std::iter::from_fn(|| inbound_rx.try_recv().ok())
+            .map(|m| m.chat_id)
+            .collect();
+        assert_eq!(chats, vec![11, 22]);
+        let next_run = store.get(&job.id).unwrap().next_run;
+
+        // What the main loop does when the run in 11 succeeds and the one in 22 fails.
+        store.finish_run(&job.id);
+        store.clear_failures(&job.id, 11);
+        store.finish_run(&job.id);
+        let at = store.schedule_retry(&job.id, 22, fired, 2, 60).unwrap();
+
+        tick_once(&store, None, &inbound_tx, &outbound_tx, at).await;
+        let chats: Vec<i64> = std::iter::from_fn(|| inbound_rx.try_recv().ok())
+            .map(|m| m.chat_id)
+            .collect();
+        assert_eq!(chats, vec![22]);
+        let job = store.get(&job.id).unwrap();
+        assert_eq!(job.running, 1);
+        assert_eq!(job.next_run, next_run);
+        assert_eq!(job.retries[0].failures, 1);
+    }
 }
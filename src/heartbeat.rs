@@ -81,6 +81,11 @@ pub fn spawn_heartbeat_runner(
                     user_id: 0,
                     text: format!("[Heartbeat Task] {task}"),
                     channel: "heartbeat".to_string(),
+                    location: None,
+                    thread_id: None,
+                    voice: None,
+                    message_id: None,
+                    cron_job: None,
                 };
                 if inbound_tx.send(msg).await.is_err() {
                     // Receiver closed (main loop exited); nothing more to do.
@@ -185,6 +190,11 @@ mod tests {
                 user_id: 0,
                 text: format!("[Heartbeat Task] {task}"),
                 channel: "heartbeat".to_string(),
+                location: None,
+                thread_id: None,
+                voice: None,
+                message_id: None,
+                cron_job: None,
             })
             .await
             .unwrap();
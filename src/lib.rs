@@ -1,4 +1,4 @@
-//! iCrab library: config, Telegram poller, agent loop, tools, workspace, LLM, skills, heartbeat, cron.
+//! iCrab library: config, Telegram poller, agent loop, tools, workspace, LLM, skills, heartbeat, cron, voice.
 
 pub mod agent;
 pub mod config;
@@ -6,8 +6,10 @@ pub mod cron_runner;
 pub mod heartbeat;
 pub mod llm;
 pub mod memory;
+pub mod shell;
 pub mod skills;
 pub mod sync;
 pub mod telegram;
 pub mod tools;
+pub mod voice;
 pub mod workspace;
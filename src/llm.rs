@@ -1,13 +1,32 @@
 //! LLM provider: `chat(messages, tools, model) -> (content, tool_calls)`.
 //!
-//! Single HTTP provider (OpenRouter default). No streaming; minimal types.
+//! [`LlmProvider`] is the provider trait; [`HttpProvider`] speaks the OpenAI-compatible schema
+//! (OpenRouter default) and supports SSE streaming via [`LlmProvider::chat_stream`].
+//! [`provider_from_config`] picks the implementation from `[llm] provider`.
 
 use std::error::Error;
+use std::sync::Arc;
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 
 use crate::config::{Config, LlmConfig};
+use crate::tools::registry::BoxFuture;
+
+pub mod anthropic;
+pub mod cache;
+pub mod debug_log;
+pub mod gemini;
+pub mod ollama;
+pub mod rate_limit;
+pub mod replay;
+pub mod structured;
+pub mod usage;
+
+pub use anthropic::AnthropicProvider;
+pub use gemini::GeminiProvider;
+pub use ollama::OllamaProvider;
 
 // --- Types ---
 
@@ -22,16 +41,132 @@ pub enum Role {
 }
 
 /// A single chat message (system/user/assistant or tool result).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Serializes in the OpenAI shape: `content` is a plain string, or a content array of text and
+/// `image_url` parts when `images` is non-empty.
+#[derive(Debug, Clone, Deserialize)]
 pub struct Message {
     pub role: Role,
     pub content: String,
     /// Set for tool-result messages (role = Tool).
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
     /// Set for assistant messages that requested tool calls (OpenAI shape).
-    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(default)]
     pub tool_calls: Option<Vec<ToolCall>>,
+    /// Images for vision models (user messages). Not persisted in chat history.
+    #[serde(skip)]
+    pub images: Vec<ImagePart>,
+}
+
+impl Serialize for Message {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        #[derive(Serialize)]
+        #[serde(tag = "type", rename_all = "snake_case")]
+        enum Part<'a> {
+            Text { text: &'a str },
+            ImageUrl { image_url: ImageUrl },
+        }
+        #[derive(Serialize)]
+        struct ImageUrl {
+            url: String,
+        }
+
+        let len = 2 + self.tool_call_id.is_some() as usize + self.tool_calls.is_some() as usize;
+        let mut st = serializer.serialize_struct("Message", len)?;
+        st.serialize_field("role", &self.role)?;
+        if self.images.is_empty() {
+            st.serialize_field("content", &self.content)?;
+        } else {
+            let mut parts = Vec::with_capacity(1 + self.images.len());
+            if !self.content.is_empty() {
+                parts.push(Part::Text {
+                    text: &self.content,
+                });
+            }
+            parts.extend(self.images.iter().map(|img| Part::ImageUrl {
+                image_url: ImageUrl { url: img.to_url() },
+            }));
+            st.serialize_field("content", &parts)?;
+        }
+        if let Some(id) = &self.tool_call_id {
+            st.serialize_field("tool_call_id", id)?;
+        }
+        if let Some(calls) = &self.tool_calls {
+            st.serialize_field("tool_calls", calls)?;
+        }
+        st.end()
+    }
+}
+
+/// An image attached to a message: inline bytes (base64) or a URL the provider can fetch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImagePart {
+    Base64 { media_type: String, data: String },
+    Url(String),
+}
+
+impl ImagePart {
+    /// Inline image from raw bytes, e.g. `("image/jpeg", &bytes)`.
+    pub fn from_bytes(media_type: &str, bytes: &[u8]) -> Self {
+        ImagePart::Base64 {
+            media_type: media_type.to_string(),
+            data: base64_encode(bytes),
+        }
+    }
+
+    /// OpenAI `image_url.url`: the URL itself, or a `data:` URL for inline images.
+    pub fn to_url(&self) -> String {
+        match self {
+            ImagePart::Base64 { media_type, data } => format!("data:{media_type};base64,{data}"),
+            ImagePart::Url(url) => url.clone(),
+        }
+    }
+}
+
+/// Standard base64 with padding.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Standard base64 decoding; skips whitespace and other stray bytes, stops at padding.
+pub fn base64_decode(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let (mut acc, mut bits) = (0u32, 0);
+    for &c in input {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => continue,
+        };
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    out
 }
 
 /// OpenAI-style function tool: `type: "function"`, `function: { name, description, parameters }`.
@@ -88,8 +223,45 @@ pub struct UsageInfo {
     pub total_tokens: Option<u64>,
 }
 
+/// Sampling settings for one request; unset fields use the provider's defaults.
+/// Also the shape of `[llm.agent]`, `[llm.subagent]` and `[llm.summarize]` in config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GenParams {
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<usize>,
+    pub top_p: Option<f64>,
+    /// Stop sequences.
+    #[serde(default)]
+    pub stop: Vec<String>,
+}
+
+/// No overrides; what [`LlmProvider::chat`] sends.
+pub static DEFAULT_PARAMS: GenParams = GenParams {
+    temperature: None,
+    max_tokens: None,
+    top_p: None,
+    stop: Vec::new(),
+};
+
+impl GenParams {
+    /// These params with unset fields taken from `fallback`.
+    pub fn or(&self, fallback: &GenParams) -> GenParams {
+        GenParams {
+            temperature: self.temperature.or(fallback.temperature),
+            max_tokens: self.max_tokens.or(fallback.max_tokens),
+            top_p: self.top_p.or(fallback.top_p),
+            stop: if self.stop.is_empty() {
+                fallback.stop.clone()
+            } else {
+                self.stop.clone()
+            },
+        }
+    }
+}
+
 /// LLM response: content, tool_calls, finish_reason, optional usage.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmResponse {
     pub content: String,
     pub tool_calls: Vec<ToolCall>,
@@ -157,6 +329,23 @@ struct ChatRequest<'a> {
     temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop: &'a [String],
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    /// Ask for a final usage chunk when streaming.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+    /// `{"type": "json_schema", ...}` for structured output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct StreamOptions {
+    include_usage: bool,
 }
 
 #[derive(Deserialize)]
@@ -177,6 +366,229 @@ struct ChoiceMessage {
     tool_calls: Option<Vec<ToolCall>>,
 }
 
+/// One SSE `data:` chunk of a streamed completion.
+#[derive(Deserialize)]
+struct StreamChunk {
+    choices: Option<Vec<StreamChoice>>,
+    usage: Option<UsageInfo>,
+    /// Some gateways (OpenRouter) report mid-stream failures as an error chunk.
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct StreamChoice {
+    delta: Option<StreamDelta>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamDelta {
+    content: Option<String>,
+    tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+/// Tool call fragment: `id` and `name` arrive once, `arguments` in pieces, keyed by `index`.
+#[derive(Deserialize)]
+struct ToolCallDelta {
+    index: Option<usize>,
+    id: Option<String>,
+    function: Option<ToolCallFunctionDelta>,
+}
+
+#[derive(Deserialize)]
+struct ToolCallFunctionDelta {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+/// Assembles SSE chunks into an [`LlmResponse`], concatenating content and tool_call deltas.
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    #[serde(default)]
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Default)]
+struct StreamAssembler {
+    content: String,
+    tool_calls: Vec<ToolCall>,
+    finish_reason: String,
+    usage: Option<UsageInfo>,
+    done: bool,
+}
+
+impl StreamAssembler {
+    /// Feed one SSE line; returns the content delta it carried, if any.
+    fn push_line(&mut self, line: &str) -> Result<Option<String>, LlmError> {
+        let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+            // Comments (": keep-alive"), `event:` lines and blank separators.
+            return Ok(None);
+        };
+        if data == "[DONE]" {
+            self.done = true;
+            return Ok(None);
+        }
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let chunk: StreamChunk =
+            serde_json::from_str(data).map_err(|e| LlmError::Parse(e.to_string()))?;
+        if let Some(err) = chunk.error {
+            return Err(LlmError::Http(format!("stream error: {}", err)));
+        }
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+        let Some(choice) = chunk.choices.and_then(|c| c.into_iter().next()) else {
+            return Ok(None);
+        };
+        if let Some(reason) = choice.finish_reason {
+            self.finish_reason = reason;
+        }
+        let Some(delta) = choice.delta else {
+            return Ok(None);
+        };
+        for tc in delta.tool_calls.unwrap_or_default() {
+            let idx = tc.index.unwrap_or(self.tool_calls.len().saturating_sub(1));
+            while self.tool_calls.len() <= idx {
+                self.tool_calls.push(ToolCall {
+                    id: String::new(),
+                    type_: "function".to_string(),
+                    function: ToolCallFunction {
+                        name: String::new(),
+                        arguments: String::new(),
+                    },
+                });
+            }
+            let call = &mut self.tool_calls[idx];
+            if let Some(id) = tc.id {
+                call.id = id;
+            }
+            if let Some(f) = tc.function {
+                if let Some(name) = f.name {
+                    call.function.name.push_str(&name);
+                }
+                if let Some(args) = f.arguments {
+                    call.function.arguments.push_str(&args);
+                }
+            }
+        }
+        match delta.content {
+            Some(text) if !text.is_empty() => {
+                self.content.push_str(&text);
+                Ok(Some(text))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn finish(self) -> LlmResponse {
+        LlmResponse {
+            content: self.content,
+            tool_calls: self.tool_calls,
+            finish_reason: self.finish_reason,
+            usage: self.usage,
+        }
+    }
+}
+
+// --- Provider trait ---
+
+/// A chat-completion backend. Callers hold `&dyn LlmProvider` / `Arc<dyn LlmProvider>`.
+pub trait LlmProvider: Send + Sync {
+    /// Send chat request with sampling `params`. Returns content and tool_calls.
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>>;
+
+    /// Send chat request; returns content and tool_calls. Empty choices yield empty content and no tool_calls.
+    fn chat<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        self.chat_with_params(messages, tools, model, &DEFAULT_PARAMS)
+    }
+
+    /// Like [`chat_with_params`](Self::chat_with_params), but sends content to `deltas` as it is
+    /// generated. The returned response is the fully assembled one. Providers without streaming
+    /// send the whole content once.
+    fn chat_stream<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+        deltas: &'a mpsc::UnboundedSender<String>,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let response = self
+                .chat_with_params(messages, tools, model, params)
+                .await?;
+            if !response.content.is_empty() {
+                let _ = deltas.send(response.content.clone());
+            }
+            Ok(response)
+        })
+    }
+
+    /// One temperature-0 completion whose content should be JSON matching `schema` (a JSON
+    /// Schema object). Providers with native support enforce it; the default describes the
+    /// schema in a leading system message. Use `chat_structured` to parse and validate the reply.
+    fn chat_json<'a>(
+        &'a self,
+        messages: &'a [Message],
+        model: &'a str,
+        schema: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let mut with_schema = Vec::with_capacity(messages.len() + 1);
+            with_schema.push(Message {
+                role: Role::System,
+                content: structured::schema_instruction(schema),
+                tool_call_id: None,
+                tool_calls: None,
+                images: Vec::new(),
+            });
+            with_schema.extend_from_slice(messages);
+            let params = GenParams {
+                temperature: Some(0.0),
+                ..GenParams::default()
+            };
+            self.chat_with_params(&with_schema, &[], model, &params)
+                .await
+        })
+    }
+
+    /// Embedding vectors for `texts` (same order) from embedding `model`. Providers without an
+    /// embeddings API return [`LlmError::Config`].
+    fn embed<'a>(
+        &'a self,
+        texts: &'a [String],
+        model: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, LlmError>> {
+        let _ = (texts, model);
+        Box::pin(async { Err(LlmError::Config("provider has no embeddings API".into())) })
+    }
+}
+
 // --- Provider ---
 
 /// HTTP provider (OpenRouter, OpenAI, Groq, etc.).
@@ -184,14 +596,40 @@ pub struct HttpProvider {
     api_base: String,
     api_key: String,
     client: reqwest::Client,
+    max_attempts: u32,
 }
 
 const DEFAULT_API_BASE: &str = "https://openrouter.ai/api/v1";
 const REQUEST_TIMEOUT_SECS: u64 = 120;
+/// Attempts per request when `[llm] max-attempts` is unset.
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY_MS: u64 = 500;
+const RETRY_MAX_DELAY_MS: u64 = 8_000;
 
-impl HttpProvider {
-    /// Build provider from validated config. Uses `cfg.llm`; default api_base is OpenRouter.
-    pub fn from_config(cfg: &Config) -> Result<Self, LlmError> {
+/// Rate limits and gateway hiccups; worth another try.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 500 | 502 | 503)
+}
+
+/// Delay before retry number `attempt` (1-based): doubling from 500ms up to 8s, with equal
+/// jitter so concurrent callers don't retry in lockstep.
+fn retry_delay(attempt: u32, rand01: f64) -> Duration {
+    let exp = attempt.saturating_sub(1).min(16);
+    let base = (RETRY_BASE_DELAY_MS << exp).min(RETRY_MAX_DELAY_MS) as f64;
+    Duration::from_millis((base * (0.5 + 0.5 * rand01.clamp(0.0, 1.0))) as u64)
+}
+
+/// Connection settings from `[llm]` shared by all providers.
+struct Endpoint {
+    api_base: String,
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl Endpoint {
+    /// Read api_base (falling back to `default_api_base`), api_key and build the HTTP client
+    /// with `[llm.headers]` and `[llm] proxy`.
+    fn from_config(cfg: &Config, default_api_base: &str) -> Result<Self, LlmError> {
         let llm: &LlmConfig = cfg
             .llm
             .as_ref()
@@ -199,18 +637,42 @@ impl HttpProvider {
         let api_key = llm
             .api_key
             .as_deref()
-            .filter(|s| !s.trim().is_empty())
-            .ok_or_else(|| LlmError::Config("llm.api_key required".into()))?
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or_default()
             .to_string();
+        if api_key.is_empty() && llm.requires_api_key() {
+            return Err(LlmError::Config("llm.api_key required".into()));
+        }
         let api_base = llm
             .api_base
             .as_deref()
             .filter(|s| !s.trim().is_empty())
-            .unwrap_or(DEFAULT_API_BASE)
+            .unwrap_or(default_api_base)
             .trim_end_matches('/')
             .to_string();
-        let client = reqwest::Client::builder()
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in llm.headers.iter().flatten() {
+            let name = reqwest::header::HeaderName::from_bytes(name.trim().as_bytes())
+                .map_err(|e| LlmError::Config(format!("llm.headers '{}': {}", name, e)))?;
+            let value = reqwest::header::HeaderValue::from_str(value.trim())
+                .map_err(|e| LlmError::Config(format!("llm.headers '{}': {}", name, e)))?;
+            headers.insert(name, value);
+        }
+        let mut builder = reqwest::Client::builder()
             .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .default_headers(headers);
+        if let Some(proxy) = llm
+            .proxy
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| LlmError::Config(format!("llm.proxy: {}", e)))?;
+            builder = builder.proxy(proxy);
+        }
+        let client = builder
             .build()
             .map_err(|e| LlmError::Config(format!("reqwest client: {}", e)))?;
         Ok(Self {
@@ -219,59 +681,109 @@ impl HttpProvider {
             client,
         })
     }
+}
 
-    /// Send chat request; returns content and tool_calls. Empty choices yield empty content and no tool_calls.
-    pub async fn chat(
-        &self,
-        messages: &[Message],
-        tools: &[ToolDef],
-        model: &str,
-    ) -> Result<LlmResponse, LlmError> {
-        self.chat_with_params(messages, tools, model, None, None)
-            .await
+/// Build the provider named by `[llm] provider`: "anthropic" uses the Messages API, "gemini"
+/// Google's `generateContent`, "ollama" Ollama's native `/api/chat`, anything else (openrouter, openai, groq, a llama.cpp server, ...)
+/// the OpenAI-compatible [`HttpProvider`].
+pub fn provider_from_config(cfg: &Config) -> Result<Arc<dyn LlmProvider>, LlmError> {
+    let name = cfg
+        .llm
+        .as_ref()
+        .map(LlmConfig::provider_name)
+        .unwrap_or_default();
+    Ok(match name.as_str() {
+        "anthropic" => Arc::new(AnthropicProvider::from_config(cfg)?),
+        "gemini" => Arc::new(GeminiProvider::from_config(cfg)?),
+        "ollama" => Arc::new(OllamaProvider::from_config(cfg)?),
+        _ => Arc::new(HttpProvider::from_config(cfg)?),
+    })
+}
+
+impl HttpProvider {
+    /// Build provider from validated config. Uses `cfg.llm`; default api_base is OpenRouter.
+    pub fn from_config(cfg: &Config) -> Result<Self, LlmError> {
+        let Endpoint {
+            api_base,
+            api_key,
+            client,
+        } = Endpoint::from_config(cfg, DEFAULT_API_BASE)?;
+        let max_attempts = cfg
+            .llm
+            .as_ref()
+            .and_then(|l| l.max_attempts)
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+            .max(1);
+        Ok(Self {
+            api_base,
+            api_key,
+            client,
+            max_attempts,
+        })
     }
 
-    /// Send chat request with optional temperature and max_tokens. Returns content and tool_calls.
-    pub async fn chat_with_params(
-        &self,
-        messages: &[Message],
-        tools: &[ToolDef],
-        model: &str,
-        temperature: Option<f64>,
-        max_tokens: Option<usize>,
-    ) -> Result<LlmResponse, LlmError> {
-        let url = format!("{}/chat/completions", self.api_base);
-        let (tools_param, tool_choice) = if tools.is_empty() {
-            (None, None)
-        } else {
-            (Some(tools), Some("auto"))
-        };
-        let body = ChatRequest {
-            model,
-            messages,
-            tools: tools_param,
-            tool_choice,
-            temperature,
-            max_tokens,
-        };
-        let res = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
+    /// POST a JSON request to `path` (e.g. `/chat/completions`); returns the successful
+    /// response (errors carry status and body). 429/500/502/503 responses and connection
+    /// failures are retried with jittered backoff, up to `max_attempts` in total.
+    async fn post(&self, path: &str, body: &impl Serialize) -> Result<reqwest::Response, LlmError> {
+        let url = format!("{}{}", self.api_base, path);
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(body)
+                .send()
+                .await;
+            let error = match result {
+                Ok(res) if res.status().is_success() => return Ok(res),
+                Ok(res) => {
+                    let status = res.status();
+                    let text = res.text().await.unwrap_or_default();
+                    let error = format!("{} {}", status, text);
+                    if !is_retryable_status(status) {
+                        return Err(LlmError::Http(error));
+                    }
+                    error
+                }
+                Err(e) => {
+                    let error = format_reqwest_error(&e);
+                    if !e.is_connect() {
+                        return Err(LlmError::Http(error));
+                    }
+                    error
+                }
+            };
+            if attempt >= self.max_attempts {
+                return Err(LlmError::Http(if attempt > 1 {
+                    format!("{} (after {} attempts)", error, attempt)
+                } else {
+                    error
+                }));
+            }
+            let delay = retry_delay(attempt, crate::telegram::jitter_rand01());
+            eprintln!(
+                "llm: {}; retrying in {}ms (attempt {}/{})",
+                error,
+                delay.as_millis(),
+                attempt + 1,
+                self.max_attempts
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
 
-        let status = res.status();
-        let text = res
+    /// POST a non-streaming request and map the first choice to an [`LlmResponse`].
+    async fn complete(&self, body: &ChatRequest<'_>) -> Result<LlmResponse, LlmError> {
+        let text = self
+            .post("/chat/completions", body)
+            .await?
             .text()
             .await
             .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
-        if !status.is_success() {
-            return Err(LlmError::Http(format!("{} {}", status, text)));
-        }
 
         let parsed: ChatResponse =
             serde_json::from_str(&text).map_err(|e| LlmError::Parse(e.to_string()))?;
@@ -298,6 +810,169 @@ impl HttpProvider {
     }
 }
 
+/// Read a streamed response body line by line (SSE or NDJSON), calling `on_line` with each
+/// line until it returns `Ok(false)` or the body ends.
+async fn read_lines(
+    res: &mut reqwest::Response,
+    mut on_line: impl FnMut(&str) -> Result<bool, LlmError>,
+) -> Result<(), LlmError> {
+    let mut buf: Vec<u8> = Vec::new();
+    while let Some(chunk) = res
+        .chunk()
+        .await
+        .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?
+    {
+        buf.extend_from_slice(&chunk);
+        // Split on complete lines only; a chunk may end mid-line (or mid-UTF-8 char).
+        while let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buf.drain(..=pos).collect();
+            if !on_line(String::from_utf8_lossy(&line).trim_end())? {
+                return Ok(());
+            }
+        }
+    }
+    if !buf.is_empty() {
+        on_line(String::from_utf8_lossy(&buf).trim_end())?;
+    }
+    Ok(())
+}
+
+/// `tools` / `tool_choice` request fields: omitted when there are no tools.
+fn tools_param(tools: &[ToolDef]) -> (Option<&[ToolDef]>, Option<&'static str>) {
+    if tools.is_empty() {
+        (None, None)
+    } else {
+        (Some(tools), Some("auto"))
+    }
+}
+
+impl LlmProvider for HttpProvider {
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let (tools, tool_choice) = tools_param(tools);
+            let body = ChatRequest {
+                model,
+                messages,
+                tools,
+                tool_choice,
+                temperature: params.temperature,
+                max_tokens: params.max_tokens,
+                top_p: params.top_p,
+                stop: &params.stop,
+                stream: false,
+                stream_options: None,
+                response_format: None,
+            };
+            self.complete(&body).await
+        })
+    }
+
+    /// Structured output via `response_format: json_schema`.
+    fn chat_json<'a>(
+        &'a self,
+        messages: &'a [Message],
+        model: &'a str,
+        schema: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let body = ChatRequest {
+                model,
+                messages,
+                tools: None,
+                tool_choice: None,
+                temperature: Some(0.0),
+                max_tokens: None,
+                top_p: None,
+                stop: &[],
+                stream: false,
+                stream_options: None,
+                response_format: Some(serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": "response", "schema": schema }
+                })),
+            };
+            self.complete(&body).await
+        })
+    }
+
+    /// SSE streaming (`stream: true`): content deltas are forwarded as they arrive and
+    /// tool_call fragments are assembled by index.
+    fn chat_stream<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+        deltas: &'a mpsc::UnboundedSender<String>,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let (tools, tool_choice) = tools_param(tools);
+            let body = ChatRequest {
+                model,
+                messages,
+                tools,
+                tool_choice,
+                temperature: params.temperature,
+                max_tokens: params.max_tokens,
+                top_p: params.top_p,
+                stop: &params.stop,
+                stream: true,
+                stream_options: Some(StreamOptions {
+                    include_usage: true,
+                }),
+                response_format: None,
+            };
+            let mut res = self.post("/chat/completions", &body).await?;
+            let mut assembler = StreamAssembler::default();
+            read_lines(&mut res, |line| {
+                if let Some(text) = assembler.push_line(line)? {
+                    let _ = deltas.send(text);
+                }
+                Ok(!assembler.done)
+            })
+            .await?;
+            Ok(assembler.finish())
+        })
+    }
+
+    /// OpenAI-style `/embeddings`; results are returned in input order.
+    fn embed<'a>(
+        &'a self,
+        texts: &'a [String],
+        model: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, LlmError>> {
+        Box::pin(async move {
+            let body = EmbeddingRequest {
+                model,
+                input: texts,
+            };
+            let text = self
+                .post("/embeddings", &body)
+                .await?
+                .text()
+                .await
+                .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
+            let mut parsed: EmbeddingResponse =
+                serde_json::from_str(&text).map_err(|e| LlmError::Parse(e.to_string()))?;
+            if parsed.data.len() != texts.len() {
+                return Err(LlmError::Parse(format!(
+                    "expected {} embeddings, got {}",
+                    texts.len(),
+                    parsed.data.len()
+                )));
+            }
+            parsed.data.sort_by_key(|d| d.index);
+            Ok(parsed.data.into_iter().map(|d| d.embedding).collect())
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -309,6 +984,7 @@ mod tests {
             content: "Hi".to_string(),
             tool_call_id: None,
             tool_calls: None,
+            images: Vec::new(),
         }];
         let body = ChatRequest {
             model: "gpt-4",
@@ -317,6 +993,11 @@ mod tests {
             tool_choice: None,
             temperature: None,
             max_tokens: None,
+            top_p: None,
+            stop: &[],
+            stream: false,
+            stream_options: None,
+            response_format: None,
         };
         let json = serde_json::to_value(&body).unwrap();
         assert_eq!(json["model"], "gpt-4");
@@ -334,6 +1015,7 @@ mod tests {
             content: "Run foo".to_string(),
             tool_call_id: None,
             tool_calls: None,
+            images: Vec::new(),
         }];
         let tools = vec![ToolDef::function(
             "foo".to_string(),
@@ -347,6 +1029,11 @@ mod tests {
             tool_choice: Some("auto"),
             temperature: None,
             max_tokens: None,
+            top_p: None,
+            stop: &[],
+            stream: false,
+            stream_options: None,
+            response_format: None,
         };
         let json = serde_json::to_value(&body).unwrap();
         assert_eq!(json["tools"][0]["type"], "function");
@@ -368,6 +1055,7 @@ mod tests {
                     arguments: r#"{"path":"x"}"#.to_string(),
                 },
             }]),
+            images: Vec::new(),
         }];
         let body = ChatRequest {
             model: "gpt-4",
@@ -376,6 +1064,11 @@ mod tests {
             tool_choice: None,
             temperature: None,
             max_tokens: None,
+            top_p: None,
+            stop: &[],
+            stream: false,
+            stream_options: None,
+            response_format: None,
         };
         let json = serde_json::to_value(&body).unwrap();
         let msg = &json["messages"][0];
@@ -389,4 +1082,302 @@ mod tests {
             r#"{"path":"x"}"#
         );
     }
+
+    #[test]
+    fn stream_assembler_joins_content_and_tool_call_deltas() {
+        let lines = [
+            ": OPENROUTER PROCESSING",
+            r#"data: {"choices":[{"delta":{"role":"assistant","content":"Hel"}}]}"#,
+            "",
+            r#"data: {"choices":[{"delta":{"content":"lo"}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"read_file","arguments":"{\"pa"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"th\":\"x\"}"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":1,"id":"call_2","function":{"name":"list_dir","arguments":"{}"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"data: {"choices":[],"usage":{"prompt_tokens":10,"completion_tokens":5,"total_tokens":15}}"#,
+            "data: [DONE]",
+        ];
+        let mut a = StreamAssembler::default();
+        let mut deltas = Vec::new();
+        for line in lines {
+            if let Some(d) = a.push_line(line).unwrap() {
+                deltas.push(d);
+            }
+        }
+        assert!(a.done);
+        assert_eq!(deltas, ["Hel", "lo"]);
+        let r = a.finish();
+        assert_eq!(r.content, "Hello");
+        assert_eq!(r.finish_reason, "tool_calls");
+        assert_eq!(r.tool_calls.len(), 2);
+        assert_eq!(r.tool_calls[0].id, "call_1");
+        assert_eq!(r.tool_calls[0].function.name, "read_file");
+        assert_eq!(r.tool_calls[0].function.arguments, r#"{"path":"x"}"#);
+        assert_eq!(r.tool_calls[1].function.name, "list_dir");
+        assert_eq!(r.usage.unwrap().total_tokens, Some(15));
+    }
+
+    #[test]
+    fn stream_assembler_surfaces_error_chunks() {
+        let mut a = StreamAssembler::default();
+        let err = a
+            .push_line(r#"data: {"error":{"message":"overloaded"}}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("overloaded"));
+    }
+
+    #[test]
+    fn request_body_stream_flag() {
+        let messages = vec![Message {
+            role: Role::User,
+            content: "Hi".to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        }];
+        let body = ChatRequest {
+            model: "gpt-4",
+            messages: &messages,
+            tools: None,
+            tool_choice: None,
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            stop: &[],
+            stream: true,
+            stream_options: Some(StreamOptions {
+                include_usage: true,
+            }),
+            response_format: None,
+        };
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["stream"], true);
+        assert_eq!(json["stream_options"]["include_usage"], true);
+    }
+
+    #[test]
+    fn request_body_sampling_params() {
+        let params = GenParams {
+            temperature: Some(0.3),
+            top_p: Some(0.9),
+            stop: vec!["\n\n".to_string()],
+            ..Default::default()
+        };
+        let body = ChatRequest {
+            model: "gpt-4",
+            messages: &[],
+            tools: None,
+            tool_choice: None,
+            temperature: params.temperature,
+            max_tokens: params.max_tokens,
+            top_p: params.top_p,
+            stop: &params.stop,
+            stream: false,
+            stream_options: None,
+            response_format: None,
+        };
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["temperature"], 0.3);
+        assert_eq!(json["top_p"], 0.9);
+        assert_eq!(json["stop"][0], "\n\n");
+        assert!(json.get("max_tokens").is_none());
+    }
+
+    #[test]
+    fn gen_params_or_fills_unset_fields() {
+        let configured = GenParams {
+            max_tokens: Some(300),
+            ..Default::default()
+        };
+        let defaults = GenParams {
+            temperature: Some(0.0),
+            max_tokens: Some(1024),
+            stop: vec!["END".to_string()],
+            ..Default::default()
+        };
+        let merged = configured.or(&defaults);
+        assert_eq!(merged.temperature, Some(0.0));
+        assert_eq!(merged.max_tokens, Some(300));
+        assert_eq!(merged.stop, vec!["END".to_string()]);
+    }
+
+    #[test]
+    fn base64_encode_pads() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(&[0xfb, 0xff]), "+/8=");
+    }
+
+    #[test]
+    fn message_with_images_serializes_as_content_array() {
+        let plain = Message {
+            role: Role::User,
+            content: "Hi".to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        };
+        let json = serde_json::to_value(&plain).unwrap();
+        assert_eq!(json, serde_json::json!({"role": "user", "content": "Hi"}));
+
+        let with_images = Message {
+            images: vec![
+                ImagePart::from_bytes("image/png", b"png"),
+                ImagePart::Url("https://example.com/a.jpg".to_string()),
+            ],
+            content: "What is this?".to_string(),
+            ..plain
+        };
+        let json = serde_json::to_value(&with_images).unwrap();
+        let parts = json["content"].as_array().unwrap();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(
+            parts[0],
+            serde_json::json!({"type": "text", "text": "What is this?"})
+        );
+        assert_eq!(parts[1]["type"], "image_url");
+        assert_eq!(parts[1]["image_url"]["url"], "data:image/png;base64,cG5n");
+        assert_eq!(parts[2]["image_url"]["url"], "https://example.com/a.jpg");
+    }
+
+    #[test]
+    fn retry_delay_doubles_with_jitter_and_caps() {
+        assert_eq!(retry_delay(1, 1.0), Duration::from_millis(500));
+        assert_eq!(retry_delay(1, 0.0), Duration::from_millis(250));
+        assert_eq!(retry_delay(3, 1.0), Duration::from_millis(2_000));
+        assert_eq!(
+            retry_delay(30, 1.0),
+            Duration::from_millis(RETRY_MAX_DELAY_MS)
+        );
+    }
+
+    #[tokio::test]
+    async fn http_provider_retries_transient_errors() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("overloaded"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"content": "ok"}, "finish_reason": "stop"}]
+            })))
+            .mount(&server)
+            .await;
+        let cfg = |max_attempts| Config {
+            llm: Some(LlmConfig {
+                api_base: Some(server.uri()),
+                api_key: Some("k".into()),
+                max_attempts: Some(max_attempts),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let r = HttpProvider::from_config(&cfg(2))
+            .unwrap()
+            .chat(&[], &[], "m")
+            .await
+            .unwrap();
+        assert_eq!(r.content, "ok");
+
+        server.reset().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad request"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let err = HttpProvider::from_config(&cfg(3))
+            .unwrap()
+            .chat(&[], &[], "m")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("bad request"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn http_provider_chat_json_sends_response_format() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let schema = serde_json::json!({"type": "object", "required": ["n"]});
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({
+                "temperature": 0.0,
+                "response_format": {"type": "json_schema", "json_schema": {"schema": schema}}
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"content": "{\"n\": 7}"}, "finish_reason": "stop"}]
+            })))
+            .mount(&server)
+            .await;
+        let cfg = Config {
+            llm: Some(LlmConfig {
+                api_base: Some(server.uri()),
+                api_key: Some("k".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let provider = HttpProvider::from_config(&cfg).unwrap();
+        let llm: &dyn LlmProvider = &provider;
+        let value: serde_json::Value = llm.chat_structured(&[], "m", &schema).await.unwrap();
+        assert_eq!(value["n"], 7);
+    }
+
+    #[tokio::test]
+    async fn http_provider_sends_configured_headers() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("X-Title", "iCrab"))
+            .and(header("Authorization", "Bearer k"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"content": "ok"}, "finish_reason": "stop"}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let cfg = |headers: &[(&str, &str)], proxy: Option<&str>| Config {
+            llm: Some(LlmConfig {
+                api_base: Some(server.uri()),
+                api_key: Some("k".into()),
+                headers: Some(
+                    headers
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                ),
+                proxy: proxy.map(String::from),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let r = HttpProvider::from_config(&cfg(&[("X-Title", "iCrab")], None))
+            .unwrap()
+            .chat(&[], &[], "m")
+            .await
+            .unwrap();
+        assert_eq!(r.content, "ok");
+
+        let bad_header = HttpProvider::from_config(&cfg(&[("bad header", "x")], None));
+        assert!(matches!(bad_header, Err(LlmError::Config(_))));
+        let bad_proxy = HttpProvider::from_config(&cfg(&[], Some("not a url")));
+        assert!(matches!(bad_proxy, Err(LlmError::Config(_))));
+    }
 }
@@ -0,0 +1,493 @@
+//! Anthropic Messages API provider (`[llm] provider = "anthropic"`).
+//!
+//! Maps the OpenAI-shaped [`Message`] history onto the Messages API: system messages become the
+//! top-level `system` prompt, assistant tool calls become `tool_use` blocks and tool results are
+//! sent back as `tool_result` blocks in a user turn.
+//!
+//! Prompt caching: the first system block (the stable prompt from `build_messages`) and the
+//! last tool definition carry `cache_control` breakpoints, so every agent iteration re-reads
+//! the tools + system prefix from the cache instead of paying for it again.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    Endpoint, GenParams, ImagePart, LlmError, LlmProvider, LlmResponse, Message, Role, ToolCall,
+    ToolCallFunction, ToolDef, UsageInfo, format_reqwest_error,
+};
+use crate::config::Config;
+use crate::tools::registry::BoxFuture;
+
+const DEFAULT_API_BASE: &str = "https://api.anthropic.com/v1";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// `max_tokens` is required by the Messages API; used when the caller sets none.
+const DEFAULT_MAX_TOKENS: usize = 4096;
+
+// --- Request/response (raw API shape for serde) ---
+
+#[derive(Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    system: Vec<SystemBlock>,
+    messages: Vec<ApiMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ApiTool<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop_sequences: &'a [String],
+}
+
+/// Prompt cache breakpoint: everything up to and including the marked block is cached.
+#[derive(Debug, Clone, Copy, Serialize)]
+struct CacheControl {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+const EPHEMERAL: CacheControl = CacheControl { kind: "ephemeral" };
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename = "text")]
+struct SystemBlock {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiMessage {
+    role: &'static str,
+    content: Vec<Block>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Block {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+    },
+    Image {
+        source: ImageSource,
+    },
+    /// Thinking and any future block types are ignored.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
+#[derive(Serialize)]
+struct ApiTool<'a> {
+    name: &'a str,
+    description: &'a str,
+    input_schema: &'a Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    #[serde(default)]
+    content: Vec<Block>,
+    stop_reason: Option<String>,
+    usage: Option<ApiUsage>,
+}
+
+#[derive(Deserialize)]
+struct ApiUsage {
+    input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+    /// Prompt tokens written to / read from the prompt cache; not included in `input_tokens`.
+    #[serde(default)]
+    cache_creation_input_tokens: Option<u64>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u64>,
+}
+
+/// Split off system messages (one `system` block each, the first marked as a cache
+/// breakpoint) and convert the rest to Messages API turns. Consecutive messages with the same
+/// API role (e.g. several tool results) are merged into one turn.
+fn to_api_messages(messages: &[Message]) -> (Vec<SystemBlock>, Vec<ApiMessage>) {
+    let mut system: Vec<SystemBlock> = Vec::new();
+    let mut out: Vec<ApiMessage> = Vec::new();
+    for m in messages {
+        let (role, blocks) = match m.role {
+            Role::System => {
+                if !m.content.trim().is_empty() {
+                    system.push(SystemBlock {
+                        text: m.content.clone(),
+                        cache_control: system.is_empty().then_some(EPHEMERAL),
+                    });
+                }
+                continue;
+            }
+            Role::User => {
+                // Images go before the text, as the Messages API docs recommend.
+                let mut blocks: Vec<Block> = m
+                    .images
+                    .iter()
+                    .map(|img| Block::Image {
+                        source: match img {
+                            ImagePart::Base64 { media_type, data } => ImageSource::Base64 {
+                                media_type: media_type.clone(),
+                                data: data.clone(),
+                            },
+                            ImagePart::Url(url) => ImageSource::Url { url: url.clone() },
+                        },
+                    })
+                    .collect();
+                if blocks.is_empty() || !m.content.is_empty() {
+                    blocks.push(Block::Text {
+                        text: m.content.clone(),
+                    });
+                }
+                ("user", blocks)
+            }
+            Role::Assistant => {
+                let mut blocks = Vec::new();
+                if !m.content.trim().is_empty() {
+                    blocks.push(Block::Text {
+                        text: m.content.clone(),
+                    });
+                }
+                for tc in m.tool_calls.iter().flatten() {
+                    let input = serde_json::from_str::<Value>(&tc.function.arguments)
+                        .ok()
+                        .filter(Value::is_object)
+                        .unwrap_or_else(|| serde_json::json!({}));
+                    blocks.push(Block::ToolUse {
+                        id: tc.id.clone(),
+                        name: tc.function.name.clone(),
+                        input,
+                    });
+                }
+                ("assistant", blocks)
+            }
+            Role::Tool => (
+                "user",
+                vec![Block::ToolResult {
+                    tool_use_id: m.tool_call_id.clone().unwrap_or_default(),
+                    content: m.content.clone(),
+                }],
+            ),
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+        match out.last_mut() {
+            Some(last) if last.role == role => last.content.extend(blocks),
+            _ => out.push(ApiMessage {
+                role,
+                content: blocks,
+            }),
+        }
+    }
+    (system, out)
+}
+
+/// Map a Messages API response to the common shape (stop reasons in OpenAI terms).
+fn from_api_response(parsed: MessagesResponse) -> LlmResponse {
+    let mut content = String::new();
+    let mut tool_calls = Vec::new();
+    for block in parsed.content {
+        match block {
+            Block::Text { text } => content.push_str(&text),
+            Block::ToolUse { id, name, input } => tool_calls.push(ToolCall {
+                id,
+                type_: "function".to_string(),
+                function: ToolCallFunction {
+                    name,
+                    arguments: input.to_string(),
+                },
+            }),
+            Block::ToolResult { .. } | Block::Image { .. } | Block::Other => {}
+        }
+    }
+    let finish_reason = match parsed.stop_reason.as_deref() {
+        Some("end_turn") | Some("stop_sequence") => "stop".to_string(),
+        Some("tool_use") => "tool_calls".to_string(),
+        Some("max_tokens") => "length".to_string(),
+        other => other.unwrap_or("").to_string(),
+    };
+    let usage = parsed.usage.map(|u| {
+        let prompt_tokens = u.input_tokens.map(|i| {
+            i + u.cache_creation_input_tokens.unwrap_or(0) + u.cache_read_input_tokens.unwrap_or(0)
+        });
+        UsageInfo {
+            prompt_tokens,
+            completion_tokens: u.output_tokens,
+            total_tokens: match (prompt_tokens, u.output_tokens) {
+                (Some(i), Some(o)) => Some(i + o),
+                _ => None,
+            },
+        }
+    });
+    LlmResponse {
+        content,
+        tool_calls,
+        finish_reason,
+        usage,
+    }
+}
+
+// --- Provider ---
+
+/// Anthropic Messages API provider.
+pub struct AnthropicProvider {
+    api_base: String,
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl AnthropicProvider {
+    /// Build provider from validated config; default api_base is `https://api.anthropic.com/v1`.
+    pub fn from_config(cfg: &Config) -> Result<Self, LlmError> {
+        let Endpoint {
+            api_base,
+            api_key,
+            client,
+        } = Endpoint::from_config(cfg, DEFAULT_API_BASE)?;
+        Ok(Self {
+            api_base,
+            api_key,
+            client,
+        })
+    }
+}
+
+impl LlmProvider for AnthropicProvider {
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let (system, messages) = to_api_messages(messages);
+            let body = MessagesRequest {
+                model,
+                max_tokens: params.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+                system,
+                messages,
+                tools: tools
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| ApiTool {
+                        name: &t.function.name,
+                        description: &t.function.description,
+                        input_schema: &t.function.parameters,
+                        // Tools come first in the cached prefix; one breakpoint covers them all.
+                        cache_control: (i + 1 == tools.len()).then_some(EPHEMERAL),
+                    })
+                    .collect(),
+                temperature: params.temperature,
+                top_p: params.top_p,
+                stop_sequences: &params.stop,
+            };
+            let url = format!("{}/messages", self.api_base);
+            let res = self
+                .client
+                .post(&url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
+            let status = res.status();
+            let text = res
+                .text()
+                .await
+                .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
+            if !status.is_success() {
+                return Err(LlmError::Http(format!("{} {}", status, text)));
+            }
+            let parsed: MessagesResponse =
+                serde_json::from_str(&text).map_err(|e| LlmError::Parse(e.to_string()))?;
+            Ok(from_api_response(parsed))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LlmConfig;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn msg(role: Role, content: &str) -> Message {
+        Message {
+            role,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn history_maps_to_messages_api_turns() {
+        let mut assistant = msg(Role::Assistant, "");
+        assistant.tool_calls = Some(vec![
+            ToolCall {
+                id: "toolu_1".into(),
+                type_: "function".into(),
+                function: ToolCallFunction {
+                    name: "read_file".into(),
+                    arguments: r#"{"path":"a.md"}"#.into(),
+                },
+            },
+            ToolCall {
+                id: "toolu_2".into(),
+                type_: "function".into(),
+                function: ToolCallFunction {
+                    name: "list_dir".into(),
+                    arguments: "not json".into(),
+                },
+            },
+        ]);
+        let mut r1 = msg(Role::Tool, "A");
+        r1.tool_call_id = Some("toolu_1".into());
+        let mut r2 = msg(Role::Tool, "B");
+        r2.tool_call_id = Some("toolu_2".into());
+        let history = vec![
+            msg(Role::System, "You are iCrab."),
+            msg(Role::User, "read a"),
+            assistant,
+            r1,
+            r2,
+        ];
+
+        let (system, turns) = to_api_messages(&history);
+        assert_eq!(system.len(), 1);
+        assert_eq!(system[0].text, "You are iCrab.");
+        let json = serde_json::to_value(&turns).unwrap();
+        assert_eq!(
+            json.as_array().unwrap().len(),
+            3,
+            "tool results merge into one turn"
+        );
+        assert_eq!(json[0]["role"], "user");
+        assert_eq!(json[0]["content"][0]["text"], "read a");
+        assert_eq!(json[1]["role"], "assistant");
+        assert_eq!(json[1]["content"][0]["type"], "tool_use");
+        assert_eq!(json[1]["content"][0]["input"]["path"], "a.md");
+        assert_eq!(json[1]["content"][1]["input"], serde_json::json!({}));
+        assert_eq!(json[2]["role"], "user");
+        assert_eq!(json[2]["content"][0]["type"], "tool_result");
+        assert_eq!(json[2]["content"][1]["tool_use_id"], "toolu_2");
+    }
+
+    #[test]
+    fn user_images_become_image_blocks() {
+        let mut user = msg(Role::User, "What is this?");
+        user.images = vec![
+            ImagePart::from_bytes("image/jpeg", b"jpg"),
+            ImagePart::Url("https://example.com/a.png".into()),
+        ];
+        let (_, turns) = to_api_messages(&[user]);
+        let json = serde_json::to_value(&turns).unwrap();
+        let content = &json[0]["content"];
+        assert_eq!(content[0]["type"], "image");
+        assert_eq!(content[0]["source"]["type"], "base64");
+        assert_eq!(content[0]["source"]["media_type"], "image/jpeg");
+        assert_eq!(content[0]["source"]["data"], "anBn");
+        assert_eq!(content[1]["source"]["type"], "url");
+        assert_eq!(content[2]["text"], "What is this?");
+    }
+
+    #[tokio::test]
+    async fn chat_parses_tool_use_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/messages"))
+            .and(header("x-api-key", "k"))
+            .and(header("anthropic-version", ANTHROPIC_VERSION))
+            .and(body_partial_json(serde_json::json!({
+                "system": [
+                    {"type": "text", "text": "sys", "cache_control": {"type": "ephemeral"}},
+                    {"type": "text", "text": "Current chat: 1."}
+                ],
+                "max_tokens": DEFAULT_MAX_TOKENS,
+                "tools": [{
+                    "name": "read_file",
+                    "input_schema": {"type": "object"},
+                    "cache_control": {"type": "ephemeral"}
+                }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "content": [
+                    {"type": "thinking", "thinking": "..."},
+                    {"type": "text", "text": "Reading."},
+                    {"type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {"path": "x"}}
+                ],
+                "stop_reason": "tool_use",
+                "usage": {
+                    "input_tokens": 12,
+                    "output_tokens": 3,
+                    "cache_read_input_tokens": 2000
+                }
+            })))
+            .mount(&server)
+            .await;
+        let cfg = Config {
+            llm: Some(LlmConfig {
+                provider: Some("anthropic".into()),
+                api_base: Some(server.uri()),
+                api_key: Some("k".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let provider = AnthropicProvider::from_config(&cfg).unwrap();
+        let tools = vec![ToolDef::function(
+            "read_file".into(),
+            "Read".into(),
+            serde_json::json!({"type": "object"}),
+        )];
+        let r = provider
+            .chat(
+                &[
+                    msg(Role::System, "sys"),
+                    msg(Role::System, "Current chat: 1."),
+                    msg(Role::User, "hi"),
+                ],
+                &tools,
+                "claude",
+            )
+            .await
+            .unwrap();
+        assert_eq!(r.content, "Reading.");
+        assert_eq!(r.finish_reason, "tool_calls");
+        assert_eq!(r.tool_calls[0].function.name, "read_file");
+        assert_eq!(r.tool_calls[0].function.arguments, r#"{"path":"x"}"#);
+        let usage = r.usage.unwrap();
+        assert_eq!(
+            usage.prompt_tokens,
+            Some(2012),
+            "cache reads count as prompt tokens"
+        );
+        assert_eq!(usage.total_tokens, Some(2015));
+    }
+}
@@ -0,0 +1,210 @@
+//! Response cache for deterministic LLM calls: [`CachedProvider`] stores the response to every
+//! temperature-0 request in `llm_cache`, keyed by a hash of model, messages, tools and sampling
+//! params, and answers identical requests from it until the entry is `ttl` old.
+//!
+//! Other calls (default temperature, streaming, structured output) always go to the provider.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use super::{GenParams, LlmError, LlmProvider, LlmResponse, Message, ToolDef};
+use crate::memory::db::BrainDb;
+use crate::tools::registry::BoxFuture;
+
+/// Entry lifetime when `[llm] cache-ttl-secs` is unset.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// Cache key for a request: hex hash of everything that determines a temperature-0 reply.
+fn cache_key(messages: &[Message], tools: &[ToolDef], model: &str, params: &GenParams) -> String {
+    let mut hasher = DefaultHasher::new();
+    model.hash(&mut hasher);
+    serde_json::to_string(params)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    serde_json::to_string(messages)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    serde_json::to_string(tools)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Provider wrapper that serves repeated temperature-0 requests from the brain DB.
+pub struct CachedProvider {
+    inner: Arc<dyn LlmProvider>,
+    db: Arc<BrainDb>,
+    ttl: Duration,
+}
+
+impl CachedProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, db: Arc<BrainDb>, ttl: Duration) -> Self {
+        Self { inner, db, ttl }
+    }
+
+    /// Cached response for `key`; DB or decode failures count as a miss.
+    async fn lookup(&self, key: &str) -> Option<LlmResponse> {
+        let db = Arc::clone(&self.db);
+        let key = key.to_string();
+        let ttl = self.ttl.as_secs();
+        match tokio::task::spawn_blocking(move || db.llm_cache_get(&key, ttl)).await {
+            Ok(Ok(Some(json))) => serde_json::from_str(&json).ok(),
+            Ok(Ok(None)) => None,
+            Ok(Err(e)) => {
+                eprintln!("llm cache: {}", e);
+                None
+            }
+            Err(e) => {
+                eprintln!("llm cache task error: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Store `response` under `key`. Failures are logged, never fatal.
+    async fn store(&self, key: String, response: &LlmResponse) {
+        let Ok(json) = serde_json::to_string(response) else {
+            return;
+        };
+        let db = Arc::clone(&self.db);
+        let ttl = self.ttl.as_secs();
+        match tokio::task::spawn_blocking(move || db.llm_cache_put(&key, &json, ttl)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("llm cache: {}", e),
+            Err(e) => eprintln!("llm cache task error: {}", e),
+        }
+    }
+}
+
+impl LlmProvider for CachedProvider {
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            if params.temperature != Some(0.0) || self.ttl.is_zero() {
+                return self
+                    .inner
+                    .chat_with_params(messages, tools, model, params)
+                    .await;
+            }
+            let key = cache_key(messages, tools, model, params);
+            if let Some(mut cached) = self.lookup(&key).await {
+                // Nothing was spent on this call.
+                cached.usage = None;
+                return Ok(cached);
+            }
+            let response = self
+                .inner
+                .chat_with_params(messages, tools, model, params)
+                .await?;
+            self.store(key, &response).await;
+            Ok(response)
+        })
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+        deltas: &'a mpsc::UnboundedSender<String>,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        self.inner
+            .chat_stream(messages, tools, model, params, deltas)
+    }
+
+    fn chat_json<'a>(
+        &'a self,
+        messages: &'a [Message],
+        model: &'a str,
+        schema: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        self.inner.chat_json(messages, model, schema)
+    }
+
+    fn embed<'a>(
+        &'a self,
+        texts: &'a [String],
+        model: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, LlmError>> {
+        self.inner.embed(texts, model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::Role;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts calls and echoes the call number.
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    impl LlmProvider for Counting {
+        fn chat_with_params<'a>(
+            &'a self,
+            _messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            let n = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Box::pin(async move {
+                Ok(LlmResponse {
+                    content: format!("reply {n}"),
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".into(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    fn user(content: &str) -> Vec<Message> {
+        vec![Message {
+            role: Role::User,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        }]
+    }
+
+    #[tokio::test]
+    async fn caches_only_temperature_zero_calls() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let inner = Arc::new(Counting::default());
+        let llm = CachedProvider::new(inner.clone(), db, DEFAULT_CACHE_TTL);
+
+        let a = user("summarize this");
+        let zero = GenParams {
+            temperature: Some(0.0),
+            ..Default::default()
+        };
+        let r1 = llm.chat_with_params(&a, &[], "m", &zero).await;
+        let r2 = llm.chat_with_params(&a, &[], "m", &zero).await;
+        assert_eq!(r1.unwrap().content, "reply 1");
+        assert_eq!(r2.unwrap().content, "reply 1", "served from cache");
+
+        let r3 = llm.chat_with_params(&a, &[], "other", &zero).await;
+        assert_eq!(r3.unwrap().content, "reply 2", "model is part of the key");
+        let r4 = llm.chat(&a, &[], "m").await;
+        assert_eq!(
+            r4.unwrap().content,
+            "reply 3",
+            "default temperature bypasses"
+        );
+        assert_eq!(inner.0.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,265 @@
+//! Opt-in request/response log for debugging (`[llm] debug-log = true`): [`DebugLogProvider`]
+//! appends one JSON line per call (model, params, messages, tools, then the response or error)
+//! to `workspace/.icrab/llm-log/llm.jsonl`.
+//!
+//! Configured secrets (API keys, bot token, allowed user ids) are replaced with `[redacted]`
+//! before anything is written. When the file passes the size cap it is rotated to
+//! `llm.jsonl.1`, so the log never holds more than two files' worth.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+use super::{GenParams, LlmError, LlmProvider, LlmResponse, Message, ToolDef};
+use crate::tools::registry::BoxFuture;
+
+/// Rotation size when `[llm] debug-log-max-bytes` is unset.
+pub const DEFAULT_DEBUG_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+const LOG_FILE: &str = "llm.jsonl";
+const REDACTED: &str = "[redacted]";
+
+/// `text` with every occurrence of each secret replaced by `[redacted]`.
+fn redact(text: &str, secrets: &[String]) -> String {
+    let mut out = text.to_string();
+    for s in secrets.iter().filter(|s| !s.is_empty()) {
+        if out.contains(s.as_str()) {
+            out = out.replace(s.as_str(), REDACTED);
+        }
+    }
+    out
+}
+
+/// Provider wrapper that logs every call to a rotating JSONL file.
+pub struct DebugLogProvider {
+    inner: Arc<dyn LlmProvider>,
+    dir: PathBuf,
+    secrets: Arc<Vec<String>>,
+    max_bytes: u64,
+    /// Serializes append + rotate across concurrent calls.
+    lock: Arc<Mutex<()>>,
+}
+
+impl DebugLogProvider {
+    pub fn new(
+        inner: Arc<dyn LlmProvider>,
+        dir: PathBuf,
+        secrets: Vec<String>,
+        max_bytes: u64,
+    ) -> Self {
+        // Longest first so a secret containing another is redacted whole.
+        let mut secrets = secrets;
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        Self {
+            inner,
+            dir,
+            secrets: Arc::new(secrets),
+            max_bytes,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Append `entry` (redacted) to the log. Failures are logged, never fatal.
+    async fn write(&self, entry: Value) {
+        let dir = self.dir.clone();
+        let secrets = Arc::clone(&self.secrets);
+        let max_bytes = self.max_bytes;
+        let lock = Arc::clone(&self.lock);
+        let res = tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+            let line = redact(&entry.to_string(), &secrets);
+            let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+            std::fs::create_dir_all(&dir)?;
+            let path = dir.join(LOG_FILE);
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            if size > 0 && size + line.len() as u64 > max_bytes {
+                std::fs::rename(&path, dir.join(format!("{LOG_FILE}.1")))?;
+            }
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?;
+            writeln!(file, "{line}")
+        })
+        .await;
+        match res {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("llm debug log: {}", e),
+            Err(e) => eprintln!("llm debug log task error: {}", e),
+        }
+    }
+
+    async fn log(&self, mut request: Value, result: &Result<LlmResponse, LlmError>) {
+        request["ts"] = json!(chrono::Utc::now().to_rfc3339());
+        match result {
+            Ok(r) => request["response"] = json!(r),
+            Err(e) => request["error"] = json!(e.to_string()),
+        }
+        self.write(request).await;
+    }
+}
+
+fn request_entry(
+    kind: &str,
+    messages: &[Message],
+    tools: &[ToolDef],
+    model: &str,
+    params: &GenParams,
+) -> Value {
+    json!({
+        "kind": kind,
+        "model": model,
+        "params": params,
+        "messages": messages,
+        "tools": tools,
+    })
+}
+
+impl LlmProvider for DebugLogProvider {
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let result = self
+                .inner
+                .chat_with_params(messages, tools, model, params)
+                .await;
+            let entry = request_entry("chat", messages, tools, model, params);
+            self.log(entry, &result).await;
+            result
+        })
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+        deltas: &'a mpsc::UnboundedSender<String>,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let result = self
+                .inner
+                .chat_stream(messages, tools, model, params, deltas)
+                .await;
+            let entry = request_entry("stream", messages, tools, model, params);
+            self.log(entry, &result).await;
+            result
+        })
+    }
+
+    fn chat_json<'a>(
+        &'a self,
+        messages: &'a [Message],
+        model: &'a str,
+        schema: &'a Value,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let result = self.inner.chat_json(messages, model, schema).await;
+            let mut entry = request_entry("json", messages, &[], model, &GenParams::default());
+            entry["schema"] = schema.clone();
+            self.log(entry, &result).await;
+            result
+        })
+    }
+
+    fn embed<'a>(
+        &'a self,
+        texts: &'a [String],
+        model: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, LlmError>> {
+        self.inner.embed(texts, model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::Role;
+
+    /// Echoes the last message back.
+    struct Echo;
+
+    impl LlmProvider for Echo {
+        fn chat_with_params<'a>(
+            &'a self,
+            messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            let content = messages
+                .last()
+                .map(|m| m.content.clone())
+                .unwrap_or_default();
+            Box::pin(async move {
+                Ok(LlmResponse {
+                    content,
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".into(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    fn user(content: &str) -> Vec<Message> {
+        vec![Message {
+            role: Role::User,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        }]
+    }
+
+    #[test]
+    fn redact_replaces_every_secret() {
+        let secrets = vec!["sk-abc123".to_string(), "424242".to_string()];
+        assert_eq!(
+            redact("key sk-abc123 for user 424242 and sk-abc123", &secrets),
+            "key [redacted] for user [redacted] and [redacted]"
+        );
+        assert_eq!(redact("nothing here", &secrets), "nothing here");
+    }
+
+    #[tokio::test]
+    async fn logs_redacted_calls_and_rotates() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("llm-log");
+        let llm = DebugLogProvider::new(
+            Arc::new(Echo),
+            dir.clone(),
+            vec!["sk-secret".into(), "987654".into()],
+            600,
+        );
+
+        llm.chat(&user("my key is sk-secret, I am 987654"), &[], "m")
+            .await
+            .unwrap();
+        let log = std::fs::read_to_string(dir.join(LOG_FILE)).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(
+            !log.contains("sk-secret") && !log.contains("987654"),
+            "{log}"
+        );
+        assert!(log.contains("my key is [redacted], I am [redacted]"));
+        let entry: Value = serde_json::from_str(log.trim()).unwrap();
+        assert_eq!(entry["model"], "m");
+        assert!(entry["response"]["content"].is_string());
+
+        for _ in 0..3 {
+            llm.chat(&user(&"x".repeat(100)), &[], "m").await.unwrap();
+        }
+        assert!(dir.join("llm.jsonl.1").exists(), "rotated past the cap");
+        let size = std::fs::metadata(dir.join(LOG_FILE)).unwrap().len();
+        assert!(size <= 600, "{size}");
+    }
+}
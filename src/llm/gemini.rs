@@ -0,0 +1,594 @@
+//! Google Gemini provider (`[llm] provider = "gemini"`): the native `generateContent` API of
+//! generativelanguage.googleapis.com, for AI Studio keys without an OpenAI-compatible shim.
+//!
+//! System messages become `systemInstruction`, tools are sent as `functionDeclarations`,
+//! assistant tool calls are `functionCall` parts of a `model` turn and tool results go back as
+//! `functionResponse` parts of a `user` turn. Gemini has no tool-call ids, so they are generated
+//! and results are matched to calls by name.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use super::{
+    Endpoint, GenParams, ImagePart, LlmError, LlmProvider, LlmResponse, Message, Role, ToolCall,
+    ToolCallFunction, ToolDef, UsageInfo, format_reqwest_error, structured,
+};
+use crate::config::Config;
+use crate::tools::registry::BoxFuture;
+
+const DEFAULT_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
+/// Sent on replayed `functionCall` parts in place of the original thought signature, which
+/// this provider does not keep; Gemini 3 rejects function calls without one.
+const SKIP_THOUGHT_SIGNATURE: &str = "skip_thought_signature_validator";
+/// Schema keywords the function-declaration schema subset rejects.
+const UNSUPPORTED_SCHEMA_KEYS: &[&str] = &["$schema", "additionalProperties"];
+
+// --- Request/response (raw API shape for serde) ---
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<Content>,
+    contents: Vec<Content>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ApiTools>,
+    #[serde(skip_serializing_if = "GenerationConfig::is_empty")]
+    generation_config: GenerationConfig,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Content {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    role: String,
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+/// One part of a turn; exactly one of the content fields is set.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inline_data: Option<InlineData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_call: Option<FunctionCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_response: Option<FunctionResponse>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thought_signature: Option<String>,
+    /// True on thought summaries, which are not part of the reply.
+    #[serde(default, skip_serializing)]
+    thought: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InlineData {
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FunctionCall {
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FunctionResponse {
+    name: String,
+    response: Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiTools {
+    function_declarations: Vec<FunctionDeclaration>,
+}
+
+#[derive(Serialize)]
+struct FunctionDeclaration {
+    name: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameters: Option<Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<&'static str>,
+}
+
+impl GenerationConfig {
+    fn from_params(params: &GenParams) -> Self {
+        Self {
+            temperature: params.temperature,
+            max_output_tokens: params.max_tokens,
+            top_p: params.top_p,
+            stop_sequences: params.stop.clone(),
+            response_mime_type: None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.temperature.is_none()
+            && self.max_output_tokens.is_none()
+            && self.top_p.is_none()
+            && self.stop_sequences.is_empty()
+            && self.response_mime_type.is_none()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    #[serde(default)]
+    content: Content,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    prompt_token_count: Option<u64>,
+    candidates_token_count: Option<u64>,
+    total_token_count: Option<u64>,
+}
+
+/// Tool parameter schema in the subset Gemini accepts: unsupported keywords removed, and
+/// None for a parameterless tool (an object schema without properties is rejected).
+fn to_gemini_schema(schema: &Value) -> Option<Value> {
+    fn strip(v: &mut Value) {
+        match v {
+            Value::Object(map) => {
+                for key in UNSUPPORTED_SCHEMA_KEYS {
+                    map.remove(*key);
+                }
+                map.values_mut().for_each(strip);
+            }
+            Value::Array(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+    let has_properties = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .is_some_and(|p| !p.is_empty());
+    if !has_properties {
+        return None;
+    }
+    let mut schema = schema.clone();
+    strip(&mut schema);
+    Some(schema)
+}
+
+fn to_api_tools(tools: &[ToolDef]) -> Vec<ApiTools> {
+    if tools.is_empty() {
+        return Vec::new();
+    }
+    vec![ApiTools {
+        function_declarations: tools
+            .iter()
+            .map(|t| FunctionDeclaration {
+                name: t.function.name.clone(),
+                description: t.function.description.clone(),
+                parameters: to_gemini_schema(&t.function.parameters),
+            })
+            .collect(),
+    }]
+}
+
+fn text_part(text: &str) -> Part {
+    Part {
+        text: Some(text.to_string()),
+        ..Part::default()
+    }
+}
+
+/// Split off system messages into `systemInstruction` and convert the rest to `user` / `model`
+/// turns. Consecutive messages with the same role (e.g. several tool results) are merged.
+fn to_contents(messages: &[Message]) -> (Option<Content>, Vec<Content>) {
+    let mut system: Vec<Part> = Vec::new();
+    let mut out: Vec<Content> = Vec::new();
+    let mut names: HashMap<&str, &str> = HashMap::new();
+    for m in messages {
+        let (role, parts) = match m.role {
+            Role::System => {
+                if !m.content.trim().is_empty() {
+                    system.push(text_part(&m.content));
+                }
+                continue;
+            }
+            Role::User => {
+                let mut parts: Vec<Part> = m
+                    .images
+                    .iter()
+                    .filter_map(|img| match img {
+                        ImagePart::Base64 { media_type, data } => Some(Part {
+                            inline_data: Some(InlineData {
+                                mime_type: media_type.clone(),
+                                data: data.clone(),
+                            }),
+                            ..Part::default()
+                        }),
+                        // Only Files API / Cloud Storage URIs are accepted, not web URLs.
+                        ImagePart::Url(_) => None,
+                    })
+                    .collect();
+                if parts.is_empty() || !m.content.is_empty() {
+                    parts.push(text_part(&m.content));
+                }
+                ("user", parts)
+            }
+            Role::Assistant => {
+                let mut parts = Vec::new();
+                if !m.content.trim().is_empty() {
+                    parts.push(text_part(&m.content));
+                }
+                for tc in m.tool_calls.iter().flatten() {
+                    names.insert(&tc.id, &tc.function.name);
+                    let args = serde_json::from_str::<Value>(&tc.function.arguments)
+                        .ok()
+                        .filter(Value::is_object)
+                        .unwrap_or_else(|| json!({}));
+                    parts.push(Part {
+                        function_call: Some(FunctionCall {
+                            name: tc.function.name.clone(),
+                            args,
+                        }),
+                        thought_signature: Some(SKIP_THOUGHT_SIGNATURE.to_string()),
+                        ..Part::default()
+                    });
+                }
+                ("model", parts)
+            }
+            Role::Tool => {
+                let name = m
+                    .tool_call_id
+                    .as_deref()
+                    .and_then(|id| names.get(id))
+                    .copied()
+                    .unwrap_or_default();
+                (
+                    "user",
+                    vec![Part {
+                        function_response: Some(FunctionResponse {
+                            name: name.to_string(),
+                            response: json!({ "content": m.content }),
+                        }),
+                        ..Part::default()
+                    }],
+                )
+            }
+        };
+        if parts.is_empty() {
+            continue;
+        }
+        match out.last_mut() {
+            Some(last) if last.role == role => last.parts.extend(parts),
+            _ => out.push(Content {
+                role: role.to_string(),
+                parts,
+            }),
+        }
+    }
+    let system = (!system.is_empty()).then(|| Content {
+        role: String::new(),
+        parts: system,
+    });
+    (system, out)
+}
+
+/// Map the first candidate to the common shape (finish reasons in OpenAI terms).
+fn from_api_response(parsed: GenerateResponse) -> LlmResponse {
+    let mut content = String::new();
+    let mut tool_calls = Vec::new();
+    let candidate = parsed.candidates.into_iter().next();
+    let finish = candidate.as_ref().and_then(|c| c.finish_reason.clone());
+    for part in candidate.map(|c| c.content.parts).unwrap_or_default() {
+        if part.thought {
+            continue;
+        }
+        if let Some(text) = part.text {
+            content.push_str(&text);
+        }
+        if let Some(call) = part.function_call {
+            tool_calls.push(ToolCall {
+                id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+                type_: "function".to_string(),
+                function: ToolCallFunction {
+                    name: call.name,
+                    arguments: match call.args {
+                        Value::Null => "{}".to_string(),
+                        v => v.to_string(),
+                    },
+                },
+            });
+        }
+    }
+    let finish_reason = if !tool_calls.is_empty() {
+        "tool_calls".to_string()
+    } else {
+        match finish.as_deref() {
+            Some("STOP") => "stop".to_string(),
+            Some("MAX_TOKENS") => "length".to_string(),
+            Some("SAFETY") | Some("RECITATION") | Some("PROHIBITED_CONTENT") => {
+                "content_filter".to_string()
+            }
+            other => other.unwrap_or("").to_ascii_lowercase(),
+        }
+    };
+    let usage = parsed.usage_metadata.map(|u| UsageInfo {
+        prompt_tokens: u.prompt_token_count,
+        completion_tokens: u.candidates_token_count,
+        total_tokens: u.total_token_count,
+    });
+    LlmResponse {
+        content,
+        tool_calls,
+        finish_reason,
+        usage,
+    }
+}
+
+// --- Provider ---
+
+/// Gemini `generateContent` provider.
+pub struct GeminiProvider {
+    api_base: String,
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl GeminiProvider {
+    /// Build provider from validated config; default api_base is
+    /// `https://generativelanguage.googleapis.com/v1beta`.
+    pub fn from_config(cfg: &Config) -> Result<Self, LlmError> {
+        let Endpoint {
+            api_base,
+            api_key,
+            client,
+        } = Endpoint::from_config(cfg, DEFAULT_API_BASE)?;
+        Ok(Self {
+            api_base,
+            api_key,
+            client,
+        })
+    }
+
+    /// POST to `models/{model}:generateContent`. A leading `models/` in `model` is accepted.
+    async fn generate(&self, model: &str, body: &GenerateRequest) -> Result<LlmResponse, LlmError> {
+        let model = model.strip_prefix("models/").unwrap_or(model);
+        let url = format!("{}/models/{}:generateContent", self.api_base, model);
+        let res = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", &self.api_key)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
+        let status = res.status();
+        let text = res
+            .text()
+            .await
+            .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
+        if !status.is_success() {
+            return Err(LlmError::Http(format!("{} {}", status, text)));
+        }
+        let parsed: GenerateResponse =
+            serde_json::from_str(&text).map_err(|e| LlmError::Parse(e.to_string()))?;
+        Ok(from_api_response(parsed))
+    }
+}
+
+impl LlmProvider for GeminiProvider {
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let (system_instruction, contents) = to_contents(messages);
+            let body = GenerateRequest {
+                system_instruction,
+                contents,
+                tools: to_api_tools(tools),
+                generation_config: GenerationConfig::from_params(params),
+            };
+            self.generate(model, &body).await
+        })
+    }
+
+    /// JSON mode (`responseMimeType`) plus the schema as a system instruction; Gemini's own
+    /// `responseSchema` only accepts an OpenAPI subset of JSON Schema.
+    fn chat_json<'a>(
+        &'a self,
+        messages: &'a [Message],
+        model: &'a str,
+        schema: &'a Value,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let (system_instruction, contents) = to_contents(messages);
+            let mut parts = vec![text_part(&structured::schema_instruction(schema))];
+            parts.extend(system_instruction.map(|s| s.parts).unwrap_or_default());
+            let mut generation_config = GenerationConfig::from_params(&GenParams {
+                temperature: Some(0.0),
+                ..GenParams::default()
+            });
+            generation_config.response_mime_type = Some("application/json");
+            let body = GenerateRequest {
+                system_instruction: Some(Content {
+                    role: String::new(),
+                    parts,
+                }),
+                contents,
+                tools: Vec::new(),
+                generation_config,
+            };
+            self.generate(model, &body).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LlmConfig;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn msg(role: Role, content: &str) -> Message {
+        Message {
+            role,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn history_maps_to_gemini_contents() {
+        let mut assistant = msg(Role::Assistant, "Checking.");
+        assistant.tool_calls = Some(vec![ToolCall {
+            id: "call_1".into(),
+            type_: "function".into(),
+            function: ToolCallFunction {
+                name: "read_file".into(),
+                arguments: r#"{"path":"a.md"}"#.into(),
+            },
+        }]);
+        let mut result = msg(Role::Tool, "file body");
+        result.tool_call_id = Some("call_1".into());
+        let history = vec![
+            msg(Role::System, "You are iCrab."),
+            msg(Role::System, "Current chat: 1."),
+            msg(Role::User, "read a"),
+            assistant,
+            result,
+        ];
+
+        let (system, contents) = to_contents(&history);
+        let system = serde_json::to_value(system.unwrap()).unwrap();
+        assert_eq!(system["parts"][1]["text"], "Current chat: 1.");
+        let json = serde_json::to_value(&contents).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 3);
+        assert_eq!(json[0]["role"], "user");
+        assert_eq!(json[1]["role"], "model");
+        assert_eq!(json[1]["parts"][0]["text"], "Checking.");
+        assert_eq!(json[1]["parts"][1]["functionCall"]["args"]["path"], "a.md");
+        assert!(json[1]["parts"][1]["thoughtSignature"].is_string());
+        assert_eq!(json[2]["role"], "user");
+        let response = &json[2]["parts"][0]["functionResponse"];
+        assert_eq!(response["name"], "read_file");
+        assert_eq!(response["response"]["content"], "file body");
+    }
+
+    #[test]
+    fn tool_schemas_are_reduced_to_the_supported_subset() {
+        let schema = json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {"q": {"type": "object", "additionalProperties": true}}
+        });
+        let out = to_gemini_schema(&schema).unwrap();
+        assert!(out.get("additionalProperties").is_none());
+        assert!(out["properties"]["q"].get("additionalProperties").is_none());
+        assert_eq!(
+            to_gemini_schema(&json!({"type": "object", "properties": {}})),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_parses_function_call_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/models/gemini-2.5-flash:generateContent"))
+            .and(header("x-goog-api-key", "k"))
+            .and(body_partial_json(json!({
+                "systemInstruction": {"parts": [{"text": "sys"}]},
+                "contents": [{"role": "user", "parts": [{"text": "hi"}]}],
+                "tools": [{"functionDeclarations": [{"name": "read_file"}]}],
+                "generationConfig": {"maxOutputTokens": 256}
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "candidates": [{
+                    "content": {"role": "model", "parts": [
+                        {"text": "thinking...", "thought": true},
+                        {"text": "Reading."},
+                        {"functionCall": {"name": "read_file", "args": {"path": "x"}},
+                         "thoughtSignature": "abc"}
+                    ]},
+                    "finishReason": "STOP"
+                }],
+                "usageMetadata": {
+                    "promptTokenCount": 12,
+                    "candidatesTokenCount": 3,
+                    "totalTokenCount": 15
+                }
+            })))
+            .mount(&server)
+            .await;
+        let cfg = Config {
+            llm: Some(LlmConfig {
+                provider: Some("gemini".into()),
+                api_base: Some(server.uri()),
+                api_key: Some("k".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let provider = GeminiProvider::from_config(&cfg).unwrap();
+        let tools = vec![ToolDef::function(
+            "read_file".into(),
+            "Read".into(),
+            json!({"type": "object", "properties": {"path": {"type": "string"}}}),
+        )];
+        let params = GenParams {
+            max_tokens: Some(256),
+            ..Default::default()
+        };
+        let r = provider
+            .chat_with_params(
+                &[msg(Role::System, "sys"), msg(Role::User, "hi")],
+                &tools,
+                "models/gemini-2.5-flash",
+                &params,
+            )
+            .await
+            .unwrap();
+        assert_eq!(r.content, "Reading.");
+        assert_eq!(r.finish_reason, "tool_calls");
+        assert_eq!(r.tool_calls[0].function.name, "read_file");
+        assert_eq!(r.tool_calls[0].function.arguments, r#"{"path":"x"}"#);
+        assert_eq!(r.usage.unwrap().total_tokens, Some(15));
+    }
+}
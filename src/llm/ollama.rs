@@ -0,0 +1,519 @@
+//! Ollama provider (`[llm] provider = "ollama"`): native `/api/chat` on a LAN or local server.
+//!
+//! Ollama passes tool-call arguments as JSON objects (not strings), has no tool-call ids and
+//! names tool results with `tool_name`; this module converts to and from the common shape.
+//! Streaming uses Ollama's NDJSON chunks. No API key is needed.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use super::{
+    Endpoint, GenParams, ImagePart, LlmError, LlmProvider, LlmResponse, Message, Role, ToolCall,
+    ToolCallFunction, ToolDef, UsageInfo, format_reqwest_error, read_lines,
+};
+use crate::config::Config;
+use crate::tools::registry::BoxFuture;
+
+const DEFAULT_API_BASE: &str = "http://localhost:11434";
+
+// --- Request/response (raw API shape for serde) ---
+
+#[derive(Serialize)]
+struct OllamaRequest<'a> {
+    model: &'a str,
+    messages: Vec<OllamaMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<&'a [ToolDef]>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Options::is_empty")]
+    options: Options<'a>,
+}
+
+/// Sampling options; Ollama calls max_tokens `num_predict`.
+#[derive(Serialize)]
+struct Options<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop: &'a [String],
+}
+
+impl<'a> Options<'a> {
+    fn from_params(params: &'a GenParams) -> Self {
+        Self {
+            temperature: params.temperature,
+            num_predict: params.max_tokens,
+            top_p: params.top_p,
+            stop: &params.stop,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.temperature.is_none()
+            && self.num_predict.is_none()
+            && self.top_p.is_none()
+            && self.stop.is_empty()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OllamaMessage {
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OllamaToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+    /// Base64 images for vision models (URLs are not supported by Ollama).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaToolCall {
+    function: OllamaFunction,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaFunction {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+/// Full response, or one NDJSON chunk when streaming (`done` on the last).
+#[derive(Deserialize)]
+struct OllamaResponse {
+    #[serde(default)]
+    message: Option<OllamaMessage>,
+    #[serde(default)]
+    done: bool,
+    done_reason: Option<String>,
+    prompt_eval_count: Option<u64>,
+    eval_count: Option<u64>,
+    error: Option<String>,
+}
+
+/// Convert history to Ollama messages. Tool results carry the tool's name, looked up from the
+/// assistant message that issued the call.
+fn to_ollama_messages(messages: &[Message]) -> Vec<OllamaMessage> {
+    let mut names: HashMap<&str, &str> = HashMap::new();
+    messages
+        .iter()
+        .map(|m| {
+            let role = match m.role {
+                Role::System => "system",
+                Role::User => "user",
+                Role::Assistant => "assistant",
+                Role::Tool => "tool",
+            };
+            let tool_calls = m
+                .tool_calls
+                .iter()
+                .flatten()
+                .map(|tc| {
+                    names.insert(&tc.id, &tc.function.name);
+                    OllamaToolCall {
+                        function: OllamaFunction {
+                            name: tc.function.name.clone(),
+                            arguments: serde_json::from_str(&tc.function.arguments)
+                                .unwrap_or_else(|_| serde_json::json!({})),
+                        },
+                    }
+                })
+                .collect();
+            let tool_name = m
+                .tool_call_id
+                .as_deref()
+                .and_then(|id| names.get(id))
+                .map(|n| n.to_string());
+            let images = m
+                .images
+                .iter()
+                .filter_map(|img| match img {
+                    ImagePart::Base64 { data, .. } => Some(data.clone()),
+                    ImagePart::Url(_) => None,
+                })
+                .collect();
+            OllamaMessage {
+                role: role.to_string(),
+                content: m.content.clone(),
+                tool_calls,
+                tool_name,
+                images,
+            }
+        })
+        .collect()
+}
+
+/// Tool calls in the common shape; Ollama has no ids, so they are generated.
+fn to_tool_calls(calls: Vec<OllamaToolCall>) -> Vec<ToolCall> {
+    calls
+        .into_iter()
+        .map(|c| ToolCall {
+            id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+            type_: "function".to_string(),
+            function: ToolCallFunction {
+                name: c.function.name,
+                arguments: match c.function.arguments {
+                    Value::Null => "{}".to_string(),
+                    Value::String(s) => s,
+                    v => v.to_string(),
+                },
+            },
+        })
+        .collect()
+}
+
+fn usage(r: &OllamaResponse) -> Option<UsageInfo> {
+    if r.prompt_eval_count.is_none() && r.eval_count.is_none() {
+        return None;
+    }
+    Some(UsageInfo {
+        prompt_tokens: r.prompt_eval_count,
+        completion_tokens: r.eval_count,
+        total_tokens: Some(r.prompt_eval_count.unwrap_or(0) + r.eval_count.unwrap_or(0)),
+    })
+}
+
+/// Ollama reports "stop" even when it returned tool calls; normalize to OpenAI terms.
+fn finish_reason(done_reason: Option<&str>, has_tool_calls: bool) -> String {
+    if has_tool_calls {
+        "tool_calls".to_string()
+    } else {
+        done_reason.unwrap_or("").to_string()
+    }
+}
+
+#[derive(Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    #[serde(default)]
+    embeddings: Vec<Vec<f32>>,
+    error: Option<String>,
+}
+
+// --- Provider ---
+
+/// Ollama `/api/chat` provider.
+pub struct OllamaProvider {
+    api_base: String,
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl OllamaProvider {
+    /// Build provider from validated config; default api_base is `http://localhost:11434`.
+    pub fn from_config(cfg: &Config) -> Result<Self, LlmError> {
+        let Endpoint {
+            api_base,
+            api_key,
+            client,
+        } = Endpoint::from_config(cfg, DEFAULT_API_BASE)?;
+        Ok(Self {
+            api_base,
+            api_key,
+            client,
+        })
+    }
+
+    /// POST a JSON request to `path` (e.g. `/api/chat`); returns the successful response.
+    async fn post(&self, path: &str, body: &impl Serialize) -> Result<reqwest::Response, LlmError> {
+        let url = format!("{}{}", self.api_base, path);
+        let mut req = self.client.post(&url).json(body);
+        // Only needed when the server sits behind an authenticating proxy.
+        if !self.api_key.is_empty() {
+            req = req.header("Authorization", format!("Bearer {}", self.api_key));
+        }
+        let res = req
+            .send()
+            .await
+            .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
+        let status = res.status();
+        if !status.is_success() {
+            let text = res.text().await.unwrap_or_default();
+            return Err(LlmError::Http(format!("{} {}", status, text)));
+        }
+        Ok(res)
+    }
+}
+
+fn tools_param(tools: &[ToolDef]) -> Option<&[ToolDef]> {
+    (!tools.is_empty()).then_some(tools)
+}
+
+impl LlmProvider for OllamaProvider {
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let body = OllamaRequest {
+                model,
+                messages: to_ollama_messages(messages),
+                tools: tools_param(tools),
+                stream: false,
+                options: Options::from_params(params),
+            };
+            let text = self
+                .post("/api/chat", &body)
+                .await?
+                .text()
+                .await
+                .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
+            let parsed: OllamaResponse =
+                serde_json::from_str(&text).map_err(|e| LlmError::Parse(e.to_string()))?;
+            if let Some(err) = parsed.error {
+                return Err(LlmError::Http(err));
+            }
+            let usage = usage(&parsed);
+            let message = parsed.message.unwrap_or_default();
+            let tool_calls = to_tool_calls(message.tool_calls);
+            Ok(LlmResponse {
+                content: message.content,
+                finish_reason: finish_reason(parsed.done_reason.as_deref(), !tool_calls.is_empty()),
+                tool_calls,
+                usage,
+            })
+        })
+    }
+
+    /// NDJSON streaming: each line carries a content piece; tool calls arrive whole.
+    fn chat_stream<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+        deltas: &'a mpsc::UnboundedSender<String>,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let body = OllamaRequest {
+                model,
+                messages: to_ollama_messages(messages),
+                tools: tools_param(tools),
+                stream: true,
+                options: Options::from_params(params),
+            };
+            let mut res = self.post("/api/chat", &body).await?;
+            let mut out = LlmResponse {
+                content: String::new(),
+                tool_calls: Vec::new(),
+                finish_reason: String::new(),
+                usage: None,
+            };
+            read_lines(&mut res, |line| {
+                if line.is_empty() {
+                    return Ok(true);
+                }
+                let chunk: OllamaResponse =
+                    serde_json::from_str(line).map_err(|e| LlmError::Parse(e.to_string()))?;
+                if let Some(err) = chunk.error {
+                    return Err(LlmError::Http(err));
+                }
+                if let Some(msg) = &chunk.message
+                    && !msg.content.is_empty()
+                {
+                    out.content.push_str(&msg.content);
+                    let _ = deltas.send(msg.content.clone());
+                }
+                if !chunk.done {
+                    if let Some(msg) = chunk.message {
+                        out.tool_calls.extend(to_tool_calls(msg.tool_calls));
+                    }
+                    return Ok(true);
+                }
+                out.usage = usage(&chunk);
+                if let Some(msg) = chunk.message {
+                    out.tool_calls.extend(to_tool_calls(msg.tool_calls));
+                }
+                out.finish_reason =
+                    finish_reason(chunk.done_reason.as_deref(), !out.tool_calls.is_empty());
+                Ok(false)
+            })
+            .await?;
+            Ok(out)
+        })
+    }
+
+    /// Native `/api/embed` (batch input).
+    fn embed<'a>(
+        &'a self,
+        texts: &'a [String],
+        model: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, LlmError>> {
+        Box::pin(async move {
+            let body = EmbedRequest {
+                model,
+                input: texts,
+            };
+            let text = self
+                .post("/api/embed", &body)
+                .await?
+                .text()
+                .await
+                .map_err(|e| LlmError::Http(format_reqwest_error(&e)))?;
+            let parsed: EmbedResponse =
+                serde_json::from_str(&text).map_err(|e| LlmError::Parse(e.to_string()))?;
+            if let Some(err) = parsed.error {
+                return Err(LlmError::Http(err));
+            }
+            if parsed.embeddings.len() != texts.len() {
+                return Err(LlmError::Parse(format!(
+                    "expected {} embeddings, got {}",
+                    texts.len(),
+                    parsed.embeddings.len()
+                )));
+            }
+            Ok(parsed.embeddings)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LlmConfig;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn provider(api_base: &str) -> OllamaProvider {
+        let cfg = Config {
+            llm: Some(LlmConfig {
+                provider: Some("ollama".into()),
+                api_base: Some(api_base.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        OllamaProvider::from_config(&cfg).expect("no api key needed")
+    }
+
+    #[test]
+    fn history_uses_object_arguments_and_tool_names() {
+        let history = vec![
+            Message {
+                role: Role::Assistant,
+                content: String::new(),
+                tool_call_id: None,
+                tool_calls: Some(vec![ToolCall {
+                    id: "call_1".into(),
+                    type_: "function".into(),
+                    function: ToolCallFunction {
+                        name: "read_file".into(),
+                        arguments: r#"{"path":"a.md"}"#.into(),
+                    },
+                }]),
+                images: Vec::new(),
+            },
+            Message {
+                role: Role::Tool,
+                content: "A".into(),
+                tool_call_id: Some("call_1".into()),
+                tool_calls: None,
+                images: Vec::new(),
+            },
+        ];
+        let json = serde_json::to_value(to_ollama_messages(&history)).unwrap();
+        assert_eq!(
+            json[0]["tool_calls"][0]["function"]["arguments"]["path"],
+            "a.md"
+        );
+        assert_eq!(json[1]["role"], "tool");
+        assert_eq!(json[1]["tool_name"], "read_file");
+    }
+
+    #[tokio::test]
+    async fn chat_maps_tool_calls_and_usage() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(serde_json::json!({
+                "stream": false,
+                "options": {"temperature": 0.2}
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{"function": {"name": "list_dir", "arguments": {"path": "."}}}]
+                },
+                "done": true,
+                "done_reason": "stop",
+                "prompt_eval_count": 20,
+                "eval_count": 4
+            })))
+            .mount(&server)
+            .await;
+        let msgs = [Message {
+            role: Role::User,
+            content: "ls".into(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        }];
+        let r = provider(&server.uri())
+            .chat_with_params(
+                &msgs,
+                &[],
+                "llama3.1",
+                &GenParams {
+                    temperature: Some(0.2),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(r.finish_reason, "tool_calls");
+        assert_eq!(r.tool_calls[0].function.name, "list_dir");
+        assert_eq!(r.tool_calls[0].function.arguments, r#"{"path":"."}"#);
+        assert!(r.tool_calls[0].id.starts_with("call_"));
+        assert_eq!(r.usage.unwrap().total_tokens, Some(24));
+    }
+
+    #[tokio::test]
+    async fn chat_stream_reads_ndjson_chunks() {
+        let server = MockServer::start().await;
+        let body = concat!(
+            r#"{"message":{"role":"assistant","content":"Hi "},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":"there"},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","eval_count":2}"#,
+            "\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(serde_json::json!({"stream": true})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(&server)
+            .await;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let r = provider(&server.uri())
+            .chat_stream(&[], &[], "llama3.1", &crate::llm::DEFAULT_PARAMS, &tx)
+            .await
+            .unwrap();
+        assert_eq!(r.content, "Hi there");
+        assert_eq!(r.finish_reason, "stop");
+        drop(tx);
+        assert_eq!(rx.recv().await.as_deref(), Some("Hi "));
+        assert_eq!(rx.recv().await.as_deref(), Some("there"));
+    }
+}
@@ -0,0 +1,172 @@
+//! Global request rate limit (`[llm] max-requests-per-minute`): [`RateLimitedProvider`] holds a
+//! token bucket shared by everything that calls the LLM (agent turns, heartbeat, cron agent
+//! jobs, subagents), so a runaway job or a burst of subagents cannot exceed the limit.
+//!
+//! The bucket starts full (a burst of N requests is allowed) and refills at N per minute.
+//! Callers over the limit wait for a token, in arrival order; they never get an error.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{Mutex, mpsc};
+use tokio::time::Instant;
+
+use super::{GenParams, LlmError, LlmProvider, LlmResponse, Message, ToolDef};
+use crate::tools::registry::BoxFuture;
+
+/// Token bucket: `capacity` tokens, refilled continuously at `capacity` per minute.
+pub struct TokenBucket {
+    capacity: f64,
+    per_sec: f64,
+    /// (available tokens, last refill). The async mutex queues waiters fairly.
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    pub fn per_minute(n: u32) -> Self {
+        let capacity = f64::from(n.max(1));
+        Self {
+            capacity,
+            per_sec: capacity / 60.0,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// Take one token, sleeping until one is available. The lock is held while sleeping so
+    /// waiters are served first come, first served.
+    pub async fn acquire(&self) {
+        let mut state = self.state.lock().await;
+        let (tokens, last) = &mut *state;
+        let now = Instant::now();
+        *tokens =
+            (*tokens + now.duration_since(*last).as_secs_f64() * self.per_sec).min(self.capacity);
+        *last = now;
+        if *tokens < 1.0 {
+            let wait = Duration::from_secs_f64((1.0 - *tokens) / self.per_sec);
+            tokio::time::sleep(wait).await;
+            *tokens = 1.0;
+            *last = Instant::now();
+        }
+        *tokens -= 1.0;
+    }
+}
+
+/// Provider wrapper that takes a token from the shared bucket before every request.
+pub struct RateLimitedProvider {
+    inner: Arc<dyn LlmProvider>,
+    bucket: TokenBucket,
+}
+
+impl RateLimitedProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, requests_per_minute: u32) -> Self {
+        Self {
+            inner,
+            bucket: TokenBucket::per_minute(requests_per_minute),
+        }
+    }
+}
+
+impl LlmProvider for RateLimitedProvider {
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            self.bucket.acquire().await;
+            self.inner
+                .chat_with_params(messages, tools, model, params)
+                .await
+        })
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+        deltas: &'a mpsc::UnboundedSender<String>,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            self.bucket.acquire().await;
+            self.inner
+                .chat_stream(messages, tools, model, params, deltas)
+                .await
+        })
+    }
+
+    fn chat_json<'a>(
+        &'a self,
+        messages: &'a [Message],
+        model: &'a str,
+        schema: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            self.bucket.acquire().await;
+            self.inner.chat_json(messages, model, schema).await
+        })
+    }
+
+    fn embed<'a>(
+        &'a self,
+        texts: &'a [String],
+        model: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, LlmError>> {
+        Box::pin(async move {
+            self.bucket.acquire().await;
+            self.inner.embed(texts, model).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn bucket_allows_burst_then_waits_for_refill() {
+        let bucket = TokenBucket::per_minute(6); // one token every 10s
+        let start = Instant::now();
+        for _ in 0..6 {
+            bucket.acquire().await;
+        }
+        assert!(
+            start.elapsed() < Duration::from_millis(1),
+            "burst is immediate"
+        );
+
+        bucket.acquire().await;
+        let waited = start.elapsed();
+        assert!(
+            waited >= Duration::from_secs(10) && waited < Duration::from_secs(11),
+            "{waited:?}"
+        );
+        bucket.acquire().await;
+        assert!(start.elapsed() >= Duration::from_secs(20));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_callers_share_the_bucket() {
+        let bucket = Arc::new(TokenBucket::per_minute(2)); // one token every 30s
+        let start = Instant::now();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let bucket = Arc::clone(&bucket);
+                tokio::spawn(async move {
+                    bucket.acquire().await;
+                    Instant::now()
+                })
+            })
+            .collect();
+        let mut done = Vec::new();
+        for h in handles {
+            done.push(h.await.unwrap().duration_since(start));
+        }
+        done.sort();
+        assert!(done[1] < Duration::from_secs(1));
+        assert!(done[3] >= Duration::from_secs(60), "{done:?}");
+    }
+}
@@ -0,0 +1,306 @@
+//! Record and replay LLM traffic (`[llm] replay = "record" | "replay"`).
+//!
+//! [`ReplayProvider::record`] wraps a live provider and appends every request/response pair to
+//! a JSONL file; [`ReplayProvider::replay`] answers from such a file without any network, for
+//! deterministic tests and offline sessions. Requests are matched by a hash of model, tools and
+//! the non-system messages (the system prompt carries the clock, so it is left out). A request
+//! recorded several times is answered with its responses in recorded order.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use super::{GenParams, LlmError, LlmProvider, LlmResponse, Message, Role, ToolDef};
+use crate::tools::registry::BoxFuture;
+
+/// One recorded exchange (a line of the replay file).
+#[derive(Debug, Serialize, Deserialize)]
+struct Recording {
+    key: String,
+    model: String,
+    response: LlmResponse,
+}
+
+/// Replay key: hex hash of model, tools and every message except system prompts.
+fn replay_key(messages: &[Message], tools: &[ToolDef], model: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    model.hash(&mut hasher);
+    let conversation: Vec<&Message> = messages.iter().filter(|m| m.role != Role::System).collect();
+    serde_json::to_string(&conversation)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    serde_json::to_string(tools)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+enum Mode {
+    Record {
+        inner: Arc<dyn LlmProvider>,
+        /// Serializes appends across concurrent calls.
+        lock: Arc<Mutex<()>>,
+    },
+    Replay {
+        recorded: Mutex<HashMap<String, VecDeque<LlmResponse>>>,
+    },
+}
+
+/// Provider that records live traffic to, or replays it from, a JSONL file.
+pub struct ReplayProvider {
+    path: PathBuf,
+    mode: Mode,
+}
+
+impl ReplayProvider {
+    /// Forward every call to `inner` and append the exchange to `path`.
+    pub fn record(inner: Arc<dyn LlmProvider>, path: PathBuf) -> Self {
+        Self {
+            path,
+            mode: Mode::Record {
+                inner,
+                lock: Arc::new(Mutex::new(())),
+            },
+        }
+    }
+
+    /// Answer calls from the exchanges recorded in `path`.
+    pub fn replay(path: &Path) -> Result<Self, LlmError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| LlmError::Config(format!("replay file {}: {}", path.display(), e)))?;
+        let mut recorded: HashMap<String, VecDeque<LlmResponse>> = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let r: Recording = serde_json::from_str(line).map_err(|e| {
+                LlmError::Parse(format!(
+                    "replay file {} line {}: {}",
+                    path.display(),
+                    i + 1,
+                    e
+                ))
+            })?;
+            recorded.entry(r.key).or_default().push_back(r.response);
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            mode: Mode::Replay {
+                recorded: Mutex::new(recorded),
+            },
+        })
+    }
+
+    /// Next recorded response for `key`; the last one is reused once the queue runs dry.
+    fn next_recorded(
+        recorded: &Mutex<HashMap<String, VecDeque<LlmResponse>>>,
+        key: &str,
+    ) -> Option<LlmResponse> {
+        let mut recorded = recorded.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = recorded.get_mut(key)?;
+        if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        }
+    }
+
+    /// Append an exchange to the file. Failures are logged, never fatal.
+    async fn append(&self, lock: &Arc<Mutex<()>>, recording: Recording) {
+        let path = self.path.clone();
+        let lock = Arc::clone(lock);
+        let res = tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+            let line = serde_json::to_string(&recording)?;
+            let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?;
+            writeln!(file, "{line}")
+        })
+        .await;
+        match res {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("llm replay record: {}", e),
+            Err(e) => eprintln!("llm replay task error: {}", e),
+        }
+    }
+
+    /// Shared by all entry points: replay, or run `live` and record its result.
+    async fn call<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        live: impl FnOnce(&'a Arc<dyn LlmProvider>) -> BoxFuture<'a, Result<LlmResponse, LlmError>>,
+    ) -> Result<LlmResponse, LlmError> {
+        let key = replay_key(messages, tools, model);
+        match &self.mode {
+            Mode::Replay { recorded } => Self::next_recorded(recorded, &key).ok_or_else(|| {
+                LlmError::Config(format!(
+                    "no recorded response for request {} in {}",
+                    key,
+                    self.path.display()
+                ))
+            }),
+            Mode::Record { inner, lock } => {
+                let response = live(inner).await?;
+                let recording = Recording {
+                    key,
+                    model: model.to_string(),
+                    response: response.clone(),
+                };
+                self.append(lock, recording).await;
+                Ok(response)
+            }
+        }
+    }
+}
+
+impl LlmProvider for ReplayProvider {
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(self.call(messages, tools, model, move |inner| {
+            inner.chat_with_params(messages, tools, model, params)
+        }))
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+        deltas: &'a mpsc::UnboundedSender<String>,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let replaying = matches!(self.mode, Mode::Replay { .. });
+            let response = self
+                .call(messages, tools, model, move |inner| {
+                    inner.chat_stream(messages, tools, model, params, deltas)
+                })
+                .await?;
+            // A replayed reply arrives in one piece, like a provider without streaming.
+            if replaying && !response.content.is_empty() {
+                let _ = deltas.send(response.content.clone());
+            }
+            Ok(response)
+        })
+    }
+
+    fn chat_json<'a>(
+        &'a self,
+        messages: &'a [Message],
+        model: &'a str,
+        schema: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(self.call(messages, &[], model, move |inner| {
+            inner.chat_json(messages, model, schema)
+        }))
+    }
+
+    fn embed<'a>(
+        &'a self,
+        texts: &'a [String],
+        model: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, LlmError>> {
+        // Embeddings are not recorded: live in record mode, unavailable when replaying.
+        match &self.mode {
+            Mode::Record { inner, .. } => inner.embed(texts, model),
+            Mode::Replay { .. } => Box::pin(async {
+                Err(LlmError::Config(
+                    "embeddings are not available in replay mode".into(),
+                ))
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts calls and echoes the call number.
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    impl LlmProvider for Counting {
+        fn chat_with_params<'a>(
+            &'a self,
+            _messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            let n = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Box::pin(async move {
+                Ok(LlmResponse {
+                    content: format!("reply {n}"),
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".into(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    fn msg(role: Role, content: &str) -> Message {
+        Message {
+            role,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_responses_in_order() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("replay.jsonl");
+        let inner = Arc::new(Counting::default());
+        let recorder = ReplayProvider::record(inner.clone(), path.clone());
+        let hi = [msg(Role::System, "time 10:00"), msg(Role::User, "hi")];
+        let bye = [msg(Role::System, "time 10:01"), msg(Role::User, "bye")];
+        for m in [&hi[..], &bye[..], &hi[..]] {
+            recorder.chat(m, &[], "m").await.unwrap();
+        }
+        assert_eq!(inner.0.load(Ordering::SeqCst), 3);
+
+        let player = ReplayProvider::replay(&path).unwrap();
+        // The system prompt differs from the recording; only the conversation matters.
+        let hi_later = [msg(Role::System, "time 18:30"), msg(Role::User, "hi")];
+        assert_eq!(
+            player.chat(&hi_later, &[], "m").await.unwrap().content,
+            "reply 1"
+        );
+        assert_eq!(
+            player.chat(&bye, &[], "m").await.unwrap().content,
+            "reply 2"
+        );
+        assert_eq!(player.chat(&hi, &[], "m").await.unwrap().content, "reply 3");
+        assert_eq!(
+            player.chat(&hi, &[], "m").await.unwrap().content,
+            "reply 3",
+            "last response repeats"
+        );
+        let miss = player.chat(&hi, &[], "other-model").await.unwrap_err();
+        assert!(miss.to_string().contains("no recorded response"), "{miss}");
+        assert_eq!(inner.0.load(Ordering::SeqCst), 3, "replay never goes live");
+    }
+}
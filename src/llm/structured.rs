@@ -0,0 +1,224 @@
+//! Structured output: [`chat_structured`](dyn LlmProvider::chat_structured) asks for JSON
+//! matching a schema via [`LlmProvider::chat_json`], deserializes it into `T` and, when the
+//! reply is malformed, tells the model what was wrong and asks again.
+//!
+//! For internal calls that need data rather than prose (e.g. extracting memories, parsing a
+//! natural-language schedule).
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::{LlmError, LlmProvider, Message, Role};
+
+/// Total tries before giving up on malformed output.
+const MAX_STRUCTURED_ATTEMPTS: usize = 3;
+
+/// System prompt text for providers without native schema support.
+pub(crate) fn schema_instruction(schema: &Value) -> String {
+    format!(
+        "Reply with a single JSON value that matches this JSON Schema, and nothing else \
+         (no prose, no code fences):\n{}",
+        schema
+    )
+}
+
+/// The JSON part of a reply: code fences and text around the outermost object/array removed.
+fn extract_json(content: &str) -> &str {
+    let t = content.trim();
+    let t = t
+        .strip_prefix("```json")
+        .or_else(|| t.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .map(str::trim)
+        .unwrap_or(t);
+    let start = t.find(['{', '[']);
+    let end = t.rfind(['}', ']']);
+    match (start, end) {
+        (Some(s), Some(e)) if s < e => &t[s..=e],
+        _ => t,
+    }
+}
+
+/// Keys listed under `required` that `value` (an object) lacks.
+fn missing_required(schema: &Value, value: &Value) -> Vec<String> {
+    let Some(required) = schema.get("required").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    required
+        .iter()
+        .filter_map(Value::as_str)
+        .filter(|k| value.get(*k).is_none())
+        .map(String::from)
+        .collect()
+}
+
+impl dyn LlmProvider + '_ {
+    /// Ask for JSON matching `schema` and deserialize it into `T`. Invalid JSON, missing
+    /// `required` keys or a shape `T` rejects are fed back to the model, up to three tries.
+    pub async fn chat_structured<T: DeserializeOwned>(
+        &self,
+        messages: &[Message],
+        model: &str,
+        schema: &Value,
+    ) -> Result<T, LlmError> {
+        let mut conversation = messages.to_vec();
+        let mut last_error = String::new();
+        for _ in 0..MAX_STRUCTURED_ATTEMPTS {
+            let response = self.chat_json(&conversation, model, schema).await?;
+            let problem = match serde_json::from_str::<Value>(extract_json(&response.content)) {
+                Err(e) => format!("invalid JSON: {e}"),
+                Ok(value) => {
+                    let missing = missing_required(schema, &value);
+                    if !missing.is_empty() {
+                        format!("missing required keys: {}", missing.join(", "))
+                    } else {
+                        match serde_json::from_value::<T>(value) {
+                            Ok(parsed) => return Ok(parsed),
+                            Err(e) => format!("does not match the schema: {e}"),
+                        }
+                    }
+                }
+            };
+            conversation.push(Message {
+                role: Role::Assistant,
+                content: response.content,
+                tool_call_id: None,
+                tool_calls: None,
+                images: Vec::new(),
+            });
+            conversation.push(Message {
+                role: Role::User,
+                content: format!(
+                    "That reply was rejected ({problem}). Reply again with only the JSON value."
+                ),
+                tool_call_id: None,
+                tool_calls: None,
+                images: Vec::new(),
+            });
+            last_error = problem;
+        }
+        Err(LlmError::Parse(format!(
+            "structured output after {MAX_STRUCTURED_ATTEMPTS} attempts: {last_error}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{GenParams, LlmResponse, ToolDef};
+    use crate::tools::registry::BoxFuture;
+    use serde::Deserialize;
+    use std::sync::Mutex;
+
+    /// Replies with the scripted contents in order and remembers the last request.
+    struct Scripted {
+        replies: Mutex<Vec<&'static str>>,
+        last_request: Mutex<Vec<Message>>,
+    }
+
+    impl Scripted {
+        fn new(replies: &[&'static str]) -> Self {
+            Self {
+                replies: Mutex::new(replies.iter().rev().copied().collect()),
+                last_request: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl LlmProvider for Scripted {
+        fn chat_with_params<'a>(
+            &'a self,
+            messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            *self.last_request.lock().unwrap() = messages.to_vec();
+            let content = self.replies.lock().unwrap().pop().unwrap_or("").to_string();
+            Box::pin(async move {
+                Ok(LlmResponse {
+                    content,
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".into(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Reminder {
+        text: String,
+        minutes: u32,
+    }
+
+    fn schema() -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "text": {"type": "string"},
+                "minutes": {"type": "integer"}
+            },
+            "required": ["text", "minutes"]
+        })
+    }
+
+    #[test]
+    fn extract_json_strips_fences_and_prose() {
+        assert_eq!(extract_json("```json\n{\"a\":1}\n```"), "{\"a\":1}");
+        assert_eq!(
+            extract_json("Sure! {\"a\":[1]} Hope it helps"),
+            "{\"a\":[1]}"
+        );
+        assert_eq!(extract_json("[1, 2]"), "[1, 2]");
+    }
+
+    #[tokio::test]
+    async fn retries_malformed_output_with_feedback() {
+        let llm = Scripted::new(&[
+            "not json at all",
+            r#"{"text": "stretch"}"#,
+            "```json\n{\"text\": \"stretch\", \"minutes\": 30}\n```",
+        ]);
+        let user = Message {
+            role: Role::User,
+            content: "remind me to stretch in half an hour".into(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: Vec::new(),
+        };
+        let provider: &dyn LlmProvider = &llm;
+        let r: Reminder = provider
+            .chat_structured(&[user], "m", &schema())
+            .await
+            .unwrap();
+        assert_eq!(
+            r,
+            Reminder {
+                text: "stretch".into(),
+                minutes: 30
+            }
+        );
+        let last = llm.last_request.lock().unwrap();
+        assert_eq!(last[0].role, Role::System, "schema instruction first");
+        assert!(last[0].content.contains("\"required\""));
+        assert!(
+            last.last()
+                .unwrap()
+                .content
+                .contains("missing required keys: minutes")
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let llm = Scripted::new(&["nope", "still nope", "no"]);
+        let provider: &dyn LlmProvider = &llm;
+        let err = provider
+            .chat_structured::<Reminder>(&[], "m", &schema())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("after 3 attempts"), "{err}");
+    }
+}
@@ -0,0 +1,220 @@
+//! Token usage accounting: [`MeteredProvider`] wraps the configured provider and stores the
+//! `usage` of every response in `llm_usage`, with an estimated cost from `[llm]` prices.
+//!
+//! Calls are attributed to the [`UsageScope`] of the running task (chat and source), set by the
+//! agent entry points with [`scoped`]; anything outside a scope is recorded with empty labels.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use super::{GenParams, LlmError, LlmProvider, LlmResponse, Message, ToolDef};
+use crate::config::LlmConfig;
+use crate::memory::db::{BrainDb, LlmUsage};
+use crate::tools::registry::BoxFuture;
+
+tokio::task_local! {
+    static SCOPE: UsageScope;
+}
+
+/// Who an LLM call is billed to: the chat (session key) and what made the call
+/// ("telegram", "cron", "heartbeat", "subagent:<label>").
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageScope {
+    pub chat_id: String,
+    pub source: String,
+}
+
+/// Run `fut` with `scope` as the usage attribution for its LLM calls.
+pub async fn scoped<F: Future>(scope: UsageScope, fut: F) -> F::Output {
+    SCOPE.scope(scope, fut).await
+}
+
+/// The current task's scope (default when unscoped).
+pub fn current_scope() -> UsageScope {
+    SCOPE.try_with(Clone::clone).unwrap_or_default()
+}
+
+/// USD per million tokens, from `[llm] input-price` / `output-price`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pricing {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl Pricing {
+    /// None unless at least one price is set (a missing one counts as free).
+    pub fn from_config(llm: &LlmConfig) -> Option<Self> {
+        if llm.input_price.is_none() && llm.output_price.is_none() {
+            return None;
+        }
+        Some(Self {
+            input_per_mtok: llm.input_price.unwrap_or(0.0),
+            output_per_mtok: llm.output_price.unwrap_or(0.0),
+        })
+    }
+
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        (prompt_tokens as f64 * self.input_per_mtok
+            + completion_tokens as f64 * self.output_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+/// Provider wrapper that records each response's token usage in the brain DB.
+pub struct MeteredProvider {
+    inner: Arc<dyn LlmProvider>,
+    db: Arc<BrainDb>,
+    pricing: Option<Pricing>,
+}
+
+impl MeteredProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, db: Arc<BrainDb>, pricing: Option<Pricing>) -> Self {
+        Self { inner, db, pricing }
+    }
+
+    /// Store the response's usage under the current scope. Failures are logged, never fatal.
+    async fn record(&self, model: &str, response: &LlmResponse) {
+        let Some(usage) = &response.usage else {
+            return;
+        };
+        let scope = current_scope();
+        let prompt_tokens = usage.prompt_tokens.unwrap_or(0);
+        let completion_tokens = usage.completion_tokens.unwrap_or(0);
+        let row = LlmUsage {
+            chat_id: scope.chat_id,
+            source: scope.source,
+            model: model.to_string(),
+            prompt_tokens,
+            completion_tokens,
+            cost_usd: self
+                .pricing
+                .map(|p| p.cost(prompt_tokens, completion_tokens)),
+        };
+        let db = Arc::clone(&self.db);
+        match tokio::task::spawn_blocking(move || db.record_llm_usage(&row)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("llm usage: {}", e),
+            Err(e) => eprintln!("llm usage task error: {}", e),
+        }
+    }
+}
+
+impl LlmProvider for MeteredProvider {
+    fn chat_with_params<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let response = self
+                .inner
+                .chat_with_params(messages, tools, model, params)
+                .await?;
+            self.record(model, &response).await;
+            Ok(response)
+        })
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        messages: &'a [Message],
+        tools: &'a [ToolDef],
+        model: &'a str,
+        params: &'a GenParams,
+        deltas: &'a mpsc::UnboundedSender<String>,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let response = self
+                .inner
+                .chat_stream(messages, tools, model, params, deltas)
+                .await?;
+            self.record(model, &response).await;
+            Ok(response)
+        })
+    }
+
+    fn chat_json<'a>(
+        &'a self,
+        messages: &'a [Message],
+        model: &'a str,
+        schema: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+        Box::pin(async move {
+            let response = self.inner.chat_json(messages, model, schema).await?;
+            self.record(model, &response).await;
+            Ok(response)
+        })
+    }
+
+    fn embed<'a>(
+        &'a self,
+        texts: &'a [String],
+        model: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, LlmError>> {
+        self.inner.embed(texts, model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::UsageInfo;
+    use crate::memory::db::UsageGroupBy;
+
+    /// Returns a fixed response with usage.
+    struct Fixed;
+
+    impl LlmProvider for Fixed {
+        fn chat_with_params<'a>(
+            &'a self,
+            _messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            Box::pin(async {
+                Ok(LlmResponse {
+                    content: "ok".into(),
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".into(),
+                    usage: Some(UsageInfo {
+                        prompt_tokens: Some(1_000),
+                        completion_tokens: Some(500),
+                        total_tokens: Some(1_500),
+                    }),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn records_usage_under_task_scope() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let pricing = Pricing {
+            input_per_mtok: 2.0,
+            output_per_mtok: 10.0,
+        };
+        let llm = MeteredProvider::new(Arc::new(Fixed), Arc::clone(&db), Some(pricing));
+        let scope = UsageScope {
+            chat_id: "42".into(),
+            source: "subagent:research".into(),
+        };
+        scoped(scope, llm.chat(&[], &[], "m")).await.unwrap();
+        llm.chat(&[], &[], "m").await.unwrap();
+
+        let totals = db.llm_usage_totals(0, UsageGroupBy::Source).unwrap();
+        assert_eq!(totals.len(), 2);
+        let sub = totals
+            .iter()
+            .find(|t| t.key == "subagent:research")
+            .unwrap();
+        assert_eq!(sub.prompt_tokens, 1_000);
+        assert!((sub.cost_usd.unwrap() - 0.007).abs() < 1e-9);
+        assert!(totals.iter().any(|t| t.key.is_empty()), "unscoped call");
+    }
+}
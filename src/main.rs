@@ -5,6 +5,7 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
 
 use tokio::sync::mpsc;
 
@@ -14,19 +15,60 @@ use icrab::agent::subagent_manager::SubagentManager;
 use icrab::config;
 use icrab::cron_runner;
 use icrab::heartbeat;
-use icrab::llm::HttpProvider;
-use icrab::memory::db::BrainDb;
+use icrab::llm::cache::{self, CachedProvider};
+use icrab::llm::debug_log::{self, DebugLogProvider};
+use icrab::llm::rate_limit::RateLimitedProvider;
+use icrab::llm::replay::ReplayProvider;
+use icrab::llm::usage::{self, MeteredProvider, Pricing, UsageScope};
+use icrab::llm::{self, LlmProvider};
+use icrab::memory::backup;
+use icrab::memory::db::{BrainDb, RetentionPolicy};
+use icrab::memory::embeddings::VaultEmbedder;
 use icrab::memory::indexer::VaultIndexer;
+use icrab::memory::maintenance;
+use icrab::memory::retention::{self, Summarizer};
+use icrab::memory::watcher;
 use icrab::sync;
-use icrab::telegram::{self, OutboundMsg};
+use icrab::telegram::{self, Allowlist, InboundMsg, OutboundKind, OutboundMsg, TelegramShared};
 use icrab::tools;
-use icrab::tools::cron::{CronStore, CronTool};
+use icrab::tools::allowlist::AllowlistTool;
+use icrab::tools::backup::BackupTool;
+use icrab::tools::bookmark::BookmarkTool;
+use icrab::tools::calendar::{CalendarTool, Calendars};
+use icrab::tools::cron::{CronStore, CronTool, JobAction};
+use icrab::tools::daily_note::DailyNoteTool;
+use icrab::tools::entity::EntityTool;
+use icrab::tools::expense::ExpenseTool;
+use icrab::tools::export_chat::ExportChatTool;
+use icrab::tools::feeds::FeedsTool;
+use icrab::tools::forget::{self, ForgetTool};
+use icrab::tools::habits::HabitsTool;
+use icrab::tools::journal::JournalTool;
+use icrab::tools::memory::MemoryTool;
 use icrab::tools::message::MessageTool;
+use icrab::tools::ocr::OcrTool;
+use icrab::tools::qr::QrTool;
+use icrab::tools::remind_me::RemindMeTool;
+use icrab::tools::send_file::SendFileTool;
+use icrab::tools::send_photo::SendPhotoTool;
+use icrab::tools::session::SessionTool;
 use icrab::tools::spawn::SpawnTool;
 use icrab::tools::subagent::SubagentTool;
-use icrab::tools::{GitSyncTool, GrepDirTool, SearchChatTool, SearchVaultTool};
+use icrab::tools::summarize_url::SummarizeUrlTool;
+use icrab::tools::telegram_poll::TelegramPollTool;
+use icrab::tools::timer::TimerTool;
+use icrab::tools::translate::TranslateTool;
+use icrab::tools::usage::{self as usage_tool, UsagePeriod, UsageTool};
+use icrab::tools::vault_stats::VaultStatsTool;
+use icrab::tools::workout::WorkoutTool;
+use icrab::tools::{
+    GitSyncTool, GrepDirTool, SearchChatTool, SearchVaultTool, SemanticSearchVaultTool,
+};
+use icrab::voice::{self, VoiceClient};
 
 const SUBAGENT_MAX_ITERATIONS: u32 = 10;
+/// How often a streamed reply's Telegram message is edited (Telegram rate-limits edits).
+const STREAM_EDIT_INTERVAL: Duration = Duration::from_millis(1500);
 
 #[tokio::main]
 async fn main() {
@@ -41,8 +83,8 @@ async fn main() {
     };
     eprintln!("workspace: {}", cfg.workspace_path());
 
-    let llm = match HttpProvider::from_config(&cfg) {
-        Ok(p) => Arc::new(p),
+    let llm: Arc<dyn LlmProvider> = match llm::provider_from_config(&cfg) {
+        Ok(p) => p,
         Err(e) => {
             eprintln!("llm: {}", e);
             std::process::exit(1);
@@ -53,6 +95,17 @@ async fn main() {
         .as_ref()
         .and_then(|l| l.model.as_deref())
         .unwrap_or("google/gemini-3-flash-preview");
+    let stream_replies = cfg.llm.as_ref().and_then(|l| l.stream).unwrap_or(false);
+    let llm_cfg = cfg.llm.clone().unwrap_or_default();
+    let turn_opts = agent::TurnOptions {
+        context_budget: llm_cfg
+            .context_budget
+            .unwrap_or(agent::context::DEFAULT_CONTEXT_BUDGET),
+        agent: llm_cfg.agent.unwrap_or_default(),
+        summarize: llm_cfg.summarize.unwrap_or_default(),
+    };
+    let subagent_params = llm_cfg.subagent.unwrap_or_default();
+    let voice = VoiceClient::from_config(&cfg);
     let workspace = PathBuf::from(cfg.workspace_path());
     let restrict = cfg.restrict_to_workspace.unwrap_or(true);
     let timezone = cfg
@@ -62,7 +115,12 @@ async fn main() {
         .to_string();
 
     // Open the SQLite brain DB once at startup; shared across all message processing.
-    let db = match BrainDb::open(&workspace) {
+    // With [database] encryption-key the file is SQLCipher-encrypted.
+    let db_key = cfg
+        .database
+        .as_ref()
+        .and_then(|d| d.encryption_key.as_deref());
+    let db = match BrainDb::open_with_key(&workspace, db_key) {
         Ok(d) => Arc::new(d),
         Err(e) => {
             eprintln!("brain db: {}", e);
@@ -74,12 +132,74 @@ async fn main() {
         icrab::workspace::brain_db_path(&workspace).display()
     );
 
+    // Record live exchanges for later, or answer from a recording without the network.
+    let replay_file = llm_cfg
+        .replay_file
+        .as_deref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| icrab::workspace::llm_replay_file(&workspace));
+    let llm: Arc<dyn LlmProvider> = match llm_cfg.replay.as_deref().map(str::trim) {
+        Some("record") => {
+            eprintln!("llm replay: recording to {}", replay_file.display());
+            Arc::new(ReplayProvider::record(llm, replay_file))
+        }
+        Some("replay") => match ReplayProvider::replay(&replay_file) {
+            Ok(p) => {
+                eprintln!("llm replay: answering from {}", replay_file.display());
+                Arc::new(p)
+            }
+            Err(e) => {
+                eprintln!("llm replay: {}", e);
+                std::process::exit(1);
+            }
+        },
+        _ => llm,
+    };
+    // Opt-in: log each provider call (secrets redacted) for debugging tool-call loops.
+    let llm: Arc<dyn LlmProvider> = if llm_cfg.debug_log.unwrap_or(false) {
+        let dir = icrab::workspace::llm_log_dir(&workspace);
+        eprintln!("llm debug log: {}", dir.display());
+        Arc::new(DebugLogProvider::new(
+            llm,
+            dir,
+            cfg.secrets(),
+            llm_cfg
+                .debug_log_max_bytes
+                .unwrap_or(debug_log::DEFAULT_DEBUG_LOG_MAX_BYTES),
+        ))
+    } else {
+        llm
+    };
+    // Every LLM call (agent, subagents, summaries) records its token usage in llm_usage.
+    let llm: Arc<dyn LlmProvider> = Arc::new(MeteredProvider::new(
+        llm,
+        Arc::clone(&db),
+        cfg.llm.as_ref().and_then(Pricing::from_config),
+    ));
+    // One token bucket for every caller; cache hits below don't spend from it.
+    let llm: Arc<dyn LlmProvider> = match llm_cfg.max_requests_per_minute {
+        Some(n) if n > 0 => Arc::new(RateLimitedProvider::new(llm, n)),
+        _ => llm,
+    };
+    // Identical temperature-0 requests are answered from llm_cache (cache hits cost nothing).
+    let cache_ttl = cfg
+        .llm
+        .as_ref()
+        .and_then(|l| l.cache_ttl_secs)
+        .map(Duration::from_secs)
+        .unwrap_or(cache::DEFAULT_CACHE_TTL);
+    let llm: Arc<dyn LlmProvider> = Arc::new(CachedProvider::new(llm, Arc::clone(&db), cache_ttl));
+
+    // Semantic search: notes are embedded after each vault scan when an embedding model is set.
+    let embedder = VaultEmbedder::from_config(&cfg, Arc::clone(&db), Arc::clone(&llm));
+
     // Kick off the vault indexer in a background task so startup isn't blocked.
-    // The indexer walks the workspace and upserts any new/modified .md files
-    // into vault_index (FTS5 stays in sync via triggers).  Errors are logged
-    // but never fatal.
+    // The indexer walks the workspace and upserts any new/modified notes
+    // into vault_index (FTS5 stays in sync via triggers), then embeddings are
+    // refreshed.  Errors are logged but never fatal.
     {
-        let indexer = VaultIndexer::new(Arc::clone(&db));
+        let indexer = VaultIndexer::from_config(&cfg, Arc::clone(&db));
+        let embedder = embedder.clone();
         let ws_clone = workspace.clone();
         tokio::spawn(async move {
             match tokio::task::spawn_blocking(move || indexer.scan(&ws_clone)).await {
@@ -87,13 +207,32 @@ async fn main() {
                 Ok(Err(e)) => eprintln!("vault index warning: {e}"),
                 Err(e) => eprintln!("vault index task error: {e}"),
             }
+            if let Some(embedder) = embedder {
+                match embedder.run().await {
+                    Ok(stats) => eprintln!("vault embeddings: {stats}"),
+                    Err(e) => eprintln!("vault embeddings warning: {e}"),
+                }
+            }
         });
     }
 
+    // Near-real-time re-indexing of edited notes ([index] watch, default on).
+    if cfg.index.as_ref().and_then(|i| i.watch).unwrap_or(true) {
+        match watcher::spawn_vault_watcher(
+            workspace.clone(),
+            VaultIndexer::from_config(&cfg, Arc::clone(&db)),
+            embedder.clone(),
+        ) {
+            Ok(()) => eprintln!("vault watcher started"),
+            Err(e) => eprintln!("vault watcher unavailable, relying on periodic scans: {e}"),
+        }
+    }
+
     // Background git pull + re-index loop (every 15 min).
     sync::spawn_git_pull_loop(
         workspace.clone(),
-        Arc::clone(&db),
+        VaultIndexer::from_config(&cfg, Arc::clone(&db)),
+        embedder.clone(),
         sync::DEFAULT_PULL_INTERVAL_SECS,
     );
     eprintln!(
@@ -101,13 +240,72 @@ async fn main() {
         sync::DEFAULT_PULL_INTERVAL_SECS / 3600
     );
 
-    // Build subagent registry (core + message + search tools — no spawn, no cron).
+    // Scheduled brain.db backups ([backup] interval-hours); the backup tool works regardless.
+    let backup_keep = cfg
+        .backup
+        .as_ref()
+        .and_then(|b| b.keep)
+        .unwrap_or(backup::DEFAULT_KEEP);
+    let backup_interval = cfg
+        .backup
+        .as_ref()
+        .and_then(|b| b.interval_hours)
+        .unwrap_or(0);
+    if backup_interval >= 1 {
+        backup::spawn_backup_loop(
+            Arc::clone(&db),
+            workspace.clone(),
+            backup_interval,
+            backup_keep,
+        );
+        eprintln!(
+            "brain.db backups enabled (every {}h, keeping {})",
+            backup_interval, backup_keep
+        );
+    }
+
+    // WAL checkpoints and query-planner statistics ([database] maintenance-minutes).
+    let maintenance_minutes = cfg
+        .database
+        .as_ref()
+        .and_then(|d| d.maintenance_minutes)
+        .unwrap_or(maintenance::DEFAULT_INTERVAL_MINUTES);
+    maintenance::spawn_maintenance_loop(Arc::clone(&db), maintenance_minutes);
+
+    // Chat history retention ([retention] max-messages / max-age-days).
+    if let Some(r) = &cfg.retention {
+        let policy = RetentionPolicy {
+            max_messages: r.max_messages,
+            max_age_days: r.max_age_days,
+        };
+        if policy != RetentionPolicy::default() {
+            let summarizer = r.summarize.unwrap_or(false).then(|| Summarizer {
+                llm: Arc::clone(&llm),
+                model: model.to_string(),
+                params: turn_opts.summarize.clone(),
+            });
+            let interval = r
+                .interval_hours
+                .unwrap_or(retention::DEFAULT_INTERVAL_HOURS);
+            retention::spawn_retention_loop(Arc::clone(&db), policy, summarizer, interval);
+            eprintln!("chat history retention enabled (every {}h)", interval);
+        }
+    }
+
+    // Build subagent registry (core + message + send_file/send_photo + search tools — no spawn, no cron).
     // MessageTool is included here so background subagents can push results to the user.
     let subagent_registry = Arc::new({
-        let reg = tools::build_core_registry(&cfg);
+        let reg = tools::build_core_registry(&cfg, &db);
         reg.register(MessageTool);
-        reg.register(SearchVaultTool::new(Arc::clone(&db)));
+        reg.register(SendFileTool);
+        reg.register(SendPhotoTool);
+        reg.register(QrTool);
+        reg.register(SearchVaultTool::new(Arc::clone(&db)).with_embedder(embedder.clone()));
         reg.register(SearchChatTool::new(Arc::clone(&db)));
+        reg.register(VaultStatsTool::new(Arc::clone(&db)));
+        if let Some(embedder) = &embedder {
+            reg.register(SemanticSearchVaultTool::new(embedder.clone()));
+        }
         reg.register(GrepDirTool);
         reg
     });
@@ -117,35 +315,184 @@ async fn main() {
         Arc::clone(&llm),
         subagent_registry,
         model.to_string(),
+        subagent_params,
         workspace.clone(),
         restrict,
         SUBAGENT_MAX_ITERATIONS,
     ));
 
-    // Main registry: core + search + git + grep + spawn + cron.
-    let registry = tools::build_core_registry(&cfg);
-    registry.register(SearchVaultTool::new(Arc::clone(&db)));
+    // Main registry: core + search + git + grep + send_file/send_photo + spawn + cron.
+    let registry = tools::build_core_registry(&cfg, &db);
+    registry.register(SearchVaultTool::new(Arc::clone(&db)).with_embedder(embedder.clone()));
     registry.register(SearchChatTool::new(Arc::clone(&db)));
+    registry.register(VaultStatsTool::new(Arc::clone(&db)));
+    registry.register(MemoryTool::new(Arc::clone(&db)));
+    registry.register(EntityTool::new(Arc::clone(&db)));
+    registry.register(SessionTool::new(Arc::clone(&db)));
+    registry.register(ExportChatTool::new(Arc::clone(&db)));
+    registry.register(BackupTool::new(Arc::clone(&db), backup_keep));
+    if let Some(embedder) = &embedder {
+        registry.register(SemanticSearchVaultTool::new(embedder.clone()));
+    }
     registry.register(GrepDirTool);
     registry.register(GitSyncTool);
+    registry.register(SendFileTool);
+    registry.register(SendPhotoTool);
+    registry.register(QrTool);
+    registry.register(OcrTool::new(
+        Arc::clone(&llm),
+        model,
+        cfg.tools.as_ref().and_then(|t| t.ocr.as_ref()),
+    ));
+    registry.register(TranslateTool::new(
+        Arc::clone(&llm),
+        model,
+        cfg.tools.as_ref().and_then(|t| t.translate.as_ref()),
+    ));
+    if let Ok(client) = tools::web::web_client() {
+        let cache_ttl = cfg
+            .tools
+            .as_ref()
+            .and_then(|t| t.web.as_ref())
+            .and_then(|w| w.web_fetch_cache_ttl_secs)
+            .unwrap_or(tools::web::DEFAULT_WEB_FETCH_CACHE_TTL_SECS);
+        registry.register(SummarizeUrlTool::new(
+            tools::web::WebFetchTool::new(client, 0).with_cache(Arc::clone(&db), cache_ttl),
+            Arc::clone(&llm),
+            model,
+            cfg.tools.as_ref().and_then(|t| t.summarize.as_ref()),
+            &turn_opts.summarize,
+        ));
+    }
     registry.register(SpawnTool::new(Arc::clone(&manager)));
     registry.register(SubagentTool::new(Arc::clone(&manager)));
 
+    // Allow-list: config owners plus users added at runtime (persisted in BrainDb).
+    let allowlist = Arc::new(Allowlist::from_config(
+        cfg.telegram.as_ref().expect("config validated"),
+    ));
+    match db.list_allowed_users() {
+        Ok(ids) => {
+            for id in ids {
+                allowlist.add(id);
+            }
+        }
+        Err(e) => eprintln!("allowlist: {}", e),
+    }
+    registry.register(AllowlistTool::new(Arc::clone(&allowlist), Arc::clone(&db)));
+
+    // Recent Telegram message ids, so /forget and the forget tool can delete them;
+    // poll results, updated by the poller for the telegram_poll tool.
+    let shared = TelegramShared {
+        allowlist: Arc::clone(&allowlist),
+        ..Default::default()
+    };
+    let message_log = Arc::clone(&shared.message_log);
+    registry.register(ForgetTool::new(Arc::clone(&message_log), Arc::clone(&db)));
+    registry.register(UsageTool::new(Arc::clone(&db)));
+    if let Ok(client) = tools::web::web_client() {
+        let feeds_cfg = cfg.tools.as_ref().and_then(|t| t.feeds.as_ref());
+        registry.register(FeedsTool::new(Arc::clone(&db), client, feeds_cfg));
+    }
+    // Calendars are shared with the heartbeat/cron briefing below.
+    let calendars = tools::web::web_client().ok().and_then(|client| {
+        let calendar_cfg = cfg.tools.as_ref().and_then(|t| t.calendar.as_ref());
+        let tz = timezone.parse().unwrap_or(chrono_tz::Europe::London);
+        Calendars::from_config(client, calendar_cfg, tz).map(Arc::new)
+    });
+    if let Some(calendars) = &calendars {
+        registry.register(CalendarTool::new(Arc::clone(calendars)));
+    }
+    let daily_notes = || {
+        DailyNoteTool::new(
+            cfg.tools.as_ref().and_then(|t| t.daily_note.as_ref()),
+            timezone.parse().unwrap_or(chrono_tz::Europe::London),
+            calendars.clone(),
+        )
+    };
+    registry.register(daily_notes());
+    registry.register(JournalTool::new(daily_notes(), Arc::clone(&db)));
+    registry.register(HabitsTool::new(
+        Arc::clone(&db),
+        cfg.tools.as_ref().and_then(|t| t.habits.as_ref()),
+        timezone.parse().unwrap_or(chrono_tz::Europe::London),
+    ));
+    registry.register(WorkoutTool::new(
+        Arc::clone(&db),
+        cfg.tools.as_ref().and_then(|t| t.workout.as_ref()),
+        timezone.parse().unwrap_or(chrono_tz::Europe::London),
+    ));
+    registry.register(ExpenseTool::new(
+        Arc::clone(&db),
+        cfg.tools.as_ref().and_then(|t| t.expense.as_ref()),
+        timezone.parse().unwrap_or(chrono_tz::Europe::London),
+    ));
+    if let Ok(client) = tools::web::web_client() {
+        registry.register(BookmarkTool::new(
+            Arc::clone(&db),
+            tools::web::WebFetchTool::new(client, 0),
+            Arc::clone(&llm),
+            model,
+            cfg.tools.as_ref().and_then(|t| t.bookmark.as_ref()),
+            timezone.parse().unwrap_or(chrono_tz::Europe::London),
+        ));
+    }
+    registry.register(TelegramPollTool::new(
+        Arc::clone(&shared.polls),
+        cfg.telegram.clone().expect("config validated"),
+    ));
+
     let (inbound_tx, mut inbound_rx) = mpsc::channel(64);
-    let outbound_tx = telegram::spawn_telegram(&cfg, inbound_tx.clone());
+    let outbound_tx = telegram::spawn_telegram_with_shared(&cfg, inbound_tx.clone(), shared);
     eprintln!("Telegram poller and sender started");
 
-    let cron_store = Arc::new(CronStore::load(&workspace).unwrap_or_else(|e| {
-        eprintln!("cron store: {}", e);
-        CronStore::empty(&workspace)
-    }));
+    let cron_store = Arc::new(
+        CronStore::load(&workspace)
+            .unwrap_or_else(|e| {
+                eprintln!("cron store: {}", e);
+                CronStore::empty(&workspace)
+            })
+            .with_history(Arc::clone(&db)),
+    );
+    let cron_agent_timeout = cfg
+        .cron
+        .as_ref()
+        .and_then(|c| c.agent_timeout_seconds)
+        .filter(|&s| s > 0)
+        .unwrap_or(cron_runner::DEFAULT_AGENT_TIMEOUT_SECS);
+    let cron_retries = cfg
+        .cron
+        .as_ref()
+        .and_then(|c| c.agent_retries)
+        .unwrap_or(cron_runner::DEFAULT_AGENT_RETRIES);
+    let cron_retry_backoff = cfg
+        .cron
+        .as_ref()
+        .and_then(|c| c.retry_backoff_seconds)
+        .filter(|&s| s > 0)
+        .unwrap_or(cron_runner::DEFAULT_RETRY_BACKOFF_SECS);
     cron_runner::spawn_cron_runner(
         Arc::clone(&cron_store),
+        Some(Arc::clone(&allowlist)),
         inbound_tx.clone(),
         outbound_tx.clone(),
-        60,
+        cron_runner::TICK_SECS,
     );
-    registry.register(CronTool::new(Arc::clone(&cron_store)));
+    registry.register(
+        CronTool::new(Arc::clone(&cron_store))
+            .with_timezone(timezone.parse().unwrap_or(chrono_tz::Europe::London))
+            .with_allowlist(Arc::clone(&allowlist)),
+    );
+    registry.register(RemindMeTool::new(
+        Arc::clone(&cron_store),
+        timezone.parse().unwrap_or(chrono_tz::Europe::London),
+        Arc::clone(&llm),
+        model,
+    ));
+    registry.register(TimerTool::new(
+        Arc::clone(&cron_store),
+        timezone.parse().unwrap_or(chrono_tz::Europe::London),
+    ));
 
     // Track the last Telegram/cron chat_id so heartbeat replies go to the right chat.
     let last_chat_id: Arc<AtomicI64> = Arc::new(AtomicI64::new(0));
@@ -171,12 +518,83 @@ async fn main() {
 
     drop(inbound_tx);
 
-    while let Some(msg) = inbound_rx.recv().await {
+    while let Some(mut msg) = inbound_rx.recv().await {
         // Update last_chat_id for non-heartbeat sources so replies go to the right place.
         if msg.channel != "heartbeat" {
             last_chat_id.store(msg.chat_id, Ordering::Relaxed);
         }
 
+        // Each forum topic gets its own session ("{chat_id}:{thread_id}").
+        let chat_id_str = msg.session_key();
+
+        // Location shares are stored per chat and never reach the agent.
+        if let Some(loc) = msg.location {
+            let (lat, lon) = (loc.latitude, loc.longitude);
+            let db = Arc::clone(&db);
+            let cid = msg.chat_id.to_string();
+            let saved = tokio::task::spawn_blocking(move || db.set_chat_location(&cid, lat, lon))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r.map_err(|e| e.to_string()));
+            // Live-location updates arrive every few seconds; only acknowledge fresh shares.
+            if !loc.is_update {
+                let text = match saved {
+                    Ok(()) => format!("📍 Location saved ({lat:.4}, {lon:.4})."),
+                    Err(e) => format!("Error saving location: {}", e),
+                };
+                let _ = outbound_tx
+                    .send(OutboundMsg {
+                        chat_id: msg.chat_id,
+                        text,
+                        channel: msg.channel,
+                        kind: OutboundKind::Text,
+                        thread_id: msg.thread_id,
+                        disable_notification: false,
+                    })
+                    .await;
+            } else if let Err(e) = saved {
+                eprintln!("location update: {}", e);
+            }
+            continue;
+        }
+
+        // Voice notes are transcribed and then handled like a typed message.
+        if let Some(file_id) = msg.voice.as_deref() {
+            match transcribe_voice_note(&cfg, voice.as_ref(), file_id).await {
+                Ok(text) => msg.text = text,
+                Err(e) => {
+                    let _ = outbound_tx
+                        .send(OutboundMsg {
+                            chat_id: msg.chat_id,
+                            text: e,
+                            channel: msg.channel,
+                            kind: OutboundKind::Text,
+                            thread_id: msg.thread_id,
+                            disable_notification: false,
+                        })
+                        .await;
+                    continue;
+                }
+            }
+        }
+
+        // Heartbeat and cron runs see what's coming up in the user's calendars.
+        if (msg.channel == "heartbeat" || msg.channel == "cron")
+            && let Some(calendars) = &calendars
+            && let Some(briefing) = calendars.briefing().await
+        {
+            msg.text = format!("{}\n\n{}", msg.text, briefing);
+        }
+
+        let location = {
+            let db = Arc::clone(&db);
+            let cid = msg.chat_id.to_string();
+            tokio::task::spawn_blocking(move || db.get_chat_location(&cid))
+                .await
+                .ok()
+                .and_then(Result::ok)
+                .flatten()
+        };
         let delivered = Arc::new(AtomicBool::new(false));
         let tool_ctx = tools::ToolCtx {
             workspace: workspace.clone(),
@@ -185,10 +603,62 @@ async fn main() {
             channel: Some(msg.channel.clone()),
             outbound_tx: Some(Arc::new(outbound_tx.clone())),
             delivered: Arc::clone(&delivered),
+            location,
+            thread_id: msg.thread_id,
         };
-        let chat_id_str = msg.chat_id.to_string();
 
-        let reply = if msg.text.trim() == "/clear" {
+        // /forget [N]: drop the last N messages from Telegram and chat_history, plus the
+        // command itself. Handled before the agent so the secret never reaches the LLM again.
+        if let Some(rest) = msg.text.trim().strip_prefix("/forget")
+            && (rest.is_empty() || rest.starts_with(' '))
+        {
+            let count = rest.trim().parse().unwrap_or(forget::DEFAULT_FORGET_COUNT);
+            let text = match forget::forget_recent(
+                &db,
+                &message_log,
+                Some(&outbound_tx),
+                &msg.channel,
+                msg.chat_id,
+                msg.thread_id,
+                count,
+                msg.message_id.as_slice(),
+            )
+            .await
+            {
+                Ok(f) => format!(
+                    "🧹 Deleted {} Telegram message(s) and {} stored history row(s).",
+                    f.telegram, f.history
+                ),
+                Err(e) => format!("Error forgetting messages: {}.", e),
+            };
+            let _ = outbound_tx
+                .send(OutboundMsg {
+                    chat_id: msg.chat_id,
+                    text,
+                    channel: msg.channel,
+                    kind: OutboundKind::Text,
+                    thread_id: msg.thread_id,
+                    disable_notification: false,
+                })
+                .await;
+            continue;
+        }
+
+        let mut preview: Option<StreamPreview> = None;
+        let usage_scope = UsageScope {
+            chat_id: chat_id_str.clone(),
+            source: msg.channel.clone(),
+        };
+        let started = cron_runner::unix_now();
+        let mut agent_failed = false;
+        let cron_timeout = msg.cron_job.as_deref().map(|id| {
+            let secs = cron_store
+                .get(id)
+                .and_then(|j| j.timeout_seconds)
+                .unwrap_or(cron_agent_timeout);
+            Duration::from_secs(secs)
+        });
+        let mut reply = if msg.text.trim() == "/clear" {
             match Session::reset(Arc::clone(&db), &chat_id_str).await {
                 Ok(()) => "Session cleared. Starting fresh! 🦀".to_string(),
                 Err(e) => {
@@ -196,16 +666,36 @@ async fn main() {
                     format!("Error clearing session: {}.", e)
                 }
             }
+        } else if let Some(rest) = msg.text.trim().strip_prefix("/usage")
+            && (rest.is_empty() || rest.starts_with(' '))
+        {
+            match UsagePeriod::parse(rest) {
+                Some(period) => {
+                    let db = Arc::clone(&db);
+                    match tokio::task::spawn_blocking(move || usage_tool::usage_report(&db, period))
+                        .await
+                    {
+                        Ok(Ok(report)) => report,
+                        Ok(Err(e)) => format!("Error reading usage: {}.", e),
+                        Err(e) => format!("Error reading usage: {}.", e),
+                    }
+                }
+                None => "Usage: /usage [day|week|month]".to_string(),
+            }
         } else if msg.channel == "heartbeat" {
-            match agent::process_heartbeat_message(
-                &llm,
-                &registry,
-                &workspace,
-                model,
-                &timezone,
-                &chat_id_str,
-                &msg.text,
-                &tool_ctx,
+            match usage::scoped(
+                usage_scope,
+                agent::process_heartbeat_message(
+                    llm.as_ref(),
+                    &registry,
+                    &workspace,
+                    model,
+                    &timezone,
+                    &chat_id_str,
+                    &msg.text,
+                    &tool_ctx,
+                    &turn_opts.agent,
+                ),
             )
             .await
             {
@@ -216,42 +706,217 @@ async fn main() {
                 }
             }
         } else {
-            match agent::process_message(
-                &llm,
-                &registry,
-                &workspace,
-                model,
-                &timezone,
-                &chat_id_str,
-                &msg.text,
-                &tool_ctx,
-                &db,
-            )
-            .await
-            {
-                Ok(r) => r,
-                Err(e) => {
+            // Voice notes are answered once the reply is complete, so only stream text.
+            if stream_replies && msg.channel == "telegram" && msg.voice.is_none() {
+                preview = Some(StreamPreview::spawn(outbound_tx.clone(), &msg));
+            }
+            let turn = usage::scoped(
+                usage_scope,
+                agent::process_message_streaming(
+                    llm.as_ref(),
+                    &registry,
+                    &workspace,
+                    model,
+                    &timezone,
+                    &chat_id_str,
+                    &msg.text,
+                    &tool_ctx,
+                    &db,
+                    &turn_opts,
+                    preview.as_ref().map(|p| &p.deltas),
+                ),
+            );
+            // Cron agent jobs are cut off so a stuck LLM call can't hold up the queue.
+            let outcome = match cron_timeout {
+                Some(limit) => tokio::time::timeout(limit, turn).await.ok(),
+                None => Some(turn.await),
+            };
+            match outcome {
+                Some(Ok(r)) => r,
+                Some(Err(e)) => {
                     eprintln!("agent error: {}", e);
+                    agent_failed = true;
                     format!("Error: {}.", e)
                 }
+                None => {
+                    let secs = cron_timeout.map_or(0, |d| d.as_secs());
+                    eprintln!("cron agent job timed out after {}s", secs);
+                    agent_failed = true;
+                    format!("Error: scheduled job timed out after {}s.", secs)
+                }
             }
         };
 
+        let streamed_key = match preview {
+            Some(p) => p.finish().await,
+            None => None,
+        };
+
+        if let Some(job) = &msg.cron_job {
+            cron_store.record_run(job, started, JobAction::Agent, !agent_failed, &reply);
+            cron_store.finish_run(job);
+            let now = cron_runner::unix_now();
+            if !agent_failed {
+                cron_store.clear_failures(job, msg.chat_id);
+            } else if let Some(at) =
+                cron_store.schedule_retry(job, msg.chat_id, now, cron_retries, cron_retry_backoff)
+            {
+                // Failed attempts stay out of the chat until the retries run out.
+                eprintln!(
+                    "cron job {} failed in chat {}, retrying in {}s",
+                    job,
+                    msg.chat_id,
+                    at.saturating_sub(now)
+                );
+                continue;
+            } else {
+                let name = cron_store
+                    .get(job)
+                    .and_then(|j| j.label)
+                    .unwrap_or_else(|| job.clone());
+                reply = format!(
+                    "⚠️ Scheduled job \"{}\" failed {} time(s) in a row. Last error: {}",
+                    name,
+                    cron_retries + 1,
+                    reply.trim_start_matches("Error: ")
+                );
+            }
+        }
+
         // Heartbeat with no known chat (chat_id == 0): no user has messaged yet, drop reply.
         if msg.channel == "heartbeat" && msg.chat_id == 0 {
             continue;
         }
 
+        // Recorded after the turn so the forget tool only sees earlier messages.
+        if let Some(id) = msg.message_id {
+            message_log.record(msg.chat_id, msg.thread_id, id);
+        }
+
+        // A streamed reply is already on screen: finish it with the final text.
+        if let Some(key) = streamed_key {
+            let _ = outbound_tx
+                .send(OutboundMsg {
+                    chat_id: msg.chat_id,
+                    text: reply,
+                    channel: msg.channel,
+                    kind: OutboundKind::Stream { key, done: true },
+                    thread_id: msg.thread_id,
+                    disable_notification: false,
+                })
+                .await;
+            continue;
+        }
+
         // Skip if a tool (message tool or for_user result) already sent content to the user
         // during the agent loop, to avoid delivering the same response twice.
         if !delivered.load(Ordering::Relaxed) {
+            // Answer voice with voice when enabled; fall back to text if synthesis fails.
+            let voice_reply = match &voice {
+                Some(v)
+                    if msg.voice.is_some()
+                        && v.replies_enabled()
+                        && reply.chars().count() <= voice::MAX_SPEECH_CHARS =>
+                {
+                    match v
+                        .synthesize_to_file(&reply, &icrab::workspace::voice_dir(&workspace))
+                        .await
+                    {
+                        Ok(path) => Some(path),
+                        Err(e) => {
+                            eprintln!("voice reply: {}", e);
+                            None
+                        }
+                    }
+                }
+                _ => None,
+            };
+            let (text, kind) = match voice_reply {
+                Some(path) => (String::new(), OutboundKind::Voice(path)),
+                None => (reply, OutboundKind::Text),
+            };
             let _ = outbound_tx
                 .send(OutboundMsg {
                     chat_id: msg.chat_id,
-                    text: reply,
+                    text,
                     channel: msg.channel,
+                    kind,
+                    thread_id: msg.thread_id,
+                    disable_notification: false,
                 })
                 .await;
         }
     }
 }
+
+/// Live preview of a streamed reply: LLM deltas in, throttled `OutboundKind::Stream` edits out.
+struct StreamPreview {
+    deltas: mpsc::UnboundedSender<String>,
+    task: tokio::task::JoinHandle<Option<String>>,
+}
+
+impl StreamPreview {
+    fn spawn(outbound_tx: mpsc::Sender<OutboundMsg>, msg: &InboundMsg) -> Self {
+        let (deltas, mut rx) = mpsc::unbounded_channel::<String>();
+        let key = uuid::Uuid::new_v4().to_string();
+        let (chat_id, thread_id, channel) = (msg.chat_id, msg.thread_id, msg.channel.clone());
+        let task = tokio::spawn(async move {
+            let mut text = String::new();
+            let mut shown = String::new();
+            let mut tick = tokio::time::interval(STREAM_EDIT_INTERVAL);
+            loop {
+                tokio::select! {
+                    delta = rx.recv() => match delta {
+                        Some(d) => text.push_str(&d),
+                        None => break,
+                    },
+                    _ = tick.tick() => {
+                        if text.trim().is_empty() || text == shown {
+                            continue;
+                        }
+                        shown.clone_from(&text);
+                        let _ = outbound_tx
+                            .send(OutboundMsg {
+                                chat_id,
+                                text: format!("{} …", text.trim_end()),
+                                channel: channel.clone(),
+                                kind: OutboundKind::Stream { key: key.clone(), done: false },
+                                thread_id,
+                                disable_notification: false,
+                            })
+                            .await;
+                    }
+                }
+            }
+            (!shown.is_empty()).then_some(key)
+        });
+        Self { deltas, task }
+    }
+
+    /// Close the delta stream; returns the stream key if a preview message was sent, in which
+    /// case the final reply must be delivered as its last edit.
+    async fn finish(self) -> Option<String> {
+        drop(self.deltas);
+        self.task.await.ok().flatten()
+    }
+}
+
+/// Download a voice note from Telegram and transcribe it. Errors are user-facing.
+async fn transcribe_voice_note(
+    cfg: &config::Config,
+    voice: Option<&VoiceClient>,
+    file_id: &str,
+) -> Result<String, String> {
+    let Some(voice) = voice else {
+        return Err("Voice notes need a [voice] section in config.toml.".to_string());
+    };
+    let telegram = cfg.telegram.as_ref().expect("config validated");
+    let audio = telegram::download_file(telegram, file_id)
+        .await
+        .map_err(|e| format!("Error downloading voice note: {}.", e))?;
+    match voice.transcribe(&audio).await {
+        Ok(text) if !text.is_empty() => Ok(text),
+        Ok(_) => Err("I couldn't make out any speech in that voice note.".to_string()),
+        Err(e) => Err(format!("Error transcribing voice note: {}.", e)),
+    }
+}
@@ -1,4 +1,15 @@
-//! Persistent brain: SQLite-backed chat history, vault index, and FTS5 search engine.
+//! Persistent brain: SQLite-backed chat history, vault index, FTS5 and semantic search.
 
+pub mod backup;
 pub mod db;
+pub mod embeddings;
+pub mod extract;
+pub mod frontmatter;
+pub mod ignore;
 pub mod indexer;
+pub mod links;
+pub mod maintenance;
+pub mod migrations;
+pub mod retention;
+pub mod tags;
+pub mod watcher;
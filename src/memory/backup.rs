@@ -0,0 +1,292 @@
+//! brain.db backups in `workspace/.icrab/backups/`.
+//!
+//! Losing the SQLite file means losing chat history, memories and usage records. Backups are
+//! compacted `VACUUM INTO` copies named `brain-YYYY-MM-DD.db` (UTC date; a second backup on
+//! the same day replaces the first). They are made by the `backup` tool or, with
+//! `[backup] interval-hours`, by [`spawn_backup_loop`]; after each one only the newest `keep`
+//! files are kept. [`restore`] copies a backup over the live database in place, after saving
+//! the current state as `brain-YYYY-MM-DD-pre-restore.db`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use chrono::Utc;
+
+use crate::memory::db::{BrainDb, DbError};
+use crate::workspace;
+
+/// Backups kept after rotation when `[backup] keep` is unset.
+pub const DEFAULT_KEEP: usize = 7;
+
+/// How often the scheduled loop checks whether a backup is due.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// One backup file.
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    /// File name inside the backups directory (what `restore` takes).
+    pub name: String,
+    pub bytes: u64,
+    pub modified: SystemTime,
+}
+
+/// Back up `db` to today's file, replacing an earlier backup from the same day.
+pub fn create(db: &BrainDb, workspace: &Path) -> Result<BackupInfo, DbError> {
+    write_backup(
+        db,
+        workspace,
+        &format!("brain-{}.db", Utc::now().format("%Y-%m-%d")),
+    )
+}
+
+/// All backups, newest first. An absent backups directory means no backups.
+pub fn list(workspace: &Path) -> Result<Vec<BackupInfo>, DbError> {
+    let dir = workspace::backups_dir(workspace);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(DbError(format!("read {}: {e}", dir.display()))),
+    };
+    let mut backups = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !is_backup_name(&name) {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        backups.push(BackupInfo {
+            name,
+            bytes: meta.len(),
+            modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+    }
+    backups.sort_by(|a, b| {
+        b.modified
+            .cmp(&a.modified)
+            .then_with(|| b.name.cmp(&a.name))
+    });
+    Ok(backups)
+}
+
+/// Delete all but the newest `keep` backups (at least one is always kept). Returns how many
+/// were removed.
+pub fn rotate(workspace: &Path, keep: usize) -> Result<usize, DbError> {
+    let dir = workspace::backups_dir(workspace);
+    let mut removed = 0;
+    for old in list(workspace)?.iter().skip(keep.max(1)) {
+        let path = dir.join(&old.name);
+        std::fs::remove_file(&path)
+            .map_err(|e| DbError(format!("remove {}: {e}", path.display())))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Replace the live database with the backup called `name` (see [`list`]).
+pub fn restore(db: &BrainDb, workspace: &Path, name: &str) -> Result<(), DbError> {
+    if !is_backup_name(name) {
+        return Err(DbError(format!("'{name}' is not a backup file name")));
+    }
+    let path = workspace::backups_dir(workspace).join(name);
+    if !path.is_file() {
+        return Err(DbError(format!("no backup named '{name}'")));
+    }
+    let safety = format!("brain-{}-pre-restore.db", Utc::now().format("%Y-%m-%d"));
+    if safety != name {
+        write_backup(db, workspace, &safety)?;
+    }
+    db.restore_from(&path)
+}
+
+/// Whether the newest backup is older than `interval` (or there is none).
+pub fn is_due(workspace: &Path, interval: Duration) -> Result<bool, DbError> {
+    Ok(match list(workspace)?.first() {
+        Some(newest) => newest.modified.elapsed().unwrap_or_default() >= interval,
+        None => true,
+    })
+}
+
+/// Spawn a background task that backs up `db` whenever the newest backup is older than
+/// `interval_hours`, then rotates to `keep` files. Checked at startup and hourly, so restarts
+/// do not postpone backups. Errors are logged but never fatal.
+pub fn spawn_backup_loop(db: Arc<BrainDb>, workspace: PathBuf, interval_hours: u64, keep: usize) {
+    let interval = Duration::from_secs(interval_hours.max(1) * 60 * 60);
+    tokio::spawn(async move {
+        loop {
+            let db = Arc::clone(&db);
+            let ws = workspace.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                if !is_due(&ws, interval)? {
+                    return Ok(None);
+                }
+                let info = create(&db, &ws)?;
+                let removed = rotate(&ws, keep)?;
+                Ok::<_, DbError>(Some((info, removed)))
+            })
+            .await;
+            match result {
+                Ok(Ok(Some((info, removed)))) => eprintln!(
+                    "brain.db backup: {} ({}), {removed} old backup(s) removed",
+                    info.name,
+                    format_bytes(info.bytes)
+                ),
+                Ok(Ok(None)) => {}
+                Ok(Err(e)) => eprintln!("brain.db backup warning: {e}"),
+                Err(e) => eprintln!("brain.db backup task error: {e}"),
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Human-readable size, e.g. "1.4 MB".
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let b = bytes as f64;
+    if b < KB {
+        format!("{bytes} B")
+    } else if b < KB * KB {
+        format!("{:.1} KB", b / KB)
+    } else {
+        format!("{:.1} MB", b / (KB * KB))
+    }
+}
+
+/// `VACUUM INTO` a temporary file, then move it over `name` so a failed backup never
+/// clobbers a good one.
+fn write_backup(db: &BrainDb, workspace: &Path, name: &str) -> Result<BackupInfo, DbError> {
+    let dir = workspace::backups_dir(workspace);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| DbError(format!("create_dir_all {}: {e}", dir.display())))?;
+    let path = dir.join(name);
+    let tmp = dir.join(format!("{name}.tmp"));
+    let _ = std::fs::remove_file(&tmp);
+    if let Err(e) = db.backup_to(&tmp) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    std::fs::rename(&tmp, &path)
+        .map_err(|e| DbError(format!("rename to {}: {e}", path.display())))?;
+    let meta =
+        std::fs::metadata(&path).map_err(|e| DbError(format!("stat {}: {e}", path.display())))?;
+    Ok(BackupInfo {
+        name: name.to_string(),
+        bytes: meta.len(),
+        modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+    })
+}
+
+/// `brain-*.db` without any path components.
+fn is_backup_name(name: &str) -> bool {
+    name.starts_with("brain-")
+        && name.ends_with(".db")
+        && !name.contains(['/', '\\'])
+        && !name.contains("..")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn create_then_restore_brings_back_old_state() {
+        let tmp = TempDir::new().unwrap();
+        let db = BrainDb::open(tmp.path()).unwrap();
+        db.save_memory("Allergic to peanuts", "health", "1")
+            .unwrap();
+
+        let info = create(&db, tmp.path()).unwrap();
+        assert!(info.name.starts_with("brain-") && info.bytes > 0);
+        assert!(!is_due(tmp.path(), Duration::from_secs(3600)).unwrap());
+
+        db.save_memory("Lives in Leeds", "", "1").unwrap();
+        for fact in db.search_memories("", 10).unwrap() {
+            if fact.fact.contains("peanuts") {
+                db.forget_memory(fact.id).unwrap();
+            }
+        }
+
+        restore(&db, tmp.path(), &info.name).unwrap();
+        let facts: Vec<String> = db
+            .search_memories("", 10)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.fact)
+            .collect();
+        assert_eq!(facts, vec!["Allergic to peanuts"]);
+        // FTS came back with the data.
+        assert_eq!(db.search_memories("peanuts", 5).unwrap().len(), 1);
+
+        // The state before the restore was saved too.
+        let names: Vec<String> = list(tmp.path())
+            .unwrap()
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(
+            names.iter().any(|n| n.ends_with("-pre-restore.db")),
+            "{names:?}"
+        );
+    }
+
+    #[test]
+    fn rotate_keeps_newest() {
+        let tmp = TempDir::new().unwrap();
+        let dir = workspace::backups_dir(tmp.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        for day in 1..=4 {
+            std::fs::write(dir.join(format!("brain-2026-01-0{day}.db")), b"x").unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), b"not a backup").unwrap();
+
+        assert_eq!(rotate(tmp.path(), 2).unwrap(), 2);
+        let mut names: Vec<String> = list(tmp.path())
+            .unwrap()
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["brain-2026-01-03.db", "brain-2026-01-04.db"]);
+        assert!(dir.join("notes.txt").exists());
+        assert_eq!(
+            rotate(tmp.path(), 0).unwrap(),
+            1,
+            "one backup always survives"
+        );
+    }
+
+    #[test]
+    fn restore_rejects_unknown_or_unsafe_names() {
+        let tmp = TempDir::new().unwrap();
+        let db = BrainDb::open(tmp.path()).unwrap();
+        assert!(is_due(tmp.path(), Duration::from_secs(3600)).unwrap());
+        for name in [
+            "brain-1999-01-01.db",
+            "../brain.db",
+            "brain-../../x.db",
+            "other.db",
+        ] {
+            assert!(restore(&db, tmp.path(), name).is_err(), "{name}");
+        }
+        assert!(
+            list(tmp.path()).unwrap().is_empty(),
+            "no safety copy on failure"
+        );
+    }
+
+    #[test]
+    fn format_bytes_units() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(2048), "2.0 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.5 MB");
+    }
+}
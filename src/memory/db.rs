@@ -1,18 +1,41 @@
 //! SQLite brain database: schema init, chat history persistence, vault index for FTS5.
 //!
-//! Lives at `workspace/.icrab/brain.db` (Git-ignored).
+//! Lives at `workspace/.icrab/brain.db` (Git-ignored); see `memory::backup` for backups.
 //!
 //! Tables:
 //! - `chat_history`  — persistent chat messages per session (replaces sessions/*.json)
 //! - `chat_summary`  — per-session LLM-generated summary string
-//! - `vault_index`   — mirrors Obsidian Markdown files
+//! - `vault_index`   — mirrors Obsidian Markdown files (content and frontmatter as JSON)
 //! - `vault_fts`     — FTS5 virtual table with BM25 scoring
-
+//! - `chat_location` — latest coordinates shared per chat (Telegram location messages)
+//! - `allowed_users` — Telegram users added at runtime by the allowlist tool
+//! - `llm_usage`     — tokens and estimated cost of every LLM call, per chat and source
+//! - `llm_cache`     — responses to deterministic (temperature 0) LLM calls, keyed by request hash
+//! - `vault_embeddings` — embedding vectors of vault note chunks, for semantic search
+//! - `vault_tags`    — frontmatter and inline `#tags` of each vault note
+//! - `memories`      — long-term facts about the user saved by the memory tool (+ `memories_fts`)
+//! - `session_archive` — summaries of sessions rotated away from, for resuming them
+//! - `entities`      — people, places and projects saved by the entity tool (+ aliases, relations)
+//! - `feed_seen`     — GUIDs of RSS/Atom items already returned by the feeds tool
+//! - `web_cache`     — pages fetched by web_fetch, with ETag/Last-Modified for revalidation
+//! - `habits`        — daily habits of the habits tool (+ `habit_checkins`, the days done)
+//! - `workout_sets`  — sets, reps and weights logged by the workout tool
+//! - `expenses`      — spending logged by the expense tool, in minor currency units
+//! - `bookmarks`     — pages saved by the bookmark tool, with summary and tags (+ `bookmarks_fts`)
+//! - `cron_runs`     — each firing of a cron job: when, how, success and a reply snippet
+//! - `schema_version` — applied schema migrations (see `memory::migrations`)
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::path::Path;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
 
-use rusqlite::{Connection, params};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{Connection, OpenFlags, params};
 
+use crate::memory::frontmatter::{FieldFilter, FieldOp};
+use crate::memory::migrations;
 use crate::workspace;
 
 // ---------------------------------------------------------------------------
@@ -36,17 +59,51 @@ impl From<rusqlite::Error> for DbError {
     }
 }
 
+/// Run `f` on `db` off the async runtime, for tools. Errors read "`what` failed: …", or
+/// "`what` task error: …" when the blocking task itself fails.
+pub async fn run_blocking<T, F>(db: &Arc<BrainDb>, what: &str, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&BrainDb) -> Result<T, DbError> + Send + 'static,
+{
+    let db = Arc::clone(db);
+    tokio::task::spawn_blocking(move || f(&db))
+        .await
+        .map_err(|e| format!("{what} task error: {e}"))?
+        .map_err(|e| format!("{what} failed: {e}"))
+}
+
 // ---------------------------------------------------------------------------
 // BrainDb
 // ---------------------------------------------------------------------------
 
+/// Read-only connections kept next to the writer.
+const READER_CONNECTIONS: usize = 3;
+
+/// How long seen feed GUIDs are remembered (180 days); items that old have left their feeds.
+pub const FEED_SEEN_KEEP_SECS: i64 = 180 * 86_400;
+
+/// Cached pages not fetched or revalidated for this long (7 days) are dropped.
+pub const WEB_CACHE_KEEP_SECS: i64 = 7 * 86_400;
+
+/// Total size of cached page bodies (20 MB); the least recently fetched go first.
+pub const WEB_CACHE_MAX_BYTES: i64 = 20 * 1024 * 1024;
+
 /// Persistent SQLite brain for iCrab.
 ///
-/// Uses a single `Mutex<Connection>` — safe to share across async tasks via
-/// `Arc<BrainDb>` since all operations take the lock synchronously.
-/// (rusqlite `Connection` is `Send` but not `Sync`.)
+/// One writer connection plus a small pool of read-only connections, each behind its own
+/// `Mutex` — safe to share across async tasks via `Arc<BrainDb>` since all operations take
+/// a lock synchronously. (rusqlite `Connection` is `Send` but not `Sync`.) The database is
+/// in WAL mode, so reads (searches, history loads) run alongside a write instead of
+/// queueing behind it, and always see the last committed state.
 pub struct BrainDb {
+    /// The only connection that writes; also used for migrations, backups and restores.
     conn: Mutex<Connection>,
+    readers: Vec<Mutex<Connection>>,
+    /// Round-robin start for [`BrainDb::reader`].
+    next_reader: AtomicUsize,
+    /// SQLCipher key (`[database] encryption-key`); also unlocks backups on restore.
+    key: Option<String>,
 }
 
 impl std::fmt::Debug for BrainDb {
@@ -59,151 +116,154 @@ impl BrainDb {
     /// Open (or create) the brain database at `workspace/.icrab/brain.db`.
     /// Creates the `.icrab/` directory if it does not exist.
     pub fn open(workspace: &Path) -> Result<Self, DbError> {
+        Self::open_with_key(workspace, None)
+    }
+
+    /// Like [`BrainDb::open`], encrypting the file with SQLCipher when `key` is set
+    /// (requires the `encryption` feature). An existing plaintext database is encrypted
+    /// in place the first time a key is given.
+    pub fn open_with_key(workspace: &Path, key: Option<&str>) -> Result<Self, DbError> {
         let db_path = workspace::brain_db_path(workspace);
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent).map_err(|e| DbError(format!("create_dir_all: {e}")))?;
         }
 
-        let conn = Connection::open(&db_path)
-            .map_err(|e| DbError(format!("open {}: {e}", db_path.display())))?;
+        let key = key.filter(|k| !k.is_empty());
+        let conn = match key {
+            Some(key) => Self::open_encrypted(&db_path, key)?,
+            None => Connection::open(&db_path)
+                .map_err(|e| DbError(format!("open {}: {e}", db_path.display())))?,
+        };
 
         // iSH-compatible PRAGMAs:
-        // TRUNCATE is safer on iSH's emulated filesystem.
+        // WAL lets the reader connections run while the writer commits.
         // Disable mmap entirely to avoid uncatchable I/O errors and memory pressure.
         // temp_store MEMORY: temp tables never hit slow iSH storage.
         conn.execute_batch(
-            "PRAGMA journal_mode = TRUNCATE;
+            "PRAGMA journal_mode = WAL;
              PRAGMA synchronous  = NORMAL;
              PRAGMA mmap_size    = 0;
              PRAGMA temp_store   = MEMORY;",
         )?;
 
-        Self::init_schema(&conn)?;
+        migrations::run(&conn)?;
+
+        let readers = (0..READER_CONNECTIONS)
+            .map(|_| Self::open_reader(&db_path, key).map(Mutex::new))
+            .collect::<Result<_, _>>()?;
 
         Ok(Self {
             conn: Mutex::new(conn),
+            readers,
+            next_reader: AtomicUsize::new(0),
+            key: key.map(str::to_string),
         })
     }
 
-    // -----------------------------------------------------------------------
-    // Schema
-    // -----------------------------------------------------------------------
-
-    fn init_schema(conn: &Connection) -> Result<(), DbError> {
+    /// A read-only connection to the (already migrated) database at `path`.
+    fn open_reader(path: &Path, key: Option<&str>) -> Result<Connection, DbError> {
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let conn = Connection::open_with_flags(path, flags)
+            .map_err(|e| DbError(format!("open {}: {e}", path.display())))?;
+        if let Some(key) = key {
+            conn.pragma_update(None, "key", key)?;
+        }
         conn.execute_batch(
-            "-- ── Chat history ─────────────────────────────────────────────────────
-            CREATE TABLE IF NOT EXISTS chat_history (
-                id           INTEGER PRIMARY KEY AUTOINCREMENT,
-                chat_id      TEXT    NOT NULL,
-                session_id   TEXT    NOT NULL DEFAULT '',
-                role         TEXT    NOT NULL,
-                content      TEXT    NOT NULL,
-                tool_call_id TEXT,
-                tool_calls   TEXT,
-                timestamp    DATETIME DEFAULT CURRENT_TIMESTAMP
-            );
-            -- Legacy index kept for compatibility; new queries use idx_chat_history_chat_session
-            CREATE INDEX IF NOT EXISTS idx_chat_history_chat_id
-                ON chat_history(chat_id, id);
-
-            -- ── Chat summaries ──────────────────────────────────────────────────────
-            CREATE TABLE IF NOT EXISTS chat_summary (
-                chat_id            TEXT PRIMARY KEY,
-                current_session_id TEXT NOT NULL DEFAULT '',
-                summary            TEXT NOT NULL DEFAULT ''
-            );
-
-            -- ── Chat FTS5 ──────────────────────────────────────────────────────────
-            CREATE VIRTUAL TABLE IF NOT EXISTS chat_fts USING fts5(
-                content,
-                content=chat_history,
-                content_rowid=id
-            );
-
-            -- Triggers: keep chat_fts in sync with chat_history
-            CREATE TRIGGER IF NOT EXISTS chat_history_ai
-                AFTER INSERT ON chat_history BEGIN
-                    INSERT INTO chat_fts(rowid, content)
-                    VALUES (new.id, new.content);
-                END;
-            CREATE TRIGGER IF NOT EXISTS chat_history_ad
-                AFTER DELETE ON chat_history BEGIN
-                    INSERT INTO chat_fts(chat_fts, rowid, content)
-                    VALUES ('delete', old.id, old.content);
-                END;
-            CREATE TRIGGER IF NOT EXISTS chat_history_au
-                AFTER UPDATE ON chat_history BEGIN
-                    INSERT INTO chat_fts(chat_fts, rowid, content)
-                    VALUES ('delete', old.id, old.content);
-                    INSERT INTO chat_fts(rowid, content)
-                    VALUES (new.id, new.content);
-                END;
-
-            -- ── Vault index  ──────────────────────────────────────────────────────
-            CREATE TABLE IF NOT EXISTS vault_index (
-                filepath      TEXT    PRIMARY KEY,
-                content       TEXT,
-                last_modified INTEGER
-            );
-
-            -- ── Vault FTS5  ──────────────────────────────────────────────────────
-            CREATE VIRTUAL TABLE IF NOT EXISTS vault_fts USING fts5(
-                filepath, content,
-                content=vault_index,
-                content_rowid=rowid
-            );
-
-            -- Triggers: keep vault_fts in sync with vault_index
-            CREATE TRIGGER IF NOT EXISTS vault_index_ai
-                AFTER INSERT ON vault_index BEGIN
-                    INSERT INTO vault_fts(rowid, filepath, content)
-                    VALUES (new.rowid, new.filepath, new.content);
-                END;
-            CREATE TRIGGER IF NOT EXISTS vault_index_ad
-                AFTER DELETE ON vault_index BEGIN
-                    INSERT INTO vault_fts(vault_fts, rowid, filepath, content)
-                    VALUES ('delete', old.rowid, old.filepath, old.content);
-                END;
-            CREATE TRIGGER IF NOT EXISTS vault_index_au
-                AFTER UPDATE ON vault_index BEGIN
-                    INSERT INTO vault_fts(vault_fts, rowid, filepath, content)
-                    VALUES ('delete', old.rowid, old.filepath, old.content);
-                    INSERT INTO vault_fts(rowid, filepath, content)
-                    VALUES (new.rowid, new.filepath, new.content);
-                END;",
+            "PRAGMA mmap_size  = 0;
+             PRAGMA temp_store = MEMORY;",
         )?;
+        Ok(conn)
+    }
 
-        // ── Schema migrations (backward-compatible) ──────────────────────────
-        // Add session_id to chat_history for databases created before this column existed.
-        let has_session_id: bool = {
-            let mut stmt = conn.prepare("PRAGMA table_info(chat_history)")?;
-            stmt.query_map([], |row| row.get::<_, String>(1))?
-                .any(|r| r.map(|n| n == "session_id").unwrap_or(false))
-        };
-        if !has_session_id {
-            conn.execute_batch(
-                "ALTER TABLE chat_history ADD COLUMN session_id TEXT NOT NULL DEFAULT '';",
-            )?;
+    /// A free reader connection, or the next one in turn when all are busy.
+    fn reader(&self) -> Result<MutexGuard<'_, Connection>, DbError> {
+        let start = self.next_reader.fetch_add(1, Ordering::Relaxed);
+        for i in 0..self.readers.len() {
+            match self.readers[(start + i) % self.readers.len()].try_lock() {
+                Ok(conn) => return Ok(conn),
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Poisoned(e)) => return Err(DbError(format!("lock: {e}"))),
+            }
         }
+        self.readers[start % self.readers.len()]
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))
+    }
 
-        // Add current_session_id to chat_summary for older databases.
-        let has_current_session_id: bool = {
-            let mut stmt = conn.prepare("PRAGMA table_info(chat_summary)")?;
-            stmt.query_map([], |row| row.get::<_, String>(1))?
-                .any(|r| r.map(|n| n == "current_session_id").unwrap_or(false))
-        };
-        if !has_current_session_id {
-            conn.execute_batch(
-                "ALTER TABLE chat_summary ADD COLUMN current_session_id TEXT NOT NULL DEFAULT '';",
-            )?;
+    // -----------------------------------------------------------------------
+    // Encryption at rest
+    // -----------------------------------------------------------------------
+
+    /// Open `path` with a SQLCipher key, encrypting a plaintext database first if needed.
+    fn open_encrypted(path: &Path, key: &str) -> Result<Connection, DbError> {
+        if !cfg!(feature = "encryption") {
+            return Err(DbError(
+                "database encryption-key is set but icrab was built without the \
+                 `encryption` feature (cargo build --features encryption)"
+                    .into(),
+            ));
+        }
+        let conn = Self::connect_keyed(path, Some(key))?;
+        if Self::readable(&conn) {
+            return Ok(conn);
+        }
+        drop(conn);
+
+        // Not readable with the key: either a wrong key or a database from before encryption.
+        let plain = Self::connect_keyed(path, None)?;
+        if !Self::readable(&plain) {
+            return Err(DbError(format!(
+                "cannot decrypt {} (wrong encryption key?)",
+                path.display()
+            )));
+        }
+        Self::encrypt_plaintext(plain, path, key)?;
+        let conn = Self::connect_keyed(path, Some(key))?;
+        if !Self::readable(&conn) {
+            return Err(DbError(format!(
+                "cannot read {} after encrypting",
+                path.display()
+            )));
+        }
+        Ok(conn)
+    }
+
+    /// Open `path`, applying `key` (SQLCipher `PRAGMA key`) when given.
+    fn connect_keyed(path: &Path, key: Option<&str>) -> Result<Connection, DbError> {
+        let conn =
+            Connection::open(path).map_err(|e| DbError(format!("open {}: {e}", path.display())))?;
+        if let Some(key) = key {
+            conn.pragma_update(None, "key", key)?;
         }
+        Ok(conn)
+    }
 
-        // Compound index used by session-scoped queries; safe to create once columns exist.
-        conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_chat_history_chat_session
-                 ON chat_history(chat_id, session_id, id);",
-        )?;
+    /// A wrong key only shows up on the first read of the schema.
+    fn readable(conn: &Connection) -> bool {
+        conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .is_ok()
+    }
 
+    /// Copy the plaintext database into an encrypted file with `sqlcipher_export`, then
+    /// move it over the original.
+    fn encrypt_plaintext(plain: Connection, path: &Path, key: &str) -> Result<(), DbError> {
+        let tmp = path.with_extension("db.encrypting");
+        let _ = std::fs::remove_file(&tmp);
+        let tmp_str = tmp
+            .to_str()
+            .ok_or_else(|| DbError(format!("non-UTF-8 path {}", tmp.display())))?;
+        plain.execute(
+            "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+            params![tmp_str, key],
+        )?;
+        plain.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))?;
+        plain.execute_batch("DETACH DATABASE encrypted")?;
+        drop(plain);
+        std::fs::rename(&tmp, path)
+            .map_err(|e| DbError(format!("replace {}: {e}", path.display())))?;
+        eprintln!("brain db: encrypted existing database {}", path.display());
         Ok(())
     }
 
@@ -211,29 +271,158 @@ impl BrainDb {
     // Chat history operations
     // -----------------------------------------------------------------------
 
-    /// Force-rotate to a brand-new session for `chat_id`.
+    /// Force-rotate to a brand-new session for `chat_id`; returns its id.
     ///
     /// Generates a fresh UUID, stores it as `current_session_id` in
     /// `chat_summary`, and resets `summary` to `""`.  Old messages remain
-    /// intact in `chat_history` under their previous `session_id`.
-    pub fn reset_session_id(&self, chat_id: &str) -> Result<String, DbError> {
-        let conn = self
+    /// intact in `chat_history` under their previous `session_id`, and the old
+    /// summary is archived so [`resume_session`](Self::resume_session) can restore it.
+    pub fn start_new_session(&self, chat_id: &str) -> Result<String, DbError> {
+        let mut conn = self
             .conn
             .lock()
             .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        archive_current_session(&tx, chat_id)?;
 
         let new_id = uuid::Uuid::new_v4().to_string();
-        conn.execute(
+        tx.execute(
             "INSERT INTO chat_summary (chat_id, current_session_id, summary)
              VALUES (?1, ?2, '')
              ON CONFLICT(chat_id) DO UPDATE
                  SET current_session_id = excluded.current_session_id,
-                     summary            = ''",
+                     summary            = '',
+                     folded_through     = 0",
             params![chat_id, &new_id],
         )?;
+        tx.commit()?;
         Ok(new_id)
     }
 
+    /// Make `session_id` the active session of `chat_id` again, archiving the current one
+    /// and restoring the resumed session's summary. Returns false when `chat_id` has no
+    /// such session.
+    pub fn resume_session(&self, chat_id: &str, session_id: &str) -> Result<bool, DbError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM chat_history WHERE chat_id = ?1 AND session_id = ?2)
+                 OR EXISTS (SELECT 1 FROM session_archive WHERE chat_id = ?1 AND session_id = ?2)",
+            params![chat_id, session_id],
+            |row| row.get(0),
+        )?;
+        if !exists || session_id.is_empty() {
+            return Ok(false);
+        }
+
+        let tx = conn.transaction()?;
+        archive_current_session(&tx, chat_id)?;
+        tx.execute(
+            "INSERT INTO chat_summary (chat_id, current_session_id, summary, folded_through)
+             SELECT ?1, ?2, COALESCE(a.summary, ''), COALESCE(a.folded_through, 0)
+             FROM (SELECT 1) LEFT JOIN session_archive a
+                 ON a.chat_id = ?1 AND a.session_id = ?2
+             WHERE true
+             ON CONFLICT(chat_id) DO UPDATE
+                 SET current_session_id = excluded.current_session_id,
+                     summary            = excluded.summary,
+                     folded_through     = excluded.folded_through",
+            params![chat_id, session_id],
+        )?;
+        tx.execute(
+            "DELETE FROM session_archive WHERE chat_id = ?1 AND session_id = ?2",
+            params![chat_id, session_id],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Sessions of `chat_id`, most recently active first; sessions pruned down to their
+    /// archived summary come last, with no messages.
+    pub fn list_sessions(&self, chat_id: &str) -> Result<Vec<SessionInfo>, DbError> {
+        let conn = self.reader()?;
+        let current: String = conn
+            .query_row(
+                "SELECT current_session_id FROM chat_summary WHERE chat_id = ?1",
+                params![chat_id],
+                |row| row.get(0),
+            )
+            .unwrap_or_default();
+
+        // Sessions whose messages were all pruned by retention live on in session_archive.
+        let mut stmt = conn.prepare(
+            "SELECT s.session_id, COUNT(h.id),
+                    COALESCE(MIN(h.timestamp), ''), COALESCE(MAX(h.timestamp), ''),
+                    COALESCE((
+                        SELECT f.content FROM chat_history f
+                        WHERE f.chat_id = ?1 AND f.session_id = s.session_id
+                          AND f.role = 'user'
+                        ORDER BY f.id ASC LIMIT 1
+                    ), '')
+             FROM (
+                 SELECT session_id FROM chat_history WHERE chat_id = ?1
+                 UNION
+                 SELECT session_id FROM session_archive WHERE chat_id = ?1
+             ) s
+             LEFT JOIN chat_history h ON h.chat_id = ?1 AND h.session_id = s.session_id
+             WHERE s.session_id != ''
+             GROUP BY s.session_id
+             ORDER BY MAX(h.id) DESC",
+        )?;
+        let rows = stmt.query_map(params![chat_id], |row| {
+            let session_id: String = row.get(0)?;
+            Ok(SessionInfo {
+                current: session_id == current,
+                session_id,
+                messages: row.get::<_, i64>(1)? as usize,
+                started_at: row.get(2)?,
+                last_active: row.get(3)?,
+                first_message: row.get(4)?,
+            })
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    /// The `limit` sessions of `chat_id` most recently rotated away from (by `/clear` or a
+    /// resume), newest first, with their summaries. Sessions with neither a summary nor a
+    /// user message are left out.
+    pub fn recent_archived_sessions(
+        &self,
+        chat_id: &str,
+        limit: usize,
+    ) -> Result<Vec<ArchivedSession>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT * FROM (
+                 SELECT a.session_id, a.archived_at, a.summary,
+                        COALESCE((
+                            SELECT f.content FROM chat_history f
+                            WHERE f.chat_id = a.chat_id AND f.session_id = a.session_id
+                              AND f.role = 'user'
+                            ORDER BY f.id ASC LIMIT 1
+                        ), '') AS first_message
+                 FROM session_archive a
+                 WHERE a.chat_id = ?1
+                   AND a.session_id NOT IN
+                       (SELECT current_session_id FROM chat_summary WHERE chat_id = ?1)
+             )
+             WHERE summary != '' OR first_message != ''
+             ORDER BY archived_at DESC, session_id ASC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![chat_id, limit as i64], |row| {
+            Ok(ArchivedSession {
+                session_id: row.get(0)?,
+                archived_at: row.get(1)?,
+                summary: row.get(2)?,
+                first_message: row.get(3)?,
+            })
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
     /// Return the active `session_id` UUID for `chat_id`, creating and
     /// persisting a new one if none exists yet.
     pub fn get_or_create_session_id(&self, chat_id: &str) -> Result<String, DbError> {
@@ -270,7 +459,9 @@ impl BrainDb {
     ///
     /// Unlike the old `save_session`, this never deletes existing rows —
     /// every call only INSERTs the provided messages. Older messages in the
-    /// same session remain intact and searchable via `chat_fts`.
+    /// same session remain intact and searchable via `chat_fts`. The summary is
+    /// only written while `session_id` is still the chat's current session, so a
+    /// turn that switched sessions mid-way leaves the new session's summary alone.
     pub fn append_session(
         &self,
         chat_id: &str,
@@ -304,7 +495,8 @@ impl BrainDb {
         conn.execute(
             "INSERT INTO chat_summary (chat_id, current_session_id, summary)
              VALUES (?1, ?2, ?3)
-             ON CONFLICT(chat_id) DO UPDATE SET summary = excluded.summary",
+             ON CONFLICT(chat_id) DO UPDATE SET summary = excluded.summary
+                 WHERE chat_summary.current_session_id = excluded.current_session_id",
             params![chat_id, session_id, summary],
         )?;
 
@@ -312,22 +504,52 @@ impl BrainDb {
         Ok(())
     }
 
-    /// Load messages for the active `session_id` and the chat summary.
-    /// Returns `(messages, summary)`. Unknown `chat_id` or `session_id` → empty vec and empty string.
-    pub fn load_session(
+    /// Mark the oldest `n` not-yet-folded messages of `session_id` as folded into the
+    /// summary, so [`load_session`](Self::load_session) no longer returns them.
+    /// No-op once `session_id` is no longer the chat's current session.
+    pub fn fold_session_messages(
         &self,
         chat_id: &str,
         session_id: &str,
-    ) -> Result<(Vec<StoredMessage>, String), DbError> {
+        n: usize,
+    ) -> Result<(), DbError> {
+        if n == 0 {
+            return Ok(());
+        }
         let conn = self
             .conn
             .lock()
             .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute(
+            "UPDATE chat_summary SET folded_through = COALESCE((
+                 SELECT MAX(id) FROM (
+                     SELECT id FROM chat_history
+                     WHERE chat_id = ?1 AND session_id = ?2 AND id > chat_summary.folded_through
+                     ORDER BY id ASC LIMIT ?3
+                 )
+             ), folded_through)
+             WHERE chat_id = ?1 AND current_session_id = ?2",
+            params![chat_id, session_id, n as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Load messages for the active `session_id` and the chat summary.
+    /// Returns `(messages, summary)`. Unknown `chat_id` or `session_id` → empty vec and empty string.
+    /// Messages already folded into the summary are skipped.
+    pub fn load_session(
+        &self,
+        chat_id: &str,
+        session_id: &str,
+    ) -> Result<(Vec<StoredMessage>, String), DbError> {
+        let conn = self.reader()?;
 
         let mut stmt = conn.prepare(
             "SELECT role, content, tool_call_id, tool_calls
              FROM chat_history
              WHERE chat_id = ?1 AND session_id = ?2
+               AND id > COALESCE(
+                   (SELECT folded_through FROM chat_summary WHERE chat_id = ?1), 0)
              ORDER BY id ASC",
         )?;
 
@@ -353,6 +575,210 @@ impl BrainDb {
         Ok((messages, summary))
     }
 
+    /// Delete the last `n` visible messages (user messages and non-empty assistant replies)
+    /// of the active session, together with any tool rows in between. Returns rows deleted.
+    pub fn delete_recent_messages(&self, chat_id: &str, n: usize) -> Result<usize, DbError> {
+        if n == 0 {
+            return Ok(0);
+        }
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+
+        let session_id: Option<String> = conn
+            .query_row(
+                "SELECT current_session_id FROM chat_summary WHERE chat_id = ?1",
+                params![chat_id],
+                |row| row.get(0),
+            )
+            .ok();
+        let Some(session_id) = session_id else {
+            return Ok(0);
+        };
+
+        // Oldest of the last n visible messages; everything from it onwards goes.
+        let cutoff: Option<i64> = conn.query_row(
+            "SELECT MIN(id) FROM (
+                 SELECT id FROM chat_history
+                 WHERE chat_id = ?1 AND session_id = ?2
+                   AND (role = 'user' OR (role = 'assistant' AND content != ''))
+                 ORDER BY id DESC
+                 LIMIT ?3
+             )",
+            params![chat_id, &session_id, n as i64],
+            |row| row.get(0),
+        )?;
+        let Some(cutoff) = cutoff else {
+            return Ok(0);
+        };
+        let deleted = conn.execute(
+            "DELETE FROM chat_history WHERE chat_id = ?1 AND session_id = ?2 AND id >= ?3",
+            params![chat_id, &session_id, cutoff],
+        )?;
+        Ok(deleted)
+    }
+
+    /// Messages of `chat_id` matching `filter` (dates, role, session), oldest first, including
+    /// those already folded into a summary. At most `limit` rows.
+    pub fn chat_messages(
+        &self,
+        chat_id: &str,
+        filter: &ChatSearchFilter,
+        limit: usize,
+    ) -> Result<Vec<ChatRecord>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT session_id, COALESCE(timestamp, ''), role, content, tool_call_id, tool_calls
+             FROM chat_history
+             WHERE chat_id = ?1
+               AND (?2 IS NULL OR date(timestamp) >= ?2)
+               AND (?3 IS NULL OR date(timestamp) < ?3)
+               AND (?4 IS NULL OR role = ?4)
+               AND (?5 IS NULL OR session_id = ?5)
+             ORDER BY id ASC
+             LIMIT ?6",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                chat_id,
+                filter.after,
+                filter.before,
+                filter.role,
+                filter.session_id,
+                limit as i64
+            ],
+            |row| {
+                Ok(ChatRecord {
+                    session_id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    message: StoredMessage {
+                        role: row.get(2)?,
+                        content: row.get(3)?,
+                        tool_call_id: row.get(4)?,
+                        tool_calls: row.get(5)?,
+                    },
+                })
+            },
+        )?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // Retention
+    // -----------------------------------------------------------------------
+
+    /// Every `chat_id` with stored history.
+    pub fn chats_with_history(&self) -> Result<Vec<String>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt =
+            conn.prepare("SELECT DISTINCT chat_id FROM chat_history ORDER BY chat_id")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    /// Messages of `chat_id` that `policy` says should go, oldest first: beyond the newest
+    /// `max_messages`, or older than `max_age_days`. Messages of the active session that are
+    /// not yet folded into its summary are never expired; they are the live context.
+    pub fn expired_messages(
+        &self,
+        chat_id: &str,
+        policy: &RetentionPolicy,
+    ) -> Result<Vec<ExpiredMessage>, DbError> {
+        if policy.max_messages.is_none() && policy.max_age_days.is_none() {
+            return Ok(Vec::new());
+        }
+        let conn = self.reader()?;
+        let age = policy.max_age_days.map(|d| format!("-{d} days"));
+        // OFFSET must be an integer; past the end the subquery is NULL and matches nothing.
+        let keep = policy.max_messages.map_or(i64::MAX, |n| n as i64);
+        let mut stmt = conn.prepare(
+            "SELECT h.id, h.session_id, h.role, h.content, h.tool_call_id, h.tool_calls,
+                    h.session_id = COALESCE(s.current_session_id, '')
+                        OR h.id <= COALESCE(a.folded_through, 0)
+             FROM chat_history h
+             LEFT JOIN chat_summary s ON s.chat_id = h.chat_id
+             LEFT JOIN session_archive a
+                 ON a.chat_id = h.chat_id AND a.session_id = h.session_id
+             WHERE h.chat_id = ?1
+               AND NOT (h.session_id = COALESCE(s.current_session_id, '')
+                        AND h.id > COALESCE(s.folded_through, 0))
+               AND ((?2 IS NOT NULL AND h.timestamp < datetime('now', ?2))
+                    OR h.id <= (SELECT id FROM chat_history
+                                WHERE chat_id = ?1 ORDER BY id DESC LIMIT 1 OFFSET ?3))
+             ORDER BY h.id ASC",
+        )?;
+        let rows = stmt.query_map(params![chat_id, age, keep], |row| {
+            Ok(ExpiredMessage {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                message: StoredMessage {
+                    role: row.get(2)?,
+                    content: row.get(3)?,
+                    tool_call_id: row.get(4)?,
+                    tool_calls: row.get(5)?,
+                },
+                summarized: row.get(6)?,
+            })
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    /// Summary of an archived (not active) session; empty when it has none.
+    pub fn archived_summary(&self, chat_id: &str, session_id: &str) -> Result<String, DbError> {
+        let conn = self.reader()?;
+        Ok(conn
+            .query_row(
+                "SELECT summary FROM session_archive WHERE chat_id = ?1 AND session_id = ?2",
+                params![chat_id, session_id],
+                |row| row.get(0),
+            )
+            .unwrap_or_default())
+    }
+
+    /// Store `summary` for an archived session, covering messages up to `folded_through`.
+    pub fn set_archived_summary(
+        &self,
+        chat_id: &str,
+        session_id: &str,
+        summary: &str,
+        folded_through: i64,
+    ) -> Result<(), DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute(
+            "INSERT INTO session_archive
+                 (chat_id, session_id, summary, folded_through, archived_at)
+             VALUES (?1, ?2, ?3, ?4, strftime('%s','now'))
+             ON CONFLICT(chat_id, session_id) DO UPDATE
+                 SET summary        = excluded.summary,
+                     folded_through = MAX(folded_through, excluded.folded_through)",
+            params![chat_id, session_id, summary, folded_through],
+        )?;
+        Ok(())
+    }
+
+    /// Delete the given `chat_history` rows of `chat_id`. Archived summaries are kept, so a
+    /// session pruned to nothing can still be resumed. Returns rows deleted.
+    pub fn delete_messages(&self, chat_id: &str, ids: &[i64]) -> Result<usize, DbError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        let mut deleted = 0;
+        for id in ids {
+            deleted += tx.execute(
+                "DELETE FROM chat_history WHERE chat_id = ?1 AND id = ?2",
+                params![chat_id, id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(deleted)
+    }
+
     /// Health check: execute a trivial query.
     pub fn health_check(&self) -> bool {
         self.conn
@@ -372,6 +798,18 @@ impl BrainDb {
         filepath: &str,
         content: &str,
         last_modified: i64,
+    ) -> Result<(), DbError> {
+        self.upsert_vault_note(filepath, content, last_modified, "{}")
+    }
+
+    /// [`upsert_vault_entry`](Self::upsert_vault_entry) with the note's frontmatter as a
+    /// JSON object (see [`frontmatter`](super::frontmatter)).
+    pub fn upsert_vault_note(
+        &self,
+        filepath: &str,
+        content: &str,
+        last_modified: i64,
+        frontmatter: &str,
     ) -> Result<(), DbError> {
         let conn = self
             .conn
@@ -379,20 +817,61 @@ impl BrainDb {
             .map_err(|e| DbError(format!("lock: {e}")))?;
 
         conn.execute(
-            "INSERT OR REPLACE INTO vault_index (filepath, content, last_modified)
-             VALUES (?1, ?2, ?3)",
-            params![filepath, content, last_modified],
+            "INSERT OR REPLACE INTO vault_index
+                 (filepath, content, last_modified, frontmatter, content_hash)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                filepath,
+                content,
+                last_modified,
+                frontmatter,
+                vault_content_hash(content, frontmatter)
+            ],
         )?;
         Ok(())
     }
 
-    /// Return the stored `last_modified` timestamp for a vault file, or `None`
-    /// if the file has not been indexed yet.
-    pub fn get_vault_last_modified(&self, filepath: &str) -> Result<Option<i64>, DbError> {
-        let conn = self
+    /// If `filepath` is stored with `content_hash` (see [`vault_content_hash`]), only move its
+    /// `last_modified` (and that of its embeddings, which stay valid) to `last_modified`,
+    /// leaving the note and its FTS entry untouched. Returns false when the entry is
+    /// missing or its content differs, i.e. the note needs a full upsert.
+    pub fn touch_vault_entry(
+        &self,
+        filepath: &str,
+        content_hash: &str,
+        last_modified: i64,
+    ) -> Result<bool, DbError> {
+        let mut conn = self
             .conn
             .lock()
             .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        let previous: i64 = match tx.query_row(
+            "SELECT last_modified FROM vault_index WHERE filepath = ?1 AND content_hash = ?2",
+            params![filepath, content_hash],
+            |row| row.get(0),
+        ) {
+            Ok(v) => v,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(false),
+            Err(e) => return Err(DbError(e.to_string())),
+        };
+        tx.execute(
+            "UPDATE vault_index SET last_modified = ?2 WHERE filepath = ?1",
+            params![filepath, last_modified],
+        )?;
+        tx.execute(
+            "UPDATE vault_embeddings SET last_modified = ?3
+             WHERE filepath = ?1 AND last_modified = ?2",
+            params![filepath, previous, last_modified],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Return the stored `last_modified` timestamp for a vault file, or `None`
+    /// if the file has not been indexed yet.
+    pub fn get_vault_last_modified(&self, filepath: &str) -> Result<Option<i64>, DbError> {
+        let conn = self.reader()?;
 
         match conn.query_row(
             "SELECT last_modified FROM vault_index WHERE filepath = ?1",
@@ -435,12 +914,24 @@ impl BrainDb {
         Ok(deleted)
     }
 
-    /// Return the filepaths of all entries currently in `vault_index`.
-    pub fn list_vault_filepaths(&self) -> Result<Vec<String>, DbError> {
+    /// Delete the entry for `filepath` and, if it was a directory, every entry below it.
+    /// Returns the number of rows deleted.
+    pub fn delete_vault_path(&self, filepath: &str) -> Result<usize, DbError> {
         let conn = self
             .conn
             .lock()
             .map_err(|e| DbError(format!("lock: {e}")))?;
+        let deleted = conn.execute(
+            "DELETE FROM vault_index
+             WHERE filepath = ?1 OR substr(filepath, 1, length(?1) + 1) = ?1 || '/'",
+            params![filepath],
+        )?;
+        Ok(deleted)
+    }
+
+    /// Return the filepaths of all entries currently in `vault_index`.
+    pub fn list_vault_filepaths(&self) -> Result<Vec<String>, DbError> {
+        let conn = self.reader()?;
         let mut stmt = conn.prepare("SELECT filepath FROM vault_index ORDER BY filepath ASC")?;
         let paths: Vec<String> = stmt
             .query_map([], |row| row.get(0))?
@@ -449,128 +940,1934 @@ impl BrainDb {
     }
 
     // -----------------------------------------------------------------------
-    // Vault FTS5 queries
+    // Vault tags
     // -----------------------------------------------------------------------
 
-    /// Count documents whose `vault_fts` entry matches `fts_query` (FTS5
-    /// syntax, e.g. `"\"squats\""` for exact-phrase match).
-    ///
-    /// Useful for diagnostics, testing, and the search tool.
-    pub fn vault_fts_count(&self, fts_query: &str) -> Result<usize, DbError> {
-        let conn = self
+    /// Replace the tags of `filepath` (normalised, see [`tags`](super::tags)).
+    pub fn set_vault_tags(&self, filepath: &str, tags: &[String]) -> Result<(), DbError> {
+        let mut conn = self
             .conn
             .lock()
             .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM vault_tags WHERE filepath = ?1",
+            params![filepath],
+        )?;
+        for tag in tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO vault_tags (filepath, tag) VALUES (?1, ?2)",
+                params![filepath, tag],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
 
-        let count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM vault_fts WHERE vault_fts MATCH ?1",
-                params![fts_query],
-                |row| row.get::<_, i64>(0),
-            )
-            .map_err(DbError::from)?;
+    /// Every tag in the vault with the number of notes carrying it, most used first.
+    pub fn vault_tag_counts(&self) -> Result<Vec<(String, usize)>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT tag, COUNT(*) AS n FROM vault_tags
+             GROUP BY tag ORDER BY n DESC, tag ASC",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
 
-        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-        Ok(count as usize)
+    /// `(filepath, properties)` of notes matching `filter`, most recently modified first.
+    /// The second element lists the note's frontmatter fields and tags.
+    pub fn vault_notes_matching(
+        &self,
+        filter: &VaultFilter,
+        limit: usize,
+    ) -> Result<Vec<(String, String)>, DbError> {
+        let conn = self.reader()?;
+        let mut values = Vec::new();
+        let sql = format!(
+            "SELECT v.filepath, v.frontmatter,
+                    (SELECT group_concat(t.tag, ', ') FROM vault_tags t
+                     WHERE t.filepath = v.filepath)
+             FROM vault_index v
+             WHERE 1 {}
+             ORDER BY v.last_modified DESC, v.filepath ASC
+             LIMIT {limit}",
+            filter.sql(&mut values)
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(values), |row| {
+            let fp: String = row.get(0)?;
+            let frontmatter: String = row.get(1)?;
+            let tags: Option<String> = row.get(2)?;
+            Ok((fp, describe_note(&frontmatter, tags.as_deref())))
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
     }
 
-    /// Return the stored content of a single vault file, or `None` if not indexed.
-    pub fn get_vault_content(&self, filepath: &str) -> Result<Option<String>, DbError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|e| DbError(format!("lock: {e}")))?;
+    /// Filepaths of every note matching `filter`.
+    pub fn vault_paths_matching(
+        &self,
+        filter: &VaultFilter,
+    ) -> Result<std::collections::HashSet<String>, DbError> {
+        let conn = self.reader()?;
+        let mut values = Vec::new();
+        let sql = format!(
+            "SELECT v.filepath FROM vault_index v WHERE 1 {}",
+            filter.sql(&mut values)
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(values), |row| row.get(0))?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // Vault statistics
+    // -----------------------------------------------------------------------
+
+    /// Note count and indexed size of the vault, how many notes changed at or after `since`
+    /// (unix seconds), and the `limit` most recently modified and largest notes.
+    pub fn vault_stats(&self, since: i64, limit: usize) -> Result<VaultStats, DbError> {
+        let conn = self.reader()?;
+        let (notes, total_bytes, modified_since) = conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(length(CAST(content AS BLOB))), 0),
+                    COALESCE(SUM(last_modified >= ?1), 0)
+             FROM vault_index",
+            params![since],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as usize,
+                    row.get::<_, i64>(1)? as u64,
+                    row.get::<_, i64>(2)? as usize,
+                ))
+            },
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT filepath, last_modified FROM vault_index
+             WHERE last_modified >= ?1
+             ORDER BY last_modified DESC, filepath ASC LIMIT ?2",
+        )?;
+        let recent = stmt
+            .query_map(params![since, limit as i64], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT filepath, length(CAST(content AS BLOB)) AS size FROM vault_index
+             ORDER BY size DESC, filepath ASC LIMIT ?1",
+        )?;
+        let largest = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<Result<_, _>>()?;
+
+        Ok(VaultStats {
+            notes,
+            total_bytes,
+            modified_since,
+            recent,
+            largest,
+        })
+    }
+
+    /// `(filepath, content)` of every indexed note, ordered by path.
+    pub fn vault_contents(&self) -> Result<Vec<(String, String)>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt =
+            conn.prepare("SELECT filepath, content FROM vault_index ORDER BY filepath ASC")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // Vault FTS5 queries
+    // -----------------------------------------------------------------------
+
+    /// Count documents whose `vault_fts` entry matches `fts_query` (FTS5
+    /// syntax, e.g. `"\"squats\""` for exact-phrase match).
+    ///
+    /// Useful for diagnostics, testing, and the search tool.
+    pub fn vault_fts_count(&self, fts_query: &str) -> Result<usize, DbError> {
+        let conn = self.reader()?;
+
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM vault_fts WHERE vault_fts MATCH ?1",
+                params![fts_query],
+                |row| row.get::<_, i64>(0),
+            )
+            .map_err(DbError::from)?;
+
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        Ok(count as usize)
+    }
+
+    /// Return the stored content of a single vault file, or `None` if not indexed.
+    pub fn get_vault_content(&self, filepath: &str) -> Result<Option<String>, DbError> {
+        let conn = self.reader()?;
 
         match conn.query_row(
             "SELECT content FROM vault_index WHERE filepath = ?1",
             params![filepath],
             |row| row.get::<_, String>(0),
         ) {
-            Ok(c) => Ok(Some(c)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(DbError(e.to_string())),
+            Ok(c) => Ok(Some(c)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError(e.to_string())),
+        }
+    }
+
+    /// Return a BM25-ranked list of `(filepath, snippet)` pairs for `fts_query`.
+    ///
+    /// `snippet_col` is the FTS5 column index for `snippet()` (-1 = best).
+    /// Returns at most `limit` results.  This is the foundation for the
+    pub fn vault_fts_search(
+        &self,
+        fts_query: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, String)>, DbError> {
+        self.vault_fts_search_filtered(fts_query, &VaultFilter::default(), limit)
+    }
+
+    /// [`vault_fts_search`](Self::vault_fts_search) restricted to notes matching `filter`
+    /// (tags and frontmatter conditions).
+    pub fn vault_fts_search_filtered(
+        &self,
+        fts_query: &str,
+        filter: &VaultFilter,
+        limit: usize,
+    ) -> Result<Vec<(String, String)>, DbError> {
+        if fts_query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.reader()?;
+
+        let mut values = vec![SqlValue::Text(fts_query.to_string())];
+        let sql = format!(
+            "SELECT v.filepath, snippet(vault_fts, -1, '**', '**', '...', 10) AS snip
+             FROM vault_fts
+             JOIN vault_index v ON v.rowid = vault_fts.rowid
+             WHERE vault_fts MATCH ?1 {}
+             ORDER BY bm25(vault_fts)
+             LIMIT {limit}",
+            filter.sql(&mut values)
+        );
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(values), |row| {
+            let fp: String = row.get(0)?;
+            let sn: String = row.get(1)?;
+            Ok((fp, sn))
+        })?;
+
+        let results: Vec<(String, String)> = rows.collect::<Result<_, _>>()?;
+        Ok(results)
+    }
+
+    // -----------------------------------------------------------------------
+    // Vault embeddings
+    // -----------------------------------------------------------------------
+
+    /// `(filepath, content, last_modified)` of every indexed note whose embeddings are missing,
+    /// older than the note, or from a different `model`.
+    pub fn vault_files_needing_embeddings(
+        &self,
+        model: &str,
+    ) -> Result<Vec<(String, String, i64)>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT v.filepath, v.content, v.last_modified FROM vault_index v
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM vault_embeddings e
+                 WHERE e.filepath = v.filepath AND e.chunk = 0
+                   AND e.last_modified = v.last_modified AND e.model = ?1
+             )
+             ORDER BY v.filepath ASC",
+        )?;
+        let rows = stmt.query_map(params![model], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    /// Replace all stored chunks of `filepath` with `chunks` (text, vector), in one transaction.
+    pub fn replace_vault_embeddings(
+        &self,
+        filepath: &str,
+        last_modified: i64,
+        model: &str,
+        chunks: &[(String, Vec<f32>)],
+    ) -> Result<(), DbError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM vault_embeddings WHERE filepath = ?1",
+            params![filepath],
+        )?;
+        for (i, (content, vector)) in chunks.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO vault_embeddings
+                     (filepath, chunk, last_modified, model, content, embedding)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    filepath,
+                    i as i64,
+                    last_modified,
+                    model,
+                    content,
+                    vector_to_blob(vector)
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Delete embeddings of notes no longer in `vault_index` or computed with another model.
+    /// Returns the number of rows deleted.
+    pub fn prune_vault_embeddings(&self, model: &str) -> Result<usize, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let deleted = conn.execute(
+            "DELETE FROM vault_embeddings
+             WHERE model != ?1 OR filepath NOT IN (SELECT filepath FROM vault_index)",
+            params![model],
+        )?;
+        Ok(deleted)
+    }
+
+    /// Every stored chunk embedded with `model`.
+    pub fn vault_embeddings(&self, model: &str) -> Result<Vec<VaultEmbedding>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT filepath, chunk, content, embedding FROM vault_embeddings
+             WHERE model = ?1 ORDER BY filepath, chunk",
+        )?;
+        let rows = stmt.query_map(params![model], |row| {
+            let blob: Vec<u8> = row.get(3)?;
+            Ok(VaultEmbedding {
+                filepath: row.get(0)?,
+                chunk: row.get::<_, i64>(1)? as usize,
+                content: row.get(2)?,
+                embedding: blob_to_vector(&blob),
+            })
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    /// BM25-ranked keyword search over `chat_fts`.
+    ///
+    /// Returns at most `limit` triples of `(chat_id, role, snippet)`.
+    pub fn chat_fts_search(
+        &self,
+        fts_query: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, String, String)>, DbError> {
+        let hits = self.chat_fts_search_filtered(fts_query, &ChatSearchFilter::default(), limit)?;
+        Ok(hits
+            .into_iter()
+            .map(|h| (h.chat_id, h.role, h.snippet))
+            .collect())
+    }
+
+    /// [`chat_fts_search`](Self::chat_fts_search) restricted by `filter` (date range, role,
+    /// session). Returns at most `limit` hits.
+    pub fn chat_fts_search_filtered(
+        &self,
+        fts_query: &str,
+        filter: &ChatSearchFilter,
+        limit: usize,
+    ) -> Result<Vec<ChatSearchHit>, DbError> {
+        if fts_query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.reader()?;
+
+        #[allow(clippy::cast_possible_wrap)]
+        let limit_i64 = limit as i64;
+
+        let mut stmt = conn.prepare(
+            "SELECT h.chat_id, h.session_id, h.role, COALESCE(h.timestamp, ''),
+                    snippet(chat_fts, 0, '**', '**', '...', 10) AS snip
+             FROM chat_fts
+             JOIN chat_history h ON h.id = chat_fts.rowid
+             WHERE chat_fts MATCH ?1
+               AND (?3 IS NULL OR date(h.timestamp) >= ?3)
+               AND (?4 IS NULL OR date(h.timestamp) < ?4)
+               AND (?5 IS NULL OR h.role = ?5)
+               AND (?6 IS NULL OR h.session_id = ?6)
+             ORDER BY bm25(chat_fts)
+             LIMIT ?2",
+        )?;
+
+        let rows = stmt.query_map(
+            params![
+                fts_query,
+                limit_i64,
+                filter.after,
+                filter.before,
+                filter.role,
+                filter.session_id
+            ],
+            |row| {
+                Ok(ChatSearchHit {
+                    chat_id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    role: row.get(2)?,
+                    timestamp: row.get(3)?,
+                    snippet: row.get(4)?,
+                })
+            },
+        )?;
+
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // Chat location
+    // -----------------------------------------------------------------------
+
+    /// Store the latest coordinates shared in `chat_id`, replacing any previous fix.
+    pub fn set_chat_location(
+        &self,
+        chat_id: &str,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<(), DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute(
+            "INSERT INTO chat_location (chat_id, latitude, longitude, updated_at)
+             VALUES (?1, ?2, ?3, strftime('%s','now'))
+             ON CONFLICT(chat_id) DO UPDATE
+                 SET latitude   = excluded.latitude,
+                     longitude  = excluded.longitude,
+                     updated_at = excluded.updated_at",
+            params![chat_id, latitude, longitude],
+        )?;
+        Ok(())
+    }
+
+    /// Latest coordinates shared in `chat_id`, if any.
+    pub fn get_chat_location(&self, chat_id: &str) -> Result<Option<ChatLocation>, DbError> {
+        let conn = self.reader()?;
+        let result = conn.query_row(
+            "SELECT latitude, longitude, updated_at FROM chat_location WHERE chat_id = ?1",
+            params![chat_id],
+            |row| {
+                Ok(ChatLocation {
+                    latitude: row.get(0)?,
+                    longitude: row.get(1)?,
+                    updated_at: row.get(2)?,
+                })
+            },
+        );
+        match result {
+            Ok(loc) => Ok(Some(loc)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Runtime allow-list
+    // -----------------------------------------------------------------------
+
+    /// Persist a runtime-allowed Telegram user. Returns false if already present.
+    pub fn add_allowed_user(&self, user_id: i64) -> Result<bool, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let n = conn.execute(
+            "INSERT OR IGNORE INTO allowed_users (user_id, added_at)
+             VALUES (?1, strftime('%s','now'))",
+            params![user_id],
+        )?;
+        Ok(n > 0)
+    }
+
+    /// Remove a runtime-allowed user. Returns false if not present.
+    pub fn remove_allowed_user(&self, user_id: i64) -> Result<bool, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let n = conn.execute(
+            "DELETE FROM allowed_users WHERE user_id = ?1",
+            params![user_id],
+        )?;
+        Ok(n > 0)
+    }
+
+    /// All runtime-allowed users, ascending.
+    pub fn list_allowed_users(&self) -> Result<Vec<i64>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare("SELECT user_id FROM allowed_users ORDER BY user_id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, i64>(0))?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // LLM usage
+    // -----------------------------------------------------------------------
+
+    /// Store the token usage of one LLM call.
+    pub fn record_llm_usage(&self, usage: &LlmUsage) -> Result<(), DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute(
+            "INSERT INTO llm_usage
+                 (created_at, chat_id, source, model, prompt_tokens, completion_tokens, cost_usd)
+             VALUES (strftime('%s','now'), ?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                usage.chat_id,
+                usage.source,
+                usage.model,
+                usage.prompt_tokens as i64,
+                usage.completion_tokens as i64,
+                usage.cost_usd,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Usage since `since` (unix seconds), grouped by chat or source, most expensive
+    /// (then most tokens) first.
+    pub fn llm_usage_totals(
+        &self,
+        since: i64,
+        group_by: UsageGroupBy,
+    ) -> Result<Vec<UsageTotals>, DbError> {
+        let conn = self.reader()?;
+        let column = match group_by {
+            UsageGroupBy::Chat => "chat_id",
+            UsageGroupBy::Source => "source",
+            UsageGroupBy::Model => "model",
+        };
+        let sql = format!(
+            "SELECT {column}, COUNT(*), SUM(prompt_tokens), SUM(completion_tokens), SUM(cost_usd)
+             FROM llm_usage
+             WHERE created_at >= ?1
+             GROUP BY {column}
+             ORDER BY SUM(cost_usd) DESC, SUM(prompt_tokens) + SUM(completion_tokens) DESC"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok(UsageTotals {
+                key: row.get(0)?,
+                calls: row.get::<_, i64>(1)? as u64,
+                prompt_tokens: row.get::<_, i64>(2)? as u64,
+                completion_tokens: row.get::<_, i64>(3)? as u64,
+                cost_usd: row.get(4)?,
+            })
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // Long-term memories
+    // -----------------------------------------------------------------------
+
+    /// Store a fact; returns its id.
+    pub fn save_memory(&self, fact: &str, category: &str, source: &str) -> Result<i64, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute(
+            "INSERT INTO memories (fact, category, source, created_at)
+             VALUES (?1, ?2, ?3, strftime('%s','now'))",
+            params![fact, category, source],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Delete a fact by id. Returns false when no such fact exists.
+    pub fn forget_memory(&self, id: i64) -> Result<bool, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        Ok(conn.execute("DELETE FROM memories WHERE id = ?1", params![id])? > 0)
+    }
+
+    /// Facts matching any word of `query` (BM25-ranked), or the newest facts when `query`
+    /// has no searchable words. At most `limit` results.
+    pub fn search_memories(&self, query: &str, limit: usize) -> Result<Vec<MemoryFact>, DbError> {
+        let conn = self.reader()?;
+        let fts_query = any_word_query(query);
+        let mut stmt;
+        let rows = if fts_query.is_empty() {
+            stmt = conn.prepare(
+                "SELECT id, fact, category, source, created_at FROM memories
+                 ORDER BY id DESC LIMIT ?1",
+            )?;
+            stmt.query_map(params![limit as i64], memory_from_row)?
+        } else {
+            stmt = conn.prepare(
+                "SELECT m.id, m.fact, m.category, m.source, m.created_at
+                 FROM memories_fts JOIN memories m ON m.id = memories_fts.rowid
+                 WHERE memories_fts MATCH ?1
+                 ORDER BY bm25(memories_fts) LIMIT ?2",
+            )?;
+            stmt.query_map(params![fts_query, limit as i64], memory_from_row)?
+        };
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    /// Facts to show the model for a message: those matching `text`, topped up with the
+    /// newest ones, `limit` in total.
+    pub fn relevant_memories(&self, text: &str, limit: usize) -> Result<Vec<MemoryFact>, DbError> {
+        let mut facts = if any_word_query(text).is_empty() {
+            Vec::new()
+        } else {
+            self.search_memories(text, limit)?
+        };
+        if facts.len() < limit {
+            for fact in self.search_memories("", limit)? {
+                if facts.len() >= limit {
+                    break;
+                }
+                if !facts.iter().any(|f| f.id == fact.id) {
+                    facts.push(fact);
+                }
+            }
+        }
+        Ok(facts)
+    }
+
+    // -----------------------------------------------------------------------
+    // Entities
+    // -----------------------------------------------------------------------
+
+    /// Create or update the entity called `name` (or known by it as an alias). Non-empty
+    /// `kind` and `notes` replace the stored ones; `aliases` are added. Returns its id.
+    pub fn save_entity(
+        &self,
+        name: &str,
+        kind: &str,
+        notes: &str,
+        aliases: &[String],
+    ) -> Result<i64, DbError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        let id = match entity_id(&tx, name)? {
+            Some(id) => {
+                tx.execute(
+                    "UPDATE entities
+                     SET kind = CASE WHEN ?2 = '' THEN kind ELSE ?2 END,
+                         notes = CASE WHEN ?3 = '' THEN notes ELSE ?3 END,
+                         updated_at = strftime('%s','now')
+                     WHERE id = ?1",
+                    params![id, kind, notes],
+                )?;
+                id
+            }
+            None => insert_entity(&tx, name, kind, notes)?,
+        };
+        for alias in aliases.iter().map(|a| a.trim()) {
+            if !alias.is_empty() && !alias.eq_ignore_ascii_case(name) {
+                tx.execute(
+                    "INSERT OR IGNORE INTO entity_aliases (entity_id, alias) VALUES (?1, ?2)",
+                    params![id, alias],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Record that `from` `relation` `to` ("Priya" "works on" "Project Falcon"), creating
+    /// either entity if it is not known yet.
+    pub fn relate_entities(&self, from: &str, relation: &str, to: &str) -> Result<(), DbError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        let from_id = match entity_id(&tx, from)? {
+            Some(id) => id,
+            None => insert_entity(&tx, from, "", "")?,
+        };
+        let to_id = match entity_id(&tx, to)? {
+            Some(id) => id,
+            None => insert_entity(&tx, to, "", "")?,
+        };
+        tx.execute(
+            "INSERT OR IGNORE INTO entity_relations (from_id, relation, to_id)
+             VALUES (?1, ?2, ?3)",
+            params![from_id, relation, to_id],
+        )?;
+        tx.execute(
+            "UPDATE entities SET updated_at = strftime('%s','now') WHERE id IN (?1, ?2)",
+            params![from_id, to_id],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Delete an entity by name or alias, with its aliases and relations. Returns false when
+    /// no such entity exists.
+    pub fn forget_entity(&self, name: &str) -> Result<bool, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let Some(id) = entity_id(&conn, name)? else {
+            return Ok(false);
+        };
+        Ok(conn.execute("DELETE FROM entities WHERE id = ?1", params![id])? > 0)
+    }
+
+    /// The entity called `name` (or known by it as an alias).
+    pub fn find_entity(&self, name: &str) -> Result<Option<Entity>, DbError> {
+        let conn = self.reader()?;
+        match entity_id(&conn, name)? {
+            Some(id) => Ok(Some(load_entity(&conn, id)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The most recently updated entities, at most `limit`.
+    pub fn list_entities(&self, limit: usize) -> Result<Vec<Entity>, DbError> {
+        let conn = self.reader()?;
+        let ids: Vec<i64> = {
+            let mut stmt =
+                conn.prepare("SELECT id FROM entities ORDER BY updated_at DESC, id DESC LIMIT ?1")?;
+            stmt.query_map(params![limit as i64], |row| row.get(0))?
+                .collect::<Result<_, _>>()?
+        };
+        ids.into_iter().map(|id| load_entity(&conn, id)).collect()
+    }
+
+    /// Entities whose name or an alias appears in `text` as a whole word (case-insensitive),
+    /// in order of first mention. At most `limit`.
+    pub fn mentioned_entities(&self, text: &str, limit: usize) -> Result<Vec<Entity>, DbError> {
+        let conn = self.reader()?;
+        let labels: Vec<(i64, String)> = {
+            let mut stmt = conn.prepare(
+                "SELECT id, name FROM entities
+                 UNION ALL SELECT entity_id, alias FROM entity_aliases",
+            )?;
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<_, _>>()?
+        };
+        let text = text.to_lowercase();
+        let mut found: Vec<(usize, i64)> = labels
+            .iter()
+            .filter_map(|(id, label)| {
+                mention_position(&text, &label.to_lowercase()).map(|p| (p, *id))
+            })
+            .collect();
+        found.sort_unstable();
+        let mut ids: Vec<i64> = Vec::new();
+        for (_, id) in found {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids.truncate(limit);
+        ids.into_iter().map(|id| load_entity(&conn, id)).collect()
+    }
+
+    // -----------------------------------------------------------------------
+    // LLM response cache
+    // -----------------------------------------------------------------------
+
+    /// Cached response JSON for `key` if stored less than `ttl_secs` ago.
+    pub fn llm_cache_get(&self, key: &str, ttl_secs: u64) -> Result<Option<String>, DbError> {
+        let conn = self.reader()?;
+        let result = conn.query_row(
+            "SELECT response FROM llm_cache
+             WHERE key = ?1 AND created_at > strftime('%s','now') - ?2",
+            params![key, ttl_secs as i64],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(response) => Ok(Some(response)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    /// Store (or refresh) a cached response and drop entries older than `ttl_secs`.
+    pub fn llm_cache_put(&self, key: &str, response: &str, ttl_secs: u64) -> Result<(), DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute(
+            "DELETE FROM llm_cache WHERE created_at <= strftime('%s','now') - ?1",
+            params![ttl_secs as i64],
+        )?;
+        conn.execute(
+            "INSERT OR REPLACE INTO llm_cache (key, response, created_at)
+             VALUES (?1, ?2, strftime('%s','now'))",
+            params![key, response],
+        )?;
+        Ok(())
+    }
+
+    // -----------------------------------------------------------------------
+    // Seen feed items
+    // -----------------------------------------------------------------------
+
+    /// Which of `guids` have not been seen for `feed` yet, in the given order. With `mark`,
+    /// they are recorded as seen, and records older than [`FEED_SEEN_KEEP_SECS`] are dropped.
+    pub fn feed_unseen(
+        &self,
+        feed: &str,
+        guids: &[String],
+        mark: bool,
+    ) -> Result<Vec<String>, DbError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        let mut unseen = Vec::new();
+        {
+            let mut exists = tx.prepare("SELECT 1 FROM feed_seen WHERE feed = ?1 AND guid = ?2")?;
+            let mut insert = tx.prepare(
+                "INSERT OR IGNORE INTO feed_seen (feed, guid, seen_at)
+                 VALUES (?1, ?2, strftime('%s','now'))",
+            )?;
+            for guid in guids {
+                if unseen.contains(guid) || exists.exists(params![feed, guid])? {
+                    continue;
+                }
+                if mark {
+                    insert.execute(params![feed, guid])?;
+                }
+                unseen.push(guid.clone());
+            }
+        }
+        if mark {
+            tx.execute(
+                "DELETE FROM feed_seen WHERE seen_at < strftime('%s','now') - ?1",
+                params![FEED_SEEN_KEEP_SECS],
+            )?;
+        }
+        tx.commit()?;
+        Ok(unseen)
+    }
+
+    // -----------------------------------------------------------------------
+    // Web fetch cache
+    // -----------------------------------------------------------------------
+
+    /// The cached page for `url`, however old.
+    pub fn web_cache_get(&self, url: &str) -> Result<Option<CachedPage>, DbError> {
+        let conn = self.reader()?;
+        let result = conn.query_row(
+            "SELECT status, content_type, etag, last_modified, body, fetched_at
+             FROM web_cache WHERE url = ?1",
+            params![url],
+            |row| {
+                Ok(CachedPage {
+                    url: url.to_string(),
+                    status: row.get(0)?,
+                    content_type: row.get(1)?,
+                    etag: row.get(2)?,
+                    last_modified: row.get(3)?,
+                    body: row.get(4)?,
+                    fetched_at: row.get(5)?,
+                })
+            },
+        );
+        match result {
+            Ok(page) => Ok(Some(page)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    /// Store (or replace) a page, then drop pages older than [`WEB_CACHE_KEEP_SECS`] and the
+    /// least recently fetched ones beyond [`WEB_CACHE_MAX_BYTES`].
+    pub fn web_cache_put(&self, page: &CachedPage) -> Result<(), DbError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO web_cache
+                 (url, status, content_type, etag, last_modified, body, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                page.url,
+                page.status,
+                page.content_type,
+                page.etag,
+                page.last_modified,
+                page.body,
+                page.fetched_at
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM web_cache WHERE fetched_at < ?1",
+            params![page.fetched_at - WEB_CACHE_KEEP_SECS],
+        )?;
+        tx.execute(
+            "DELETE FROM web_cache WHERE url IN (
+                 SELECT url FROM (
+                     SELECT url, SUM(length(body)) OVER (ORDER BY fetched_at DESC, url) AS total
+                     FROM web_cache
+                 ) WHERE total > ?1
+             )",
+            params![WEB_CACHE_MAX_BYTES],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record that the cached page for `url` was revalidated (HTTP 304) at `at`.
+    pub fn web_cache_touch(&self, url: &str, at: i64) -> Result<(), DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute(
+            "UPDATE web_cache SET fetched_at = ?2 WHERE url = ?1",
+            params![url, at],
+        )?;
+        Ok(())
+    }
+
+    // -----------------------------------------------------------------------
+    // Habits
+    // -----------------------------------------------------------------------
+
+    /// Start tracking a habit; `notes` replace the stored ones when it exists. Returns false
+    /// when it already existed.
+    pub fn define_habit(&self, name: &str, notes: &str) -> Result<bool, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let added = conn.execute(
+            "INSERT OR IGNORE INTO habits (name, notes, created_at)
+             VALUES (?1, ?2, strftime('%s','now'))",
+            params![name.trim(), notes],
+        )? > 0;
+        if !added && !notes.is_empty() {
+            conn.execute(
+                "UPDATE habits SET notes = ?2 WHERE name = ?1",
+                params![name.trim(), notes],
+            )?;
+        }
+        Ok(added)
+    }
+
+    /// Stop tracking a habit and drop its history. Returns false when there is no such habit.
+    pub fn remove_habit(&self, name: &str) -> Result<bool, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        Ok(conn.execute("DELETE FROM habits WHERE name = ?1", params![name.trim()])? > 0)
+    }
+
+    /// Mark the habit done (or, with `done` false, not done) on `day` ("YYYY-MM-DD").
+    /// None when there is no such habit, else whether anything changed.
+    pub fn set_habit_done(
+        &self,
+        name: &str,
+        day: &str,
+        done: bool,
+    ) -> Result<Option<bool>, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let id = match conn.query_row(
+            "SELECT id FROM habits WHERE name = ?1",
+            params![name.trim()],
+            |row| row.get::<_, i64>(0),
+        ) {
+            Ok(id) => id,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(DbError::from(e)),
+        };
+        let sql = if done {
+            "INSERT OR IGNORE INTO habit_checkins (habit_id, day) VALUES (?1, ?2)"
+        } else {
+            "DELETE FROM habit_checkins WHERE habit_id = ?1 AND day = ?2"
+        };
+        Ok(Some(conn.execute(sql, params![id, day])? > 0))
+    }
+
+    /// Every habit, oldest first, with the days it was done.
+    pub fn habits(&self) -> Result<Vec<Habit>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT h.name, h.notes, h.created_at,
+                    (SELECT group_concat(day, ',') FROM
+                        (SELECT day FROM habit_checkins c WHERE c.habit_id = h.id ORDER BY day))
+             FROM habits h ORDER BY h.created_at, h.id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let days: Option<String> = row.get(3)?;
+            Ok(Habit {
+                name: row.get(0)?,
+                notes: row.get(1)?,
+                created_at: row.get(2)?,
+                days: days
+                    .map(|d| d.split(',').map(str::to_string).collect())
+                    .unwrap_or_default(),
+            })
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // Workouts
+    // -----------------------------------------------------------------------
+
+    /// Store `sets` in order.
+    pub fn log_workout_sets(&self, sets: &[WorkoutSet]) -> Result<(), DbError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let tx = conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO workout_sets (day, exercise, reps, weight, unit, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, strftime('%s','now'))",
+            )?;
+            for set in sets {
+                insert.execute(params![
+                    set.day,
+                    set.exercise.trim(),
+                    set.reps,
+                    set.weight,
+                    set.unit
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Sets from `from` to `to` (inclusive "YYYY-MM-DD"), of one exercise or all, in the
+    /// order they were logged.
+    pub fn workout_sets(
+        &self,
+        exercise: Option<&str>,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<WorkoutSet>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT day, exercise, reps, weight, unit FROM workout_sets
+             WHERE day BETWEEN ?1 AND ?2 AND (?3 IS NULL OR exercise = ?3)
+             ORDER BY day, id",
+        )?;
+        let rows = stmt.query_map(params![from, to, exercise.map(str::trim)], |row| {
+            Ok(WorkoutSet {
+                day: row.get(0)?,
+                exercise: row.get(1)?,
+                reps: row.get(2)?,
+                weight: row.get(3)?,
+                unit: row.get(4)?,
+            })
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    /// The latest day on or before `day` with sets of `exercise` (or of anything).
+    pub fn last_workout_day(
+        &self,
+        exercise: Option<&str>,
+        day: &str,
+    ) -> Result<Option<String>, DbError> {
+        let conn = self.reader()?;
+        conn.query_row(
+            "SELECT MAX(day) FROM workout_sets
+             WHERE day <= ?1 AND (?2 IS NULL OR exercise = ?2)",
+            params![day, exercise.map(str::trim)],
+            |row| row.get(0),
+        )
+        .map_err(DbError::from)
+    }
+
+    /// Every exercise logged, with its number of sets, most sets first.
+    pub fn workout_exercises(&self) -> Result<Vec<(String, usize)>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT exercise, COUNT(*) AS n FROM workout_sets
+             GROUP BY exercise ORDER BY n DESC, exercise",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+        })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // Expenses
+    // -----------------------------------------------------------------------
+
+    /// Store an expense (its `id` is ignored); returns the new id.
+    pub fn add_expense(&self, expense: &Expense) -> Result<i64, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute(
+            "INSERT INTO expenses (day, amount, currency, category, note, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, strftime('%s','now'))",
+            params![
+                expense.day,
+                expense.amount,
+                expense.currency,
+                expense.category.trim(),
+                expense.note
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Delete an expense by id, returning it; None when there is no such expense.
+    pub fn remove_expense(&self, id: i64) -> Result<Option<Expense>, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let found = match conn.query_row(
+            "SELECT id, day, amount, currency, category, note FROM expenses WHERE id = ?1",
+            params![id],
+            expense_row,
+        ) {
+            Ok(e) => e,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(DbError::from(e)),
+        };
+        conn.execute("DELETE FROM expenses WHERE id = ?1", params![id])?;
+        Ok(Some(found))
+    }
+
+    /// Expenses from `from` to `to` (inclusive "YYYY-MM-DD"), optionally of one category,
+    /// by day then in the order logged.
+    pub fn expenses(
+        &self,
+        from: &str,
+        to: &str,
+        category: Option<&str>,
+    ) -> Result<Vec<Expense>, DbError> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, day, amount, currency, category, note FROM expenses
+             WHERE day BETWEEN ?1 AND ?2 AND (?3 IS NULL OR category = ?3)
+             ORDER BY day, id",
+        )?;
+        let rows = stmt.query_map(params![from, to, category.map(str::trim)], expense_row)?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // Bookmarks
+    // -----------------------------------------------------------------------
+
+    /// Save a bookmark (its `id` and `created_at` are ignored). A URL saved before is
+    /// updated in place and keeps its date. Returns the id and whether it is new.
+    pub fn save_bookmark(&self, bookmark: &Bookmark) -> Result<(i64, bool), DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let existing: Option<i64> = match conn.query_row(
+            "SELECT id FROM bookmarks WHERE url = ?1",
+            params![bookmark.url],
+            |row| row.get(0),
+        ) {
+            Ok(id) => Some(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(DbError::from(e)),
+        };
+        let tags = bookmark
+            .tags
+            .iter()
+            .map(|t| bookmark_tag(t))
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some(id) = existing {
+            conn.execute(
+                "UPDATE bookmarks SET title = ?2, summary = ?3, tags = ?4, note = ?5 WHERE id = ?1",
+                params![id, bookmark.title, bookmark.summary, tags, bookmark.note],
+            )?;
+            return Ok((id, false));
         }
+        conn.execute(
+            "INSERT INTO bookmarks (url, title, summary, tags, note, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, strftime('%s','now'))",
+            params![
+                bookmark.url,
+                bookmark.title,
+                bookmark.summary,
+                tags,
+                bookmark.note
+            ],
+        )?;
+        Ok((conn.last_insert_rowid(), true))
     }
 
-    /// Return a BM25-ranked list of `(filepath, snippet)` pairs for `fts_query`.
-    ///
-    /// `snippet_col` is the FTS5 column index for `snippet()` (-1 = best).
-    /// Returns at most `limit` results.  This is the foundation for the
-    pub fn vault_fts_search(
+    /// Delete a bookmark by id, returning it; None when there is no such bookmark.
+    pub fn remove_bookmark(&self, id: i64) -> Result<Option<Bookmark>, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        let found = match conn.query_row(
+            "SELECT id, url, title, summary, tags, note, created_at FROM bookmarks WHERE id = ?1",
+            params![id],
+            bookmark_row,
+        ) {
+            Ok(b) => b,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(DbError::from(e)),
+        };
+        conn.execute("DELETE FROM bookmarks WHERE id = ?1", params![id])?;
+        Ok(Some(found))
+    }
+
+    /// Bookmarks matching any word of `query` (title, summary, tags, note; best first), or
+    /// the newest when `query` has no searchable words. `tag` keeps only bookmarks with
+    /// that tag.
+    pub fn search_bookmarks(
         &self,
-        fts_query: &str,
+        query: &str,
+        tag: Option<&str>,
         limit: usize,
-    ) -> Result<Vec<(String, String)>, DbError> {
-        if fts_query.trim().is_empty() {
-            return Ok(Vec::new());
-        }
+    ) -> Result<Vec<Bookmark>, DbError> {
+        let conn = self.reader()?;
+        let fts_query = any_word_query(query);
+        let tag = tag.map(bookmark_tag);
+        let mut stmt;
+        let rows = if fts_query.is_empty() {
+            stmt = conn.prepare(
+                "SELECT id, url, title, summary, tags, note, created_at FROM bookmarks
+                 WHERE ?1 IS NULL OR (' ' || tags || ' ') LIKE ('% ' || ?1 || ' %')
+                 ORDER BY created_at DESC, id DESC LIMIT ?2",
+            )?;
+            stmt.query_map(params![tag, limit as i64], bookmark_row)?
+        } else {
+            stmt = conn.prepare(
+                "SELECT b.id, b.url, b.title, b.summary, b.tags, b.note, b.created_at
+                 FROM bookmarks_fts JOIN bookmarks b ON b.id = bookmarks_fts.rowid
+                 WHERE bookmarks_fts MATCH ?1
+                   AND (?2 IS NULL OR (' ' || b.tags || ' ') LIKE ('% ' || ?2 || ' %'))
+                 ORDER BY bm25(bookmarks_fts) LIMIT ?3",
+            )?;
+            stmt.query_map(params![fts_query, tag, limit as i64], bookmark_row)?
+        };
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
+
+    // -----------------------------------------------------------------------
+    // Cron runs
+    // -----------------------------------------------------------------------
 
+    /// Record one firing of a cron job (its `id` is ignored), keeping only the newest
+    /// [`CRON_RUNS_KEPT`] of that job.
+    pub fn record_cron_run(&self, run: &CronRun) -> Result<i64, DbError> {
         let conn = self
             .conn
             .lock()
             .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute(
+            "INSERT INTO cron_runs (job_id, ran_at, action, ok, detail)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![run.job_id, run.ran_at, run.action, run.ok, run.detail],
+        )?;
+        let id = conn.last_insert_rowid();
+        conn.execute(
+            "DELETE FROM cron_runs WHERE job_id = ?1 AND id NOT IN
+                 (SELECT id FROM cron_runs WHERE job_id = ?1 ORDER BY id DESC LIMIT ?2)",
+            params![run.job_id, CRON_RUNS_KEPT as i64],
+        )?;
+        Ok(id)
+    }
 
-        #[allow(clippy::cast_possible_wrap)]
-        let limit_i64 = limit as i64;
-
+    /// The latest `limit` runs of a job, newest first.
+    pub fn cron_runs(&self, job_id: &str, limit: usize) -> Result<Vec<CronRun>, DbError> {
+        let conn = self.reader()?;
         let mut stmt = conn.prepare(
-            "SELECT filepath, snippet(vault_fts, -1, '**', '**', '...', 10) AS snip
-             FROM vault_fts
-             WHERE vault_fts MATCH ?1
-             ORDER BY bm25(vault_fts)
-             LIMIT ?2",
+            "SELECT id, job_id, ran_at, action, ok, detail FROM cron_runs
+             WHERE job_id = ?1 ORDER BY id DESC LIMIT ?2",
         )?;
-
-        let rows = stmt.query_map(params![fts_query, limit_i64], |row| {
-            let fp: String = row.get(0)?;
-            let sn: String = row.get(1)?;
-            Ok((fp, sn))
+        let rows = stmt.query_map(params![job_id, limit as i64], |row| {
+            Ok(CronRun {
+                id: row.get(0)?,
+                job_id: row.get(1)?,
+                ran_at: row.get(2)?,
+                action: row.get(3)?,
+                ok: row.get(4)?,
+                detail: row.get(5)?,
+            })
         })?;
+        rows.collect::<Result<_, _>>().map_err(DbError::from)
+    }
 
-        let results: Vec<(String, String)> = rows.collect::<Result<_, _>>()?;
-        Ok(results)
+    // -----------------------------------------------------------------------
+    // Backup & restore
+    // -----------------------------------------------------------------------
+
+    /// Write a compacted, consistent copy of the database to `dest` (`VACUUM INTO`).
+    /// `dest` must not exist yet.
+    pub fn backup_to(&self, dest: &Path) -> Result<(), DbError> {
+        let dest = dest
+            .to_str()
+            .ok_or_else(|| DbError(format!("non-UTF-8 backup path {}", dest.display())))?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute("VACUUM INTO ?1", params![dest])?;
+        Ok(())
     }
 
-    /// BM25-ranked keyword search over `chat_fts`.
-    ///
-    /// Returns at most `limit` triples of `(chat_id, role, snippet)`.
-    pub fn chat_fts_search(
-        &self,
-        fts_query: &str,
-        limit: usize,
-    ) -> Result<Vec<(String, String, String)>, DbError> {
-        if fts_query.trim().is_empty() {
-            return Ok(Vec::new());
+    /// Replace the whole database with the contents of the backup at `src`, in place, so
+    /// every holder of this `BrainDb` sees the restored data. Migrations the backup predates
+    /// are applied afterwards.
+    pub fn restore_from(&self, src: &Path) -> Result<(), DbError> {
+        let source = Self::connect_keyed(src, self.key.as_deref())?;
+        if !Self::readable(&source) {
+            return Err(DbError(format!(
+                "{} is not a readable backup",
+                src.display()
+            )));
+        }
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        rusqlite::backup::Backup::new(&source, &mut conn)?
+            .run_to_completion(256, std::time::Duration::ZERO, None)
+            .map_err(|e| DbError(format!("restore {}: {e}", src.display())))?;
+        migrations::run(&conn)
+    }
+
+    // -----------------------------------------------------------------------
+    // Maintenance
+    // -----------------------------------------------------------------------
+
+    /// Copy the WAL back into the database file and truncate it to zero bytes
+    /// (`PRAGMA wal_checkpoint(TRUNCATE)`). Returns the number of frames the WAL held;
+    /// errors when a reader kept the WAL busy, so the next pass retries.
+    pub fn checkpoint(&self) -> Result<usize, DbError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| DbError(format!("lock: {e}")))?;
+        // TRUNCATE reports zero frames once it has emptied the log, so count them first.
+        let (_, frames) = wal_checkpoint(&conn, "PASSIVE")?;
+        let (busy, _) = wal_checkpoint(&conn, "TRUNCATE")?;
+        if busy {
+            return Err(DbError("wal checkpoint blocked by an active reader".into()));
         }
+        Ok(frames)
+    }
 
+    /// `PRAGMA optimize`, or a full `ANALYZE` of every table when `analyze` is set, so the
+    /// query planner's statistics follow the data.
+    pub fn optimize(&self, analyze: bool) -> Result<(), DbError> {
         let conn = self
             .conn
             .lock()
             .map_err(|e| DbError(format!("lock: {e}")))?;
+        conn.execute_batch(if analyze {
+            "ANALYZE; PRAGMA optimize;"
+        } else {
+            "PRAGMA optimize;"
+        })?;
+        Ok(())
+    }
+}
 
-        #[allow(clippy::cast_possible_wrap)]
-        let limit_i64 = limit as i64;
+/// `PRAGMA wal_checkpoint(mode)`: whether it was blocked, and the frames in the WAL.
+fn wal_checkpoint(conn: &Connection, mode: &str) -> Result<(bool, usize), DbError> {
+    let (busy, frames): (i64, i64) =
+        conn.query_row(&format!("PRAGMA wal_checkpoint({mode})"), [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+    Ok((busy != 0, frames.max(0) as usize))
+}
 
-        let mut stmt = conn.prepare(
-            "SELECT h.chat_id, h.role,
-                    snippet(chat_fts, 0, '**', '**', '...', 10) AS snip
-             FROM chat_fts
-             JOIN chat_history h ON h.id = chat_fts.rowid
-             WHERE chat_fts MATCH ?1
-             ORDER BY bm25(chat_fts)
-             LIMIT ?2",
-        )?;
+// ---------------------------------------------------------------------------
+// LLM usage
+// ---------------------------------------------------------------------------
 
-        let rows = stmt.query_map(params![fts_query, limit_i64], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-            ))
-        })?;
+/// One LLM call's token usage. `source` is what made the call ("telegram", "cron",
+/// "heartbeat", "subagent:<label>"); `cost_usd` is None when no prices are configured.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LlmUsage {
+    pub chat_id: String,
+    pub source: String,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: Option<f64>,
+}
 
-        rows.collect::<Result<_, _>>().map_err(DbError::from)
+/// Grouping for [`BrainDb::llm_usage_totals`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageGroupBy {
+    Chat,
+    Source,
+    Model,
+}
+
+/// Aggregated usage for one chat, source or model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageTotals {
+    pub key: String,
+    pub calls: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// None when none of the calls had a cost estimate.
+    pub cost_usd: Option<f64>,
+}
+
+// ---------------------------------------------------------------------------
+// Sessions
+// ---------------------------------------------------------------------------
+
+/// One conversation of a chat, as listed by [`BrainDb::list_sessions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub messages: usize,
+    /// `YYYY-MM-DD HH:MM:SS` (UTC) of the first and last message.
+    pub started_at: String,
+    pub last_active: String,
+    /// First user message, for telling sessions apart; may be empty.
+    pub first_message: String,
+    /// Whether this is the chat's active session.
+    pub current: bool,
+}
+
+/// A session rotated away from, as returned by [`BrainDb::recent_archived_sessions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedSession {
+    pub session_id: String,
+    /// Unix seconds.
+    pub archived_at: i64,
+    /// May be empty when the session was never summarized.
+    pub summary: String,
+    /// First user message; empty once retention pruned the session's messages.
+    pub first_message: String,
+}
+
+/// Save the summary state of `chat_id`'s current session to `session_archive`.
+fn archive_current_session(conn: &Connection, chat_id: &str) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO session_archive
+             (chat_id, session_id, summary, folded_through, archived_at)
+         SELECT chat_id, current_session_id, summary, folded_through, strftime('%s','now')
+         FROM chat_summary
+         WHERE chat_id = ?1 AND current_session_id != ''",
+        params![chat_id],
+    )?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Retention
+// ---------------------------------------------------------------------------
+
+/// Per-chat limits on stored history (`[retention]`); None means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub max_messages: Option<usize>,
+    pub max_age_days: Option<u64>,
+}
+
+/// A `chat_history` row selected for deletion by [`BrainDb::expired_messages`].
+#[derive(Debug, Clone)]
+pub struct ExpiredMessage {
+    pub id: i64,
+    pub session_id: String,
+    pub message: StoredMessage,
+    /// Already folded into its session's summary, so nothing is lost by deleting it.
+    pub summarized: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Chat search
+// ---------------------------------------------------------------------------
+
+/// Optional restrictions for [`BrainDb::chat_fts_search_filtered`] and
+/// [`BrainDb::chat_messages`]. Dates are `YYYY-MM-DD` (UTC, as stored): `after` is
+/// inclusive, `before` exclusive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChatSearchFilter {
+    pub after: Option<String>,
+    pub before: Option<String>,
+    /// "user", "assistant", "tool" or "system".
+    pub role: Option<String>,
+    pub session_id: Option<String>,
+}
+
+/// One matching chat message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatSearchHit {
+    pub chat_id: String,
+    pub session_id: String,
+    pub role: String,
+    /// `YYYY-MM-DD HH:MM:SS` (UTC).
+    pub timestamp: String,
+    pub snippet: String,
+}
+
+/// One stored message with where and when it was said ([`BrainDb::chat_messages`]).
+#[derive(Debug, Clone)]
+pub struct ChatRecord {
+    pub session_id: String,
+    /// `YYYY-MM-DD HH:MM:SS` (UTC).
+    pub timestamp: String,
+    pub message: StoredMessage,
+}
+
+// ---------------------------------------------------------------------------
+// Vault filters
+// ---------------------------------------------------------------------------
+
+/// Restricts vault searches to notes carrying every tag and meeting every frontmatter
+/// condition.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VaultFilter {
+    /// Normalised tags (see [`tags`](super::tags)); a tag also matches its nested tags
+    /// (`book` → `book/fiction`).
+    pub tags: Vec<String>,
+    pub fields: Vec<FieldFilter>,
+}
+
+impl VaultFilter {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.fields.is_empty()
+    }
+
+    /// ` AND …` conditions on the `vault_index` row aliased `v`. Their values are appended
+    /// to `values` and referenced by position, after any parameters already there.
+    fn sql(&self, values: &mut Vec<SqlValue>) -> String {
+        let mut sql = String::new();
+        for tag in &self.tags {
+            values.push(SqlValue::Text(tag.clone()));
+            let n = values.len();
+            sql.push_str(&format!(
+                " AND v.filepath IN (SELECT filepath FROM vault_tags
+                   WHERE tag = ?{n} OR substr(tag, 1, length(?{n}) + 1) = ?{n} || '/')"
+            ));
+        }
+        for field in &self.fields {
+            values.push(SqlValue::Text(field.json_path()));
+            let path = values.len();
+            match field.op {
+                FieldOp::Eq | FieldOp::Ne => {
+                    // json_each yields a scalar itself, or each item of a list.
+                    values.push(SqlValue::Text(field.value.to_lowercase()));
+                    let n = values.len();
+                    let not = if field.op == FieldOp::Ne { "NOT " } else { "" };
+                    sql.push_str(&format!(
+                        " AND {not}EXISTS (SELECT 1 FROM json_each(v.frontmatter, ?{path})
+                           WHERE lower(CAST(value AS TEXT)) = ?{n})"
+                    ));
+                }
+                op => {
+                    values.push(match field.value.parse::<f64>() {
+                        Ok(x) => SqlValue::Real(x),
+                        Err(_) => SqlValue::Text(field.value.clone()),
+                    });
+                    let n = values.len();
+                    sql.push_str(&format!(
+                        " AND json_extract(v.frontmatter, ?{path}) {} ?{n}",
+                        op.sql()
+                    ));
+                }
+            }
+        }
+        sql
+    }
+}
+
+/// Hex hash of a note's indexed text and frontmatter, stored as `vault_index.content_hash`.
+/// Tags are derived from the same text, so they are covered too.
+pub fn vault_content_hash(content: &str, frontmatter: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    frontmatter.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// `key: value; …; tags: a, b` for a listed note.
+fn describe_note(frontmatter: &str, tags: Option<&str>) -> String {
+    let fields: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(frontmatter).unwrap_or_default();
+    let mut parts: Vec<String> = fields
+        .iter()
+        .filter(|(k, _)| !matches!(k.as_str(), "tags" | "tag"))
+        .map(|(k, v)| match v {
+            serde_json::Value::String(s) => format!("{k}: {s}"),
+            other => format!("{k}: {other}"),
+        })
+        .collect();
+    if let Some(tags) = tags {
+        parts.push(format!("tags: {tags}"));
+    }
+    if parts.is_empty() {
+        "(no properties)".to_string()
+    } else {
+        parts.join("; ")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Vault statistics
+// ---------------------------------------------------------------------------
+
+/// Overview of the vault index ([`BrainDb::vault_stats`]). Sizes are of the indexed text
+/// (for CSV files, the preview), in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VaultStats {
+    pub notes: usize,
+    pub total_bytes: u64,
+    /// Notes modified in the requested window.
+    pub modified_since: usize,
+    /// `(filepath, last_modified)` of the most recently modified notes in the window.
+    pub recent: Vec<(String, i64)>,
+    /// `(filepath, bytes)`, largest first.
+    pub largest: Vec<(String, u64)>,
+}
+
+// ---------------------------------------------------------------------------
+// Vault embeddings
+// ---------------------------------------------------------------------------
+
+/// One embedded chunk of a vault note.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultEmbedding {
+    pub filepath: String,
+    /// Position of the chunk within the note (0-based).
+    pub chunk: usize,
+    pub content: String,
+    pub embedding: Vec<f32>,
+}
+
+/// Vectors are stored as little-endian `f32`s.
+fn vector_to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn blob_to_vector(blob: &[u8]) -> Vec<f32> {
+    let (words, _) = blob.as_chunks::<4>();
+    words.iter().map(|b| f32::from_le_bytes(*b)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Long-term memories
+// ---------------------------------------------------------------------------
+
+/// A fact about the user that outlives sessions ("I'm vegetarian").
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryFact {
+    pub id: i64,
+    pub fact: String,
+    /// Free-form grouping such as "preference" or "health"; may be empty.
+    pub category: String,
+    /// Where the fact came from, e.g. the chat id it was saved in.
+    pub source: String,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+fn memory_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<MemoryFact> {
+    Ok(MemoryFact {
+        id: row.get(0)?,
+        fact: row.get(1)?,
+        category: row.get(2)?,
+        source: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+/// FTS5 query matching any word (3+ characters) of `text`: `"a" OR "b"`. Empty when `text`
+/// has no such words. Quoting makes arbitrary user text safe to MATCH.
+fn any_word_query(text: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    for w in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+    {
+        let w = w.to_lowercase();
+        if !words.contains(&w) {
+            words.push(w);
+        }
+    }
+    words
+        .iter()
+        .map(|w| format!("\"{w}\""))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+// ---------------------------------------------------------------------------
+// Entities
+// ---------------------------------------------------------------------------
+
+/// A person, place, project or other thing the user talks about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    pub id: i64,
+    pub name: String,
+    /// Free-form type such as "person", "place" or "project"; may be empty.
+    pub kind: String,
+    pub notes: String,
+    pub aliases: Vec<String>,
+    /// Relations in both directions.
+    pub relations: Vec<EntityRelation>,
+}
+
+/// `from relation to`, with entity names ("Priya" "works on" "Project Falcon").
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityRelation {
+    pub from: String,
+    pub relation: String,
+    pub to: String,
+}
+
+/// Id of the entity named `name` or having it as an alias.
+fn entity_id(conn: &Connection, name: &str) -> Result<Option<i64>, DbError> {
+    match conn.query_row(
+        "SELECT id FROM entities WHERE name = ?1
+         UNION ALL SELECT entity_id FROM entity_aliases WHERE alias = ?1
+         LIMIT 1",
+        params![name.trim()],
+        |row| row.get(0),
+    ) {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(DbError(e.to_string())),
+    }
+}
+
+fn insert_entity(conn: &Connection, name: &str, kind: &str, notes: &str) -> Result<i64, DbError> {
+    conn.execute(
+        "INSERT INTO entities (name, kind, notes, updated_at)
+         VALUES (?1, ?2, ?3, strftime('%s','now'))",
+        params![name.trim(), kind, notes],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn load_entity(conn: &Connection, id: i64) -> Result<Entity, DbError> {
+    let (name, kind, notes): (String, String, String) = conn.query_row(
+        "SELECT name, kind, notes FROM entities WHERE id = ?1",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    let mut stmt =
+        conn.prepare("SELECT alias FROM entity_aliases WHERE entity_id = ?1 ORDER BY alias")?;
+    let aliases = stmt
+        .query_map(params![id], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    let mut stmt = conn.prepare(
+        "SELECT f.name, r.relation, t.name FROM entity_relations r
+         JOIN entities f ON f.id = r.from_id
+         JOIN entities t ON t.id = r.to_id
+         WHERE r.from_id = ?1 OR r.to_id = ?1
+         ORDER BY r.from_id != ?1, r.relation, t.name",
+    )?;
+    let relations = stmt
+        .query_map(params![id], |row| {
+            Ok(EntityRelation {
+                from: row.get(0)?,
+                relation: row.get(1)?,
+                to: row.get(2)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(Entity {
+        id,
+        name,
+        kind,
+        notes,
+        aliases,
+        relations,
+    })
+}
+
+/// Byte offset of the first whole-word occurrence of `label` in `text` (both lowercase).
+fn mention_position(text: &str, label: &str) -> Option<usize> {
+    if label.is_empty() {
+        return None;
     }
+    let mut start = 0;
+    while let Some(found) = text[start..].find(label) {
+        let at = start + found;
+        let end = at + label.len();
+        let before = text[..at].chars().next_back();
+        let after = text[end..].chars().next();
+        if !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric) {
+            return Some(at);
+        }
+        start = at + text[at..].chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// ChatLocation
+// ---------------------------------------------------------------------------
+
+/// Latest coordinates a user shared in a chat (WGS84 degrees).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChatLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Unix seconds when the location was stored.
+    pub updated_at: i64,
+}
+
+// ---------------------------------------------------------------------------
+// CachedPage
+// ---------------------------------------------------------------------------
+
+/// A response body kept by `web_fetch`, with what is needed to revalidate it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedPage {
+    pub url: String,
+    pub status: u16,
+    pub content_type: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: Vec<u8>,
+    /// Unix seconds of the last download or successful revalidation.
+    pub fetched_at: i64,
+}
+
+/// A daily habit tracked by the habits tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Habit {
+    pub name: String,
+    pub notes: String,
+    /// Unix seconds.
+    pub created_at: i64,
+    /// Days it was done, "YYYY-MM-DD", ascending.
+    pub days: Vec<String>,
+}
+
+/// One set logged by the workout tool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkoutSet {
+    /// "YYYY-MM-DD", local.
+    pub day: String,
+    pub exercise: String,
+    pub reps: u32,
+    /// None for bodyweight sets.
+    pub weight: Option<f64>,
+    /// "kg" or "lb".
+    pub unit: String,
+}
+
+/// An expense logged by the expense tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expense {
+    pub id: i64,
+    /// "YYYY-MM-DD", local.
+    pub day: String,
+    /// Minor units of `currency` (pence for GBP).
+    pub amount: i64,
+    /// ISO code, e.g. "GBP".
+    pub currency: String,
+    pub category: String,
+    pub note: String,
+}
+
+fn expense_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Expense> {
+    Ok(Expense {
+        id: row.get(0)?,
+        day: row.get(1)?,
+        amount: row.get(2)?,
+        currency: row.get(3)?,
+        category: row.get(4)?,
+        note: row.get(5)?,
+    })
+}
+
+/// A page saved by the bookmark tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub id: i64,
+    pub url: String,
+    pub title: String,
+    pub summary: String,
+    /// Lowercase, without `#`.
+    pub tags: Vec<String>,
+    /// The user's own note on why it was saved.
+    pub note: String,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// A tag as stored: lowercase, no `#`, words joined by `-` ("Machine learning" is
+/// "machine-learning").
+fn bookmark_tag(tag: &str) -> String {
+    tag.trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+fn bookmark_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Bookmark> {
+    let tags: String = row.get(4)?;
+    Ok(Bookmark {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        summary: row.get(3)?,
+        tags: tags.split_whitespace().map(String::from).collect(),
+        note: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// Runs kept per cron job; older ones are dropped as new ones are recorded.
+pub const CRON_RUNS_KEPT: usize = 100;
+
+/// One firing of a cron job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronRun {
+    pub id: i64,
+    pub job_id: String,
+    /// Unix seconds.
+    pub ran_at: i64,
+    /// "agent" or "direct".
+    pub action: String,
+    pub ok: bool,
+    /// Start of the agent's reply, or what went wrong.
+    pub detail: String,
 }
 
 // ---------------------------------------------------------------------------
@@ -631,24 +2928,44 @@ mod tests {
         assert!(db2.health_check());
     }
 
-    // ── reset_session_id ─────────────────────────────────────────────────────
+    #[test]
+    fn reads_do_not_wait_for_the_writer() {
+        let (_tmp, db) = temp_db();
+        db.upsert_vault_entry("note.md", "committed", 1).unwrap();
+        let writer = db.conn.lock().unwrap();
+        let mode: String = writer
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+        writer
+            .execute_batch("BEGIN IMMEDIATE; DELETE FROM vault_index;")
+            .unwrap();
+
+        // The writer is locked mid-transaction; readers still see the last commit.
+        assert_eq!(db.list_vault_filepaths().unwrap(), vec!["note.md"]);
+        writer.execute_batch("COMMIT").unwrap();
+        drop(writer);
+        assert!(db.list_vault_filepaths().unwrap().is_empty());
+    }
+
+    // ── start_new_session ────────────────────────────────────────────────────
 
     #[test]
-    fn reset_session_id_returns_new_uuid() {
+    fn start_new_session_returns_new_uuid() {
         let (_tmp, db) = temp_db();
         let sid1 = db.get_or_create_session_id("chat").unwrap();
-        let sid2 = db.reset_session_id("chat").unwrap();
+        let sid2 = db.start_new_session("chat").unwrap();
         assert_ne!(sid1, sid2, "reset must produce a different session_id");
         assert_eq!(sid2.len(), 36);
     }
 
     #[test]
-    fn reset_session_id_clears_summary() {
+    fn start_new_session_clears_summary() {
         let (_tmp, db) = temp_db();
         let sid = db.get_or_create_session_id("chat").unwrap();
         db.append_session("chat", &sid, &[], "old summary").unwrap();
 
-        db.reset_session_id("chat").unwrap();
+        db.start_new_session("chat").unwrap();
         let new_sid = db.get_or_create_session_id("chat").unwrap();
         // The session_id must have changed and the summary must be empty
         assert_ne!(sid, new_sid);
@@ -657,7 +2974,7 @@ mod tests {
     }
 
     #[test]
-    fn reset_session_id_keeps_old_messages() {
+    fn start_new_session_keeps_old_messages() {
         let (_tmp, db) = temp_db();
         let old_sid = db.get_or_create_session_id("chat").unwrap();
         db.append_session(
@@ -673,7 +2990,7 @@ mod tests {
         )
         .unwrap();
 
-        db.reset_session_id("chat").unwrap();
+        db.start_new_session("chat").unwrap();
 
         // Old messages still retrievable by their original session_id
         let (old_msgs, _) = db.load_session("chat", &old_sid).unwrap();
@@ -682,7 +2999,7 @@ mod tests {
     }
 
     #[test]
-    fn reset_session_id_new_session_starts_empty() {
+    fn start_new_session_new_session_starts_empty() {
         let (_tmp, db) = temp_db();
         let old_sid = db.get_or_create_session_id("chat").unwrap();
         db.append_session(
@@ -698,11 +3015,131 @@ mod tests {
         )
         .unwrap();
 
-        let new_sid = db.reset_session_id("chat").unwrap();
+        let new_sid = db.start_new_session("chat").unwrap();
         let (new_msgs, _) = db.load_session("chat", &new_sid).unwrap();
         assert!(new_msgs.is_empty(), "new session must start with no messages");
     }
 
+    #[test]
+    fn list_and_resume_sessions_restores_summary() {
+        let (_tmp, db) = temp_db();
+        let user = |content: &str| StoredMessage {
+            role: "user".into(),
+            content: content.into(),
+            tool_call_id: None,
+            tool_calls: None,
+        };
+        let first = db.get_or_create_session_id("chat").unwrap();
+        db.append_session("chat", &first, &[user("plan the trip")], "trip summary")
+            .unwrap();
+        let second = db.start_new_session("chat").unwrap();
+        db.append_session("chat", &second, &[user("gym log"), user("more")], "")
+            .unwrap();
+
+        let sessions = db.list_sessions("chat").unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, second);
+        assert!(sessions[0].current);
+        assert_eq!(sessions[0].messages, 2);
+        assert_eq!(sessions[1].first_message, "plan the trip");
+        assert!(!sessions[1].current);
+
+        assert!(db.resume_session("chat", &first).unwrap());
+        assert_eq!(db.get_or_create_session_id("chat").unwrap(), first);
+        let (msgs, summary) = db.load_session("chat", &first).unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(summary, "trip summary");
+
+        assert!(!db.resume_session("chat", "no-such-session").unwrap());
+        assert!(!db.resume_session("other", &second).unwrap());
+    }
+
+    #[test]
+    fn recent_archived_sessions_skip_current_and_empty_sessions() {
+        let (_tmp, db) = temp_db();
+        let user = |content: &str| StoredMessage {
+            role: "user".into(),
+            content: content.into(),
+            tool_call_id: None,
+            tool_calls: None,
+        };
+        let first = db.get_or_create_session_id("chat").unwrap();
+        db.append_session("chat", &first, &[user("plan the trip")], "trip summary")
+            .unwrap();
+        let second = db.start_new_session("chat").unwrap();
+        db.append_session("chat", &second, &[user("gym log")], "")
+            .unwrap();
+        db.start_new_session("chat").unwrap();
+        let current = db.start_new_session("chat").unwrap();
+        db.append_session("chat", &current, &[user("today")], "")
+            .unwrap();
+
+        let mut recalled = db.recent_archived_sessions("chat", 5).unwrap();
+        recalled.sort_by(|a, b| a.first_message.cmp(&b.first_message));
+        let found: Vec<(&str, &str)> = recalled
+            .iter()
+            .map(|s| (s.first_message.as_str(), s.summary.as_str()))
+            .collect();
+        assert_eq!(found, [("gym log", ""), ("plan the trip", "trip summary")]);
+        assert_eq!(db.recent_archived_sessions("chat", 1).unwrap().len(), 1);
+        assert!(db.recent_archived_sessions("other", 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn append_to_rotated_session_keeps_new_summary() {
+        let (_tmp, db) = temp_db();
+        let old = db.get_or_create_session_id("chat").unwrap();
+        let new = db.start_new_session("chat").unwrap();
+        // A turn that started before the rotation saves afterwards.
+        db.append_session("chat", &old, &[], "stale summary")
+            .unwrap();
+        let (_, summary) = db.load_session("chat", &new).unwrap();
+        assert_eq!(summary, "");
+    }
+
+    #[test]
+    fn expired_messages_by_age_skip_live_context() {
+        let (_tmp, db) = temp_db();
+        let msg = |content: &str| StoredMessage {
+            role: "user".into(),
+            content: content.into(),
+            tool_call_id: None,
+            tool_calls: None,
+        };
+        let old = db.get_or_create_session_id("chat").unwrap();
+        db.append_session("chat", &old, &[msg("ancient"), msg("recent")], "")
+            .unwrap();
+        let live = db.start_new_session("chat").unwrap();
+        db.append_session("chat", &live, &[msg("live but old")], "")
+            .unwrap();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "UPDATE chat_history SET timestamp = datetime('now', '-400 days')
+                 WHERE content IN ('ancient', 'live but old');",
+            )
+            .unwrap();
+
+        let policy = RetentionPolicy {
+            max_messages: None,
+            max_age_days: Some(365),
+        };
+        let expired = db.expired_messages("chat", &policy).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].message.content, "ancient");
+        assert!(!expired[0].summarized);
+
+        let ids: Vec<i64> = expired.iter().map(|m| m.id).collect();
+        assert_eq!(db.delete_messages("chat", &ids).unwrap(), 1);
+        assert!(db.chat_fts_search("ancient", 5).unwrap().is_empty());
+        assert!(
+            db.expired_messages("chat", &RetentionPolicy::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     // ── get_or_create_session_id ─────────────────────────────────────────────
 
     #[test]
@@ -772,6 +3209,38 @@ mod tests {
         assert_eq!(summary, "brief summary");
     }
 
+    #[test]
+    fn folded_messages_are_not_reloaded() {
+        let (_tmp, db) = temp_db();
+        let sid = db.get_or_create_session_id("chat1").unwrap();
+        let messages: Vec<StoredMessage> = (0..5)
+            .map(|i| StoredMessage {
+                role: "user".into(),
+                content: format!("m{i}"),
+                tool_call_id: None,
+                tool_calls: None,
+            })
+            .collect();
+        db.append_session("chat1", &sid, &messages, "").unwrap();
+
+        db.fold_session_messages("chat1", &sid, 2).unwrap();
+        let (loaded, _) = db.load_session("chat1", &sid).unwrap();
+        let contents: Vec<&str> = loaded.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["m2", "m3", "m4"]);
+
+        // Folding is relative to what is still unfolded; overshooting folds everything.
+        db.fold_session_messages("chat1", &sid, 1).unwrap();
+        assert_eq!(db.load_session("chat1", &sid).unwrap().0[0].content, "m3");
+        db.fold_session_messages("chat1", &sid, 10).unwrap();
+        assert!(db.load_session("chat1", &sid).unwrap().0.is_empty());
+
+        // A new session starts unfolded.
+        let sid2 = db.start_new_session("chat1").unwrap();
+        db.append_session("chat1", &sid2, &messages[..1], "")
+            .unwrap();
+        assert_eq!(db.load_session("chat1", &sid2).unwrap().0.len(), 1);
+    }
+
     // ── chat_history: append is additive (no delete) ─────────────────────────
 
     #[test]
@@ -862,6 +3331,43 @@ mod tests {
         assert_eq!(msgs2[0].content, "from session 2");
     }
 
+    // ── chat_history: delete recent (forget) ─────────────────────────────────
+
+    #[test]
+    fn delete_recent_messages_removes_last_visible_and_tool_rows() {
+        let (_tmp, db) = temp_db();
+        let sid = db.get_or_create_session_id("c").unwrap();
+        let msg = |role: &str, content: &str| StoredMessage {
+            role: role.into(),
+            content: content.into(),
+            tool_call_id: None,
+            tool_calls: None,
+        };
+        db.append_session(
+            "c",
+            &sid,
+            &[
+                msg("user", "hello"),
+                msg("assistant", "hi"),
+                msg("user", "my password is hunter2"),
+                msg("assistant", ""),
+                msg("tool", "saved"),
+                msg("assistant", "Noted."),
+            ],
+            "",
+        )
+        .unwrap();
+
+        assert_eq!(db.delete_recent_messages("c", 2).unwrap(), 4);
+        let (left, _) = db.load_session("c", &sid).unwrap();
+        let contents: Vec<_> = left.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["hello", "hi"]);
+
+        assert_eq!(db.delete_recent_messages("c", 10).unwrap(), 2);
+        assert_eq!(db.delete_recent_messages("c", 1).unwrap(), 0);
+        assert_eq!(db.delete_recent_messages("unknown", 1).unwrap(), 0);
+    }
+
     // ── chat_history: sessions are isolated by chat_id ──────────────────────
 
     #[test]
@@ -954,7 +3460,19 @@ mod tests {
     fn schema_has_all_tables() {
         let (_tmp, db) = temp_db();
         let conn = db.conn.lock().unwrap();
-        for table in &["chat_history", "chat_summary", "vault_index"] {
+        for table in &[
+            "chat_history",
+            "chat_summary",
+            "vault_index",
+            "chat_location",
+            "allowed_users",
+            "llm_usage",
+            "llm_cache",
+            "vault_embeddings",
+            "memories",
+            "feed_seen",
+            "web_cache",
+        ] {
             let count: i64 = conn
                 .query_row(
                     "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
@@ -1041,6 +3559,19 @@ mod tests {
         assert_eq!(paths, vec!["a.md", "m.md", "z.md"]);
     }
 
+    #[test]
+    fn delete_vault_path_removes_file_or_directory() {
+        let (_tmp, db) = temp_db();
+        db.upsert_vault_entry("Trips/Lisbon.md", "a", 1).unwrap();
+        db.upsert_vault_entry("Trips/Porto/day1.md", "b", 1)
+            .unwrap();
+        db.upsert_vault_entry("Trips2/x.md", "c", 1).unwrap();
+
+        assert_eq!(db.delete_vault_path("Trips/Lisbon.md").unwrap(), 1);
+        assert_eq!(db.delete_vault_path("Trips").unwrap(), 1);
+        assert_eq!(db.list_vault_filepaths().unwrap(), vec!["Trips2/x.md"]);
+    }
+
     #[test]
     fn delete_vault_stale_removes_unlisted() {
         use std::collections::HashSet;
@@ -1085,6 +3616,127 @@ mod tests {
         assert_eq!(db.list_vault_filepaths().unwrap().len(), 2);
     }
 
+    #[test]
+    fn vault_stats_counts_recent_and_largest() {
+        let (_tmp, db) = temp_db();
+        assert_eq!(db.vault_stats(0, 5).unwrap(), VaultStats::default());
+        db.upsert_vault_entry("old.md", "tiny", 100).unwrap();
+        db.upsert_vault_entry("new.md", "a much longer note", 500)
+            .unwrap();
+        db.upsert_vault_entry("newer.md", "café", 900).unwrap();
+
+        let stats = db.vault_stats(400, 1).unwrap();
+        assert_eq!(stats.notes, 3);
+        assert_eq!(stats.total_bytes, 4 + 18 + 5);
+        assert_eq!(stats.modified_since, 2);
+        assert_eq!(stats.recent, vec![("newer.md".to_string(), 900)]);
+        assert_eq!(stats.largest, vec![("new.md".to_string(), 18)]);
+        assert_eq!(db.vault_contents().unwrap()[0].0, "new.md");
+    }
+
+    // ── Vault embeddings ─────────────────────────────────────────────────────
+
+    #[test]
+    fn vault_embeddings_roundtrip_and_staleness() {
+        let (_tmp, db) = temp_db();
+        db.upsert_vault_entry("a.md", "alpha", 1).unwrap();
+        db.upsert_vault_entry("b.md", "beta", 2).unwrap();
+        assert_eq!(db.vault_files_needing_embeddings("m").unwrap().len(), 2);
+
+        let chunks = vec![
+            ("alpha one".to_string(), vec![1.0, -0.5]),
+            ("alpha two".to_string(), vec![0.25, 3.0]),
+        ];
+        db.replace_vault_embeddings("a.md", 1, "m", &chunks)
+            .unwrap();
+        let pending = db.vault_files_needing_embeddings("m").unwrap();
+        assert_eq!(pending, vec![("b.md".to_string(), "beta".to_string(), 2)]);
+        assert_eq!(db.vault_files_needing_embeddings("other").unwrap().len(), 2);
+
+        let stored = db.vault_embeddings("m").unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[1].chunk, 1);
+        assert_eq!(stored[1].content, "alpha two");
+        assert_eq!(stored[1].embedding, vec![0.25, 3.0]);
+
+        // An edited note needs new embeddings; a deleted one loses them.
+        db.upsert_vault_entry("a.md", "alpha v2", 5).unwrap();
+        assert_eq!(db.vault_files_needing_embeddings("m").unwrap().len(), 2);
+        db.delete_vault_stale(&std::collections::HashSet::new())
+            .unwrap();
+        assert_eq!(db.prune_vault_embeddings("m").unwrap(), 2);
+        assert!(db.vault_embeddings("m").unwrap().is_empty());
+    }
+
+    // ── Long-term memories ───────────────────────────────────────────────────
+
+    #[test]
+    fn memories_save_search_forget() {
+        let (_tmp, db) = temp_db();
+        let veg = db.save_memory("I'm vegetarian", "diet", "42").unwrap();
+        db.save_memory("Partner is called Sam", "people", "42")
+            .unwrap();
+        db.save_memory("Prefers metric units", "preference", "42")
+            .unwrap();
+
+        let hits = db.search_memories("any vegetarian recipes?", 5).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].fact, "I'm vegetarian");
+        assert_eq!(hits[0].category, "diet");
+        assert_eq!(hits[0].source, "42");
+        // Porter stemming: "preferences" finds "Prefers".
+        assert_eq!(db.search_memories("my preferences", 5).unwrap().len(), 1);
+        // Punctuation and FTS operators in user text are harmless.
+        assert!(db.search_memories("\"AND OR* (", 5).unwrap().is_empty());
+        // No searchable words: newest first.
+        let recent = db.search_memories("", 2).unwrap();
+        assert_eq!(recent[0].fact, "Prefers metric units");
+        assert_eq!(recent.len(), 2);
+
+        // Relevant facts come first, then the newest others fill the limit.
+        let relevant = db
+            .relevant_memories("dinner ideas for a vegetarian", 2)
+            .unwrap();
+        assert_eq!(relevant[0].id, veg);
+        assert_eq!(relevant[1].fact, "Prefers metric units");
+
+        assert!(db.forget_memory(veg).unwrap());
+        assert!(!db.forget_memory(veg).unwrap());
+        assert!(db.search_memories("vegetarian", 5).unwrap().is_empty());
+    }
+
+    // ── Entities ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn entities_save_relate_mention_forget() {
+        let (_tmp, db) = temp_db();
+        let priya = db
+            .save_entity("Priya", "person", "Designer at Acme", &["P.S.".into()])
+            .unwrap();
+        db.relate_entities("priya", "works on", "Project Falcon")
+            .unwrap();
+        // Saving by alias updates the same entity and keeps unset fields.
+        assert_eq!(db.save_entity("p.s.", "", "", &[]).unwrap(), priya);
+
+        let found = db.find_entity("PRIYA").unwrap().unwrap();
+        assert_eq!(found.notes, "Designer at Acme");
+        assert_eq!(found.aliases, ["P.S."]);
+        assert_eq!(found.relations[0].to, "Project Falcon");
+
+        // Whole words only, in order of mention.
+        let hits = db
+            .mentioned_entities("Is project falcon late? Ask priya", 5)
+            .unwrap();
+        let names: Vec<&str> = hits.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Project Falcon", "Priya"]);
+        assert!(db.mentioned_entities("Priyanka", 5).unwrap().is_empty());
+
+        assert!(db.forget_entity("Priya").unwrap());
+        assert!(!db.forget_entity("P.S.").unwrap());
+        let falcon = db.find_entity("Project Falcon").unwrap().unwrap();
+        assert!(falcon.relations.is_empty());
+    }
+
     // ── Vault index: basic insert & fts5 roundtrip ───────────────────────────
 
     #[test]
@@ -1215,6 +3867,38 @@ mod tests {
         assert!(rows[0].2.contains("squats") || rows[0].2.contains("**"));
     }
 
+    #[test]
+    fn open_backfills_chat_fts_for_older_databases() {
+        let tmp = TempDir::new().unwrap();
+        let db = BrainDb::open(tmp.path()).unwrap();
+        db.append_session(
+            "chat1",
+            "session-s",
+            &[StoredMessage {
+                role: "user".into(),
+                content: "bought new running shoes".into(),
+                tool_call_id: None,
+                tool_calls: None,
+            }],
+            "",
+        )
+        .unwrap();
+        // Simulate a database created before the FTS index existed.
+        db.conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "DROP TRIGGER chat_history_ai; DROP TRIGGER chat_history_ad;
+                 DROP TRIGGER chat_history_au; DROP TABLE chat_fts;
+                 DROP TABLE schema_version;",
+            )
+            .unwrap();
+        drop(db);
+
+        let db = BrainDb::open(tmp.path()).unwrap();
+        assert_eq!(db.chat_fts_search("shoes", 5).unwrap().len(), 1);
+    }
+
     #[test]
     fn chat_fts_search_empty_query_returns_empty() {
         let (_tmp, db) = temp_db();
@@ -1258,6 +3942,72 @@ mod tests {
         assert!(rows.len() <= 3);
     }
 
+    #[test]
+    fn chat_fts_search_filtered_by_date_role_and_session() {
+        let (_tmp, db) = temp_db();
+        let msg = |role: &str, content: &str| StoredMessage {
+            role: role.into(),
+            content: content.into(),
+            tool_call_id: None,
+            tool_calls: None,
+        };
+        db.append_session("c", "s1", &[msg("user", "rent is 900")], "")
+            .unwrap();
+        db.append_session(
+            "c",
+            "s2",
+            &[
+                msg("user", "rent went up"),
+                msg("assistant", "noted the rent"),
+            ],
+            "",
+        )
+        .unwrap();
+        {
+            let conn = db.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE chat_history SET timestamp = '2026-01-15 09:00:00' WHERE session_id = 's1'",
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                "UPDATE chat_history SET timestamp = '2026-02-03 18:30:00' WHERE session_id = 's2'",
+                [],
+            )
+            .unwrap();
+        }
+        let search =
+            |filter: ChatSearchFilter| db.chat_fts_search_filtered("rent", &filter, 10).unwrap();
+
+        assert_eq!(search(ChatSearchFilter::default()).len(), 3);
+        let feb = search(ChatSearchFilter {
+            after: Some("2026-02-01".into()),
+            before: Some("2026-03-01".into()),
+            ..Default::default()
+        });
+        assert_eq!(feb.len(), 2);
+        assert!(feb.iter().all(|h| h.timestamp.starts_with("2026-02-03")));
+        let jan = search(ChatSearchFilter {
+            before: Some("2026-02-01".into()),
+            ..Default::default()
+        });
+        assert_eq!(jan.len(), 1);
+        assert_eq!(jan[0].session_id, "s1");
+        let replies = search(ChatSearchFilter {
+            role: Some("assistant".into()),
+            ..Default::default()
+        });
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].role, "assistant");
+        let s2_user = search(ChatSearchFilter {
+            role: Some("user".into()),
+            session_id: Some("s2".into()),
+            ..Default::default()
+        });
+        assert_eq!(s2_user.len(), 1);
+        assert!(s2_user[0].snippet.contains("went up"));
+    }
+
     #[test]
     fn message_ordering_preserved() {
         let (_tmp, db) = temp_db();
@@ -1276,4 +4026,465 @@ mod tests {
             assert_eq!(msg.content, format!("message {i}"));
         }
     }
+
+    // ── Chat location ────────────────────────────────────────────────────────
+
+    #[test]
+    fn chat_location_missing_returns_none() {
+        let (_tmp, db) = temp_db();
+        assert!(db.get_chat_location("nobody").unwrap().is_none());
+    }
+
+    #[test]
+    fn chat_location_upsert_keeps_latest() {
+        let (_tmp, db) = temp_db();
+        db.set_chat_location("c1", 51.5, -0.12).unwrap();
+        db.set_chat_location("c1", 48.85, 2.35).unwrap();
+        db.set_chat_location("c2", 1.0, 2.0).unwrap();
+        let loc = db.get_chat_location("c1").unwrap().unwrap();
+        assert_eq!((loc.latitude, loc.longitude), (48.85, 2.35));
+        assert!(loc.updated_at > 0);
+        let other = db.get_chat_location("c2").unwrap().unwrap();
+        assert_eq!((other.latitude, other.longitude), (1.0, 2.0));
+    }
+
+    // ── Runtime allow-list ───────────────────────────────────────────────────
+
+    #[test]
+    fn allowed_users_add_remove_list() {
+        let (_tmp, db) = temp_db();
+        assert!(db.list_allowed_users().unwrap().is_empty());
+        assert!(db.add_allowed_user(42).unwrap());
+        assert!(
+            !db.add_allowed_user(42).unwrap(),
+            "duplicate add is a no-op"
+        );
+        assert!(db.add_allowed_user(7).unwrap());
+        assert_eq!(db.list_allowed_users().unwrap(), vec![7, 42]);
+        assert!(db.remove_allowed_user(42).unwrap());
+        assert!(!db.remove_allowed_user(42).unwrap());
+        assert_eq!(db.list_allowed_users().unwrap(), vec![7]);
+    }
+
+    // ── LLM usage ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn llm_usage_totals_group_and_filter() {
+        let (_tmp, db) = temp_db();
+        let rec = |chat: &str, source: &str, p, c, cost| LlmUsage {
+            chat_id: chat.into(),
+            source: source.into(),
+            model: "m".into(),
+            prompt_tokens: p,
+            completion_tokens: c,
+            cost_usd: cost,
+        };
+        db.record_llm_usage(&rec("1", "telegram", 100, 10, Some(0.5)))
+            .unwrap();
+        db.record_llm_usage(&rec("1", "subagent:web", 50, 5, Some(1.0)))
+            .unwrap();
+        db.record_llm_usage(&rec("2", "cron", 7, 3, None)).unwrap();
+
+        let by_chat = db.llm_usage_totals(0, UsageGroupBy::Chat).unwrap();
+        assert_eq!(by_chat.len(), 2);
+        assert_eq!(by_chat[0].key, "1");
+        assert_eq!(by_chat[0].calls, 2);
+        assert_eq!(by_chat[0].prompt_tokens, 150);
+        assert_eq!(by_chat[0].cost_usd, Some(1.5));
+        assert_eq!(by_chat[1].cost_usd, None);
+
+        let by_source = db.llm_usage_totals(0, UsageGroupBy::Source).unwrap();
+        assert_eq!(by_source[0].key, "subagent:web");
+
+        let future = chrono::Utc::now().timestamp() + 60;
+        assert!(
+            db.llm_usage_totals(future, UsageGroupBy::Chat)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    // ── LLM cache ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn llm_cache_put_get_and_expiry() {
+        let (_tmp, db) = temp_db();
+        assert_eq!(db.llm_cache_get("k", 60).unwrap(), None);
+        db.llm_cache_put("k", "{\"content\":\"a\"}", 60).unwrap();
+        assert_eq!(
+            db.llm_cache_get("k", 60).unwrap().as_deref(),
+            Some("{\"content\":\"a\"}")
+        );
+        db.llm_cache_put("k", "b", 60).unwrap();
+        assert_eq!(db.llm_cache_get("k", 60).unwrap().as_deref(), Some("b"));
+        assert_eq!(
+            db.llm_cache_get("k", 0).unwrap(),
+            None,
+            "expired with zero ttl"
+        );
+    }
+
+    // ── Web fetch cache ──────────────────────────────────────────────────────
+
+    #[test]
+    fn web_cache_put_touch_and_pruning() {
+        let (_tmp, db) = temp_db();
+        let page = |url: &str, body: Vec<u8>, fetched_at: i64| CachedPage {
+            url: url.to_string(),
+            status: 200,
+            content_type: "text/html".to_string(),
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+            body,
+            fetched_at,
+        };
+        assert_eq!(db.web_cache_get("https://a.example/").unwrap(), None);
+        db.web_cache_put(&page("https://a.example/", b"<p>a</p>".to_vec(), 1_000))
+            .unwrap();
+        db.web_cache_touch("https://a.example/", 2_000).unwrap();
+        let cached = db.web_cache_get("https://a.example/").unwrap().unwrap();
+        assert_eq!(cached.body, b"<p>a</p>");
+        assert_eq!(cached.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(cached.fetched_at, 2_000);
+
+        // A week later the untouched page is dropped.
+        let later = 2_001 + WEB_CACHE_KEEP_SECS;
+        db.web_cache_put(&page("https://b.example/", Vec::new(), later))
+            .unwrap();
+        assert_eq!(db.web_cache_get("https://a.example/").unwrap(), None);
+
+        // Over the size cap, the least recently fetched pages go.
+        let half = vec![0u8; (WEB_CACHE_MAX_BYTES / 2 + 1) as usize];
+        db.web_cache_put(&page("https://c.example/", half.clone(), later + 1))
+            .unwrap();
+        db.web_cache_put(&page("https://d.example/", half, later + 2))
+            .unwrap();
+        assert_eq!(db.web_cache_get("https://b.example/").unwrap(), None);
+        assert_eq!(db.web_cache_get("https://c.example/").unwrap(), None);
+        assert!(db.web_cache_get("https://d.example/").unwrap().is_some());
+    }
+
+    #[test]
+    fn feed_unseen_marks_and_filters_per_feed() {
+        let (_tmp, db) = temp_db();
+        let ids = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            db.feed_unseen("a", &ids(&["1", "2"]), false).unwrap(),
+            ids(&["1", "2"])
+        );
+        assert_eq!(
+            db.feed_unseen("a", &ids(&["1", "2", "1"]), true).unwrap(),
+            ids(&["1", "2"])
+        );
+        assert_eq!(
+            db.feed_unseen("a", &ids(&["3", "2", "1"]), true).unwrap(),
+            ids(&["3"])
+        );
+        assert!(
+            db.feed_unseen("a", &ids(&["1", "3"]), true)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            db.feed_unseen("b", &ids(&["1"]), true).unwrap(),
+            ids(&["1"])
+        );
+    }
+
+    // ── Habits ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn habits_define_check_and_remove() {
+        let (_tmp, db) = temp_db();
+        assert!(db.define_habit("Stretch", "").unwrap());
+        assert!(!db.define_habit("stretch", "10 minutes").unwrap());
+        assert!(db.define_habit("Read", "").unwrap());
+        assert_eq!(
+            db.set_habit_done("Floss", "2026-10-16", true).unwrap(),
+            None
+        );
+        assert_eq!(
+            db.set_habit_done("STRETCH", "2026-10-16", true).unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            db.set_habit_done("Stretch", "2026-10-16", true).unwrap(),
+            Some(false)
+        );
+        db.set_habit_done("Stretch", "2026-10-14", true).unwrap();
+        db.set_habit_done("Read", "2026-10-15", true).unwrap();
+        assert_eq!(
+            db.set_habit_done("Read", "2026-10-15", false).unwrap(),
+            Some(true)
+        );
+
+        let habits = db.habits().unwrap();
+        assert_eq!(habits.len(), 2);
+        assert_eq!(habits[0].name, "Stretch");
+        assert_eq!(habits[0].notes, "10 minutes");
+        assert_eq!(habits[0].days, vec!["2026-10-14", "2026-10-16"]);
+        assert!(habits[1].days.is_empty());
+
+        assert!(db.remove_habit("stretch").unwrap());
+        assert!(!db.remove_habit("stretch").unwrap());
+        let left: i64 = db
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM habit_checkins", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(left, 0);
+    }
+
+    // ── Workouts ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn workout_sets_by_exercise_and_day() {
+        let (_tmp, db) = temp_db();
+        let set = |day: &str, exercise: &str, reps: u32, weight: Option<f64>| WorkoutSet {
+            day: day.to_string(),
+            exercise: exercise.to_string(),
+            reps,
+            weight,
+            unit: "kg".to_string(),
+        };
+        db.log_workout_sets(&[
+            set("2026-10-12", "Squat", 5, Some(100.0)),
+            set("2026-10-12", "Squat", 3, Some(110.0)),
+            set("2026-10-12", "Pull-up", 8, None),
+            set("2026-10-14", "Bench press", 5, Some(70.0)),
+        ])
+        .unwrap();
+
+        let squats = db
+            .workout_sets(Some("squat"), "2026-10-01", "2026-10-31")
+            .unwrap();
+        assert_eq!(squats.len(), 2);
+        assert_eq!(squats[1].weight, Some(110.0));
+        assert_eq!(
+            db.workout_sets(None, "2026-10-12", "2026-10-12")
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            db.last_workout_day(Some("SQUAT"), "2026-10-16")
+                .unwrap()
+                .as_deref(),
+            Some("2026-10-12")
+        );
+        assert_eq!(
+            db.last_workout_day(None, "2026-10-16").unwrap().as_deref(),
+            Some("2026-10-14")
+        );
+        assert_eq!(
+            db.last_workout_day(Some("Squat"), "2026-10-11").unwrap(),
+            None
+        );
+        assert_eq!(
+            db.workout_exercises().unwrap(),
+            vec![
+                ("Squat".to_string(), 2),
+                ("Bench press".to_string(), 1),
+                ("Pull-up".to_string(), 1)
+            ]
+        );
+    }
+
+    // ── Expenses ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn expenses_add_filter_and_remove() {
+        let (_tmp, db) = temp_db();
+        let expense = |day: &str, amount: i64, category: &str| Expense {
+            id: 0,
+            day: day.to_string(),
+            amount,
+            currency: "GBP".to_string(),
+            category: category.to_string(),
+            note: String::new(),
+        };
+        let first = db
+            .add_expense(&expense("2026-10-16", 1450, "Groceries"))
+            .unwrap();
+        db.add_expense(&expense("2026-10-02", 300, "Coffee"))
+            .unwrap();
+        db.add_expense(&expense("2026-11-01", 999, "groceries"))
+            .unwrap();
+
+        let october = db.expenses("2026-10-01", "2026-10-31", None).unwrap();
+        assert_eq!(october.len(), 2);
+        assert_eq!(october[0].category, "Coffee");
+        assert_eq!(october[1].id, first);
+        let groceries = db
+            .expenses("2026-01-01", "2026-12-31", Some("GROCERIES"))
+            .unwrap();
+        assert_eq!(groceries.len(), 2);
+
+        let removed = db.remove_expense(first).unwrap().unwrap();
+        assert_eq!(removed.amount, 1450);
+        assert_eq!(db.remove_expense(first).unwrap(), None);
+        assert_eq!(
+            db.expenses("2026-10-01", "2026-10-31", None).unwrap().len(),
+            1
+        );
+    }
+
+    // ── Bookmarks ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn bookmarks_save_search_and_remove() {
+        let (_tmp, db) = temp_db();
+        let bookmark = |url: &str, title: &str, summary: &str, tags: &[&str]| Bookmark {
+            id: 0,
+            url: url.to_string(),
+            title: title.to_string(),
+            summary: summary.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            note: String::new(),
+            created_at: 0,
+        };
+        let (rust, new) = db
+            .save_bookmark(&bookmark(
+                "https://a.example/rust",
+                "Async Rust",
+                "How executors poll futures.",
+                &["#Rust", "Machine learning"],
+            ))
+            .unwrap();
+        assert!(new);
+        db.save_bookmark(&bookmark(
+            "https://b.example/bread",
+            "Sourdough",
+            "Baking bread at home.",
+            &["cooking"],
+        ))
+        .unwrap();
+
+        let hits = db.search_bookmarks("polling futures", None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, rust);
+        assert_eq!(hits[0].tags, vec!["rust", "machine-learning"]);
+        assert_eq!(db.search_bookmarks("", None, 10).unwrap().len(), 2);
+        let tagged = db.search_bookmarks("", Some("#Cooking"), 10).unwrap();
+        assert_eq!(tagged[0].title, "Sourdough");
+        assert!(
+            db.search_bookmarks("bread", Some("rust"), 10)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Saving the same URL again updates it and the index.
+        let (again, new) = db
+            .save_bookmark(&bookmark(
+                "https://a.example/rust",
+                "Tokio internals",
+                "Work stealing.",
+                &["rust"],
+            ))
+            .unwrap();
+        assert_eq!((again, new), (rust, false));
+        assert!(
+            db.search_bookmarks("executors", None, 10)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            db.search_bookmarks("stealing", None, 10).unwrap()[0].id,
+            rust
+        );
+
+        let removed = db.remove_bookmark(rust).unwrap().unwrap();
+        assert_eq!(removed.title, "Tokio internals");
+        assert_eq!(db.remove_bookmark(rust).unwrap(), None);
+        assert!(
+            db.search_bookmarks("stealing", None, 10)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    // ── Encryption at rest ───────────────────────────────────────────────────
+
+    #[cfg(not(feature = "encryption"))]
+    #[test]
+    fn encryption_key_requires_the_feature() {
+        let tmp = TempDir::new().unwrap();
+        let err = BrainDb::open_with_key(tmp.path(), Some("k")).unwrap_err();
+        assert!(err.0.contains("encryption"), "{err}");
+        // An empty key means no encryption.
+        BrainDb::open_with_key(tmp.path(), Some("")).unwrap();
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_db_needs_the_key() {
+        let tmp = TempDir::new().unwrap();
+        {
+            let db = BrainDb::open_with_key(tmp.path(), Some("s3cret")).unwrap();
+            db.save_memory("Bank PIN hint: birthday", "", "1").unwrap();
+        }
+        let raw = std::fs::read(workspace::brain_db_path(tmp.path())).unwrap();
+        assert!(!raw.starts_with(b"SQLite format 3"));
+        assert!(!raw.windows(8).any(|w| w == b"birthday"));
+
+        assert!(BrainDb::open(tmp.path()).is_err());
+        assert!(BrainDb::open_with_key(tmp.path(), Some("wrong")).is_err());
+        let db = BrainDb::open_with_key(tmp.path(), Some("s3cret")).unwrap();
+        assert_eq!(db.search_memories("birthday", 5).unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn plaintext_db_is_encrypted_when_a_key_is_set() {
+        let tmp = TempDir::new().unwrap();
+        BrainDb::open(tmp.path())
+            .unwrap()
+            .save_memory("Likes jazz", "", "1")
+            .unwrap();
+
+        let db = BrainDb::open_with_key(tmp.path(), Some("k")).unwrap();
+        assert_eq!(db.search_memories("jazz", 5).unwrap().len(), 1);
+        let raw = std::fs::read(workspace::brain_db_path(tmp.path())).unwrap();
+        assert!(!raw.starts_with(b"SQLite format 3"));
+
+        // Backups stay encrypted and restore with the same key.
+        let backup = tmp.path().join("backup.db");
+        db.backup_to(&backup).unwrap();
+        let raw = std::fs::read(&backup).unwrap();
+        assert!(!raw.starts_with(b"SQLite format 3"));
+        db.save_memory("Likes techno", "", "1").unwrap();
+        db.restore_from(&backup).unwrap();
+        assert!(db.search_memories("techno", 5).unwrap().is_empty());
+        assert_eq!(db.search_memories("jazz", 5).unwrap().len(), 1);
+    }
+
+    // ── Cron runs ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn cron_runs_newest_first_and_capped() {
+        let (_tmp, db) = temp_db();
+        let run = |job: &str, ran_at: i64, ok: bool| CronRun {
+            id: 0,
+            job_id: job.to_string(),
+            ran_at,
+            action: "agent".to_string(),
+            ok,
+            detail: format!("run at {ran_at}"),
+        };
+        for i in 0..(CRON_RUNS_KEPT as i64 + 5) {
+            db.record_cron_run(&run("job-1", 1_000 + i, i % 2 == 0))
+                .unwrap();
+        }
+        db.record_cron_run(&run("job-2", 5, false)).unwrap();
+
+        let runs = db.cron_runs("job-1", 1_000).unwrap();
+        assert_eq!(runs.len(), CRON_RUNS_KEPT);
+        assert_eq!(runs[0].ran_at, 1_000 + CRON_RUNS_KEPT as i64 + 4);
+        assert_eq!(runs.last().unwrap().ran_at, 1_005);
+        let latest = db.cron_runs("job-2", 10).unwrap();
+        assert_eq!(latest.len(), 1);
+        assert!(!latest[0].ok);
+        assert!(db.cron_runs("job-3", 10).unwrap().is_empty());
+    }
 }
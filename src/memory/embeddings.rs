@@ -0,0 +1,320 @@
+//! Vault embeddings for semantic search (`[llm] embedding-model`).
+//!
+//! BM25 only finds notes that share words with the query; "leg day plan" misses a note
+//! titled "Squat program". [`VaultEmbedder`] splits every indexed note into paragraph-aligned
+//! chunks, embeds them through the provider's embeddings API ([`LlmProvider::embed`]) and
+//! stores the vectors in `vault_embeddings`. Queries are embedded the same way and ranked by
+//! cosine similarity.
+//!
+//! # Triggering
+//!
+//! [`VaultEmbedder::run`] follows every vault scan (startup and after each git pull). Only
+//! notes that are new, edited, or were embedded with another model are sent to the API;
+//! embeddings of deleted notes are pruned.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::llm::{LlmError, LlmProvider};
+use crate::memory::db::{BrainDb, VaultEmbedding};
+use crate::memory::indexer::IndexerError;
+
+/// Chunk size target in characters (a paragraph longer than this is split).
+pub const MAX_CHUNK_CHARS: usize = 1500;
+
+/// Chunks sent per embeddings request.
+const BATCH_SIZE: usize = 16;
+
+/// Characters of the best chunk shown with each search hit.
+const SNIPPET_CHARS: usize = 300;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// Summary of a completed embedding pass.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EmbedStats {
+    /// Notes (re-)embedded in this pass.
+    pub embedded: usize,
+    /// Chunks sent to the embeddings API.
+    pub chunks: usize,
+    /// Stale chunk rows removed (deleted notes, old model).
+    pub removed: usize,
+}
+
+impl std::fmt::Display for EmbedStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} notes embedded ({} chunks), {} stale chunks removed",
+            self.embedded, self.chunks, self.removed
+        )
+    }
+}
+
+/// One semantic search result: a note and its best-matching chunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemanticHit {
+    pub filepath: String,
+    /// Cosine similarity of the best chunk (-1..1).
+    pub score: f32,
+    /// Start of the best chunk.
+    pub snippet: String,
+}
+
+// ---------------------------------------------------------------------------
+// VaultEmbedder
+// ---------------------------------------------------------------------------
+
+/// Embeds vault notes into `vault_embeddings` and answers semantic queries.
+#[derive(Clone)]
+pub struct VaultEmbedder {
+    db: Arc<BrainDb>,
+    llm: Arc<dyn LlmProvider>,
+    model: String,
+}
+
+impl VaultEmbedder {
+    pub fn new(db: Arc<BrainDb>, llm: Arc<dyn LlmProvider>, model: impl Into<String>) -> Self {
+        Self {
+            db,
+            llm,
+            model: model.into(),
+        }
+    }
+
+    /// Embedder for `[llm] embedding-model`; `None` when unset (semantic search disabled).
+    pub fn from_config(cfg: &Config, db: Arc<BrainDb>, llm: Arc<dyn LlmProvider>) -> Option<Self> {
+        let model = cfg
+            .llm
+            .as_ref()?
+            .embedding_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())?;
+        Some(Self::new(db, llm, model))
+    }
+
+    /// Embed every note that is new or changed since its last embedding, and prune
+    /// embeddings of removed notes. A failed request stops the pass; the next one retries.
+    pub async fn run(&self) -> Result<EmbedStats, IndexerError> {
+        let db = Arc::clone(&self.db);
+        let model = self.model.clone();
+        let (removed, pending) = tokio::task::spawn_blocking(move || {
+            let removed = db.prune_vault_embeddings(&model)?;
+            let pending = db.vault_files_needing_embeddings(&model)?;
+            Ok::<_, IndexerError>((removed, pending))
+        })
+        .await
+        .map_err(|e| IndexerError(format!("embedding task: {e}")))??;
+
+        let mut stats = EmbedStats {
+            removed,
+            ..Default::default()
+        };
+        for (filepath, content, last_modified) in pending {
+            let chunks = chunk_text(&content, MAX_CHUNK_CHARS);
+            if chunks.is_empty() {
+                continue;
+            }
+            // The path carries the note's title and folder, which are often the best signal.
+            let inputs: Vec<String> = chunks
+                .iter()
+                .map(|c| format!("{filepath}\n\n{c}"))
+                .collect();
+            let mut vectors = Vec::with_capacity(inputs.len());
+            for batch in inputs.chunks(BATCH_SIZE) {
+                vectors.extend(self.embed(batch).await?);
+            }
+            stats.embedded += 1;
+            stats.chunks += chunks.len();
+
+            let rows: Vec<(String, Vec<f32>)> = chunks.into_iter().zip(vectors).collect();
+            let db = Arc::clone(&self.db);
+            let model = self.model.clone();
+            tokio::task::spawn_blocking(move || {
+                db.replace_vault_embeddings(&filepath, last_modified, &model, &rows)
+            })
+            .await
+            .map_err(|e| IndexerError(format!("embedding task: {e}")))??;
+        }
+        Ok(stats)
+    }
+
+    /// The `limit` notes most similar to `query`, best first.
+    pub async fn search(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SemanticHit>, IndexerError> {
+        let db = Arc::clone(&self.db);
+        let model = self.model.clone();
+        let stored = tokio::task::spawn_blocking(move || db.vault_embeddings(&model))
+            .await
+            .map_err(|e| IndexerError(format!("embedding task: {e}")))??;
+        if stored.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = self
+            .embed(&[query.to_string()])
+            .await?
+            .pop()
+            .unwrap_or_default();
+        Ok(rank(&query, &stored, limit))
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, IndexerError> {
+        let vectors = self
+            .llm
+            .embed(texts, &self.model)
+            .await
+            .map_err(|e: LlmError| IndexerError(format!("embeddings: {e}")))?;
+        if vectors.len() != texts.len() {
+            return Err(IndexerError(format!(
+                "embeddings: expected {} vectors, got {}",
+                texts.len(),
+                vectors.len()
+            )));
+        }
+        Ok(vectors)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Split `text` into chunks of at most `max_chars` characters, packing whole paragraphs
+/// (separated by blank lines) together. Paragraphs longer than `max_chars` are cut.
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    for para in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let chars: Vec<char> = para.chars().collect();
+        for piece in chars.chunks(max_chars) {
+            if current_chars > 0 && current_chars + 2 + piece.len() > max_chars {
+                chunks.push(std::mem::take(&mut current));
+                current_chars = 0;
+            }
+            if current_chars > 0 {
+                current.push_str("\n\n");
+                current_chars += 2;
+            }
+            current.extend(piece);
+            current_chars += piece.len();
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Cosine similarity of `a` and `b`; 0 for mismatched lengths or zero vectors.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut dot, mut na, mut nb) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        na += x * x;
+        nb += y * y;
+    }
+    if na == 0.0 || nb == 0.0 {
+        0.0
+    } else {
+        dot / (na.sqrt() * nb.sqrt())
+    }
+}
+
+/// Score every chunk against `query`, keep each note's best chunk, return the top `limit`.
+fn rank(query: &[f32], stored: &[VaultEmbedding], limit: usize) -> Vec<SemanticHit> {
+    let mut best: HashMap<&str, (f32, &str)> = HashMap::new();
+    for e in stored {
+        let score = cosine(query, &e.embedding);
+        let entry = best
+            .entry(e.filepath.as_str())
+            .or_insert((f32::MIN, e.content.as_str()));
+        if score > entry.0 {
+            *entry = (score, e.content.as_str());
+        }
+    }
+    let mut hits: Vec<SemanticHit> = best
+        .into_iter()
+        .map(|(filepath, (score, content))| SemanticHit {
+            filepath: filepath.to_string(),
+            score,
+            snippet: snippet(content),
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.filepath.cmp(&b.filepath))
+    });
+    hits.truncate(limit);
+    hits
+}
+
+fn snippet(content: &str) -> String {
+    let flat = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= SNIPPET_CHARS {
+        flat
+    } else {
+        let cut: String = flat.chars().take(SNIPPET_CHARS).collect();
+        format!("{cut}...")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_text_packs_paragraphs_and_splits_long_ones() {
+        assert!(chunk_text("  \n\n ", 10).is_empty());
+        assert_eq!(chunk_text("ab\n\ncd\n\nef", 6), vec!["ab\n\ncd", "ef"]);
+        assert_eq!(chunk_text("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        // Multi-byte characters are never cut in half.
+        assert_eq!(chunk_text("ééé", 2), vec!["éé", "é"]);
+    }
+
+    #[test]
+    fn cosine_similarity() {
+        assert!((cosine(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-6);
+        assert!((cosine(&[1.0, 1.0], &[-1.0, -1.0]) + 1.0).abs() < 1e-6);
+        assert_eq!(cosine(&[1.0], &[1.0, 2.0]), 0.0);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    fn rank_keeps_best_chunk_per_note() {
+        let e = |filepath: &str, chunk, content: &str, embedding: Vec<f32>| VaultEmbedding {
+            filepath: filepath.into(),
+            chunk,
+            content: content.into(),
+            embedding,
+        };
+        let stored = vec![
+            e("a.md", 0, "intro", vec![0.0, 1.0]),
+            e("a.md", 1, "squats", vec![1.0, 0.1]),
+            e("b.md", 0, "bench", vec![0.7, 0.7]),
+            e("c.md", 0, "cooking", vec![-1.0, 0.0]),
+        ];
+        let hits = rank(&[1.0, 0.0], &stored, 2);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].filepath, "a.md");
+        assert_eq!(hits[0].snippet, "squats");
+        assert_eq!(hits[1].filepath, "b.md");
+    }
+}
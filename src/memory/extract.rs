@@ -0,0 +1,192 @@
+//! Searchable text of vault files by format, for the vault index.
+//!
+//! Markdown, plain text and Org files are indexed as they are. A CSV file becomes its header
+//! followed by up to [`CSV_PREVIEW_ROWS`] rows, each cell labelled with its column
+//! (`date: 2026-02-01 | lift: squat`), so a search for a column name or a value finds it. An
+//! Obsidian canvas (JSON Canvas) becomes the text of its cards, the files and links it points
+//! to, and its group labels, one per paragraph.
+
+use serde_json::Value;
+
+/// Extensions indexed when `[index] extensions` is not set.
+pub const DEFAULT_EXTENSIONS: &[&str] = &["md", "txt", "org", "csv", "canvas"];
+
+/// Data rows of a CSV file kept in the index.
+pub const CSV_PREVIEW_ROWS: usize = 100;
+
+/// Lowercase extension of `filepath` without the dot; empty when it has none.
+pub fn extension(filepath: &str) -> String {
+    std::path::Path::new(filepath)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+/// Normalise configured extensions: trimmed, lowercase, without a leading dot.
+pub fn normalize_extensions<S: AsRef<str>>(extensions: &[S]) -> Vec<String> {
+    let mut out: Vec<String> = extensions
+        .iter()
+        .map(|e| {
+            e.as_ref()
+                .trim()
+                .trim_start_matches('.')
+                .to_ascii_lowercase()
+        })
+        .filter(|e| !e.is_empty())
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Text to index for a file with this extension.
+pub fn searchable_text(extension: &str, raw: &str) -> String {
+    match extension {
+        "csv" => csv_preview(raw),
+        "canvas" => canvas_text(raw).unwrap_or_else(|| raw.to_string()),
+        _ => raw.to_string(),
+    }
+}
+
+/// Header line plus labelled rows, with a note of how many rows were left out.
+fn csv_preview(raw: &str) -> String {
+    let mut records = parse_csv(raw).into_iter();
+    let Some(header) = records.next() else {
+        return String::new();
+    };
+    let mut lines = vec![format!("Columns: {}", header.join(", "))];
+    let mut omitted = 0;
+    for (i, row) in records.enumerate() {
+        if i >= CSV_PREVIEW_ROWS {
+            omitted += 1;
+            continue;
+        }
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(c, v)| match header.get(c).filter(|h| !h.is_empty()) {
+                Some(h) => format!("{h}: {v}"),
+                None => v.clone(),
+            })
+            .collect();
+        lines.push(cells.join(" | "));
+    }
+    if omitted > 0 {
+        lines.push(format!("… {omitted} more row(s)"));
+    }
+    lines.join("\n")
+}
+
+/// RFC 4180 records: comma separated, double-quoted fields may hold commas, newlines and
+/// doubled quotes. Blank lines are skipped.
+fn parse_csv(raw: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = raw.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field).trim().to_string()),
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field).trim().to_string());
+                if record.iter().any(|f| !f.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            '\r' if !quoted => {}
+            c => field.push(c),
+        }
+    }
+    record.push(field.trim().to_string());
+    if record.iter().any(|f| !f.is_empty()) {
+        records.push(record);
+    }
+    records
+}
+
+/// Card texts, file and link targets and group labels of a JSON Canvas; None if not one.
+fn canvas_text(raw: &str) -> Option<String> {
+    let canvas: Value = serde_json::from_str(raw).ok()?;
+    let nodes = canvas.get("nodes")?.as_array()?;
+    let parts: Vec<&str> = nodes
+        .iter()
+        .filter_map(|node| {
+            let key = match node.get("type").and_then(Value::as_str)? {
+                "text" => "text",
+                "file" => "file",
+                "link" => "url",
+                "group" => "label",
+                _ => return None,
+            };
+            node.get(key).and_then(Value::as_str)
+        })
+        .filter(|s| !s.trim().is_empty())
+        .collect();
+    Some(parts.join("\n\n"))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_rows_are_labelled_by_column() {
+        let csv = "date,lift,note\r\n2026-02-01,squat,\"felt good, easy\"\r\n\r\n2026-02-03,bench,\"6\"\" grip\"\r\n";
+        assert_eq!(
+            searchable_text("csv", csv),
+            "Columns: date, lift, note\n\
+             date: 2026-02-01 | lift: squat | note: felt good, easy\n\
+             date: 2026-02-03 | lift: bench | note: 6\" grip"
+        );
+    }
+
+    #[test]
+    fn csv_preview_is_capped() {
+        let mut csv = String::from("n\n");
+        for i in 0..CSV_PREVIEW_ROWS + 3 {
+            csv.push_str(&format!("{i}\n"));
+        }
+        let text = searchable_text("csv", &csv);
+        assert!(text.ends_with("… 3 more row(s)"), "{text}");
+        assert_eq!(text.lines().count(), CSV_PREVIEW_ROWS + 2);
+    }
+
+    #[test]
+    fn canvas_keeps_card_text_files_links_and_labels() {
+        let canvas = r#"{"nodes":[
+            {"id":"1","type":"text","text":"Trip ideas"},
+            {"id":"2","type":"file","file":"Travel/Lisbon.md"},
+            {"id":"3","type":"link","url":"https://example.com"},
+            {"id":"4","type":"group","label":"Portugal"}
+        ],"edges":[]}"#;
+        assert_eq!(
+            searchable_text("canvas", canvas),
+            "Trip ideas\n\nTravel/Lisbon.md\n\nhttps://example.com\n\nPortugal"
+        );
+        assert_eq!(searchable_text("canvas", "not json"), "not json");
+    }
+
+    #[test]
+    fn extensions_are_normalised() {
+        assert_eq!(
+            normalize_extensions(&[".TXT", " md ", "md", ""]),
+            ["md", "txt"]
+        );
+        assert_eq!(extension("Notes/Plan.ORG"), "org");
+        assert_eq!(extension("Makefile"), "");
+    }
+}
@@ -0,0 +1,221 @@
+//! YAML frontmatter of vault notes, stored as JSON in `vault_index.frontmatter` so
+//! search_vault can filter on it (`type: workout`, `date >= 2026-01-01`).
+//!
+//! Only the flat subset Obsidian properties use is understood: `key: value` scalars,
+//! inline lists (`[a, b]`) and block lists (`- a`). Nested maps and multi-line strings are
+//! skipped. Keys are lowercased; `true`/`false` and numbers become JSON booleans and numbers,
+//! everything else (dates included) stays a string.
+
+use serde_json::{Map, Value};
+
+/// `(frontmatter, body)`: the YAML between a leading `---` line and the next `---` line.
+/// No frontmatter when the note does not start with `---` or it is never closed.
+pub fn split(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+/// Properties of a note as a JSON object (empty when it has no frontmatter).
+pub fn parse(content: &str) -> Map<String, Value> {
+    let mut fields = Map::new();
+    let Some(yaml) = split(content).0 else {
+        return fields;
+    };
+    // Key whose value is a block list still being read.
+    let mut list: Option<String> = None;
+    for line in yaml.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) || line.starts_with('-') {
+            if let (Some(key), Some(item)) = (&list, line.trim_start().strip_prefix('-'))
+                && let Some(Value::Array(items)) = fields.get_mut(key)
+            {
+                items.push(scalar(item));
+            }
+            continue;
+        }
+        list = None;
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+        if key.is_empty() {
+            continue;
+        }
+        if value.is_empty() {
+            fields.insert(key.clone(), Value::Array(Vec::new()));
+            list = Some(key);
+        } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            let items = inner
+                .split(',')
+                .filter(|i| !i.trim().is_empty())
+                .map(scalar)
+                .collect();
+            fields.insert(key, Value::Array(items));
+        } else {
+            fields.insert(key, scalar(value));
+        }
+    }
+    // `key:` followed by nothing is an empty value, not a list.
+    fields.retain(|_, v| !matches!(v, Value::Array(a) if a.is_empty()));
+    fields
+}
+
+fn scalar(raw: &str) -> Value {
+    let raw = raw.trim();
+    let unquoted = raw
+        .strip_prefix('"')
+        .and_then(|r| r.strip_suffix('"'))
+        .or_else(|| raw.strip_prefix('\'').and_then(|r| r.strip_suffix('\'')));
+    if let Some(s) = unquoted {
+        return Value::String(s.to_string());
+    }
+    match raw {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+    if let Ok(n) = raw.parse::<i64>() {
+        return Value::from(n);
+    }
+    if let Ok(f) = raw.parse::<f64>()
+        && f.is_finite()
+    {
+        return Value::from(f);
+    }
+    Value::String(raw.to_string())
+}
+
+/// Comparison in a [`FieldFilter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldOp {
+    /// Equal, ignoring case; for a list, any item equal.
+    Eq,
+    /// Not [`FieldOp::Eq`] (also true when the field is missing).
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl FieldOp {
+    /// SQL operator for ordered comparisons.
+    pub fn sql(self) -> &'static str {
+        match self {
+            FieldOp::Eq => "=",
+            FieldOp::Ne => "!=",
+            FieldOp::Lt => "<",
+            FieldOp::Le => "<=",
+            FieldOp::Gt => ">",
+            FieldOp::Ge => ">=",
+        }
+    }
+}
+
+/// One condition on a frontmatter field, e.g. `date >= 2026-01-01`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldFilter {
+    pub key: String,
+    pub op: FieldOp,
+    pub value: String,
+}
+
+impl FieldFilter {
+    /// Parse `key: value`, `key = value`, `key != value` or `key <op> value` with `<`, `<=`,
+    /// `>`, `>=`. Dates and strings compare as text, numbers numerically.
+    pub fn parse(condition: &str) -> Result<Self, String> {
+        const OPS: &[(&str, FieldOp)] = &[
+            (">=", FieldOp::Ge),
+            ("<=", FieldOp::Le),
+            ("!=", FieldOp::Ne),
+            (">", FieldOp::Gt),
+            ("<", FieldOp::Lt),
+            ("=", FieldOp::Eq),
+            (":", FieldOp::Eq),
+        ];
+        let (at, token, op) = OPS
+            .iter()
+            .filter_map(|(token, op)| condition.find(token).map(|at| (at, *token, *op)))
+            .min_by_key(|(at, token, _)| (*at, std::cmp::Reverse(token.len())))
+            .ok_or_else(|| format!("'{condition}' is not a condition like 'type: workout'"))?;
+        let key = condition[..at].trim().to_lowercase();
+        let value = condition[at + token.len()..]
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'')
+            .to_string();
+        let valid_key = !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | ' ' | '.'));
+        if !valid_key || value.is_empty() {
+            return Err(format!(
+                "'{condition}' is not a condition like 'type: workout'"
+            ));
+        }
+        Ok(Self { key, op, value })
+    }
+
+    /// JSON path of the field for `json_extract` / `json_each`.
+    pub fn json_path(&self) -> String {
+        format!("$.\"{}\"", self.key)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_scalars_and_lists() {
+        let note = "---\nTitle: \"Leg day\"\ndate: 2026-02-01\nweight: 82.5\nsets: 5\ndone: true\n\
+                    aliases: [legs, squats]\ntags:\n  - workout\n  - gym\nempty:\n---\nbody\n";
+        let fm = Value::Object(parse(note));
+        assert_eq!(
+            fm,
+            serde_json::json!({
+                "title": "Leg day", "date": "2026-02-01", "weight": 82.5, "sets": 5,
+                "done": true, "aliases": ["legs", "squats"], "tags": ["workout", "gym"]
+            })
+        );
+        assert!(parse("no frontmatter\n---\n").is_empty());
+    }
+
+    #[test]
+    fn parses_filter_conditions() {
+        let f = FieldFilter::parse("Type: workout").unwrap();
+        assert_eq!(
+            (f.key.as_str(), f.op, f.value.as_str()),
+            ("type", FieldOp::Eq, "workout")
+        );
+        let f = FieldFilter::parse("date >= 2026-01-01").unwrap();
+        assert_eq!(
+            (f.key.as_str(), f.op, f.value.as_str()),
+            ("date", FieldOp::Ge, "2026-01-01")
+        );
+        assert_eq!(
+            FieldFilter::parse("status != done").unwrap().op,
+            FieldOp::Ne
+        );
+        assert!(FieldFilter::parse("workout").is_err());
+        assert!(FieldFilter::parse("a\"b: c").is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! `.icrabignore`: gitignore-style rules at the workspace root for files the vault indexer
+//! and `grep_dir` should never look at (large exports, attachment folders, private notes).
+//!
+//! Supported syntax, as in `.gitignore`: blank lines and `#` comments; `*`, `?` and `[...]`
+//! wildcards that do not cross `/`; `**` for any number of directories; a trailing `/` to match
+//! directories only; a leading or inner `/` to anchor the pattern to the workspace root
+//! (otherwise it matches a name at any depth); `!` to re-include; `\` to escape. The last
+//! matching rule wins, and everything under an ignored directory is ignored.
+
+use std::path::Path;
+
+use regex_lite::Regex;
+
+use crate::workspace;
+
+#[derive(Debug)]
+struct Rule {
+    regex: Regex,
+    /// Pattern has a `/`: matched against the whole workspace-relative path.
+    anchored: bool,
+    dir_only: bool,
+    negated: bool,
+}
+
+/// Parsed `.icrabignore`; empty (ignores nothing) when the file is missing.
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Rules from `workspace/.icrabignore`. An unreadable file ignores nothing.
+    pub fn load(workspace: &Path) -> Self {
+        match std::fs::read_to_string(workspace::icrabignore_file(workspace)) {
+            Ok(text) => Self::parse(&text),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse gitignore syntax. Patterns that cannot be compiled are skipped.
+    pub fn parse(text: &str) -> Self {
+        let rules = text.lines().filter_map(parse_rule).collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether the workspace-relative `rel_path` (forward slashes) is ignored, itself or
+    /// through one of its parent directories.
+    pub fn is_ignored(&self, rel_path: &str, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let rel_path = rel_path.trim_matches('/');
+        let ignored_parent = rel_path
+            .match_indices('/')
+            .any(|(i, _)| self.matches(&rel_path[..i], true));
+        ignored_parent || self.matches(rel_path, is_dir)
+    }
+
+    /// Last matching rule for `path` itself decides.
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        self.rules
+            .iter()
+            .rev()
+            .find(|r| {
+                (is_dir || !r.dir_only) && r.regex.is_match(if r.anchored { path } else { name })
+            })
+            .is_some_and(|r| !r.negated)
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    if pattern.is_empty() {
+        return None;
+    }
+    let regex = glob_regex(pattern)?;
+    Some(Rule {
+        regex,
+        anchored,
+        dir_only,
+        negated,
+    })
+}
+
+/// A single glob with the wildcards above (`*`, `?`, `[...]`, `**`), matching a whole name
+/// or slash-separated path; None when it does not compile.
+pub fn glob_regex(glob: &str) -> Option<Regex> {
+    Regex::new(&format!("^{}$", glob_to_regex(glob))).ok()
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::new();
+    let mut chars = glob.chars().peekable();
+    let mut at_segment_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') && at_segment_start => {
+                chars.next();
+                match chars.next() {
+                    // `**/`: zero or more directories.
+                    Some('/') => out.push_str("(?:.*/)?"),
+                    // Trailing `**`: everything below.
+                    None => out.push_str(".*"),
+                    // `**x` is just `*` followed by x.
+                    Some(other) => {
+                        out.push_str("[^/]*");
+                        out.push_str(&regex_lite::escape(&other.to_string()));
+                        at_segment_start = false;
+                    }
+                }
+                continue;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => {
+                let mut class = String::from("[");
+                if matches!(chars.peek(), Some('!') | Some('^')) {
+                    chars.next();
+                    class.push('^');
+                }
+                let mut closed = false;
+                for ch in chars.by_ref() {
+                    if ch == ']' {
+                        closed = true;
+                        break;
+                    }
+                    if matches!(ch, '\\' | '[' | '&' | '~') {
+                        class.push('\\');
+                    }
+                    class.push(ch);
+                }
+                if closed {
+                    out.push_str(&class);
+                    out.push(']');
+                } else {
+                    out.push_str(&regex_lite::escape(&class));
+                }
+            }
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    out.push_str(&regex_lite::escape(&next.to_string()));
+                }
+            }
+            c => out.push_str(&regex_lite::escape(&c.to_string())),
+        }
+        at_segment_start = c == '/';
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_match_at_any_depth_and_anchored_paths_at_root() {
+        let rules = IgnoreRules::parse("# exports\n*.pdf\n/Private\nArchive/2024/\n");
+        assert!(rules.is_ignored("scan.pdf", false));
+        assert!(rules.is_ignored("Travel/tickets/scan.pdf", false));
+        assert!(rules.is_ignored("Private/diary.md", false));
+        assert!(!rules.is_ignored("Notes/Private/diary.md", false));
+        assert!(rules.is_ignored("Archive/2024", true));
+        assert!(rules.is_ignored("Archive/2024/jan.md", false));
+        assert!(!rules.is_ignored("Archive/2024", false), "dir-only rule");
+        assert!(!rules.is_ignored("Archive/2025/jan.md", false));
+    }
+
+    #[test]
+    fn double_star_negation_and_classes() {
+        let rules =
+            IgnoreRules::parse("**/attachments\nlogs/**\ndraft-[0-9].md\n*.csv\n!keep.csv\n");
+        assert!(rules.is_ignored("attachments/a.png", false));
+        assert!(rules.is_ignored("Trips/2026/attachments/b.png", false));
+        assert!(rules.is_ignored("logs/x/y.md", false));
+        assert!(rules.is_ignored("draft-3.md", false));
+        assert!(!rules.is_ignored("draft-x.md", false));
+        assert!(rules.is_ignored("lifts.csv", false));
+        assert!(!rules.is_ignored("Data/keep.csv", false));
+    }
+
+    #[test]
+    fn missing_file_ignores_nothing() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let rules = IgnoreRules::load(tmp.path());
+        assert!(rules.is_empty());
+        assert!(!rules.is_ignored("anything.md", false));
+    }
+}
@@ -4,12 +4,18 @@
 //! # How it works
 //!
 //! [`scan_vault`] recursively walks the workspace directory, skipping
-//! `.git/`, `.icrab/`, and `.obsidian/`.  For every `.md` file it finds it
-//! compares the on-disk modification time against the timestamp stored in
-//! `vault_index`.  If the file is new or has been modified it upserts the
-//! content.  After the walk, any row in `vault_index` whose file no longer
-//! exists on disk is removed (the FTS5 delete triggers handle the shadow
-//! table automatically).
+//! `.git/`, `.icrab/`, `.obsidian/` and whatever `.icrabignore` lists (see
+//! [`ignore`](super::ignore)).  For every file with an indexed
+//! extension (`[index] extensions`, default `md`, `txt`, `org`, `csv` and
+//! `canvas`) it compares the on-disk modification time against the timestamp
+//! stored in `vault_index`.  If the file is new or its text has changed it
+//! upserts its searchable text (see [`extract`](super::extract)) and, for
+//! Markdown, its frontmatter (see [`frontmatter`](super::frontmatter)) and
+//! its tags (`vault_tags`, see [`tags`](super::tags)); a file whose mtime
+//! moved but whose text hashes the same (git pulls touch every file) only
+//! gets its stored mtime updated.  After the walk, any row in `vault_index`
+//! whose file no longer exists on disk is removed (the delete triggers clean
+//! up the FTS5 shadow table and tags).
 //!
 //! # Threading
 //!
@@ -28,20 +34,26 @@
 //! The indexer should run:
 //! - **On startup** — wired in `main.rs` immediately after the DB is opened.
 //! - **After every Git sync** — called at the end of the sync task (Phase 5).
+//! - **When files change** — the [`watcher`](super::watcher) re-indexes just
+//!   the changed paths with [`index_paths`] a couple of seconds after edits.
 
 use std::collections::HashSet;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::UNIX_EPOCH;
 
-use crate::memory::db::{BrainDb, DbError};
+use crate::config::Config;
+use crate::memory::db::{BrainDb, DbError, vault_content_hash};
+use crate::memory::ignore::IgnoreRules;
+use crate::memory::{extract, frontmatter, tags};
+use crate::workspace;
 
 // ---------------------------------------------------------------------------
 // Public types
 // ---------------------------------------------------------------------------
 
 /// Directories to skip during the vault walk (relative names, not full paths).
-const SKIP_DIRS: &[&str] = &[".git", ".icrab", ".obsidian"];
+pub const SKIP_DIRS: &[&str] = &[".git", ".icrab", ".obsidian"];
 
 /// Summary of a completed vault scan.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -50,6 +62,9 @@ pub struct ScanStats {
     pub indexed: usize,
     /// Files already up-to-date (mtime matched stored value — skipped).
     pub skipped: usize,
+    /// Files with a new mtime but the same content (e.g. touched by a git pull); only
+    /// their stored mtime was updated.
+    pub unchanged: usize,
     /// Stale `vault_index` rows removed (files deleted from disk since last scan).
     pub removed: usize,
 }
@@ -58,8 +73,8 @@ impl std::fmt::Display for ScanStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{} indexed, {} up-to-date, {} removed",
-            self.indexed, self.skipped, self.removed
+            "{} indexed, {} up-to-date, {} unchanged content, {} removed",
+            self.indexed, self.skipped, self.unchanged, self.removed
         )
     }
 }
@@ -91,17 +106,45 @@ impl From<DbError> for IndexerError {
 #[derive(Debug, Clone)]
 pub struct VaultIndexer {
     db: Arc<BrainDb>,
+    extensions: Vec<String>,
 }
 
 impl VaultIndexer {
-    /// Create a new indexer bound to `db`.
+    /// Create a new indexer bound to `db`, indexing the default extensions.
     pub fn new(db: Arc<BrainDb>) -> Self {
-        Self { db }
+        Self {
+            db,
+            extensions: extract::normalize_extensions(extract::DEFAULT_EXTENSIONS),
+        }
+    }
+
+    /// Indexer for `[index] extensions` (the defaults when unset).
+    pub fn from_config(cfg: &Config, db: Arc<BrainDb>) -> Self {
+        let indexer = Self::new(db);
+        match cfg.index.as_ref().and_then(|i| i.extensions.as_deref()) {
+            Some(extensions) => indexer.with_extensions(extensions),
+            None => indexer,
+        }
+    }
+
+    /// Index files with these extensions (case-insensitive, with or without the dot).
+    pub fn with_extensions<S: AsRef<str>>(mut self, extensions: &[S]) -> Self {
+        self.extensions = extract::normalize_extensions(extensions);
+        self
     }
 
     /// Run the scan synchronously. Intended for `spawn_blocking`.
     pub fn scan(&self, workspace: &Path) -> Result<ScanStats, IndexerError> {
-        scan_vault(workspace, &self.db)
+        scan_vault_extensions(workspace, &self.db, &self.extensions)
+    }
+
+    /// Re-index only `paths` synchronously (see [`index_paths`]).
+    pub fn index_paths(
+        &self,
+        workspace: &Path,
+        paths: &[PathBuf],
+    ) -> Result<ScanStats, IndexerError> {
+        index_paths(workspace, &self.db, &self.extensions, paths)
     }
 }
 
@@ -109,15 +152,41 @@ impl VaultIndexer {
 // Public entry point
 // ---------------------------------------------------------------------------
 
-/// Synchronously scan all `.md` files under `workspace`, upsert those that
-/// are new or modified, and prune DB entries whose files no longer exist.
+/// Synchronously scan all files with a default extension under `workspace`,
+/// upsert those that are new or modified, and prune DB entries whose files
+/// no longer exist.
 ///
 /// Returns a [`ScanStats`] summary.
 pub fn scan_vault(workspace: &Path, db: &BrainDb) -> Result<ScanStats, IndexerError> {
+    scan_vault_extensions(
+        workspace,
+        db,
+        &extract::normalize_extensions(extract::DEFAULT_EXTENSIONS),
+    )
+}
+
+/// [`scan_vault`] for the given (normalised, see
+/// [`extract::normalize_extensions`]) extensions.  Indexed files with other
+/// extensions, or now matched by `.icrabignore`, are pruned like deleted ones.
+pub fn scan_vault_extensions(
+    workspace: &Path,
+    db: &BrainDb,
+    extensions: &[String],
+) -> Result<ScanStats, IndexerError> {
     let mut stats = ScanStats::default();
     let mut live_paths: HashSet<String> = HashSet::new();
-
-    walk_dir(workspace, workspace, &mut live_paths, db, &mut stats)?;
+    // Read on every scan so edits to .icrabignore apply without a restart.
+    let ignore = IgnoreRules::load(workspace);
+
+    walk_dir(
+        workspace,
+        workspace,
+        extensions,
+        &ignore,
+        &mut live_paths,
+        db,
+        &mut stats,
+    )?;
 
     // Remove entries for files that are no longer on disk.
     stats.removed = db.delete_vault_stale(&live_paths)?;
@@ -125,16 +194,74 @@ pub fn scan_vault(workspace: &Path, db: &BrainDb) -> Result<ScanStats, IndexerEr
     Ok(stats)
 }
 
+/// Re-index only `paths` (under `workspace`), e.g. those reported by the
+/// [`watcher`](super::watcher): new or modified files are upserted, paths
+/// that no longer exist (files or whole directories) are removed, and
+/// directories that appeared are walked.  A change to `.icrabignore` runs a
+/// full [`scan_vault_extensions`] instead.
+pub fn index_paths(
+    workspace: &Path,
+    db: &BrainDb,
+    extensions: &[String],
+    paths: &[PathBuf],
+) -> Result<ScanStats, IndexerError> {
+    if paths.contains(&workspace::icrabignore_file(workspace)) {
+        return scan_vault_extensions(workspace, db, extensions);
+    }
+    let mut stats = ScanStats::default();
+    // Walked directories report their files here; nothing is pruned from it.
+    let mut live_paths: HashSet<String> = HashSet::new();
+    let ignore = IgnoreRules::load(workspace);
+
+    for path in paths {
+        let rel = match path.strip_prefix(workspace) {
+            Ok(r) => r.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+        if rel.is_empty() || rel.split('/').any(|c| SKIP_DIRS.contains(&c)) {
+            continue;
+        }
+        match std::fs::metadata(path) {
+            Ok(meta) if ignore.is_ignored(&rel, meta.is_dir()) => {}
+            Ok(meta) if meta.is_dir() => walk_dir(
+                path,
+                workspace,
+                extensions,
+                &ignore,
+                &mut live_paths,
+                db,
+                &mut stats,
+            )?,
+            Ok(meta) if meta.is_file() => {
+                let ext = extract::extension(&rel);
+                if extensions.contains(&ext) {
+                    index_file(path, &rel, &ext, &meta, db, &mut stats)?;
+                }
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                stats.removed += db.delete_vault_path(&rel)?;
+            }
+            Err(e) => eprintln!("vault indexer: metadata {}: {e}", path.display()),
+        }
+    }
+
+    Ok(stats)
+}
+
 // ---------------------------------------------------------------------------
 // Private helpers
 // ---------------------------------------------------------------------------
 
-/// Recursive directory walker.  Skips dirs in [`SKIP_DIRS`] and non-`.md`
-/// files.  Errors reading individual entries are logged but not fatal so that
-/// one bad file doesn't abort the whole scan.
+/// Recursive directory walker.  Skips dirs in [`SKIP_DIRS`], paths matched
+/// by `ignore` and files without an indexed extension.  Errors reading
+/// individual entries are logged but not fatal so that one bad file doesn't
+/// abort the whole scan.
 fn walk_dir(
     dir: &Path,
     workspace: &Path,
+    extensions: &[String],
+    ignore: &IgnoreRules,
     live_paths: &mut HashSet<String>,
     db: &BrainDb,
     stats: &mut ScanStats,
@@ -169,54 +296,89 @@ fn walk_dir(
         let name = entry.file_name();
         let name_str = name.to_string_lossy();
 
+        // Build a workspace-relative path with forward slashes.
+        let rel = match path.strip_prefix(workspace) {
+            Ok(r) => r.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+        if ignore.is_ignored(&rel, meta.is_dir()) {
+            continue;
+        }
+
         if meta.is_dir() {
             if SKIP_DIRS.contains(&name_str.as_ref()) {
                 continue;
             }
-            walk_dir(&path, workspace, live_paths, db, stats)?;
+            walk_dir(&path, workspace, extensions, ignore, live_paths, db, stats)?;
         } else if meta.is_file() {
-            // Only index Markdown files.
-            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            let ext = extract::extension(&name_str);
+            if !extensions.contains(&ext) {
                 continue;
             }
 
-            // Build a workspace-relative path with forward slashes.
-            let rel = match path.strip_prefix(workspace) {
-                Ok(r) => r.to_string_lossy().replace('\\', "/"),
-                Err(_) => continue,
-            };
-
-            let mtime = mtime_unix(&meta);
-
             // Mark as live regardless of whether we upsert.
             live_paths.insert(rel.clone());
 
-            // Check whether this file is already up-to-date.
-            let stored = db
-                .get_vault_last_modified(&rel)
-                .map_err(IndexerError::from)?;
+            index_file(&path, &rel, &ext, &meta, db, stats)?;
+        }
+    }
 
-            if stored == Some(mtime) {
-                stats.skipped += 1;
-                continue;
-            }
+    Ok(())
+}
 
-            // Read and upsert.
-            match std::fs::read_to_string(&path) {
-                Ok(content) => {
-                    db.upsert_vault_entry(&rel, &content, mtime)
-                        .map_err(IndexerError::from)?;
-                    stats.indexed += 1;
-                }
-                Err(e) => {
-                    // Non-UTF-8 or unreadable files: log, keep in live_paths,
-                    // skip upsert.  We don't remove the old entry either.
-                    eprintln!("vault indexer: read {}: {e}", path.display());
-                }
+/// Upsert one file unless its stored mtime is current.
+fn index_file(
+    path: &Path,
+    rel: &str,
+    ext: &str,
+    meta: &std::fs::Metadata,
+    db: &BrainDb,
+    stats: &mut ScanStats,
+) -> Result<(), IndexerError> {
+    let mtime = mtime_unix(meta);
+
+    // Check whether this file is already up-to-date.
+    let stored = db
+        .get_vault_last_modified(rel)
+        .map_err(IndexerError::from)?;
+
+    if stored == Some(mtime) {
+        stats.skipped += 1;
+        return Ok(());
+    }
+
+    // Read and upsert.
+    match std::fs::read_to_string(path) {
+        Ok(raw) => {
+            // Frontmatter and tags are Markdown (Obsidian) conventions.
+            let (fields, note_tags) = if ext == "md" {
+                (frontmatter::parse(&raw), tags::extract_tags(&raw))
+            } else {
+                Default::default()
+            };
+            let content = extract::searchable_text(ext, &raw);
+            let fields = serde_json::Value::Object(fields).to_string();
+            // Same text as indexed: keep the FTS entry, tags and embeddings.
+            let hash = vault_content_hash(&content, &fields);
+            if db
+                .touch_vault_entry(rel, &hash, mtime)
+                .map_err(IndexerError::from)?
+            {
+                stats.unchanged += 1;
+                return Ok(());
             }
+            db.upsert_vault_note(rel, &content, mtime, &fields)
+                .map_err(IndexerError::from)?;
+            db.set_vault_tags(rel, &note_tags)
+                .map_err(IndexerError::from)?;
+            stats.indexed += 1;
+        }
+        Err(e) => {
+            // Non-UTF-8 or unreadable files: log, keep in live_paths,
+            // skip upsert.  We don't remove the old entry either.
+            eprintln!("vault indexer: read {}: {e}", path.display());
         }
     }
-
     Ok(())
 }
 
@@ -305,6 +467,43 @@ mod tests {
         assert_eq!(stats.indexed, 1, "only note.md should be indexed");
     }
 
+    #[test]
+    fn scan_indexes_plaintext_formats() {
+        let ws = TempDir::new().unwrap();
+        let (_db_tmp, db) = temp_db();
+
+        write_md(ws.path(), "todo.TXT", "buy #milk");
+        write_md(ws.path(), "Agenda.org", "* TODO call plumber");
+        write_md(ws.path(), "lifts.csv", "date,lift\n2026-02-01,deadlift\n");
+
+        let stats = scan_vault(ws.path(), &db).unwrap();
+        assert_eq!(stats.indexed, 3);
+        assert_eq!(
+            db.get_vault_content("lifts.csv").unwrap().as_deref(),
+            Some("Columns: date, lift\ndate: 2026-02-01 | lift: deadlift")
+        );
+        assert_eq!(db.vault_fts_count("\"plumber\"").unwrap(), 1);
+        assert!(
+            db.vault_tag_counts().unwrap().is_empty(),
+            "#tags are only read from Markdown"
+        );
+    }
+
+    #[test]
+    fn configured_extensions_replace_defaults() {
+        let ws = TempDir::new().unwrap();
+        let (_db_tmp, db) = temp_db();
+
+        write_md(ws.path(), "note.md", "markdown");
+        write_md(ws.path(), "notes.txt", "plain");
+        scan_vault(ws.path(), &db).unwrap();
+
+        let indexer = VaultIndexer::new(Arc::clone(&db)).with_extensions(&[".md"]);
+        let stats = indexer.scan(ws.path()).unwrap();
+        assert_eq!(stats.removed, 1, "notes.txt is no longer indexed");
+        assert_eq!(db.list_vault_filepaths().unwrap(), vec!["note.md"]);
+    }
+
     #[test]
     fn scan_recursive_subdirectories() {
         let ws = TempDir::new().unwrap();
@@ -411,6 +610,30 @@ mod tests {
         assert_eq!(stored.as_deref(), Some("updated_content_beta"));
     }
 
+    #[test]
+    fn touched_file_with_same_content_only_updates_mtime() {
+        let ws = TempDir::new().unwrap();
+        let (_db_tmp, db) = temp_db();
+        write_md(ws.path(), "pulled.md", "same_text_gamma #gym");
+        scan_vault(ws.path(), &db).unwrap();
+        let mtime = db.get_vault_last_modified("pulled.md").unwrap().unwrap();
+        db.replace_vault_embeddings("pulled.md", mtime, "m", &[("same".into(), vec![1.0])])
+            .unwrap();
+
+        // Pretend the file was touched: only the stored mtime is behind.
+        let hash = vault_content_hash("same_text_gamma #gym", "{}");
+        assert!(db.touch_vault_entry("pulled.md", &hash, 0).unwrap());
+        let stats = scan_vault(ws.path(), &db).unwrap();
+        assert_eq!((stats.indexed, stats.unchanged), (0, 1));
+        assert_eq!(
+            db.get_vault_last_modified("pulled.md").unwrap(),
+            Some(mtime)
+        );
+        assert!(db.vault_files_needing_embeddings("m").unwrap().is_empty());
+        assert_eq!(db.vault_fts_count("\"same_text_gamma\"").unwrap(), 1);
+        assert_eq!(db.vault_tag_counts().unwrap(), vec![("gym".to_string(), 1)]);
+    }
+
     // ── Stale entry pruning ──────────────────────────────────────────────────
 
     #[test]
@@ -465,6 +688,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn scan_indexes_tags_and_drops_them_with_the_note() {
+        let (ws, db) = temp_db();
+        write_md(ws.path(), "dune.md", "---\ntags: [book]\n---\nRead #sci-fi");
+        let gone = write_md(ws.path(), "old.md", "#book");
+        scan_vault(ws.path(), &db).unwrap();
+        assert_eq!(
+            db.vault_tag_counts().unwrap(),
+            [("book".to_string(), 2), ("sci-fi".to_string(), 1)]
+        );
+
+        std::fs::remove_file(gone).unwrap();
+        scan_vault(ws.path(), &db).unwrap();
+        assert_eq!(db.vault_tag_counts().unwrap()[0], ("book".to_string(), 1));
+    }
+
     // ── Content stored in vault_index ────────────────────────────────────────
 
     #[test]
@@ -495,6 +734,57 @@ mod tests {
         assert_eq!(paths, vec!["Daily log/2026-02-20.md"]);
     }
 
+    #[test]
+    fn scan_honours_icrabignore() {
+        let ws = TempDir::new().unwrap();
+        let (_db_tmp, db) = temp_db();
+
+        write_md(ws.path(), "note.md", "keep");
+        write_md(ws.path(), "Private/diary.md", "secret");
+        write_md(ws.path(), "Exports/big.csv", "a,b\n1,2\n");
+        scan_vault(ws.path(), &db).unwrap();
+        assert_eq!(db.list_vault_filepaths().unwrap().len(), 3);
+
+        std::fs::write(ws.path().join(".icrabignore"), "Private/\n*.csv\n").unwrap();
+        let stats = scan_vault(ws.path(), &db).unwrap();
+        assert_eq!(stats.removed, 2);
+        assert_eq!(db.list_vault_filepaths().unwrap(), vec!["note.md"]);
+    }
+
+    #[test]
+    fn index_paths_updates_only_changed_entries() {
+        let ws = TempDir::new().unwrap();
+        let (_db_tmp, db) = temp_db();
+
+        let note = write_md(ws.path(), "note.md", "first draft");
+        write_md(ws.path(), "Trips/Lisbon.md", "tram 28");
+        scan_vault(ws.path(), &db).unwrap();
+
+        std::fs::write(&note, "second draft with more words").unwrap();
+        let file = std::fs::File::options().write(true).open(&note).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(5))
+            .unwrap();
+        std::fs::remove_dir_all(ws.path().join("Trips")).unwrap();
+        let added = write_md(ws.path(), "Gym/legs.md", "squats");
+
+        let exts = extract::normalize_extensions(extract::DEFAULT_EXTENSIONS);
+        let paths = [
+            note,
+            ws.path().join("Trips"),
+            added.parent().unwrap().to_path_buf(),
+        ];
+        let stats = index_paths(ws.path(), &db, &exts, &paths).unwrap();
+        assert_eq!((stats.indexed, stats.removed), (2, 1));
+        assert_eq!(
+            db.get_vault_content("note.md").unwrap().as_deref(),
+            Some("second draft with more words")
+        );
+        assert_eq!(
+            db.list_vault_filepaths().unwrap(),
+            vec!["Gym/legs.md", "note.md"]
+        );
+    }
+
     // ── FTS5 search after indexing ────────────────────────────────────────────
 
     #[test]
@@ -551,11 +841,13 @@ mod tests {
         let s = ScanStats {
             indexed: 3,
             skipped: 7,
+            unchanged: 2,
             removed: 1,
         };
         let text = s.to_string();
         assert!(text.contains("3 indexed"));
         assert!(text.contains("7 up-to-date"));
+        assert!(text.contains("2 unchanged content"));
         assert!(text.contains("1 removed"));
     }
 
@@ -0,0 +1,115 @@
+//! Links between vault notes, for finding orphans (notes nothing links to and that link to
+//! nothing).
+//!
+//! Both Obsidian link forms count: `[[Note]]` / `[[Folder/Note#Heading|alias]]` / `![[embed]]`
+//! and Markdown `[text](Folder/Note.md)` (external URLs are not links between notes). As in
+//! Obsidian, a target resolves by path or by bare note name, case-insensitively, with or
+//! without the `.md` extension.
+
+use std::collections::{HashMap, HashSet};
+
+/// Normalised link targets of a note, in order of appearance.
+pub fn link_targets(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("[[") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("]]") else { break };
+        push_target(&after[..end], &mut targets);
+        rest = &after[end + 2..];
+    }
+    let mut rest = content;
+    while let Some(start) = rest.find("](") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find(')') else { break };
+        let target = &after[..end];
+        if !target.contains("://") && !target.starts_with("mailto:") {
+            push_target(&target.replace("%20", " "), &mut targets);
+        }
+        rest = &after[end + 1..];
+    }
+    targets
+}
+
+fn push_target(raw: &str, targets: &mut Vec<String>) {
+    let target = raw.split(['|', '#', '^']).next().unwrap_or("");
+    let target = normalize(target.trim().trim_start_matches("./"));
+    if !target.is_empty() {
+        targets.push(target);
+    }
+}
+
+/// Lowercase path without a `.md` extension.
+fn normalize(path: &str) -> String {
+    let lower = path.to_lowercase();
+    lower.strip_suffix(".md").unwrap_or(&lower).to_string()
+}
+
+/// Markdown notes among `notes` (filepath, content) with no resolvable link in or out,
+/// sorted by path.
+pub fn orphans(notes: &[(String, String)]) -> Vec<String> {
+    let markdown: Vec<&(String, String)> = notes
+        .iter()
+        .filter(|(fp, _)| fp.to_lowercase().ends_with(".md"))
+        .collect();
+    // Lookup by full path and by bare name (first note wins for duplicate names).
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+    for (i, (fp, _)) in markdown.iter().enumerate() {
+        let path = normalize(fp);
+        let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+        by_key.entry(name).or_insert(i);
+        by_key.insert(path, i);
+    }
+    let mut linked: HashSet<usize> = HashSet::new();
+    for (i, (_, content)) in markdown.iter().enumerate() {
+        for target in link_targets(content) {
+            if let Some(&j) = by_key.get(&target)
+                && j != i
+            {
+                linked.insert(i);
+                linked.insert(j);
+            }
+        }
+    }
+    let mut out: Vec<String> = markdown
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !linked.contains(i))
+        .map(|(_, (fp, _))| fp.clone())
+        .collect();
+    out.sort();
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_wikilinks_and_markdown_links() {
+        let note = "See [[Trips/Lisbon#Day 1|the trip]], ![[chart.png]] and [plan](Gym/Leg%20day.md). \
+                    Not [a site](https://example.com).";
+        assert_eq!(
+            link_targets(note),
+            ["trips/lisbon", "chart.png", "gym/leg day"]
+        );
+    }
+
+    #[test]
+    fn orphans_have_no_links_either_way() {
+        let notes = vec![
+            ("Index.md".to_string(), "Start at [[lisbon]]".to_string()),
+            ("Trips/Lisbon.md".to_string(), "tram".to_string()),
+            (
+                "Ideas.md".to_string(),
+                "links to [[Nowhere]] and [[Ideas]]".to_string(),
+            ),
+            ("lifts.csv".to_string(), "no links".to_string()),
+        ];
+        assert_eq!(orphans(&notes), ["Ideas.md"]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Routine brain.db upkeep (`[database] maintenance-minutes`).
+//!
+//! In WAL mode SQLite never shrinks the `-wal` file by itself, and its automatic checkpoints
+//! give up whenever a reader is active, so on iSH the file grows until the app is killed. [`spawn_maintenance_loop`] runs `PRAGMA optimize` (a full `ANALYZE` once a day)
+//! and then checkpoints with `TRUNCATE`, at startup and every interval.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::memory::db::{BrainDb, DbError};
+
+/// Minutes between maintenance passes when `[database] maintenance-minutes` is unset.
+pub const DEFAULT_INTERVAL_MINUTES: u64 = 30;
+
+/// Time between full `ANALYZE` runs.
+const ANALYZE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// What one maintenance pass did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MaintenanceStats {
+    /// Frames the WAL held before it was checkpointed and truncated.
+    pub checkpointed: usize,
+    pub analyzed: bool,
+}
+
+impl std::fmt::Display for MaintenanceStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} WAL frame(s) checkpointed", self.checkpointed)?;
+        if self.analyzed {
+            write!(f, ", statistics re-analyzed")?;
+        }
+        Ok(())
+    }
+}
+
+/// One pass: optimize (with `ANALYZE` when `analyze`), then checkpoint and truncate the WAL,
+/// which also takes in the statistics just written.
+pub fn run(db: &BrainDb, analyze: bool) -> Result<MaintenanceStats, DbError> {
+    db.optimize(analyze)?;
+    Ok(MaintenanceStats {
+        checkpointed: db.checkpoint()?,
+        analyzed: analyze,
+    })
+}
+
+/// Spawn a background task that runs a maintenance pass now and every `interval_minutes`.
+/// Errors are logged but never fatal.
+pub fn spawn_maintenance_loop(db: Arc<BrainDb>, interval_minutes: u64) {
+    let interval = Duration::from_secs(interval_minutes.max(1) * 60);
+    tokio::spawn(async move {
+        let mut last_analyze: Option<Instant> = None;
+        loop {
+            let analyze = last_analyze.is_none_or(|t| t.elapsed() >= ANALYZE_INTERVAL);
+            let db = Arc::clone(&db);
+            match tokio::task::spawn_blocking(move || run(&db, analyze)).await {
+                Ok(Ok(stats)) => {
+                    if analyze {
+                        last_analyze = Some(Instant::now());
+                    }
+                    if stats.checkpointed > 0 || stats.analyzed {
+                        eprintln!("brain.db maintenance: {stats}");
+                    }
+                }
+                Ok(Err(e)) => eprintln!("brain.db maintenance warning: {e}"),
+                Err(e) => eprintln!("brain.db maintenance task error: {e}"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace;
+
+    #[test]
+    fn pass_truncates_the_wal() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = BrainDb::open(tmp.path()).unwrap();
+        for i in 0..50 {
+            db.upsert_vault_entry(&format!("n{i}.md"), &"words ".repeat(200), i)
+                .unwrap();
+        }
+        let wal = workspace::brain_db_path(tmp.path()).with_extension("db-wal");
+        assert!(std::fs::metadata(&wal).unwrap().len() > 0);
+
+        let stats = run(&db, true).unwrap();
+        assert!(stats.checkpointed > 0, "{stats}");
+        assert!(stats.analyzed);
+        assert_eq!(std::fs::metadata(&wal).unwrap().len(), 0);
+        assert_eq!(db.list_vault_filepaths().unwrap().len(), 50);
+    }
+}
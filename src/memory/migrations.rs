@@ -0,0 +1,706 @@
+//! Versioned schema migrations for [`BrainDb`](super::db::BrainDb).
+//!
+//! `schema_version` records every applied step. On open, each step in [`MIGRATIONS`] with a
+//! higher version than the newest recorded one runs in its own transaction, together with
+//! its `schema_version` row, so a failed step leaves the database at the previous version.
+//!
+//! To change the schema, append a step with the next version number. Never edit, reorder or
+//! remove a released step: existing databases have already applied it.
+//!
+//! Steps 1 and 2 are the original schema. Steps 3–7 are the tables and columns added after it
+//! but before versioning existed, so an unversioned database may already contain any of them.
+//! Every later step is one migration. All steps are idempotent (`IF NOT EXISTS`, column
+//! checks): keep new steps that way, so running one against a database that already has its
+//! changes is harmless.
+
+use rusqlite::{Connection, params};
+
+use super::db::DbError;
+
+/// One ordered schema change.
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    apply: fn(&Connection) -> Result<(), DbError>,
+}
+
+/// Every schema change, oldest first. Versions are consecutive from 1.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "chat history and vault index",
+        apply: v1_chat_history_and_vault_index,
+    },
+    Migration {
+        version: 2,
+        name: "chat full-text search",
+        apply: v2_chat_fts,
+    },
+    Migration {
+        version: 3,
+        name: "chat location and runtime allow-list",
+        apply: v3_location_and_allowlist,
+    },
+    Migration {
+        version: 4,
+        name: "llm usage and response cache",
+        apply: v4_llm_usage_and_cache,
+    },
+    Migration {
+        version: 5,
+        name: "vault embeddings",
+        apply: v5_vault_embeddings,
+    },
+    Migration {
+        version: 6,
+        name: "long-term memories",
+        apply: v6_memories,
+    },
+    Migration {
+        version: 7,
+        name: "rolling summary watermark",
+        apply: v7_folded_through,
+    },
+    Migration {
+        version: 8,
+        name: "archived session summaries",
+        apply: v8_session_archive,
+    },
+    Migration {
+        version: 9,
+        name: "vault tags",
+        apply: v9_vault_tags,
+    },
+    Migration {
+        version: 10,
+        name: "entity memory",
+        apply: v10_entities,
+    },
+    Migration {
+        version: 11,
+        name: "vault frontmatter",
+        apply: v11_vault_frontmatter,
+    },
+    Migration {
+        version: 12,
+        name: "vault content hash",
+        apply: v12_vault_content_hash,
+    },
+    Migration {
+        version: 13,
+        name: "seen feed items",
+        apply: v13_feed_seen,
+    },
+    Migration {
+        version: 14,
+        name: "web fetch cache",
+        apply: v14_web_cache,
+    },
+    Migration {
+        version: 15,
+        name: "habits",
+        apply: v15_habits,
+    },
+    Migration {
+        version: 16,
+        name: "workout sets",
+        apply: v16_workout_sets,
+    },
+    Migration {
+        version: 17,
+        name: "expenses",
+        apply: v17_expenses,
+    },
+    Migration {
+        version: 18,
+        name: "bookmarks",
+        apply: v18_bookmarks,
+    },
+    Migration {
+        version: 19,
+        name: "cron_runs",
+        apply: v19_cron_runs,
+    },
+];
+
+/// Version a fully migrated database is at.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// Newest applied version (0 for a new or pre-versioning database).
+pub fn current_version(conn: &Connection) -> Result<u32, DbError> {
+    Ok(conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// Bring the database up to [`latest_version`]. Refuses databases written by a newer build.
+pub fn run(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+             version    INTEGER PRIMARY KEY,
+             name       TEXT    NOT NULL,
+             applied_at INTEGER NOT NULL
+         );",
+    )?;
+    let current = current_version(conn)?;
+    if current > latest_version() {
+        return Err(DbError(format!(
+            "brain.db is at schema version {current}, newer than this build supports ({}); \
+             upgrade icrab",
+            latest_version()
+        )));
+    }
+    for m in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (m.apply)(&tx)
+            .map_err(|e| DbError(format!("migration {} ({}): {}", m.version, m.name, e.0)))?;
+        tx.execute(
+            "INSERT INTO schema_version (version, name, applied_at)
+             VALUES (?1, ?2, strftime('%s','now'))",
+            params![m.version, m.name],
+        )?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Whether `table` has a column called `column`.
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool, DbError> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let found = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .any(|r| r.map(|n| n == column).unwrap_or(false));
+    Ok(found)
+}
+
+// ---------------------------------------------------------------------------
+// Steps
+// ---------------------------------------------------------------------------
+
+fn v1_chat_history_and_vault_index(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS chat_history (
+             id           INTEGER PRIMARY KEY AUTOINCREMENT,
+             chat_id      TEXT    NOT NULL,
+             session_id   TEXT    NOT NULL DEFAULT '',
+             role         TEXT    NOT NULL,
+             content      TEXT    NOT NULL,
+             tool_call_id TEXT,
+             tool_calls   TEXT,
+             timestamp    DATETIME DEFAULT CURRENT_TIMESTAMP
+         );
+         -- Legacy index kept for compatibility; new queries use idx_chat_history_chat_session
+         CREATE INDEX IF NOT EXISTS idx_chat_history_chat_id
+             ON chat_history(chat_id, id);
+
+         CREATE TABLE IF NOT EXISTS chat_summary (
+             chat_id            TEXT PRIMARY KEY,
+             current_session_id TEXT NOT NULL DEFAULT '',
+             summary            TEXT NOT NULL DEFAULT ''
+         );
+
+         CREATE TABLE IF NOT EXISTS vault_index (
+             filepath      TEXT    PRIMARY KEY,
+             content       TEXT,
+             last_modified INTEGER
+         );
+         CREATE VIRTUAL TABLE IF NOT EXISTS vault_fts USING fts5(
+             filepath, content,
+             content=vault_index,
+             content_rowid=rowid
+         );
+
+         -- Triggers: keep vault_fts in sync with vault_index
+         CREATE TRIGGER IF NOT EXISTS vault_index_ai
+             AFTER INSERT ON vault_index BEGIN
+                 INSERT INTO vault_fts(rowid, filepath, content)
+                 VALUES (new.rowid, new.filepath, new.content);
+             END;
+         CREATE TRIGGER IF NOT EXISTS vault_index_ad
+             AFTER DELETE ON vault_index BEGIN
+                 INSERT INTO vault_fts(vault_fts, rowid, filepath, content)
+                 VALUES ('delete', old.rowid, old.filepath, old.content);
+             END;
+         CREATE TRIGGER IF NOT EXISTS vault_index_au
+             AFTER UPDATE ON vault_index BEGIN
+                 INSERT INTO vault_fts(vault_fts, rowid, filepath, content)
+                 VALUES ('delete', old.rowid, old.filepath, old.content);
+                 INSERT INTO vault_fts(rowid, filepath, content)
+                 VALUES (new.rowid, new.filepath, new.content);
+             END;",
+    )?;
+
+    // Session columns arrived after the first release.
+    if !has_column(conn, "chat_history", "session_id")? {
+        conn.execute_batch(
+            "ALTER TABLE chat_history ADD COLUMN session_id TEXT NOT NULL DEFAULT '';",
+        )?;
+    }
+    if !has_column(conn, "chat_summary", "current_session_id")? {
+        conn.execute_batch(
+            "ALTER TABLE chat_summary ADD COLUMN current_session_id TEXT NOT NULL DEFAULT '';",
+        )?;
+    }
+    // Compound index used by session-scoped queries; safe to create once columns exist.
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_chat_history_chat_session
+             ON chat_history(chat_id, session_id, id);",
+    )?;
+    Ok(())
+}
+
+fn v2_chat_fts(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS chat_fts USING fts5(
+             content,
+             content=chat_history,
+             content_rowid=id
+         );
+
+         -- Triggers: keep chat_fts in sync with chat_history
+         CREATE TRIGGER IF NOT EXISTS chat_history_ai
+             AFTER INSERT ON chat_history BEGIN
+                 INSERT INTO chat_fts(rowid, content)
+                 VALUES (new.id, new.content);
+             END;
+         CREATE TRIGGER IF NOT EXISTS chat_history_ad
+             AFTER DELETE ON chat_history BEGIN
+                 INSERT INTO chat_fts(chat_fts, rowid, content)
+                 VALUES ('delete', old.id, old.content);
+             END;
+         CREATE TRIGGER IF NOT EXISTS chat_history_au
+             AFTER UPDATE ON chat_history BEGIN
+                 INSERT INTO chat_fts(chat_fts, rowid, content)
+                 VALUES ('delete', old.id, old.content);
+                 INSERT INTO chat_fts(rowid, content)
+                 VALUES (new.id, new.content);
+             END;
+
+         -- Index history stored before the triggers existed (external content rebuilds in place).
+         INSERT INTO chat_fts(chat_fts) VALUES ('rebuild');",
+    )?;
+    Ok(())
+}
+
+fn v3_location_and_allowlist(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS chat_location (
+             chat_id    TEXT    PRIMARY KEY,
+             latitude   REAL    NOT NULL,
+             longitude  REAL    NOT NULL,
+             updated_at INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS allowed_users (
+             user_id  INTEGER PRIMARY KEY,
+             added_at INTEGER NOT NULL
+         );",
+    )?;
+    Ok(())
+}
+
+fn v4_llm_usage_and_cache(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS llm_usage (
+             id                INTEGER PRIMARY KEY AUTOINCREMENT,
+             created_at        INTEGER NOT NULL,
+             chat_id           TEXT    NOT NULL DEFAULT '',
+             source            TEXT    NOT NULL DEFAULT '',
+             model             TEXT    NOT NULL,
+             prompt_tokens     INTEGER NOT NULL DEFAULT 0,
+             completion_tokens INTEGER NOT NULL DEFAULT 0,
+             cost_usd          REAL
+         );
+         CREATE INDEX IF NOT EXISTS idx_llm_usage_created
+             ON llm_usage(created_at);
+
+         CREATE TABLE IF NOT EXISTS llm_cache (
+             key        TEXT    PRIMARY KEY,
+             response   TEXT    NOT NULL,
+             created_at INTEGER NOT NULL
+         );",
+    )?;
+    Ok(())
+}
+
+fn v5_vault_embeddings(conn: &Connection) -> Result<(), DbError> {
+    // One row per note chunk. last_modified/model record what the vectors were computed
+    // from, so edited notes and a changed embedding model are re-embedded.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS vault_embeddings (
+             filepath      TEXT    NOT NULL,
+             chunk         INTEGER NOT NULL,
+             last_modified INTEGER NOT NULL,
+             model         TEXT    NOT NULL,
+             content       TEXT    NOT NULL,
+             embedding     BLOB    NOT NULL,
+             PRIMARY KEY (filepath, chunk)
+         );",
+    )?;
+    Ok(())
+}
+
+fn v6_memories(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS memories (
+             id         INTEGER PRIMARY KEY AUTOINCREMENT,
+             fact       TEXT    NOT NULL,
+             category   TEXT    NOT NULL DEFAULT '',
+             source     TEXT    NOT NULL DEFAULT '',
+             created_at INTEGER NOT NULL
+         );
+         CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts USING fts5(
+             fact,
+             category,
+             content=memories,
+             content_rowid=id,
+             tokenize='porter unicode61'
+         );
+         CREATE TRIGGER IF NOT EXISTS memories_ai
+             AFTER INSERT ON memories BEGIN
+                 INSERT INTO memories_fts(rowid, fact, category)
+                 VALUES (new.id, new.fact, new.category);
+             END;
+         CREATE TRIGGER IF NOT EXISTS memories_ad
+             AFTER DELETE ON memories BEGIN
+                 INSERT INTO memories_fts(memories_fts, rowid, fact, category)
+                 VALUES ('delete', old.id, old.fact, old.category);
+             END;",
+    )?;
+    Ok(())
+}
+
+fn v7_folded_through(conn: &Connection) -> Result<(), DbError> {
+    // Last chat_history id folded into the session summary.
+    if !has_column(conn, "chat_summary", "folded_through")? {
+        conn.execute_batch(
+            "ALTER TABLE chat_summary ADD COLUMN folded_through INTEGER NOT NULL DEFAULT 0;",
+        )?;
+    }
+    Ok(())
+}
+
+fn v8_session_archive(conn: &Connection) -> Result<(), DbError> {
+    // Summary state of sessions rotated away from, restored when one is resumed.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session_archive (
+             chat_id        TEXT    NOT NULL,
+             session_id     TEXT    NOT NULL,
+             summary        TEXT    NOT NULL DEFAULT '',
+             folded_through INTEGER NOT NULL DEFAULT 0,
+             archived_at    INTEGER NOT NULL,
+             PRIMARY KEY (chat_id, session_id)
+         );",
+    )?;
+    Ok(())
+}
+
+fn v9_vault_tags(conn: &Connection) -> Result<(), DbError> {
+    // Tags of each indexed note (frontmatter and inline #tags), lowercase without '#'.
+    // Resetting last_modified makes the next scan re-read existing notes to fill it.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS vault_tags (
+             filepath TEXT NOT NULL,
+             tag      TEXT NOT NULL,
+             PRIMARY KEY (filepath, tag)
+         );
+         CREATE INDEX IF NOT EXISTS vault_tags_tag ON vault_tags (tag);
+         CREATE TRIGGER IF NOT EXISTS vault_index_tags_ad
+             AFTER DELETE ON vault_index BEGIN
+                 DELETE FROM vault_tags WHERE filepath = old.filepath;
+             END;
+         UPDATE vault_index SET last_modified = 0;",
+    )?;
+    Ok(())
+}
+
+fn v10_entities(conn: &Connection) -> Result<(), DbError> {
+    // People, places and projects the user talks about, with alternative names and
+    // relations between them (entity tool). Names and aliases compare case-insensitively.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS entities (
+             id         INTEGER PRIMARY KEY AUTOINCREMENT,
+             name       TEXT    NOT NULL UNIQUE COLLATE NOCASE,
+             kind       TEXT    NOT NULL DEFAULT '',
+             notes      TEXT    NOT NULL DEFAULT '',
+             updated_at INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS entity_aliases (
+             entity_id INTEGER NOT NULL,
+             alias     TEXT    NOT NULL COLLATE NOCASE,
+             PRIMARY KEY (entity_id, alias)
+         );
+         CREATE TABLE IF NOT EXISTS entity_relations (
+             from_id  INTEGER NOT NULL,
+             relation TEXT    NOT NULL,
+             to_id    INTEGER NOT NULL,
+             PRIMARY KEY (from_id, relation, to_id)
+         );
+         CREATE TRIGGER IF NOT EXISTS entities_ad
+             AFTER DELETE ON entities BEGIN
+                 DELETE FROM entity_aliases WHERE entity_id = old.id;
+                 DELETE FROM entity_relations WHERE from_id = old.id OR to_id = old.id;
+             END;",
+    )?;
+    Ok(())
+}
+
+fn v11_vault_frontmatter(conn: &Connection) -> Result<(), DbError> {
+    // Note properties as a JSON object (see memory::frontmatter). Resetting last_modified
+    // makes the next scan re-read existing notes to fill it.
+    if !has_column(conn, "vault_index", "frontmatter")? {
+        conn.execute_batch(
+            "ALTER TABLE vault_index ADD COLUMN frontmatter TEXT NOT NULL DEFAULT '{}';",
+        )?;
+    }
+    conn.execute_batch("UPDATE vault_index SET last_modified = 0;")?;
+    Ok(())
+}
+
+fn v12_vault_content_hash(conn: &Connection) -> Result<(), DbError> {
+    // Hash of what a note was indexed as, so a changed mtime with identical content only
+    // updates last_modified (rows from before have none and are re-read once). A later step
+    // that needs notes re-read must clear content_hash as well as last_modified. The update
+    // trigger now skips last_modified-only updates instead of rewriting the FTS entry.
+    if !has_column(conn, "vault_index", "content_hash")? {
+        conn.execute_batch("ALTER TABLE vault_index ADD COLUMN content_hash TEXT;")?;
+    }
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS vault_index_au;
+         CREATE TRIGGER vault_index_au
+             AFTER UPDATE OF filepath, content ON vault_index BEGIN
+                 INSERT INTO vault_fts(vault_fts, rowid, filepath, content)
+                 VALUES ('delete', old.rowid, old.filepath, old.content);
+                 INSERT INTO vault_fts(rowid, filepath, content)
+                 VALUES (new.rowid, new.filepath, new.content);
+             END;",
+    )?;
+    Ok(())
+}
+
+fn v13_feed_seen(conn: &Connection) -> Result<(), DbError> {
+    // GUIDs of RSS/Atom items the feeds tool has already returned, per feed URL.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS feed_seen (
+             feed    TEXT    NOT NULL,
+             guid    TEXT    NOT NULL,
+             seen_at INTEGER NOT NULL,
+             PRIMARY KEY (feed, guid)
+         );",
+    )?;
+    Ok(())
+}
+
+fn v14_web_cache(conn: &Connection) -> Result<(), DbError> {
+    // Bodies fetched by web_fetch, with the validators needed to revalidate them.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS web_cache (
+             url           TEXT    PRIMARY KEY,
+             status        INTEGER NOT NULL,
+             content_type  TEXT    NOT NULL DEFAULT '',
+             etag          TEXT,
+             last_modified TEXT,
+             body          BLOB    NOT NULL,
+             fetched_at    INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_web_cache_fetched ON web_cache(fetched_at);",
+    )?;
+    Ok(())
+}
+
+fn v15_habits(conn: &Connection) -> Result<(), DbError> {
+    // Daily habits of the habits tool and the days each was done (local `YYYY-MM-DD`).
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS habits (
+             id         INTEGER PRIMARY KEY AUTOINCREMENT,
+             name       TEXT    NOT NULL UNIQUE COLLATE NOCASE,
+             notes      TEXT    NOT NULL DEFAULT '',
+             created_at INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS habit_checkins (
+             habit_id INTEGER NOT NULL,
+             day      TEXT    NOT NULL,
+             PRIMARY KEY (habit_id, day)
+         );
+         CREATE TRIGGER IF NOT EXISTS habits_ad
+             AFTER DELETE ON habits BEGIN
+                 DELETE FROM habit_checkins WHERE habit_id = old.id;
+             END;",
+    )?;
+    Ok(())
+}
+
+fn v16_workout_sets(conn: &Connection) -> Result<(), DbError> {
+    // One row per set logged by the workout tool; weight is NULL for bodyweight sets.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS workout_sets (
+             id         INTEGER PRIMARY KEY AUTOINCREMENT,
+             day        TEXT    NOT NULL,
+             exercise   TEXT    NOT NULL COLLATE NOCASE,
+             reps       INTEGER NOT NULL,
+             weight     REAL,
+             unit       TEXT    NOT NULL DEFAULT 'kg',
+             created_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_workout_sets_exercise ON workout_sets(exercise, day);",
+    )?;
+    Ok(())
+}
+
+fn v17_expenses(conn: &Connection) -> Result<(), DbError> {
+    // Spending logged by the expense tool, in minor units (pence, cents) of its currency.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS expenses (
+             id         INTEGER PRIMARY KEY AUTOINCREMENT,
+             day        TEXT    NOT NULL,
+             amount     INTEGER NOT NULL,
+             currency   TEXT    NOT NULL,
+             category   TEXT    NOT NULL COLLATE NOCASE,
+             note       TEXT    NOT NULL DEFAULT '',
+             created_at INTEGER NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_expenses_day ON expenses(day);",
+    )?;
+    Ok(())
+}
+
+fn v18_bookmarks(conn: &Connection) -> Result<(), DbError> {
+    // Pages saved by the bookmark tool; `tags` is space-separated, lowercase.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS bookmarks (
+             id         INTEGER PRIMARY KEY AUTOINCREMENT,
+             url        TEXT    NOT NULL UNIQUE,
+             title      TEXT    NOT NULL DEFAULT '',
+             summary    TEXT    NOT NULL DEFAULT '',
+             tags       TEXT    NOT NULL DEFAULT '',
+             note       TEXT    NOT NULL DEFAULT '',
+             created_at INTEGER NOT NULL
+         );
+         CREATE VIRTUAL TABLE IF NOT EXISTS bookmarks_fts USING fts5(
+             title,
+             summary,
+             tags,
+             note,
+             content=bookmarks,
+             content_rowid=id,
+             tokenize='porter unicode61'
+         );
+         CREATE TRIGGER IF NOT EXISTS bookmarks_ai
+             AFTER INSERT ON bookmarks BEGIN
+                 INSERT INTO bookmarks_fts(rowid, title, summary, tags, note)
+                 VALUES (new.id, new.title, new.summary, new.tags, new.note);
+             END;
+         CREATE TRIGGER IF NOT EXISTS bookmarks_ad
+             AFTER DELETE ON bookmarks BEGIN
+                 INSERT INTO bookmarks_fts(bookmarks_fts, rowid, title, summary, tags, note)
+                 VALUES ('delete', old.id, old.title, old.summary, old.tags, old.note);
+             END;
+         CREATE TRIGGER IF NOT EXISTS bookmarks_au
+             AFTER UPDATE ON bookmarks BEGIN
+                 INSERT INTO bookmarks_fts(bookmarks_fts, rowid, title, summary, tags, note)
+                 VALUES ('delete', old.id, old.title, old.summary, old.tags, old.note);
+                 INSERT INTO bookmarks_fts(rowid, title, summary, tags, note)
+                 VALUES (new.id, new.title, new.summary, new.tags, new.note);
+             END;",
+    )?;
+    Ok(())
+}
+
+fn v19_cron_runs(conn: &Connection) -> Result<(), DbError> {
+    // One row per cron job firing; `detail` is the reply snippet or the error.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS cron_runs (
+             id      INTEGER PRIMARY KEY AUTOINCREMENT,
+             job_id  TEXT    NOT NULL,
+             ran_at  INTEGER NOT NULL,
+             action  TEXT    NOT NULL,
+             ok      INTEGER NOT NULL,
+             detail  TEXT    NOT NULL DEFAULT ''
+         );
+         CREATE INDEX IF NOT EXISTS idx_cron_runs_job ON cron_runs(job_id, id);",
+    )?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info({table})"))
+            .unwrap();
+        stmt.query_map([], |row| row.get::<_, String>(1))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn versions_are_consecutive() {
+        for (i, m) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(m.version as usize, i + 1, "{}", m.name);
+        }
+    }
+
+    #[test]
+    fn fresh_database_reaches_latest_version_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        run(&conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        run(&conn).unwrap();
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows as usize, MIGRATIONS.len());
+        assert!(columns(&conn, "chat_summary").contains(&"folded_through".to_string()));
+    }
+
+    #[test]
+    fn pre_versioning_database_is_upgraded_in_place() {
+        let conn = Connection::open_in_memory().unwrap();
+        // The very first schema: no sessions, no FTS over history.
+        conn.execute_batch(
+            "CREATE TABLE chat_history (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT, chat_id TEXT NOT NULL,
+                 role TEXT NOT NULL, content TEXT NOT NULL, tool_call_id TEXT,
+                 tool_calls TEXT, timestamp DATETIME DEFAULT CURRENT_TIMESTAMP);
+             CREATE TABLE chat_summary (chat_id TEXT PRIMARY KEY, summary TEXT NOT NULL DEFAULT '');
+             INSERT INTO chat_history (chat_id, role, content) VALUES ('1', 'user', 'hello crab');",
+        )
+        .unwrap();
+
+        run(&conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(columns(&conn, "chat_history").contains(&"session_id".to_string()));
+        assert!(columns(&conn, "chat_summary").contains(&"current_session_id".to_string()));
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM chat_fts WHERE chat_fts MATCH 'crab'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1, "existing history is indexed");
+    }
+
+    #[test]
+    fn newer_schema_is_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        run(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, 'future', 0)",
+            params![latest_version() + 1],
+        )
+        .unwrap();
+        let err = run(&conn).unwrap_err();
+        assert!(err.0.contains("newer than this build"), "{err}");
+    }
+}
@@ -0,0 +1,272 @@
+//! Retention for chat history (`[retention]`): keep at most `max-messages` rows or
+//! `max-age-days` of history per chat, so brain.db does not grow without bound on the phone.
+//!
+//! [`spawn_retention_loop`] prunes every chat at startup and then every `interval-hours`.
+//! Messages of the active session that are not yet in its summary are never pruned. With
+//! `summarize = true`, pruned messages not already covered by a summary are first folded into
+//! their session's archived summary, which outlives the messages, so resuming that session
+//! keeps the gist.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::agent::session::stored_to_message;
+use crate::agent::summarize;
+use crate::llm::usage::{self, UsageScope};
+use crate::llm::{GenParams, LlmProvider, Message};
+use crate::memory::db::{BrainDb, DbError, ExpiredMessage, RetentionPolicy};
+
+/// Hours between retention passes when `[retention] interval-hours` is unset.
+pub const DEFAULT_INTERVAL_HOURS: u64 = 24;
+
+/// LLM used to summarize messages before they are deleted.
+pub struct Summarizer {
+    pub llm: Arc<dyn LlmProvider>,
+    pub model: String,
+    /// `[llm.summarize]`.
+    pub params: GenParams,
+}
+
+/// What one retention pass did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneStats {
+    /// Chats that lost at least one message.
+    pub chats: usize,
+    pub deleted: usize,
+    /// Sessions whose archived summary absorbed pruned messages.
+    pub summarized: usize,
+}
+
+impl std::fmt::Display for PruneStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} message(s) deleted from {} chat(s), {} session summary(ies) updated",
+            self.deleted, self.chats, self.summarized
+        )
+    }
+}
+
+/// Apply `policy` to every chat. A session whose summary cannot be updated keeps its
+/// messages until the next pass.
+pub async fn prune(
+    db: &Arc<BrainDb>,
+    policy: RetentionPolicy,
+    summarizer: Option<&Summarizer>,
+) -> Result<PruneStats, DbError> {
+    let chats = blocking(db, |db| db.chats_with_history()).await?;
+    let mut stats = PruneStats::default();
+    for chat_id in chats {
+        let expired = {
+            let chat_id = chat_id.clone();
+            blocking(db, move |db| db.expired_messages(&chat_id, &policy)).await?
+        };
+        if expired.is_empty() {
+            continue;
+        }
+
+        let mut ids: Vec<i64> = Vec::with_capacity(expired.len());
+        let mut pending: BTreeMap<String, Vec<ExpiredMessage>> = BTreeMap::new();
+        for m in expired {
+            if m.summarized || summarizer.is_none() {
+                ids.push(m.id);
+            } else {
+                pending.entry(m.session_id.clone()).or_default().push(m);
+            }
+        }
+        if let Some(summarizer) = summarizer {
+            for (session_id, messages) in pending {
+                match fold_session(db, summarizer, &chat_id, &session_id, &messages).await {
+                    Ok(updated) => {
+                        stats.summarized += usize::from(updated);
+                        ids.extend(messages.iter().map(|m| m.id));
+                    }
+                    Err(e) => eprintln!(
+                        "retention: keeping messages of {chat_id}/{session_id}, summary failed: {e}"
+                    ),
+                }
+            }
+        }
+
+        let deleted = {
+            let chat_id = chat_id.clone();
+            blocking(db, move |db| db.delete_messages(&chat_id, &ids)).await?
+        };
+        if deleted > 0 {
+            stats.chats += 1;
+            stats.deleted += deleted;
+        }
+    }
+    Ok(stats)
+}
+
+/// Fold `messages` into the archived summary of `session_id`. Returns whether the summary
+/// changed (false when the messages were only tool rows).
+async fn fold_session(
+    db: &Arc<BrainDb>,
+    summarizer: &Summarizer,
+    chat_id: &str,
+    session_id: &str,
+    messages: &[ExpiredMessage],
+) -> Result<bool, String> {
+    let existing = {
+        let (chat_id, session_id) = (chat_id.to_string(), session_id.to_string());
+        blocking(db, move |db| db.archived_summary(&chat_id, &session_id))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    let llm_messages: Vec<Message> = messages
+        .iter()
+        .filter_map(|m| stored_to_message(m.message.clone()).ok())
+        .collect();
+    let scope = UsageScope {
+        chat_id: chat_id.to_string(),
+        source: "retention".to_string(),
+    };
+    let folded = usage::scoped(
+        scope,
+        summarize::fold_into_summary(
+            summarizer.llm.as_ref(),
+            &llm_messages,
+            &existing,
+            &summarizer.model,
+            &summarizer.params,
+        ),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some(summary) = folded.filter(|s| !s.is_empty()) else {
+        return Ok(false);
+    };
+
+    let through = messages.iter().map(|m| m.id).max().unwrap_or(0);
+    let (chat_id, session_id) = (chat_id.to_string(), session_id.to_string());
+    blocking(db, move |db| {
+        db.set_archived_summary(&chat_id, &session_id, &summary, through)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Spawn a background task that prunes history now and every `interval_hours`. Errors are
+/// logged but never fatal.
+pub fn spawn_retention_loop(
+    db: Arc<BrainDb>,
+    policy: RetentionPolicy,
+    summarizer: Option<Summarizer>,
+    interval_hours: u64,
+) {
+    let interval = Duration::from_secs(interval_hours.max(1) * 60 * 60);
+    tokio::spawn(async move {
+        loop {
+            match prune(&db, policy, summarizer.as_ref()).await {
+                Ok(stats) if stats.deleted > 0 => eprintln!("retention: {stats}"),
+                Ok(_) => {}
+                Err(e) => eprintln!("retention warning: {e}"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// Run a BrainDb call on the blocking pool.
+async fn blocking<T, F>(db: &Arc<BrainDb>, f: F) -> Result<T, DbError>
+where
+    T: Send + 'static,
+    F: FnOnce(&BrainDb) -> Result<T, DbError> + Send + 'static,
+{
+    let db = Arc::clone(db);
+    tokio::task::spawn_blocking(move || f(&db))
+        .await
+        .map_err(|e| DbError(format!("retention task: {e}")))?
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{LlmError, LlmResponse, ToolDef};
+    use crate::memory::db::StoredMessage;
+    use crate::tools::registry::BoxFuture;
+    use tempfile::TempDir;
+
+    /// Summarizes everything as a fixed line.
+    struct Fixed;
+
+    impl LlmProvider for Fixed {
+        fn chat_with_params<'a>(
+            &'a self,
+            _messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            Box::pin(async {
+                Ok(LlmResponse {
+                    content: "- talked about the old days".into(),
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".into(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    fn user(content: &str) -> StoredMessage {
+        StoredMessage {
+            role: "user".into(),
+            content: content.into(),
+            tool_call_id: None,
+            tool_calls: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn prune_summarizes_archived_sessions_and_spares_live_context() {
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let old = db.get_or_create_session_id("c").unwrap();
+        db.append_session("c", &old, &[user("a"), user("b"), user("c")], "")
+            .unwrap();
+        let live = db.start_new_session("c").unwrap();
+        db.append_session("c", &live, &[user("d"), user("e")], "")
+            .unwrap();
+
+        let policy = RetentionPolicy {
+            max_messages: Some(1),
+            max_age_days: None,
+        };
+        let summarizer = Summarizer {
+            llm: Arc::new(Fixed),
+            model: "m".into(),
+            params: GenParams::default(),
+        };
+        let stats = prune(&db, policy, Some(&summarizer)).await.unwrap();
+        assert_eq!(
+            stats,
+            PruneStats {
+                chats: 1,
+                deleted: 3,
+                summarized: 1
+            }
+        );
+        // The live session is untouched even though it exceeds the limit.
+        assert_eq!(db.load_session("c", &live).unwrap().0.len(), 2);
+        // The pruned session can still be resumed from its summary.
+        let sessions = db.list_sessions("c").unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(
+            (sessions[1].session_id.as_str(), sessions[1].messages),
+            (old.as_str(), 0)
+        );
+        assert!(db.resume_session("c", &old).unwrap());
+        let (messages, summary) = db.load_session("c", &old).unwrap();
+        assert!(messages.is_empty());
+        assert_eq!(summary, "- talked about the old days");
+    }
+}
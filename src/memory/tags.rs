@@ -0,0 +1,128 @@
+//! Obsidian tag extraction for the vault index.
+//!
+//! Tags come from two places, as in Obsidian:
+//! - frontmatter `tags:` (or `tag:`), either inline (`tags: [book, reading]`, `tags: book, to-read`)
+//!   or as a YAML list (`- book`);
+//! - inline `#tag` in the body, outside code. A tag may contain letters, digits, `_`, `-` and
+//!   `/` (nesting: `#book/fiction`) but not only digits, so `#1` and `#2026` are not tags.
+//!
+//! Tags are stored lowercase without the `#`.
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::memory::frontmatter;
+
+/// Normalise a user-given tag for lookup: trimmed, lowercase, without a leading `#`.
+pub fn normalize(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').to_lowercase()
+}
+
+/// Every distinct tag of a note, sorted.
+pub fn extract_tags(content: &str) -> Vec<String> {
+    let mut tags = BTreeSet::new();
+    let fields = frontmatter::parse(content);
+    for value in ["tags", "tag"].iter().filter_map(|k| fields.get(*k)) {
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    add(&value_text(item), &mut tags);
+                }
+            }
+            other => {
+                for item in value_text(other).split([',', ' ']) {
+                    add(item, &mut tags);
+                }
+            }
+        }
+    }
+    inline_tags(frontmatter::split(content).1, &mut tags);
+    tags.into_iter().collect()
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn inline_tags(body: &str, tags: &mut BTreeSet<String>) {
+    let mut in_fence = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        // Inline code spans sit between odd and even backticks.
+        for (i, segment) in line.split('`').enumerate() {
+            if i % 2 == 0 {
+                segment_tags(segment, tags);
+            }
+        }
+    }
+}
+
+fn segment_tags(text: &str, tags: &mut BTreeSet<String>) {
+    let mut prev: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        let starts_tag = c == '#' && prev.is_none_or(|p| p.is_whitespace() || "([{,;".contains(p));
+        prev = Some(c);
+        if !starts_tag {
+            continue;
+        }
+        let rest = &text[i + 1..];
+        let end = rest.find(|ch: char| !is_tag_char(ch)).unwrap_or(rest.len());
+        add(&rest[..end], tags);
+    }
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Insert `raw` if it is a valid tag.
+fn add(raw: &str, tags: &mut BTreeSet<String>) {
+    let tag = normalize(raw.trim().trim_matches(|c| c == '"' || c == '\''));
+    let tag = tag.trim_matches('/');
+    if !tag.is_empty()
+        && tag.chars().all(is_tag_char)
+        && !tag.chars().all(|c| c.is_ascii_digit() || c == '/')
+    {
+        tags.insert(tag.to_string());
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_tags_skip_headings_numbers_and_code() {
+        let note = "# Reading\n\nFinished #Book/Fiction today (#reading). Issue #42, see x.com/#anchor.\n\
+                    `#notatag` and\n```\n#nope\n```\n";
+        assert_eq!(extract_tags(note), ["book/fiction", "reading"]);
+    }
+
+    #[test]
+    fn frontmatter_inline_and_list_forms() {
+        let inline = "---\ntitle: Dune\ntags: [book, \"sci-fi\"]\n---\nGreat. #book\n";
+        assert_eq!(extract_tags(inline), ["book", "sci-fi"]);
+
+        let list = "---\ntags:\n  - book\n  - '#to-read'\nauthor: Herbert\n---\nbody\n";
+        assert_eq!(extract_tags(list), ["book", "to-read"]);
+    }
+
+    #[test]
+    fn unterminated_frontmatter_is_body() {
+        assert_eq!(extract_tags("---\ntags: book\nno end #x"), ["x"]);
+    }
+}
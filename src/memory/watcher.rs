@@ -0,0 +1,149 @@
+//! Vault file watcher: re-indexes notes a couple of seconds after they change, so edits made
+//! in Obsidian are searchable without waiting for the next startup scan or git pull.
+//!
+//! File events (inotify on Linux, via `notify`) are collected until the vault has been quiet
+//! for [`DEBOUNCE`]; then only the changed paths go through
+//! [`VaultIndexer::index_paths`], and embeddings are refreshed if anything changed. Events
+//! under `.git/`, `.icrab/` and `.obsidian/` are dropped before they wake the task, so the
+//! database's own writes never trigger a re-index.
+//!
+//! Disable with `[index] watch = false`; the startup scan and git loop still run.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::memory::embeddings::VaultEmbedder;
+use crate::memory::indexer::{IndexerError, SKIP_DIRS, VaultIndexer};
+
+/// Quiet period after the last change before changed files are re-indexed.
+pub const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Start watching `workspace` recursively. Errors when the platform offers no file
+/// notifications (some iSH builds); the caller logs it and relies on periodic scans.
+pub fn spawn_vault_watcher(
+    workspace: PathBuf,
+    indexer: VaultIndexer,
+    embedder: Option<VaultEmbedder>,
+) -> Result<(), IndexerError> {
+    let (tx, rx) = mpsc::unbounded_channel::<Vec<PathBuf>>();
+    let root = workspace.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) => {
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            let paths: Vec<PathBuf> = event
+                .paths
+                .into_iter()
+                .filter(|p| !in_skipped_dir(&root, p))
+                .collect();
+            if !paths.is_empty() {
+                let _ = tx.send(paths);
+            }
+        }
+        Err(e) => eprintln!("vault watcher: {e}"),
+    })
+    .map_err(|e| IndexerError(format!("watcher: {e}")))?;
+    watcher
+        .watch(&workspace, RecursiveMode::Recursive)
+        .map_err(|e| IndexerError(format!("watch {}: {e}", workspace.display())))?;
+
+    tokio::spawn(async move {
+        // Dropping the watcher stops the notifications, so the task owns it.
+        let _watcher = watcher;
+        watch_loop(rx, workspace, indexer, embedder).await;
+    });
+    Ok(())
+}
+
+async fn watch_loop(
+    mut rx: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    workspace: PathBuf,
+    indexer: VaultIndexer,
+    embedder: Option<VaultEmbedder>,
+) {
+    while let Some(first) = rx.recv().await {
+        let mut pending: HashSet<PathBuf> = first.into_iter().collect();
+        // A save often arrives as several events (and editors save repeatedly while typing).
+        while let Ok(Some(more)) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+            pending.extend(more);
+        }
+
+        let paths: Vec<PathBuf> = pending.into_iter().collect();
+        let ws = workspace.clone();
+        let idx = indexer.clone();
+        let stats = match tokio::task::spawn_blocking(move || idx.index_paths(&ws, &paths)).await {
+            Ok(Ok(stats)) => stats,
+            Ok(Err(e)) => {
+                eprintln!("vault watcher: re-index warning: {e}");
+                continue;
+            }
+            Err(e) => {
+                eprintln!("vault watcher: re-index task error: {e}");
+                continue;
+            }
+        };
+        if stats.indexed + stats.removed == 0 {
+            continue;
+        }
+        eprintln!("vault watcher: {stats}");
+        if let Some(embedder) = &embedder
+            && let Err(e) = embedder.run().await
+        {
+            eprintln!("vault embeddings warning: {e}");
+        }
+    }
+}
+
+/// Whether `path` lies in one of the directories the indexer never reads.
+fn in_skipped_dir(workspace: &Path, path: &Path) -> bool {
+    path.strip_prefix(workspace).is_ok_and(|rel| {
+        rel.components()
+            .any(|c| SKIP_DIRS.iter().any(|d| c.as_os_str() == *d))
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    use crate::memory::db::BrainDb;
+
+    #[test]
+    fn skipped_dirs_are_filtered() {
+        let ws = Path::new("/vault");
+        assert!(in_skipped_dir(ws, Path::new("/vault/.icrab/brain.db-wal")));
+        assert!(in_skipped_dir(ws, Path::new("/vault/.git/index")));
+        assert!(!in_skipped_dir(ws, Path::new("/vault/Daily/today.md")));
+    }
+
+    #[tokio::test]
+    async fn edits_are_indexed_after_the_debounce() {
+        let ws = TempDir::new().unwrap();
+        let db_dir = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(db_dir.path()).unwrap());
+        let ws_path = ws.path().canonicalize().unwrap();
+
+        spawn_vault_watcher(ws_path.clone(), VaultIndexer::new(Arc::clone(&db)), None).unwrap();
+        std::fs::write(ws_path.join("fresh.md"), "watched_term_qx").unwrap();
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(15);
+        while db.vault_fts_count("\"watched_term_qx\"").unwrap() == 0 {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "note not indexed in time"
+            );
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+}
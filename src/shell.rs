@@ -0,0 +1,112 @@
+//! Run command lines through libc `system`: `std::process` does not work on iSH. Used by
+//! `exec`, `sync_vault`, `ocr` and the background vault pull.
+
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::process::{ExitStatus, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Quote `s` as one word for `sh`.
+pub fn escape_sh(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Run `script` with `sh`, stdin closed, and capture stdout and stderr through temp files.
+/// With `max_bytes`, at most that much of each stream is written (through `head -c`), so a
+/// command writing without end fails on its next write instead of filling the disk.
+pub fn run(script: &str, max_bytes: Option<u64>) -> Result<Output, String> {
+    // SAFETY: `system` is a standard POSIX libc function. Its C signature is
+    // `int system(const char *command)`. We correctly map `const char *` to
+    // `*const std::ffi::c_char` and `int` to `std::ffi::c_int`.
+    unsafe extern "C" {
+        fn system(command: *const std::ffi::c_char) -> std::ffi::c_int;
+    }
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let temp_dir = std::env::temp_dir();
+    let pid = std::process::id();
+    let c = COUNTER.fetch_add(1, Ordering::SeqCst);
+    let out_file = temp_dir.join(format!("icrab_sh_{pid}_{c}.out"));
+    let err_file = temp_dir.join(format!("icrab_sh_{pid}_{c}.err"));
+    let status_file = temp_dir.join(format!("icrab_sh_{pid}_{c}.status"));
+    let path = |p: &Path| escape_sh(&p.to_string_lossy());
+
+    let cmd_str = match max_bytes {
+        None => format!(
+            "( {script}\n) < /dev/null > {} 2> {}",
+            path(&out_file),
+            path(&err_file)
+        ),
+        // stdout goes through one `head -c`, stderr (via fd 3) through another; the
+        // pipeline hides the script's exit code, so it is written to a file of its own.
+        Some(max) => format!(
+            "{{ {{ ( {script}\n) < /dev/null 2>&3 3>&-; echo $? > {status}; }} \
+             | head -c {max} > {out}; }} 3>&1 | head -c {max} > {err}",
+            status = path(&status_file),
+            out = path(&out_file),
+            err = path(&err_file),
+        ),
+    };
+    let c_cmd = std::ffi::CString::new(cmd_str).map_err(|e| e.to_string())?;
+    // SAFETY: `c_cmd` is a valid, null-terminated C string created by `CString::new`.
+    // The pointer remains valid for the duration of the `system` call.
+    let raw = unsafe { system(c_cmd.as_ptr()) };
+
+    let read = |p: &Path| {
+        let buf = std::fs::read(p).unwrap_or_default();
+        let _ = std::fs::remove_file(p);
+        buf
+    };
+    let stdout = read(&out_file);
+    let stderr = read(&err_file);
+    let status = match max_bytes {
+        None => ExitStatus::from_raw(raw),
+        Some(_) => {
+            let code = String::from_utf8_lossy(&read(&status_file))
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| {
+                    format!(
+                        "shell failed (status {raw}): {}",
+                        String::from_utf8_lossy(&stderr).trim()
+                    )
+                })?;
+            // A wait status with the exit code in its second byte.
+            ExitStatus::from_raw(code << 8)
+        }
+    };
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_output_and_exit_code() {
+        let out = run("echo out; echo err >&2; exit 3", None).unwrap();
+        assert_eq!(out.status.code(), Some(3));
+        assert_eq!(out.stdout, b"out\n");
+        assert_eq!(out.stderr, b"err\n");
+
+        let out = run(&format!("printf %s {}", escape_sh("it's")), Some(100)).unwrap();
+        assert!(out.status.success());
+        assert_eq!(out.stdout, b"it's");
+    }
+
+    #[test]
+    fn capped_streams_stop_where_they_are_written() {
+        let out = run("echo out; yes err >&2", Some(10)).unwrap();
+        assert_eq!(out.stdout, b"out\n");
+        assert_eq!(out.stderr.len(), 10);
+        assert!(!out.status.success());
+
+        let out = run("exit 7", Some(10)).unwrap();
+        assert_eq!(out.status.code(), Some(7));
+    }
+}
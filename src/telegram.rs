@@ -1,13 +1,18 @@
 //! Telegram poller: getUpdates (long poll), allow-list, sendMessage; glue to agent in/out.
 //!
 //! Single long-poll input, replies via sendMessage. No webhooks, no SDK.
+//! Outbound messages go through a spooled queue that retries transient failures.
 
-use std::time::Duration;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
-use crate::config::{Config, TelegramConfig};
+use crate::config::{Config, QuietHours, TelegramConfig};
+use crate::workspace;
 
 // --- Channel types (bounded mpsc, cap 32–64) ---
 
@@ -20,15 +25,76 @@ pub struct InboundMsg {
     /// Optional channel label for multi-channel or logging (e.g. "telegram").
     #[allow(dead_code)]
     pub channel: String,
+    /// Set when the user shared a location (text is then empty).
+    pub location: Option<SharedLocation>,
+    /// Forum topic (`message_thread_id`) the message was posted in, for supergroups with topics.
+    pub thread_id: Option<i64>,
+    /// Telegram `file_id` of a voice note (text is then empty until transcribed).
+    pub voice: Option<String>,
+    /// Telegram `message_id` (None for cron/heartbeat messages).
+    pub message_id: Option<i64>,
+    /// ID of the cron agent job that queued this message, so its run can be recorded.
+    pub cron_job: Option<String>,
+}
+
+impl InboundMsg {
+    /// Session key for this message; see [`session_key`].
+    pub fn session_key(&self) -> String {
+        session_key(self.chat_id, self.thread_id)
+    }
+}
+
+/// Session key: `"{chat_id}"`, or `"{chat_id}:{thread_id}"` inside a forum topic so each
+/// topic keeps its own conversation.
+pub fn session_key(chat_id: i64, thread_id: Option<i64>) -> String {
+    match thread_id {
+        Some(t) => format!("{}:{}", chat_id, t),
+        None => chat_id.to_string(),
+    }
+}
+
+/// Coordinates from a Telegram location message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SharedLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// True for live-location updates (edits of an earlier share), false for a fresh share.
+    pub is_update: bool,
 }
 
 /// One reply to send to Telegram; agent/tools send these.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutboundMsg {
     pub chat_id: i64,
+    /// Message text, or the caption when `kind` carries a file.
     pub text: String,
     #[allow(dead_code)]
     pub channel: String,
+    pub kind: OutboundKind,
+    /// Forum topic to post into (`message_thread_id`); None for the main chat.
+    pub thread_id: Option<i64>,
+    /// Deliver without a notification sound. Cron and heartbeat messages are also sent
+    /// silently during `telegram.quiet-hours`.
+    #[serde(default)]
+    pub disable_notification: bool,
+}
+
+/// What an outbound message carries: plain text (sendMessage) or a file payload.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutboundKind {
+    #[default]
+    Text,
+    /// Local file sent via sendDocument; `OutboundMsg::text` is the caption.
+    Document(PathBuf),
+    /// Local image (PNG/JPEG) sent inline via sendPhoto; `OutboundMsg::text` is the caption.
+    Photo(PathBuf),
+    /// Temporary OGG/Opus file sent via sendVoice; deleted once delivered or dropped.
+    Voice(PathBuf),
+    /// Delete these messages from the chat (deleteMessages); `OutboundMsg::text` is unused.
+    Delete(Vec<i64>),
+    /// Live reply: the first message with `key` is sent, later ones edit it in place
+    /// (editMessageText) with the full text so far. `done` marks the final text.
+    Stream { key: String, done: bool },
 }
 
 /// Errors from Telegram API or HTTP; poll loop retries without advancing offset on transient failures.
@@ -36,7 +102,14 @@ pub struct OutboundMsg {
 pub enum TelegramError {
     Http(String),
     Parse(String),
-    Api { code: i64, description: String },
+    Api {
+        code: i64,
+        description: String,
+        /// Seconds to wait before retrying (Telegram sends this with 429 Too Many Requests).
+        retry_after: Option<u64>,
+    },
+    /// Local failure before any request was made (e.g. attachment unreadable).
+    Io(String),
 }
 
 impl std::fmt::Display for TelegramError {
@@ -44,15 +117,55 @@ impl std::fmt::Display for TelegramError {
         match self {
             TelegramError::Http(s) => write!(f, "telegram http: {}", s),
             TelegramError::Parse(s) => write!(f, "telegram parse: {}", s),
-            TelegramError::Api { code, description } => {
+            TelegramError::Api {
+                code, description, ..
+            } => {
                 write!(f, "telegram api {}: {}", code, description)
             }
+            TelegramError::Io(s) => write!(f, "telegram io: {}", s),
         }
     }
 }
 
 impl std::error::Error for TelegramError {}
 
+impl TelegramError {
+    /// Network errors, 5xx and 429 are worth retrying; other API errors (bad chat, bad file) are not.
+    fn is_transient(&self) -> bool {
+        match self {
+            TelegramError::Http(_) => true,
+            TelegramError::Api { code, .. } => *code == 429 || *code >= 500,
+            TelegramError::Parse(_) | TelegramError::Io(_) => false,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            TelegramError::Api {
+                retry_after: Some(s),
+                ..
+            } => Some(Duration::from_secs(*s)),
+            _ => None,
+        }
+    }
+}
+
+/// Map a non-success HTTP response to an error, preferring Telegram's JSON error body.
+fn api_error(status: reqwest::StatusCode, body: &str) -> TelegramError {
+    match serde_json::from_str::<ApiErrorResponse>(body) {
+        Ok(api_err) => TelegramError::Api {
+            code: if api_err.error_code != 0 {
+                api_err.error_code
+            } else {
+                status.as_u16() as i64
+            },
+            description: api_err.description,
+            retry_after: api_err.parameters.and_then(|p| p.retry_after),
+        },
+        Err(_) => TelegramError::Http(format!("{} {}", status, body)),
+    }
+}
+
 /// Format a reqwest/HTTP error and its source chain for logging (surfaces TLS, DNS, etc.).
 fn format_error_chain(e: &impl std::error::Error) -> String {
     let mut s = e.to_string();
@@ -80,16 +193,123 @@ struct Update {
     update_id: i64,
     #[serde(default)]
     message: Option<Message>,
+    /// Live-location updates arrive as edits of the original location message.
+    #[serde(default)]
+    edited_message: Option<Message>,
+    /// New state of a poll the bot sent (vote counts changed or poll closed).
+    #[serde(default)]
+    poll: Option<Poll>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Poll {
+    id: String,
+    question: String,
+    #[serde(default)]
+    options: Vec<PollOption>,
+    #[serde(default)]
+    total_voter_count: i64,
+    #[serde(default)]
+    is_closed: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PollOption {
+    text: String,
+    #[serde(default)]
+    voter_count: i64,
+}
+
+/// sendPoll / stopPoll success bodies.
+#[derive(Debug, Deserialize)]
+struct SentPollResponse {
+    result: SentPollMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct SentPollMessage {
+    message_id: i64,
+    poll: Poll,
+}
+
+#[derive(Debug, Deserialize)]
+struct StopPollResponse {
+    result: Poll,
+}
+
+#[derive(Debug, Serialize)]
+struct SendPollBody<'a> {
+    chat_id: i64,
+    question: &'a str,
+    options: Vec<InputPollOption<'a>>,
+    is_anonymous: bool,
+    allows_multiple_answers: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_thread_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct InputPollOption<'a> {
+    text: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct StopPollBody {
+    chat_id: i64,
+    message_id: i64,
+}
+
+/// One getUpdates batch: forwarded messages and poll updates, plus the highest update_id
+/// seen (skipped updates included) so the offset always moves past them.
+#[derive(Debug, Default)]
+struct Updates {
+    last_update_id: Option<i64>,
+    messages: Vec<InboundMsg>,
+    polls: Vec<Poll>,
 }
 
 #[derive(Debug, Deserialize)]
 struct Message {
+    #[serde(default)]
+    message_id: Option<i64>,
     #[serde(default)]
     from: Option<From>,
     #[serde(default)]
     chat: Option<Chat>,
     #[serde(default)]
     text: Option<String>,
+    #[serde(default)]
+    location: Option<Location>,
+    #[serde(default)]
+    voice: Option<Voice>,
+    #[serde(default)]
+    message_thread_id: Option<i64>,
+    /// True only for messages inside a forum topic (reply threads also carry a thread id).
+    #[serde(default)]
+    is_topic_message: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Location {
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Voice {
+    file_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetFileResponse {
+    #[serde(default)]
+    result: Option<File>,
+}
+
+#[derive(Debug, Deserialize)]
+struct File {
+    #[serde(default)]
+    file_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -102,10 +322,47 @@ struct Chat {
     id: i64,
 }
 
+/// Success body of send* methods; only the new message's id is needed.
+#[derive(Debug, Deserialize)]
+struct SentMessageResponse {
+    #[serde(default)]
+    result: Option<SentMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SentMessage {
+    message_id: i64,
+}
+
+/// Message id of a successful send, if the body carries one.
+fn sent_message_id(body: &str) -> Option<i64> {
+    serde_json::from_str::<SentMessageResponse>(body)
+        .ok()
+        .and_then(|r| r.result)
+        .map(|m| m.message_id)
+}
+
+#[derive(Debug, Serialize)]
+struct EditMessageTextBody<'a> {
+    chat_id: i64,
+    message_id: i64,
+    text: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct DeleteMessagesBody<'a> {
+    chat_id: i64,
+    message_ids: &'a [i64],
+}
+
 #[derive(Debug, Serialize)]
 struct SendMessageBody {
     chat_id: i64,
     text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_thread_id: Option<i64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    disable_notification: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -117,6 +374,14 @@ struct ApiErrorResponse {
     error_code: i64,
     #[serde(default)]
     description: String,
+    #[serde(default)]
+    parameters: Option<ResponseParameters>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseParameters {
+    #[serde(default)]
+    retry_after: Option<u64>,
 }
 
 const CHANNEL_CAP: usize = 64;
@@ -124,11 +389,77 @@ const GET_UPDATES_TIMEOUT_SECS: u64 = 25;
 const HTTP_TIMEOUT_SECS: u64 = 30;
 const TELEGRAM_MAX_MESSAGE_LEN: usize = 4096;
 const TRUNCATE_TO: usize = 4090;
+const TELEGRAM_MAX_CAPTION_LEN: usize = 1024;
+
+/// A fresh random multipart boundary that occurs nowhere in `parts`.
+fn multipart_boundary(parts: &[&[u8]]) -> String {
+    loop {
+        let boundary = format!("icrab-{}", uuid::Uuid::new_v4().simple());
+        let needle = boundary.as_bytes();
+        if !parts
+            .iter()
+            .any(|p| p.windows(needle.len()).any(|w| w == needle))
+        {
+            return boundary;
+        }
+    }
+}
+
+fn field_bytes<'a>(fields: &'a [(&str, String)]) -> impl Iterator<Item = &'a [u8]> {
+    fields.iter().map(|(_, v)| v.as_bytes())
+}
+
+fn push_fields(body: &mut Vec<u8>, boundary: &str, fields: &[(&str, String)]) {
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+}
+
+/// Build a multipart/form-data body by hand (reqwest's multipart feature pulls in extra deps).
+/// Returns the Content-Type header value and the encoded body.
+pub(crate) fn multipart_body(
+    fields: &[(&str, String)],
+    file_field: &str,
+    file_name: &str,
+    file_bytes: &[u8],
+) -> (String, Vec<u8>) {
+    let mut parts: Vec<&[u8]> = field_bytes(fields).collect();
+    parts.push(file_bytes);
+    let boundary = multipart_boundary(&parts);
+    let mut body = Vec::with_capacity(file_bytes.len() + 512);
+    push_fields(&mut body, &boundary, fields);
+    let file_name = file_name.replace(['"', '\r', '\n'], "_");
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{file_field}\"; filename=\"{file_name}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(file_bytes);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+/// Like [`multipart_body`], for forms with text fields only.
+pub(crate) fn multipart_fields(fields: &[(&str, String)]) -> (String, Vec<u8>) {
+    let boundary = multipart_boundary(&field_bytes(fields).collect::<Vec<_>>());
+    let mut body = Vec::new();
+    push_fields(&mut body, &boundary, fields);
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
 
 /// Shared Telegram API client: getUpdates and sendMessage.
 struct TelegramClient {
     client: reqwest::Client,
     base_url: String,
+    /// File downloads live under `/file/bot{token}`, not the method base.
+    file_base_url: String,
 }
 
 impl TelegramClient {
@@ -136,22 +467,31 @@ impl TelegramClient {
         Self::with_base_url(bot_token, None)
     }
 
+    /// One-off client for tools that call the API directly (not via the send queue).
+    fn from_config(config: &TelegramConfig) -> Self {
+        Self::with_base_url(
+            config.bot_token.as_deref().unwrap_or(""),
+            config.api_base.as_deref(),
+        )
+    }
+
     fn with_base_url(bot_token: &str, api_base: Option<&str>) -> Self {
         let client = reqwest::Client::builder()
             .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
             .build()
             .expect("reqwest client");
-        let base_url = api_base
-            .map(|b| format!("{}/bot{}", b.trim_end_matches('/'), bot_token))
-            .unwrap_or_else(|| format!("https://api.telegram.org/bot{}", bot_token));
-        Self { client, base_url }
+        let root = api_base
+            .map(|b| b.trim_end_matches('/'))
+            .unwrap_or("https://api.telegram.org");
+        Self {
+            client,
+            base_url: format!("{}/bot{}", root, bot_token),
+            file_base_url: format!("{}/file/bot{}", root, bot_token),
+        }
     }
 
-    async fn get_updates(
-        &self,
-        offset: i64,
-        timeout_secs: u64,
-    ) -> Result<Vec<(i64, i64, i64, String)>, TelegramError> {
+    /// Long-poll getUpdates; collects text, location and voice messages and poll updates.
+    async fn get_updates(&self, offset: i64, timeout_secs: u64) -> Result<Updates, TelegramError> {
         let url = format!(
             "{}/getUpdates?offset={}&timeout={}",
             self.base_url, offset, timeout_secs
@@ -169,40 +509,68 @@ impl TelegramClient {
             .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
 
         if !status.is_success() {
-            if let Ok(api_err) = serde_json::from_str::<ApiErrorResponse>(&body) {
-                return Err(TelegramError::Api {
-                    code: api_err.error_code,
-                    description: api_err.description,
-                });
-            }
-            return Err(TelegramError::Http(format!("{} {}", status, body)));
+            return Err(api_error(status, &body));
         }
 
         let parsed: GetUpdatesResponse =
             serde_json::from_str(&body).map_err(|e| TelegramError::Parse(e.to_string()))?;
         if !parsed.ok {
-            return Ok(Vec::new());
+            return Ok(Updates::default());
         }
 
-        let mut out = Vec::new();
+        let mut out = Updates::default();
         for update in parsed.result {
-            if let Some(msg) = update.message {
-                let text = match msg.text {
-                    Some(t) if !t.is_empty() => t,
-                    _ => continue,
-                };
-                let from_id = msg.from.as_ref().map(|f| f.id);
-                let chat_id = msg.chat.as_ref().map(|c| c.id);
-                match (from_id, chat_id) {
-                    (Some(uid), Some(cid)) => out.push((update.update_id, cid, uid, text)),
-                    _ => continue,
-                }
+            out.last_update_id = out.last_update_id.max(Some(update.update_id));
+            if let Some(poll) = update.poll {
+                out.polls.push(poll);
+                continue;
+            }
+            // Edited messages only matter for live location; edited text is ignored.
+            let (msg, edited) = match (update.message, update.edited_message) {
+                (Some(m), _) => (m, false),
+                (None, Some(m)) => (m, true),
+                (None, None) => continue,
+            };
+            let location = msg.location.as_ref().map(|l| SharedLocation {
+                latitude: l.latitude,
+                longitude: l.longitude,
+                is_update: edited,
+            });
+            let voice = msg.voice.filter(|_| !edited).map(|v| v.file_id);
+            let text = match msg.text {
+                Some(t) if !t.is_empty() && !edited => t,
+                _ if location.is_some() || voice.is_some() => String::new(),
+                _ => continue,
+            };
+            let thread_id = msg.message_thread_id.filter(|_| msg.is_topic_message);
+            let from_id = msg.from.as_ref().map(|f| f.id);
+            let chat_id = msg.chat.as_ref().map(|c| c.id);
+            match (from_id, chat_id) {
+                (Some(user_id), Some(chat_id)) => out.messages.push(InboundMsg {
+                    chat_id,
+                    user_id,
+                    text,
+                    channel: "telegram".to_string(),
+                    location,
+                    thread_id,
+                    voice,
+                    message_id: msg.message_id,
+                    cron_job: None,
+                }),
+                _ => continue,
             }
         }
         Ok(out)
     }
 
-    async fn send_message(&self, chat_id: i64, text: String) -> Result<(), TelegramError> {
+    /// Send text; returns the new message's id.
+    async fn send_message(
+        &self,
+        chat_id: i64,
+        thread_id: Option<i64>,
+        text: String,
+        disable_notification: bool,
+    ) -> Result<Option<i64>, TelegramError> {
         let url = format!("{}/sendMessage", self.base_url);
         let mut text = text;
         let mut retried = false;
@@ -210,6 +578,8 @@ impl TelegramClient {
             let body = SendMessageBody {
                 chat_id,
                 text: text.clone(),
+                message_thread_id: thread_id,
+                disable_notification,
             };
             let res = self
                 .client
@@ -225,7 +595,7 @@ impl TelegramClient {
                 .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
 
             if status.is_success() {
-                return Ok(());
+                return Ok(sent_message_id(&body_str));
             }
 
             if status.as_u16() == 400 && !retried {
@@ -239,85 +609,941 @@ impl TelegramClient {
                     }
                 }
             }
-            if status.as_u16() == 400 {
-                if let Ok(api_err) = serde_json::from_str::<ApiErrorResponse>(&body_str) {
-                    return Err(TelegramError::Api {
-                        code: api_err.error_code,
-                        description: api_err.description,
-                    });
+            return Err(api_error(status, &body_str));
+        }
+    }
+
+    /// Upload a local file via `method` (sendDocument, sendPhoto) as form field `field`.
+    /// Caption is truncated to Telegram's 1024-char limit. Returns the new message's id.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_upload(
+        &self,
+        method: &str,
+        field: &str,
+        chat_id: i64,
+        thread_id: Option<i64>,
+        path: &Path,
+        caption: &str,
+        disable_notification: bool,
+    ) -> Result<Option<i64>, TelegramError> {
+        let url = format!("{}/{}", self.base_url, method);
+        let bytes = tokio::fs::read(path)
+            .await
+            .map_err(|e| TelegramError::Io(format!("read {}: {}", path.display(), e)))?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "file".to_string());
+        let mut fields = vec![("chat_id", chat_id.to_string())];
+        if let Some(t) = thread_id {
+            fields.push(("message_thread_id", t.to_string()));
+        }
+        if disable_notification {
+            fields.push(("disable_notification", "true".to_string()));
+        }
+        if !caption.is_empty() {
+            fields.push((
+                "caption",
+                caption.chars().take(TELEGRAM_MAX_CAPTION_LEN).collect(),
+            ));
+        }
+        let (content_type, body) = multipart_body(&fields, field, &file_name, &bytes);
+        let res = self
+            .client
+            .post(&url)
+            .header("Content-Type", content_type)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
+        let status = res.status();
+        let body_str = res
+            .text()
+            .await
+            .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
+        if status.is_success() {
+            return Ok(sent_message_id(&body_str));
+        }
+        Err(api_error(status, &body_str))
+    }
+
+    /// Delete up to 100 messages at once (deleteMessages). Messages that are already gone
+    /// or too old are skipped by Telegram without failing the whole call.
+    async fn delete_messages(
+        &self,
+        chat_id: i64,
+        message_ids: &[i64],
+    ) -> Result<(), TelegramError> {
+        let url = format!("{}/deleteMessages", self.base_url);
+        for chunk in message_ids.chunks(100) {
+            let res = self
+                .client
+                .post(&url)
+                .json(&DeleteMessagesBody {
+                    chat_id,
+                    message_ids: chunk,
+                })
+                .send()
+                .await
+                .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
+            let status = res.status();
+            let body_str = res
+                .text()
+                .await
+                .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
+            if !status.is_success() {
+                return Err(api_error(status, &body_str));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace a message's text. Unchanged text is not an error.
+    async fn edit_message_text(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        text: &str,
+    ) -> Result<(), TelegramError> {
+        let truncated;
+        let text = if text.len() > TELEGRAM_MAX_MESSAGE_LEN {
+            truncated = format!("{}...", text.chars().take(TRUNCATE_TO).collect::<String>());
+            truncated.as_str()
+        } else {
+            text
+        };
+        let body = EditMessageTextBody {
+            chat_id,
+            message_id,
+            text,
+        };
+        match self.post_json("editMessageText", &body).await {
+            Err(TelegramError::Api { description, .. })
+                if description.contains("message is not modified") =>
+            {
+                Ok(())
+            }
+            res => res.map(|_| ()),
+        }
+    }
+
+    /// Post a native poll; returns the sent message id and the poll.
+    async fn send_poll(&self, body: &SendPollBody<'_>) -> Result<SentPollMessage, TelegramError> {
+        let body_str = self.post_json("sendPoll", body).await?;
+        serde_json::from_str::<SentPollResponse>(&body_str)
+            .map(|r| r.result)
+            .map_err(|e| TelegramError::Parse(e.to_string()))
+    }
+
+    /// Close a poll; returns its final state.
+    async fn stop_poll(&self, chat_id: i64, message_id: i64) -> Result<Poll, TelegramError> {
+        let body_str = self
+            .post_json(
+                "stopPoll",
+                &StopPollBody {
+                    chat_id,
+                    message_id,
+                },
+            )
+            .await?;
+        serde_json::from_str::<StopPollResponse>(&body_str)
+            .map(|r| r.result)
+            .map_err(|e| TelegramError::Parse(e.to_string()))
+    }
+
+    /// POST a JSON body to `method`; returns the raw success body.
+    async fn post_json(
+        &self,
+        method: &str,
+        body: &impl Serialize,
+    ) -> Result<String, TelegramError> {
+        let url = format!("{}/{}", self.base_url, method);
+        let res = self
+            .client
+            .post(&url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
+        let status = res.status();
+        let body_str = res
+            .text()
+            .await
+            .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
+        if !status.is_success() {
+            return Err(api_error(status, &body_str));
+        }
+        Ok(body_str)
+    }
+
+    /// Deliver one outbound message according to its kind; returns the sent message's id.
+    async fn deliver(&self, msg: &OutboundMsg, silent: bool) -> Result<Option<i64>, TelegramError> {
+        match &msg.kind {
+            OutboundKind::Text => {
+                self.send_message(msg.chat_id, msg.thread_id, msg.text.clone(), silent)
+                    .await
+            }
+            OutboundKind::Document(path) => {
+                self.send_upload(
+                    "sendDocument",
+                    "document",
+                    msg.chat_id,
+                    msg.thread_id,
+                    path,
+                    &msg.text,
+                    silent,
+                )
+                .await
+            }
+            OutboundKind::Photo(path) => {
+                self.send_upload(
+                    "sendPhoto",
+                    "photo",
+                    msg.chat_id,
+                    msg.thread_id,
+                    path,
+                    &msg.text,
+                    silent,
+                )
+                .await
+            }
+            OutboundKind::Voice(path) => {
+                self.send_upload(
+                    "sendVoice",
+                    "voice",
+                    msg.chat_id,
+                    msg.thread_id,
+                    path,
+                    &msg.text,
+                    silent,
+                )
+                .await
+            }
+            OutboundKind::Delete(ids) => {
+                self.delete_messages(msg.chat_id, ids).await.map(|()| None)
+            }
+            // First message of a live reply; edits are handled by the send loop.
+            OutboundKind::Stream { .. } => {
+                self.send_message(msg.chat_id, msg.thread_id, msg.text.clone(), silent)
+                    .await
+            }
+        }
+    }
+
+    /// Download a file the user sent (getFile, then fetch from the file endpoint).
+    async fn download_file(&self, file_id: &str) -> Result<Vec<u8>, TelegramError> {
+        // file_ids are URL-safe base64, so no escaping is needed.
+        let url = format!("{}/getFile?file_id={}", self.base_url, file_id);
+        let res = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
+        let status = res.status();
+        let body = res
+            .text()
+            .await
+            .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
+        if !status.is_success() {
+            return Err(api_error(status, &body));
+        }
+        let parsed: GetFileResponse =
+            serde_json::from_str(&body).map_err(|e| TelegramError::Parse(e.to_string()))?;
+        let file_path = parsed
+            .result
+            .and_then(|f| f.file_path)
+            .ok_or_else(|| TelegramError::Parse("getFile: no file_path".to_string()))?;
+
+        let res = self
+            .client
+            .get(format!("{}/{}", self.file_base_url, file_path))
+            .send()
+            .await
+            .map_err(|e| TelegramError::Http(format_error_chain(&e)))?;
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(api_error(status, &body));
+        }
+        res.bytes()
+            .await
+            .map(|b| b.to_vec())
+            .map_err(|e| TelegramError::Http(format_error_chain(&e)))
+    }
+}
+
+/// Download a file a user sent (e.g. a voice note) by its Telegram `file_id`.
+pub async fn download_file(
+    config: &TelegramConfig,
+    file_id: &str,
+) -> Result<Vec<u8>, TelegramError> {
+    TelegramClient::from_config(config)
+        .download_file(file_id)
+        .await
+}
+
+// --- Outbound queue (retry, per-chat rate limit, disk spool) ---
+
+/// Minimum gap between messages to one private chat (Telegram allows ~1/s).
+const PRIVATE_CHAT_INTERVAL: Duration = Duration::from_secs(1);
+/// Minimum gap between messages to one group (Telegram allows ~20/min).
+const GROUP_CHAT_INTERVAL: Duration = Duration::from_secs(3);
+const SEND_BACKOFF_INITIAL_SECS: u64 = 1;
+const SEND_BACKOFF_MAX_SECS: u64 = 300;
+
+/// Pending outbound messages, mirrored to a JSONL spool file so replies queued during a
+/// network outage survive a restart. The spool is rewritten on every change (low volume).
+struct Outbox {
+    pending: VecDeque<OutboundMsg>,
+    spool_path: Option<PathBuf>,
+}
+
+impl Outbox {
+    /// Load any spooled messages; unreadable lines are skipped.
+    fn load(spool_path: Option<PathBuf>) -> Self {
+        let pending = spool_path
+            .as_deref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|s| {
+                s.lines()
+                    .filter_map(|l| serde_json::from_str::<OutboundMsg>(l).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            pending,
+            spool_path,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn front(&self) -> Option<&OutboundMsg> {
+        self.pending.front()
+    }
+
+    fn push(&mut self, msg: OutboundMsg) {
+        self.pending.push_back(msg);
+        self.persist();
+    }
+
+    fn pop_front(&mut self) {
+        self.pending.pop_front();
+        self.persist();
+    }
+
+    /// Whether a newer update for live reply `key` is queued behind the front message.
+    fn has_newer_stream(&self, key: &str) -> bool {
+        self.pending
+            .iter()
+            .skip(1)
+            .any(|m| matches!(&m.kind, OutboundKind::Stream { key: k, .. } if k == key))
+    }
+
+    /// Write the queue to the spool (tmp + rename); remove the spool once empty.
+    fn persist(&self) {
+        let Some(path) = &self.spool_path else {
+            return;
+        };
+        if self.pending.is_empty() {
+            let _ = std::fs::remove_file(path);
+            return;
+        }
+        let mut out = String::new();
+        for msg in &self.pending {
+            if let Ok(line) = serde_json::to_string(msg) {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let tmp = path.with_extension("jsonl.tmp");
+        let res = std::fs::write(&tmp, out).and_then(|()| std::fs::rename(&tmp, path));
+        if let Err(e) = res {
+            eprintln!("telegram outbox spool {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Tracks the last send per chat to stay under Telegram's per-chat limits.
+#[derive(Default)]
+struct ChatRateLimiter {
+    last_sent: HashMap<i64, Instant>,
+}
+
+impl ChatRateLimiter {
+    /// How long to wait before the next message to `chat_id` may be sent.
+    fn wait_time(&self, chat_id: i64, now: Instant) -> Duration {
+        // Negative chat ids are groups/supergroups/channels.
+        let interval = if chat_id < 0 {
+            GROUP_CHAT_INTERVAL
+        } else {
+            PRIVATE_CHAT_INTERVAL
+        };
+        self.last_sent
+            .get(&chat_id)
+            .map(|&last| (last + interval).saturating_duration_since(now))
+            .unwrap_or(Duration::ZERO)
+    }
+
+    fn mark(&mut self, chat_id: i64, now: Instant) {
+        self.last_sent.insert(chat_id, now);
+    }
+}
+
+/// Sleep for `dur` while still accepting (and spooling) new messages from the channel.
+async fn wait_spooling(
+    outbound_rx: &mut mpsc::Receiver<OutboundMsg>,
+    outbox: &mut Outbox,
+    dur: Duration,
+) {
+    let deadline = tokio::time::Instant::now() + dur;
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep_until(deadline) => return,
+            msg = outbound_rx.recv() => match msg {
+                Some(m) => outbox.push(m),
+                None => {
+                    tokio::time::sleep_until(deadline).await;
+                    return;
                 }
+            },
+        }
+    }
+}
+
+// --- Quiet hours ---
+
+/// `telegram.quiet-hours` evaluated in the configured timezone.
+struct QuietWindow {
+    hours: QuietHours,
+    tz: chrono_tz::Tz,
+}
+
+impl QuietWindow {
+    fn from_config(config: &Config) -> Option<Self> {
+        let hours = config.telegram.as_ref()?.quiet_hours()?;
+        let tz = config
+            .timezone
+            .as_deref()
+            .unwrap_or("Europe/London")
+            .parse()
+            .ok()?;
+        Some(Self { hours, tz })
+    }
+
+    /// Background output (cron, heartbeat) inside the window is delivered silently.
+    fn silences(&self, msg: &OutboundMsg, now: chrono::DateTime<chrono::Utc>) -> bool {
+        matches!(msg.channel.as_str(), "cron" | "heartbeat")
+            && self.hours.contains(now.with_timezone(&self.tz).time())
+    }
+}
+
+// --- Message log (for /forget) ---
+
+/// Most recent message ids kept per chat.
+const MESSAGE_LOG_CAP: usize = 500;
+
+/// Recent Telegram message ids per chat/topic, both the user's and the bot's, so `/forget`
+/// can delete them. In memory only: messages from before a restart cannot be deleted.
+#[derive(Debug, Default)]
+pub struct MessageLog {
+    ids: Mutex<HashMap<ChatThread, BTreeSet<i64>>>,
+}
+
+/// `(chat_id, thread_id)`: one forum topic or a whole chat.
+type ChatThread = (i64, Option<i64>);
+
+impl MessageLog {
+    /// Remember a message; the oldest id is evicted once the chat exceeds the cap.
+    pub fn record(&self, chat_id: i64, thread_id: Option<i64>, message_id: i64) {
+        if let Ok(mut map) = self.ids.lock() {
+            let ids = map.entry((chat_id, thread_id)).or_default();
+            ids.insert(message_id);
+            if ids.len() > MESSAGE_LOG_CAP {
+                ids.pop_first();
             }
-            return Err(TelegramError::Http(format!("{} {}", status, body_str)));
         }
     }
+
+    /// Remove and return the `n` most recent ids (message ids grow monotonically per chat).
+    pub fn take_recent(&self, chat_id: i64, thread_id: Option<i64>, n: usize) -> Vec<i64> {
+        let Ok(mut map) = self.ids.lock() else {
+            return Vec::new();
+        };
+        let Some(ids) = map.get_mut(&(chat_id, thread_id)) else {
+            return Vec::new();
+        };
+        let mut out = Vec::with_capacity(n.min(ids.len()));
+        while out.len() < n {
+            match ids.pop_last() {
+                Some(id) => out.push(id),
+                None => break,
+            }
+        }
+        out
+    }
+}
+
+// --- Polls ---
+
+/// Latest known state of a poll the bot posted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PollState {
+    pub chat_id: i64,
+    pub message_id: i64,
+    pub question: String,
+    /// `(option text, votes)` in display order.
+    pub options: Vec<(String, i64)>,
+    pub total_voters: i64,
+    pub is_closed: bool,
+}
+
+impl PollState {
+    fn apply(&mut self, poll: &Poll) {
+        self.options = poll
+            .options
+            .iter()
+            .map(|o| (o.text.clone(), o.voter_count))
+            .collect();
+        self.total_voters = poll.total_voter_count;
+        self.is_closed = poll.is_closed;
+    }
+
+    /// Human-readable tally, e.g. for the agent.
+    pub fn tally(&self) -> String {
+        let mut out = format!("Poll: {}\n", self.question);
+        for (text, votes) in &self.options {
+            let pct = if self.total_voters > 0 {
+                votes * 100 / self.total_voters
+            } else {
+                0
+            };
+            out.push_str(&format!("- {text}: {votes} ({pct}%)\n"));
+        }
+        out.push_str(&format!(
+            "{} voter(s), {}",
+            self.total_voters,
+            if self.is_closed { "closed" } else { "open" }
+        ));
+        out
+    }
 }
 
-/// True if user is allowed: empty/None list = allow all (document: setting IDs recommended for security).
-fn is_allowed(cfg: &TelegramConfig, user_id: i64) -> bool {
-    match &cfg.allowed_user_ids {
-        None => true,
-        Some(ids) if ids.is_empty() => true,
-        Some(ids) => ids.contains(&user_id),
+/// Polls sent by the bot, keyed by poll id; the poller applies `poll` updates as votes come
+/// in. In memory only.
+#[derive(Debug, Default)]
+pub struct PollStore {
+    polls: Mutex<HashMap<String, PollState>>,
+}
+
+impl PollStore {
+    pub fn get(&self, poll_id: &str) -> Option<PollState> {
+        self.polls.lock().ok()?.get(poll_id).cloned()
+    }
+
+    fn insert(&self, poll_id: String, state: PollState) {
+        if let Ok(mut polls) = self.polls.lock() {
+            polls.insert(poll_id, state);
+        }
+    }
+
+    /// Apply a poll update; unknown polls (not sent by this process) are ignored.
+    fn update(&self, poll: &Poll) {
+        if let Ok(mut polls) = self.polls.lock()
+            && let Some(state) = polls.get_mut(&poll.id)
+        {
+            state.apply(poll);
+        }
     }
 }
 
+/// Options for [`send_poll`].
+#[derive(Debug, Clone, Default)]
+pub struct NewPoll {
+    pub chat_id: i64,
+    pub thread_id: Option<i64>,
+    pub question: String,
+    pub options: Vec<String>,
+    pub anonymous: bool,
+    pub multiple_answers: bool,
+}
+
+/// Post a native poll and register it in `store`; returns the poll id.
+pub async fn send_poll(
+    config: &TelegramConfig,
+    store: &PollStore,
+    poll: &NewPoll,
+) -> Result<String, TelegramError> {
+    let client = TelegramClient::from_config(config);
+    let sent = client
+        .send_poll(&SendPollBody {
+            chat_id: poll.chat_id,
+            question: &poll.question,
+            options: poll
+                .options
+                .iter()
+                .map(|text| InputPollOption { text })
+                .collect(),
+            is_anonymous: poll.anonymous,
+            allows_multiple_answers: poll.multiple_answers,
+            message_thread_id: poll.thread_id,
+        })
+        .await?;
+    let mut state = PollState {
+        chat_id: poll.chat_id,
+        message_id: sent.message_id,
+        question: sent.poll.question.clone(),
+        options: Vec::new(),
+        total_voters: 0,
+        is_closed: false,
+    };
+    state.apply(&sent.poll);
+    store.insert(sent.poll.id.clone(), state);
+    Ok(sent.poll.id)
+}
+
+/// Close a registered poll (stopPoll) and return its final state.
+pub async fn stop_poll(
+    config: &TelegramConfig,
+    store: &PollStore,
+    poll_id: &str,
+) -> Result<PollState, TelegramError> {
+    let mut state = store
+        .get(poll_id)
+        .ok_or_else(|| TelegramError::Io(format!("unknown poll '{poll_id}'")))?;
+    if state.is_closed {
+        return Ok(state);
+    }
+    let client = TelegramClient::from_config(config);
+    let poll = client.stop_poll(state.chat_id, state.message_id).await?;
+    store.update(&poll);
+    state.apply(&poll);
+    Ok(state)
+}
+
+/// State shared between the Telegram tasks and the tools that manage them.
+#[derive(Debug, Clone, Default)]
+pub struct TelegramShared {
+    pub allowlist: Arc<Allowlist>,
+    pub message_log: Arc<MessageLog>,
+    pub polls: Arc<PollStore>,
+}
+
+// --- Allow-list ---
+
+/// Users allowed to talk to the bot: config `allowed-user-ids` (the owners) plus users added at
+/// runtime by the `allowlist` tool (persisted in BrainDb). Shared by the poller and the tool.
+#[derive(Debug, Default)]
+pub struct Allowlist {
+    config_ids: Vec<i64>,
+    runtime_ids: RwLock<HashSet<i64>>,
+}
+
+impl Allowlist {
+    /// Owners from `telegram.allowed-user-ids`; no runtime additions yet.
+    pub fn from_config(cfg: &TelegramConfig) -> Self {
+        Self {
+            config_ids: cfg.allowed_user_ids.clone().unwrap_or_default(),
+            runtime_ids: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// True when no IDs are configured: everyone is allowed (setting IDs is recommended).
+    pub fn is_open(&self) -> bool {
+        self.config_ids.is_empty()
+    }
+
+    /// True if user is allowed: open allow-list, a configured owner, or a runtime addition.
+    pub fn is_allowed(&self, user_id: i64) -> bool {
+        self.is_open()
+            || self.config_ids.contains(&user_id)
+            || self
+                .runtime_ids
+                .read()
+                .map(|ids| ids.contains(&user_id))
+                .unwrap_or(false)
+    }
+
+    /// Owners are the IDs from config.toml; only they may change the allow-list.
+    pub fn is_owner(&self, user_id: i64) -> bool {
+        self.config_ids.contains(&user_id)
+    }
+
+    pub fn config_ids(&self) -> &[i64] {
+        &self.config_ids
+    }
+
+    /// Add a runtime user. Returns false if already present.
+    pub fn add(&self, user_id: i64) -> bool {
+        self.runtime_ids
+            .write()
+            .map(|mut ids| ids.insert(user_id))
+            .unwrap_or(false)
+    }
+
+    /// Remove a runtime user. Returns false if not present (config owners cannot be removed).
+    pub fn remove(&self, user_id: i64) -> bool {
+        self.runtime_ids
+            .write()
+            .map(|mut ids| ids.remove(&user_id))
+            .unwrap_or(false)
+    }
+
+    /// Every user allowed by ID: owners, then runtime additions not among them.
+    pub fn members(&self) -> Vec<i64> {
+        let mut ids = self.config_ids.clone();
+        for id in self.runtime_ids() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    /// Runtime-added users, sorted.
+    pub fn runtime_ids(&self) -> Vec<i64> {
+        let mut ids: Vec<i64> = self
+            .runtime_ids
+            .read()
+            .map(|ids| ids.iter().copied().collect())
+            .unwrap_or_default();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+// --- Poll backoff (iSH suspends networking aggressively) ---
+
+const POLL_BACKOFF_INITIAL_SECS: u64 = 1;
+const POLL_BACKOFF_MAX_SECS: u64 = 60;
+/// Consecutive failures after which the device is treated as sleepy.
+const SLEEPY_AFTER_FAILURES: u32 = 3;
+/// Long-poll timeout while sleepy: short requests recover faster from dead connections.
+const SLEEPY_GET_UPDATES_TIMEOUT_SECS: u64 = 5;
+/// Successful short polls before returning to the normal long-poll timeout.
+const SLEEPY_SHORT_POLLS: u32 = 5;
+
+/// Failure/suspend tracking for the poll loop: exponential backoff with jitter, a shorter
+/// long-poll timeout while the connection looks unreliable, and reconnect logging.
+#[derive(Debug, Default)]
+struct PollBackoff {
+    failures: u32,
+    down_since: Option<Instant>,
+    short_polls_left: u32,
+}
+
+impl PollBackoff {
+    /// Long-poll timeout for the next getUpdates call.
+    fn timeout_secs(&self) -> u64 {
+        if self.short_polls_left > 0 || self.failures >= SLEEPY_AFTER_FAILURES {
+            SLEEPY_GET_UPDATES_TIMEOUT_SECS
+        } else {
+            GET_UPDATES_TIMEOUT_SECS
+        }
+    }
+
+    /// Record a failure; returns how long to wait. `rand01` in [0, 1) picks the jitter:
+    /// the delay is uniform in [base/2, base] where base doubles per failure.
+    fn on_failure(&mut self, now: Instant, rand01: f64) -> Duration {
+        self.failures += 1;
+        self.down_since.get_or_insert(now);
+        if self.failures >= SLEEPY_AFTER_FAILURES {
+            self.short_polls_left = SLEEPY_SHORT_POLLS;
+        }
+        let exp = (self.failures - 1).min(16);
+        let base = (POLL_BACKOFF_INITIAL_SECS << exp).min(POLL_BACKOFF_MAX_SECS) as f64;
+        Duration::from_secs_f64(base * (0.5 + 0.5 * rand01.clamp(0.0, 1.0)))
+    }
+
+    /// Record a success; returns a reconnect log line if we were failing before.
+    fn on_success(&mut self, now: Instant) -> Option<String> {
+        self.short_polls_left = self.short_polls_left.saturating_sub(1);
+        let failures = std::mem::take(&mut self.failures);
+        let since = self.down_since.take()?;
+        Some(format!(
+            "telegram: reconnected after {} failed poll(s), down {}s",
+            failures,
+            now.saturating_duration_since(since).as_secs()
+        ))
+    }
+
+    /// A request outlived its HTTP timeout: the process was suspended (timers don't fire while
+    /// iSH is in the background), so the connection is probably stale.
+    fn on_suspend(&mut self) {
+        self.short_polls_left = SLEEPY_SHORT_POLLS;
+    }
+}
+
+/// Cheap randomness for jitter (no rand crate): v4 UUIDs come from the OS RNG.
+pub(crate) fn jitter_rand01() -> f64 {
+    (uuid::Uuid::new_v4().as_u128() % 1_000_000) as f64 / 1_000_000.0
+}
+
 /// Poll loop: long poll getUpdates, filter by allow-list, push InboundMsg to channel.
 async fn poll_loop(
     client: TelegramClient,
-    bot_token: String,
-    allowed_user_ids: Option<Vec<i64>>,
+    allowlist: Arc<Allowlist>,
+    polls: Arc<PollStore>,
     inbound_tx: mpsc::Sender<InboundMsg>,
 ) {
-    let cfg = TelegramConfig {
-        bot_token: Some(bot_token),
-        allowed_user_ids,
-        api_base: None,
-    };
     let mut offset: i64 = 0;
-    let mut backoff_secs = 1u64;
+    let mut backoff = PollBackoff::default();
 
     loop {
-        match client.get_updates(offset, GET_UPDATES_TIMEOUT_SECS).await {
+        let started = Instant::now();
+        let result = client.get_updates(offset, backoff.timeout_secs()).await;
+        if started.elapsed() > Duration::from_secs(HTTP_TIMEOUT_SECS + 5) {
+            eprintln!(
+                "telegram: poll took {}s, device was likely asleep; using short polls",
+                started.elapsed().as_secs()
+            );
+            backoff.on_suspend();
+        }
+        match result {
             Ok(updates) => {
-                backoff_secs = 1;
-                if !updates.is_empty() {
-                    let mut max_update_id = offset;
-                    for (update_id, chat_id, user_id, text) in updates {
-                        max_update_id = max_update_id.max(update_id);
-                        if !is_allowed(&cfg, user_id) {
-                            continue;
-                        }
-                        let msg = InboundMsg {
-                            chat_id,
-                            user_id,
-                            text,
-                            channel: "telegram".to_string(),
-                        };
-                        if inbound_tx.send(msg).await.is_err() {
-                            return;
-                        }
+                if let Some(line) = backoff.on_success(Instant::now()) {
+                    eprintln!("{}", line);
+                }
+                for poll in &updates.polls {
+                    polls.update(poll);
+                }
+                for msg in updates.messages {
+                    if !allowlist.is_allowed(msg.user_id) {
+                        continue;
+                    }
+                    if inbound_tx.send(msg).await.is_err() {
+                        return;
                     }
-                    offset = max_update_id + 1;
+                }
+                if let Some(id) = updates.last_update_id {
+                    offset = offset.max(id + 1);
                 }
             }
             Err(e) => {
+                let delay = backoff.on_failure(Instant::now(), jitter_rand01());
                 eprintln!(
-                    "telegram getUpdates error: {} (backoff {}s)",
-                    e, backoff_secs
+                    "telegram getUpdates error #{}: {} (retry in {:.1}s)",
+                    backoff.failures,
+                    e,
+                    delay.as_secs_f64()
                 );
-                tokio::time::sleep(Duration::from_secs(backoff_secs)).await;
-                backoff_secs = (backoff_secs * 2).min(30);
+                tokio::time::sleep(delay).await;
             }
         }
     }
 }
 
-/// Send loop: receive OutboundMsg from channel, call send_message; truncate and retry once on 400 if len > 4096.
-async fn send_loop(client: TelegramClient, mut outbound_rx: mpsc::Receiver<OutboundMsg>) {
-    while let Some(msg) = outbound_rx.recv().await {
-        if let Err(e) = client.send_message(msg.chat_id, msg.text).await {
-            eprintln!("telegram sendMessage error: {}", e);
+/// Send loop: drain the channel into the spooled outbox and deliver in FIFO order.
+/// Transient failures (network, 5xx, 429) retry with exponential backoff, honoring `retry_after`;
+/// permanent failures are logged and dropped. Text is truncated and retried once if > 4096.
+async fn send_loop(
+    client: TelegramClient,
+    mut outbound_rx: mpsc::Receiver<OutboundMsg>,
+    mut outbox: Outbox,
+    message_log: Arc<MessageLog>,
+    quiet: Option<QuietWindow>,
+) {
+    let mut limiter = ChatRateLimiter::default();
+    let mut backoff_secs = SEND_BACKOFF_INITIAL_SECS;
+    // Live reply key -> message id being edited.
+    let mut streams: HashMap<String, i64> = HashMap::new();
+    loop {
+        while let Ok(msg) = outbound_rx.try_recv() {
+            outbox.push(msg);
+        }
+        let Some(msg) = outbox.front().cloned() else {
+            match outbound_rx.recv().await {
+                Some(msg) => {
+                    outbox.push(msg);
+                    continue;
+                }
+                None => return,
+            }
+        };
+
+        // A live reply only needs its latest text; skip superseded intermediate updates.
+        let stream = match &msg.kind {
+            OutboundKind::Stream { key, done } => Some((key.clone(), *done)),
+            _ => None,
+        };
+        if let Some((key, false)) = &stream
+            && outbox.has_newer_stream(key)
+        {
+            outbox.pop_front();
+            continue;
+        }
+
+        let wait = limiter.wait_time(msg.chat_id, Instant::now());
+        if !wait.is_zero() {
+            wait_spooling(&mut outbound_rx, &mut outbox, wait).await;
         }
+        limiter.mark(msg.chat_id, Instant::now());
+
+        let silent = msg.disable_notification
+            || quiet
+                .as_ref()
+                .is_some_and(|q| q.silences(&msg, chrono::Utc::now()));
+        let editing = stream
+            .as_ref()
+            .and_then(|(key, _)| streams.get(key).copied());
+        let result = match editing {
+            Some(id) => client
+                .edit_message_text(msg.chat_id, id, &msg.text)
+                .await
+                .map(|()| None),
+            None => client.deliver(&msg, silent).await,
+        };
+        match result {
+            Ok(sent_id) => {
+                if let Some(id) = sent_id {
+                    message_log.record(msg.chat_id, msg.thread_id, id);
+                }
+                if let Some((key, done)) = stream {
+                    match (done, sent_id) {
+                        (true, _) => {
+                            streams.remove(&key);
+                        }
+                        (false, Some(id)) => {
+                            streams.insert(key, id);
+                        }
+                        (false, None) => {}
+                    }
+                }
+                backoff_secs = SEND_BACKOFF_INITIAL_SECS;
+                outbox.pop_front();
+                remove_temp_file(&msg);
+            }
+            Err(e) if e.is_transient() => {
+                let delay = e.retry_after().unwrap_or(Duration::from_secs(backoff_secs));
+                backoff_secs = (backoff_secs * 2).min(SEND_BACKOFF_MAX_SECS);
+                eprintln!(
+                    "telegram send error: {} (retry in {}s, {} queued)",
+                    e,
+                    delay.as_secs(),
+                    outbox.len()
+                );
+                wait_spooling(&mut outbound_rx, &mut outbox, delay).await;
+            }
+            Err(e) => {
+                eprintln!("telegram send error (dropped): {}", e);
+                backoff_secs = SEND_BACKOFF_INITIAL_SECS;
+                outbox.pop_front();
+                remove_temp_file(&msg);
+            }
+        }
+    }
+}
+
+/// Voice replies are rendered to a temporary file per message; clean it up once done.
+fn remove_temp_file(msg: &OutboundMsg) {
+    if let OutboundKind::Voice(path) = &msg.kind {
+        let _ = std::fs::remove_file(path);
     }
 }
 
@@ -326,29 +1552,304 @@ async fn send_loop(client: TelegramClient, mut outbound_rx: mpsc::Receiver<Outbo
 /// Caller creates the inbound channel and passes `inbound_tx` so other producers (e.g. cron runner)
 /// can inject messages. Poll loop pushes allowed user messages to inbound; main/agent sends
 /// replies via returned outbound_tx. Shutdown in v1: process kill; later add cancel token.
+/// Undelivered replies are spooled to `workspace/.icrab/outbox.jsonl` and resent on startup.
 pub fn spawn_telegram(
     config: &Config,
     inbound_tx: mpsc::Sender<InboundMsg>,
+) -> mpsc::Sender<OutboundMsg> {
+    let telegram = config.telegram.as_ref().expect("config validated");
+    spawn_telegram_with_shared(
+        config,
+        inbound_tx,
+        TelegramShared {
+            allowlist: Arc::new(Allowlist::from_config(telegram)),
+            ..Default::default()
+        },
+    )
+}
+
+/// Like [`spawn_telegram`], but with state shared with tools: incoming users are filtered
+/// through `shared.allowlist` (runtime changes apply without a restart) and sent message ids
+/// are recorded in `shared.message_log`.
+pub fn spawn_telegram_with_shared(
+    config: &Config,
+    inbound_tx: mpsc::Sender<InboundMsg>,
+    shared: TelegramShared,
 ) -> mpsc::Sender<OutboundMsg> {
     let telegram = config.telegram.as_ref().expect("config validated");
     let bot_token = telegram.bot_token.clone().expect("config validated");
-    let allowed_user_ids = telegram.allowed_user_ids.clone();
     let api_base = telegram.api_base.as_deref();
 
     let client = TelegramClient::with_base_url(&bot_token, api_base);
     let (outbound_tx, outbound_rx) = mpsc::channel(CHANNEL_CAP);
+    let outbox = Outbox::load(Some(workspace::outbox_file(Path::new(
+        &config.workspace_path(),
+    ))));
+    if outbox.len() > 0 {
+        eprintln!(
+            "telegram outbox: {} spooled message(s) to resend",
+            outbox.len()
+        );
+    }
 
     let poll_client = TelegramClient {
         client: client.client.clone(),
         base_url: client.base_url.clone(),
+        file_base_url: client.file_base_url.clone(),
     };
-    tokio::spawn(
-        async move { poll_loop(poll_client, bot_token, allowed_user_ids, inbound_tx).await },
-    );
+    let TelegramShared {
+        allowlist,
+        message_log,
+        polls,
+    } = shared;
+    let quiet = QuietWindow::from_config(config);
+    tokio::spawn(async move { poll_loop(poll_client, allowlist, polls, inbound_tx).await });
 
     tokio::spawn(async move {
-        send_loop(client, outbound_rx).await;
+        send_loop(client, outbound_rx, outbox, message_log, quiet).await;
     });
 
     outbound_tx
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multipart_body_layout() {
+        let (ct, body) = multipart_body(
+            &[("chat_id", "42".to_string())],
+            "document",
+            "notes.md",
+            b"hello",
+        );
+        let boundary = ct.strip_prefix("multipart/form-data; boundary=").unwrap();
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.contains("name=\"chat_id\"\r\n\r\n42\r\n"));
+        assert!(body.contains("name=\"document\"; filename=\"notes.md\""));
+        assert!(body.contains("\r\n\r\nhello\r\n"));
+        assert!(body.ends_with(&format!("--{boundary}--\r\n")));
+    }
+
+    #[test]
+    fn multipart_boundary_is_fresh_and_not_in_payload() {
+        let (first, _) = multipart_fields(&[("caption", "hi".to_string())]);
+        let (second, _) = multipart_fields(&[("caption", "hi".to_string())]);
+        assert_ne!(first, second);
+        let boundary = first
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        let payload = format!("--{boundary}--\r\n");
+        let (ct, _) = multipart_body(&[], "document", "a.txt", payload.as_bytes());
+        assert!(!payload.contains(ct.strip_prefix("multipart/form-data; boundary=").unwrap()));
+    }
+
+    #[test]
+    fn multipart_body_sanitizes_file_name() {
+        let (_, body) = multipart_body(&[], "document", "a\"b\r\n.txt", b"");
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.contains("filename=\"a_b__.txt\""));
+    }
+
+    #[test]
+    fn session_key_includes_forum_topic() {
+        let mut msg = InboundMsg {
+            chat_id: -100123,
+            user_id: 1,
+            text: "hi".into(),
+            channel: "telegram".into(),
+            location: None,
+            thread_id: None,
+            voice: None,
+            message_id: None,
+            cron_job: None,
+        };
+        assert_eq!(msg.session_key(), "-100123");
+        msg.thread_id = Some(42);
+        assert_eq!(msg.session_key(), "-100123:42");
+    }
+
+    #[test]
+    fn send_message_body_thread_id_optional() {
+        let body = SendMessageBody {
+            chat_id: 1,
+            text: "x".into(),
+            message_thread_id: None,
+            disable_notification: false,
+        };
+        let json = serde_json::to_value(&body).unwrap();
+        assert!(json.get("message_thread_id").is_none());
+        assert!(json.get("disable_notification").is_none());
+        let body = SendMessageBody {
+            chat_id: 1,
+            text: "x".into(),
+            message_thread_id: Some(7),
+            disable_notification: true,
+        };
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["message_thread_id"], 7);
+        assert_eq!(json["disable_notification"], true);
+    }
+
+    #[test]
+    fn poll_backoff_grows_with_jitter_and_caps() {
+        let mut b = PollBackoff::default();
+        let now = Instant::now();
+        assert_eq!(b.on_failure(now, 0.0), Duration::from_millis(500));
+        assert_eq!(b.on_failure(now, 0.999_999).as_secs(), 1);
+        for _ in 0..20 {
+            b.on_failure(now, 1.0);
+        }
+        assert_eq!(
+            b.on_failure(now, 1.0),
+            Duration::from_secs(POLL_BACKOFF_MAX_SECS)
+        );
+    }
+
+    #[test]
+    fn poll_backoff_uses_short_polls_when_sleepy() {
+        let mut b = PollBackoff::default();
+        let now = Instant::now();
+        assert_eq!(b.timeout_secs(), GET_UPDATES_TIMEOUT_SECS);
+        for _ in 0..SLEEPY_AFTER_FAILURES {
+            b.on_failure(now, 0.5);
+        }
+        assert_eq!(b.timeout_secs(), SLEEPY_GET_UPDATES_TIMEOUT_SECS);
+
+        let line = b
+            .on_success(now + Duration::from_secs(42))
+            .expect("reconnect log");
+        assert!(line.contains("3 failed poll(s), down 42s"), "{line}");
+        // Stays on short polls for a while after reconnecting.
+        for _ in 1..SLEEPY_SHORT_POLLS {
+            assert_eq!(b.timeout_secs(), SLEEPY_GET_UPDATES_TIMEOUT_SECS);
+            assert!(b.on_success(now).is_none());
+        }
+        assert_eq!(b.timeout_secs(), GET_UPDATES_TIMEOUT_SECS);
+
+        b.on_suspend();
+        assert_eq!(b.timeout_secs(), SLEEPY_GET_UPDATES_TIMEOUT_SECS);
+    }
+
+    #[test]
+    fn quiet_window_silences_only_background_messages() {
+        let quiet = QuietWindow {
+            hours: QuietHours::parse("23:00-07:00").unwrap(),
+            tz: chrono_tz::Tz::UTC,
+        };
+        let night = chrono::DateTime::parse_from_rfc3339("2025-01-01T02:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let noon = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let mut msg = text_msg(1, "reminder");
+        msg.channel = "cron".into();
+        assert!(quiet.silences(&msg, night));
+        assert!(!quiet.silences(&msg, noon));
+        msg.channel = "telegram".into();
+        assert!(
+            !quiet.silences(&msg, night),
+            "replies to the user still notify"
+        );
+    }
+
+    #[test]
+    fn topic_thread_id_requires_is_topic_message() {
+        let topic: Message = serde_json::from_value(serde_json::json!({
+            "text": "a", "message_thread_id": 5, "is_topic_message": true
+        }))
+        .unwrap();
+        assert_eq!(
+            topic.message_thread_id.filter(|_| topic.is_topic_message),
+            Some(5)
+        );
+        let reply_thread: Message = serde_json::from_value(serde_json::json!({
+            "text": "a", "message_thread_id": 5
+        }))
+        .unwrap();
+        assert!(!reply_thread.is_topic_message);
+    }
+
+    fn text_msg(chat_id: i64, text: &str) -> OutboundMsg {
+        OutboundMsg {
+            chat_id,
+            text: text.into(),
+            channel: "telegram".into(),
+            kind: OutboundKind::Text,
+            thread_id: None,
+            disable_notification: false,
+        }
+    }
+
+    #[test]
+    fn outbox_spool_roundtrip() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let spool = tmp.path().join(".icrab").join("outbox.jsonl");
+        let mut outbox = Outbox::load(Some(spool.clone()));
+        assert_eq!(outbox.len(), 0);
+        outbox.push(text_msg(1, "first"));
+        let mut photo = text_msg(2, "chart");
+        photo.kind = OutboundKind::Photo(PathBuf::from("/tmp/c.png"));
+        photo.thread_id = Some(9);
+        outbox.push(photo);
+        assert!(spool.exists());
+
+        let mut restored = Outbox::load(Some(spool.clone()));
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored.front().unwrap().text, "first");
+        restored.pop_front();
+        let next = restored.front().unwrap();
+        assert_eq!(next.kind, OutboundKind::Photo(PathBuf::from("/tmp/c.png")));
+        assert_eq!(next.thread_id, Some(9));
+        restored.pop_front();
+        assert!(!spool.exists(), "empty outbox removes the spool");
+    }
+
+    #[test]
+    fn outbox_skips_corrupt_spool_lines() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let spool = tmp.path().join("outbox.jsonl");
+        let good = serde_json::to_string(&text_msg(1, "ok")).unwrap();
+        std::fs::write(&spool, format!("{{not json\n{good}\n")).unwrap();
+        assert_eq!(Outbox::load(Some(spool)).len(), 1);
+    }
+
+    #[test]
+    fn rate_limiter_spaces_messages_per_chat() {
+        let mut limiter = ChatRateLimiter::default();
+        let now = Instant::now();
+        assert_eq!(limiter.wait_time(5, now), Duration::ZERO);
+        limiter.mark(5, now);
+        limiter.mark(-100, now);
+        assert_eq!(limiter.wait_time(5, now), PRIVATE_CHAT_INTERVAL);
+        assert_eq!(limiter.wait_time(-100, now), GROUP_CHAT_INTERVAL);
+        assert_eq!(limiter.wait_time(6, now), Duration::ZERO);
+        assert_eq!(
+            limiter.wait_time(5, now + Duration::from_secs(2)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn api_error_classification() {
+        let e = api_error(
+            reqwest::StatusCode::TOO_MANY_REQUESTS,
+            r#"{"ok":false,"error_code":429,"description":"Too Many Requests: retry after 7","parameters":{"retry_after":7}}"#,
+        );
+        assert!(e.is_transient());
+        assert_eq!(e.retry_after(), Some(Duration::from_secs(7)));
+
+        let e = api_error(
+            reqwest::StatusCode::BAD_REQUEST,
+            r#"{"ok":false,"error_code":400,"description":"chat not found"}"#,
+        );
+        assert!(!e.is_transient());
+
+        let e = api_error(reqwest::StatusCode::BAD_GATEWAY, "<html>bad gateway</html>");
+        assert!(e.is_transient());
+        assert!(e.retry_after().is_none());
+        assert!(!TelegramError::Io("gone".into()).is_transient());
+    }
+}
@@ -1,18 +1,63 @@
-//! Tool registry and implementations: file, web, message, cron, spawn; optional exec.
+//! Tool registry and implementations: file, web, http_request, message, cron, spawn; optional exec.
 
+pub mod allowlist;
+pub mod archive;
+pub mod backup;
+pub mod bookmark;
+pub mod calc;
+pub mod calendar;
 pub mod context;
+pub mod convert;
 pub mod cron;
+pub mod csv;
+pub mod daily_note;
+pub mod download;
+pub mod email;
+pub mod entity;
+pub mod exec;
+pub mod expense;
+pub mod export_chat;
+pub mod feeds;
 pub mod file;
+pub mod forget;
+pub mod generate_image;
 pub mod git;
+pub mod github;
 pub mod grep_dir;
+pub mod habits;
+pub mod hn;
+pub mod http_request;
+pub mod journal;
+pub mod memory;
 pub mod message;
+pub mod ocr;
+pub mod patch;
+pub mod qr;
 pub mod registry;
+pub mod remind_me;
 pub mod result;
 pub mod search;
 pub mod search_chat;
+pub mod semantic_search;
+pub mod send_file;
+pub mod send_photo;
+pub mod session;
+pub mod share;
 pub mod spawn;
 pub mod subagent;
+pub mod summarize_url;
+pub mod telegram_poll;
+pub mod time;
+pub mod timer;
+pub mod todo;
+pub mod translate;
+pub mod tts;
+pub mod usage;
+pub mod vault_stats;
+pub mod weather;
 pub mod web;
+pub mod wikipedia;
+pub mod workout;
 
 pub use context::ToolCtx;
 pub use git::GitSyncTool;
@@ -21,3 +66,4 @@ pub use registry::{Tool, ToolRegistry, build_core_registry, build_default_regist
 pub use result::ToolResult;
 pub use search::SearchVaultTool;
 pub use search_chat::SearchChatTool;
+pub use semantic_search::SemanticSearchVaultTool;
@@ -0,0 +1,234 @@
+//! `allowlist` tool: owner-only runtime management of allowed Telegram users.
+//!
+//! Additions are persisted in BrainDb (`allowed_users`) and applied to the shared
+//! [`Allowlist`] the poller checks, so they take effect without editing config.toml
+//! or restarting. Owners are the IDs in `telegram.allowed-user-ids`.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::memory::db::BrainDb;
+use crate::telegram::Allowlist;
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+pub struct AllowlistTool {
+    allowlist: Arc<Allowlist>,
+    db: Arc<BrainDb>,
+}
+
+impl AllowlistTool {
+    pub fn new(allowlist: Arc<Allowlist>, db: Arc<BrainDb>) -> Self {
+        Self { allowlist, db }
+    }
+}
+
+fn get_user_id(args: &Value) -> Result<i64, String> {
+    match args.get("user_id") {
+        Some(Value::Number(n)) => n.as_i64(),
+        Some(Value::String(s)) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|id| *id > 0)
+    .ok_or_else(|| "missing or invalid 'user_id' (positive Telegram user ID)".to_string())
+}
+
+impl Tool for AllowlistTool {
+    fn name(&self) -> &str {
+        "allowlist"
+    }
+
+    fn description(&self) -> &str {
+        "Manage which Telegram users may talk to the bot (owner only). \
+         Actions: add, remove, list. Changes persist and apply immediately."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["add", "remove", "list"],
+                    "description": "add/remove a user, or list allowed users"
+                },
+                "user_id": {
+                    "type": "integer",
+                    "description": "Telegram user ID (required for add/remove)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let allowlist = Arc::clone(&self.allowlist);
+        let db = Arc::clone(&self.db);
+        let args = args.clone();
+        let chat_id = ctx.chat_id;
+
+        Box::pin(async move {
+            // Private chats share the user's ID, so the owner check is on the chat.
+            if !chat_id.is_some_and(|id| allowlist.is_owner(id)) {
+                return ToolResult::error(
+                    "allowlist is owner-only (use it from an owner's private chat)",
+                );
+            }
+            let action = args
+                .get("action")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            match action.as_str() {
+                "list" => {
+                    let owners = join_ids(allowlist.config_ids());
+                    let runtime = allowlist.runtime_ids();
+                    let added = if runtime.is_empty() {
+                        "none".to_string()
+                    } else {
+                        join_ids(&runtime)
+                    };
+                    ToolResult::ok(format!(
+                        "Owners (config.toml): {owners}\nAdded at runtime: {added}"
+                    ))
+                }
+                "add" | "remove" => {
+                    let user_id = match get_user_id(&args) {
+                        Ok(id) => id,
+                        Err(e) => return ToolResult::error(e),
+                    };
+                    if allowlist.is_owner(user_id) {
+                        return ToolResult::error(format!(
+                            "{user_id} is an owner from config.toml; edit the config to change owners"
+                        ));
+                    }
+                    let adding = action == "add";
+                    let result = tokio::task::spawn_blocking(move || {
+                        if adding {
+                            db.add_allowed_user(user_id)
+                        } else {
+                            db.remove_allowed_user(user_id)
+                        }
+                    })
+                    .await;
+                    match result {
+                        Ok(Ok(changed)) => {
+                            if adding {
+                                allowlist.add(user_id);
+                            } else {
+                                allowlist.remove(user_id);
+                            }
+                            let msg = match (adding, changed) {
+                                (true, true) => format!("User {user_id} is now allowed."),
+                                (true, false) => format!("User {user_id} was already allowed."),
+                                (false, true) => format!("User {user_id} removed."),
+                                (false, false) => format!("User {user_id} was not on the list."),
+                            };
+                            ToolResult::ok(msg)
+                        }
+                        Ok(Err(e)) => ToolResult::error(format!("allowlist update failed: {e}")),
+                        Err(e) => ToolResult::error(format!("allowlist task error: {e}")),
+                    }
+                }
+                other => ToolResult::error(format!(
+                    "unknown action '{other}' (use add, remove or list)"
+                )),
+            }
+        })
+    }
+}
+
+fn join_ids(ids: &[i64]) -> String {
+    ids.iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    use crate::config::TelegramConfig;
+
+    fn setup() -> (TempDir, Arc<Allowlist>, AllowlistTool) {
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let allowlist = Arc::new(Allowlist::from_config(&TelegramConfig {
+            bot_token: None,
+            allowed_user_ids: Some(vec![100]),
+            api_base: None,
+            quiet_hours: None,
+        }));
+        let tool = AllowlistTool::new(Arc::clone(&allowlist), db);
+        (tmp, allowlist, tool)
+    }
+
+    fn ctx(chat_id: i64) -> ToolCtx {
+        ToolCtx {
+            chat_id: Some(chat_id),
+            ..ToolCtx::for_tests(std::env::temp_dir())
+        }
+    }
+
+    #[tokio::test]
+    async fn non_owner_is_rejected() {
+        let (_tmp, allowlist, tool) = setup();
+        let res = tool
+            .execute(
+                &ctx(555),
+                &serde_json::json!({"action": "add", "user_id": 555}),
+            )
+            .await;
+        assert!(res.is_error);
+        assert!(!allowlist.is_allowed(555));
+    }
+
+    #[tokio::test]
+    async fn owner_adds_and_removes_user() {
+        let (_tmp, allowlist, tool) = setup();
+        let owner = ctx(100);
+        let res = tool
+            .execute(
+                &owner,
+                &serde_json::json!({"action": "add", "user_id": "555"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(allowlist.is_allowed(555));
+
+        let res = tool
+            .execute(&owner, &serde_json::json!({"action": "list"}))
+            .await;
+        assert!(res.for_llm.contains("555"));
+
+        let res = tool
+            .execute(
+                &owner,
+                &serde_json::json!({"action": "remove", "user_id": 555}),
+            )
+            .await;
+        assert!(!res.is_error);
+        assert!(!allowlist.is_allowed(555));
+    }
+
+    #[tokio::test]
+    async fn owner_cannot_be_removed() {
+        let (_tmp, allowlist, tool) = setup();
+        let res = tool
+            .execute(
+                &ctx(100),
+                &serde_json::json!({"action": "remove", "user_id": 100}),
+            )
+            .await;
+        assert!(res.is_error);
+        assert!(allowlist.is_allowed(100));
+    }
+}
@@ -0,0 +1,1071 @@
+//! `archive` tool: zip or tar a workspace folder (e.g. to send a backup with `send_file`), and
+//! list or extract .zip, .tar and .tar.gz archives into `Inbox/`.
+//!
+//! Archives are read and written here rather than with `zip`/`tar` binaries, which iSH may lack
+//! and which would decide for themselves where entries land. Extraction writes only regular
+//! files and never over existing ones: absolute names, `..` components and links are skipped,
+//! and entry count and total size are capped against zip bombs.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::memory::backup::format_bytes;
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Cap on the files packed, and on what an archive may unpack to.
+const MAX_TOTAL_BYTES: u64 = 100 * 1024 * 1024;
+const MAX_ENTRIES: usize = 10_000;
+/// Folders left out when creating an archive.
+const SKIPPED_DIRS: &[&str] = &[".git"];
+/// Entry names listed in a reply before "… and N more".
+const MAX_LISTED: usize = 50;
+const DEFAULT_EXTRACT_DIR: &str = "Inbox";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl Format {
+    /// Format and name without the extension, from a file name like "notes.tar.gz".
+    fn detect(name: &str) -> Option<(Self, &str)> {
+        let lower = name.to_ascii_lowercase();
+        [
+            (".tar.gz", Self::TarGz),
+            (".tgz", Self::TarGz),
+            (".tar", Self::Tar),
+            (".zip", Self::Zip),
+        ]
+        .into_iter()
+        .find(|(ext, _)| lower.ends_with(ext) && lower.len() > ext.len())
+        .map(|(ext, format)| (format, &name[..name.len() - ext.len()]))
+    }
+}
+
+/// A regular file in an archive: its `/`-separated name, contents and modification time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    data: Vec<u8>,
+    mtime: i64,
+}
+
+/// Files read from an archive, and the names of entries left out (links, unsafe paths).
+#[derive(Debug, Default)]
+struct Contents {
+    files: Vec<Entry>,
+    skipped: Vec<String>,
+}
+
+impl Contents {
+    fn push(&mut self, name: String, data: Vec<u8>, mtime: i64) -> Result<(), String> {
+        if self.files.len() + self.skipped.len() >= MAX_ENTRIES {
+            return Err(format!("the archive has more than {MAX_ENTRIES} entries"));
+        }
+        if safe_entry_path(&name).is_none() {
+            self.skipped.push(name);
+            return Ok(());
+        }
+        let total: u64 = self.files.iter().map(|f| f.data.len() as u64).sum();
+        if total + data.len() as u64 > MAX_TOTAL_BYTES {
+            return Err(format!(
+                "the archive unpacks to more than {}",
+                format_bytes(MAX_TOTAL_BYTES)
+            ));
+        }
+        self.files.push(Entry { name, data, mtime });
+        Ok(())
+    }
+}
+
+/// Relative path for an entry name; None for absolute names, `..` components or no name.
+fn safe_entry_path(name: &str) -> Option<PathBuf> {
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') {
+        return None;
+    }
+    let mut path = PathBuf::new();
+    for part in name.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => return None,
+            part => path.push(part),
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+// ---------------------------------------------------------------------------
+// Checksums and byte helpers
+// ---------------------------------------------------------------------------
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32 as used by zip and gzip.
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |c, &b| {
+        CRC_TABLE[((c ^ b as u32) & 0xff) as usize] ^ (c >> 8)
+    })
+}
+
+/// `at + len`, as an error instead of wrapping on crafted offsets (usize is 32 bits on i686).
+fn offset(at: usize, len: usize) -> Result<usize, String> {
+    at.checked_add(len)
+        .ok_or_else(|| "the archive is truncated".to_string())
+}
+
+/// `bytes[at..at + len]`.
+fn slice(bytes: &[u8], at: usize, len: usize) -> Result<&[u8], String> {
+    bytes
+        .get(at..offset(at, len)?)
+        .ok_or_else(|| "the archive is truncated".to_string())
+}
+
+fn le16(bytes: &[u8], at: usize) -> Result<u16, String> {
+    slice(bytes, at, 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn le32(bytes: &[u8], at: usize) -> Result<u32, String> {
+    slice(bytes, at, 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+// ---------------------------------------------------------------------------
+// Zip
+// ---------------------------------------------------------------------------
+
+const ZIP_LOCAL: u32 = 0x0403_4b50;
+const ZIP_CENTRAL: u32 = 0x0201_4b50;
+const ZIP_END: u32 = 0x0605_4b50;
+
+/// MS-DOS (time, date) for a Unix timestamp, as zip stores it; 1980 at the earliest.
+fn dos_time(mtime: i64) -> (u16, u16) {
+    use chrono::{Datelike, Timelike};
+    let Some(t) = chrono::DateTime::from_timestamp(mtime, 0).filter(|t| t.year() >= 1980) else {
+        return (0, 1 << 5 | 1);
+    };
+    let time = (t.hour() << 11 | t.minute() << 5 | (t.second() / 2)) as u16;
+    let date = ((t.year() as u32 - 1980) << 9 | t.month() << 5 | t.day()) as u16;
+    (time, date)
+}
+
+/// A zip archive of `entries`, each deflated unless that makes it bigger.
+fn write_zip(entries: &[Entry]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for entry in entries {
+        let deflated = miniz_oxide::deflate::compress_to_vec(&entry.data, 6);
+        let (method, body): (u16, &[u8]) = if deflated.len() < entry.data.len() {
+            (8, &deflated)
+        } else {
+            (0, &entry.data)
+        };
+        let (time, date) = dos_time(entry.mtime);
+        let name = entry.name.as_bytes();
+        // Shared by the local and central headers: version needed, flags (UTF-8 names),
+        // method, time, date, CRC, sizes, name length, extra length.
+        let mut common = Vec::with_capacity(26);
+        common.extend_from_slice(&20u16.to_le_bytes());
+        common.extend_from_slice(&0x0800u16.to_le_bytes());
+        common.extend_from_slice(&method.to_le_bytes());
+        common.extend_from_slice(&time.to_le_bytes());
+        common.extend_from_slice(&date.to_le_bytes());
+        common.extend_from_slice(&crc32(&entry.data).to_le_bytes());
+        common.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+
+        let offset = out.len() as u32;
+        out.extend_from_slice(&ZIP_LOCAL.to_le_bytes());
+        out.extend_from_slice(&common);
+        out.extend_from_slice(name);
+        out.extend_from_slice(body);
+
+        central.extend_from_slice(&ZIP_CENTRAL.to_le_bytes());
+        // Made by Unix, zip 2.0.
+        central.extend_from_slice(&(3u16 << 8 | 20).to_le_bytes());
+        central.extend_from_slice(&common);
+        // Comment length, disk number, internal attributes.
+        central.extend_from_slice(&[0; 6]);
+        central.extend_from_slice(&(0o100644u32 << 16).to_le_bytes());
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name);
+    }
+    let central_offset = out.len() as u32;
+    let count = entries.len() as u16;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&ZIP_END.to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&count.to_le_bytes());
+    out.extend_from_slice(&count.to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+}
+
+/// Files of a zip archive, from its central directory. Stored and deflated entries only.
+fn read_zip(bytes: &[u8]) -> Result<Contents, String> {
+    let search_from = bytes.len().saturating_sub(22 + u16::MAX as usize);
+    let end = (search_from..=bytes.len().saturating_sub(22))
+        .rev()
+        .find(|&i| le32(bytes, i) == Ok(ZIP_END))
+        .ok_or("not a zip archive")?;
+    let count = le16(bytes, end + 10)?;
+    let mut at = le32(bytes, end + 16)? as usize;
+    if at == u32::MAX as usize {
+        return Err("zip64 archives are not supported".into());
+    }
+
+    let mut contents = Contents::default();
+    for _ in 0..count {
+        if le32(bytes, at)? != ZIP_CENTRAL {
+            return Err("the zip central directory is corrupt".into());
+        }
+        let header = slice(bytes, at, 46)?;
+        let flags = le16(header, 8)?;
+        let method = le16(header, 10)?;
+        let crc = le32(header, 16)?;
+        let packed = le32(header, 20)? as usize;
+        let size = le32(header, 24)? as usize;
+        let name_len = le16(header, 28)? as usize;
+        let extra_len = le16(header, 30)? as usize;
+        let comment_len = le16(header, 32)? as usize;
+        let mode = le32(header, 38)? >> 16;
+        let local = le32(header, 42)? as usize;
+        let name = slice(bytes, at + 46, name_len)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        at = offset(at + 46, name_len + extra_len + comment_len)?;
+
+        if name.ends_with('/') {
+            continue;
+        }
+        if flags & 1 != 0 {
+            return Err(format!(
+                "{name} is encrypted; encrypted zips are not supported"
+            ));
+        }
+        if mode & 0o170000 == 0o120000 || !matches!(method, 0 | 8) {
+            contents.skipped.push(name);
+            continue;
+        }
+        if size as u64 > MAX_TOTAL_BYTES {
+            return Err(format!(
+                "the archive unpacks to more than {}",
+                format_bytes(MAX_TOTAL_BYTES)
+            ));
+        }
+        let header = slice(bytes, local, 30)?;
+        if le32(header, 0)? != ZIP_LOCAL {
+            return Err(format!("{name}: corrupt local header"));
+        }
+        let start = offset(
+            local + 30,
+            le16(header, 26)? as usize + le16(header, 28)? as usize,
+        )?;
+        let body = slice(bytes, start, packed)?;
+        let data = if method == 0 {
+            body.to_vec()
+        } else {
+            miniz_oxide::inflate::decompress_to_vec_with_limit(body, size)
+                .map_err(|e| format!("{name}: cannot inflate ({e})"))?
+        };
+        if data.len() != size || crc32(&data) != crc {
+            return Err(format!("{name} is corrupt (size or CRC mismatch)"));
+        }
+        contents.push(name, data, 0)?;
+    }
+    Ok(contents)
+}
+
+// ---------------------------------------------------------------------------
+// Tar and gzip
+// ---------------------------------------------------------------------------
+
+const BLOCK: usize = 512;
+
+/// A ustar header. `name` is cut to 100 bytes; longer names go in a preceding PAX header.
+fn tar_header(name: &str, size: u64, mtime: i64, typeflag: u8) -> [u8; BLOCK] {
+    let mut h = [0u8; BLOCK];
+    let mut cut = name.len().min(100);
+    while !name.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    h[..cut].copy_from_slice(&name.as_bytes()[..cut]);
+    let octal = |h: &mut [u8; BLOCK], at: usize, width: usize, value: u64| {
+        let s = format!("{value:0w$o}", w = width - 1);
+        h[at..at + width - 1].copy_from_slice(s.as_bytes());
+    };
+    octal(&mut h, 100, 8, 0o644);
+    octal(&mut h, 108, 8, 0);
+    octal(&mut h, 116, 8, 0);
+    octal(&mut h, 124, 12, size);
+    octal(&mut h, 136, 12, mtime.max(0) as u64);
+    h[148..156].fill(b' ');
+    h[156] = typeflag;
+    h[257..263].copy_from_slice(b"ustar\0");
+    h[263..265].copy_from_slice(b"00");
+    let sum: u32 = h.iter().map(|&b| b as u32).sum();
+    let s = format!("{sum:06o}\0 ");
+    h[148..156].copy_from_slice(s.as_bytes());
+    h
+}
+
+/// Append `data` padded to whole blocks.
+fn push_blocks(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend_from_slice(data);
+    out.resize(out.len().div_ceil(BLOCK) * BLOCK, 0);
+}
+
+/// A PAX extended header record "<len> path=<name>\n", where <len> counts itself.
+fn pax_path_record(name: &str) -> String {
+    let body = format!(" path={name}\n");
+    let mut len = body.len() + 1;
+    while format!("{len}{body}").len() != len {
+        len += 1;
+    }
+    format!("{len}{body}")
+}
+
+fn write_tar(entries: &[Entry]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for entry in entries {
+        if entry.name.len() > 100 {
+            let record = pax_path_record(&entry.name);
+            out.extend_from_slice(&tar_header(
+                "././@PaxHeader",
+                record.len() as u64,
+                entry.mtime,
+                b'x',
+            ));
+            push_blocks(&mut out, record.as_bytes());
+        }
+        out.extend_from_slice(&tar_header(
+            &entry.name,
+            entry.data.len() as u64,
+            entry.mtime,
+            b'0',
+        ));
+        push_blocks(&mut out, &entry.data);
+    }
+    out.extend_from_slice(&[0; 2 * BLOCK]);
+    out
+}
+
+/// Text of a NUL-terminated header field.
+fn field(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn octal(bytes: &[u8]) -> Result<u64, String> {
+    let s = field(bytes);
+    let s = s.trim_matches([' ', '\0']);
+    if s.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(s, 8).map_err(|_| "not a tar archive (bad header)".to_string())
+}
+
+/// The `path` of a PAX extended header, if it sets one.
+fn pax_path(data: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(data).lines().find_map(|record| {
+        let (_, kv) = record.split_once(' ')?;
+        kv.strip_prefix("path=").map(String::from)
+    })
+}
+
+fn read_tar(bytes: &[u8]) -> Result<Contents, String> {
+    let mut contents = Contents::default();
+    let mut long_name: Option<String> = None;
+    let mut at = 0;
+    while at + BLOCK <= bytes.len() {
+        let h = &bytes[at..at + BLOCK];
+        if h.iter().all(|&b| b == 0) {
+            break;
+        }
+        let stored = octal(&h[148..156])?;
+        let sum: u64 = h
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| {
+                if (148..156).contains(&i) {
+                    32
+                } else {
+                    b as u64
+                }
+            })
+            .sum();
+        if stored != sum {
+            return Err("not a tar archive (bad header checksum)".into());
+        }
+        let size = octal(&h[124..136])?;
+        if size > MAX_TOTAL_BYTES {
+            return Err(format!(
+                "the archive unpacks to more than {}",
+                format_bytes(MAX_TOTAL_BYTES)
+            ));
+        }
+        let size = size as usize;
+        let mut name = field(&h[..100]);
+        if &h[257..262] == b"ustar" {
+            let prefix = field(&h[345..500]);
+            if !prefix.is_empty() {
+                name = format!("{prefix}/{name}");
+            }
+        }
+        let start = at + BLOCK;
+        let data = slice(bytes, start, size)?;
+        let padded = size
+            .div_ceil(BLOCK)
+            .checked_mul(BLOCK)
+            .ok_or("the archive is truncated")?;
+        at = offset(start, padded)?;
+
+        match h[156] {
+            b'x' => long_name = pax_path(data).or(long_name),
+            b'L' => long_name = Some(field(data)),
+            b'g' => {}
+            b'0' | b'7' | 0 => {
+                let name = long_name.take().unwrap_or(name);
+                if !name.ends_with('/') {
+                    let mtime = octal(&h[136..148]).unwrap_or(0) as i64;
+                    contents.push(name, data.to_vec(), mtime)?;
+                }
+            }
+            b'5' => long_name = None,
+            // Hard and symbolic links, devices, FIFOs.
+            _ => contents.skipped.push(long_name.take().unwrap_or(name)),
+        }
+    }
+    Ok(contents)
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255];
+    out.extend_from_slice(&miniz_oxide::deflate::compress_to_vec(data, 6));
+    out.extend_from_slice(&crc32(data).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out
+}
+
+/// Contents of a single-member gzip file, at most `limit` bytes.
+fn gunzip(bytes: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    if bytes.len() < 18 || bytes[..3] != [0x1f, 0x8b, 8] {
+        return Err("not a gzip file".into());
+    }
+    let flags = bytes[3];
+    let mut at = 10;
+    if flags & 4 != 0 {
+        at += 2 + le16(bytes, at)? as usize;
+    }
+    // File name, then comment, each NUL-terminated.
+    for flag in [8, 16] {
+        if flags & flag != 0 {
+            let end = bytes[at.min(bytes.len())..]
+                .iter()
+                .position(|&b| b == 0)
+                .ok_or("the archive is truncated")?;
+            at += end + 1;
+        }
+    }
+    if flags & 2 != 0 {
+        at += 2;
+    }
+    let body = bytes
+        .get(at..bytes.len() - 8)
+        .ok_or("the archive is truncated")?;
+    let data = miniz_oxide::inflate::decompress_to_vec_with_limit(body, limit).map_err(|_| {
+        format!(
+            "cannot decompress (corrupt, or larger than {})",
+            format_bytes(limit as u64)
+        )
+    })?;
+    if crc32(&data) != le32(bytes, bytes.len() - 8)? {
+        return Err("the archive is corrupt (CRC mismatch)".into());
+    }
+    Ok(data)
+}
+
+fn read_archive(format: Format, bytes: &[u8]) -> Result<Contents, String> {
+    match format {
+        Format::Zip => read_zip(bytes),
+        Format::Tar => read_tar(bytes),
+        // Tar headers and padding on top of the file data.
+        Format::TarGz => read_tar(&gunzip(
+            bytes,
+            MAX_TOTAL_BYTES as usize + (MAX_ENTRIES + 2) * 2 * BLOCK,
+        )?),
+    }
+}
+
+fn write_archive(format: Format, entries: &[Entry]) -> Vec<u8> {
+    match format {
+        Format::Zip => write_zip(entries),
+        Format::Tar => write_tar(entries),
+        Format::TarGz => gzip(&write_tar(entries)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Files
+// ---------------------------------------------------------------------------
+
+/// Regular files under `root` (or `root` itself), named `<root name>/<relative path>`.
+/// Links and [`SKIPPED_DIRS`] are left out.
+fn collect(root: &Path, root_name: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    let mut total = 0u64;
+    let mut pending = vec![(root.to_path_buf(), root_name.to_string())];
+    while let Some((path, name)) = pending.pop() {
+        let meta = std::fs::symlink_metadata(&path).map_err(|e| format!("{name}: {e}"))?;
+        if meta.is_dir() {
+            let mut children: Vec<_> = std::fs::read_dir(&path)
+                .map_err(|e| format!("{name}: {e}"))?
+                .filter_map(Result::ok)
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .filter(|n| !SKIPPED_DIRS.contains(&n.as_str()))
+                .collect();
+            // Popped from the end, so reversed to come out in name order.
+            children.sort_unstable_by(|a, b| b.cmp(a));
+            pending.extend(
+                children
+                    .into_iter()
+                    .map(|child| (path.join(&child), format!("{name}/{child}"))),
+            );
+        } else if meta.is_file() {
+            total += meta.len();
+            if total > MAX_TOTAL_BYTES || entries.len() >= MAX_ENTRIES {
+                return Err(format!(
+                    "too much to archive (limit {} files, {})",
+                    MAX_ENTRIES,
+                    format_bytes(MAX_TOTAL_BYTES)
+                ));
+            }
+            let mtime = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs() as i64);
+            let data = std::fs::read(&path).map_err(|e| format!("{name}: {e}"))?;
+            entries.push(Entry { name, data, mtime });
+        }
+    }
+    Ok(entries)
+}
+
+/// Write `files` under `dest`, creating folders as needed. Nothing is written if any file
+/// already exists, two entries share a path or one entry needs another as its folder, and
+/// anything written before a failure is removed again. Returns the bytes written.
+fn extract_to(dest: &Path, files: &[Entry]) -> Result<u64, String> {
+    let targets: Vec<(PathBuf, &Entry)> = files
+        .iter()
+        .filter_map(|f| safe_entry_path(&f.name).map(|rel| (dest.join(rel), f)))
+        .collect();
+    let mut names: HashMap<&Path, &str> = HashMap::new();
+    for (target, file) in &targets {
+        if names.insert(target, &file.name).is_some() {
+            return Err(format!("{} appears twice in the archive", file.name));
+        }
+    }
+    for (target, file) in &targets {
+        if target.exists() {
+            return Err(format!("{} already exists", file.name));
+        }
+        for folder in target.ancestors().skip(1).take_while(|p| *p != dest) {
+            if let Some(other) = names.get(folder) {
+                return Err(format!(
+                    "{other} is a file but {} needs it as a folder",
+                    file.name
+                ));
+            }
+            if folder.exists() && !folder.is_dir() {
+                let rel = folder.strip_prefix(dest).unwrap_or(folder);
+                return Err(format!(
+                    "{} already exists and is not a folder",
+                    rel.display()
+                ));
+            }
+        }
+    }
+
+    let mut created: Vec<PathBuf> = Vec::new();
+    let mut write_all = || -> Result<u64, String> {
+        let mut written = 0;
+        for (target, file) in &targets {
+            let err = |e: std::io::Error| format!("{}: {e}", file.name);
+            if let Some(parent) = target.parent() {
+                let missing: Vec<&Path> = parent.ancestors().take_while(|p| !p.exists()).collect();
+                for folder in missing.into_iter().rev() {
+                    std::fs::create_dir(folder).map_err(err)?;
+                    created.push(folder.to_path_buf());
+                }
+            }
+            let mut out = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(target)
+                .map_err(err)?;
+            created.push(target.clone());
+            out.write_all(&file.data).map_err(err)?;
+            written += file.data.len() as u64;
+        }
+        Ok(written)
+    };
+    let result = write_all();
+    if result.is_err() {
+        // Newest first, so folders are empty by the time they are removed.
+        for path in created.iter().rev() {
+            let _ = std::fs::remove_file(path).or_else(|_| std::fs::remove_dir(path));
+        }
+    }
+    result
+}
+
+/// The folder every file sits under, when there is exactly one.
+fn common_root(files: &[Entry]) -> Option<&str> {
+    let (root, _) = files.first()?.name.split_once('/')?;
+    files
+        .iter()
+        .all(|f| f.name.split_once('/').is_some_and(|(r, _)| r == root))
+        .then_some(root)
+}
+
+/// "- name (size)" lines for the first [`MAX_LISTED`] entries.
+fn listing(files: &[Entry]) -> String {
+    let mut out: String = files
+        .iter()
+        .take(MAX_LISTED)
+        .map(|f| format!("\n- {} ({})", f.name, format_bytes(f.data.len() as u64)))
+        .collect();
+    if files.len() > MAX_LISTED {
+        out.push_str(&format!("\n… and {} more", files.len() - MAX_LISTED));
+    }
+    out
+}
+
+fn skipped_note(skipped: &[String]) -> String {
+    if skipped.is_empty() {
+        return String::new();
+    }
+    let names: Vec<&str> = skipped.iter().take(10).map(String::as_str).collect();
+    format!(
+        "\nSkipped {} entries (links, unsafe paths or unsupported compression): {}",
+        skipped.len(),
+        names.join(", ")
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+pub struct ArchiveTool;
+
+impl ArchiveTool {
+    async fn create(ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let rel = args
+            .get("path")
+            .and_then(Value::as_str)
+            .ok_or("missing 'path' (folder or file to archive)")?
+            .trim()
+            .trim_end_matches('/');
+        let source = resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        let root_name = Path::new(rel)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "workspace".to_string());
+        let output_rel = match args.get("output").and_then(Value::as_str) {
+            Some(o) => o.trim().to_string(),
+            None => Path::new(rel)
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(format!("{root_name}.zip"))
+                .to_string_lossy()
+                .into_owned(),
+        };
+        let (format, _) = Format::detect(&output_rel)
+            .ok_or("'output' must end in .zip, .tar, .tar.gz or .tgz")?;
+        let output = resolve_path(&output_rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        if tokio::fs::try_exists(&output).await.unwrap_or(false) {
+            return Err(format!("{output_rel} already exists"));
+        }
+
+        let (count, size, written) = tokio::task::spawn_blocking(move || {
+            let entries = collect(&source, &root_name)?;
+            if entries.is_empty() {
+                return Err("nothing to archive (no files)".to_string());
+            }
+            let archive = write_archive(format, &entries);
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(&output, &archive).map_err(|e| e.to_string())?;
+            let size: u64 = entries.iter().map(|e| e.data.len() as u64).sum();
+            Ok((entries.len(), size, archive.len() as u64))
+        })
+        .await
+        .map_err(|e| format!("archive task error: {e}"))??;
+        Ok(format!(
+            "Created {output_rel}: {count} files, {} ({} before compression).",
+            format_bytes(written),
+            format_bytes(size)
+        ))
+    }
+
+    /// The archive at `path` in `args`, read and parsed.
+    async fn open(ctx: &ToolCtx, args: &Value) -> Result<(String, Contents), String> {
+        let rel = args
+            .get("path")
+            .and_then(Value::as_str)
+            .ok_or("missing 'path' (the archive)")?
+            .trim()
+            .to_string();
+        let file_name = Path::new(&rel)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (format, _) =
+            Format::detect(&file_name).ok_or("archive reads .zip, .tar, .tar.gz and .tgz files")?;
+        let path = resolve_path(&rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        let meta = tokio::fs::metadata(&path)
+            .await
+            .map_err(|e| format!("{rel}: {e}"))?;
+        if meta.len() > MAX_TOTAL_BYTES {
+            return Err(format!(
+                "{rel} is {}; the limit is {}",
+                format_bytes(meta.len()),
+                format_bytes(MAX_TOTAL_BYTES)
+            ));
+        }
+        let bytes = tokio::fs::read(&path)
+            .await
+            .map_err(|e| format!("{rel}: {e}"))?;
+        let contents = tokio::task::spawn_blocking(move || read_archive(format, &bytes))
+            .await
+            .map_err(|e| format!("archive task error: {e}"))?
+            .map_err(|e| format!("{rel}: {e}"))?;
+        Ok((rel, contents))
+    }
+
+    async fn extract(ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let (rel, contents) = Self::open(ctx, args).await?;
+        if contents.files.is_empty() {
+            return Err(format!(
+                "{rel} has no files to extract{}",
+                skipped_note(&contents.skipped)
+            ));
+        }
+        // An archive of one folder unpacks as that folder; anything else gets a folder named
+        // after the archive.
+        let to_rel = match args.get("to").and_then(Value::as_str) {
+            Some(to) => to.trim().trim_end_matches('/').to_string(),
+            None if common_root(&contents.files).is_some() => DEFAULT_EXTRACT_DIR.to_string(),
+            None => {
+                let name = Path::new(&rel)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let stem = Format::detect(&name).map_or(name.as_str(), |(_, stem)| stem);
+                format!("{DEFAULT_EXTRACT_DIR}/{stem}")
+            }
+        };
+        let dest = resolve_path(&to_rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+
+        let Contents { files, skipped } = contents;
+        let (files, written) = tokio::task::spawn_blocking(move || {
+            extract_to(&dest, &files).map(|written| (files, written))
+        })
+        .await
+        .map_err(|e| format!("archive task error: {e}"))?
+        .map_err(|e| format!("{e}; nothing was extracted (pass another 'to')"))?;
+        Ok(format!(
+            "Extracted {} files ({}) to {to_rel}/:{}{}",
+            files.len(),
+            format_bytes(written),
+            listing(&files),
+            skipped_note(&skipped)
+        ))
+    }
+
+    async fn list(ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let (rel, contents) = Self::open(ctx, args).await?;
+        let size: u64 = contents.files.iter().map(|f| f.data.len() as u64).sum();
+        Ok(format!(
+            "{rel}: {} files, {} unpacked:{}{}",
+            contents.files.len(),
+            format_bytes(size),
+            listing(&contents.files),
+            skipped_note(&contents.skipped)
+        ))
+    }
+}
+
+impl Tool for ArchiveTool {
+    fn name(&self) -> &str {
+        "archive"
+    }
+
+    fn description(&self) -> &str {
+        "Create, list or extract .zip, .tar and .tar.gz archives in the workspace. create packs a \
+         folder or file (default <folder>.zip beside it), e.g. to send with send_file. extract \
+         unpacks into Inbox/ (or 'to') without overwriting anything; entries with unsafe paths \
+         and links are skipped."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["create", "list", "extract"] },
+                "path": { "type": "string", "description": "create: folder or file to pack; list/extract: the archive" },
+                "output": { "type": "string", "description": "create: archive path; the extension (.zip, .tar, .tar.gz, .tgz) picks the format" },
+                "to": { "type": "string", "description": "extract: destination folder (default Inbox, or Inbox/<archive name> when the archive is not one folder)" }
+            },
+            "required": ["action", "path"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let result = match args.get("action").and_then(Value::as_str).unwrap_or("") {
+                "create" => Self::create(ctx, args).await,
+                "list" => Self::list(ctx, args).await,
+                "extract" => Self::extract(ctx, args).await,
+                other => Err(format!(
+                    "unknown action '{other}' (use create, list or extract)"
+                )),
+            };
+            match result {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    fn entry(name: &str, data: &[u8]) -> Entry {
+        Entry {
+            name: name.to_string(),
+            data: data.to_vec(),
+            mtime: 1_767_225_600,
+        }
+    }
+
+    #[test]
+    fn crc_and_entry_paths() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            safe_entry_path("a/./b\\c.md"),
+            Some(PathBuf::from("a/b/c.md"))
+        );
+        assert_eq!(safe_entry_path("../evil"), None);
+        assert_eq!(safe_entry_path("a/../../evil"), None);
+        assert_eq!(safe_entry_path("/etc/passwd"), None);
+        assert_eq!(safe_entry_path("./"), None);
+        assert_eq!(Format::detect("x.TAR.GZ"), Some((Format::TarGz, "x")));
+        assert_eq!(Format::detect(".zip"), None);
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        let long = format!("{}note.md", "deep/".repeat(30));
+        let entries = vec![
+            entry("Notes/a.md", &b"hello ".repeat(100)),
+            entry("Notes/b.bin", &[0, 1, 2]),
+            entry(&long, b"x"),
+        ];
+        for format in [Format::Zip, Format::Tar, Format::TarGz] {
+            let contents = read_archive(format, &write_archive(format, &entries)).unwrap();
+            let names: Vec<&str> = contents.files.iter().map(|f| f.name.as_str()).collect();
+            assert_eq!(
+                names,
+                ["Notes/a.md", "Notes/b.bin", long.as_str()],
+                "{format:?}"
+            );
+            assert_eq!(contents.files[0].data, entries[0].data);
+            assert!(contents.skipped.is_empty());
+        }
+        // Deflate shrank the repetitive file.
+        assert!(write_zip(&entries[..1]).len() < 300);
+    }
+
+    #[test]
+    fn unsafe_entries_and_links_are_skipped() {
+        let zip = write_zip(&[
+            entry("../evil.sh", b"x"),
+            entry("/etc/cron.d/x", b"x"),
+            entry("ok.txt", b"fine"),
+        ]);
+        let contents = read_zip(&zip).unwrap();
+        assert_eq!(contents.files.len(), 1);
+        assert_eq!(contents.files[0].data, b"fine");
+        assert_eq!(contents.skipped, ["../evil.sh", "/etc/cron.d/x"]);
+
+        let mut tar = tar_header("link", 0, 0, b'2').to_vec();
+        tar.extend_from_slice(&tar_header("ok.txt", 4, 0, b'0'));
+        push_blocks(&mut tar, b"fine");
+        tar.extend_from_slice(&[0; 2 * BLOCK]);
+        let contents = read_tar(&tar).unwrap();
+        assert_eq!(contents.files[0].data, b"fine");
+        assert_eq!(contents.skipped, ["link"]);
+
+        assert!(
+            read_tar(&[1; BLOCK])
+                .unwrap_err()
+                .contains("not a tar archive")
+        );
+        assert_eq!(read_zip(b"PK nonsense").unwrap_err(), "not a zip archive");
+    }
+
+    #[test]
+    fn clashing_entries_are_refused_before_anything_is_written() {
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("Inbox");
+        let refused = |files: &[Entry]| {
+            let err = extract_to(&dest, files).unwrap_err();
+            assert!(!dest.exists(), "{err}");
+            err
+        };
+        assert_eq!(
+            refused(&[entry("a.txt", b"1"), entry("x", b"2"), entry("x/y", b"3")]),
+            "x is a file but x/y needs it as a folder"
+        );
+        assert_eq!(
+            refused(&[entry("x/y/z", b"1"), entry("x", b"2")]),
+            "x is a file but x/y/z needs it as a folder"
+        );
+        assert_eq!(
+            refused(&[entry("a.txt", b"1"), entry("./a.txt", b"2")]),
+            "./a.txt appears twice in the archive"
+        );
+        // A write that fails part-way takes the earlier files and folders with it.
+        let long = format!("b/{}", "n".repeat(300));
+        assert!(refused(&[entry("a/ok.txt", b"1"), entry(&long, b"2")]).starts_with(&long));
+
+        std::fs::create_dir(&dest).unwrap();
+        std::fs::write(dest.join("x"), "mine").unwrap();
+        let err = extract_to(&dest, &[entry("a.txt", b"1"), entry("x/y", b"2")]).unwrap_err();
+        assert_eq!(err, "x already exists and is not a folder");
+        assert!(!dest.join("a.txt").exists());
+    }
+
+    #[test]
+    fn crafted_offsets_are_errors() {
+        assert_eq!(
+            offset(usize::MAX - 1, 2).unwrap_err(),
+            "the archive is truncated"
+        );
+        assert!(slice(b"abc", usize::MAX, 4).is_err());
+        assert!(le32(b"abc", usize::MAX - 1).is_err());
+        // A central directory entry pointing its local header past the end of memory.
+        let mut zip = write_zip(&[entry("a.txt", b"x")]);
+        let central = (0..zip.len() - 4)
+            .find(|&i| le32(&zip, i) == Ok(ZIP_CENTRAL))
+            .unwrap();
+        zip[central + 42..central + 46].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(read_zip(&zip).unwrap_err(), "the archive is truncated");
+    }
+
+    #[tokio::test]
+    async fn create_then_extract_into_inbox() {
+        let dir = TempDir::new().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join("Projects/Garden/.git")).unwrap();
+        std::fs::write(ws.join("Projects/Garden/plan.md"), "# Plan\n").unwrap();
+        std::fs::create_dir(ws.join("Projects/Garden/photos")).unwrap();
+        std::fs::write(ws.join("Projects/Garden/photos/bed.jpg"), [0xff; 64]).unwrap();
+        std::fs::write(ws.join("Projects/Garden/.git/HEAD"), "ref").unwrap();
+
+        let args = json!({"action": "create", "path": "Projects/Garden/"});
+        let res = ArchiveTool.execute(&ctx(ws), &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm
+                .starts_with("Created Projects/Garden.zip: 2 files,")
+        );
+        let res = ArchiveTool.execute(&ctx(ws), &args).await;
+        assert_eq!(res.for_llm, "Projects/Garden.zip already exists");
+
+        let args = json!({"action": "extract", "path": "Projects/Garden.zip"});
+        let res = ArchiveTool.execute(&ctx(ws), &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Extracted 2 files (71 B) to Inbox/:\n\
+             - Garden/photos/bed.jpg (64 B)\n\
+             - Garden/plan.md (7 B)"
+        );
+        assert_eq!(
+            std::fs::read_to_string(ws.join("Inbox/Garden/plan.md")).unwrap(),
+            "# Plan\n"
+        );
+        let res = ArchiveTool.execute(&ctx(ws), &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Garden/photos/bed.jpg already exists; nothing was extracted (pass another 'to')"
+        );
+
+        let args = json!({"action": "create", "path": "Projects", "output": "../out.tgz"});
+        assert!(ArchiveTool.execute(&ctx(ws), &args).await.is_error);
+        let args = json!({"action": "create", "path": "Projects", "output": "out.rar"});
+        let res = ArchiveTool.execute(&ctx(ws), &args).await;
+        assert_eq!(
+            res.for_llm,
+            "'output' must end in .zip, .tar, .tar.gz or .tgz"
+        );
+    }
+
+    #[tokio::test]
+    async fn list_shows_files_and_skipped_entries() {
+        let dir = TempDir::new().unwrap();
+        let tar = write_tar(&[entry("a.txt", b"abc"), entry("../x", b"y")]);
+        std::fs::write(dir.path().join("upload.tar.gz"), gzip(&tar)).unwrap();
+        let args = json!({"action": "list", "path": "upload.tar.gz"});
+        let res = ArchiveTool.execute(&ctx(dir.path()), &args).await;
+        assert_eq!(
+            res.for_llm,
+            "upload.tar.gz: 1 files, 3 B unpacked:\n- a.txt (3 B)\n\
+             Skipped 1 entries (links, unsafe paths or unsupported compression): ../x"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! `backup` tool: back up, list and restore brain.db (see `memory::backup`).
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::memory::backup::{self, BackupInfo};
+use crate::memory::db::{BrainDb, DbError};
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+pub struct BackupTool {
+    db: Arc<BrainDb>,
+    /// Backups kept after `create` rotates.
+    keep: usize,
+}
+
+impl BackupTool {
+    pub fn new(db: Arc<BrainDb>, keep: usize) -> Self {
+        Self { db, keep }
+    }
+}
+
+impl Tool for BackupTool {
+    fn name(&self) -> &str {
+        "backup"
+    }
+
+    fn description(&self) -> &str {
+        "Back up the assistant's database (chat history, memories, usage). \
+         create: make a backup now (old ones are rotated out). \
+         list: show existing backups. \
+         restore: replace the database with a backup by name; the current state is saved \
+         first. Only restore when the user explicitly asks."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["create", "list", "restore"],
+                    "description": "create a backup, list backups, or restore one"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Backup file name from list, e.g. brain-2026-01-31.db (restore)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let db = Arc::clone(&self.db);
+        let workspace = ctx.workspace.clone();
+        let keep = self.keep;
+        let action = args
+            .get("action")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        let name = args
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .unwrap_or("")
+            .to_string();
+
+        Box::pin(async move {
+            if !matches!(action.as_str(), "create" | "list" | "restore") {
+                return ToolResult::error(format!(
+                    "unknown action '{action}' (use create, list or restore)"
+                ));
+            }
+            if action == "restore" && name.is_empty() {
+                return ToolResult::error("restore requires 'name' (from list)");
+            }
+            let result = tokio::task::spawn_blocking(move || match action.as_str() {
+                "create" => {
+                    let info = backup::create(&db, &workspace)?;
+                    let removed = backup::rotate(&workspace, keep)?;
+                    Ok(format!(
+                        "Backed up to {} ({}); {removed} old backup(s) removed.",
+                        info.name,
+                        backup::format_bytes(info.bytes)
+                    ))
+                }
+                "list" => {
+                    let backups = backup::list(&workspace)?;
+                    if backups.is_empty() {
+                        Ok("No backups yet.".to_string())
+                    } else {
+                        Ok(format_list(&backups))
+                    }
+                }
+                _ => {
+                    backup::restore(&db, &workspace, &name)?;
+                    Ok::<_, DbError>(format!("Restored the database from {name}."))
+                }
+            })
+            .await;
+            match result {
+                Ok(Ok(text)) => ToolResult::ok(text),
+                Ok(Err(e)) => ToolResult::error(format!("backup failed: {e}")),
+                Err(e) => ToolResult::error(format!("backup task error: {e}")),
+            }
+        })
+    }
+}
+
+/// One line per backup, newest first: name, size and time (UTC).
+fn format_list(backups: &[BackupInfo]) -> String {
+    backups
+        .iter()
+        .map(|b| {
+            let at: DateTime<Utc> = b.modified.into();
+            format!(
+                "{}  {}  {}",
+                b.name,
+                backup::format_bytes(b.bytes),
+                at.format("%Y-%m-%d %H:%M UTC")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn ctx(workspace: &std::path::Path) -> ToolCtx {
+        ToolCtx {
+            chat_id: Some(1),
+            channel: Some("telegram".into()),
+            ..ToolCtx::for_tests(workspace)
+        }
+    }
+
+    #[tokio::test]
+    async fn create_list_restore() {
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let tool = BackupTool::new(Arc::clone(&db), 3);
+        let ctx = ctx(tmp.path());
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"action": "list"}))
+            .await;
+        assert_eq!(res.for_llm, "No backups yet.");
+
+        db.save_memory("Prefers tea", "", "1").unwrap();
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"action": "create"}))
+            .await;
+        assert!(
+            res.for_llm.starts_with("Backed up to brain-"),
+            "{}",
+            res.for_llm
+        );
+        let name = backup::list(tmp.path()).unwrap()[0].name.clone();
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"action": "list"}))
+            .await;
+        assert!(res.for_llm.contains(&name), "{}", res.for_llm);
+
+        db.save_memory("Prefers coffee", "", "1").unwrap();
+        let res = tool
+            .execute(
+                &ctx,
+                &serde_json::json!({"action": "restore", "name": name}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(db.search_memories("coffee", 5).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments_are_errors() {
+        let tmp = TempDir::new().unwrap();
+        let tool = BackupTool::new(Arc::new(BrainDb::open(tmp.path()).unwrap()), 3);
+        for args in [
+            serde_json::json!({"action": "restore"}),
+            serde_json::json!({"action": "restore", "name": "brain-2000-01-01.db"}),
+            serde_json::json!({"action": "restore", "name": "../secrets.db"}),
+            serde_json::json!({"action": "shred"}),
+        ] {
+            assert!(
+                tool.execute(&ctx(tmp.path()), &args).await.is_error,
+                "{args}"
+            );
+        }
+    }
+}
@@ -0,0 +1,596 @@
+//! `bookmark` tool: "save this for later". A page's URL, title, a short summary and tags go
+//! into BrainDb (`bookmarks`, full-text indexed) and a vault note (`[tools.bookmark] note`,
+//! default `Bookmarks.md`) that is rewritten after every change. The summary and tags come
+//! from its own model (`[tools.bookmark] model`, default the chat model).
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::BookmarkConfig;
+use crate::llm::{LlmProvider, Message, Role};
+use crate::memory::db::{Bookmark, BrainDb, run_blocking};
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::web::WebFetchTool;
+
+const DEFAULT_NOTE: &str = "Bookmarks.md";
+/// Page text the model reads; the start of a page says what it is about.
+const MAX_PAGE_CHARS: usize = 12_000;
+const DEFAULT_RESULTS: u64 = 10;
+const MAX_RESULTS: u64 = 50;
+/// Bookmarks written to the vault note, newest first.
+const MAX_NOTE_ENTRIES: usize = 5_000;
+/// Tags already in use offered to the model, so it reuses them.
+const MAX_KNOWN_TAGS: usize = 100;
+
+const SYSTEM_PROMPT: &str = "You file web pages for later. From the page text give its real \
+    title (not the site name alone), a summary of two or three sentences saying what it is \
+    and why it could be useful, and one to five short lowercase topic tags. Prefer tags the \
+    user already has when they fit.";
+
+#[derive(Debug, Deserialize)]
+struct Described {
+    title: String,
+    summary: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+fn schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "title": { "type": "string" },
+            "summary": { "type": "string" },
+            "tags": { "type": "array", "items": { "type": "string" } }
+        },
+        "required": ["title", "summary", "tags"]
+    })
+}
+
+/// Local day a bookmark was saved.
+fn saved_day(b: &Bookmark, tz: Tz) -> Option<NaiveDate> {
+    DateTime::from_timestamp(b.created_at, 0).map(|d| d.with_timezone(&tz).date_naive())
+}
+
+/// "#3 Async Rust — https://… #rust #tokio", then the summary and note indented.
+fn describe(b: &Bookmark) -> String {
+    let mut out = format!("#{} {} — {}", b.id, b.title, b.url);
+    for tag in &b.tags {
+        out.push_str(&format!(" #{tag}"));
+    }
+    if !b.summary.is_empty() {
+        out.push_str(&format!("\n  {}", b.summary));
+    }
+    if !b.note.is_empty() {
+        out.push_str(&format!("\n  Note: {}", b.note));
+    }
+    out
+}
+
+/// The vault note: bookmarks newest first under a heading per month.
+fn render(bookmarks: &[Bookmark], tz: Tz) -> String {
+    let mut out = "# Bookmarks\n\n_Updated by iCrab; edits here are overwritten._\n".to_string();
+    if bookmarks.is_empty() {
+        out.push_str("\nNo bookmarks saved.\n");
+        return out;
+    }
+    let line = |s: &str| s.replace('\n', " ");
+    let mut month = String::new();
+    for b in bookmarks {
+        let day = saved_day(b, tz);
+        let heading = day.map_or_else(String::new, |d| d.format("%B %Y").to_string());
+        if heading != month {
+            out.push_str(&format!("\n## {heading}\n\n"));
+            month = heading;
+        }
+        let title = line(&b.title).replace(['[', ']'], "");
+        out.push_str(&format!("- [{title}]({})", b.url.replace(' ', "%20")));
+        for tag in &b.tags {
+            out.push_str(&format!(" #{tag}"));
+        }
+        if let Some(day) = day {
+            out.push_str(&format!(" ({day})"));
+        }
+        out.push('\n');
+        if !b.summary.is_empty() {
+            out.push_str(&format!("  {}\n", line(&b.summary)));
+        }
+        if !b.note.is_empty() {
+            out.push_str(&format!("  > {}\n", line(&b.note)));
+        }
+    }
+    out
+}
+
+/// Tags given as `["a", "b"]` or `"a, b"`.
+fn tag_list(v: Option<&Value>) -> Vec<String> {
+    match v {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(Value::String(s)) => s.split(',').map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+    .into_iter()
+    .map(|t| t.trim().to_string())
+    .filter(|t| !t.is_empty())
+    .collect()
+}
+
+fn message(role: Role, content: String) -> Message {
+    Message {
+        role,
+        content,
+        tool_call_id: None,
+        tool_calls: None,
+        images: Vec::new(),
+    }
+}
+
+pub struct BookmarkTool {
+    db: Arc<BrainDb>,
+    fetcher: WebFetchTool,
+    llm: Arc<dyn LlmProvider>,
+    model: String,
+    note: String,
+    timezone: Tz,
+}
+
+impl BookmarkTool {
+    /// `model` is used unless `[tools.bookmark] model` overrides it.
+    pub fn new(
+        db: Arc<BrainDb>,
+        fetcher: WebFetchTool,
+        llm: Arc<dyn LlmProvider>,
+        model: &str,
+        config: Option<&BookmarkConfig>,
+        timezone: Tz,
+    ) -> Self {
+        Self {
+            db,
+            fetcher,
+            llm,
+            model: config
+                .and_then(|c| c.model.clone())
+                .unwrap_or_else(|| model.to_string()),
+            note: config
+                .and_then(|c| c.note.as_deref())
+                .map(|n| n.trim_matches('/').to_string())
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| DEFAULT_NOTE.to_string()),
+            timezone,
+        }
+    }
+
+    /// Title, summary and tags of a page from its text.
+    async fn describe_page(&self, url: &str, text: &str) -> Result<Described, String> {
+        let known: BTreeSet<String> = run_blocking(&self.db, "bookmarks", |db| {
+            db.search_bookmarks("", None, MAX_NOTE_ENTRIES)
+        })
+        .await?
+        .into_iter()
+        .flat_map(|b| b.tags)
+        .take(MAX_KNOWN_TAGS)
+        .collect();
+        let mut prompt = format!("URL: {url}\n");
+        if !known.is_empty() {
+            let known: Vec<String> = known.into_iter().collect();
+            prompt.push_str(&format!("Tags already in use: {}\n", known.join(", ")));
+        }
+        let text: String = text.chars().take(MAX_PAGE_CHARS).collect();
+        prompt.push_str(&format!("\n{text}"));
+        let messages = [
+            message(Role::System, SYSTEM_PROMPT.to_string()),
+            message(Role::User, prompt),
+        ];
+        self.llm
+            .chat_structured::<Described>(&messages, &self.model, &schema())
+            .await
+            .map_err(|e| format!("summarizing failed: {e}"))
+    }
+
+    /// Rewrite the bookmarks note.
+    async fn refresh(&self, ctx: &ToolCtx) -> Result<(), String> {
+        let all = run_blocking(&self.db, "bookmarks", |db| {
+            db.search_bookmarks("", None, MAX_NOTE_ENTRIES)
+        })
+        .await?;
+        let path = resolve_path(&self.note, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("creating the folder of {} failed: {e}", self.note))?;
+        }
+        tokio::fs::write(&path, render(&all, self.timezone))
+            .await
+            .map_err(|e| format!("writing {} failed: {e}", self.note))
+    }
+
+    async fn save(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let url = args
+            .get("url")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .ok_or("save needs 'url'")?;
+        match reqwest::Url::parse(url) {
+            Ok(u) if matches!(u.scheme(), "http" | "https") => {}
+            _ => return Err(format!("'{url}' is not an http(s) URL")),
+        }
+        let str_arg = |key: &str| {
+            args.get(key)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        let mut tags = tag_list(args.get("tags"));
+
+        // A page that cannot be fetched or summarized is still saved, just without a summary.
+        let mut problem = None;
+        let described = match self.fetcher.fetch_text(url).await {
+            Ok(text) if text.trim().is_empty() => {
+                problem = Some("the page has no text".to_string());
+                None
+            }
+            Ok(text) => match self.describe_page(url, &text).await {
+                Ok(d) => Some(d),
+                Err(e) => {
+                    problem = Some(e);
+                    None
+                }
+            },
+            Err(e) => {
+                problem = Some(e);
+                None
+            }
+        };
+        let (title, summary) = match described {
+            Some(d) => {
+                tags.extend(d.tags);
+                (d.title.trim().to_string(), d.summary.trim().to_string())
+            }
+            None => (String::new(), String::new()),
+        };
+        let mut bookmark = Bookmark {
+            id: 0,
+            url: url.to_string(),
+            title: str_arg("title")
+                .or_else(|| Some(title).filter(|t| !t.is_empty()))
+                .unwrap_or_else(|| url.to_string()),
+            summary,
+            tags,
+            note: str_arg("note").unwrap_or_default(),
+            created_at: 0,
+        };
+        let b = bookmark.clone();
+        let (id, new) = run_blocking(&self.db, "bookmarks", move |db| db.save_bookmark(&b)).await?;
+        bookmark.id = id;
+        // Tags as stored (normalized, deduplicated).
+        let saved = run_blocking(&self.db, "bookmarks", move |db| {
+            db.search_bookmarks("", None, MAX_NOTE_ENTRIES)
+        })
+        .await?
+        .into_iter()
+        .find(|b| b.id == id);
+        if let Some(saved) = saved {
+            bookmark = saved;
+        }
+        self.refresh(ctx).await?;
+
+        let mut out = format!(
+            "{} {} (in {}).",
+            if new { "Saved" } else { "Updated" },
+            describe(&bookmark),
+            self.note
+        );
+        if let Some(problem) = problem {
+            out.push_str(&format!("\nNo summary: {problem}"));
+        }
+        Ok(out)
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let action = args.get("action").and_then(Value::as_str).unwrap_or("");
+        let tag = args
+            .get("tag")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+        let limit = args
+            .get("limit")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_RESULTS)
+            .clamp(1, MAX_RESULTS) as usize;
+
+        match action {
+            "save" => self.save(ctx, args).await,
+            "search" | "list" => {
+                let query = args
+                    .get("query")
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .unwrap_or("")
+                    .to_string();
+                if action == "search" && query.is_empty() && tag.is_none() {
+                    return Err("search needs 'query' or 'tag'".into());
+                }
+                let q = query.clone();
+                let t = tag.clone();
+                let found = run_blocking(&self.db, "bookmarks", move |db| {
+                    db.search_bookmarks(&q, t.as_deref(), limit)
+                })
+                .await?;
+                if found.is_empty() {
+                    return Ok(match (query.is_empty(), tag) {
+                        (true, None) => "No bookmarks saved yet.".to_string(),
+                        (true, Some(t)) => format!("No bookmarks tagged #{t}."),
+                        (false, _) => format!("No bookmarks match '{query}'."),
+                    });
+                }
+                let mut out = if query.is_empty() {
+                    "Latest bookmarks:".to_string()
+                } else {
+                    format!("Bookmarks matching '{query}':")
+                };
+                for b in &found {
+                    out.push_str(&format!("\n- {}", describe(b)));
+                }
+                Ok(out)
+            }
+            "remove" => {
+                let id = args
+                    .get("id")
+                    .and_then(Value::as_i64)
+                    .ok_or("remove needs 'id' (from search or list)")?;
+                let Some(removed) =
+                    run_blocking(&self.db, "bookmarks", move |db| db.remove_bookmark(id)).await?
+                else {
+                    return Err(format!("no bookmark #{id}"));
+                };
+                self.refresh(ctx).await?;
+                Ok(format!(
+                    "Removed #{} {} — {}.",
+                    removed.id, removed.title, removed.url
+                ))
+            }
+            "" => Err("missing 'action' (save, search, list or remove)".into()),
+            other => Err(format!(
+                "unknown action '{other}' (save, search, list or remove)"
+            )),
+        }
+    }
+}
+
+impl Tool for BookmarkTool {
+    fn name(&self) -> &str {
+        "bookmark"
+    }
+
+    fn description(&self) -> &str {
+        "Bookmarks (\"save this for later\"). save: store a URL with its title, a generated \
+         summary and tags (optionally the user's own tags and note). search: find saved pages \
+         by words or tag. list: the newest, optionally of one tag. remove: delete by id. All \
+         bookmarks are also listed in a vault note."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["save", "search", "list", "remove"] },
+                "url": { "type": "string", "description": "Page to save (save)" },
+                "title": { "type": "string", "description": "Title to use instead of the page's (save)" },
+                "tags": { "type": "array", "items": { "type": "string" }, "description": "Tags to add to the generated ones (save)" },
+                "note": { "type": "string", "description": "Why it was saved, in the user's words (save)" },
+                "query": { "type": "string", "description": "Words to look for (search)" },
+                "tag": { "type": "string", "description": "Only bookmarks with this tag (search, list)" },
+                "limit": { "type": "integer", "description": format!("Results (search, list; default {DEFAULT_RESULTS}, max {MAX_RESULTS})") },
+                "id": { "type": "integer", "description": "Bookmark id (remove)" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::Mutex;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::llm::{GenParams, LlmError, LlmResponse, ToolDef};
+    use crate::tools::web::web_client;
+
+    /// Describes every page the same way; records the user messages.
+    #[derive(Default)]
+    struct Fake {
+        seen: Mutex<Vec<String>>,
+    }
+
+    impl LlmProvider for Fake {
+        fn chat_with_params<'a>(
+            &'a self,
+            messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            let user = messages.last().unwrap().content.clone();
+            self.seen.lock().unwrap().push(user);
+            Box::pin(async move {
+                Ok(LlmResponse {
+                    content: r#"{"title": "Async Rust explained", "summary": "How executors poll futures.", "tags": ["Rust", "async"]}"#.to_string(),
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".to_string(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    fn bookmark(id: i64, title: &str, created_at: i64) -> Bookmark {
+        Bookmark {
+            id,
+            url: format!("https://example.com/{id}"),
+            title: title.to_string(),
+            summary: format!("About {title}."),
+            tags: vec!["reading".to_string()],
+            note: String::new(),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn note_groups_by_month_newest_first() {
+        let mut b = bookmark(2, "Tokio [docs]", 1_792_108_800); // 2026-10-16
+        b.note = "for the\nrewrite".to_string();
+        let note = render(
+            &[b, bookmark(1, "Serde", 1_788_220_800)], // 2026-09-01
+            chrono_tz::Europe::London,
+        );
+        assert!(note.starts_with("# Bookmarks\n"));
+        assert!(note.contains(
+            "\n## October 2026\n\n- [Tokio docs](https://example.com/2) #reading (2026-10-16)\n  About Tokio [docs].\n  > for the rewrite\n"
+        ));
+        assert!(note.contains("\n## September 2026\n\n- [Serde](https://example.com/1)"));
+        assert!(render(&[], chrono_tz::UTC).contains("No bookmarks saved."));
+    }
+
+    #[test]
+    fn tags_from_list_or_text() {
+        assert_eq!(
+            tag_list(Some(&serde_json::json!("rust, web ,"))),
+            vec!["rust", "web"]
+        );
+        assert_eq!(tag_list(Some(&serde_json::json!(["a"]))), vec!["a"]);
+        assert!(tag_list(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn save_search_and_remove() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/async"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "<html><title>Blog</title><body><p>Futures are polled.</p></body></html>",
+                "text/html",
+            ))
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(BrainDb::open(dir.path()).unwrap());
+        let fake = Arc::new(Fake::default());
+        let tool = BookmarkTool::new(
+            db,
+            WebFetchTool::new(web_client().unwrap(), 0),
+            fake.clone(),
+            "chat-model",
+            None,
+            chrono_tz::Europe::London,
+        );
+        let ctx = ctx(dir.path());
+        let url = format!("{}/async", server.uri());
+
+        let res = tool
+            .execute(
+                &ctx,
+                &serde_json::json!({"action": "save", "url": url, "tags": ["reading list"], "note": "for work"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm.starts_with(&format!(
+                "Saved #1 Async Rust explained — {url} #reading-list #rust #async\n"
+            )),
+            "{}",
+            res.for_llm
+        );
+        assert!(fake.seen.lock().unwrap()[0].contains("Futures are polled."));
+        let note = std::fs::read_to_string(dir.path().join("Bookmarks.md")).unwrap();
+        assert!(note.contains(&format!(
+            "- [Async Rust explained]({url}) #reading-list #rust #async"
+        )));
+
+        // An unreachable page is still saved, with the title given.
+        let missing = format!("{}/gone", server.uri());
+        let res = tool
+            .execute(
+                &ctx,
+                &serde_json::json!({"action": "save", "url": missing, "title": "Old post"}),
+            )
+            .await;
+        assert!(
+            res.for_llm.starts_with("Saved #2 Old post"),
+            "{}",
+            res.for_llm
+        );
+        assert!(res.for_llm.contains("No summary:"), "{}", res.for_llm);
+        // A page that could not be fetched is not sent to the model.
+        assert_eq!(fake.seen.lock().unwrap().len(), 1);
+
+        let res = tool
+            .execute(
+                &ctx,
+                &serde_json::json!({"action": "search", "query": "executors"}),
+            )
+            .await;
+        assert!(
+            res.for_llm.contains("#1 Async Rust explained"),
+            "{}",
+            res.for_llm
+        );
+        assert!(!res.for_llm.contains("Old post"));
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"action": "list", "tag": "async"}))
+            .await;
+        assert!(res.for_llm.contains("#1 "));
+        assert!(!res.for_llm.contains("#2 "));
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"action": "remove", "id": 1}))
+            .await;
+        assert!(res.for_llm.starts_with("Removed #1 Async Rust explained"));
+        let note = std::fs::read_to_string(dir.path().join("Bookmarks.md")).unwrap();
+        assert!(!note.contains("Async Rust"));
+        assert!(note.contains("[Old post]"));
+
+        let res = tool
+            .execute(
+                &ctx,
+                &serde_json::json!({"action": "save", "url": "notes.md"}),
+            )
+            .await;
+        assert!(res.is_error);
+    }
+}
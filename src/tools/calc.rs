@@ -0,0 +1,614 @@
+//! `calc` tool: deterministic arithmetic, percentages and date math, so sums of macros or
+//! expenses are computed rather than guessed by the model.
+//!
+//! Supported: `+ - * / ^` (also `×`, `÷`, `**`), `mod`, parentheses, `pi`, `e` and the
+//! functions `sqrt abs round floor ceil min max ln log sin cos tan`. Percentages behave as on
+//! a calculator: `200 + 15%` is 230, `15% of 80` is 12. Dates are `YYYY-MM-DD`, `today`,
+//! `tomorrow` or `yesterday`; `date ± 3 weeks` (or a plain number of days) gives a date,
+//! `date - date` a number of days, and `days|weeks|months until|since <date>` or
+//! `days between <date> and <date>` count the gap. Currency symbols and thousands separators
+//! in numbers like `£1,250.50` are ignored.
+
+use chrono::{Datelike, Months, NaiveDate};
+use chrono_tz::Tz;
+use serde_json::Value as Json;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(f64),
+    Date(NaiveDate),
+    Word(String),
+    Op(char),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Tok>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut toks = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() || matches!(c, '$' | '€' | '£' | '¥' | '₹') {
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) {
+            let start = i;
+            // A YYYY-MM-DD date.
+            let date: String = chars[i..chars.len().min(i + 10)].iter().collect();
+            if date.len() == 10
+                && let Ok(d) = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            {
+                toks.push(Tok::Date(d));
+                i += 10;
+                continue;
+            }
+            while i < chars.len() {
+                let d = chars[i];
+                let thousands = d == ','
+                    && chars.get(i + 1..i + 4).is_some_and(|g| {
+                        g.iter().all(char::is_ascii_digit)
+                            && !chars.get(i + 4).is_some_and(char::is_ascii_digit)
+                    });
+                let exponent = matches!(d, 'e' | 'E')
+                    && chars
+                        .get(i + 1)
+                        .is_some_and(|n| n.is_ascii_digit() || *n == '-')
+                    && i > start;
+                if d.is_ascii_digit() || d == '.' || thousands {
+                    i += 1;
+                } else if exponent {
+                    i += 2;
+                } else {
+                    break;
+                }
+            }
+            let text: String = chars[start..i].iter().filter(|c| **c != ',').collect();
+            let n = text
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{text}'"))?;
+            toks.push(Tok::Num(n));
+            continue;
+        }
+        if c.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            toks.push(Tok::Word(word.to_lowercase()));
+            continue;
+        }
+        let op = match c {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                i += 1;
+                '^'
+            }
+            '×' => '*',
+            '÷' => '/',
+            '+' | '-' | '*' | '/' | '^' | '%' | '(' | ')' | ',' => c,
+            other => return Err(format!("unexpected character '{other}'")),
+        };
+        toks.push(Tok::Op(op));
+        i += 1;
+    }
+    Ok(toks)
+}
+
+/// A calculation result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Number(f64),
+    /// `15%`, kept as the fraction 0.15 until it meets another operand.
+    Percent(f64),
+    Date(NaiveDate),
+    Span {
+        days: i64,
+        months: i32,
+    },
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "a number",
+            Value::Percent(_) => "a percentage",
+            Value::Date(_) => "a date",
+            Value::Span { .. } => "a duration",
+        }
+    }
+
+    fn number(&self) -> Result<f64, String> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            Value::Percent(p) => Ok(*p),
+            other => Err(format!("expected a number, got {}", other.kind())),
+        }
+    }
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        return format!("{}", n as i64);
+    }
+    let s = format!("{n:.10}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", format_number(*n)),
+            Value::Percent(p) => write!(f, "{}%", format_number(p * 100.0)),
+            Value::Date(d) => write!(f, "{} ({})", d.format("%Y-%m-%d"), d.format("%A")),
+            Value::Span { days, months } => match (months, days) {
+                (0, d) => write!(f, "{d} day(s)"),
+                (m, 0) => write!(f, "{m} month(s)"),
+                (m, d) => write!(f, "{m} month(s) {d} day(s)"),
+            },
+        }
+    }
+}
+
+fn shift(date: NaiveDate, days: i64, months: i32) -> Result<Value, String> {
+    let date = if months >= 0 {
+        date.checked_add_months(Months::new(months as u32))
+    } else {
+        date.checked_sub_months(Months::new(months.unsigned_abs()))
+    };
+    date.and_then(|d| d.checked_add_signed(chrono::Duration::days(days)))
+        .map(Value::Date)
+        .ok_or_else(|| "date out of range".to_string())
+}
+
+/// Whole months from `a` to `b` (negative when `b` is earlier).
+fn months_between(a: NaiveDate, b: NaiveDate) -> i64 {
+    let (early, late, sign) = if a <= b { (a, b, 1) } else { (b, a, -1) };
+    let mut months =
+        (late.year() - early.year()) as i64 * 12 + late.month() as i64 - early.month() as i64;
+    if late.day() < early.day() {
+        months -= 1;
+    }
+    sign * months
+}
+
+fn add(lhs: Value, rhs: Value, sign: f64) -> Result<Value, String> {
+    use Value::*;
+    let s = sign as i64;
+    Ok(match (lhs, rhs) {
+        (Number(a), Number(b)) => Number(a + sign * b),
+        (Number(a), Percent(p)) => Number(a * (1.0 + sign * p)),
+        (Percent(a), Percent(b)) => Percent(a + sign * b),
+        (Percent(a), Number(b)) => Number(a + sign * b),
+        (Date(d), Span { days, months }) => shift(d, s * days, s as i32 * months)?,
+        (Span { days, months }, Date(d)) if sign > 0.0 => shift(d, days, months)?,
+        (Date(d), Number(n)) if n.fract() == 0.0 => shift(d, s * n as i64, 0)?,
+        (Date(a), Date(b)) if sign < 0.0 => Number((a - b).num_days() as f64),
+        (
+            Span {
+                days: d1,
+                months: m1,
+            },
+            Span {
+                days: d2,
+                months: m2,
+            },
+        ) => Span {
+            days: d1 + s * d2,
+            months: m1 + s as i32 * m2,
+        },
+        (a, b) => {
+            let op = if sign > 0.0 { "add" } else { "subtract" };
+            return Err(format!("cannot {op} {} and {}", a.kind(), b.kind()));
+        }
+    })
+}
+
+fn multiply(lhs: Value, rhs: Value) -> Result<Value, String> {
+    use Value::*;
+    Ok(match (lhs, rhs) {
+        (Percent(a), Percent(b)) => Percent(a * b),
+        (Span { days, months }, n) | (n, Span { days, months })
+            if matches!(n, Number(_) | Percent(_)) =>
+        {
+            let n = n.number()?;
+            Span {
+                days: (days as f64 * n).round() as i64,
+                months: (months as f64 * n).round() as i32,
+            }
+        }
+        (a, b) => Number(a.number()? * b.number()?),
+    })
+}
+
+fn divide(lhs: Value, rhs: Value) -> Result<Value, String> {
+    use Value::*;
+    if let (Span { days: a, months: 0 }, Span { days: b, months: 0 }) = (lhs, rhs) {
+        return if b == 0 {
+            Err("division by zero".to_string())
+        } else {
+            Ok(Number(a as f64 / b as f64))
+        };
+    }
+    let divisor = rhs.number()?;
+    if divisor == 0.0 {
+        return Err("division by zero".to_string());
+    }
+    Ok(match lhs {
+        Span { days, months } => Span {
+            days: (days as f64 / divisor).round() as i64,
+            months: (months as f64 / divisor).round() as i32,
+        },
+        other => Number(other.number()? / divisor),
+    })
+}
+
+fn call(name: &str, args: &[Value]) -> Result<Value, String> {
+    let nums: Vec<f64> = args.iter().map(Value::number).collect::<Result<_, _>>()?;
+    let one = || match nums.as_slice() {
+        [x] => Ok(*x),
+        _ => Err(format!("{name}() takes one argument")),
+    };
+    let n = match name {
+        "sqrt" => {
+            let x = one()?;
+            if x < 0.0 {
+                return Err("sqrt of a negative number".to_string());
+            }
+            x.sqrt()
+        }
+        "abs" => one()?.abs(),
+        "floor" => one()?.floor(),
+        "ceil" => one()?.ceil(),
+        "ln" => one()?.ln(),
+        "log" => one()?.log10(),
+        "sin" => one()?.sin(),
+        "cos" => one()?.cos(),
+        "tan" => one()?.tan(),
+        "round" => match nums.as_slice() {
+            [x] => x.round(),
+            [x, digits] => {
+                let factor = 10f64.powi(*digits as i32);
+                (x * factor).round() / factor
+            }
+            _ => return Err("round() takes a number and optional digits".to_string()),
+        },
+        "min" | "max" if !nums.is_empty() => {
+            let fold = if name == "min" { f64::min } else { f64::max };
+            nums.iter().copied().fold(nums[0], fold)
+        }
+        _ => return Err(format!("unknown function '{name}'")),
+    };
+    if n.is_finite() {
+        Ok(Value::Number(n))
+    } else {
+        Err(format!("{name}() is undefined here"))
+    }
+}
+
+const UNITS: &[(&str, i64, i32)] = &[
+    ("day", 1, 0),
+    ("days", 1, 0),
+    ("d", 1, 0),
+    ("week", 7, 0),
+    ("weeks", 7, 0),
+    ("w", 7, 0),
+    ("month", 0, 1),
+    ("months", 0, 1),
+    ("year", 0, 12),
+    ("years", 0, 12),
+];
+
+struct Parser {
+    toks: Vec<Tok>,
+    pos: usize,
+    today: NaiveDate,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.toks.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Tok> {
+        let t = self.toks.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn eat_op(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Tok::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Tok::Word(w)) if w == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<Value, String> {
+        let mut lhs = self.term()?;
+        loop {
+            if self.eat_op('+') {
+                lhs = add(lhs, self.term()?, 1.0)?;
+            } else if self.eat_op('-') {
+                lhs = add(lhs, self.term()?, -1.0)?;
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Value, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            if self.eat_op('*') || self.eat_word("of") || self.eat_word("x") {
+                lhs = multiply(lhs, self.unary()?)?;
+            } else if self.eat_op('/') {
+                lhs = divide(lhs, self.unary()?)?;
+            } else if self.eat_word("mod") {
+                let (a, b) = (lhs.number()?, self.unary()?.number()?);
+                if b == 0.0 {
+                    return Err("division by zero".to_string());
+                }
+                lhs = Value::Number(a.rem_euclid(b));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Value, String> {
+        if self.eat_op('-') {
+            return multiply(self.unary()?, Value::Number(-1.0));
+        }
+        if self.eat_op('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Value, String> {
+        let base = self.postfix()?;
+        if self.eat_op('^') {
+            let exp = self.unary()?.number()?;
+            let n = base.number()?.powf(exp);
+            return if n.is_finite() {
+                Ok(Value::Number(n))
+            } else {
+                Err("result is not a finite number".to_string())
+            };
+        }
+        Ok(base)
+    }
+
+    fn postfix(&mut self) -> Result<Value, String> {
+        let mut value = self.primary()?;
+        while self.eat_op('%') {
+            value = Value::Percent(value.number()? / 100.0);
+        }
+        if let Some(Tok::Word(w)) = self.peek()
+            && let Some(&(unit, days, months)) = UNITS.iter().find(|(u, _, _)| u == w)
+            && let Value::Number(n) = value
+        {
+            self.pos += 1;
+            if n.fract() != 0.0 {
+                return Err(format!("use whole numbers of {unit}"));
+            }
+            value = Value::Span {
+                days: n as i64 * days,
+                months: n as i32 * months,
+            };
+        }
+        Ok(value)
+    }
+
+    /// `days until X`, `weeks since X`, `months between X and Y`.
+    fn count(&mut self, unit: &str) -> Result<Value, String> {
+        let (from, to) = if self.eat_word("until") || self.eat_word("till") {
+            (self.today, self.date_operand()?)
+        } else if self.eat_word("since") {
+            (self.date_operand()?, self.today)
+        } else if self.eat_word("between") || self.eat_word("from") {
+            let a = self.date_operand()?;
+            if !(self.eat_word("and") || self.eat_word("to")) {
+                return Err("expected 'and' after the first date".to_string());
+            }
+            (a, self.date_operand()?)
+        } else {
+            return Err(format!(
+                "expected 'until', 'since' or 'between' after '{unit}'"
+            ));
+        };
+        let days = (to - from).num_days() as f64;
+        Ok(Value::Number(match unit {
+            "days" => days,
+            "weeks" => (days / 7.0 * 100.0).round() / 100.0,
+            "months" => months_between(from, to) as f64,
+            _ => (months_between(from, to) / 12) as f64,
+        }))
+    }
+
+    fn date_operand(&mut self) -> Result<NaiveDate, String> {
+        match self.expr()? {
+            Value::Date(d) => Ok(d),
+            other => Err(format!("expected a date, got {}", other.kind())),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Tok::Num(n)) => Ok(Value::Number(n)),
+            Some(Tok::Date(d)) => Ok(Value::Date(d)),
+            Some(Tok::Op('(')) => {
+                let v = self.expr()?;
+                if !self.eat_op(')') {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(v)
+            }
+            Some(Tok::Word(w)) => match w.as_str() {
+                "today" | "now" => Ok(Value::Date(self.today)),
+                "tomorrow" => shift(self.today, 1, 0),
+                "yesterday" => shift(self.today, -1, 0),
+                "pi" => Ok(Value::Number(std::f64::consts::PI)),
+                "e" => Ok(Value::Number(std::f64::consts::E)),
+                "days" | "weeks" | "months" | "years" => self.count(&w),
+                name if self.eat_op('(') => {
+                    let mut args = Vec::new();
+                    if !self.eat_op(')') {
+                        loop {
+                            args.push(self.expr()?);
+                            if self.eat_op(')') {
+                                break;
+                            }
+                            if !self.eat_op(',') {
+                                return Err(format!("expected ',' or ')' in {name}()"));
+                            }
+                        }
+                    }
+                    call(name, &args)
+                }
+                other => Err(format!("unknown word '{other}'")),
+            },
+            Some(Tok::Op(c)) => Err(format!("unexpected '{c}'")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Evaluate `expression`, with `today` for relative dates.
+pub fn evaluate(expression: &str, today: NaiveDate) -> Result<Value, String> {
+    let toks = tokenize(expression)?;
+    if toks.is_empty() {
+        return Err("empty expression".to_string());
+    }
+    let mut parser = Parser {
+        toks,
+        pos: 0,
+        today,
+    };
+    let value = parser.expr()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(t) => Err(format!("unexpected {t:?} after the expression")),
+    }
+}
+
+pub struct CalcTool {
+    timezone: Tz,
+}
+
+impl CalcTool {
+    pub fn new(timezone: Tz) -> Self {
+        Self { timezone }
+    }
+}
+
+impl Tool for CalcTool {
+    fn name(&self) -> &str {
+        "calc"
+    }
+
+    fn description(&self) -> &str {
+        "Evaluate arithmetic exactly instead of doing it yourself: + - * / ^ mod, parentheses, \
+         sqrt/round/min/max etc., percentages (\"200 + 15%\", \"15% of 80\") and date math \
+         (\"today + 3 weeks\", \"2026-06-01 - 2026-01-15\", \"days until 2026-06-01\", \"days \
+         between 2026-01-01 and 2026-03-01\"). Use for totals of macros, expenses and dates."
+    }
+
+    fn parameters(&self) -> Json {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "expression": { "type": "string", "description": "Expression to evaluate" }
+            },
+            "required": ["expression"]
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Json) -> BoxFuture<'a, ToolResult> {
+        let expression = args
+            .get("expression")
+            .and_then(Json::as_str)
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let today = chrono::Utc::now()
+            .with_timezone(&self.timezone)
+            .date_naive();
+        Box::pin(async move {
+            match evaluate(&expression, today) {
+                Ok(value) => ToolResult::ok(format!("{expression} = {value}")),
+                Err(e) => ToolResult::error(format!("cannot evaluate '{expression}': {e}")),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calc(expr: &str) -> String {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        match evaluate(expr, today) {
+            Ok(v) => v.to_string(),
+            Err(e) => format!("error: {e}"),
+        }
+    }
+
+    #[test]
+    fn arithmetic_and_percentages() {
+        assert_eq!(calc("2 + 3 * 4"), "14");
+        assert_eq!(calc("(2 + 3) * 4"), "20");
+        assert_eq!(calc("-2^2 + 2**3"), "4");
+        assert_eq!(calc("0.1 + 0.2"), "0.3");
+        assert_eq!(calc("7 / 2"), "3.5");
+        assert_eq!(calc("17 mod 5"), "2");
+        assert_eq!(calc("£1,250.50 + £49.50"), "1300");
+        assert_eq!(calc("3 × 154 + 2 × 31 ÷ 2"), "493");
+        assert_eq!(calc("round(sqrt(2), 3)"), "1.414");
+        assert_eq!(calc("max(3, 9, 4) - min(3, 9, 4)"), "6");
+        assert_eq!(calc("200 + 15%"), "230");
+        assert_eq!(calc("80 - 25%"), "60");
+        assert_eq!(calc("15% of 80"), "12");
+        assert_eq!(calc("12.5%"), "12.5%");
+        assert_eq!(calc("1 / 0"), "error: division by zero");
+        assert_eq!(calc("2 +"), "error: unexpected end of expression");
+        assert_eq!(calc("foo(1)"), "error: unknown function 'foo'");
+    }
+
+    #[test]
+    fn date_math() {
+        assert_eq!(calc("days until 2026-12-25"), "70");
+        assert_eq!(calc("days since 2026-10-01"), "15");
+        assert_eq!(calc("weeks until 2026-12-25"), "10");
+        assert_eq!(calc("months between 2026-01-31 and 2026-10-16"), "8");
+        assert_eq!(calc("2026-06-01 - 2026-01-15"), "137");
+        assert_eq!(calc("today + 3 weeks"), "2026-11-06 (Friday)");
+        assert_eq!(calc("2026-01-31 + 1 month"), "2026-02-28 (Saturday)");
+        assert_eq!(calc("tomorrow - 10"), "2026-10-07 (Wednesday)");
+        assert_eq!(calc("2 weeks + 3 days"), "17 day(s)");
+        assert_eq!(calc("today + today"), "error: cannot add a date and a date");
+    }
+}
@@ -0,0 +1,1147 @@
+//! `calendar` tool: events from the ICS feeds and CalDAV calendars in
+//! `[tools.calendar.calendars]`, recurring events expanded, for "what's on tomorrow".
+//! [`Calendars::briefing`] also hands heartbeat and cron runs the upcoming events.
+//!
+//! The ICS support covers what calendar apps export: UTC, TZID, floating and all-day times,
+//! DURATION, RRULE (DAILY/WEEKLY/MONTHLY/YEARLY with INTERVAL, COUNT, UNTIL, BYDAY,
+//! BYMONTHDAY, BYMONTH), EXDATE, RECURRENCE-ID overrides and cancelled events.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use regex_lite::Regex;
+use reqwest::{Client, Method};
+use serde_json::Value;
+
+use crate::config::{CalendarConfig, CalendarSource};
+use crate::tools::context::ToolCtx;
+use crate::tools::feeds::decode_entities;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::web::validate_fetch_url;
+
+/// Hours of upcoming events in heartbeat and cron messages when `briefing-hours` is unset.
+pub const DEFAULT_BRIEFING_HOURS: u32 = 24;
+const MAX_DAYS: i64 = 31;
+/// Upper bound on recurrence periods walked per event (a daily event for ~27 years).
+const MAX_PERIODS: i64 = 10_000;
+
+/// A DTSTART/DTEND/EXDATE/RECURRENCE-ID value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stamp {
+    /// All-day (`VALUE=DATE`).
+    Date(NaiveDate),
+    /// Wall-clock time in a zone (UTC for `…Z`, the config zone for floating times).
+    Local(NaiveDateTime, Tz),
+}
+
+impl Stamp {
+    fn naive(&self) -> NaiveDateTime {
+        match self {
+            Stamp::Date(d) => d.and_time(NaiveTime::MIN),
+            Stamp::Local(t, _) => *t,
+        }
+    }
+
+    /// The same kind of stamp at another wall-clock time.
+    fn with_naive(&self, t: NaiveDateTime) -> Stamp {
+        match self {
+            Stamp::Date(_) => Stamp::Date(t.date()),
+            Stamp::Local(_, zone) => Stamp::Local(t, *zone),
+        }
+    }
+
+    /// The instant; dates are local midnight in `tz`.
+    fn instant(&self, tz: Tz) -> DateTime<Utc> {
+        match self {
+            Stamp::Date(d) => local_instant(d.and_time(NaiveTime::MIN), tz),
+            Stamp::Local(t, zone) => local_instant(*t, *zone),
+        }
+    }
+}
+
+fn local_instant(naive: NaiveDateTime, tz: Tz) -> DateTime<Utc> {
+    // A time skipped by a DST change moves forward an hour.
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(naive + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&naive))
+}
+
+#[derive(Debug, Clone, Default)]
+struct VEvent {
+    uid: String,
+    summary: String,
+    location: Option<String>,
+    start: Option<Stamp>,
+    end: Option<Stamp>,
+    duration: Option<Duration>,
+    rrule: Option<String>,
+    exdates: Vec<Stamp>,
+    recurrence_id: Option<Stamp>,
+    cancelled: bool,
+}
+
+/// ICS text value: `\n`, `\,`, `\;` and `\\` escapes resolved.
+fn unescape(v: &str) -> String {
+    let mut out = String::with_capacity(v.len());
+    let mut chars = v.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Property parameters as (NAME, value).
+type Params = Vec<(String, String)>;
+
+/// Content line as (NAME, params, value).
+fn split_line(line: &str) -> Option<(String, Params, &str)> {
+    let mut in_quotes = false;
+    let (colon, _) = line.char_indices().find(|(_, c)| {
+        if *c == '"' {
+            in_quotes = !in_quotes;
+        }
+        *c == ':' && !in_quotes
+    })?;
+    let mut head = line[..colon].split(';');
+    let name = head.next()?.trim().to_ascii_uppercase();
+    let params = head
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| {
+            (
+                k.trim().to_ascii_uppercase(),
+                v.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    Some((name, params, &line[colon + 1..]))
+}
+
+fn parse_stamp(value: &str, params: &[(String, String)], tz: Tz) -> Option<Stamp> {
+    let value = value.trim();
+    let is_date = value.len() == 8
+        || params
+            .iter()
+            .any(|(k, v)| k == "VALUE" && v.eq_ignore_ascii_case("DATE"));
+    if is_date {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(Stamp::Date);
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|t| Stamp::Local(t, Tz::UTC));
+    }
+    // Unknown (e.g. Windows) zone names fall back to the configured zone.
+    let zone = params
+        .iter()
+        .find(|(k, _)| k == "TZID")
+        .and_then(|(_, v)| v.trim_start_matches('/').parse().ok())
+        .unwrap_or(tz);
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|t| Stamp::Local(t, zone))
+}
+
+/// `P1D`, `PT1H30M`, `P2W` (sign ignored).
+fn parse_duration(v: &str) -> Option<Duration> {
+    let rest = v.trim().trim_start_matches(['+', '-']).strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut num = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => num.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let n: i64 = num.parse().ok()?;
+                num.clear();
+                total += match c {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    _ => Duration::seconds(n),
+                };
+            }
+            _ => return None,
+        }
+    }
+    Some(total)
+}
+
+/// VEVENTs of an iCalendar document (several concatenated documents are fine).
+fn parse_ics(text: &str, tz: Tz) -> Vec<VEvent> {
+    // Unfold: a line starting with a space or tab continues the previous one.
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        if let Some(cont) = raw.strip_prefix([' ', '\t'])
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(cont);
+            continue;
+        }
+        lines.push(raw.to_string());
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<VEvent> = None;
+    // Components nested in the event (VALARM); their properties are not the event's.
+    let mut nested = 0usize;
+    for line in &lines {
+        let Some((name, params, value)) = split_line(line) else {
+            continue;
+        };
+        let component = value.trim().to_ascii_uppercase();
+        match name.as_str() {
+            "BEGIN" if component == "VEVENT" => {
+                current = Some(VEvent::default());
+                nested = 0;
+                continue;
+            }
+            "END" if component == "VEVENT" => {
+                if let Some(ev) = current.take().filter(|e| e.start.is_some()) {
+                    events.push(ev);
+                }
+                continue;
+            }
+            "BEGIN" if current.is_some() => {
+                nested += 1;
+                continue;
+            }
+            "END" if current.is_some() => {
+                nested = nested.saturating_sub(1);
+                continue;
+            }
+            _ => {}
+        }
+        let Some(ev) = current.as_mut().filter(|_| nested == 0) else {
+            continue;
+        };
+        match name.as_str() {
+            "UID" => ev.uid = value.trim().to_string(),
+            "SUMMARY" => ev.summary = unescape(value.trim()),
+            "LOCATION" => ev.location = Some(unescape(value.trim())).filter(|l| !l.is_empty()),
+            "DTSTART" => ev.start = parse_stamp(value, &params, tz),
+            "DTEND" => ev.end = parse_stamp(value, &params, tz),
+            "DURATION" => ev.duration = parse_duration(value),
+            "RRULE" => ev.rrule = Some(value.trim().to_string()),
+            "EXDATE" => ev
+                .exdates
+                .extend(value.split(',').filter_map(|v| parse_stamp(v, &params, tz))),
+            "RECURRENCE-ID" => ev.recurrence_id = parse_stamp(value, &params, tz),
+            "STATUS" => ev.cancelled = value.trim().eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+    events
+}
+
+#[derive(Debug, Default)]
+struct Rule {
+    freq: String,
+    interval: i64,
+    count: Option<u32>,
+    until: Option<DateTime<Utc>>,
+    /// (ordinal, weekday); ordinal 0 means every such weekday.
+    by_day: Vec<(i32, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+}
+
+fn weekday(code: &str) -> Option<Weekday> {
+    Some(match code.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn parse_rule(s: &str, tz: Tz) -> Option<Rule> {
+    let mut rule = Rule {
+        interval: 1,
+        ..Default::default()
+    };
+    for part in s.split(';').filter(|p| !p.is_empty()) {
+        let (key, value) = part.split_once('=')?;
+        match key.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => rule.freq = value.trim().to_ascii_uppercase(),
+            "INTERVAL" => rule.interval = value.trim().parse::<i64>().ok()?.max(1),
+            "COUNT" => rule.count = value.trim().parse().ok(),
+            "UNTIL" => {
+                rule.until = match parse_stamp(value, &[], tz)? {
+                    // A date UNTIL includes that whole day.
+                    Stamp::Date(d) => {
+                        Some(Stamp::Date(d.succ_opt()?).instant(tz) - Duration::seconds(1))
+                    }
+                    stamp => Some(stamp.instant(tz)),
+                }
+            }
+            "BYDAY" => {
+                for day in value.split(',').map(str::trim) {
+                    let (ord, code) = day.split_at(day.len().checked_sub(2)?);
+                    let ord = if ord.is_empty() { 0 } else { ord.parse().ok()? };
+                    rule.by_day.push((ord, weekday(code)?));
+                }
+            }
+            "BYMONTHDAY" => {
+                rule.by_month_day = value
+                    .split(',')
+                    .filter_map(|d| d.trim().parse().ok())
+                    .collect()
+            }
+            "BYMONTH" => {
+                rule.by_month = value
+                    .split(',')
+                    .filter_map(|m| m.trim().parse().ok())
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+    matches!(
+        rule.freq.as_str(),
+        "DAILY" | "WEEKLY" | "MONTHLY" | "YEARLY"
+    )
+    .then_some(rule)
+}
+
+fn days_in_month(y: i32, m: u32) -> u32 {
+    let next = if m == 12 {
+        NaiveDate::from_ymd_opt(y + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(y, m + 1, 1)
+    };
+    next.and_then(|d| d.pred_opt()).map_or(28, |d| d.day())
+}
+
+/// Days of month `m` the rule selects, sorted; `default_day` without BYDAY/BYMONTHDAY.
+fn month_days(rule: &Rule, y: i32, m: u32, default_day: u32) -> Vec<NaiveDate> {
+    let last = days_in_month(y, m) as i32;
+    let date = |d: i32| {
+        u32::try_from(d)
+            .ok()
+            .and_then(|d| NaiveDate::from_ymd_opt(y, m, d))
+    };
+    let mut days: Vec<NaiveDate> = if !rule.by_month_day.is_empty() {
+        rule.by_month_day
+            .iter()
+            .filter_map(|&d| date(if d > 0 { d } else { last + d + 1 }))
+            .filter(|d| {
+                rule.by_day.is_empty() || rule.by_day.iter().any(|(_, w)| *w == d.weekday())
+            })
+            .collect()
+    } else if !rule.by_day.is_empty() {
+        let mut out = Vec::new();
+        for &(ord, wd) in &rule.by_day {
+            let matching: Vec<NaiveDate> = (1..=last)
+                .filter_map(date)
+                .filter(|d| d.weekday() == wd)
+                .collect();
+            match ord {
+                0 => out.extend(matching),
+                n if n > 0 => out.extend(matching.get(n as usize - 1)),
+                n => out.extend(
+                    matching
+                        .len()
+                        .checked_sub(n.unsigned_abs() as usize)
+                        .and_then(|i| matching.get(i)),
+                ),
+            }
+        }
+        out
+    } else {
+        date(default_day as i32).into_iter().collect()
+    };
+    days.sort();
+    days.dedup();
+    days
+}
+
+/// Wall-clock starts of the occurrences of `rule` from `start` up to `window_end`.
+/// `instant` converts a wall-clock start to its instant (for UNTIL and the window).
+fn recurrences(
+    start: NaiveDateTime,
+    rule: &Rule,
+    instant: impl Fn(NaiveDateTime) -> DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<NaiveDateTime> {
+    let d0 = start.date();
+    let mut out = Vec::new();
+    let mut emitted = 0u32;
+    for k in 0..MAX_PERIODS {
+        let step = k * rule.interval;
+        let (period_start, candidates): (NaiveDate, Vec<NaiveDate>) = match rule.freq.as_str() {
+            "DAILY" => {
+                let d = d0 + Duration::days(step);
+                let keep = (rule.by_day.is_empty()
+                    || rule.by_day.iter().any(|(_, w)| *w == d.weekday()))
+                    && (rule.by_month_day.is_empty()
+                        || rule.by_month_day.contains(&(d.day() as i32)));
+                (d, if keep { vec![d] } else { Vec::new() })
+            }
+            "WEEKLY" => {
+                let monday = d0 - Duration::days(d0.weekday().num_days_from_monday() as i64)
+                    + Duration::weeks(step);
+                let mut days: Vec<NaiveDate> = if rule.by_day.is_empty() {
+                    vec![monday + Duration::days(d0.weekday().num_days_from_monday() as i64)]
+                } else {
+                    rule.by_day
+                        .iter()
+                        .map(|(_, w)| monday + Duration::days(w.num_days_from_monday() as i64))
+                        .collect()
+                };
+                days.sort();
+                days.dedup();
+                (monday, days)
+            }
+            "MONTHLY" => {
+                let months = d0.year() as i64 * 12 + d0.month0() as i64 + step;
+                let (y, m) = ((months / 12) as i32, (months % 12) as u32 + 1);
+                let Some(first) = NaiveDate::from_ymd_opt(y, m, 1) else {
+                    break;
+                };
+                (first, month_days(rule, y, m, d0.day()))
+            }
+            _ => {
+                let y = d0.year() + step as i32;
+                let Some(first) = NaiveDate::from_ymd_opt(y, 1, 1) else {
+                    break;
+                };
+                let months = if rule.by_month.is_empty() {
+                    vec![d0.month()]
+                } else {
+                    rule.by_month.clone()
+                };
+                let days = months
+                    .into_iter()
+                    .flat_map(|m| {
+                        if rule.by_day.is_empty() && rule.by_month_day.is_empty() {
+                            NaiveDate::from_ymd_opt(y, m, d0.day())
+                                .into_iter()
+                                .collect()
+                        } else {
+                            month_days(rule, y, m, d0.day())
+                        }
+                    })
+                    .collect();
+                (first, days)
+            }
+        };
+        if instant(period_start.and_time(NaiveTime::MIN)) > window_end {
+            break;
+        }
+        for d in candidates {
+            if !rule.by_month.is_empty() && !rule.by_month.contains(&d.month()) {
+                continue;
+            }
+            let t = d.and_time(start.time());
+            if t < start {
+                continue;
+            }
+            if rule.count.is_some_and(|c| emitted >= c) {
+                return out;
+            }
+            let at = instant(t);
+            if rule.until.is_some_and(|u| at > u) || at > window_end {
+                return out;
+            }
+            emitted += 1;
+            out.push(t);
+        }
+    }
+    out
+}
+
+/// One occurrence of an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub calendar: String,
+    pub summary: String,
+    pub location: Option<String>,
+    pub start: DateTime<Tz>,
+    /// Exclusive; for all-day events midnight after the last day.
+    pub end: DateTime<Tz>,
+    pub all_day: bool,
+}
+
+/// Occurrences of `events` overlapping `[from, to)`, times in `tz`.
+fn occurrences(
+    events: &[VEvent],
+    calendar: &str,
+    tz: Tz,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<Occurrence> {
+    // Instances that a RECURRENCE-ID override replaces (moved, renamed or cancelled).
+    let overridden: HashSet<(&str, DateTime<Utc>)> = events
+        .iter()
+        .filter_map(|e| Some((e.uid.as_str(), e.recurrence_id?.instant(tz))))
+        .collect();
+    let mut out = Vec::new();
+    for ev in events.iter().filter(|e| !e.cancelled) {
+        let Some(start) = ev.start else { continue };
+        let all_day = matches!(start, Stamp::Date(_));
+        let days = match (ev.end, ev.duration) {
+            (Some(Stamp::Date(end)), _) => (end - start.naive().date()).num_days().max(1),
+            (_, Some(d)) => d.num_days().max(1),
+            _ => 1,
+        };
+        let length = match (ev.end, ev.duration) {
+            (Some(end), _) => end.instant(tz) - start.instant(tz),
+            (None, Some(d)) => d,
+            (None, None) => Duration::zero(),
+        }
+        .max(Duration::zero());
+
+        let rule = ev
+            .rrule
+            .as_deref()
+            .filter(|_| ev.recurrence_id.is_none())
+            .and_then(|r| parse_rule(r, tz));
+        let starts = match rule {
+            Some(rule) => {
+                let excluded: HashSet<DateTime<Utc>> =
+                    ev.exdates.iter().map(|x| x.instant(tz)).collect();
+                let mut starts = recurrences(
+                    start.naive(),
+                    &rule,
+                    |t| start.with_naive(t).instant(tz),
+                    to,
+                );
+                starts.retain(|t| {
+                    let at = start.with_naive(*t).instant(tz);
+                    !excluded.contains(&at) && !overridden.contains(&(ev.uid.as_str(), at))
+                });
+                starts
+            }
+            None => vec![start.naive()],
+        };
+
+        for t in starts {
+            let (begin, end) = if all_day {
+                let day = t.date();
+                (
+                    local_instant(day.and_time(NaiveTime::MIN), tz),
+                    local_instant((day + Duration::days(days)).and_time(NaiveTime::MIN), tz),
+                )
+            } else {
+                let begin = start.with_naive(t).instant(tz);
+                (begin, begin + length)
+            };
+            let overlaps = begin < to && (end > from || (end == begin && begin >= from));
+            if overlaps {
+                out.push(Occurrence {
+                    calendar: calendar.to_string(),
+                    summary: if ev.summary.is_empty() {
+                        "(no title)".to_string()
+                    } else {
+                        ev.summary.clone()
+                    },
+                    location: ev.location.clone(),
+                    start: begin.with_timezone(&tz),
+                    end: end.with_timezone(&tz),
+                    all_day,
+                });
+            }
+        }
+    }
+    out
+}
+
+/// CalDAV `calendar-query` REPORT body for events overlapping `[from, to)`.
+fn calendar_query(from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><c:calendar-data/></d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT">
+        <c:time-range start="{}" end="{}"/>
+      </c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#,
+        from.format("%Y%m%dT%H%M%SZ"),
+        to.format("%Y%m%dT%H%M%SZ")
+    )
+}
+
+/// The iCalendar documents inside a CalDAV multistatus response.
+fn calendar_data(xml: &str) -> String {
+    let re = Regex::new(
+        r"(?s)<(?:[A-Za-z0-9_-]+:)?calendar-data[^>]*>(.*?)</(?:[A-Za-z0-9_-]+:)?calendar-data>",
+    )
+    .unwrap();
+    re.captures_iter(xml)
+        .map(|c| {
+            let raw = c[1].trim();
+            match raw
+                .strip_prefix("<![CDATA[")
+                .and_then(|r| r.strip_suffix("]]>"))
+            {
+                Some(cdata) => cdata.to_string(),
+                None => decode_entities(raw),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The configured calendars; shared by the tool and the heartbeat/cron briefing.
+pub struct Calendars {
+    client: Client,
+    sources: BTreeMap<String, CalendarSource>,
+    timezone: Tz,
+    briefing_hours: u32,
+}
+
+impl Calendars {
+    /// None when no calendars are configured.
+    pub fn from_config(
+        client: Client,
+        config: Option<&CalendarConfig>,
+        timezone: Tz,
+    ) -> Option<Self> {
+        let config = config?;
+        let sources = config.calendars.clone().filter(|c| !c.is_empty())?;
+        Some(Self {
+            client,
+            sources,
+            timezone,
+            briefing_hours: config.briefing_hours.unwrap_or(DEFAULT_BRIEFING_HOURS),
+        })
+    }
+
+    async fn fetch(
+        &self,
+        source: &CalendarSource,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<String, String> {
+        let url = match source.url.strip_prefix("webcal://") {
+            Some(rest) => format!("https://{rest}"),
+            None => source.url.clone(),
+        };
+        let url = validate_fetch_url(&url)?;
+        let caldav = source.caldav.unwrap_or(false);
+        let mut req = if caldav {
+            let report = Method::from_bytes(b"REPORT").map_err(|e| e.to_string())?;
+            self.client
+                .request(report, url)
+                .header("depth", "1")
+                .header("content-type", "application/xml; charset=utf-8")
+                .body(calendar_query(from, to))
+        } else {
+            self.client.get(url).header("accept", "text/calendar")
+        };
+        if let Some(user) = &source.username {
+            req = req.basic_auth(user, source.password.as_deref());
+        }
+        let res = req
+            .send()
+            .await
+            .map_err(|e| format!("request failed: {e}"))?;
+        let status = res.status();
+        if !status.is_success() {
+            return Err(format!("HTTP {status}"));
+        }
+        let body = res
+            .text()
+            .await
+            .map_err(|e| format!("reading calendar failed: {e}"))?;
+        Ok(if caldav { calendar_data(&body) } else { body })
+    }
+
+    /// Occurrences overlapping `[from, to)` in every calendar (or the one named), sorted by
+    /// start; calendars that could not be read are returned as "name: error" lines.
+    pub async fn events(
+        &self,
+        only: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> (Vec<Occurrence>, Vec<String>) {
+        let selected = self
+            .sources
+            .iter()
+            .filter(|(name, _)| only.is_none_or(|o| o.eq_ignore_ascii_case(name)));
+        let results = futures_util::future::join_all(
+            selected.map(|(name, src)| async move { (name, self.fetch(src, from, to).await) }),
+        )
+        .await;
+        let mut events = Vec::new();
+        let mut errors = Vec::new();
+        for (name, result) in results {
+            match result {
+                Ok(ics) => events.extend(occurrences(
+                    &parse_ics(&ics, self.timezone),
+                    name,
+                    self.timezone,
+                    from,
+                    to,
+                )),
+                Err(e) => errors.push(format!("{name}: {e}")),
+            }
+        }
+        events.sort_by(|a, b| {
+            (a.start, !a.all_day, &a.summary).cmp(&(b.start, !b.all_day, &b.summary))
+        });
+        (events, errors)
+    }
+
+    /// "09:00–09:30 Standup @ Zoom [work]", optionally prefixed with the day.
+    fn describe(&self, e: &Occurrence, with_day: bool) -> String {
+        let mut out = if with_day {
+            format!("{} ", e.start.format("%a %-d %b"))
+        } else {
+            String::new()
+        };
+        if e.all_day {
+            let last = e.end - Duration::days(1);
+            if last.date_naive() > e.start.date_naive() {
+                out.push_str(&format!("all day until {}: ", last.format("%a %-d %b")));
+            } else {
+                out.push_str("all day: ");
+            }
+        } else if e.end == e.start {
+            out.push_str(&format!("{} ", e.start.format("%H:%M")));
+        } else if e.end.date_naive() == e.start.date_naive() {
+            out.push_str(&format!(
+                "{}–{} ",
+                e.start.format("%H:%M"),
+                e.end.format("%H:%M")
+            ));
+        } else {
+            out.push_str(&format!(
+                "{}–{} ",
+                e.start.format("%H:%M"),
+                e.end.format("%a %-d %b %H:%M")
+            ));
+        }
+        out.push_str(&e.summary);
+        if let Some(loc) = &e.location {
+            out.push_str(&format!(" @ {loc}"));
+        }
+        if self.sources.len() > 1 {
+            out.push_str(&format!(" [{}]", e.calendar));
+        }
+        out
+    }
+
+    /// One day's events as "- 09:00–09:30 Standup" lines, or "- (no events)"; used by the
+    /// daily note template.
+    pub async fn day_agenda(&self, day: NaiveDate) -> String {
+        let from = local_instant(day.and_time(NaiveTime::MIN), self.timezone);
+        let to = local_instant(
+            (day + Duration::days(1)).and_time(NaiveTime::MIN),
+            self.timezone,
+        );
+        let (events, errors) = self.events(None, from, to).await;
+        let mut lines: Vec<String> = events
+            .iter()
+            .map(|e| format!("- {}", self.describe(e, false)))
+            .collect();
+        if lines.is_empty() && errors.is_empty() {
+            lines.push("- (no events)".to_string());
+        }
+        lines.extend(
+            errors
+                .iter()
+                .map(|e| format!("- (could not read calendar {e})")),
+        );
+        lines.join("\n")
+    }
+
+    /// Upcoming events for a heartbeat or cron message; None when the briefing is off.
+    pub async fn briefing(&self) -> Option<String> {
+        if self.briefing_hours == 0 {
+            return None;
+        }
+        let now = Utc::now();
+        let (events, errors) = self
+            .events(None, now, now + Duration::hours(self.briefing_hours as i64))
+            .await;
+        let mut out = format!("Upcoming calendar events (next {} h):", self.briefing_hours);
+        if events.is_empty() && errors.is_empty() {
+            out.push_str(" none.");
+        }
+        for e in &events {
+            out.push_str(&format!("\n- {}", self.describe(e, true)));
+        }
+        for e in &errors {
+            out.push_str(&format!("\n- (calendar {e})"));
+        }
+        Some(out)
+    }
+}
+
+/// `today`, `tomorrow`, `yesterday` or `YYYY-MM-DD`.
+fn parse_day(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "" | "today" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        "yesterday" => today.pred_opt(),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d").ok(),
+    }
+}
+
+pub struct CalendarTool {
+    calendars: Arc<Calendars>,
+}
+
+impl CalendarTool {
+    pub fn new(calendars: Arc<Calendars>) -> Self {
+        Self { calendars }
+    }
+
+    async fn agenda(&self, args: &Value) -> Result<String, String> {
+        let cals = &self.calendars;
+        let tz = cals.timezone;
+        let today = Utc::now().with_timezone(&tz).date_naive();
+        let start_arg = args.get("start").and_then(Value::as_str).unwrap_or("today");
+        let first = parse_day(start_arg, today).ok_or_else(|| {
+            format!("invalid start '{start_arg}' (today, tomorrow or YYYY-MM-DD)")
+        })?;
+        let days = args
+            .get("days")
+            .and_then(Value::as_i64)
+            .unwrap_or(1)
+            .clamp(1, MAX_DAYS);
+        let last = first + Duration::days(days - 1);
+        let only = args.get("calendar").and_then(Value::as_str);
+        if let Some(name) = only
+            && !cals.sources.keys().any(|k| k.eq_ignore_ascii_case(name))
+        {
+            let known: Vec<&str> = cals.sources.keys().map(String::as_str).collect();
+            return Err(format!(
+                "unknown calendar '{name}' (configured: {})",
+                known.join(", ")
+            ));
+        }
+        let query = args
+            .get("query")
+            .and_then(Value::as_str)
+            .map(str::to_lowercase)
+            .filter(|q| !q.trim().is_empty());
+
+        let from = local_instant(first.and_time(NaiveTime::MIN), tz);
+        let to = local_instant((last + Duration::days(1)).and_time(NaiveTime::MIN), tz);
+        let (mut events, errors) = cals.events(only, from, to).await;
+        if let Some(q) = &query {
+            events.retain(|e| {
+                e.summary.to_lowercase().contains(q)
+                    || e.location
+                        .as_deref()
+                        .is_some_and(|l| l.to_lowercase().contains(q))
+            });
+        }
+        if events.is_empty() && !errors.is_empty() {
+            return Err(format!("could not read calendars: {}", errors.join("; ")));
+        }
+
+        let range = if days == 1 {
+            first.format("%a %-d %b %Y").to_string()
+        } else {
+            format!(
+                "{} – {}",
+                first.format("%a %-d %b"),
+                last.format("%a %-d %b %Y")
+            )
+        };
+        let mut out = format!("Calendar, {range} ({tz}):");
+        if events.is_empty() {
+            out.push_str(" no events");
+            if let Some(q) = &query {
+                out.push_str(&format!(" matching '{q}'"));
+            }
+            out.push('.');
+        }
+        let mut current_day = None;
+        for e in &events {
+            // Events that began before the range are listed under its first day.
+            let day = e.start.date_naive().max(first);
+            if current_day != Some(day) {
+                out.push_str(&format!("\n\n{}", day.format("%a %-d %b")));
+                current_day = Some(day);
+            }
+            out.push_str(&format!("\n- {}", cals.describe(e, false)));
+        }
+        for e in &errors {
+            out.push_str(&format!("\n\n(could not read calendar {e})"));
+        }
+        Ok(out)
+    }
+}
+
+impl Tool for CalendarTool {
+    fn name(&self) -> &str {
+        "calendar"
+    }
+
+    fn description(&self) -> &str {
+        "The user's calendar events (recurring events expanded) for a day or range: \"what's on \
+         tomorrow\", \"this week\", \"when is the dentist\". Times are in the user's timezone."
+    }
+
+    fn parameters(&self) -> Value {
+        let names: Vec<&str> = self.calendars.sources.keys().map(String::as_str).collect();
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "start": { "type": "string", "description": "First day: today, tomorrow or YYYY-MM-DD (default today)" },
+                "days": { "type": "integer", "description": "Number of days, 1-31 (default 1)" },
+                "calendar": { "type": "string", "description": format!("Only this calendar: {}", names.join(", ")) },
+                "query": { "type": "string", "description": "Only events whose title or location contains this text" }
+            }
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.agenda(args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::web::web_client;
+    use std::path::PathBuf;
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const LONDON: Tz = chrono_tz::Europe::London;
+
+    const ICS: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup\r\n\
+SUMMARY:Standup\r\n\
+DTSTART;TZID=Europe/London:20261005T093000\r\n\
+DTEND;TZID=Europe/London:20261005T094500\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10\r\n\
+EXDATE;TZID=Europe/London:20261021T093000\r\n\
+LOCATION:Zoom\\, room 2\r\n\
+BEGIN:VALARM\r\n\
+SUMMARY:not the event\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup\r\n\
+RECURRENCE-ID;TZID=Europe/London:20261019T093000\r\n\
+SUMMARY:Standup (moved)\r\n\
+DTSTART;TZID=Europe/London:20261019T110000\r\n\
+DTEND;TZID=Europe/London:20261019T111500\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:pay\r\n\
+SUMMARY:Payday\r\n\
+DTSTART;VALUE=DATE:20260130\r\n\
+RRULE:FREQ=MONTHLY;BYDAY=-1FR\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:trip\r\n\
+SUMMARY:Lisbon \r\n\x20trip\r\n\
+DTSTART;VALUE=DATE:20261029\r\n\
+DTEND;VALUE=DATE:20261102\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:call\r\n\
+SUMMARY:Call NY\r\n\
+DTSTART:20261030T170000Z\r\n\
+DURATION:PT1H\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:gone\r\n\
+SUMMARY:Cancelled thing\r\n\
+STATUS:CANCELLED\r\n\
+DTSTART:20261019T120000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    fn window(from: &str, days: i64) -> (DateTime<Utc>, DateTime<Utc>) {
+        let d = NaiveDate::parse_from_str(from, "%Y-%m-%d").unwrap();
+        (
+            local_instant(d.and_time(NaiveTime::MIN), LONDON),
+            local_instant((d + Duration::days(days)).and_time(NaiveTime::MIN), LONDON),
+        )
+    }
+
+    fn listed(from: &str, days: i64) -> Vec<String> {
+        let (a, b) = window(from, days);
+        let mut occ = occurrences(&parse_ics(ICS, LONDON), "home", LONDON, a, b);
+        occ.sort_by_key(|o| o.start);
+        occ.iter()
+            .map(|o| {
+                format!(
+                    "{} {}{}",
+                    o.start.format("%Y-%m-%d %H:%M"),
+                    o.summary,
+                    if o.all_day { " (all day)" } else { "" }
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expands_recurrences_with_exceptions_and_overrides() {
+        assert_eq!(
+            listed("2026-10-19", 7),
+            ["2026-10-19 11:00 Standup (moved)",]
+        );
+        // COUNT=10 from Mon 5 Oct: the 10th is Wed 4 Nov; 21 Oct was excluded but still counts.
+        assert_eq!(
+            listed("2026-11-02", 7),
+            ["2026-11-02 09:30 Standup", "2026-11-04 09:30 Standup"]
+        );
+        assert!(listed("2026-11-09", 7).is_empty());
+        // After the clocks go back the standup stays at 09:30 local; the UTC call moves.
+        assert_eq!(
+            listed("2026-10-26", 5),
+            [
+                "2026-10-26 09:30 Standup",
+                "2026-10-28 09:30 Standup",
+                "2026-10-29 00:00 Lisbon trip (all day)",
+                "2026-10-30 00:00 Payday (all day)",
+                "2026-10-30 17:00 Call NY",
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_rules_and_values() {
+        let rule =
+            parse_rule("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1;UNTIL=20280301", LONDON).unwrap();
+        let start = NaiveDate::from_ymd_opt(2026, 2, 28)
+            .unwrap()
+            .and_time(NaiveTime::MIN);
+        let end = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+        let dates: Vec<String> = recurrences(start, &rule, |t| local_instant(t, LONDON), end)
+            .iter()
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .collect();
+        assert_eq!(dates, ["2026-02-28", "2027-02-28", "2028-02-29"]);
+        assert!(parse_rule("FREQ=SECONDLY", LONDON).is_none());
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::days(7)));
+        assert_eq!(unescape(r"a\, b\; c\\n\nd"), "a, b; c\\n\nd");
+    }
+
+    fn calendars(server: &MockServer) -> Arc<Calendars> {
+        let mut sources = BTreeMap::new();
+        sources.insert(
+            "home".to_string(),
+            CalendarSource {
+                url: format!("{}/home.ics", server.uri()),
+                ..Default::default()
+            },
+        );
+        sources.insert(
+            "work".to_string(),
+            CalendarSource {
+                url: format!("{}/dav/work/", server.uri()),
+                username: Some("me".to_string()),
+                password: Some("pw".to_string()),
+                caldav: Some(true),
+            },
+        );
+        let config = CalendarConfig {
+            calendars: Some(sources),
+            briefing_hours: None,
+        };
+        Arc::new(Calendars::from_config(web_client().unwrap(), Some(&config), LONDON).unwrap())
+    }
+
+    #[tokio::test]
+    async fn tool_merges_ics_and_caldav_calendars() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/home.ics"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(ICS))
+            .mount(&server)
+            .await;
+        let multistatus = "<?xml version=\"1.0\"?>\n<d:multistatus xmlns:d=\"DAV:\" \
+            xmlns:cal=\"urn:ietf:params:xml:ns:caldav\"><d:response><d:propstat><d:prop>\
+            <cal:calendar-data>BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:x\nSUMMARY:Design review &amp; \
+            lunch\nDTSTART:20261029T120000Z\nDTEND:20261029T133000Z\nEND:VEVENT\nEND:VCALENDAR\n\
+            </cal:calendar-data></d:prop></d:propstat></d:response></d:multistatus>";
+        Mock::given(method("REPORT"))
+            .and(path("/dav/work/"))
+            .and(header("depth", "1"))
+            .and(header("authorization", "Basic bWU6cHc="))
+            .and(body_string_contains(
+                r#"<c:time-range start="20261029T000000Z" end="20261030T000000Z"/>"#,
+            ))
+            .respond_with(ResponseTemplate::new(207).set_body_string(multistatus))
+            .mount(&server)
+            .await;
+
+        let tool = CalendarTool::new(calendars(&server));
+        let ctx = ToolCtx::for_tests(PathBuf::from("/tmp"));
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"start": "2026-10-29"}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "Calendar, Thu 29 Oct 2026 (Europe/London):\n\n\
+             Thu 29 Oct\n\
+             - all day until Sun 1 Nov: Lisbon trip [home]\n\
+             - 12:00–13:30 Design review & lunch [work]"
+        );
+
+        let res = tool
+            .execute(
+                &ctx,
+                &serde_json::json!({"start": "2026-10-28", "days": 3, "calendar": "home", "query": "zoom"}),
+            )
+            .await;
+        assert_eq!(
+            res.for_llm,
+            "Calendar, Wed 28 Oct – Fri 30 Oct 2026 (Europe/London):\n\n\
+             Wed 28 Oct\n\
+             - 09:30–09:45 Standup @ Zoom, room 2 [home]"
+        );
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"calendar": "gym"}))
+            .await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("configured: home, work"));
+    }
+}
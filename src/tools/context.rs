@@ -6,6 +6,7 @@ use std::sync::Arc;
 
 use tokio::sync::mpsc;
 
+use crate::memory::db::ChatLocation;
 use crate::telegram::OutboundMsg;
 
 /// Context passed into each tool execution.
@@ -25,4 +26,25 @@ pub struct ToolCtx {
     /// Shared via Arc so clones (e.g. sub-ctx) observe the same flag.
     /// main.rs reads this after the agent loop to skip redundant delivery.
     pub delivered: Arc<AtomicBool>,
+    /// Latest location shared in this chat, for "near me" style tools (weather, timezone).
+    pub location: Option<ChatLocation>,
+    /// Forum topic of the current message; replies and files go back into the same topic.
+    pub thread_id: Option<i64>,
+}
+
+#[cfg(test)]
+impl ToolCtx {
+    /// Context for tool tests: `workspace`, restricted to it, with no chat attached.
+    pub fn for_tests(workspace: impl Into<PathBuf>) -> Self {
+        Self {
+            workspace: workspace.into(),
+            restrict_to_workspace: true,
+            chat_id: None,
+            channel: None,
+            outbound_tx: None,
+            delivered: Default::default(),
+            location: None,
+            thread_id: None,
+        }
+    }
 }
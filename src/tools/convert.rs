@@ -0,0 +1,678 @@
+//! `convert` tool: unit conversions offline (length, weight, volume, temperature, speed,
+//! energy, time, area, data) and currency via a cached exchange-rate API.
+//!
+//! Quantities may be compound — `12 stone 4` (the bare 4 is pounds), `5 ft 10 in`, `5'10"` —
+//! and so may targets (`to: "st lb"`). Cooking and liquid measures are UK/metric; prefix
+//! `us_` for US ones (`us_cup`, `us_gallon`). Currencies are ISO codes or symbols (`80 USD`,
+//! `£12`); rates come from open.er-api.com (no key) and are reused for [`RATES_TTL`].
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+use Dim::*;
+
+/// Exchange-rate endpoint; `{url}/{BASE}` returns the rates from `BASE`.
+pub const DEFAULT_RATES_URL: &str = "https://open.er-api.com/v6/latest";
+/// How long fetched rates are reused (the API updates daily).
+pub const RATES_TTL: Duration = Duration::from_secs(12 * 3600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dim {
+    Length,
+    Mass,
+    Volume,
+    Temperature,
+    Speed,
+    Energy,
+    Time,
+    Area,
+    Data,
+}
+
+/// A unit: space-separated aliases (canonical first), dimension and size in the dimension's
+/// base unit (m, kg, L, °C, m/s, J, s, m², byte).
+struct Unit {
+    names: &'static str,
+    dim: Dim,
+    factor: f64,
+}
+
+const fn unit_def(names: &'static str, dim: Dim, factor: f64) -> Unit {
+    Unit { names, dim, factor }
+}
+
+const UNITS: &[Unit] = &[
+    unit_def(
+        "mm millimetre millimetres millimeter millimeters",
+        Length,
+        0.001,
+    ),
+    unit_def(
+        "cm centimetre centimetres centimeter centimeters",
+        Length,
+        0.01,
+    ),
+    unit_def("m metre metres meter meters", Length, 1.0),
+    unit_def(
+        "km kilometre kilometres kilometer kilometers",
+        Length,
+        1000.0,
+    ),
+    unit_def("in inch inches \"", Length, 0.0254),
+    unit_def("ft foot feet '", Length, 0.3048),
+    unit_def("yd yard yards", Length, 0.9144),
+    unit_def("mi mile miles", Length, 1609.344),
+    unit_def("nmi nautical_mile nautical_miles", Length, 1852.0),
+    unit_def("mg milligram milligrams", Mass, 1e-6),
+    unit_def("g gram grams", Mass, 0.001),
+    unit_def("kg kilo kilos kilogram kilograms", Mass, 1.0),
+    unit_def("t tonne tonnes", Mass, 1000.0),
+    unit_def("oz ounce ounces", Mass, 0.028_349_523_125),
+    unit_def("lb lbs pound pounds", Mass, 0.453_592_37),
+    unit_def("st stone stones", Mass, 6.350_293_18),
+    unit_def(
+        "ml millilitre millilitres milliliter milliliters",
+        Volume,
+        0.001,
+    ),
+    unit_def("cl centilitre centilitres", Volume, 0.01),
+    unit_def("dl decilitre decilitres", Volume, 0.1),
+    unit_def("l litre litres liter liters", Volume, 1.0),
+    unit_def("m3 cubic_metre cubic_metres", Volume, 1000.0),
+    unit_def("tsp teaspoon teaspoons", Volume, 0.005),
+    unit_def("tbsp tablespoon tablespoons", Volume, 0.015),
+    unit_def("cup cups", Volume, 0.25),
+    unit_def("fl_oz floz", Volume, 0.028_413_062_5),
+    unit_def("pt pint pints", Volume, 0.568_261_25),
+    unit_def("gal gallon gallons", Volume, 4.546_09),
+    unit_def("us_cup us_cups", Volume, 0.236_588_236_5),
+    unit_def("us_fl_oz us_floz", Volume, 0.029_573_529_562_5),
+    unit_def("us_pint us_pints", Volume, 0.473_176_473),
+    unit_def("us_gal us_gallon us_gallons", Volume, 3.785_411_784),
+    unit_def("c celsius centigrade", Temperature, 1.0),
+    unit_def("f fahrenheit", Temperature, 1.0),
+    unit_def("k kelvin", Temperature, 1.0),
+    unit_def("m/s mps", Speed, 1.0),
+    unit_def("km/h kmh kph", Speed, 1.0 / 3.6),
+    unit_def("mph", Speed, 0.447_04),
+    unit_def("kn knot knots", Speed, 1852.0 / 3600.0),
+    unit_def("j joule joules", Energy, 1.0),
+    unit_def("kj kilojoule kilojoules", Energy, 1000.0),
+    unit_def("cal calorie calories", Energy, 4.184),
+    unit_def("kcal kilocalorie kilocalories", Energy, 4184.0),
+    unit_def("wh", Energy, 3600.0),
+    unit_def("kwh", Energy, 3.6e6),
+    unit_def("s sec secs second seconds", Time, 1.0),
+    unit_def("min mins minute minutes", Time, 60.0),
+    unit_def("h hr hrs hour hours", Time, 3600.0),
+    unit_def("d day days", Time, 86_400.0),
+    unit_def("wk week weeks", Time, 604_800.0),
+    unit_def("cm2 sq_cm", Area, 1e-4),
+    unit_def("m2 sq_m sqm", Area, 1.0),
+    unit_def("ft2 sq_ft sqft", Area, 0.092_903_04),
+    unit_def("ha hectare hectares", Area, 10_000.0),
+    unit_def("acre acres", Area, 4_046.856_422_4),
+    unit_def("km2 sq_km", Area, 1e6),
+    unit_def("b byte bytes", Data, 1.0),
+    unit_def("kb kilobyte kilobytes", Data, 1e3),
+    unit_def("mb megabyte megabytes", Data, 1e6),
+    unit_def("gb gigabyte gigabytes", Data, 1e9),
+    unit_def("tb terabyte terabytes", Data, 1e12),
+    unit_def("kib", Data, 1024.0),
+    unit_def("mib", Data, 1_048_576.0),
+    unit_def("gib", Data, 1_073_741_824.0),
+];
+
+/// Unit a bare trailing number is taken to be in: `12 st 4` is 4 lb.
+const SUBUNITS: &[(&str, &str)] = &[
+    ("st", "lb"),
+    ("lb", "oz"),
+    ("ft", "in"),
+    ("m", "cm"),
+    ("h", "min"),
+    ("min", "s"),
+];
+
+pub(crate) const CURRENCY_SYMBOLS: &[(&str, &str)] = &[
+    ("$", "USD"),
+    ("£", "GBP"),
+    ("€", "EUR"),
+    ("¥", "JPY"),
+    ("₹", "INR"),
+];
+
+fn unit(name: &str) -> Option<&'static Unit> {
+    let name = name
+        .trim_end_matches('.')
+        .replace('°', "")
+        .replace('²', "2")
+        .replace('³', "3")
+        .replace(' ', "_")
+        .to_lowercase();
+    UNITS.iter().find(|u| u.names.split(' ').any(|n| n == name))
+}
+
+fn canonical(u: &Unit) -> &'static str {
+    u.names.split(' ').next().unwrap_or(u.names)
+}
+
+fn to_base(u: &Unit, v: f64) -> f64 {
+    match canonical(u) {
+        "f" => (v - 32.0) * 5.0 / 9.0,
+        "k" => v - 273.15,
+        _ => v * u.factor,
+    }
+}
+
+fn from_base(u: &Unit, v: f64) -> f64 {
+    match canonical(u) {
+        "f" => v * 9.0 / 5.0 + 32.0,
+        "k" => v + 273.15,
+        _ => v / u.factor,
+    }
+}
+
+/// Display name: the canonical alias, upper-cased for temperatures.
+fn label(u: &Unit) -> String {
+    if u.dim == Dim::Temperature {
+        format!("°{}", canonical(u).to_uppercase())
+    } else {
+        canonical(u).to_string()
+    }
+}
+
+/// `n` rounded to `decimals` places, without trailing zeros.
+fn format_amount(n: f64, decimals: usize) -> String {
+    let s = format!("{n:.decimals$}");
+    let s = if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &s
+    };
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// Four significant-ish places: enough for kitchen scales without float noise.
+fn format_value(n: f64) -> String {
+    let decimals = match n.abs() {
+        a if a >= 100.0 || a == 0.0 => 2,
+        a if a >= 1.0 => 3,
+        _ => 6,
+    };
+    format_amount(n, decimals)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(f64),
+    Word(String),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Tok>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut toks = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (matches!(c, '-' | '.') && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_digit() || matches!(chars[i], '.' | ',')) {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().filter(|c| **c != ',').collect();
+            let n = text
+                .parse()
+                .map_err(|_| format!("invalid number '{text}'"))?;
+            toks.push(Tok::Num(n));
+        } else if matches!(c, '\'' | '"') || CURRENCY_SYMBOLS.iter().any(|(s, _)| s.starts_with(c))
+        {
+            toks.push(Tok::Word(c.to_string()));
+            i += 1;
+        } else {
+            let start = i;
+            while i < chars.len()
+                && !chars[i].is_whitespace()
+                && !chars[i].is_ascii_digit()
+                && !matches!(chars[i], '\'' | '"')
+            {
+                i += 1;
+            }
+            // Digits inside a unit name (m2, km2) stay with it.
+            while i < chars.len() && matches!(chars[i], '2' | '3') && chars[i - 1].is_alphabetic() {
+                i += 1;
+            }
+            toks.push(Tok::Word(chars[start..i].iter().collect()));
+        }
+    }
+    Ok(toks)
+}
+
+/// ISO code for a currency symbol or three-letter word; units win (`cup` is not CUP).
+fn currency(word: &str) -> Option<String> {
+    if let Some((_, code)) = CURRENCY_SYMBOLS.iter().find(|(s, _)| *s == word) {
+        return Some(code.to_string());
+    }
+    let is_code = word.len() == 3 && word.chars().all(|c| c.is_ascii_alphabetic());
+    (is_code && unit(word).is_none()).then(|| word.to_ascii_uppercase())
+}
+
+/// What the `quantity` argument parsed to.
+#[derive(Debug, PartialEq)]
+enum Quantity {
+    Money(f64, String),
+    /// Value in the dimension's base unit.
+    Measure(f64, Dim),
+}
+
+fn parse_quantity(input: &str) -> Result<Quantity, String> {
+    let mut toks = tokenize(input)?.into_iter().peekable();
+    let mut parts: Vec<(f64, &'static Unit)> = Vec::new();
+    let mut money: Option<(Option<f64>, Option<String>)> = None;
+    // A leading currency symbol: "$80", "£12.50".
+    if let Some(Tok::Word(w)) = toks.peek()
+        && let Some(code) = currency(w).filter(|_| w.chars().count() == 1)
+    {
+        toks.next();
+        money = Some((None, Some(code)));
+    }
+    while let Some(tok) = toks.next() {
+        let n = match tok {
+            Tok::Num(n) => n,
+            Tok::Word(w) => return Err(format!("expected a number before '{w}'")),
+        };
+        if let Some((amount @ None, _)) = &mut money {
+            *amount = Some(n);
+            continue;
+        }
+        let word = match toks.peek() {
+            Some(Tok::Word(w)) => Some(w.clone()),
+            _ => None,
+        };
+        if let Some(w) = &word {
+            toks.next();
+            if let Some(u) = unit(w) {
+                parts.push((n, u));
+            } else if let Some(code) = currency(w).filter(|_| parts.is_empty()) {
+                money = Some((Some(n), Some(code)));
+            } else {
+                return Err(format!("unknown unit '{w}'"));
+            }
+        } else {
+            let Some(&(_, prev)) = parts.last() else {
+                return Err(format!("give a unit for {}", format_value(n)));
+            };
+            let sub = SUBUNITS
+                .iter()
+                .find(|(big, _)| *big == canonical(prev))
+                .and_then(|(_, small)| unit(small))
+                .ok_or_else(|| {
+                    format!("give a unit for {} after {}", format_value(n), label(prev))
+                })?;
+            parts.push((n, sub));
+        }
+    }
+    if let Some((amount, code)) = money {
+        if !parts.is_empty() {
+            return Err("cannot mix money and units".to_string());
+        }
+        let amount = amount.ok_or("expected an amount after the currency symbol")?;
+        return Ok(Quantity::Money(amount, code.unwrap_or_default()));
+    }
+    let Some(&(_, first)) = parts.first() else {
+        return Err("empty quantity".to_string());
+    };
+    if parts.len() > 1 && first.dim == Dim::Temperature {
+        return Err("temperatures cannot be compound".to_string());
+    }
+    let mut base = 0.0;
+    for (n, u) in &parts {
+        if u.dim != first.dim {
+            return Err(format!("cannot combine {} and {}", label(first), label(u)));
+        }
+        base += to_base(u, *n);
+    }
+    Ok(Quantity::Measure(base, first.dim))
+}
+
+/// `base` expressed in `targets` (largest first): whole numbers for all but the last.
+fn express(base: f64, dim: Dim, targets: &str) -> Result<String, String> {
+    let units = targets
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '+')
+        .filter(|s| !s.is_empty())
+        .map(|name| unit(name).ok_or_else(|| format!("unknown unit '{name}'")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(last) = units.last() else {
+        return Err("missing target unit".to_string());
+    };
+    if let Some(u) = units.iter().find(|u| u.dim != dim) {
+        return Err(format!(
+            "cannot convert {:?} to {} ({:?})",
+            dim,
+            label(u),
+            u.dim
+        ));
+    }
+    if units.len() == 1 {
+        return Ok(format!(
+            "{} {}",
+            format_value(from_base(last, base)),
+            label(last)
+        ));
+    }
+    if dim == Dim::Temperature {
+        return Err("temperatures cannot be compound".to_string());
+    }
+    let sign = if base < 0.0 { "-" } else { "" };
+    let mut rest = base.abs();
+    let mut whole: Vec<f64> = Vec::new();
+    for u in &units[..units.len() - 1] {
+        let n = (rest / u.factor + 1e-9).floor();
+        whole.push(n);
+        rest -= n * u.factor;
+    }
+    let mut tail: f64 = format_amount(rest / last.factor, 2).parse().unwrap_or(0.0);
+    // Rounding up to a whole larger unit (13.999 lb) carries.
+    let prev = units[units.len() - 2];
+    if (tail * last.factor - prev.factor).abs() < 1e-9 {
+        tail = 0.0;
+        *whole.last_mut().expect("compound target") += 1.0;
+    }
+    let mut out: Vec<String> = units[..units.len() - 1]
+        .iter()
+        .zip(&whole)
+        .skip_while(|(_, n)| **n == 0.0)
+        .map(|(u, n)| format!("{n} {}", label(u)))
+        .collect();
+    out.push(format!("{} {}", format_amount(tail, 2), label(last)));
+    Ok(format!("{sign}{}", out.join(" ")))
+}
+
+struct CachedRates {
+    fetched: Instant,
+    rates: HashMap<String, f64>,
+    as_of: String,
+}
+
+pub struct ConvertTool {
+    client: Client,
+    rates_url: String,
+    cache: Mutex<HashMap<String, CachedRates>>,
+}
+
+impl ConvertTool {
+    pub fn new(client: Client) -> Self {
+        Self::with_rates_url(client, DEFAULT_RATES_URL)
+    }
+
+    /// Use another rates endpoint (same response shape as open.er-api.com).
+    pub fn with_rates_url(client: Client, rates_url: &str) -> Self {
+        Self {
+            client,
+            rates_url: rates_url.trim_end_matches('/').to_string(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Rate from `from` to `to` and when it was published; cached per base currency.
+    async fn rate(&self, from: &str, to: &str) -> Result<(f64, String), String> {
+        let lookup = |cached: &CachedRates| {
+            cached
+                .rates
+                .get(to)
+                .map(|r| (*r, cached.as_of.clone()))
+                .ok_or_else(|| format!("no exchange rate for {to}"))
+        };
+        {
+            let cache = self.cache.lock().expect("rates cache lock");
+            if let Some(cached) = cache.get(from).filter(|c| c.fetched.elapsed() < RATES_TTL) {
+                return lookup(cached);
+            }
+        }
+        match self.fetch(from).await {
+            Ok(fresh) => {
+                let result = lookup(&fresh);
+                self.cache
+                    .lock()
+                    .expect("rates cache lock")
+                    .insert(from.to_string(), fresh);
+                result
+            }
+            Err(e) => {
+                // Stale rates beat none when the API is down.
+                let cache = self.cache.lock().expect("rates cache lock");
+                match cache.get(from) {
+                    Some(stale) => lookup(stale).map(|(r, as_of)| (r, format!("{as_of}, stale"))),
+                    None => Err(e),
+                }
+            }
+        }
+    }
+
+    async fn fetch(&self, base: &str) -> Result<CachedRates, String> {
+        let url = format!("{}/{base}", self.rates_url);
+        let res = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("exchange-rate request failed: {e}"))?;
+        let status = res.status();
+        let body: Value = res
+            .json()
+            .await
+            .map_err(|e| format!("exchange-rate response unreadable: {e}"))?;
+        if !status.is_success() || body.get("result").and_then(Value::as_str) == Some("error") {
+            let reason = body
+                .get("error-type")
+                .and_then(Value::as_str)
+                .unwrap_or(status.as_str());
+            return Err(format!("exchange-rate API error for {base}: {reason}"));
+        }
+        let rates = body
+            .get("rates")
+            .and_then(Value::as_object)
+            .ok_or("exchange-rate response has no rates")?
+            .iter()
+            .filter_map(|(code, r)| r.as_f64().map(|r| (code.clone(), r)))
+            .collect();
+        let as_of = body
+            .get("time_last_update_utc")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown date")
+            .to_string();
+        Ok(CachedRates {
+            fetched: Instant::now(),
+            rates,
+            as_of,
+        })
+    }
+
+    async fn convert(&self, quantity: &str, to: &str) -> Result<String, String> {
+        match parse_quantity(quantity)? {
+            Quantity::Measure(base, dim) => Ok(format!("{quantity} = {}", express(base, dim, to)?)),
+            Quantity::Money(amount, from) => {
+                let to =
+                    currency(to.trim()).ok_or_else(|| format!("'{to}' is not a currency code"))?;
+                let (rate, as_of) = if from == to {
+                    (1.0, "fixed".to_string())
+                } else {
+                    self.rate(&from, &to).await?
+                };
+                Ok(format!(
+                    "{} {from} = {} {to} (1 {from} = {} {to}; rates as of {as_of})",
+                    format_amount(amount, 2),
+                    format_amount(amount * rate, 2),
+                    format_amount(rate, 4)
+                ))
+            }
+        }
+    }
+}
+
+impl Tool for ConvertTool {
+    fn name(&self) -> &str {
+        "convert"
+    }
+
+    fn description(&self) -> &str {
+        "Convert units or currency. Units work offline: length, weight (incl. stone), volume \
+         (UK/metric; us_cup, us_gallon for US), temperature, speed, energy (kcal, kJ), time, \
+         area and data sizes; compound values like '12 stone 4' or \"5'10\\\"\" and targets \
+         like 'st lb'. Currency uses daily exchange rates: '80 USD' to 'INR'."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "quantity": {
+                    "type": "string",
+                    "description": "Amount with unit or currency, e.g. '12 st 4', '350 F', '80 USD', '£12.50'"
+                },
+                "to": {
+                    "type": "string",
+                    "description": "Target unit(s) or currency code, e.g. 'kg', 'st lb', 'C', 'INR'"
+                }
+            },
+            "required": ["quantity", "to"]
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let quantity = args.get("quantity").and_then(Value::as_str).unwrap_or("");
+            let to = args.get("to").and_then(Value::as_str).unwrap_or("");
+            if quantity.trim().is_empty() || to.trim().is_empty() {
+                return ToolResult::error("missing 'quantity' or 'to'");
+            }
+            match self.convert(quantity.trim(), to).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(format!("cannot convert '{quantity}': {e}")),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::web::web_client;
+    use std::path::PathBuf;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn ctx() -> ToolCtx {
+        ToolCtx::for_tests(PathBuf::from("/tmp"))
+    }
+
+    fn units(quantity: &str, to: &str) -> String {
+        let tool = ConvertTool::with_rates_url(web_client().unwrap(), "http://127.0.0.1:9");
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        match rt.block_on(tool.convert(quantity, to)) {
+            Ok(s) => s,
+            Err(e) => format!("error: {e}"),
+        }
+    }
+
+    #[test]
+    fn converts_units_and_compounds() {
+        assert_eq!(units("12 stone 4", "kg"), "12 stone 4 = 78.018 kg");
+        assert_eq!(units("78.018 kg", "st lb"), "78.018 kg = 12 st 4 lb");
+        assert_eq!(units("5'10\"", "cm"), "5'10\" = 177.8 cm");
+        assert_eq!(units("180cm", "ft in"), "180cm = 5 ft 10.87 in");
+        assert_eq!(units("350 °F", "C"), "350 °F = 176.67 °C");
+        assert_eq!(units("-40 c", "fahrenheit"), "-40 c = -40 °F");
+        assert_eq!(units("2 us_cups", "ml"), "2 us_cups = 473.18 ml");
+        assert_eq!(units("1 pint", "ml"), "1 pint = 568.26 ml");
+        assert_eq!(units("2,000 kcal", "kJ"), "2,000 kcal = 8368 kj");
+        assert_eq!(units("10 km/h", "mph"), "10 km/h = 6.214 mph");
+        assert_eq!(units("1 h 30", "min"), "1 h 30 = 90 min");
+        assert_eq!(units("1.5 GiB", "MB"), "1.5 GiB = 1610.61 mb");
+        assert_eq!(
+            units("12 lb 15.99999 oz", "st lb"),
+            "12 lb 15.99999 oz = 13 lb"
+        );
+        assert_eq!(
+            units("5 kg", "m"),
+            "error: cannot convert Mass to m (Length)"
+        );
+        assert_eq!(units("5 furlongs", "m"), "error: unknown unit 'furlongs'");
+        assert_eq!(units("5", "m"), "error: give a unit for 5");
+        assert_eq!(units("20 C 4", "F"), "error: give a unit for 4 after °C");
+    }
+
+    #[tokio::test]
+    async fn converts_currency_with_cached_rates() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/latest/USD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "result": "success",
+                "base_code": "USD",
+                "time_last_update_utc": "Fri, 16 Oct 2026 00:02:31 +0000",
+                "rates": {"USD": 1, "INR": 83.125, "GBP": 0.79}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let tool =
+            ConvertTool::with_rates_url(web_client().unwrap(), &format!("{}/latest", server.uri()));
+
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"quantity": "80 USD", "to": "INR"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "80 USD = 6650 INR (1 USD = 83.125 INR; rates as of Fri, 16 Oct 2026 00:02:31 +0000)"
+        );
+        // Second lookup is served from the cache (the mock expects one call).
+        let res = tool
+            .execute(&ctx(), &serde_json::json!({"quantity": "$10", "to": "gbp"}))
+            .await;
+        assert!(
+            res.for_llm.starts_with("10 USD = 7.9 GBP"),
+            "{}",
+            res.for_llm
+        );
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"quantity": "5 usd", "to": "XYZ"}),
+            )
+            .await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("no exchange rate for XYZ"));
+    }
+}
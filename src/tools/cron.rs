@@ -1,17 +1,24 @@
-//! Cron tool: add, list, remove, enable, disable; store in workspace/cron/jobs.json.
+//! Cron tool: add, update, list, remove, enable, disable, pause_all, resume_all, history,
+//! export, import; store in workspace/cron/jobs.json, with each job's runs in the brain db
+//! (`cron_runs`).
 //! Cron expression parser (5-field) and CronStore shared with cron_runner.
 
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc};
 
+use crate::memory::db::{BrainDb, CronRun};
+use crate::telegram::Allowlist;
 use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
 use crate::tools::registry::{BoxFuture, Tool};
 use crate::tools::result::ToolResult;
+use crate::tools::time::{self, Zone};
 use crate::workspace;
 
 // --- Data types ---
@@ -28,6 +35,155 @@ pub struct CronJob {
     pub created_at: u64,
     pub last_run: Option<u64>,
     pub next_run: Option<u64>,
+    /// Countdown timer from the `timer` tool (label = its name); removed once it fires.
+    #[serde(default)]
+    pub timer: bool,
+    /// Seconds an agent run of this job may take; None uses `[cron] agent-timeout-seconds`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+    /// Recipient chats whose last agent run failed; each is retried on its own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retries: Vec<Retry>,
+    /// Disabled by `pause_all` (not by hand), so `resume_all` turns it back on.
+    #[serde(default)]
+    pub paused: bool,
+    /// What an agent firing does while an earlier run of the job is still going.
+    #[serde(default)]
+    pub overlap: Overlap,
+    /// Agent runs handed to the main loop and not finished yet (not persisted).
+    #[serde(skip)]
+    pub running: u32,
+    /// A firing held back by [`Overlap::Queue`], run once the job is idle (not persisted).
+    #[serde(skip)]
+    pub queued: bool,
+    /// Chats the job is sent to; by default only `chat_id`, the one it was created from.
+    #[serde(default, skip_serializing_if = "Targets::is_origin")]
+    pub targets: Targets,
+}
+
+/// Failed agent runs of a job in one recipient chat.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Retry {
+    pub chat_id: i64,
+    /// Runs that failed in a row; the entry goes on a success or when retries run out.
+    pub failures: u32,
+    /// When the retry fires, outside the schedule; None once it has fired.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<u64>,
+}
+
+/// Who a job is sent to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Targets {
+    /// The chat the job was created from.
+    #[default]
+    Origin,
+    /// These chats (private chat IDs are user IDs).
+    Chats(Vec<i64>),
+    /// Everyone on the Telegram allow-list (the origin chat when the list is open).
+    AllAllowed,
+}
+
+impl Targets {
+    pub fn is_origin(&self) -> bool {
+        *self == Targets::Origin
+    }
+
+    /// "chats 1, 2", "all allowed users" or "this chat".
+    pub fn describe(&self) -> String {
+        match self {
+            Targets::Origin => "this chat".to_string(),
+            Targets::Chats(ids) => {
+                let ids: Vec<String> = ids.iter().map(i64::to_string).collect();
+                format!("chats {}", ids.join(", "))
+            }
+            Targets::AllAllowed => "all allowed users".to_string(),
+        }
+    }
+}
+
+impl CronJob {
+    /// True when the schedule itself is due, not only a retry or a queued firing.
+    pub fn scheduled(&self, now: u64) -> bool {
+        self.enabled && self.next_run.is_some_and(|n| n <= now)
+    }
+
+    fn retry_due(&self, now: u64) -> bool {
+        self.retries
+            .iter()
+            .any(|r| r.at.is_some_and(|at| at <= now))
+    }
+
+    /// Chats an agent firing at `now` runs in: every recipient when the schedule or a
+    /// queued firing is due, otherwise only the chats whose retry is.
+    pub fn due_chats(&self, now: u64, allowlist: Option<&Allowlist>) -> Vec<i64> {
+        if self.scheduled(now) || (self.queued && self.running == 0) {
+            return self.recipients(allowlist);
+        }
+        self.retries
+            .iter()
+            .filter(|r| r.at.is_some_and(|at| at <= now))
+            .map(|r| r.chat_id)
+            .collect()
+    }
+
+    /// Chats a firing of this job goes to, without repeats. Listed chats no longer on
+    /// `allowlist` are left out.
+    pub fn recipients(&self, allowlist: Option<&Allowlist>) -> Vec<i64> {
+        let mut ids = match &self.targets {
+            Targets::Origin => vec![self.chat_id],
+            Targets::Chats(ids) => match allowlist {
+                Some(list) => {
+                    let members = list.members();
+                    ids.iter()
+                        .copied()
+                        .filter(|id| members.contains(id))
+                        .collect()
+                }
+                None => ids.clone(),
+            },
+            Targets::AllAllowed => allowlist.map(Allowlist::members).unwrap_or_default(),
+        };
+        let mut seen = std::collections::HashSet::new();
+        ids.retain(|id| seen.insert(*id));
+        if ids.is_empty() {
+            ids.push(self.chat_id);
+        }
+        ids
+    }
+}
+
+/// Overlap policy of an agent job.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overlap {
+    /// Drop the firing (recorded as a skipped run).
+    #[default]
+    Skip,
+    /// Run it once the earlier run finishes; further firings meanwhile fold into it.
+    Queue,
+    /// Start another run alongside.
+    RunAnyway,
+}
+
+impl Overlap {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "skip" => Some(Overlap::Skip),
+            "queue" => Some(Overlap::Queue),
+            "run_anyway" => Some(Overlap::RunAnyway),
+            _ => None,
+        }
+    }
+}
+
+/// Whether an agent firing may start, from [`CronStore::start_run`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStart {
+    Started,
+    Skipped,
+    Queued,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -37,12 +193,94 @@ pub enum JobAction {
     Direct,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl JobAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobAction::Agent => "agent",
+            JobAction::Direct => "direct",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Schedule {
-    Once { at_unix: u64 },
-    Interval { every_seconds: u64 },
-    Cron { expr: String },
+    Once {
+        at_unix: u64,
+    },
+    /// `jitter_seconds` moves each firing up to that far either side of `every_seconds`, so
+    /// polling jobs don't all hit the network in the same second.
+    Interval {
+        every_seconds: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        jitter_seconds: Option<u64>,
+    },
+    /// `tz` (IANA name) reads the fields as wall-clock time there; absent means UTC.
+    Cron {
+        expr: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tz: Option<String>,
+    },
+}
+
+/// Fields of a job to change with [`CronStore::update`]; `None` keeps the current value.
+#[derive(Debug, Clone, Default)]
+pub struct JobUpdate {
+    pub label: Option<String>,
+    pub message: Option<String>,
+    pub action: Option<JobAction>,
+    pub schedule: Option<Schedule>,
+    /// `Some(0)` goes back to the configured default.
+    pub timeout_seconds: Option<u64>,
+    pub overlap: Option<Overlap>,
+    pub targets: Option<Targets>,
+}
+
+/// Where `export` writes and `import` reads by default, relative to the workspace.
+pub const EXPORT_PATH: &str = "cron/export.json";
+
+/// Version of the [`JobExport`] format written by this build.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Jobs written by `export` for `import` on another install.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobExport {
+    pub version: u32,
+    pub exported_at: u64,
+    pub jobs: Vec<PortableJob>,
+}
+
+/// What a job does and when, without its ID, chat or run state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableJob {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub message: String,
+    pub action: JobAction,
+    pub schedule: Schedule,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+    #[serde(default)]
+    pub overlap: Overlap,
+    /// `Origin` means the chat that imports it.
+    #[serde(default, skip_serializing_if = "Targets::is_origin")]
+    pub targets: Targets,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Outcome of [`CronStore::import`].
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub added: Vec<CronJob>,
+    /// Jobs already here with the same message, action and schedule.
+    pub duplicates: usize,
+    /// Jobs that could not be added (e.g. a one-shot whose time has passed), with why.
+    pub failed: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -241,6 +479,25 @@ pub fn next_match(expr: &CronExpr, after_unix: u64) -> Option<u64> {
     None
 }
 
+/// Like [`next_match`], with the fields read as wall-clock time in `tz`. A time skipped when
+/// the clocks go forward does not fire; one repeated when they go back fires once.
+pub fn next_match_in(expr: &CronExpr, after_unix: u64, tz: Tz) -> Option<u64> {
+    let after = DateTime::from_timestamp(after_unix.min(i64::MAX as u64) as i64, 0)?;
+    // Search local time as if it were UTC, then map each candidate back to an instant.
+    let mut local = after.with_timezone(&tz).naive_local().and_utc().timestamp();
+    for _ in 0..1_000 {
+        let candidate = next_match(expr, local.max(0) as u64)?;
+        let naive = DateTime::from_timestamp(candidate as i64, 0)?.naive_utc();
+        if let Some(at) = tz.from_local_datetime(&naive).earliest()
+            && at.timestamp() > after_unix as i64
+        {
+            return Some(at.timestamp() as u64);
+        }
+        local = candidate as i64;
+    }
+    None
+}
+
 fn next_matching_month(dt: DateTime<Utc>, expr: &CronExpr) -> Option<DateTime<Utc>> {
     let mut y = dt.year();
     let mut m = dt.month() as u8;
@@ -276,6 +533,15 @@ fn next_hour_in_expr(dt: DateTime<Utc>, expr: &CronExpr) -> (NaiveDate, u8) {
     }
 }
 
+/// `every` seconds moved by a random amount within `jitter` either way.
+fn jittered(every: u64, jitter: u64) -> u64 {
+    if jitter == 0 {
+        return every;
+    }
+    let offset = (crate::telegram::jitter_rand01() * (2 * jitter + 1) as f64) as u64;
+    (every + offset.min(2 * jitter)).saturating_sub(jitter)
+}
+
 impl Schedule {
     pub fn next_fire_after(&self, after_unix: u64) -> Option<u64> {
         match self {
@@ -286,10 +552,19 @@ impl Schedule {
                     None
                 }
             }
-            Schedule::Interval { every_seconds } => Some(after_unix + every_seconds),
-            Schedule::Cron { expr } => parse_cron_expr(expr)
+            Schedule::Interval {
+                every_seconds,
+                jitter_seconds,
+            } => Some(after_unix + jittered(*every_seconds, jitter_seconds.unwrap_or(0))),
+            Schedule::Cron { expr, tz: None } => parse_cron_expr(expr)
                 .ok()
                 .and_then(|e| next_match(&e, after_unix)),
+            Schedule::Cron { expr, tz: Some(tz) } => {
+                let tz: Tz = tz.parse().ok()?;
+                parse_cron_expr(expr)
+                    .ok()
+                    .and_then(|e| next_match_in(&e, after_unix, tz))
+            }
         }
     }
 }
@@ -300,8 +575,13 @@ pub struct CronStore {
     jobs: RwLock<Vec<CronJob>>,
     jobs_path: std::path::PathBuf,
     next_id: AtomicU64,
+    /// Where runs are recorded; None keeps no history.
+    history: Option<Arc<BrainDb>>,
 }
 
+/// Characters of a reply (or error) kept with each recorded run.
+pub const RUN_DETAIL_CHARS: usize = 200;
+
 fn unix_now() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -380,6 +660,7 @@ impl CronStore {
             jobs: RwLock::new(jobs),
             jobs_path,
             next_id: AtomicU64::new(next_id),
+            history: None,
         })
     }
 
@@ -388,9 +669,50 @@ impl CronStore {
             jobs: RwLock::new(Vec::new()),
             jobs_path: workspace::cron_jobs_file(workspace),
             next_id: AtomicU64::new(1),
+            history: None,
+        }
+    }
+
+    /// Record each job's runs in `db` (`cron_runs`), for the `history` action.
+    pub fn with_history(mut self, db: Arc<BrainDb>) -> Self {
+        self.history = Some(db);
+        self
+    }
+
+    /// Record one firing of job `id`. `detail` is the reply or error, cut to
+    /// [`RUN_DETAIL_CHARS`]. Failures to record are logged, not returned.
+    pub fn record_run(&self, id: &str, ran_at: u64, action: JobAction, ok: bool, detail: &str) {
+        let Some(db) = &self.history else {
+            return;
+        };
+        let detail = detail.trim();
+        let mut snippet: String = detail.chars().take(RUN_DETAIL_CHARS).collect();
+        if snippet.len() < detail.len() {
+            snippet.push('…');
+        }
+        let run = CronRun {
+            id: 0,
+            job_id: id.to_string(),
+            ran_at: ran_at as i64,
+            action: action.as_str().to_string(),
+            ok,
+            detail: snippet,
+        };
+        if let Err(e) = db.record_cron_run(&run) {
+            eprintln!("cron: recording run of {}: {}", id, e);
         }
     }
 
+    /// The latest `limit` runs of job `id`, newest first.
+    pub fn history(&self, id: &str, limit: usize) -> Result<Vec<CronRun>, CronError> {
+        let db = self
+            .history
+            .as_ref()
+            .ok_or_else(|| CronError::Validation("run history is not enabled".into()))?;
+        db.cron_runs(id, limit)
+            .map_err(|e| CronError::Io(e.to_string()))
+    }
+
     pub fn add(
         &self,
         label: Option<String>,
@@ -399,15 +721,47 @@ impl CronStore {
         schedule: Schedule,
         chat_id: i64,
     ) -> Result<CronJob, CronError> {
-        if let Schedule::Interval { every_seconds } = &schedule {
+        self.insert(label, message, action, schedule, chat_id, false)
+    }
+
+    /// Add a countdown timer: a one-shot direct message at `at_unix`, dropped after it fires.
+    pub fn add_timer(
+        &self,
+        name: String,
+        message: String,
+        at_unix: u64,
+        chat_id: i64,
+    ) -> Result<CronJob, CronError> {
+        let schedule = Schedule::Once { at_unix };
+        self.insert(
+            Some(name),
+            message,
+            JobAction::Direct,
+            schedule,
+            chat_id,
+            true,
+        )
+    }
+
+    /// When a job on `schedule` first fires after `now`; errors for schedules that never do.
+    fn first_run(schedule: &Schedule, now: u64) -> Result<Option<u64>, CronError> {
+        if let Schedule::Interval {
+            every_seconds,
+            jitter_seconds,
+        } = schedule
+        {
             if *every_seconds < 60 {
                 return Err(CronError::Validation(
                     "interval must be at least 60 seconds".into(),
                 ));
             }
+            if jitter_seconds.is_some_and(|j| j >= *every_seconds) {
+                return Err(CronError::Validation(
+                    "jitter must be shorter than the interval".into(),
+                ));
+            }
         }
-        let now = unix_now();
-        let next_run = match &schedule {
+        let next_run = match schedule {
             Schedule::Once { at_unix } => {
                 if *at_unix <= now {
                     return Err(CronError::Validation(
@@ -418,11 +772,25 @@ impl CronStore {
             }
             _ => schedule.next_fire_after(now),
         };
-        if matches!(&schedule, Schedule::Cron { .. }) && next_run.is_none() {
+        if matches!(schedule, Schedule::Cron { .. }) && next_run.is_none() {
             return Err(CronError::Validation(
                 "cron expression has no upcoming matches".into(),
             ));
         }
+        Ok(next_run)
+    }
+
+    fn insert(
+        &self,
+        label: Option<String>,
+        message: String,
+        action: JobAction,
+        schedule: Schedule,
+        chat_id: i64,
+        timer: bool,
+    ) -> Result<CronJob, CronError> {
+        let now = unix_now();
+        let next_run = Self::first_run(&schedule, now)?;
         let id = format!("job-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
         let job = CronJob {
             id: id.clone(),
@@ -435,6 +803,14 @@ impl CronStore {
             created_at: now,
             last_run: None,
             next_run,
+            timer,
+            timeout_seconds: None,
+            retries: Vec::new(),
+            paused: false,
+            overlap: Overlap::default(),
+            running: 0,
+            queued: false,
+            targets: Targets::Origin,
         };
         {
             let mut guard = self.jobs.write().expect("cron lock");
@@ -455,11 +831,54 @@ impl CronStore {
         }
     }
 
+    /// Change fields of a job in place, keeping its id and run history. A new schedule is
+    /// checked like a new job's, and an enabled job's next run is recomputed from it.
+    /// Returns the updated job; None when there is no such job.
+    pub fn update(&self, id: &str, changes: JobUpdate) -> Result<Option<CronJob>, CronError> {
+        let now = unix_now();
+        let next_run = match &changes.schedule {
+            Some(schedule) => Some(Self::first_run(schedule, now)?),
+            None => None,
+        };
+        let mut guard = self.jobs.write().expect("cron lock");
+        let Some(job) = guard.iter_mut().find(|j| j.id == id) else {
+            return Ok(None);
+        };
+        if let Some(label) = changes.label {
+            job.label = Some(label);
+        }
+        if let Some(message) = changes.message {
+            job.message = message;
+        }
+        if let Some(action) = changes.action {
+            job.action = action;
+        }
+        if let Some(secs) = changes.timeout_seconds {
+            job.timeout_seconds = (secs > 0).then_some(secs);
+        }
+        if let Some(overlap) = changes.overlap {
+            job.overlap = overlap;
+        }
+        if let Some(targets) = changes.targets {
+            job.targets = targets;
+        }
+        if let (Some(schedule), Some(next_run)) = (changes.schedule, next_run) {
+            job.schedule = schedule;
+            if job.enabled {
+                job.next_run = next_run;
+            }
+        }
+        let job = job.clone();
+        Self::save_inner(&guard, &self.jobs_path)?;
+        Ok(Some(job))
+    }
+
     pub fn enable(&self, id: &str) -> bool {
         let now = unix_now();
         let mut guard = self.jobs.write().expect("cron lock");
         if let Some(j) = guard.iter_mut().find(|x| x.id == id) {
             j.enabled = true;
+            j.paused = false;
             j.next_run = j.schedule.next_fire_after(now);
             let _ = Self::save_inner(&guard, &self.jobs_path);
             true
@@ -472,7 +891,10 @@ impl CronStore {
         let mut guard = self.jobs.write().expect("cron lock");
         if let Some(j) = guard.iter_mut().find(|x| x.id == id) {
             j.enabled = false;
+            j.paused = false;
             j.next_run = None;
+            j.retries.clear();
+            j.queued = false;
             let _ = Self::save_inner(&guard, &self.jobs_path);
             true
         } else {
@@ -480,6 +902,46 @@ impl CronStore {
         }
     }
 
+    /// Disable every enabled job of `chat` (None: of every chat; timers keep running), marking
+    /// them paused. Returns how many.
+    pub fn pause_all(&self, chat: Option<i64>) -> Result<usize, CronError> {
+        let mut guard = self.jobs.write().expect("cron lock");
+        let mut count = 0;
+        for j in guard
+            .iter_mut()
+            .filter(|x| x.enabled && !x.timer && in_scope(x, chat))
+        {
+            j.enabled = false;
+            j.paused = true;
+            j.next_run = None;
+            j.retries.clear();
+            count += 1;
+        }
+        Self::save_inner(&guard, &self.jobs_path)?;
+        Ok(count)
+    }
+
+    /// Re-enable the jobs of `chat` (None: of every chat) that `pause_all` disabled, leaving
+    /// ones disabled by hand alone. Returns how many resumed and how many one-shot jobs stay
+    /// off because their time passed.
+    pub fn resume_all(&self, chat: Option<i64>) -> Result<(usize, usize), CronError> {
+        let now = unix_now();
+        let mut guard = self.jobs.write().expect("cron lock");
+        let (mut resumed, mut missed) = (0, 0);
+        for j in guard.iter_mut().filter(|x| x.paused && in_scope(x, chat)) {
+            j.paused = false;
+            j.next_run = j.schedule.next_fire_after(now);
+            j.enabled = j.next_run.is_some();
+            if j.enabled {
+                resumed += 1;
+            } else {
+                missed += 1;
+            }
+        }
+        Self::save_inner(&guard, &self.jobs_path)?;
+        Ok((resumed, missed))
+    }
+
     pub fn list(&self) -> Vec<CronJob> {
         self.jobs.read().expect("cron lock").clone()
     }
@@ -498,38 +960,377 @@ impl CronStore {
             .read()
             .expect("cron lock")
             .iter()
-            .filter(|j| j.enabled && j.next_run.map_or(false, |n| n <= now))
+            .filter(|j| j.scheduled(now) || j.retry_due(now) || (j.queued && j.running == 0))
             .cloned()
             .collect()
     }
 
     pub fn mark_fired(&self, id: &str, now: u64) {
         let mut guard = self.jobs.write().expect("cron lock");
-        if let Some(j) = guard.iter_mut().find(|x| x.id == id) {
+        if let Some(pos) = guard.iter().position(|x| x.id == id && x.timer) {
+            guard.remove(pos);
+            let _ = Self::save_inner(&guard, &self.jobs_path);
+        } else if let Some(j) = guard.iter_mut().find(|x| x.id == id) {
+            // A scheduled firing runs every chat, so it stands in for the pending retries.
+            let scheduled = j.scheduled(now);
+            let mut retried = false;
+            for r in &mut j.retries {
+                if scheduled || r.at.is_some_and(|at| at <= now) {
+                    retried |= r.at.take().is_some();
+                }
+            }
+            // A retry or queued firing on its own leaves the schedule where it is.
+            if !scheduled {
+                if retried {
+                    let _ = Self::save_inner(&guard, &self.jobs_path);
+                }
+                return;
+            }
             j.last_run = Some(now);
             j.next_run = match &j.schedule {
                 Schedule::Once { .. } => {
                     j.enabled = false;
                     None
                 }
-                Schedule::Interval { every_seconds } => Some(now + every_seconds),
-                Schedule::Cron { .. } => j.schedule.next_fire_after(now),
+                Schedule::Interval { .. } | Schedule::Cron { .. } => {
+                    j.schedule.next_fire_after(now)
+                }
+            };
+            let _ = Self::save_inner(&guard, &self.jobs_path);
+        }
+    }
+
+    /// Every job of `chat` (None: of every chat) except timers, for moving to another install.
+    /// Paused jobs count as enabled.
+    pub fn export(&self, chat: Option<i64>) -> JobExport {
+        let jobs = self
+            .jobs
+            .read()
+            .expect("cron lock")
+            .iter()
+            .filter(|j| !j.timer && in_scope(j, chat))
+            .map(|j| PortableJob {
+                label: j.label.clone(),
+                message: j.message.clone(),
+                action: j.action,
+                schedule: j.schedule.clone(),
+                enabled: j.enabled || j.paused,
+                timeout_seconds: j.timeout_seconds,
+                overlap: j.overlap,
+                targets: j.targets.clone(),
+            })
+            .collect();
+        JobExport {
+            version: EXPORT_VERSION,
+            exported_at: unix_now(),
+            jobs,
+        }
+    }
+
+    /// Add the exported jobs for `chat_id` under new IDs, skipping ones it already has.
+    pub fn import(&self, export: JobExport, chat_id: i64) -> Result<ImportReport, CronError> {
+        if export.version > EXPORT_VERSION {
+            return Err(CronError::Validation(format!(
+                "export version {} is newer than this build supports ({})",
+                export.version, EXPORT_VERSION
+            )));
+        }
+        let mut report = ImportReport::default();
+        for p in export.jobs {
+            let present = self.jobs.read().expect("cron lock").iter().any(|j| {
+                j.chat_id == chat_id
+                    && j.message == p.message
+                    && j.action == p.action
+                    && j.schedule == p.schedule
+            });
+            if present {
+                report.duplicates += 1;
+                continue;
+            }
+            let name = p.label.clone().unwrap_or_else(|| p.message.clone());
+            let job = match self.add(p.label, p.message, p.action, p.schedule, chat_id) {
+                Ok(job) => job,
+                Err(e) => {
+                    report.failed.push(format!("{}: {}", name, e));
+                    continue;
+                }
             };
+            if p.timeout_seconds.is_some()
+                || p.overlap != Overlap::default()
+                || !p.targets.is_origin()
+            {
+                let changes = JobUpdate {
+                    timeout_seconds: p.timeout_seconds,
+                    overlap: Some(p.overlap),
+                    targets: Some(p.targets),
+                    ..Default::default()
+                };
+                self.update(&job.id, changes)?;
+            }
+            if !p.enabled {
+                self.disable(&job.id);
+            }
+            report.added.extend(self.get(&job.id));
+        }
+        Ok(report)
+    }
+
+    /// Claim `runs` agent runs of job `id` (one per recipient chat) under its overlap
+    /// policy. Each started run must be released with [`CronStore::finish_run`].
+    pub fn start_run(&self, id: &str, runs: u32) -> RunStart {
+        let mut guard = self.jobs.write().expect("cron lock");
+        let Some(j) = guard.iter_mut().find(|x| x.id == id) else {
+            return RunStart::Skipped;
+        };
+        if j.running > 0 {
+            match j.overlap {
+                Overlap::Skip => return RunStart::Skipped,
+                Overlap::Queue => {
+                    j.queued = true;
+                    return RunStart::Queued;
+                }
+                Overlap::RunAnyway => {}
+            }
+        }
+        j.queued = false;
+        j.running += runs;
+        RunStart::Started
+    }
+
+    /// An agent run of job `id` ended (done, failed or never delivered).
+    pub fn finish_run(&self, id: &str) {
+        let mut guard = self.jobs.write().expect("cron lock");
+        if let Some(j) = guard.iter_mut().find(|x| x.id == id) {
+            j.running = j.running.saturating_sub(1);
+        }
+    }
+
+    /// After a failed agent run of job `id` in `chat_id`: schedule a retry in that chat only,
+    /// `backoff_secs` after `now` and doubling with each failure there in a row, and return
+    /// when it fires. None once `max_retries` retries have failed too (the count starts over)
+    /// or the job is gone.
+    pub fn schedule_retry(
+        &self,
+        id: &str,
+        chat_id: i64,
+        now: u64,
+        max_retries: u32,
+        backoff_secs: u64,
+    ) -> Option<u64> {
+        let mut guard = self.jobs.write().expect("cron lock");
+        let j = guard.iter_mut().find(|x| x.id == id)?;
+        let pos = match j.retries.iter().position(|r| r.chat_id == chat_id) {
+            Some(pos) => pos,
+            None => {
+                j.retries.push(Retry {
+                    chat_id,
+                    failures: 0,
+                    at: None,
+                });
+                j.retries.len() - 1
+            }
+        };
+        let retry = &mut j.retries[pos];
+        retry.failures += 1;
+        let at = if retry.failures > max_retries {
+            j.retries.remove(pos);
+            None
+        } else {
+            let delay = backoff_secs.saturating_mul(1 << (retry.failures - 1).min(16));
+            retry.at = Some(now.saturating_add(delay));
+            retry.at
+        };
+        let _ = Self::save_inner(&guard, &self.jobs_path);
+        at
+    }
+
+    /// After a successful agent run of job `id` in `chat_id`: forget its earlier failures there.
+    pub fn clear_failures(&self, id: &str, chat_id: i64) {
+        let mut guard = self.jobs.write().expect("cron lock");
+        if let Some(j) = guard.iter_mut().find(|x| x.id == id)
+            && let Some(pos) = j.retries.iter().position(|r| r.chat_id == chat_id)
+        {
+            j.retries.remove(pos);
             let _ = Self::save_inner(&guard, &self.jobs_path);
         }
     }
 }
 
+/// True when `job` was created from `chat`, or `chat` is None (every chat).
+fn in_scope(job: &CronJob, chat: Option<i64>) -> bool {
+    chat.is_none_or(|id| job.chat_id == id)
+}
+
 // --- CronTool ---
 
 pub struct CronTool {
     store: Arc<CronStore>,
+    timezone: Zone,
+    allowlist: Option<Arc<Allowlist>>,
 }
 
 impl CronTool {
     #[inline]
     pub fn new(store: Arc<CronStore>) -> Self {
-        Self { store }
+        Self {
+            store,
+            timezone: Zone::Named(chrono_tz::UTC),
+            allowlist: None,
+        }
+    }
+
+    /// Allow-list that `chat_ids` and `all_allowed_users` are checked against. Without one,
+    /// jobs only go to the chat they were created from.
+    pub fn with_allowlist(mut self, allowlist: Arc<Allowlist>) -> Self {
+        self.allowlist = Some(allowlist);
+        self
+    }
+
+    /// Zone of `at` times that name none ("9am" rather than "9am PST"); default UTC.
+    pub fn with_timezone(mut self, timezone: chrono_tz::Tz) -> Self {
+        self.timezone = Zone::Named(timezone);
+        self
+    }
+}
+
+/// The schedule a tool call describes with `schedule_type` and its fields.
+fn schedule_from_args(args: &Value, timezone: &Zone) -> Result<Schedule, String> {
+    match args.get("schedule_type").and_then(Value::as_str) {
+        Some("once") => {
+            let at = args.get("at").and_then(Value::as_str);
+            let at_unix = args.get("at_unix").and_then(Value::as_i64);
+            let delay = args.get("delay").and_then(Value::as_str);
+            let at_unix = match (at, at_unix, delay) {
+                (None, Some(t), None) => t as u64,
+                (None, None, Some(d)) => {
+                    let secs = parse_delay(d).map_err(|e| e.to_string())?;
+                    unix_now().saturating_add(secs)
+                }
+                (Some(when), None, None) => time::next_at(when, timezone, Utc::now())?
+                    .timestamp()
+                    .max(0) as u64,
+                (None, None, None) => {
+                    return Err("once requires 'at' (e.g. '9am PST'), 'at_unix' or 'delay' (e.g. '30m', '2h')".into());
+                }
+                _ => {
+                    return Err(
+                        "once accepts only one of 'at', 'at_unix' or 'delay', not both".into(),
+                    );
+                }
+            };
+            Ok(Schedule::Once { at_unix })
+        }
+        Some("interval") => {
+            let every = args
+                .get("every_seconds")
+                .and_then(Value::as_i64)
+                .ok_or("interval requires 'every_seconds' (min 60)")?;
+            if every < 60 {
+                return Err("every_seconds must be at least 60".into());
+            }
+            let jitter = args
+                .get("jitter_seconds")
+                .and_then(Value::as_u64)
+                .filter(|&j| j > 0);
+            if jitter.is_some_and(|j| j >= every as u64) {
+                return Err("jitter_seconds must be less than every_seconds".into());
+            }
+            Ok(Schedule::Interval {
+                every_seconds: every as u64,
+                jitter_seconds: jitter,
+            })
+        }
+        Some("cron") => {
+            let expr = args
+                .get("cron_expr")
+                .and_then(Value::as_str)
+                .ok_or("cron requires 'cron_expr'")?;
+            if parse_cron_expr(expr).is_err() {
+                return Err("invalid cron expression".into());
+            }
+            Ok(Schedule::Cron {
+                expr: expr.to_string(),
+                tz: None,
+            })
+        }
+        _ => Err("'schedule_type' must be once, interval, or cron".into()),
+    }
+}
+
+/// Recipients from `chat_ids` / `all_allowed_users`, if either is given. An empty
+/// `chat_ids` (or `all_allowed_users: false` alone) goes back to the origin chat.
+fn targets_from_args(args: &Value) -> Option<Targets> {
+    if args.get("all_allowed_users").and_then(Value::as_bool) == Some(true) {
+        return Some(Targets::AllAllowed);
+    }
+    match args.get("chat_ids").and_then(Value::as_array) {
+        Some(ids) => {
+            let ids: Vec<i64> = ids.iter().filter_map(Value::as_i64).collect();
+            Some(if ids.is_empty() {
+                Targets::Origin
+            } else {
+                Targets::Chats(ids)
+            })
+        }
+        None if args.get("all_allowed_users").is_some() => Some(Targets::Origin),
+        None => None,
+    }
+}
+
+/// Whether `chat_id` may send a job to `targets`. Any chat but the current one is owner-only
+/// (private chats share the user's ID, so the owner check is on the chat), and listed chats
+/// must be on the allow-list.
+fn check_targets(
+    targets: &Targets,
+    chat_id: Option<i64>,
+    allowlist: Option<&Allowlist>,
+) -> Result<(), String> {
+    if targets.is_origin() {
+        return Ok(());
+    }
+    let Some(allowlist) = allowlist else {
+        return Err("jobs can only be sent to this chat here".into());
+    };
+    if !chat_id.is_some_and(|id| allowlist.is_owner(id)) {
+        return Err(
+            "only owners may send jobs to other chats (use chat_ids or all_allowed_users from an owner's private chat)"
+                .into(),
+        );
+    }
+    if let Targets::Chats(ids) = targets {
+        let members = allowlist.members();
+        let unknown: Vec<String> = ids
+            .iter()
+            .filter(|id| !members.contains(id))
+            .map(i64::to_string)
+            .collect();
+        if !unknown.is_empty() {
+            return Err(format!("not on the allow-list: {}", unknown.join(", ")));
+        }
+    }
+    Ok(())
+}
+
+/// Which jobs `chat_id` may pause, resume, export or import all at once: every chat's for an
+/// owner (None), otherwise only the ones created from it.
+fn bulk_scope(chat_id: Option<i64>, allowlist: Option<&Allowlist>) -> Result<Option<i64>, String> {
+    let Some(chat_id) = chat_id else {
+        return Err("requires chat_id (current chat)".into());
+    };
+    if allowlist.is_some_and(|list| list.is_owner(chat_id)) {
+        Ok(None)
+    } else {
+        Ok(Some(chat_id))
+    }
+}
+
+/// The `overlap` argument, if given.
+fn overlap_from_args(args: &Value) -> Result<Option<Overlap>, String> {
+    match args.get("overlap").and_then(Value::as_str) {
+        Some(s) => Overlap::parse(s)
+            .map(Some)
+            .ok_or_else(|| "overlap must be skip, queue or run_anyway".to_string()),
+        None => Ok(None),
     }
 }
 
@@ -539,7 +1340,7 @@ impl Tool for CronTool {
     }
 
     fn description(&self) -> &str {
-        "Manage scheduled jobs: add, list, remove, enable, disable. Jobs fire on schedule—either running the agent with a message or sending directly to Telegram. When both dom and dow are restricted, the job fires only when both match (AND semantics)."
+        "Manage scheduled jobs: add, update (change message, label, schedule or job_action in place, keeping the ID), list, remove, enable, disable, pause_all / resume_all (silence every job, then restore exactly those; jobs disabled by hand stay off; owners act on every chat's jobs, others on this chat's), history (the job's latest runs: when, success, reply snippet), export / import (jobs to or from a JSON file in the workspace, for moving to another install; export covers every chat's jobs for owners, this chat's for others). Jobs fire on schedule—either running the agent with a message or sending directly to Telegram. When both dom and dow are restricted, the job fires only when both match (AND semantics)."
     }
 
     fn parameters(&self) -> Value {
@@ -548,34 +1349,45 @@ impl Tool for CronTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["add", "list", "remove", "enable", "disable"],
+                    "enum": [
+                        "add", "update", "list", "remove", "enable", "disable", "pause_all",
+                        "resume_all", "history", "export", "import"
+                    ],
                     "description": "Action to perform"
                 },
                 "id": {
                     "type": "string",
-                    "description": "Job ID (for remove/enable/disable)"
+                    "description": "Job ID (for update/remove/enable/disable/history)"
                 },
                 "message": {
                     "type": "string",
-                    "description": "Message text for the job (for add)"
+                    "description": "Message text for the job (for add; update to change it)"
                 },
                 "schedule_type": {
                     "type": "string",
                     "enum": ["once", "interval", "cron"],
-                    "description": "Schedule type (for add)"
+                    "description": "Schedule type (for add; update to change the schedule, with its fields below)"
+                },
+                "at": {
+                    "type": "string",
+                    "description": "When to fire, in words (for schedule_type=once). E.g. '9am PST', 'tomorrow 18:30', 'monday 9:00 in Tokyo', '2026-11-02 14:00 Europe/Paris'; without a zone, the user's timezone; a time already past today means tomorrow. Use one of at, at_unix or delay."
                 },
                 "at_unix": {
                     "type": "integer",
-                    "description": "Unix timestamp to fire (for schedule_type=once). Use either at_unix or delay, not both."
+                    "description": "Unix timestamp to fire (for schedule_type=once). Use one of at, at_unix or delay."
                 },
                 "delay": {
                     "type": "string",
-                    "description": "Delay from now for one-shot (for schedule_type=once). E.g. '30m', '2h', '1d', '1w'. Use either delay or at_unix, not both."
+                    "description": "Delay from now for one-shot (for schedule_type=once). E.g. '30m', '2h', '1d', '1w'. Use one of at, at_unix or delay."
                 },
                 "every_seconds": {
                     "type": "integer",
                     "description": "Interval in seconds (for schedule_type=interval, min 60)"
                 },
+                "jitter_seconds": {
+                    "type": "integer",
+                    "description": "For schedule_type=interval: fire up to this many seconds early or late each time, so polling jobs spread out (less than every_seconds)"
+                },
                 "cron_expr": {
                     "type": "string",
                     "description": "5-field cron expression: 'minute hour dom month dow' (for schedule_type=cron). Supports *, N, N-M, N,M, */N, N-M/S."
@@ -583,11 +1395,37 @@ impl Tool for CronTool {
                 "job_action": {
                     "type": "string",
                     "enum": ["agent", "direct"],
-                    "description": "How to execute: 'agent' runs agent loop, 'direct' sends message to chat. Default: direct"
+                    "description": "How to execute: 'agent' runs agent loop, 'direct' sends message to chat. Default: direct (for add/update)"
                 },
                 "label": {
                     "type": "string",
                     "description": "Optional human-readable label"
+                },
+                "timeout_seconds": {
+                    "type": "integer",
+                    "description": "For agent jobs: seconds a run may take before it is cancelled and recorded as failed (for add/update; 0 = configured default)"
+                },
+                "overlap": {
+                    "type": "string",
+                    "enum": ["skip", "queue", "run_anyway"],
+                    "description": "For agent jobs: what a firing does while the previous run is still going — skip it (default), queue one run for when it finishes, or run anyway (for add/update)"
+                },
+                "chat_ids": {
+                    "type": "array",
+                    "items": {"type": "integer"},
+                    "description": "Owner only: send the job to these allowed users' chats instead of only this one, e.g. shared household reminders; include this chat's ID to keep it. [] goes back to this chat (for add/update)"
+                },
+                "all_allowed_users": {
+                    "type": "boolean",
+                    "description": "Owner only: send the job to every user on the bot's allow-list (for add/update)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Runs to show (for history; default 10, max 50)"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Workspace-relative file (for export/import; default cron/export.json)"
                 }
             },
             "required": ["action"]
@@ -596,6 +1434,8 @@ impl Tool for CronTool {
 
     fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
         let store = Arc::clone(&self.store);
+        let timezone = self.timezone;
+        let allowlist = self.allowlist.clone();
         let args = args.clone();
         let ctx = ctx.clone();
 
@@ -614,67 +1454,9 @@ impl Tool for CronTool {
                         Some(m) if !m.is_empty() => m,
                         _ => return ToolResult::error("add requires non-empty 'message'"),
                     };
-                    let schedule_type = args.get("schedule_type").and_then(Value::as_str);
-                    let schedule = match schedule_type {
-                        Some("once") => {
-                            let at_unix_opt = args.get("at_unix").and_then(Value::as_i64);
-                            let delay_opt = args.get("delay").and_then(Value::as_str);
-                            let at_unix = match (at_unix_opt, delay_opt) {
-                                (Some(t), None) => t as u64,
-                                (None, Some(d)) => {
-                                    let secs = match parse_delay(d) {
-                                        Ok(s) => s,
-                                        Err(e) => return ToolResult::error(e.to_string()),
-                                    };
-                                    let now = unix_now();
-                                    now.saturating_add(secs)
-                                }
-                                (None, None) => {
-                                    return ToolResult::error(
-                                        "once requires either 'at_unix' or 'delay' (e.g. '30m', '2h')",
-                                    );
-                                }
-                                (Some(_), Some(_)) => {
-                                    return ToolResult::error(
-                                        "once accepts either 'at_unix' or 'delay', not both",
-                                    );
-                                }
-                            };
-                            Schedule::Once { at_unix }
-                        }
-                        Some("interval") => {
-                            let every = match args.get("every_seconds").and_then(Value::as_i64) {
-                                Some(x) => x,
-                                None => {
-                                    return ToolResult::error(
-                                        "interval requires 'every_seconds' (min 60)",
-                                    );
-                                }
-                            };
-                            if every < 60 {
-                                return ToolResult::error("every_seconds must be at least 60");
-                            }
-                            Schedule::Interval {
-                                every_seconds: every as u64,
-                            }
-                        }
-                        Some("cron") => {
-                            let expr = match args.get("cron_expr").and_then(Value::as_str) {
-                                Some(s) => s,
-                                None => return ToolResult::error("cron requires 'cron_expr'"),
-                            };
-                            if parse_cron_expr(expr).is_err() {
-                                return ToolResult::error("invalid cron expression");
-                            }
-                            Schedule::Cron {
-                                expr: expr.to_string(),
-                            }
-                        }
-                        _ => {
-                            return ToolResult::error(
-                                "add requires 'schedule_type': once, interval, or cron",
-                            );
-                        }
+                    let schedule = match schedule_from_args(&args, &timezone) {
+                        Ok(s) => s,
+                        Err(e) => return ToolResult::error(e),
                     };
                     let job_action = match args.get("job_action").and_then(Value::as_str) {
                         Some("agent") => JobAction::Agent,
@@ -687,13 +1469,104 @@ impl Tool for CronTool {
                             return ToolResult::error("cron add requires chat_id (current chat)");
                         }
                     };
-                    match store.add(label, message, job_action, schedule, chat_id) {
+                    let extras = JobUpdate {
+                        timeout_seconds: args.get("timeout_seconds").and_then(Value::as_u64),
+                        overlap: match overlap_from_args(&args) {
+                            Ok(o) => o,
+                            Err(e) => return ToolResult::error(e),
+                        },
+                        targets: targets_from_args(&args),
+                        ..Default::default()
+                    };
+                    if let Some(targets) = &extras.targets
+                        && let Err(e) = check_targets(targets, ctx.chat_id, allowlist.as_deref())
+                    {
+                        return ToolResult::error(e);
+                    }
+                    let has_extras = extras.timeout_seconds.is_some()
+                        || extras.overlap.is_some()
+                        || extras.targets.is_some();
+                    let added = match store.add(label, message, job_action, schedule, chat_id) {
+                        Ok(job) if has_extras => {
+                            store.update(&job.id, extras).map(|j| j.unwrap_or(job))
+                        }
+                        added => added,
+                    };
+                    match added {
                         Ok(job) => ToolResult::ok(format!(
-                            "Added job {} ({}): next_run={:?}",
+                            "Added job {} ({}): next_run={:?}, sent to {}",
                             job.id,
                             job.label.as_deref().unwrap_or("(no label)"),
+                            job.next_run,
+                            job.targets.describe()
+                        )),
+                        Err(e) => ToolResult::error(e.to_string()),
+                    }
+                }
+                "update" => {
+                    let id = args.get("id").and_then(Value::as_str).unwrap_or("");
+                    if id.is_empty() {
+                        return ToolResult::error("update requires 'id'");
+                    }
+                    let text = |key: &str| {
+                        args.get(key)
+                            .and_then(Value::as_str)
+                            .filter(|s| !s.is_empty())
+                            .map(String::from)
+                    };
+                    let schedule = match args.get("schedule_type") {
+                        Some(_) => match schedule_from_args(&args, &timezone) {
+                            Ok(s) => Some(s),
+                            Err(e) => return ToolResult::error(e),
+                        },
+                        None => None,
+                    };
+                    let changes = JobUpdate {
+                        label: text("label"),
+                        message: text("message"),
+                        action: match args.get("job_action").and_then(Value::as_str) {
+                            Some("agent") => Some(JobAction::Agent),
+                            Some("direct") => Some(JobAction::Direct),
+                            _ => None,
+                        },
+                        schedule,
+                        timeout_seconds: args.get("timeout_seconds").and_then(Value::as_u64),
+                        overlap: match overlap_from_args(&args) {
+                            Ok(o) => o,
+                            Err(e) => return ToolResult::error(e),
+                        },
+                        targets: targets_from_args(&args),
+                    };
+                    if changes.label.is_none()
+                        && changes.message.is_none()
+                        && changes.action.is_none()
+                        && changes.schedule.is_none()
+                        && changes.timeout_seconds.is_none()
+                        && changes.overlap.is_none()
+                        && changes.targets.is_none()
+                    {
+                        return ToolResult::error(
+                            "update needs something to change: message, label, job_action, schedule_type, timeout_seconds, overlap, chat_ids or all_allowed_users",
+                        );
+                    }
+                    // Editing a job that reaches other chats is owner-only too.
+                    let targets = match (&changes.targets, store.get(id)) {
+                        (Some(t), _) => t.clone(),
+                        (None, Some(job)) => job.targets,
+                        (None, None) => Targets::Origin,
+                    };
+                    if let Err(e) = check_targets(&targets, ctx.chat_id, allowlist.as_deref()) {
+                        return ToolResult::error(e);
+                    }
+                    match store.update(id, changes) {
+                        Ok(Some(job)) => ToolResult::ok(format!(
+                            "Updated job {} ({}): enabled={} next_run={:?}",
+                            job.id,
+                            job.label.as_deref().unwrap_or("(no label)"),
+                            job.enabled,
                             job.next_run
                         )),
+                        Ok(None) => ToolResult::error("Job not found."),
                         Err(e) => ToolResult::error(e.to_string()),
                     }
                 }
@@ -711,17 +1584,58 @@ impl Tool for CronTool {
                                 j.message.clone()
                             };
                             format!(
-                                "{} | {} | enabled={} | next_run={:?} | {}",
+                                "{} | {} | enabled={}{} | next_run={:?} | to {} | {}",
                                 j.id,
                                 j.label.as_deref().unwrap_or("(no label)"),
                                 j.enabled,
+                                if j.paused { " (paused)" } else { "" },
                                 j.next_run,
+                                j.targets.describe(),
                                 msg_preview
                             )
                         })
                         .collect();
                     ToolResult::ok(lines.join("\n"))
                 }
+                "history" => {
+                    let id = args.get("id").and_then(Value::as_str).unwrap_or("");
+                    if id.is_empty() {
+                        return ToolResult::error("history requires 'id'");
+                    }
+                    let limit = args
+                        .get("limit")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(10)
+                        .clamp(1, 50) as usize;
+                    let runs = match store.history(id, limit) {
+                        Ok(r) => r,
+                        Err(e) => return ToolResult::error(e.to_string()),
+                    };
+                    if runs.is_empty() {
+                        return ToolResult::ok(format!("No recorded runs for {}.", id));
+                    }
+                    let lines: Vec<String> = runs
+                        .iter()
+                        .map(|r| {
+                            let at = DateTime::from_timestamp(r.ran_at, 0)
+                                .map(|t| timezone.local(t).format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_else(|| r.ran_at.to_string());
+                            let status = if r.ok { "ok" } else { "FAILED" };
+                            if r.detail.is_empty() {
+                                format!("{} | {} | {}", at, r.action, status)
+                            } else {
+                                format!("{} | {} | {} | {}", at, r.action, status, r.detail)
+                            }
+                        })
+                        .collect();
+                    ToolResult::ok(format!(
+                        "Last {} run(s) of {} ({}):\n{}",
+                        runs.len(),
+                        id,
+                        timezone.name(),
+                        lines.join("\n")
+                    ))
+                }
                 "remove" => {
                     let id = args.get("id").and_then(Value::as_str).unwrap_or("");
                     if id.is_empty() {
@@ -746,7 +1660,135 @@ impl Tool for CronTool {
                     let ok = store.disable(id);
                     ToolResult::ok(if ok { "Disabled." } else { "Job not found." })
                 }
-                _ => ToolResult::error("action must be: add, list, remove, enable, disable"),
+                "pause_all" => {
+                    let scope = match bulk_scope(ctx.chat_id, allowlist.as_deref()) {
+                        Ok(scope) => scope,
+                        Err(e) => return ToolResult::error(format!("cron pause_all {}", e)),
+                    };
+                    match store.pause_all(scope) {
+                        Ok(0) => ToolResult::ok("No enabled jobs to pause."),
+                        Ok(n) => ToolResult::ok(format!(
+                            "Paused {} job(s); resume_all turns them back on.",
+                            n
+                        )),
+                        Err(e) => ToolResult::error(e.to_string()),
+                    }
+                }
+                "resume_all" => {
+                    let scope = match bulk_scope(ctx.chat_id, allowlist.as_deref()) {
+                        Ok(scope) => scope,
+                        Err(e) => return ToolResult::error(format!("cron resume_all {}", e)),
+                    };
+                    match store.resume_all(scope) {
+                        Ok((0, 0)) => ToolResult::ok("No paused jobs."),
+                        Ok((n, 0)) => ToolResult::ok(format!("Resumed {} job(s).", n)),
+                        Ok((n, missed)) => ToolResult::ok(format!(
+                            "Resumed {} job(s); {} one-shot job(s) stay disabled, their time passed while paused.",
+                            n, missed
+                        )),
+                        Err(e) => ToolResult::error(e.to_string()),
+                    }
+                }
+                "export" => {
+                    let scope = match bulk_scope(ctx.chat_id, allowlist.as_deref()) {
+                        Ok(scope) => scope,
+                        Err(e) => return ToolResult::error(format!("cron export {}", e)),
+                    };
+                    let rel = args
+                        .get("path")
+                        .and_then(Value::as_str)
+                        .unwrap_or(EXPORT_PATH);
+                    let path =
+                        match resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await {
+                            Ok(p) => p,
+                            Err(e) => return ToolResult::error(e),
+                        };
+                    let export = store.export(scope);
+                    let json = match serde_json::to_string_pretty(&export) {
+                        Ok(j) => j,
+                        Err(e) => return ToolResult::error(e.to_string()),
+                    };
+                    if let Some(parent) = path.parent()
+                        && let Err(e) = tokio::fs::create_dir_all(parent).await
+                    {
+                        return ToolResult::error(format!("export: {}", e));
+                    }
+                    match tokio::fs::write(&path, json).await {
+                        Ok(()) => ToolResult::ok(format!(
+                            "Exported {} job(s) to {}; import it on the other install with \
+                             action=import.",
+                            export.jobs.len(),
+                            rel
+                        )),
+                        Err(e) => ToolResult::error(format!("export: {}", e)),
+                    }
+                }
+                "import" => {
+                    let chat_id = match ctx.chat_id {
+                        Some(id) => id,
+                        None => {
+                            return ToolResult::error(
+                                "cron import requires chat_id (current chat)",
+                            );
+                        }
+                    };
+                    let rel = args
+                        .get("path")
+                        .and_then(Value::as_str)
+                        .unwrap_or(EXPORT_PATH);
+                    let path =
+                        match resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await {
+                            Ok(p) => p,
+                            Err(e) => return ToolResult::error(e),
+                        };
+                    let export: JobExport = match tokio::fs::read_to_string(&path).await {
+                        Ok(s) => match serde_json::from_str(&s) {
+                            Ok(e) => e,
+                            Err(e) => {
+                                return ToolResult::error(format!(
+                                    "{} is not a job export: {}",
+                                    rel, e
+                                ));
+                            }
+                        },
+                        Err(e) => return ToolResult::error(format!("import {}: {}", rel, e)),
+                    };
+                    for job in &export.jobs {
+                        if let Err(e) =
+                            check_targets(&job.targets, ctx.chat_id, allowlist.as_deref())
+                        {
+                            return ToolResult::error(format!("import {}: {}", rel, e));
+                        }
+                    }
+                    let report = match store.import(export, chat_id) {
+                        Ok(r) => r,
+                        Err(e) => return ToolResult::error(e.to_string()),
+                    };
+                    let mut lines = vec![format!("Imported {} job(s).", report.added.len())];
+                    lines.extend(report.added.iter().map(|j| {
+                        format!(
+                            "{} | {} | enabled={} | next_run={:?}",
+                            j.id,
+                            j.label.as_deref().unwrap_or("(no label)"),
+                            j.enabled,
+                            j.next_run
+                        )
+                    }));
+                    if report.duplicates > 0 {
+                        lines.push(format!(
+                            "Skipped {} already scheduled here.",
+                            report.duplicates
+                        ));
+                    }
+                    if !report.failed.is_empty() {
+                        lines.push(format!("Not imported: {}", report.failed.join("; ")));
+                    }
+                    ToolResult::ok(lines.join("\n"))
+                }
+                _ => ToolResult::error(
+                    "action must be: add, update, list, remove, enable, disable, pause_all, \
+                     resume_all, history, export, import",
+                ),
             }
         })
     }
@@ -770,10 +1812,35 @@ mod tests {
 
     #[test]
     fn interval_next_fire() {
-        let s = Schedule::Interval { every_seconds: 300 };
+        let s = Schedule::Interval {
+            every_seconds: 300,
+            jitter_seconds: None,
+        };
         assert_eq!(s.next_fire_after(1000), Some(1300));
     }
 
+    #[test]
+    fn interval_jitter_stays_in_window() {
+        let s = Schedule::Interval {
+            every_seconds: 3600,
+            jitter_seconds: Some(120),
+        };
+        let fires: Vec<u64> = (0..200)
+            .map(|_| s.next_fire_after(1000).unwrap() - 1000)
+            .collect();
+        assert!(fires.iter().all(|d| (3480..=3720).contains(d)));
+        assert!(
+            fires.iter().any(|&d| d != fires[0]),
+            "firings should spread"
+        );
+        let too_wide = Schedule::Interval {
+            every_seconds: 600,
+            jitter_seconds: Some(600),
+        };
+        let err = CronStore::first_run(&too_wide, 1000).unwrap_err();
+        assert!(err.to_string().contains("jitter"));
+    }
+
     #[test]
     fn parse_every_minute() {
         let e = parse_cron_expr("* * * * *").unwrap();
@@ -845,16 +1912,38 @@ mod tests {
     fn cron_next_fire() {
         let s = Schedule::Cron {
             expr: "0 9 * * *".to_string(),
+            tz: None,
         };
         let ref_time = 1739707200u64;
         let next = s.next_fire_after(ref_time);
         assert!(next.is_some());
     }
 
+    #[test]
+    fn cron_in_timezone_follows_local_clock() {
+        let s = Schedule::Cron {
+            expr: "30 7 * * 1-5".to_string(),
+            tz: Some("Europe/London".to_string()),
+        };
+        // Fri 2026-10-23 12:00 UTC: next is Mon 26 Oct 07:30 GMT (clocks went back on the 25th).
+        let friday = 1_792_756_800;
+        assert_eq!(s.next_fire_after(friday), Some(1_792_999_800));
+        // Thu 2026-10-22 06:00 UTC: 07:30 BST that morning is 06:30 UTC.
+        assert_eq!(s.next_fire_after(1_792_648_800), Some(1_792_650_600));
+        // Skipped hour: 01:30 on 2026-03-29 does not exist in London.
+        let s = Schedule::Cron {
+            expr: "30 1 * * *".to_string(),
+            tz: Some("Europe/London".to_string()),
+        };
+        let before = 1_774_742_400; // 2026-03-29 00:00 UTC
+        assert_eq!(s.next_fire_after(before), Some(1_774_830_600)); // 30 Mar 01:30 BST
+    }
+
     #[test]
     fn cron_invalid_expr() {
         let s = Schedule::Cron {
             expr: "bad".to_string(),
+            tz: None,
         };
         assert_eq!(s.next_fire_after(1000), None);
     }
@@ -965,6 +2054,24 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn mark_fired_timer_removes() {
+        let dir = std::env::temp_dir().join("icrab_cron_test_fired_timer");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = CronStore::empty(&dir);
+        let base = unix_now();
+        let job = store
+            .add_timer("tea".into(), "Timer done: tea".into(), base + 20, 1)
+            .unwrap();
+        assert!(job.timer);
+        assert_eq!(job.label.as_deref(), Some("tea"));
+        assert_eq!(store.find_due(base + 20).len(), 1);
+        store.mark_fired(&job.id, base + 20);
+        assert!(store.list().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn add_once_past_returns_error() {
         let dir = std::env::temp_dir().join("icrab_cron_test_past");
@@ -987,6 +2094,183 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn update_keeps_id_and_recomputes_next_run() {
+        let dir = std::env::temp_dir().join("icrab_cron_test_update");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = CronStore::empty(&dir);
+        let base = unix_now();
+        store
+            .add(
+                Some("old".into()),
+                "x".into(),
+                JobAction::Direct,
+                Schedule::Once {
+                    at_unix: base + 100,
+                },
+                1,
+            )
+            .unwrap();
+        let job = store
+            .update(
+                "job-1",
+                JobUpdate {
+                    label: Some("new".into()),
+                    action: Some(JobAction::Agent),
+                    schedule: Some(Schedule::Interval {
+                        every_seconds: 600,
+                        jitter_seconds: None,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.id, "job-1");
+        assert_eq!(job.label.as_deref(), Some("new"));
+        assert_eq!(job.message, "x");
+        assert!(matches!(job.action, JobAction::Agent));
+        assert!(job.next_run.unwrap() >= base + 600);
+        assert_eq!(store.list().len(), 1);
+        let err = store
+            .update(
+                "job-1",
+                JobUpdate {
+                    schedule: Some(Schedule::Interval {
+                        every_seconds: 5,
+                        jitter_seconds: None,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("60 seconds"));
+        let timeout = |secs| JobUpdate {
+            timeout_seconds: Some(secs),
+            ..Default::default()
+        };
+        let job = store.update("job-1", timeout(900)).unwrap().unwrap();
+        assert_eq!(job.timeout_seconds, Some(900));
+        let job = store.update("job-1", timeout(0)).unwrap().unwrap();
+        assert_eq!(job.timeout_seconds, None);
+        assert!(
+            store
+                .update("job-9", JobUpdate::default())
+                .unwrap()
+                .is_none()
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failed_runs_retry_with_backoff_then_give_up() {
+        let dir = std::env::temp_dir().join("icrab_cron_test_retry");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = CronStore::empty(&dir);
+        let base = unix_now();
+        store
+            .add(
+                None,
+                "backup".into(),
+                JobAction::Agent,
+                Schedule::Interval {
+                    every_seconds: 86_400,
+                    jitter_seconds: None,
+                },
+                1,
+            )
+            .unwrap();
+        let next_run = store.get("job-1").unwrap().next_run;
+        assert_eq!(
+            store.schedule_retry("job-1", 1, base, 2, 60),
+            Some(base + 60)
+        );
+        assert!(store.find_due(base + 59).is_empty());
+        assert_eq!(store.find_due(base + 60).len(), 1);
+        // The retry fires without moving the daily schedule.
+        store.mark_fired("job-1", base + 60);
+        let job = store.get("job-1").unwrap();
+        assert_eq!(job.retries[0].at, None);
+        assert_eq!(job.next_run, next_run);
+        assert!(job.last_run.is_none());
+        assert_eq!(
+            store.schedule_retry("job-1", 1, base + 100, 2, 60),
+            Some(base + 220)
+        );
+        store.mark_fired("job-1", base + 220);
+        assert_eq!(store.schedule_retry("job-1", 1, base + 300, 2, 60), None);
+        assert!(store.get("job-1").unwrap().retries.is_empty());
+
+        // Each chat keeps its own count, and a success clears only its own.
+        store.schedule_retry("job-1", 1, base, 2, 60);
+        store.schedule_retry("job-1", 1, base + 60, 2, 60);
+        assert_eq!(
+            store.schedule_retry("job-1", 2, base + 60, 2, 60),
+            Some(base + 120)
+        );
+        store.clear_failures("job-1", 1);
+        let job = store.get("job-1").unwrap();
+        assert_eq!(
+            job.retries,
+            vec![Retry {
+                chat_id: 2,
+                failures: 1,
+                at: Some(base + 120),
+            }]
+        );
+        assert_eq!(job.due_chats(base + 120, None), vec![2]);
+        assert_eq!(store.schedule_retry("job-9", 1, base, 2, 60), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pause_all_and_resume_all_keep_hand_disabled_jobs_off() {
+        let dir = std::env::temp_dir().join("icrab_cron_test_pause_all");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = CronStore::empty(&dir);
+        let base = unix_now();
+        for message in ["daily", "off", "once"] {
+            let schedule = match message {
+                "once" => Schedule::Once {
+                    at_unix: base + 600,
+                },
+                _ => Schedule::Interval {
+                    every_seconds: 3600,
+                    jitter_seconds: None,
+                },
+            };
+            store
+                .add(None, message.into(), JobAction::Direct, schedule, 1)
+                .unwrap();
+        }
+        store
+            .add_timer("tea".into(), "tea".into(), base + 60, 1)
+            .unwrap();
+        store.disable("job-2");
+
+        assert_eq!(store.pause_all(None).unwrap(), 2);
+        assert_eq!(
+            store.find_due(base + 100_000).len(),
+            1,
+            "timers keep running"
+        );
+        assert!(store.get("job-1").unwrap().paused);
+        assert!(!store.get("job-2").unwrap().paused);
+        // The one-shot's time passes during the holiday.
+        store.jobs.write().unwrap()[2].schedule = Schedule::Once { at_unix: 1 };
+
+        assert_eq!(store.resume_all(None).unwrap(), (1, 1));
+        let daily = store.get("job-1").unwrap();
+        assert!(daily.enabled && !daily.paused && daily.next_run.is_some());
+        assert!(!store.get("job-2").unwrap().enabled);
+        assert!(!store.get("job-3").unwrap().enabled);
+        assert_eq!(store.resume_all(None).unwrap(), (0, 0));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn parse_delay_accepts_units() {
         assert_eq!(parse_delay("30s").unwrap(), 30);
@@ -1006,12 +2290,8 @@ mod tool_tests {
 
     fn empty_ctx(chat_id: Option<i64>) -> ToolCtx {
         ToolCtx {
-            workspace: std::env::temp_dir(),
-            restrict_to_workspace: true,
             chat_id,
-            channel: None,
-            outbound_tx: None,
-            delivered: Default::default(),
+            ..ToolCtx::for_tests(std::env::temp_dir())
         }
     }
 
@@ -1115,6 +2395,40 @@ mod tool_tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[tokio::test]
+    async fn cron_tool_add_once_at_time_in_words() {
+        let dir = std::env::temp_dir().join("icrab_cron_tool_at");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = Arc::new(CronStore::empty(&dir));
+        let tool = CronTool::new(Arc::clone(&store)).with_timezone(chrono_tz::Europe::London);
+        let ctx = empty_ctx(Some(1));
+        let args = serde_json::json!({
+            "action": "add",
+            "message": "Standup",
+            "schedule_type": "once",
+            "at": "9am PST"
+        });
+        let res = tool.execute(&ctx, &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let next_run = store.list()[0].next_run.unwrap();
+        let at = DateTime::<Utc>::from_timestamp(next_run as i64, 0)
+            .unwrap()
+            .with_timezone(&chrono_tz::America::Los_Angeles);
+        assert_eq!((at.hour(), at.minute()), (9, 0));
+        assert!(next_run > unix_now() && next_run <= unix_now() + 86_400);
+
+        let args = serde_json::json!({
+            "action": "add",
+            "message": "Hi",
+            "schedule_type": "once",
+            "at": "9am PST",
+            "delay": "1h"
+        });
+        assert!(tool.execute(&ctx, &args).await.is_error);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[tokio::test]
     async fn cron_tool_add_once_neither_at_unix_nor_delay_returns_error() {
         let dir = std::env::temp_dir().join("icrab_cron_tool_once_missing");
@@ -1190,4 +2504,305 @@ mod tool_tests {
         );
         let _ = std::fs::remove_dir_all(&dir);
     }
+
+    #[tokio::test]
+    async fn cron_tool_update_changes_message_in_place() {
+        let dir = std::env::temp_dir().join("icrab_cron_tool_update");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = Arc::new(CronStore::empty(&dir));
+        let tool = CronTool::new(store.clone());
+        let ctx = empty_ctx(Some(1));
+        let args = serde_json::json!({
+            "action": "add",
+            "message": "Hi",
+            "schedule_type": "interval",
+            "every_seconds": 3600,
+            "job_action": "agent",
+            "timeout_seconds": 120
+        });
+        assert!(!tool.execute(&ctx, &args).await.is_error);
+        assert_eq!(store.get("job-1").unwrap().timeout_seconds, Some(120));
+        let args = serde_json::json!({"action": "update", "id": "job-1"});
+        let res = tool.execute(&ctx, &args).await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("something to change"));
+        let args = serde_json::json!({
+            "action": "update",
+            "id": "job-1",
+            "message": "Hello",
+            "schedule_type": "cron",
+            "cron_expr": "0 9 * * *"
+        });
+        let res = tool.execute(&ctx, &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(res.for_llm.contains("Updated job job-1"));
+        let job = store.get("job-1").unwrap();
+        assert_eq!(job.message, "Hello");
+        assert!(matches!(job.schedule, Schedule::Cron { .. }));
+        let args = serde_json::json!({"action": "update", "id": "job-7", "label": "x"});
+        assert!(
+            tool.execute(&ctx, &args)
+                .await
+                .for_llm
+                .contains("not found")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn cron_tool_history_lists_latest_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(BrainDb::open(dir.path()).unwrap());
+        let store = Arc::new(CronStore::empty(dir.path()).with_history(db));
+        let tool = CronTool::new(Arc::clone(&store));
+        let ctx = empty_ctx(Some(1));
+        let args = serde_json::json!({"action": "history", "id": "job-1"});
+        let res = tool.execute(&ctx, &args).await;
+        assert!(!res.is_error);
+        assert!(res.for_llm.contains("No recorded runs"));
+
+        let reply = "Backup done. ".repeat(40);
+        store.record_run("job-1", 1_792_108_800, JobAction::Agent, true, &reply);
+        store.record_run(
+            "job-1",
+            1_792_195_200,
+            JobAction::Agent,
+            false,
+            "Error: timeout.",
+        );
+        let res = tool.execute(&ctx, &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let lines: Vec<&str> = res.for_llm.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("2026-10-17 00:00 | agent | FAILED | Error: timeout."));
+        assert!(lines[2].contains("| ok | Backup done."));
+        assert!(lines[2].ends_with('…'));
+
+        let bare = CronTool::new(Arc::new(CronStore::empty(dir.path())));
+        assert!(bare.execute(&ctx, &args).await.is_error);
+    }
+
+    #[tokio::test]
+    async fn cron_tool_export_then_import_on_another_install() {
+        let old = tempfile::tempdir().unwrap();
+        let store = Arc::new(CronStore::empty(old.path()));
+        let tool = CronTool::new(Arc::clone(&store));
+        let ctx = ToolCtx {
+            workspace: old.path().to_path_buf(),
+            ..empty_ctx(Some(1))
+        };
+        let add = |message: &str, every: u64| {
+            serde_json::json!({
+                "action": "add",
+                "message": message,
+                "label": message,
+                "schedule_type": "interval",
+                "every_seconds": every,
+                "job_action": "agent",
+                "timeout_seconds": 90
+            })
+        };
+        tool.execute(&ctx, &add("backup", 86_400)).await;
+        tool.execute(&ctx, &add("stretch", 3_600)).await;
+        store.disable("job-2");
+        store
+            .add_timer("tea".into(), "tea".into(), unix_now() + 60, 1)
+            .unwrap();
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"action": "export"}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm
+                .contains("Exported 2 job(s) to cron/export.json")
+        );
+
+        let new = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(new.path().join("cron")).unwrap();
+        std::fs::copy(old.path().join(EXPORT_PATH), new.path().join(EXPORT_PATH)).unwrap();
+        let store = Arc::new(CronStore::empty(new.path()));
+        let tool = CronTool::new(Arc::clone(&store));
+        let ctx = ToolCtx {
+            workspace: new.path().to_path_buf(),
+            ..empty_ctx(Some(42))
+        };
+        let import = serde_json::json!({"action": "import"});
+        let res = tool.execute(&ctx, &import).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(res.for_llm.starts_with("Imported 2 job(s)."));
+        let jobs = store.list();
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs.iter().all(|j| j.chat_id == 42
+            && j.action == JobAction::Agent
+            && j.timeout_seconds == Some(90)));
+        assert!(jobs[0].enabled && !jobs[1].enabled);
+
+        let res = tool.execute(&ctx, &import).await;
+        assert!(res.for_llm.contains("Imported 0 job(s)."));
+        assert!(res.for_llm.contains("Skipped 2 already scheduled here."));
+        assert_eq!(store.list().len(), 2);
+    }
+
+    /// Owner 1 plus runtime user 2.
+    fn allowlist() -> Arc<Allowlist> {
+        let allowlist = Allowlist::from_config(&crate::config::TelegramConfig {
+            allowed_user_ids: Some(vec![1]),
+            ..Default::default()
+        });
+        allowlist.add(2);
+        Arc::new(allowlist)
+    }
+
+    #[tokio::test]
+    async fn bulk_actions_reach_other_chats_only_for_owners() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(CronStore::empty(dir.path()));
+        let tool = CronTool::new(Arc::clone(&store)).with_allowlist(allowlist());
+        for chat_id in [1, 2] {
+            store
+                .add(
+                    None,
+                    format!("from {chat_id}"),
+                    JobAction::Direct,
+                    Schedule::Interval {
+                        every_seconds: 3600,
+                        jitter_seconds: None,
+                    },
+                    chat_id,
+                )
+                .unwrap();
+        }
+        let run = |chat_id: Option<i64>, action: &str| {
+            let ctx = ToolCtx {
+                workspace: dir.path().to_path_buf(),
+                ..empty_ctx(chat_id)
+            };
+            let tool = &tool;
+            let args = serde_json::json!({ "action": action });
+            async move { tool.execute(&ctx, &args).await }
+        };
+        let paused = |id: &str| store.get(id).unwrap().paused;
+
+        // Runtime user 2 is not an owner: only its own chat's job is touched.
+        let res = run(Some(2), "pause_all").await;
+        assert!(res.for_llm.contains("Paused 1 job(s)"), "{}", res.for_llm);
+        assert!(!paused("job-1") && paused("job-2"));
+        let res = run(Some(2), "export").await;
+        assert!(res.for_llm.contains("Exported 1 job(s)"), "{}", res.for_llm);
+        let export = std::fs::read_to_string(dir.path().join(EXPORT_PATH)).unwrap();
+        assert!(export.contains("from 2") && !export.contains("from 1"));
+        assert!(run(None, "pause_all").await.is_error);
+
+        // Owner 1 pauses and resumes every chat's jobs.
+        let res = run(Some(1), "pause_all").await;
+        assert!(res.for_llm.contains("Paused 1 job(s)"), "{}", res.for_llm);
+        assert!(paused("job-1") && paused("job-2"));
+        let res = run(Some(2), "resume_all").await;
+        assert!(res.for_llm.contains("Resumed 1 job(s)"), "{}", res.for_llm);
+        assert!(paused("job-1") && !paused("job-2"));
+        let res = run(Some(1), "resume_all").await;
+        assert!(res.for_llm.contains("Resumed 1 job(s)"), "{}", res.for_llm);
+        assert!(!paused("job-1"));
+    }
+
+    #[tokio::test]
+    async fn cron_tool_add_and_update_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(CronStore::empty(dir.path()));
+        let tool = CronTool::new(Arc::clone(&store)).with_allowlist(allowlist());
+        let ctx = empty_ctx(Some(1));
+        let args = serde_json::json!({
+            "action": "add",
+            "message": "Bins out",
+            "schedule_type": "cron",
+            "cron_expr": "0 19 * * 3",
+            "chat_ids": [1, 2]
+        });
+        let res = tool.execute(&ctx, &args).await;
+        assert!(
+            res.for_llm.contains("sent to chats 1, 2"),
+            "{}",
+            res.for_llm
+        );
+        assert_eq!(
+            store.get("job-1").unwrap().targets,
+            Targets::Chats(vec![1, 2])
+        );
+
+        let update = |extra: Value| {
+            let mut args = serde_json::json!({"action": "update", "id": "job-1"});
+            args.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            args
+        };
+        tool.execute(
+            &ctx,
+            &update(serde_json::json!({"all_allowed_users": true})),
+        )
+        .await;
+        assert_eq!(store.get("job-1").unwrap().targets, Targets::AllAllowed);
+        let list = tool
+            .execute(&ctx, &serde_json::json!({"action": "list"}))
+            .await;
+        assert!(list.for_llm.contains("to all allowed users"));
+        tool.execute(&ctx, &update(serde_json::json!({"chat_ids": []})))
+            .await;
+        let job = store.get("job-1").unwrap();
+        assert_eq!(job.targets, Targets::Origin);
+        assert_eq!(job.recipients(None), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn cron_tool_targets_are_owner_only_and_allowed_chats_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(CronStore::empty(dir.path()));
+        let tool = CronTool::new(Arc::clone(&store)).with_allowlist(allowlist());
+        let add = |targets: Value| {
+            let mut args = serde_json::json!({
+                "action": "add",
+                "message": "Send me the owner's notes",
+                "schedule_type": "interval",
+                "every_seconds": 3600,
+                "job_action": "agent"
+            });
+            args.as_object_mut()
+                .unwrap()
+                .extend(targets.as_object().unwrap().clone());
+            args
+        };
+
+        // A runtime-added user can't reach the owner's chat or everyone.
+        let user = empty_ctx(Some(2));
+        for targets in [
+            serde_json::json!({"chat_ids": [1]}),
+            serde_json::json!({"all_allowed_users": true}),
+        ] {
+            let res = tool.execute(&user, &add(targets)).await;
+            assert!(res.is_error);
+            assert!(res.for_llm.contains("only owners"), "{}", res.for_llm);
+        }
+        // Nor can anyone without an allow-list, and owners only reach allowed chats.
+        let bare = CronTool::new(Arc::clone(&store));
+        let owner = empty_ctx(Some(1));
+        let res = bare
+            .execute(&owner, &add(serde_json::json!({"chat_ids": [2]})))
+            .await;
+        assert!(res.is_error);
+        let res = tool
+            .execute(&owner, &add(serde_json::json!({"chat_ids": [2, 99]})))
+            .await;
+        assert!(res.for_llm.contains("not on the allow-list: 99"));
+        assert!(store.list().is_empty());
+
+        // The owner's job to chat 2 can't be rewritten by the user it reaches.
+        let res = tool
+            .execute(&owner, &add(serde_json::json!({"chat_ids": [1, 2]})))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let edit = serde_json::json!({"action": "update", "id": "job-1", "message": "hi"});
+        assert!(tool.execute(&user, &edit).await.is_error);
+        assert!(!tool.execute(&owner, &edit).await.is_error);
+    }
 }
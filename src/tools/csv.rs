@@ -0,0 +1,786 @@
+//! `csv` tool: schema and first rows of a workspace CSV, plus filters, sorting and
+//! count/sum/avg/min/max (optionally grouped), so questions about an exported bank statement
+//! are answered without reading the whole file into context.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+const DEFAULT_HEAD: usize = 5;
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 200;
+/// Cells longer than this are cut in output tables.
+const MAX_CELL_CHARS: usize = 60;
+
+/// Date layouts tried for comparisons and sorting; day-first, as UK and EU banks export.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%d/%m/%Y", "%d.%m.%Y", "%d-%m-%Y", "%Y/%m/%d"];
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+/// The most common of `,`, `;` and tab in the header line.
+fn detect_delimiter(text: &str) -> char {
+    let header = text.lines().next().unwrap_or("");
+    [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| header.matches(*d).count())
+        .unwrap_or(',')
+}
+
+/// RFC 4180 records: quoted fields may hold delimiters, newlines and doubled quotes. Blank
+/// lines are skipped.
+fn parse_records(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => quoted = true,
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.is_empty()) {
+        records.push(record);
+    }
+    records
+}
+
+/// A number as banks write it: "1,234.56", "1.234,56", "£-12.50", "(12.50)", "12,50".
+fn parse_number(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let (s, negative) = match s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        Some(inner) => (inner, true),
+        None => (s, false),
+    };
+    let s: String = s
+        .chars()
+        .filter(|c| !matches!(c, '£' | '$' | '€' | ' ' | '\u{a0}'))
+        .collect();
+    if !s.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let normalized = match (s.rfind('.'), s.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => s.replace('.', "").replace(',', "."),
+        (_, Some(comma)) if s.matches(',').count() == 1 && s.len() - comma == 3 => {
+            s.replace(',', ".")
+        }
+        _ => s.replace(',', ""),
+    };
+    let n: f64 = normalized.parse().ok()?;
+    n.is_finite().then_some(if negative { -n } else { n })
+}
+
+/// A date in one of [`DATE_FORMATS`]; a time after it is ignored.
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    let day = s.trim().split([' ', 'T']).next()?;
+    DATE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(day, f).ok())
+}
+
+/// Dates compare by date, numbers numerically, anything else case-insensitively.
+fn compare(a: &str, b: &str) -> Ordering {
+    if let (Some(x), Some(y)) = (parse_date(a), parse_date(b)) {
+        return x.cmp(&y);
+    }
+    if let (Some(x), Some(y)) = (parse_number(a), parse_number(b)) {
+        return x.total_cmp(&y);
+    }
+    a.trim().to_lowercase().cmp(&b.trim().to_lowercase())
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        format!("{n:.2}")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Table
+// ---------------------------------------------------------------------------
+
+struct Table {
+    delimiter: char,
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn parse(text: &str) -> Result<Self, String> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        let delimiter = detect_delimiter(text);
+        let mut records = parse_records(text, delimiter).into_iter();
+        let columns: Vec<String> = records
+            .next()
+            .ok_or("the file is empty")?
+            .into_iter()
+            .map(|c| c.trim().to_string())
+            .collect();
+        let rows = records
+            .map(|mut r| {
+                r.resize(columns.len(), String::new());
+                r
+            })
+            .collect();
+        Ok(Self {
+            delimiter,
+            columns,
+            rows,
+        })
+    }
+
+    /// Index of column `name` (case-insensitive).
+    fn column(&self, name: &str) -> Result<usize, String> {
+        let name = name.trim();
+        self.columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("no column '{name}'; columns: {}", self.columns.join(", ")))
+    }
+
+    /// "number", "date", "text" or "empty", from every non-empty value in column `i`.
+    fn kind(&self, i: usize) -> &'static str {
+        let values: Vec<&str> = self
+            .rows
+            .iter()
+            .map(|r| r[i].trim())
+            .filter(|v| !v.is_empty())
+            .collect();
+        if values.is_empty() {
+            "empty"
+        } else if values.iter().all(|v| parse_date(v).is_some()) {
+            "date"
+        } else if values.iter().all(|v| parse_number(v).is_some()) {
+            "number"
+        } else {
+            "text"
+        }
+    }
+
+    fn schema(&self, head: usize) -> String {
+        let delimiter = match self.delimiter {
+            '\t' => "tab".to_string(),
+            d => format!("'{d}'"),
+        };
+        let mut out = format!(
+            "{} rows, {} columns (delimiter {delimiter})\nColumns:\n",
+            self.rows.len(),
+            self.columns.len()
+        );
+        for (i, name) in self.columns.iter().enumerate() {
+            let kind = self.kind(i);
+            let values = || {
+                self.rows
+                    .iter()
+                    .map(|r| r[i].as_str())
+                    .filter(|v| !v.trim().is_empty())
+            };
+            let detail = match kind {
+                "number" => {
+                    let nums: Vec<f64> = values().filter_map(parse_number).collect();
+                    let min = nums.iter().copied().fold(f64::INFINITY, f64::min);
+                    let max = nums.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                    format!(", {} to {}", format_number(min), format_number(max))
+                }
+                "date" => {
+                    let dates: Vec<NaiveDate> = values().filter_map(parse_date).collect();
+                    let first = dates
+                        .iter()
+                        .min()
+                        .map(|d| d.to_string())
+                        .unwrap_or_default();
+                    let last = dates
+                        .iter()
+                        .max()
+                        .map(|d| d.to_string())
+                        .unwrap_or_default();
+                    format!(", {first} to {last}")
+                }
+                "text" => {
+                    let mut distinct: Vec<&str> = values().collect();
+                    distinct.sort_unstable();
+                    distinct.dedup();
+                    format!(", {} distinct", distinct.len())
+                }
+                _ => String::new(),
+            };
+            out.push_str(&format!("- {name} ({kind}{detail})\n"));
+        }
+        if head > 0 && !self.rows.is_empty() {
+            let rows: Vec<&Vec<String>> = self.rows.iter().take(head).collect();
+            out.push_str(&format!("First {} rows:\n", rows.len()));
+            out.push_str(&render(&self.columns, &rows));
+        }
+        out
+    }
+}
+
+/// Rows as a Markdown table.
+fn render(columns: &[String], rows: &[&Vec<String>]) -> String {
+    let cell = |s: &str| {
+        let s = s.trim().replace('|', "\\|").replace(['\n', '\r'], " ");
+        if s.chars().count() > MAX_CELL_CHARS {
+            let cut: String = s.chars().take(MAX_CELL_CHARS).collect();
+            format!("{cut}…")
+        } else {
+            s
+        }
+    };
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut out = line(columns.iter().map(|c| cell(c)).collect());
+    out.push_str(&line(vec!["---".to_string(); columns.len()]));
+    for row in rows {
+        out.push_str(&line(row.iter().map(|c| cell(c)).collect()));
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Query
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+impl Op {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s.trim().to_ascii_lowercase().as_str() {
+            "=" | "==" | "eq" => Self::Eq,
+            "!=" | "<>" | "ne" => Self::Ne,
+            ">" | "gt" => Self::Gt,
+            ">=" | "ge" => Self::Ge,
+            "<" | "lt" => Self::Lt,
+            "<=" | "le" => Self::Le,
+            "contains" => Self::Contains,
+            _ => return None,
+        })
+    }
+
+    fn matches(self, cell: &str, value: &str) -> bool {
+        let ord = compare(cell, value);
+        match self {
+            Self::Eq => ord == Ordering::Equal,
+            Self::Ne => ord != Ordering::Equal,
+            Self::Gt => ord == Ordering::Greater,
+            Self::Ge => ord != Ordering::Less,
+            Self::Lt => ord == Ordering::Less,
+            Self::Le => ord != Ordering::Greater,
+            Self::Contains => cell.to_lowercase().contains(&value.to_lowercase()),
+        }
+    }
+}
+
+struct Filter {
+    column: usize,
+    op: Op,
+    value: String,
+}
+
+fn parse_filters(table: &Table, args: &Value) -> Result<Vec<Filter>, String> {
+    let Some(list) = args.get("where") else {
+        return Ok(Vec::new());
+    };
+    let list = list
+        .as_array()
+        .ok_or("'where' must be a list of {column, op, value}")?;
+    list.iter()
+        .map(|f| {
+            let column = f
+                .get("column")
+                .and_then(Value::as_str)
+                .ok_or("each filter needs 'column'")?;
+            let op = f.get("op").and_then(Value::as_str).unwrap_or("=");
+            let value = match f.get("value") {
+                Some(Value::String(s)) => s.clone(),
+                Some(Value::Number(n)) => n.to_string(),
+                _ => return Err("each filter needs 'value'".to_string()),
+            };
+            Ok(Filter {
+                column: table.column(column)?,
+                op: Op::parse(op).ok_or_else(|| {
+                    format!("unknown op '{op}' (use =, !=, >, >=, <, <= or contains)")
+                })?,
+                value,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s.trim().to_ascii_lowercase().as_str() {
+            "count" => Self::Count,
+            "sum" => Self::Sum,
+            "avg" | "mean" | "average" => Self::Avg,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::Avg => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+
+    /// The aggregate of `values`; None for an empty numeric aggregate.
+    fn apply(self, count: usize, values: &[f64]) -> Option<f64> {
+        match self {
+            Self::Count => Some(count as f64),
+            _ if values.is_empty() => None,
+            Self::Sum => Some(values.iter().sum()),
+            Self::Avg => Some(values.iter().sum::<f64>() / values.len() as f64),
+            Self::Min => values.iter().copied().reduce(f64::min),
+            Self::Max => values.iter().copied().reduce(f64::max),
+        }
+    }
+}
+
+fn query(table: &Table, args: &Value) -> Result<String, String> {
+    let filters = parse_filters(table, args)?;
+    let matched: Vec<&Vec<String>> = table
+        .rows
+        .iter()
+        .filter(|r| filters.iter().all(|f| f.op.matches(&r[f.column], &f.value)))
+        .collect();
+    let limit = args
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_LIMIT)
+        .clamp(1, MAX_LIMIT);
+    let sort = args.get("sort").and_then(Value::as_str).map(str::trim);
+    let desc = args.get("desc").and_then(Value::as_bool).unwrap_or(false);
+    let group = args
+        .get("group_by")
+        .and_then(Value::as_str)
+        .map(|g| table.column(g))
+        .transpose()?;
+    let aggregate =
+        match args.get("aggregate").and_then(Value::as_str) {
+            Some(a) => Some(Aggregate::parse(a).ok_or_else(|| {
+                format!("unknown aggregate '{a}' (use count, sum, avg, min or max)")
+            })?),
+            None if group.is_some() => Some(Aggregate::Count),
+            None => None,
+        };
+
+    let Some(aggregate) = aggregate else {
+        let mut rows = matched;
+        if let Some(sort) = sort {
+            let col = table.column(sort)?;
+            rows.sort_by(|a, b| compare(&a[col], &b[col]));
+            if desc {
+                rows.reverse();
+            }
+        }
+        let columns = match args.get("columns").and_then(Value::as_array) {
+            Some(names) if !names.is_empty() => names
+                .iter()
+                .map(|n| table.column(n.as_str().unwrap_or("")))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => (0..table.columns.len()).collect(),
+        };
+        let total = rows.len();
+        if total == 0 {
+            return Ok("No rows match.".to_string());
+        }
+        let names: Vec<String> = columns.iter().map(|&i| table.columns[i].clone()).collect();
+        let picked: Vec<Vec<String>> = rows
+            .iter()
+            .take(limit)
+            .map(|r| columns.iter().map(|&i| r[i].clone()).collect())
+            .collect();
+        let shown = picked.len();
+        let mut out = format!("{total} matching rows");
+        if shown < total {
+            out.push_str(&format!(" (first {shown})"));
+        }
+        out.push_str(":\n");
+        out.push_str(&render(&names, &picked.iter().collect::<Vec<_>>()));
+        return Ok(out);
+    };
+
+    let column = match args.get("column").and_then(Value::as_str) {
+        Some(c) => Some(table.column(c)?),
+        None if aggregate == Aggregate::Count => None,
+        None => return Err(format!("'{}' needs 'column'", aggregate.name())),
+    };
+    // (rows, numeric values) per group; one unnamed group without group_by.
+    let mut groups: BTreeMap<String, (usize, Vec<f64>)> = BTreeMap::new();
+    let mut skipped = 0;
+    for row in &matched {
+        let key = group.map(|g| row[g].trim().to_string()).unwrap_or_default();
+        let entry = groups.entry(key).or_default();
+        entry.0 += 1;
+        if let Some(c) = column {
+            match parse_number(&row[c]) {
+                Some(n) => entry.1.push(n),
+                None if !row[c].trim().is_empty() => skipped += 1,
+                None => {}
+            }
+        }
+    }
+    let label = match column {
+        Some(c) => format!("{}({})", aggregate.name(), table.columns[c]),
+        None => aggregate.name().to_string(),
+    };
+    let note = if skipped > 0 && aggregate != Aggregate::Count {
+        format!(" ({skipped} non-numeric values skipped)")
+    } else {
+        String::new()
+    };
+
+    let Some(group) = group else {
+        let (count, values) = groups.remove("").unwrap_or_default();
+        let result = aggregate
+            .apply(count, &values)
+            .map(format_number)
+            .unwrap_or_else(|| "no values".to_string());
+        return Ok(format!(
+            "{label} over {} rows: {result}{note}",
+            matched.len()
+        ));
+    };
+    let mut results: Vec<(String, Option<f64>)> = groups
+        .into_iter()
+        .map(|(key, (count, values))| {
+            let value = aggregate.apply(count, &values);
+            (key, value)
+        })
+        .collect();
+    match sort {
+        Some(s) if s.eq_ignore_ascii_case("value") => results.sort_by(|a, b| {
+            a.1.unwrap_or(f64::NEG_INFINITY)
+                .total_cmp(&b.1.unwrap_or(f64::NEG_INFINITY))
+        }),
+        _ => results.sort_by(|a, b| compare(&a.0, &b.0)),
+    }
+    if desc {
+        results.reverse();
+    }
+    let total = results.len();
+    if total == 0 {
+        return Ok("No rows match.".to_string());
+    }
+    let rows: Vec<Vec<String>> = results
+        .into_iter()
+        .take(limit)
+        .map(|(key, value)| {
+            let value = value.map(format_number).unwrap_or_default();
+            vec![key, value]
+        })
+        .collect();
+    let mut out = format!("{total} groups over {} rows", matched.len());
+    if rows.len() < total {
+        out.push_str(&format!(" (first {})", rows.len()));
+    }
+    out.push_str(&format!("{note}:\n"));
+    let names = [table.columns[group].clone(), label];
+    out.push_str(&render(&names, &rows.iter().collect::<Vec<_>>()));
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+pub struct CsvTool;
+
+impl CsvTool {
+    async fn run(ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let rel = args
+            .get("path")
+            .and_then(Value::as_str)
+            .ok_or("missing 'path'")?;
+        let path = resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        let meta = tokio::fs::metadata(&path)
+            .await
+            .map_err(|e| format!("{rel}: {e}"))?;
+        if meta.len() > MAX_FILE_BYTES {
+            return Err(format!(
+                "{rel} is {} MB; the limit is {} MB",
+                meta.len() / (1024 * 1024),
+                MAX_FILE_BYTES / (1024 * 1024)
+            ));
+        }
+        let bytes = tokio::fs::read(&path)
+            .await
+            .map_err(|e| format!("{rel}: {e}"))?;
+        let table = Table::parse(&String::from_utf8_lossy(&bytes))?;
+        match args
+            .get("action")
+            .and_then(Value::as_str)
+            .unwrap_or("schema")
+        {
+            "schema" => {
+                let head = args
+                    .get("head")
+                    .and_then(Value::as_u64)
+                    .map(|n| (n as usize).min(MAX_LIMIT))
+                    .unwrap_or(DEFAULT_HEAD);
+                Ok(format!("{rel}: {}", table.schema(head)))
+            }
+            "query" => query(&table, args),
+            other => Err(format!("unknown action '{other}' (use schema or query)")),
+        }
+    }
+}
+
+impl Tool for CsvTool {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn description(&self) -> &str {
+        "Inspect and query a CSV file in the workspace (e.g. a bank statement export) without \
+         reading it all. action=schema: columns with types and ranges plus the first rows. \
+         action=query: filter rows (where), sort, pick columns, or aggregate \
+         (count/sum/avg/min/max of a column, optionally per group_by value). Dates are day-first \
+         (31/01/2026) or ISO."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "CSV file, e.g. Finance/statement.csv" },
+                "action": { "type": "string", "enum": ["schema", "query"], "description": "Default schema" },
+                "head": { "type": "integer", "description": "schema: rows to show (default 5)" },
+                "where": {
+                    "type": "array",
+                    "description": "query: filters, all must match",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "column": { "type": "string" },
+                            "op": { "type": "string", "enum": ["=", "!=", ">", ">=", "<", "<=", "contains"] },
+                            "value": { "type": ["string", "number"] }
+                        },
+                        "required": ["column", "value"]
+                    }
+                },
+                "aggregate": { "type": "string", "enum": ["count", "sum", "avg", "min", "max"], "description": "query: aggregate instead of listing rows" },
+                "column": { "type": "string", "description": "query: column to aggregate (not needed for count)" },
+                "group_by": { "type": "string", "description": "query: aggregate per value of this column (default aggregate count)" },
+                "columns": { "type": "array", "items": { "type": "string" }, "description": "query: columns to list (default all)" },
+                "sort": { "type": "string", "description": "query: column to sort rows by; with group_by, \"value\" sorts by the aggregate (default by group)" },
+                "desc": { "type": "boolean", "description": "query: sort descending" },
+                "limit": { "type": "integer", "description": "query: max rows or groups shown (default 20, max 200)" }
+            },
+            "required": ["path"]
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match Self::run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    const STATEMENT: &str = "\u{feff}Date;Description;Category;Amount\r\n\
+        02/01/2026;\"TESCO; Store 12\";Groceries;-45,20\r\n\
+        05/01/2026;Salary;Income;2.500,00\r\n\
+        \r\n\
+        14/01/2026;\"Rent \"\"Jan\"\"\";Housing;-1.200,00\r\n\
+        28/01/2026;Tesco Express;Groceries;-8,99\r\n\
+        03/02/2026;Coffee;Eating out;-3,50\r\n";
+
+    fn ctx(workspace: &std::path::Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    async fn run(args: Value) -> ToolResult {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("statement.csv"), STATEMENT).unwrap();
+        let mut args = args;
+        args["path"] = json!("statement.csv");
+        CsvTool.execute(&ctx(dir.path()), &args).await
+    }
+
+    #[test]
+    fn parses_numbers_and_quoted_fields() {
+        assert_eq!(parse_number("1,234.56"), Some(1234.56));
+        assert_eq!(parse_number("1.234,56"), Some(1234.56));
+        assert_eq!(parse_number("£-12.50"), Some(-12.5));
+        assert_eq!(parse_number("(12.50)"), Some(-12.5));
+        assert_eq!(parse_number("12,50"), Some(12.5));
+        assert_eq!(parse_number("1,000"), Some(1000.0));
+        assert_eq!(parse_number("Tesco"), None);
+        assert_eq!(
+            parse_records("a,\"b,\nc\",\"d\"\"\"\n\n1,2,3", ','),
+            [vec!["a", "b,\nc", "d\""], vec!["1", "2", "3"]]
+        );
+        assert_eq!(compare("02/01/2026", "14/01/2025"), Ordering::Greater);
+        assert_eq!(compare("-45,20", "-8,99"), Ordering::Less);
+    }
+
+    #[tokio::test]
+    async fn schema_shows_types_ranges_and_head() {
+        let res = run(json!({"head": 2})).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "statement.csv: 5 rows, 4 columns (delimiter ';')\n\
+             Columns:\n\
+             - Date (date, 2026-01-02 to 2026-02-03)\n\
+             - Description (text, 5 distinct)\n\
+             - Category (text, 4 distinct)\n\
+             - Amount (number, -1200 to 2500)\n\
+             First 2 rows:\n\
+             | Date | Description | Category | Amount |\n\
+             | --- | --- | --- | --- |\n\
+             | 02/01/2026 | TESCO; Store 12 | Groceries | -45,20 |\n\
+             | 05/01/2026 | Salary | Income | 2.500,00 |\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn query_filters_sorts_and_picks_columns() {
+        let res = run(json!({
+            "action": "query",
+            "where": [
+                {"column": "amount", "op": "<", "value": 0},
+                {"column": "Date", "op": "<", "value": "2026-02-01"}
+            ],
+            "sort": "Amount",
+            "columns": ["Description", "Amount"],
+            "limit": 2
+        }))
+        .await;
+        assert_eq!(
+            res.for_llm,
+            "3 matching rows (first 2):\n\
+             | Description | Amount |\n\
+             | --- | --- |\n\
+             | Rent \"Jan\" | -1.200,00 |\n\
+             | TESCO; Store 12 | -45,20 |\n"
+        );
+        let res = run(json!({"action": "query", "where": [{"column": "Description", "op": "contains", "value": "tesco"}]})).await;
+        assert!(res.for_llm.starts_with("2 matching rows:"));
+    }
+
+    #[tokio::test]
+    async fn query_aggregates_with_and_without_groups() {
+        let res = run(json!({
+            "action": "query",
+            "aggregate": "sum",
+            "column": "Amount",
+            "where": [{"column": "Category", "value": "groceries"}]
+        }))
+        .await;
+        assert_eq!(res.for_llm, "sum(Amount) over 2 rows: -54.19");
+
+        let res = run(json!({
+            "action": "query",
+            "aggregate": "sum",
+            "column": "Amount",
+            "group_by": "Category",
+            "sort": "value",
+            "limit": 3
+        }))
+        .await;
+        assert_eq!(
+            res.for_llm,
+            "4 groups over 5 rows (first 3):\n\
+             | Category | sum(Amount) |\n\
+             | --- | --- |\n\
+             | Housing | -1200 |\n\
+             | Groceries | -54.19 |\n\
+             | Eating out | -3.50 |\n"
+        );
+        let res = run(json!({"action": "query", "group_by": "category"})).await;
+        assert!(res.for_llm.contains("| Groceries | 2 |"));
+    }
+
+    #[tokio::test]
+    async fn reports_unknown_columns_and_ops() {
+        let res = run(json!({"action": "query", "aggregate": "sum", "column": "Total"})).await;
+        assert!(res.is_error);
+        assert_eq!(
+            res.for_llm,
+            "no column 'Total'; columns: Date, Description, Category, Amount"
+        );
+        let res =
+            run(json!({"action": "query", "where": [{"column": "Amount", "op": "~", "value": 1}]}))
+                .await;
+        assert!(res.for_llm.starts_with("unknown op '~'"));
+        let res = run(json!({"action": "query", "aggregate": "avg"})).await;
+        assert_eq!(res.for_llm, "'avg' needs 'column'");
+    }
+}
@@ -0,0 +1,404 @@
+//! `daily_note` tool: creates a day's note (`Daily log/<YYYY-MM-DD>.md`) from a template and
+//! appends entries under a heading, so every day's note has the same structure.
+//!
+//! The template (`[tools.daily-note] template`, else a built-in one) may use `{{date}}`,
+//! `{{weekday}}`, `{{yesterday}}`, `{{tomorrow}}` and `{{events}}` (that day's calendar events
+//! when calendars are configured).
+
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde_json::Value;
+
+use crate::config::DailyNoteConfig;
+use crate::tools::calendar::Calendars;
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const DEFAULT_DIR: &str = "Daily log";
+const DEFAULT_TEMPLATE: &str = "Templates/Daily log.md";
+const DEFAULT_HEADING: &str = "Log";
+
+/// Used when the template file does not exist.
+const BUILTIN_TEMPLATE: &str = "# {{weekday}}, {{date}}
+
+## Calendar
+{{events}}
+
+## Log
+
+## Notes
+";
+
+/// `today`, `yesterday`, `tomorrow` or `YYYY-MM-DD`.
+pub(crate) fn parse_day(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "" | "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        "tomorrow" => today.succ_opt(),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d").ok(),
+    }
+}
+
+/// Fill in the template variables; unknown `{{…}}` are left as they are.
+fn render(template: &str, day: NaiveDate, events: &str) -> String {
+    let ymd = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+    template
+        .replace("{{date}}", &ymd(day))
+        .replace("{{weekday}}", &day.format("%A").to_string())
+        .replace("{{yesterday}}", &ymd(day - Duration::days(1)))
+        .replace("{{tomorrow}}", &ymd(day + Duration::days(1)))
+        .replace("{{events}}", events)
+}
+
+/// Level and title of a Markdown heading line ("## Log" is `(2, "Log")`).
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some((level, rest.trim()))
+}
+
+/// `content` with `entry` added at the end of the section under `title` (before its trailing
+/// blank lines); a `## title` section is added at the end when there is none.
+fn append_under(content: &str, title: &str, entry: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let found = lines.iter().enumerate().find_map(|(i, l)| {
+        heading(l)
+            .filter(|(_, t)| t.eq_ignore_ascii_case(title))
+            .map(|(level, _)| (i, level))
+    });
+    let Some((start, level)) = found else {
+        let mut out = content.trim_end().to_string();
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(&format!("## {title}\n{entry}\n"));
+        return out;
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| heading(l).is_some_and(|(lv, _)| lv <= level))
+        .map_or(lines.len(), |p| start + 1 + p);
+    let mut insert = end;
+    while insert > start + 1 && lines[insert - 1].trim().is_empty() {
+        insert -= 1;
+    }
+    let mut out: Vec<&str> = lines[..insert].to_vec();
+    out.extend(entry.lines());
+    out.extend(&lines[insert..]);
+    let mut out = out.join("\n");
+    out.push('\n');
+    out
+}
+
+/// The body of the section under `title` (without the heading line), trimmed; None when
+/// the note has no such heading.
+pub(crate) fn section(content: &str, title: &str) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let (start, level) = lines.iter().enumerate().find_map(|(i, l)| {
+        heading(l)
+            .filter(|(_, t)| t.eq_ignore_ascii_case(title))
+            .map(|(level, _)| (i, level))
+    })?;
+    let body: Vec<&str> = lines[start + 1..]
+        .iter()
+        .take_while(|l| heading(l).is_none_or(|(lv, _)| lv > level))
+        .copied()
+        .collect();
+    Some(body.join("\n").trim().to_string())
+}
+
+pub struct DailyNoteTool {
+    dir: String,
+    template: String,
+    timezone: Tz,
+    calendars: Option<Arc<Calendars>>,
+}
+
+impl DailyNoteTool {
+    pub fn new(
+        config: Option<&DailyNoteConfig>,
+        timezone: Tz,
+        calendars: Option<Arc<Calendars>>,
+    ) -> Self {
+        Self {
+            dir: config
+                .and_then(|c| c.dir.as_deref())
+                .map(|d| d.trim_matches('/').to_string())
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| DEFAULT_DIR.to_string()),
+            template: config
+                .and_then(|c| c.template.clone())
+                .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string()),
+            timezone,
+            calendars,
+        }
+    }
+
+    /// Vault folder of the daily notes.
+    pub(crate) fn dir(&self) -> &str {
+        &self.dir
+    }
+
+    /// Today in the user's timezone.
+    pub(crate) fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.timezone).date_naive()
+    }
+
+    /// Vault path of the note for `day`.
+    pub(crate) fn note_path(&self, day: NaiveDate) -> String {
+        format!("{}/{}.md", self.dir, day.format("%Y-%m-%d"))
+    }
+
+    /// The day's note, created from the template when missing; returns (path, content, created).
+    async fn ensure(
+        &self,
+        ctx: &ToolCtx,
+        day: NaiveDate,
+    ) -> Result<(String, String, bool), String> {
+        let rel = self.note_path(day);
+        let path = resolve_path(&rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        if let Ok(content) = tokio::fs::read_to_string(&path).await {
+            return Ok((rel, content, false));
+        }
+
+        let template_path =
+            resolve_path(&self.template, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        let template = tokio::fs::read_to_string(&template_path)
+            .await
+            .unwrap_or_else(|_| BUILTIN_TEMPLATE.to_string());
+        let events = match &self.calendars {
+            Some(cals) if template.contains("{{events}}") => cals.day_agenda(day).await,
+            _ => String::new(),
+        };
+        let content = render(&template, day, &events);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("creating {} failed: {e}", self.dir))?;
+        }
+        tokio::fs::write(&path, &content)
+            .await
+            .map_err(|e| format!("writing {rel} failed: {e}"))?;
+        Ok((rel, content, true))
+    }
+
+    /// Add `entry` under `title` in the day's note (created first when missing); returns
+    /// (path, created).
+    pub(crate) async fn append(
+        &self,
+        ctx: &ToolCtx,
+        day: NaiveDate,
+        title: &str,
+        entry: &str,
+    ) -> Result<(String, bool), String> {
+        let (rel, content, created) = self.ensure(ctx, day).await?;
+        let path = resolve_path(&rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        tokio::fs::write(&path, append_under(&content, title, entry))
+            .await
+            .map_err(|e| format!("writing {rel} failed: {e}"))?;
+        Ok((rel, created))
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let action = args.get("action").and_then(Value::as_str).unwrap_or("open");
+        let today = self.today();
+        let date_arg = args.get("date").and_then(Value::as_str).unwrap_or("today");
+        let day = parse_day(date_arg, today)
+            .ok_or_else(|| format!("invalid date '{date_arg}' (today, yesterday or YYYY-MM-DD)"))?;
+
+        match action {
+            "open" => {
+                let (rel, content, created) = self.ensure(ctx, day).await?;
+                let how = if created {
+                    " (created from the template)"
+                } else {
+                    ""
+                };
+                Ok(format!("{rel}{how}:\n\n{content}"))
+            }
+            "append" => {
+                let text = args
+                    .get("text")
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .ok_or("append needs 'text'")?;
+                let title = args
+                    .get("heading")
+                    .and_then(Value::as_str)
+                    .map(|h| h.trim().trim_start_matches('#').trim())
+                    .filter(|h| !h.is_empty())
+                    .unwrap_or(DEFAULT_HEADING);
+                let (rel, created) = self.append(ctx, day, title, text).await?;
+                let how = if created {
+                    " (created from the template)"
+                } else {
+                    ""
+                };
+                Ok(format!("Added to {rel}{how} under \"{title}\"."))
+            }
+            other => Err(format!("unknown action '{other}' (open or append)")),
+        }
+    }
+}
+
+impl Tool for DailyNoteTool {
+    fn name(&self) -> &str {
+        "daily_note"
+    }
+
+    fn description(&self) -> &str {
+        "The user's daily note in the vault. open: return the day's note, creating it from the \
+         daily template first if needed. append: add an entry under a heading of the note \
+         (default \"Log\"), creating the note if needed. Use this instead of writing daily \
+         notes by hand so they keep the template's structure."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["open", "append"], "description": "open (default) or append" },
+                "date": { "type": "string", "description": "today (default), yesterday or YYYY-MM-DD" },
+                "text": { "type": "string", "description": "Entry to add, e.g. \"- 18:30 Ran 5 km\" (append)" },
+                "heading": { "type": "string", "description": "Heading to add the entry under (append, default Log)" }
+            }
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    #[test]
+    fn render_fills_variables() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        assert_eq!(
+            render(
+                "# {{weekday}} {{date}}\n[[{{yesterday}}]] [[{{tomorrow}}]]\n{{events}}\n{{mood}}",
+                day,
+                "- 09:00 Gym"
+            ),
+            "# Friday 2026-10-16\n[[2026-10-15]] [[2026-10-17]]\n- 09:00 Gym\n{{mood}}"
+        );
+    }
+
+    #[test]
+    fn append_under_heading() {
+        let note = "# Day\n\n## Log\n- 08:00 Coffee\n\n## Notes\nx\n";
+        assert_eq!(
+            append_under(note, "log", "- 09:00 Walk"),
+            "# Day\n\n## Log\n- 08:00 Coffee\n- 09:00 Walk\n\n## Notes\nx\n"
+        );
+        // Subheadings belong to the section; the next heading of the same level ends it.
+        let note = "## Log\n### Morning\n- a\n## Notes\n";
+        assert_eq!(
+            append_under(note, "Log", "- b"),
+            "## Log\n### Morning\n- a\n- b\n## Notes\n"
+        );
+        assert_eq!(
+            append_under("# Day\n", "Gratitude", "- sun"),
+            "# Day\n\n## Gratitude\n- sun\n"
+        );
+        assert_eq!(append_under("## Log\n", "Log", "- a"), "## Log\n- a\n");
+    }
+
+    #[test]
+    fn section_body() {
+        let note = "# Day\n## Journal\n- Mood:: 7\n### Later\n- ok\n\n## Log\n- x\n";
+        assert_eq!(
+            section(note, "journal").as_deref(),
+            Some("- Mood:: 7\n### Later\n- ok")
+        );
+        assert_eq!(section(note, "Food"), None);
+    }
+
+    #[tokio::test]
+    async fn creates_from_template_then_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("Templates")).unwrap();
+        std::fs::write(
+            dir.path().join("Templates/Daily log.md"),
+            "# {{date}}\n\n## Log\n\n## Food\n",
+        )
+        .unwrap();
+        let tool = DailyNoteTool::new(None, chrono_tz::Europe::London, None);
+        let ctx = ctx(dir.path());
+
+        let args = serde_json::json!({"action": "append", "date": "2026-10-16", "text": "- porridge", "heading": "## Food"});
+        let res = tool.execute(&ctx, &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Added to Daily log/2026-10-16.md (created from the template) under \"Food\"."
+        );
+        let args =
+            serde_json::json!({"action": "append", "date": "2026-10-16", "text": "- 07:00 Up"});
+        tool.execute(&ctx, &args).await;
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"date": "2026-10-16"}))
+            .await;
+        assert_eq!(
+            res.for_llm,
+            "Daily log/2026-10-16.md:\n\n# 2026-10-16\n\n## Log\n- 07:00 Up\n\n## Food\n- porridge\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn builtin_template_and_bad_arguments() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DailyNoteConfig {
+            dir: Some("Journal/".to_string()),
+            template: None,
+        };
+        let tool = DailyNoteTool::new(Some(&config), chrono_tz::Europe::London, None);
+        let ctx = ctx(dir.path());
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"date": "2026-10-16"}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm.starts_with(
+                "Journal/2026-10-16.md (created from the template):\n\n# Friday, 2026-10-16\n"
+            ),
+            "{}",
+            res.for_llm
+        );
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"date": "16/10"}))
+            .await;
+        assert!(res.is_error);
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"action": "append"}))
+            .await;
+        assert_eq!(res.for_llm, "append needs 'text'");
+    }
+}
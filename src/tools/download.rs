@@ -0,0 +1,470 @@
+//! `download_file` tool: stream an http(s) URL into the workspace, so "save this PDF into
+//! References/" doesn't need the file to pass through the chat.
+//!
+//! The body is written to `<path>.part` as it arrives and renamed when complete. Downloads
+//! over the size cap are stopped, and a content type that contradicts the file extension
+//! (typically an HTML login or error page instead of a PDF) is refused.
+
+use std::path::Path;
+use std::time::Duration;
+
+use regex_lite::Regex;
+use reqwest::Client;
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+
+use crate::memory::backup::format_bytes;
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::web::validate_fetch_url;
+
+/// The same limit as `send_file`, so anything downloaded can be sent on.
+const MAX_DOWNLOAD_BYTES: u64 = 50 * 1024 * 1024;
+/// Whole-request limit; the shared web client's 60s is too short for large files.
+const DOWNLOAD_TIMEOUT_SECS: u64 = 600;
+const FALLBACK_NAME: &str = "download";
+
+/// Media types accepted for a file extension (prefix match). Extensions not listed accept
+/// anything but HTML.
+const EXPECTED_TYPES: &[(&str, &[&str])] = &[
+    ("pdf", &["application/pdf", "application/x-pdf"]),
+    ("png", &["image/png"]),
+    ("jpg", &["image/jpeg"]),
+    ("jpeg", &["image/jpeg"]),
+    ("gif", &["image/gif"]),
+    ("webp", &["image/webp"]),
+    ("svg", &["image/svg+xml"]),
+    ("zip", &["application/zip", "application/x-zip"]),
+    ("gz", &["application/gzip", "application/x-gzip"]),
+    ("tgz", &["application/gzip", "application/x-gzip"]),
+    ("epub", &["application/epub+zip"]),
+    ("mp3", &["audio/mpeg"]),
+    ("m4a", &["audio/mp4", "audio/x-m4a"]),
+    ("mp4", &["video/mp4"]),
+    ("csv", &["text/csv", "text/plain", "application/csv"]),
+    ("json", &["application/json", "text/plain"]),
+    ("md", &["text/"]),
+    ("txt", &["text/"]),
+    ("html", &["text/html", "application/xhtml"]),
+    ("htm", &["text/html", "application/xhtml"]),
+];
+
+/// Types that say nothing about the contents.
+const GENERIC_TYPES: &[&str] = &["", "application/octet-stream", "binary/octet-stream"];
+
+/// Extension for a nameless file of this media type.
+const TYPE_EXTENSIONS: &[(&str, &str)] = &[
+    ("application/pdf", "pdf"),
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("application/zip", "zip"),
+    ("text/csv", "csv"),
+    ("application/json", "json"),
+    ("text/plain", "txt"),
+    ("audio/mpeg", "mp3"),
+];
+
+/// `%XX` escapes decoded; invalid escapes are kept as they are.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// A file name safe to create: no separators or leading dots.
+fn clean_name(name: &str) -> Option<String> {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if matches!(c, '/' | '\\' | '\0') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let name = name.trim().trim_start_matches('.').trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// File name from a Content-Disposition header (`filename*=UTF-8''…` or `filename="…"`).
+fn disposition_name(header: &str) -> Option<String> {
+    let re = Regex::new(r#"(?i)filename\*\s*=\s*[^']*'[^']*'([^;]+)"#).expect("valid regex");
+    if let Some(c) = re.captures(header) {
+        return clean_name(&percent_decode(c[1].trim()));
+    }
+    let re = Regex::new(r#"(?i)filename\s*=\s*(?:"([^"]*)"|([^;]+))"#).expect("valid regex");
+    let c = re.captures(header)?;
+    clean_name(c.get(1).or(c.get(2))?.as_str().trim())
+}
+
+/// The response's media type, lowercased and without parameters.
+fn media_type(res: &reqwest::Response) -> String {
+    res.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Err when `media_type` contradicts the extension of `name`.
+fn check_type(name: &str, media_type: &str) -> Result<(), String> {
+    if GENERIC_TYPES.contains(&media_type) {
+        return Ok(());
+    }
+    let ext = Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let ok = match EXPECTED_TYPES.iter().find(|(e, _)| *e == ext) {
+        Some((_, types)) => types.iter().any(|t| media_type.starts_with(t)),
+        None => !media_type.starts_with("text/html"),
+    };
+    if ok {
+        Ok(())
+    } else if media_type.starts_with("text/html") {
+        Err(format!(
+            "the server sent an HTML page, not {name} (a login or error page?); save it as \
+             .html to keep it anyway"
+        ))
+    } else {
+        Err(format!(
+            "the server sent {media_type}, which does not match {name}"
+        ))
+    }
+}
+
+pub struct DownloadFileTool {
+    client: Client,
+    max_bytes: u64,
+}
+
+impl DownloadFileTool {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            max_bytes: MAX_DOWNLOAD_BYTES,
+        }
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let url = args
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or("missing 'url'")?;
+        let url = validate_fetch_url(url.trim())?;
+        let rel = args
+            .get("path")
+            .and_then(Value::as_str)
+            .ok_or("missing 'path' (file or folder in the workspace)")?
+            .trim();
+        let overwrite = args
+            .get("overwrite")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let target = resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        let into_folder =
+            rel.ends_with('/') || tokio::fs::metadata(&target).await.is_ok_and(|m| m.is_dir());
+
+        let mut res = self
+            .client
+            .get(url.clone())
+            .timeout(Duration::from_secs(DOWNLOAD_TIMEOUT_SECS))
+            .send()
+            .await
+            .map_err(|e| format!("download failed: {e}"))?;
+        if !res.status().is_success() {
+            return Err(format!("download failed: HTTP {}", res.status()));
+        }
+        let media_type = media_type(&res);
+        if let Some(len) = res.content_length()
+            && len > self.max_bytes
+        {
+            return Err(format!(
+                "the file is {}; the limit is {}",
+                format_bytes(len),
+                format_bytes(self.max_bytes)
+            ));
+        }
+
+        let (path, rel) = if into_folder {
+            let disposition = res
+                .headers()
+                .get(reqwest::header::CONTENT_DISPOSITION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(disposition_name);
+            let from_url = res
+                .url()
+                .path_segments()
+                .and_then(|mut s| s.next_back())
+                .and_then(|s| clean_name(&percent_decode(s)));
+            let mut name = disposition
+                .or(from_url)
+                .unwrap_or_else(|| FALLBACK_NAME.to_string());
+            if Path::new(&name).extension().is_none()
+                && let Some((_, ext)) = TYPE_EXTENSIONS.iter().find(|(t, _)| *t == media_type)
+            {
+                name = format!("{name}.{ext}");
+            }
+            let rel = format!("{}/{name}", rel.trim_end_matches('/'));
+            let rel = rel.trim_start_matches('/').to_string();
+            (target.join(&name), rel)
+        } else {
+            (target, rel.to_string())
+        };
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        check_type(&name, &media_type)?;
+        if !overwrite && tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Err(format!(
+                "{rel} already exists (pass overwrite=true to replace it)"
+            ));
+        }
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("creating the folder failed: {e}"))?;
+        }
+        let part = path.with_file_name(format!("{name}.part"));
+        let written = match self.stream(&mut res, &part).await {
+            Ok(written) => written,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&part).await;
+                return Err(e);
+            }
+        };
+        tokio::fs::rename(&part, &path)
+            .await
+            .map_err(|e| format!("saving {rel} failed: {e}"))?;
+        let kind = if media_type.is_empty() {
+            String::new()
+        } else {
+            format!(", {media_type}")
+        };
+        Ok(format!("Saved {rel} ({}{kind}).", format_bytes(written)))
+    }
+
+    /// Write the body to `part` chunk by chunk, stopping past the size cap.
+    async fn stream(&self, res: &mut reqwest::Response, part: &Path) -> Result<u64, String> {
+        let mut file = tokio::fs::File::create(part)
+            .await
+            .map_err(|e| format!("creating the file failed: {e}"))?;
+        let mut written = 0u64;
+        while let Some(chunk) = res
+            .chunk()
+            .await
+            .map_err(|e| format!("download failed: {e}"))?
+        {
+            written += chunk.len() as u64;
+            if written > self.max_bytes {
+                return Err(format!(
+                    "the file is larger than the {} limit",
+                    format_bytes(self.max_bytes)
+                ));
+            }
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| format!("writing the file failed: {e}"))?;
+        }
+        file.flush()
+            .await
+            .map_err(|e| format!("writing the file failed: {e}"))?;
+        Ok(written)
+    }
+}
+
+impl Tool for DownloadFileTool {
+    fn name(&self) -> &str {
+        "download_file"
+    }
+
+    fn description(&self) -> &str {
+        "Download a URL (http or https) straight into the workspace, e.g. a PDF into \
+         References/. path is a file, or a folder ending in / to keep the server's file name. \
+         Max 50 MB; an HTML page served instead of the expected file is refused."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "URL to download" },
+                "path": { "type": "string", "description": "Destination file (References/paper.pdf) or folder (References/)" },
+                "overwrite": { "type": "boolean", "description": "Replace an existing file (default false)" }
+            },
+            "required": ["url", "path"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::tools::web::web_client;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    async fn server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/papers/Attention%20Is%20All.pdf"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(b"%PDF-1.7 body".to_vec(), "application/pdf"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/get"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(b"%PDF".to_vec(), "application/octet-stream")
+                    .insert_header(
+                        "content-disposition",
+                        "attachment; filename=\"../Q3 report.pdf\"",
+                    ),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/login"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(b"<html>".to_vec(), "text/html"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/big.zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(vec![0; 4096], "application/zip"))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[test]
+    fn names_and_types() {
+        assert_eq!(percent_decode("a%20b%2"), "a b%2");
+        assert_eq!(
+            disposition_name("attachment; filename*=UTF-8''na%C3%AFve.pdf"),
+            Some("naïve.pdf".to_string())
+        );
+        assert_eq!(
+            disposition_name("inline; filename=notes.txt"),
+            Some("notes.txt".to_string())
+        );
+        assert_eq!(clean_name("../.."), Some("_..".to_string()));
+        assert_eq!(clean_name(" .."), None);
+        assert!(check_type("a.pdf", "application/pdf").is_ok());
+        assert!(check_type("a.pdf", "").is_ok());
+        assert!(check_type("a.bin", "image/png").is_ok());
+        assert!(check_type("page.html", "text/html").is_ok());
+        assert!(check_type("a.pdf", "image/png").is_err());
+        assert!(check_type("a.bin", "text/html").is_err());
+    }
+
+    #[tokio::test]
+    async fn saves_to_a_file_or_a_folder() {
+        let server = server().await;
+        let dir = TempDir::new().unwrap();
+        let tool = DownloadFileTool::new(web_client().unwrap());
+
+        let url = format!("{}/papers/Attention%20Is%20All.pdf", server.uri());
+        let args = json!({"url": url, "path": "References/"});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Saved References/Attention Is All.pdf (13 B, application/pdf)."
+        );
+        assert_eq!(
+            std::fs::read(dir.path().join("References/Attention Is All.pdf")).unwrap(),
+            b"%PDF-1.7 body"
+        );
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert_eq!(
+            res.for_llm,
+            "References/Attention Is All.pdf already exists (pass overwrite=true to replace it)"
+        );
+
+        let args = json!({"url": format!("{}/get", server.uri()), "path": "References"});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Saved References/_Q3 report.pdf (4 B, application/octet-stream)."
+        );
+
+        let args = json!({"url": url, "path": "paper.pdf"});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(dir.path().join("paper.pdf").exists());
+    }
+
+    #[tokio::test]
+    async fn refuses_html_oversized_and_outside_paths() {
+        let server = server().await;
+        let dir = TempDir::new().unwrap();
+        let mut tool = DownloadFileTool::new(web_client().unwrap());
+        tool.max_bytes = 1024;
+
+        let args = json!({"url": format!("{}/login", server.uri()), "path": "doc.pdf"});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert!(res.is_error);
+        assert!(
+            res.for_llm
+                .starts_with("the server sent an HTML page, not doc.pdf")
+        );
+
+        let args = json!({"url": format!("{}/big.zip", server.uri()), "path": "big.zip"});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert_eq!(res.for_llm, "the file is 4.0 KB; the limit is 1.0 KB");
+
+        let args = json!({"url": format!("{}/big.zip", server.uri()), "path": "../big.zip"});
+        assert!(tool.execute(&ctx(dir.path()), &args).await.is_error);
+        let args = json!({"url": "file:///etc/passwd", "path": "passwd"});
+        assert!(tool.execute(&ctx(dir.path()), &args).await.is_error);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,1302 @@
+//! `email` tool: `list_unread`, `read` and `send` for the mailboxes in
+//! `[tools.email.accounts]` — IMAP to read, SMTP to send. Passwords stay in config; the
+//! agent only names the account.
+//!
+//! Both protocols are spoken directly over tokio-rustls (the handful of commands needed here),
+//! with a small MIME reader: plain text preferred over HTML, base64 and quoted-printable
+//! bodies, RFC 2047 encoded headers, attachment names.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use regex_lite::Regex;
+use rustls_platform_verifier::ConfigVerifierExt;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::ClientConfig;
+use tokio_rustls::rustls::pki_types::ServerName;
+
+use crate::config::{EmailAccount, EmailConfig};
+use crate::llm::{base64_decode, base64_encode};
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::web::html_to_text;
+
+const DEFAULT_IMAP_PORT: u16 = 993;
+const DEFAULT_SMTP_PORT: u16 = 465;
+const DEFAULT_MAILBOX: &str = "INBOX";
+const DEFAULT_LIST_LIMIT: usize = 10;
+const MAX_LIST_LIMIT: usize = 50;
+const MAX_BODY_CHARS: usize = 20_000;
+/// Limit on one IMAP or SMTP conversation, connect to logout.
+const SESSION_TIMEOUT_SECS: u64 = 60;
+/// Header fields fetched to answer a message.
+const REPLY_FIELDS: &str = "FROM REPLY-TO SUBJECT MESSAGE-ID REFERENCES";
+
+// ---------------------------------------------------------------------------
+// Transport
+// ---------------------------------------------------------------------------
+
+async fn connect(host: &str, port: u16) -> Result<TcpStream, String> {
+    TcpStream::connect((host, port))
+        .await
+        .map_err(|e| format!("connecting to {host}:{port} failed: {e}"))
+}
+
+async fn tls_wrap<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    host: &str,
+) -> Result<TlsStream<S>, String> {
+    let config =
+        ClientConfig::with_platform_verifier().map_err(|e| format!("TLS setup failed: {e}"))?;
+    let name = ServerName::try_from(host.to_string())
+        .map_err(|_| format!("invalid mail server name '{host}'"))?;
+    TlsConnector::from(Arc::new(config))
+        .connect(name, stream)
+        .await
+        .map_err(|e| format!("TLS handshake with {host} failed: {e}"))
+}
+
+async fn within<T>(session: impl Future<Output = Result<T, String>>) -> Result<T, String> {
+    tokio::time::timeout(
+        std::time::Duration::from_secs(SESSION_TIMEOUT_SECS),
+        session,
+    )
+    .await
+    .map_err(|_| format!("mail server did not finish within {SESSION_TIMEOUT_SECS}s"))?
+}
+
+/// IMAP quoted string.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// One untagged IMAP response: its text with each literal replaced by `{}`, and the literals.
+#[derive(Debug, Default)]
+struct Untagged {
+    text: String,
+    literals: Vec<Vec<u8>>,
+}
+
+struct Imap<S> {
+    io: BufReader<S>,
+    tag: u32,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Imap<S> {
+    async fn open(stream: S) -> Result<Self, String> {
+        let mut imap = Self {
+            io: BufReader::new(stream),
+            tag: 0,
+        };
+        let greeting = imap.line().await?;
+        if !greeting.starts_with("* OK") && !greeting.starts_with("* PREAUTH") {
+            return Err(format!("unexpected IMAP greeting: {}", greeting.trim()));
+        }
+        Ok(imap)
+    }
+
+    async fn line(&mut self) -> Result<String, String> {
+        let mut buf = Vec::new();
+        let n = self
+            .io
+            .read_until(b'\n', &mut buf)
+            .await
+            .map_err(|e| format!("IMAP read failed: {e}"))?;
+        if n == 0 {
+            return Err("IMAP server closed the connection".to_string());
+        }
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    /// Run `command`; its untagged responses, or the server's NO/BAD text as the error.
+    async fn run(&mut self, command: &str) -> Result<Vec<Untagged>, String> {
+        self.tag += 1;
+        let tag = format!("a{} ", self.tag);
+        let stream = self.io.get_mut();
+        stream
+            .write_all(format!("{tag}{command}\r\n").as_bytes())
+            .await
+            .map_err(|e| format!("IMAP write failed: {e}"))?;
+        stream
+            .flush()
+            .await
+            .map_err(|e| format!("IMAP write failed: {e}"))?;
+
+        let mut out = Vec::new();
+        loop {
+            let mut line = self.line().await?;
+            if let Some(status) = line.strip_prefix(&tag) {
+                let status = status.trim();
+                return if status
+                    .get(..2)
+                    .is_some_and(|s| s.eq_ignore_ascii_case("OK"))
+                {
+                    Ok(out)
+                } else {
+                    Err(format!("IMAP: {status}"))
+                };
+            }
+            let mut resp = Untagged::default();
+            loop {
+                let trimmed = line.trim_end_matches(['\r', '\n']);
+                // A line ending in {n} is followed by n bytes, then the rest of the response.
+                let literal = trimmed
+                    .strip_suffix('}')
+                    .and_then(|t| t.rsplit_once('{'))
+                    .and_then(|(head, n)| Some((head, n.parse::<usize>().ok()?)));
+                let Some((head, len)) = literal else {
+                    resp.text.push_str(trimmed);
+                    break;
+                };
+                resp.text.push_str(head);
+                resp.text.push_str("{}");
+                let mut data = vec![0; len];
+                self.io
+                    .read_exact(&mut data)
+                    .await
+                    .map_err(|e| format!("IMAP read failed: {e}"))?;
+                resp.literals.push(data);
+                line = self.line().await?;
+            }
+            out.push(resp);
+        }
+    }
+
+    async fn login(&mut self, username: &str, password: &str) -> Result<(), String> {
+        self.run(&format!("LOGIN {} {}", quote(username), quote(password)))
+            .await
+            .map(drop)
+            .map_err(|e| format!("login failed: {e}"))
+    }
+
+    /// Unread messages in `mailbox`, newest first: the total, and headers of the first `limit`.
+    async fn unread(
+        &mut self,
+        mailbox: &str,
+        limit: usize,
+    ) -> Result<(usize, Vec<Summary>), String> {
+        self.run(&format!("EXAMINE {}", quote(mailbox))).await?;
+        let mut uids: Vec<u32> = self
+            .run("UID SEARCH UNSEEN")
+            .await?
+            .iter()
+            .filter_map(|r| r.text.strip_prefix("* SEARCH"))
+            .flat_map(|rest| rest.split_whitespace().filter_map(|n| n.parse().ok()))
+            .collect();
+        uids.sort_unstable_by_key(|&uid| std::cmp::Reverse(uid));
+        let total = uids.len();
+        uids.truncate(limit);
+        if uids.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+        let set: Vec<String> = uids.iter().map(u32::to_string).collect();
+        let responses = self
+            .run(&format!(
+                "UID FETCH {} (UID BODY.PEEK[HEADER.FIELDS (FROM SUBJECT DATE)])",
+                set.join(",")
+            ))
+            .await?;
+        let uid_re = Regex::new(r"UID (\d+)").unwrap();
+        let mut summaries: Vec<Summary> = responses
+            .iter()
+            .filter_map(|r| {
+                let uid = uid_re.captures(&r.text)?[1].parse().ok()?;
+                let (headers, _) = split_message(r.literals.first()?);
+                Some(Summary {
+                    uid,
+                    from: decode_words(header(&headers, "from").unwrap_or("")),
+                    subject: decode_words(header(&headers, "subject").unwrap_or("")),
+                    date: header(&headers, "date")
+                        .and_then(|d| DateTime::parse_from_rfc2822(d).ok())
+                        .map(|d| d.with_timezone(&Utc)),
+                })
+            })
+            .collect();
+        summaries.sort_by_key(|s| std::cmp::Reverse(s.uid));
+        Ok((total, summaries))
+    }
+
+    /// The data of `item` (e.g. `BODY[]`) for message `uid`. `BODY[]` needs `writable`, since
+    /// fetching it marks the message read.
+    async fn fetch(
+        &mut self,
+        mailbox: &str,
+        uid: u32,
+        item: &str,
+        writable: bool,
+    ) -> Result<Vec<u8>, String> {
+        let open = if writable { "SELECT" } else { "EXAMINE" };
+        self.run(&format!("{open} {}", quote(mailbox))).await?;
+        self.run(&format!("UID FETCH {uid} ({item})"))
+            .await?
+            .into_iter()
+            .find_map(|r| r.literals.into_iter().next())
+            .ok_or_else(|| format!("no message with uid {uid} in {mailbox}"))
+    }
+
+    async fn logout(&mut self) {
+        let _ = self.run("LOGOUT").await;
+    }
+}
+
+async fn imap_session(account: &EmailAccount) -> Result<Imap<TlsStream<TcpStream>>, String> {
+    let port = account.imap_port.unwrap_or(DEFAULT_IMAP_PORT);
+    let tcp = connect(&account.imap_host, port).await?;
+    let mut imap = Imap::open(tls_wrap(tcp, &account.imap_host).await?).await?;
+    imap.login(&account.username, &account.password).await?;
+    Ok(imap)
+}
+
+struct Smtp<S> {
+    io: BufReader<S>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Smtp<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            io: BufReader::new(stream),
+        }
+    }
+
+    /// Read a (possibly multi-line) reply; an error unless its code is in `class` (2xx, 3xx).
+    async fn reply(&mut self, class: u16) -> Result<String, String> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            let n = self
+                .io
+                .read_line(&mut line)
+                .await
+                .map_err(|e| format!("SMTP read failed: {e}"))?;
+            if n == 0 {
+                return Err("SMTP server closed the connection".to_string());
+            }
+            let code: u16 = line
+                .get(..3)
+                .and_then(|c| c.parse().ok())
+                .ok_or_else(|| format!("unexpected SMTP reply: {}", line.trim()))?;
+            text.push_str(line.get(4..).unwrap_or("").trim());
+            text.push('\n');
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return if code / 100 == class {
+                    Ok(text)
+                } else {
+                    Err(format!("SMTP {code} {}", text.trim()))
+                };
+            }
+        }
+    }
+
+    async fn send(&mut self, line: &str, class: u16) -> Result<String, String> {
+        let stream = self.io.get_mut();
+        stream
+            .write_all(format!("{line}\r\n").as_bytes())
+            .await
+            .map_err(|e| format!("SMTP write failed: {e}"))?;
+        stream
+            .flush()
+            .await
+            .map_err(|e| format!("SMTP write failed: {e}"))?;
+        self.reply(class).await
+    }
+
+    /// Log in and hand over `message` (CRLF lines) for `to`, after EHLO.
+    async fn deliver(
+        &mut self,
+        account: &EmailAccount,
+        from: &str,
+        to: &[String],
+        message: &str,
+    ) -> Result<(), String> {
+        let login = format!("\0{}\0{}", account.username, account.password);
+        self.send(
+            &format!("AUTH PLAIN {}", base64_encode(login.as_bytes())),
+            2,
+        )
+        .await
+        .map_err(|e| format!("login failed: {e}"))?;
+        self.send(&format!("MAIL FROM:<{from}>"), 2).await?;
+        for rcpt in to {
+            self.send(&format!("RCPT TO:<{rcpt}>"), 2).await?;
+        }
+        self.send("DATA", 3).await?;
+        // Lines starting with "." are dot-stuffed so they can't end the data early.
+        let data: Vec<String> = message
+            .split("\r\n")
+            .map(|l| {
+                if l.starts_with('.') {
+                    format!(".{l}")
+                } else {
+                    l.to_string()
+                }
+            })
+            .collect();
+        self.send(&format!("{}\r\n.", data.join("\r\n")), 2).await?;
+        let _ = self.send("QUIT", 2).await;
+        Ok(())
+    }
+}
+
+/// Send `message` through the account's SMTP server: TLS from the start on 465, STARTTLS
+/// on any other port.
+async fn smtp_send(
+    account: &EmailAccount,
+    host: &str,
+    from: &str,
+    to: &[String],
+    message: &str,
+) -> Result<(), String> {
+    let port = account.smtp_port.unwrap_or(DEFAULT_SMTP_PORT);
+    let tcp = connect(host, port).await?;
+    let mut smtp = if port == DEFAULT_SMTP_PORT {
+        let mut smtp = Smtp::new(tls_wrap(tcp, host).await?);
+        smtp.reply(2).await?;
+        smtp
+    } else {
+        let mut plain = Smtp::new(tcp);
+        plain.reply(2).await?;
+        plain.send("EHLO localhost", 2).await?;
+        plain.send("STARTTLS", 2).await?;
+        Smtp::new(tls_wrap(plain.io.into_inner(), host).await?)
+    };
+    smtp.send("EHLO localhost", 2).await?;
+    smtp.deliver(account, from, to, message).await
+}
+
+// ---------------------------------------------------------------------------
+// Messages
+// ---------------------------------------------------------------------------
+
+type Headers = Vec<(String, String)>;
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Header fields (unfolded, names lowercased) and the body of a message or MIME part.
+fn split_message(raw: &[u8]) -> (Headers, &[u8]) {
+    let (head, body) = match (find(raw, b"\r\n\r\n"), find(raw, b"\n\n")) {
+        (Some(i), Some(j)) if j < i => (&raw[..j], &raw[j + 2..]),
+        (Some(i), _) => (&raw[..i], &raw[i + 4..]),
+        (None, Some(j)) => (&raw[..j], &raw[j + 2..]),
+        (None, None) => (raw, &raw[raw.len()..]),
+    };
+    let mut headers: Headers = Vec::new();
+    for line in String::from_utf8_lossy(head).lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// `text/plain; charset="utf-8"` as ("text/plain", {"charset": "utf-8"}).
+fn content_type(value: &str) -> (String, BTreeMap<String, String>) {
+    let mut parts = value.split(';');
+    let mime = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| {
+            (
+                k.trim().to_ascii_lowercase(),
+                v.trim().trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    (mime, params)
+}
+
+fn qp_decode(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] == b'=' {
+            let rest = &input[i + 1..];
+            // Soft line break.
+            if rest.starts_with(b"\r\n") {
+                i += 3;
+                continue;
+            }
+            if rest.starts_with(b"\n") {
+                i += 2;
+                continue;
+            }
+            let byte = rest
+                .get(..2)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+            if let Some(b) = byte {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(input[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Text in `charset`: UTF-8 (and ASCII), Latin-1 and Windows-1252 (approximated as Latin-1).
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    let charset = charset.unwrap_or("utf-8").to_ascii_lowercase();
+    if ["iso-8859-1", "latin1", "windows-1252", "cp1252"].contains(&charset.as_str()) {
+        bytes.iter().map(|&b| b as char).collect()
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// Header value with RFC 2047 encoded words (`=?utf-8?B?…?=`) decoded.
+fn decode_words(value: &str) -> String {
+    let re = Regex::new(r"=\?([^?]+)\?([BbQq])\?([^?]*)\?=").unwrap();
+    let mut out = String::new();
+    let mut last = 0;
+    for caps in re.captures_iter(value) {
+        let whole = caps.get(0).unwrap();
+        let gap = &value[last..whole.start()];
+        // Whitespace between two encoded words is not part of the text.
+        if last == 0 || !gap.trim().is_empty() {
+            out.push_str(gap);
+        }
+        let bytes = if caps[2].eq_ignore_ascii_case("B") {
+            base64_decode(caps[3].as_bytes())
+        } else {
+            qp_decode(caps[3].replace('_', " ").as_bytes())
+        };
+        out.push_str(&decode_charset(&bytes, Some(&caps[1])));
+        last = whole.end();
+    }
+    out.push_str(&value[last..]);
+    out
+}
+
+/// The parts of a `multipart/*` body, delimited by `boundary`.
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delim = format!("--{boundary}");
+    let delim = delim.as_bytes();
+    let mut parts = Vec::new();
+    let Some(start) = find(body, delim) else {
+        return parts;
+    };
+    let mut rest = &body[start + delim.len()..];
+    // After each delimiter: "--" closes the body, otherwise a part follows the line break.
+    while !rest.starts_with(b"--") {
+        let Some(nl) = rest.iter().position(|&b| b == b'\n') else {
+            break;
+        };
+        rest = &rest[nl + 1..];
+        let end = find(rest, delim);
+        let part = &rest[..end.unwrap_or(rest.len())];
+        let part = part
+            .strip_suffix(b"\r\n")
+            .or_else(|| part.strip_suffix(b"\n"))
+            .unwrap_or(part);
+        parts.push(part);
+        match end {
+            Some(e) => rest = &rest[e + delim.len()..],
+            None => break,
+        }
+    }
+    parts
+}
+
+/// A non-multipart MIME entity, transfer encoding undone.
+struct Leaf {
+    mime: String,
+    charset: Option<String>,
+    filename: Option<String>,
+    attachment: bool,
+    data: Vec<u8>,
+}
+
+fn walk(headers: &[(String, String)], body: &[u8], depth: usize, leaves: &mut Vec<Leaf>) {
+    let (mime, params) = content_type(header(headers, "content-type").unwrap_or("text/plain"));
+    if mime.starts_with("multipart/")
+        && let Some(boundary) = params.get("boundary")
+        && depth < 8
+    {
+        for part in split_multipart(body, boundary) {
+            let (h, b) = split_message(part);
+            walk(&h, b, depth + 1, leaves);
+        }
+        return;
+    }
+    let encoding = header(headers, "content-transfer-encoding")
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    let data = match encoding.as_str() {
+        "base64" => base64_decode(body),
+        "quoted-printable" => qp_decode(body),
+        _ => body.to_vec(),
+    };
+    let disposition = header(headers, "content-disposition").map(content_type);
+    let filename = disposition
+        .as_ref()
+        .and_then(|(_, p)| p.get("filename"))
+        .or_else(|| params.get("name"))
+        .map(|f| decode_words(f));
+    let attachment =
+        filename.is_some() || disposition.is_some_and(|(kind, _)| kind == "attachment");
+    leaves.push(Leaf {
+        charset: params.get("charset").cloned(),
+        mime,
+        filename,
+        attachment,
+        data,
+    });
+}
+
+/// Readable body text (plain preferred over HTML) and attachment names of a message.
+fn message_text(headers: &[(String, String)], body: &[u8]) -> (String, Vec<String>) {
+    let mut leaves = Vec::new();
+    walk(headers, body, 0, &mut leaves);
+    let inline = |mime: &str| {
+        leaves
+            .iter()
+            .find(|l| !l.attachment && l.mime == mime)
+            .map(|l| decode_charset(&l.data, l.charset.as_deref()))
+    };
+    let text = inline("text/plain")
+        .or_else(|| inline("text/html").map(|html| html_to_text(&html)))
+        .unwrap_or_default()
+        .replace("\r\n", "\n");
+    let attachments = leaves.iter().filter_map(|l| l.filename.clone()).collect();
+    (text.trim().to_string(), attachments)
+}
+
+/// Headers and text of a fetched message, dates shown in `tz`.
+fn render_message(raw: &[u8], tz: Tz) -> String {
+    let (headers, body) = split_message(raw);
+    let mut out = String::new();
+    for (label, name) in [
+        ("From", "from"),
+        ("To", "to"),
+        ("Cc", "cc"),
+        ("Subject", "subject"),
+    ] {
+        if let Some(value) = header(&headers, name) {
+            out.push_str(&format!("{label}: {}\n", decode_words(value)));
+        }
+    }
+    if let Some(date) = header(&headers, "date") {
+        let shown = DateTime::parse_from_rfc2822(date)
+            .map(|d| {
+                d.with_timezone(&tz)
+                    .format("%a %-d %b %Y %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| date.to_string());
+        out.push_str(&format!("Date: {shown}\n"));
+    }
+    let (text, attachments) = message_text(&headers, body);
+    if !attachments.is_empty() {
+        out.push_str(&format!("Attachments: {}\n", attachments.join(", ")));
+    }
+    out.push('\n');
+    match text.char_indices().nth(MAX_BODY_CHARS) {
+        Some((cut, _)) => {
+            out.push_str(&text[..cut]);
+            out.push_str("\n[… message truncated]");
+        }
+        None if text.is_empty() => out.push_str("(no text)"),
+        None => out.push_str(&text),
+    }
+    out
+}
+
+/// Mail addresses in a header or argument: the `<…>` parts, else comma-separated items.
+fn addresses(value: &str) -> Vec<String> {
+    let re = Regex::new(r"<([^<>\s]+@[^<>\s]+)>").unwrap();
+    let bracketed: Vec<String> = re.captures_iter(value).map(|c| c[1].to_string()).collect();
+    if !bracketed.is_empty() {
+        return bracketed;
+    }
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|a| a.contains('@'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Header text, RFC 2047-encoded when not ASCII.
+fn encode_words(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    // Encoded words are limited to 75 chars; split on char boundaries.
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in text.chars() {
+        if chunk.len() + c.len_utf8() > 45 {
+            words.push(format!("=?UTF-8?B?{}?=", base64_encode(chunk.as_bytes())));
+            chunk.clear();
+        }
+        chunk.push(c);
+    }
+    words.push(format!("=?UTF-8?B?{}?=", base64_encode(chunk.as_bytes())));
+    words.join("\r\n ")
+}
+
+/// A plain-text message (CRLF lines). `thread` is the (Message-ID, References) answered.
+fn build_message(
+    from: &str,
+    to: &[String],
+    subject: &str,
+    body: &str,
+    thread: Option<&(String, String)>,
+    date: DateTime<Utc>,
+) -> String {
+    let sender = addresses(from).into_iter().next().unwrap_or_default();
+    let domain = sender.rsplit_once('@').map_or("icrab.local", |(_, d)| d);
+    let mut headers = vec![
+        format!("Date: {}", date.to_rfc2822()),
+        format!("From: {from}"),
+        format!("To: {}", to.join(", ")),
+        format!("Subject: {}", encode_words(subject)),
+        format!("Message-ID: <{}@{domain}>", uuid::Uuid::new_v4()),
+    ];
+    if let Some((id, references)) = thread {
+        headers.push(format!("In-Reply-To: {id}"));
+        headers.push(format!("References: {references}"));
+    }
+    headers.push("MIME-Version: 1.0".to_string());
+    headers.push("Content-Type: text/plain; charset=utf-8".to_string());
+    let body = body.replace("\r\n", "\n");
+    let body = if body.is_ascii() {
+        headers.push("Content-Transfer-Encoding: 7bit".to_string());
+        body.replace('\n', "\r\n")
+    } else {
+        headers.push("Content-Transfer-Encoding: base64".to_string());
+        let encoded = base64_encode(body.replace('\n', "\r\n").as_bytes());
+        let lines: Vec<&str> = encoded
+            .as_bytes()
+            .chunks(76)
+            .map(|c| std::str::from_utf8(c).expect("base64 is ASCII"))
+            .collect();
+        lines.join("\r\n")
+    };
+    format!("{}\r\n\r\n{body}", headers.join("\r\n"))
+}
+
+/// An unread message in a `list_unread` result.
+#[derive(Debug)]
+struct Summary {
+    uid: u32,
+    from: String,
+    subject: String,
+    date: Option<DateTime<Utc>>,
+}
+
+impl Summary {
+    fn line(&self, tz: Tz) -> String {
+        let date = self
+            .date
+            .map(|d| format!("{} · ", d.with_timezone(&tz).format("%a %-d %b %H:%M")))
+            .unwrap_or_default();
+        let subject = if self.subject.is_empty() {
+            "(no subject)"
+        } else {
+            &self.subject
+        };
+        format!("- [uid {}] {date}{}: {subject}", self.uid, self.from)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+pub struct EmailTool {
+    accounts: BTreeMap<String, EmailAccount>,
+    timezone: Tz,
+}
+
+impl EmailTool {
+    /// None when no accounts are configured.
+    pub fn from_config(config: Option<&EmailConfig>, timezone: Tz) -> Option<Self> {
+        let accounts = config?.accounts.clone().filter(|a| !a.is_empty())?;
+        Some(Self { accounts, timezone })
+    }
+
+    /// The named account, or the only one when `account` is omitted.
+    fn account<'a>(&'a self, args: &Value) -> Result<(&'a str, &'a EmailAccount), String> {
+        let names = || self.accounts.keys().cloned().collect::<Vec<_>>().join(", ");
+        match args.get("account").and_then(Value::as_str) {
+            Some(name) => self
+                .accounts
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(n, a)| (n.as_str(), a))
+                .ok_or_else(|| format!("unknown account '{name}' (configured: {})", names())),
+            None if self.accounts.len() == 1 => self
+                .accounts
+                .iter()
+                .next()
+                .map(|(n, a)| (n.as_str(), a))
+                .ok_or_else(|| "no email accounts configured".to_string()),
+            None => Err(format!("account is required (configured: {})", names())),
+        }
+    }
+
+    async fn list_unread(&self, args: &Value) -> Result<String, String> {
+        let mailbox = mailbox(args);
+        let limit = args
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map_or(DEFAULT_LIST_LIMIT, |n| n as usize)
+            .clamp(1, MAX_LIST_LIMIT);
+        let selected: Vec<(&str, &EmailAccount)> = if args.get("account").is_some() {
+            vec![self.account(args)?]
+        } else {
+            self.accounts.iter().map(|(n, a)| (n.as_str(), a)).collect()
+        };
+        let results =
+            futures_util::future::join_all(selected.iter().map(|&(name, account)| async move {
+                let listed = within(async {
+                    let mut imap = imap_session(account).await?;
+                    let listed = imap.unread(mailbox, limit).await;
+                    imap.logout().await;
+                    listed
+                })
+                .await;
+                (name, listed)
+            }))
+            .await;
+
+        let mut sections = Vec::new();
+        for (name, listed) in results {
+            sections.push(match listed {
+                Ok((0, _)) => format!("{name}: no unread mail in {mailbox}."),
+                Ok((total, summaries)) => {
+                    let mut out = format!("{name}: {total} unread in {mailbox}");
+                    if total > summaries.len() {
+                        out.push_str(&format!(" (newest {} shown)", summaries.len()));
+                    }
+                    out.push(':');
+                    for s in &summaries {
+                        out.push('\n');
+                        out.push_str(&s.line(self.timezone));
+                    }
+                    out
+                }
+                Err(e) => format!("{name}: could not check mail: {e}"),
+            });
+        }
+        Ok(sections.join("\n\n"))
+    }
+
+    async fn read(&self, args: &Value) -> Result<String, String> {
+        let (_, account) = self.account(args)?;
+        let uid = uid_arg(args, "uid")?.ok_or("uid is required for read")?;
+        let mailbox = mailbox(args);
+        let raw = within(async {
+            let mut imap = imap_session(account).await?;
+            let raw = imap.fetch(mailbox, uid, "BODY[]", true).await;
+            imap.logout().await;
+            raw
+        })
+        .await?;
+        Ok(render_message(&raw, self.timezone))
+    }
+
+    async fn send(&self, args: &Value) -> Result<String, String> {
+        let (name, account) = self.account(args)?;
+        let smtp_host = account
+            .smtp_host
+            .as_deref()
+            .filter(|h| !h.trim().is_empty())
+            .ok_or_else(|| format!("account '{name}' has no smtp-host, so it can only read"))?;
+        let body = args
+            .get("body")
+            .and_then(Value::as_str)
+            .filter(|b| !b.trim().is_empty())
+            .ok_or("body is required for send")?;
+        let mut to: Vec<String> = match args.get("to") {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(Value::as_str)
+                .flat_map(addresses)
+                .collect(),
+            Some(Value::String(s)) => addresses(s),
+            _ => Vec::new(),
+        };
+        let mut subject = args
+            .get("subject")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        // Replying: recipients, subject and threading headers come from the original.
+        let mut thread = None;
+        if let Some(uid) = uid_arg(args, "reply_to")? {
+            let item = format!("BODY.PEEK[HEADER.FIELDS ({REPLY_FIELDS})]");
+            let mailbox = mailbox(args);
+            let raw = within(async {
+                let mut imap = imap_session(account).await?;
+                let raw = imap.fetch(mailbox, uid, &item, false).await;
+                imap.logout().await;
+                raw
+            })
+            .await?;
+            let (original, _) = split_message(&raw);
+            if to.is_empty() {
+                let sender = header(&original, "reply-to").or_else(|| header(&original, "from"));
+                to = addresses(sender.unwrap_or(""));
+            }
+            if subject.is_none() {
+                let original = decode_words(header(&original, "subject").unwrap_or(""));
+                subject = Some(if original.to_ascii_lowercase().starts_with("re:") {
+                    original
+                } else {
+                    format!("Re: {original}")
+                });
+            }
+            if let Some(id) = header(&original, "message-id") {
+                let references = match header(&original, "references") {
+                    Some(refs) => format!("{refs} {id}"),
+                    None => id.to_string(),
+                };
+                thread = Some((id.to_string(), references));
+            }
+        }
+
+        if to.is_empty() {
+            return Err(
+                "to is required (or reply_to with the uid of the message to answer)".into(),
+            );
+        }
+        if let Some(bad) = to
+            .iter()
+            .find(|a| a.contains(|c: char| c.is_whitespace() || "<>,;\"".contains(c)))
+        {
+            return Err(format!("invalid address '{bad}'"));
+        }
+        let subject = subject.unwrap_or_default().replace(['\r', '\n'], " ");
+        let from = account.from.as_deref().unwrap_or(&account.username);
+        let message = build_message(from, &to, &subject, body, thread.as_ref(), Utc::now());
+        // The envelope sender is the bare address of a "Name <address>" from.
+        let sender = addresses(from)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| from.to_string());
+        within(smtp_send(account, smtp_host, &sender, &to, &message)).await?;
+        Ok(format!(
+            "Sent \"{subject}\" from {from} to {}.",
+            to.join(", ")
+        ))
+    }
+}
+
+fn mailbox(args: &Value) -> &str {
+    args.get("mailbox")
+        .and_then(Value::as_str)
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or(DEFAULT_MAILBOX)
+}
+
+fn uid_arg(args: &Value, key: &str) -> Result<Option<u32>, String> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .or_else(|| v.as_str().and_then(|s| s.trim().parse().ok()))
+            .and_then(|n| u32::try_from(n).ok())
+            .map(Some)
+            .ok_or_else(|| format!("{key} must be a message uid from list_unread")),
+    }
+}
+
+impl Tool for EmailTool {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn description(&self) -> &str {
+        "The user's email. list_unread: newest unread messages (all accounts unless one is \
+         named; use for inbox digests). read: one message by uid (marks it read). send: a \
+         plain-text mail, or with reply_to the answer to a uid (recipient, subject and threading \
+         filled in). Only send what the user asked to send."
+    }
+
+    fn parameters(&self) -> Value {
+        let names: Vec<&str> = self.accounts.keys().map(String::as_str).collect();
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["list_unread", "read", "send"] },
+                "account": { "type": "string", "description": format!("Account: {}", names.join(", ")) },
+                "mailbox": { "type": "string", "description": "Mailbox for list_unread/read (default INBOX)" },
+                "limit": { "type": "integer", "description": "list_unread: max messages per account (default 10)" },
+                "uid": { "type": "integer", "description": "read: uid from list_unread" },
+                "to": {
+                    "description": "send: recipient address(es)",
+                    "anyOf": [{ "type": "string" }, { "type": "array", "items": { "type": "string" } }]
+                },
+                "subject": { "type": "string", "description": "send: subject" },
+                "body": { "type": "string", "description": "send: plain-text body" },
+                "reply_to": { "type": "integer", "description": "send: uid of the message being answered" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let result = match args.get("action").and_then(Value::as_str) {
+                Some("list_unread") => self.list_unread(args).await,
+                Some("read") => self.read(args).await,
+                Some("send") => self.send(args).await,
+                _ => Err("action must be list_unread, read or send".to_string()),
+            };
+            match result {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tokio::io::DuplexStream;
+
+    const LONDON: Tz = chrono_tz::Europe::London;
+
+    /// Sends `greeting`, then answers each client line (a whole DATA block counts as one) with
+    /// the next reply, `{tag}` standing for the line's first word. Returns what the client sent.
+    async fn serve(stream: DuplexStream, greeting: &str, replies: Vec<String>) -> String {
+        let mut io = BufReader::new(stream);
+        io.get_mut().write_all(greeting.as_bytes()).await.unwrap();
+        let mut transcript = String::new();
+        let mut in_data = false;
+        for reply in replies {
+            let mut received = String::new();
+            loop {
+                let mut line = String::new();
+                if io.read_line(&mut line).await.unwrap() == 0 {
+                    return transcript;
+                }
+                received.push_str(&line);
+                if !in_data || line == ".\r\n" {
+                    break;
+                }
+            }
+            transcript.push_str(&received);
+            in_data = received == "DATA\r\n";
+            let tag = received.split_whitespace().next().unwrap_or("");
+            io.get_mut()
+                .write_all(reply.replace("{tag}", tag).as_bytes())
+                .await
+                .unwrap();
+        }
+        transcript
+    }
+
+    fn fetch_response(seq: u32, uid: u32, literal: &str) -> String {
+        format!(
+            "* {seq} FETCH (UID {uid} BODY[HEADER.FIELDS (FROM SUBJECT DATE)] {{{}}}\r\n{literal})\r\n",
+            literal.len()
+        )
+    }
+
+    #[tokio::test]
+    async fn imap_lists_unread_newest_first_and_fetches_bodies() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let sam = "From: Sam <sam@example.com>\r\nSubject: =?UTF-8?Q?Caf=C3=A9?= Friday?\r\nDate: Thu, 15 Oct 2026 08:12:00 +0000\r\n\r\n";
+        let bank = "From: bank@example.com\r\nSubject: Statement\r\n\r\n";
+        let message = "Subject: Hi\r\n\r\nHello there\r\n";
+        let replies = vec![
+            "{tag} OK LOGIN completed\r\n".to_string(),
+            "* 3 EXISTS\r\n{tag} OK [READ-ONLY] EXAMINE completed\r\n".to_string(),
+            "* SEARCH 7 9 4\r\n{tag} OK SEARCH completed\r\n".to_string(),
+            format!(
+                "{}{}* 2 FLAGS (\\Recent)\r\n{{tag}} OK FETCH completed\r\n",
+                fetch_response(1, 7, bank),
+                fetch_response(2, 9, sam)
+            ),
+            "{tag} OK SELECT completed\r\n".to_string(),
+            format!(
+                "* 2 FETCH (UID 9 BODY[] {{{}}}\r\n{message} FLAGS (\\Seen))\r\n{{tag}} OK\r\n",
+                message.len()
+            ),
+            "* BYE\r\n{tag} OK LOGOUT completed\r\n".to_string(),
+        ];
+        let server = tokio::spawn(async move { serve(server, "* OK ready\r\n", replies).await });
+
+        let mut imap = Imap::open(client).await.unwrap();
+        imap.login("me@example.com", "p\"w").await.unwrap();
+        let (total, summaries) = imap.unread("INBOX", 2).await.unwrap();
+        assert_eq!(total, 3);
+        let lines: Vec<String> = summaries.iter().map(|s| s.line(LONDON)).collect();
+        assert_eq!(
+            lines,
+            [
+                "- [uid 9] Thu 15 Oct 09:12 · Sam <sam@example.com>: Café Friday?",
+                "- [uid 7] bank@example.com: Statement",
+            ]
+        );
+        let raw = imap.fetch("INBOX", 9, "BODY[]", true).await.unwrap();
+        assert_eq!(raw, message.as_bytes());
+        imap.logout().await;
+
+        let transcript = server.await.unwrap();
+        assert_eq!(
+            transcript,
+            "a1 LOGIN \"me@example.com\" \"p\\\"w\"\r\n\
+             a2 EXAMINE \"INBOX\"\r\n\
+             a3 UID SEARCH UNSEEN\r\n\
+             a4 UID FETCH 9,7 (UID BODY.PEEK[HEADER.FIELDS (FROM SUBJECT DATE)])\r\n\
+             a5 SELECT \"INBOX\"\r\n\
+             a6 UID FETCH 9 (BODY[])\r\n\
+             a7 LOGOUT\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn imap_reports_server_errors() {
+        let (client, server) = tokio::io::duplex(4096);
+        let replies = vec!["{tag} NO [AUTHENTICATIONFAILED] Invalid credentials\r\n".to_string()];
+        tokio::spawn(async move { serve(server, "* OK ready\r\n", replies).await });
+        let mut imap = Imap::open(client).await.unwrap();
+        let err = imap.login("me", "wrong").await.unwrap_err();
+        assert_eq!(
+            err,
+            "login failed: IMAP: NO [AUTHENTICATIONFAILED] Invalid credentials"
+        );
+    }
+
+    #[test]
+    fn renders_multipart_messages() {
+        let raw = b"From: =?ISO-8859-1?Q?Ren=E9?= <rene@example.com>\r\n\
+To: me@example.com\r\n\
+Subject: =?UTF-8?B?UmU6IHRyaXA=?=\r\n =?UTF-8?B?IPCfm6s=?=\r\n\
+Date: Fri, 16 Oct 2026 14:05:00 +0000\r\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+\r\n\
+preamble\r\n\
+--outer\r\n\
+Content-Type: multipart/alternative; boundary=inner\r\n\
+\r\n\
+--inner\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+Tickets are booked =E2=80=94 see the att=\r\n\
+ached PDF.\r\n\
+--inner\r\n\
+Content-Type: text/html\r\n\
+\r\n\
+<p>Tickets are booked</p>\r\n\
+--inner--\r\n\
+--outer\r\n\
+Content-Type: application/pdf; name=\"tickets.pdf\"\r\n\
+Content-Disposition: attachment; filename=\"tickets.pdf\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+JVBERi0xLjQ=\r\n\
+--outer--\r\n";
+        assert_eq!(
+            render_message(raw, LONDON),
+            "From: René <rene@example.com>\n\
+             To: me@example.com\n\
+             Subject: Re: trip 🛫\n\
+             Date: Fri 16 Oct 2026 15:05\n\
+             Attachments: tickets.pdf\n\
+             \n\
+             Tickets are booked — see the attached PDF."
+        );
+
+        // HTML-only mail falls back to its text.
+        let html = b"Content-Type: text/html; charset=utf-8\r\n\r\n<p>Hello <b>there</b></p>";
+        assert_eq!(render_message(html, LONDON).trim(), "Hello there");
+    }
+
+    #[test]
+    fn parses_addresses_and_encodes_messages() {
+        assert_eq!(
+            addresses("\"Smith, Sam\" <sam@example.com>, Jo <jo@example.com>"),
+            ["sam@example.com", "jo@example.com"]
+        );
+        assert_eq!(addresses("a@x.com, b@y.org"), ["a@x.com", "b@y.org"]);
+
+        let date = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+        let thread = (
+            "<orig@example.com>".to_string(),
+            "<a@x> <orig@example.com>".to_string(),
+        );
+        let msg = build_message(
+            "Me <me@example.com>",
+            &["sam@example.com".to_string()],
+            "Re: Café",
+            "Yes, see you there.\n.\nMe",
+            Some(&thread),
+            date,
+        );
+        let (headers, body) = split_message(msg.as_bytes());
+        assert_eq!(
+            header(&headers, "subject").map(decode_words).as_deref(),
+            Some("Re: Café")
+        );
+        assert_eq!(header(&headers, "in-reply-to"), Some("<orig@example.com>"));
+        assert_eq!(
+            header(&headers, "references"),
+            Some("<a@x> <orig@example.com>")
+        );
+        assert_eq!(
+            header(&headers, "date"),
+            Some("Fri, 16 Oct 2026 09:00:00 +0000")
+        );
+        assert!(header(&headers, "message-id").is_some_and(|id| id.ends_with("@example.com>")));
+        assert_eq!(body, b"Yes, see you there.\r\n.\r\nMe");
+
+        let accented = build_message("me@x.com", &[], "Hi", "Olá", None, date);
+        let (headers, body) = split_message(accented.as_bytes());
+        assert_eq!(
+            header(&headers, "content-transfer-encoding"),
+            Some("base64")
+        );
+        assert_eq!(base64_decode(body), "Olá".as_bytes());
+    }
+
+    #[tokio::test]
+    async fn smtp_delivers_with_auth_and_dot_stuffing() {
+        let (client, server) = tokio::io::duplex(16 * 1024);
+        let replies = [
+            "250-mail.example.com\r\n250-AUTH PLAIN LOGIN\r\n250 8BITMIME\r\n",
+            "235 2.7.0 Authentication successful\r\n",
+            "250 OK\r\n",
+            "250 OK\r\n",
+            "251 User not local; will forward\r\n",
+            "354 End data with <CR><LF>.<CR><LF>\r\n",
+            "250 OK queued as 12345\r\n",
+            "221 Bye\r\n",
+        ];
+        let replies = replies.iter().map(|r| r.to_string()).collect();
+        let server =
+            tokio::spawn(
+                async move { serve(server, "220 mail.example.com ESMTP\r\n", replies).await },
+            );
+
+        let account = EmailAccount {
+            username: "me@example.com".to_string(),
+            password: "pw".to_string(),
+            ..Default::default()
+        };
+        let mut smtp = Smtp::new(client);
+        smtp.reply(2).await.unwrap();
+        smtp.send("EHLO localhost", 2).await.unwrap();
+        let to = ["a@x.com".to_string(), "b@y.org".to_string()];
+        smtp.deliver(
+            &account,
+            "me@example.com",
+            &to,
+            "Subject: Hi\r\n\r\nline\r\n.hidden",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            server.await.unwrap(),
+            "EHLO localhost\r\n\
+             AUTH PLAIN AG1lQGV4YW1wbGUuY29tAHB3\r\n\
+             MAIL FROM:<me@example.com>\r\n\
+             RCPT TO:<a@x.com>\r\n\
+             RCPT TO:<b@y.org>\r\n\
+             DATA\r\n\
+             Subject: Hi\r\n\r\nline\r\n..hidden\r\n.\r\n\
+             QUIT\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn smtp_surfaces_rejections() {
+        let (client, server) = tokio::io::duplex(4096);
+        let replies = vec!["535 5.7.8 Authentication credentials invalid\r\n".to_string()];
+        tokio::spawn(async move { serve(server, "220 hi\r\n", replies).await });
+        let mut smtp = Smtp::new(client);
+        smtp.reply(2).await.unwrap();
+        let account = EmailAccount::default();
+        let err = smtp
+            .deliver(&account, "me@x.com", &[], "")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "login failed: SMTP 535 5.7.8 Authentication credentials invalid"
+        );
+    }
+
+    #[tokio::test]
+    async fn tool_validates_arguments_before_connecting() {
+        let mut accounts = BTreeMap::new();
+        for name in ["home", "work"] {
+            accounts.insert(
+                name.to_string(),
+                EmailAccount {
+                    imap_host: "imap.invalid".to_string(),
+                    ..Default::default()
+                },
+            );
+        }
+        let config = EmailConfig {
+            accounts: Some(accounts),
+        };
+        let tool = EmailTool::from_config(Some(&config), LONDON).unwrap();
+        let ctx = ToolCtx::for_tests(std::path::PathBuf::from("/tmp"));
+        let cases = [
+            (
+                serde_json::json!({"action": "read", "uid": 3}),
+                "account is required (configured: home, work)",
+            ),
+            (
+                serde_json::json!({"action": "read", "account": "Home"}),
+                "uid is required for read",
+            ),
+            (
+                serde_json::json!({"action": "send", "account": "gym"}),
+                "unknown account 'gym' (configured: home, work)",
+            ),
+            (
+                serde_json::json!({"action": "send", "account": "work", "to": "a@x.com", "body": "hi"}),
+                "account 'work' has no smtp-host, so it can only read",
+            ),
+            (
+                serde_json::json!({"action": "archive"}),
+                "action must be list_unread, read or send",
+            ),
+        ];
+        for (args, expected) in cases {
+            let res = tool.execute(&ctx, &args).await;
+            assert!(res.is_error, "{args}");
+            assert_eq!(res.for_llm, expected);
+        }
+    }
+}
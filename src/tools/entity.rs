@@ -0,0 +1,296 @@
+//! `entity` tool: lightweight memory of the people, places and projects the user talks
+//! about, kept in BrainDb (`entities`, with aliases and relations).
+//!
+//! The agent saves an entity when the user introduces one ("Priya is the designer on
+//! Falcon"), links entities with relations, and looks them up on request. Whenever a later
+//! message mentions an entity by name or alias, its card is added to the prompt (see
+//! `agent::process_message`), so "what did Priya say?" needs no explanation.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::memory::db::{BrainDb, Entity};
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Entities listed by `recall` without a name.
+const DEFAULT_LIMIT: usize = 20;
+
+pub struct EntityTool {
+    db: Arc<BrainDb>,
+}
+
+impl EntityTool {
+    pub fn new(db: Arc<BrainDb>) -> Self {
+        Self { db }
+    }
+}
+
+impl Tool for EntityTool {
+    fn name(&self) -> &str {
+        "entity"
+    }
+
+    fn description(&self) -> &str {
+        "Remember people, places, projects and other things the user mentions; they are \
+         shown to you again whenever their name or an alias comes up. \
+         save: create or update one (kind, notes, aliases). \
+         relate: link two, e.g. Priya 'works on' Project Falcon. \
+         recall: look one up by name (omit name to list recent ones). \
+         forget: delete one with its aliases and relations."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["save", "relate", "recall", "forget"],
+                    "description": "save, relate, recall or forget an entity"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Entity name or alias (save, relate: the subject, recall, forget)"
+                },
+                "kind": {
+                    "type": "string",
+                    "description": "e.g. person, place, project, pet, organisation (save)"
+                },
+                "notes": {
+                    "type": "string",
+                    "description": "Who or what it is, in a sentence or two; replaces earlier notes (save)"
+                },
+                "aliases": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Other names it goes by (save)"
+                },
+                "relation": {
+                    "type": "string",
+                    "description": "How name relates to target, e.g. 'works on', 'sister of' (relate)"
+                },
+                "target": {
+                    "type": "string",
+                    "description": "The other entity (relate)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let db = Arc::clone(&self.db);
+        let args = args.clone();
+
+        Box::pin(async move {
+            let action = text(&args, "action");
+            let name = text(&args, "name");
+            match action.as_str() {
+                "save" => {
+                    if name.is_empty() {
+                        return ToolResult::error("save requires 'name'");
+                    }
+                    let kind = text(&args, "kind").to_lowercase();
+                    let notes = text(&args, "notes");
+                    let aliases: Vec<String> = args
+                        .get("aliases")
+                        .and_then(Value::as_array)
+                        .map(|a| {
+                            a.iter()
+                                .filter_map(Value::as_str)
+                                .map(String::from)
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let result = tokio::task::spawn_blocking(move || {
+                        db.save_entity(&name, &kind, &notes, &aliases)?;
+                        db.find_entity(&name)
+                    })
+                    .await;
+                    match result {
+                        Ok(Ok(Some(entity))) => {
+                            ToolResult::ok(format!("Saved: {}", format_entity(&entity)))
+                        }
+                        Ok(Ok(None)) => ToolResult::error("entity vanished after save"),
+                        Ok(Err(e)) => ToolResult::error(format!("entity save failed: {e}")),
+                        Err(e) => ToolResult::error(format!("entity task error: {e}")),
+                    }
+                }
+                "relate" => {
+                    let relation = text(&args, "relation").to_lowercase();
+                    let target = text(&args, "target");
+                    if name.is_empty() || relation.is_empty() || target.is_empty() {
+                        return ToolResult::error(
+                            "relate requires 'name', 'relation' and 'target'",
+                        );
+                    }
+                    let line = format!("{name} {relation} {target}");
+                    let result = tokio::task::spawn_blocking(move || {
+                        db.relate_entities(&name, &relation, &target)
+                    })
+                    .await;
+                    match result {
+                        Ok(Ok(())) => ToolResult::ok(format!("Noted: {line}.")),
+                        Ok(Err(e)) => ToolResult::error(format!("entity relate failed: {e}")),
+                        Err(e) => ToolResult::error(format!("entity task error: {e}")),
+                    }
+                }
+                "recall" => {
+                    let result = tokio::task::spawn_blocking(move || {
+                        if name.is_empty() {
+                            db.list_entities(DEFAULT_LIMIT)
+                        } else {
+                            db.find_entity(&name).map(|e| e.into_iter().collect())
+                        }
+                    })
+                    .await;
+                    match result {
+                        Ok(Ok(entities)) if entities.is_empty() => {
+                            ToolResult::ok("No matching entities.")
+                        }
+                        Ok(Ok(entities)) => ToolResult::ok(format_entities(&entities)),
+                        Ok(Err(e)) => ToolResult::error(format!("entity recall failed: {e}")),
+                        Err(e) => ToolResult::error(format!("entity task error: {e}")),
+                    }
+                }
+                "forget" => {
+                    if name.is_empty() {
+                        return ToolResult::error("forget requires 'name'");
+                    }
+                    let label = name.clone();
+                    match tokio::task::spawn_blocking(move || db.forget_entity(&name)).await {
+                        Ok(Ok(true)) => ToolResult::ok(format!("Forgot {label}.")),
+                        Ok(Ok(false)) => ToolResult::error(format!("no entity named '{label}'")),
+                        Ok(Err(e)) => ToolResult::error(format!("entity forget failed: {e}")),
+                        Err(e) => ToolResult::error(format!("entity task error: {e}")),
+                    }
+                }
+                other => ToolResult::error(format!(
+                    "unknown action '{other}' (use save, relate, recall or forget)"
+                )),
+            }
+        })
+    }
+}
+
+fn text(args: &Value, key: &str) -> String {
+    args.get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or("")
+        .to_string()
+}
+
+/// `Priya [person] (aka P.S.): Designer at Acme. Priya works on Project Falcon.`
+pub fn format_entity(entity: &Entity) -> String {
+    let mut line = entity.name.clone();
+    if !entity.kind.is_empty() {
+        line.push_str(&format!(" [{}]", entity.kind));
+    }
+    if !entity.aliases.is_empty() {
+        line.push_str(&format!(" (aka {})", entity.aliases.join(", ")));
+    }
+    line.push(':');
+    if !entity.notes.is_empty() {
+        line.push(' ');
+        line.push_str(entity.notes.trim_end_matches('.'));
+        line.push('.');
+    }
+    for r in &entity.relations {
+        line.push_str(&format!(" {} {} {}.", r.from, r.relation, r.to));
+    }
+    line
+}
+
+/// One line per entity. Also used for the system prompt.
+pub fn format_entities(entities: &[Entity]) -> String {
+    entities
+        .iter()
+        .map(format_entity)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn ctx() -> ToolCtx {
+        ToolCtx {
+            chat_id: Some(42),
+            channel: Some("telegram".into()),
+            ..ToolCtx::for_tests(std::env::temp_dir())
+        }
+    }
+
+    #[tokio::test]
+    async fn save_relate_recall_forget() {
+        let tmp = TempDir::new().unwrap();
+        let tool = EntityTool::new(Arc::new(BrainDb::open(tmp.path()).unwrap()));
+
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"action": "save", "name": "Priya", "kind": "Person",
+                    "notes": "Designer at Acme", "aliases": ["P.S."]}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"action": "relate", "name": "Priya",
+                    "relation": "works on", "target": "Project Falcon"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"action": "recall", "name": "p.s."}),
+            )
+            .await;
+        assert_eq!(
+            res.for_llm,
+            "Priya [person] (aka P.S.): Designer at Acme. Priya works on Project Falcon."
+        );
+
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"action": "forget", "name": "Priya"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"action": "recall", "name": "Priya"}),
+            )
+            .await;
+        assert_eq!(res.for_llm, "No matching entities.");
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments_are_errors() {
+        let tmp = TempDir::new().unwrap();
+        let tool = EntityTool::new(Arc::new(BrainDb::open(tmp.path()).unwrap()));
+        for args in [
+            serde_json::json!({"action": "save"}),
+            serde_json::json!({"action": "relate", "name": "Priya"}),
+            serde_json::json!({"action": "forget", "name": "nobody"}),
+            serde_json::json!({"action": "dance"}),
+        ] {
+            assert!(tool.execute(&ctx(), &args).await.is_error, "{args}");
+        }
+    }
+}
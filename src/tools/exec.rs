@@ -0,0 +1,802 @@
+//! `exec` tool: run a shell command in the workspace (`[tools.exec]`, off by default).
+//!
+//! The command runs with `sh -c` in the workspace directory, stdin closed and only the
+//! variables in [`PASSED_ENV`] set, so secrets from the environment (`TELEGRAM_BOT_TOKEN`,
+//! `ICRAB_*`) never reach it. It is killed after `timeout-secs`, and only the first
+//! `max-output-chars` of stdout and stderr are kept: the rest is cut off as it is written,
+//! so a runaway command cannot fill the disk.
+//!
+//! Before running, every program the command invokes — the first word of each pipeline or
+//! list segment after any `if`/`while`/`do`-style keyword or `case` pattern, plus the program
+//! behind wrappers such as `env` or `xargs`, the programs `find -exec` runs and those in the
+//! string given to `eval` or `sh -c` — is checked against `deny` (a built-in list of
+//! destructive commands when unset) and, when set, `allow`. Command substitution (`` `…` ``,
+//! `$(…)`), programs named by a variable and awk programs that run commands are refused
+//! because they hide programs from that check, and the default `deny` includes `source` and
+//! shells reading a script or stdin for the same reason. This guards against mistakes, not
+//! against a determined attacker: keep `allow` short.
+//!
+//! Like `sync_vault`, it goes through [`shell::run`] rather than `std::process`, which does
+//! not work on iSH.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::config::ExecConfig;
+use crate::shell::{self, escape_sh};
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Seconds a command may run when `[tools.exec] timeout-secs` is unset.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// Characters of stdout (and of stderr) returned when `max-output-chars` is unset.
+pub const DEFAULT_MAX_OUTPUT_CHARS: usize = 10_000;
+/// Programs refused when `[tools.exec] deny` is unset.
+pub const DEFAULT_DENY: &[&str] = &[
+    "rm", "rmdir", "dd", "mkfs", "shred", "sudo", "su", "doas", "chmod", "chown", "kill",
+    "killall", "pkill", "shutdown", "reboot", "halt", "poweroff", "mount", "umount", "crontab",
+    "source", ".", "sh", "bash", "dash", "ash", "zsh", "ksh",
+];
+
+/// Environment variables passed on to commands when set; the rest of the environment is
+/// dropped.
+pub const PASSED_ENV: &[&str] = &[
+    "PATH", "HOME", "USER", "LOGNAME", "SHELL", "LANG", "LC_ALL", "LC_CTYPE", "TERM", "TZ",
+    "TMPDIR",
+];
+
+/// Programs that run another program given as an argument; that one is checked too.
+const WRAPPERS: &[&str] = &[
+    "env", "nohup", "nice", "time", "timeout", "command", "exec", "xargs", "builtin",
+];
+
+/// Options of wrappers that take the next word as their value.
+const WRAPPER_VALUE_OPTIONS: &[(&str, &[&str])] = &[
+    ("env", &["-u", "--unset", "-C", "--chdir"]),
+    ("nice", &["-n", "--adjustment"]),
+    ("timeout", &["-s", "--signal", "-k", "--kill-after"]),
+    ("exec", &["-a"]),
+    ("time", &["-f", "--format", "-o", "--output"]),
+    (
+        "xargs",
+        &[
+            "-I",
+            "-d",
+            "--delimiter",
+            "-E",
+            "-L",
+            "--max-lines",
+            "-n",
+            "--max-args",
+            "-P",
+            "--max-procs",
+            "-s",
+            "--max-chars",
+            "-a",
+            "--arg-file",
+        ],
+    ),
+];
+
+/// Shell keywords that may start a segment; the program is the word after them.
+const KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "do", "done", "while", "until", "!", "esac",
+];
+
+/// `find` actions whose arguments, up to `;` or `+`, are a command.
+const FIND_EXEC: &[&str] = &["-exec", "-execdir", "-ok", "-okdir"];
+
+/// awk interpreters; their programs may not run commands.
+const AWKS: &[&str] = &["awk", "gawk", "mawk", "nawk"];
+
+/// Shells whose `-c` string is checked like a command of its own. Run without `-c` (a script
+/// or stdin) they count as the program itself, which the default `deny` refuses.
+const SHELLS: &[&str] = &["sh", "bash", "dash", "ash", "zsh", "ksh"];
+
+/// Which programs commands may run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecPolicy {
+    /// Empty: anything not denied.
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl ExecPolicy {
+    pub fn from_config(cfg: &ExecConfig) -> Self {
+        Self {
+            allow: cfg.allow.clone().unwrap_or_default(),
+            deny: cfg
+                .deny
+                .clone()
+                .unwrap_or_else(|| DEFAULT_DENY.iter().map(|s| s.to_string()).collect()),
+        }
+    }
+
+    /// Err with the reason when `command` may not run.
+    pub fn check(&self, command: &str) -> Result<(), String> {
+        if command.contains('`') || command.contains("$(") {
+            return Err("command substitution (`…` or $(…)) is not allowed".into());
+        }
+        let programs = programs(command)?;
+        if programs.is_empty() {
+            return Err("empty command".into());
+        }
+        for program in programs {
+            if program.starts_with('$') {
+                return Err(format!("'{program}' runs a program named by a variable"));
+            }
+            if self.deny.contains(&program) {
+                return Err(format!("'{program}' is denied by [tools.exec] deny"));
+            }
+            if !self.allow.is_empty() && !self.allow.contains(&program) {
+                return Err(format!("'{program}' is not in [tools.exec] allow"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Program names (basenames) a shell command runs: the first word of every segment between
+/// `;`, `&`, `|` and newlines outside quotes, skipping `VAR=value` prefixes, shell keywords,
+/// `for` headers and `case` patterns. Err when the command runs programs that cannot be
+/// checked.
+fn programs(command: &str) -> Result<Vec<String>, String> {
+    let mut out = Vec::new();
+    // Inside `case … in`, words up to the next `)` are a pattern, not a program.
+    let mut in_pattern = false;
+    for segment in segments(command) {
+        if segment == ";;" {
+            in_pattern = true;
+            continue;
+        }
+        let raw = words(&segment);
+        let mut start = 0;
+        while let Some(word) = raw.get(start) {
+            if in_pattern {
+                in_pattern = !(word.ends_with(')') || word == "esac");
+                start += 1;
+                continue;
+            }
+            match word.as_str() {
+                "case" => {
+                    start += raw[start..]
+                        .iter()
+                        .position(|w| w == "in")
+                        .map_or(raw.len(), |i| i + 1);
+                    in_pattern = true;
+                }
+                // `for NAME in WORDS` runs nothing; the loop body is the next segment.
+                "for" | "select" => start = raw.len(),
+                w if KEYWORDS.contains(&w) => start += 1,
+                _ => break,
+            }
+        }
+        let words: Vec<String> = raw[start.min(raw.len())..]
+            .iter()
+            .map(|w| match w.as_str() {
+                // The `find`/`xargs` placeholder, not a group.
+                "{}" => w.clone(),
+                _ => w
+                    .trim_matches(|c| matches!(c, '(' | ')' | '{' | '}'))
+                    .to_string(),
+            })
+            .filter(|w| !w.is_empty() && !is_assignment(w))
+            .collect();
+        out.extend(command_programs(&words)?);
+    }
+    Ok(out)
+}
+
+/// Programs run by one simple command given as words: its program plus the first operand of
+/// each wrapper. The arguments of `eval` and the `-c` string of a shell are commands
+/// themselves; their programs take the place of `eval` or the shell.
+fn command_programs(words: &[String]) -> Result<Vec<String>, String> {
+    let mut out = Vec::new();
+    let mut rest = words.iter();
+    let mut wrapper: Option<String> = None;
+    while let Some(word) = rest.next() {
+        if let Some(wrapper) = &wrapper {
+            if wrapper == "env" {
+                let split = match word.as_str() {
+                    "-S" | "--split-string" => rest.next().map(String::as_str),
+                    w => w
+                        .strip_prefix("--split-string=")
+                        .or_else(|| w.strip_prefix("-S").filter(|v| !v.is_empty())),
+                };
+                if let Some(command) = split {
+                    out.extend(programs(command)?);
+                    return Ok(out);
+                }
+            }
+            // Operand of a wrapper: skip its options and numeric arguments.
+            if word.starts_with('-') {
+                if takes_value(wrapper, word) {
+                    rest.next();
+                }
+                continue;
+            }
+            if is_number(word) {
+                continue;
+            }
+        }
+        let name = word.rsplit('/').next().unwrap_or(word).to_string();
+        let args = rest.as_slice();
+        if name == "eval" {
+            let inner: Vec<&str> = args.iter().map(String::as_str).collect();
+            out.extend(programs(&inner.join(" "))?);
+            return Ok(out);
+        }
+        if SHELLS.contains(&name.as_str()) {
+            match shell_command(args.iter()) {
+                Some(inner) => out.extend(programs(inner)?),
+                None => out.push(name),
+            }
+            return Ok(out);
+        }
+        if AWKS.contains(&name.as_str()) && args.iter().any(|a| awk_runs_commands(a)) {
+            return Err(format!(
+                "'{name}' programs that run commands (system() or pipes) are not allowed"
+            ));
+        }
+        if name == "find" {
+            out.push(name);
+            let mut args = args.iter();
+            while let Some(arg) = args.by_ref().find(|a| FIND_EXEC.contains(&a.as_str())) {
+                let inner: Vec<String> = args
+                    .by_ref()
+                    .take_while(|a| *a != ";" && *a != "+")
+                    .cloned()
+                    .collect();
+                if inner.is_empty() {
+                    return Err(format!("'find {arg}' without a command"));
+                }
+                out.extend(command_programs(&inner)?);
+            }
+            return Ok(out);
+        }
+        let is_wrapper = WRAPPERS.contains(&name.as_str());
+        out.push(name.clone());
+        if !is_wrapper {
+            break;
+        }
+        wrapper = Some(name);
+    }
+    Ok(out)
+}
+
+/// True when `option` of `wrapper` takes the next word as its value.
+fn takes_value(wrapper: &str, option: &str) -> bool {
+    WRAPPER_VALUE_OPTIONS
+        .iter()
+        .any(|(w, options)| *w == wrapper && options.contains(&option))
+}
+
+/// A number or a `timeout`-style duration ("10", "1.5", "10s", "2m").
+fn is_number(word: &str) -> bool {
+    word.strip_suffix(['s', 'm', 'h', 'd'])
+        .unwrap_or(word)
+        .parse::<f64>()
+        .is_ok()
+}
+
+/// True when an awk program calls `system()` or pipes to or from a command.
+fn awk_runs_commands(program: &str) -> bool {
+    let compact: String = program.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.contains("system(")
+        || compact.contains("|\"")
+        || compact.contains("|getline")
+        || compact.contains("|&")
+}
+
+/// The string a shell runs with `-c` (other options may come first), or None when it runs
+/// a script or reads stdin.
+fn shell_command<'a>(mut args: impl Iterator<Item = &'a String>) -> Option<&'a str> {
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') || arg == "--" {
+            return None;
+        }
+        if !arg.starts_with("--") && arg.contains('c') {
+            return args.next().map(String::as_str);
+        }
+    }
+    None
+}
+
+/// Split a segment into words on unquoted whitespace, removing quotes and escapes.
+fn words(segment: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in segment.chars() {
+        if escaped {
+            current.push(c);
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                escaped = true;
+                in_word = true;
+            }
+            (Some(_), _) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, _) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, _) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+/// Split on unquoted `;`, `&`, `|` and newlines. A `;;` (the end of a `case` branch) is kept
+/// as a segment of its own.
+fn segments(command: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        if escaped {
+            current.push(c);
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('"'), '\\') | (None, '\\') => escaped = true,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ';' | '&' | '|' | '\n') => {
+                segments.push(std::mem::take(&mut current));
+                if c == ';' && chars.next_if_eq(&';').is_some() {
+                    segments.push(";;".to_string());
+                }
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    segments.push(current);
+    segments.retain(|s| !s.trim().is_empty());
+    segments
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Exit status and captured output of a finished command.
+struct ExecOutput {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    timed_out: bool,
+    /// Failed after filling stdout or stderr, most likely because the rest was cut off.
+    cut_off: bool,
+}
+
+/// Run `command` in `workspace`, killing it after `timeout`. At most `max_bytes` of each
+/// stream are written out; once a stream is full the command fails on its next write there.
+fn run_command(
+    workspace: &Path,
+    command: &str,
+    timeout: Duration,
+    max_bytes: u64,
+) -> Result<ExecOutput, String> {
+    let env: Vec<String> = PASSED_ENV
+        .iter()
+        .filter_map(|name| {
+            let value = std::env::var(name).ok()?;
+            Some(escape_sh(&format!("{name}={value}")))
+        })
+        .collect();
+    let script = format!(
+        "cd {} && env -i {} timeout -s KILL {} sh -c {}",
+        escape_sh(&workspace.to_string_lossy()),
+        env.join(" "),
+        timeout.as_secs().max(1),
+        escape_sh(command),
+    );
+    let started = std::time::Instant::now();
+    let out = shell::run(&script, Some(max_bytes))?;
+    let elapsed = started.elapsed();
+    let code = out.status.code();
+    // A command writing on after `head` has all it keeps fails with SIGPIPE or EPIPE.
+    let full = |s: &[u8]| s.len() as u64 >= max_bytes;
+    Ok(ExecOutput {
+        cut_off: code != Some(0) && (full(&out.stdout) || full(&out.stderr)),
+        code,
+        stdout: String::from_utf8_lossy(&out.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&out.stderr).into_owned(),
+        timed_out: code != Some(0) && elapsed >= timeout,
+    })
+}
+
+/// First `max_chars` of `text`, noting how much was cut.
+fn cap(text: &str, max_chars: usize) -> String {
+    let text = text.trim_end();
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_chars).collect();
+    format!("{kept}\n… (output truncated at {max_chars} characters)")
+}
+
+pub struct ExecTool {
+    policy: ExecPolicy,
+    timeout: Duration,
+    max_output_chars: usize,
+}
+
+impl ExecTool {
+    pub fn from_config(cfg: &ExecConfig) -> Self {
+        Self {
+            policy: ExecPolicy::from_config(cfg),
+            timeout: Duration::from_secs(cfg.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).max(1)),
+            max_output_chars: cfg
+                .max_output_chars
+                .unwrap_or(DEFAULT_MAX_OUTPUT_CHARS)
+                .max(1),
+        }
+    }
+}
+
+impl Tool for ExecTool {
+    fn name(&self) -> &str {
+        "exec"
+    }
+
+    fn description(&self) -> &str {
+        "Run a shell command in the workspace (e.g. `git status`, `pandoc note.md -o \
+         note.pdf`, `curl -s URL`) and return its exit code and output. Commands have a \
+         time limit, and programs outside the configured allow-list are refused."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "Shell command, run with sh -c in the workspace directory"
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let workspace = ctx.workspace.clone();
+        let command = args
+            .get("command")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let timeout = self.timeout;
+        let max_chars = self.max_output_chars;
+
+        Box::pin(async move {
+            if command.is_empty() {
+                return ToolResult::error("missing or invalid 'command'");
+            }
+            if let Err(reason) = self.policy.check(&command) {
+                return ToolResult::error(format!("command refused: {reason}"));
+            }
+            // Up to 4 bytes per character.
+            let max_bytes = max_chars as u64 * 4 + 4;
+            let task = tokio::task::spawn_blocking(move || {
+                run_command(&workspace, &command, timeout, max_bytes)
+            });
+            // `timeout` in the shell kills the command; this only guards against a hung shell.
+            let out = match tokio::time::timeout(timeout + Duration::from_secs(5), task).await {
+                Ok(Ok(Ok(out))) => out,
+                Ok(Ok(Err(e))) => return ToolResult::error(format!("exec failed: {e}")),
+                Ok(Err(e)) => return ToolResult::error(format!("exec task error: {e}")),
+                Err(_) => return ToolResult::error("exec did not finish in time"),
+            };
+
+            let mut report = if out.timed_out {
+                format!("killed after {}s", timeout.as_secs())
+            } else if out.cut_off {
+                "stopped at the output limit".to_string()
+            } else {
+                match out.code {
+                    Some(code) => format!("exit code {code}"),
+                    None => "terminated by a signal".to_string(),
+                }
+            };
+            if !out.stdout.trim().is_empty() {
+                report.push_str(&format!("\n[stdout]\n{}", cap(&out.stdout, max_chars)));
+            }
+            if !out.stderr.trim().is_empty() {
+                report.push_str(&format!("\n[stderr]\n{}", cap(&out.stderr, max_chars)));
+            }
+            if out.code == Some(0) || out.cut_off {
+                ToolResult::ok(report)
+            } else {
+                ToolResult::error(report)
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    fn policy(allow: &[&str]) -> ExecPolicy {
+        ExecPolicy::from_config(&ExecConfig {
+            allow: Some(allow.iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn every_program_of_a_command_is_checked() {
+        assert_eq!(
+            programs("LANG=C git status && cat 'a;b' | /usr/bin/wc -l; env X=1 nice -n 5 rm x")
+                .unwrap(),
+            ["git", "cat", "wc", "env", "nice", "rm"]
+        );
+        let allowed = policy(&["git", "cat", "wc", "xargs"]);
+        assert!(allowed.check("git log -3 | cat").is_ok());
+        assert!(
+            allowed
+                .check("git status; curl x")
+                .unwrap_err()
+                .contains("'curl'")
+        );
+        assert!(allowed.check("ls | xargs rm").unwrap_err().contains("'ls'"));
+        assert!(allowed.check("git log $(whoami)").is_err());
+
+        let open = policy(&[]);
+        assert!(open.check("pandoc a.md -o a.pdf").is_ok());
+        assert!(
+            open.check("echo hi && sudo reboot")
+                .unwrap_err()
+                .contains("'sudo'")
+        );
+        assert!(
+            open.check("find . | xargs /bin/rm")
+                .unwrap_err()
+                .contains("'rm'")
+        );
+        assert!(open.check("  ").is_err());
+    }
+
+    #[test]
+    fn eval_and_shell_strings_are_checked() {
+        assert_eq!(
+            programs("bash -c 'git status && cat \"a b\"' | wc -l").unwrap(),
+            ["git", "cat", "wc"]
+        );
+        let open = policy(&[]);
+        for bypass in [
+            "eval rm -rf .",
+            "sh -c 'rm -rf .'",
+            "bash -c \"echo ok; rm -rf .\"",
+            "dash -ec 'rm x'",
+            "env ash -c 'rm x'",
+            "builtin eval 'rm x'",
+        ] {
+            assert!(open.check(bypass).unwrap_err().contains("'rm'"), "{bypass}");
+        }
+        for hidden in [
+            "source evil.sh",
+            ". ./evil.sh",
+            "echo 'rm -rf .' | sh",
+            "bash script.sh",
+            "$CMD x",
+            "sh -c \"$CMD\"",
+        ] {
+            assert!(open.check(hidden).is_err(), "{hidden}");
+        }
+        assert!(open.check("sh -c 'ls | wc -l'").is_ok());
+
+        let allowed = policy(&["git"]);
+        assert!(allowed.check("sh -c 'git log'").is_ok());
+        assert!(
+            allowed
+                .check("sh -c 'curl x'")
+                .unwrap_err()
+                .contains("'curl'")
+        );
+    }
+
+    #[test]
+    fn keywords_find_exec_and_wrapper_options_do_not_hide_programs() {
+        assert_eq!(
+            programs("if git pull; then cat log; else ! wc -l x; fi").unwrap(),
+            ["git", "cat", "wc"]
+        );
+        assert_eq!(
+            programs("for f in *.md; do wc -l \"$f\"; done").unwrap(),
+            ["wc"]
+        );
+        assert_eq!(
+            programs("case $1 in a|b) git log;; (*) ls;; esac").unwrap(),
+            ["git", "ls"]
+        );
+        assert_eq!(
+            programs("find . -name '*.md' -exec wc -l {} + -execdir cat {} \\;").unwrap(),
+            ["find", "wc", "cat"]
+        );
+        assert_eq!(programs("awk '/a|b/ { print $1 }' x").unwrap(), ["awk"]);
+
+        let open = policy(&[]);
+        for bypass in [
+            "if true; then rm -rf .; fi",
+            "for f in *; do rm $f; done",
+            "! rm x",
+            "while true; do rm x; done",
+            "until false\ndo rm x\ndone",
+            "case a in a) rm x;; esac",
+            "find . -exec rm {} \\;",
+            "find . -execdir /bin/rm {} +",
+            "ls | xargs -I {} rm {}",
+            "xargs -d '\\n' -n 1 rm < list",
+            "timeout 10s rm x",
+            "env -S 'rm x'",
+            "env --split-string='rm x'",
+        ] {
+            assert!(
+                open.check(bypass).unwrap_err().contains("'rm'"),
+                "{bypass}: {:?}",
+                programs(bypass)
+            );
+        }
+        for awk in [
+            "awk 'BEGIN { system(\"rm x\") }'",
+            "gawk '{ print | \"sh\" }' x",
+            "awk 'BEGIN { \"rm x\" | getline }'",
+        ] {
+            assert!(open.check(awk).unwrap_err().contains("awk"), "{awk}");
+        }
+        assert!(open.check("find . -exec").is_err());
+
+        let allowed = policy(&["find", "git"]);
+        assert!(allowed.check("find . -exec git add {} +").is_ok());
+        assert!(
+            allowed
+                .check("find . -exec wc -l {} +")
+                .unwrap_err()
+                .contains("'wc'")
+        );
+    }
+
+    #[tokio::test]
+    async fn runs_in_the_workspace_and_reports_output() {
+        let ws = TempDir::new().unwrap();
+        std::fs::write(ws.path().join("hello.txt"), "from the vault").unwrap();
+        let tool = ExecTool::from_config(&ExecConfig::default());
+
+        let res = tool
+            .execute(
+                &ctx(ws.path()),
+                &serde_json::json!({"command": "cat hello.txt"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(res.for_llm, "exit code 0\n[stdout]\nfrom the vault");
+
+        let res = tool
+            .execute(
+                &ctx(ws.path()),
+                &serde_json::json!({"command": "echo oops >&2; exit 3"}),
+            )
+            .await;
+        assert!(res.is_error);
+        assert_eq!(res.for_llm, "exit code 3\n[stderr]\noops");
+    }
+
+    #[tokio::test]
+    async fn only_passed_variables_reach_the_command() {
+        let ws = TempDir::new().unwrap();
+        let tool = ExecTool::from_config(&ExecConfig::default());
+        let res = tool
+            .execute(
+                &ctx(ws.path()),
+                &serde_json::json!({"command": "env | cut -d= -f1"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let names = res.for_llm.trim_start_matches("exit code 0\n[stdout]\n");
+        for name in names.lines() {
+            // The shell sets PWD, SHLVL and _ itself.
+            assert!(
+                PASSED_ENV.contains(&name) || ["PWD", "OLDPWD", "SHLVL", "_"].contains(&name),
+                "{name} leaked into the command's environment"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn slow_commands_are_killed_and_output_is_capped() {
+        let ws = TempDir::new().unwrap();
+        let tool = ExecTool::from_config(&ExecConfig {
+            timeout_secs: Some(1),
+            max_output_chars: Some(5),
+            ..Default::default()
+        });
+        let res = tool
+            .execute(&ctx(ws.path()), &serde_json::json!({"command": "sleep 10"}))
+            .await;
+        assert!(res.is_error);
+        assert!(
+            res.for_llm.starts_with("killed after 1s"),
+            "{}",
+            res.for_llm
+        );
+
+        let res = tool
+            .execute(
+                &ctx(ws.path()),
+                &serde_json::json!({"command": "echo 0123456789"}),
+            )
+            .await;
+        assert!(
+            res.for_llm
+                .ends_with("01234\n… (output truncated at 5 characters)"),
+            "{}",
+            res.for_llm
+        );
+    }
+
+    #[test]
+    fn output_is_cut_off_where_it_is_written() {
+        let ws = TempDir::new().unwrap();
+        // Without the cap `yes` would write until it is killed.
+        let out = run_command(ws.path(), "yes; echo never", Duration::from_secs(30), 1000).unwrap();
+        assert!(out.cut_off);
+        assert!(!out.timed_out);
+        assert_eq!(out.stdout.len(), 1000);
+        assert!(!out.stdout.contains("never"));
+
+        let out = run_command(
+            ws.path(),
+            "echo out; yes err >&2",
+            Duration::from_secs(30),
+            10,
+        )
+        .unwrap();
+        assert_eq!((out.stdout.as_str(), out.stderr.len()), ("out\n", 10));
+        assert!(out.cut_off);
+
+        let out = run_command(ws.path(), "exit 7", Duration::from_secs(30), 10).unwrap();
+        assert_eq!(out.code, Some(7));
+        assert!(!out.cut_off);
+    }
+}
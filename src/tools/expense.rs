@@ -0,0 +1,542 @@
+//! `expense` tool: spending kept in BrainDb (`expenses`, amounts in minor units) — add,
+//! list, remove and a monthly summary by category. After every change the month's report
+//! is rewritten in the vault (`[tools.expense] dir`, default `Expenses/<YYYY-MM>.md`).
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde_json::Value;
+
+use crate::config::ExpenseConfig;
+use crate::memory::db::{BrainDb, Expense, run_blocking};
+use crate::tools::context::ToolCtx;
+use crate::tools::convert::CURRENCY_SYMBOLS;
+use crate::tools::daily_note::parse_day;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const DEFAULT_DIR: &str = "Expenses";
+const DEFAULT_CURRENCY: &str = "GBP";
+/// Expenses listed by `list`; the summary and report cover every one.
+const MAX_LISTED: usize = 100;
+
+/// Digits after the decimal point in `currency`'s minor unit.
+fn decimals(currency: &str) -> u32 {
+    match currency {
+        "JPY" | "KRW" | "ISK" | "HUF" => 0,
+        _ => 2,
+    }
+}
+
+/// "£14.50", "-$3.00", "1,200 CHF".
+fn fmt_money(minor: i64, currency: &str) -> String {
+    let dec = decimals(currency);
+    let scale = 10i64.pow(dec);
+    let (sign, abs) = if minor < 0 {
+        ("-", -minor)
+    } else {
+        ("", minor)
+    };
+    let whole = (abs / scale).to_string();
+    let mut grouped = String::new();
+    for (i, c) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let number = if dec > 0 {
+        format!("{grouped}.{:0width$}", abs % scale, width = dec as usize)
+    } else {
+        grouped
+    };
+    match CURRENCY_SYMBOLS.iter().find(|(_, code)| *code == currency) {
+        Some((symbol, _)) => format!("{sign}{symbol}{number}"),
+        None => format!("{sign}{number} {currency}"),
+    }
+}
+
+/// An amount such as `14.5`, `"£14.50"`, `"14.50 EUR"` or `"USD 3"`: the value and the
+/// currency when one was given.
+fn parse_amount(v: &Value) -> Result<(f64, Option<String>), String> {
+    if let Some(n) = v.as_f64() {
+        return Ok((n, None));
+    }
+    let raw = v.as_str().ok_or("missing 'amount'")?.trim();
+    let bad = || format!("could not read amount '{raw}' (e.g. 14.50 or £14.50)");
+    let mut rest = raw;
+    let mut currency = None;
+    if let Some((symbol, code)) = CURRENCY_SYMBOLS.iter().find(|(s, _)| rest.starts_with(s)) {
+        currency = Some(code.to_string());
+        rest = &rest[symbol.len()..];
+    }
+    let code = |s: &str| {
+        (s.len() == 3 && s.chars().all(|c| c.is_ascii_alphabetic())).then(|| s.to_uppercase())
+    };
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    let number = match words.as_slice() {
+        [n] => *n,
+        [n, c] if currency.is_none() && code(c).is_some() => {
+            currency = code(c);
+            n
+        }
+        [c, n] if currency.is_none() && code(c).is_some() => {
+            currency = code(c);
+            n
+        }
+        _ => return Err(bad()),
+    };
+    let value: f64 = number.replace(',', "").parse().map_err(|_| bad())?;
+    Ok((value, currency))
+}
+
+/// First and last day of a "YYYY-MM" month, or of this (`this`, default) or last month.
+fn month_range(arg: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let this = today.with_day(1)?;
+    let first = match arg.trim().to_ascii_lowercase().as_str() {
+        "" | "this" => this,
+        "last" => this.checked_sub_months(Months::new(1))?,
+        other => NaiveDate::parse_from_str(&format!("{other}-01"), "%Y-%m-%d").ok()?,
+    };
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    Some((first, last))
+}
+
+/// Currency, category and total in minor units.
+type CategoryTotal<'a> = (&'a str, &'a str, i64);
+
+/// Totals per currency, then per (currency, category) largest first.
+fn totals(expenses: &[Expense]) -> (BTreeMap<&str, i64>, Vec<CategoryTotal<'_>>) {
+    let mut by_currency: BTreeMap<&str, i64> = BTreeMap::new();
+    let mut by_category: BTreeMap<(&str, String), (&str, i64)> = BTreeMap::new();
+    for e in expenses {
+        *by_currency.entry(&e.currency).or_default() += e.amount;
+        // Categories group case-insensitively under the first spelling seen.
+        let entry = by_category
+            .entry((&e.currency, e.category.to_lowercase()))
+            .or_insert((&e.category, 0));
+        entry.1 += e.amount;
+    }
+    let mut categories: Vec<CategoryTotal> = by_category
+        .into_iter()
+        .map(|((cur, _), (name, total))| (cur, name, total))
+        .collect();
+    categories.sort_by(|a, b| a.0.cmp(b.0).then(b.2.cmp(&a.2)).then(a.1.cmp(b.1)));
+    (by_currency, categories)
+}
+
+fn total_line(by_currency: &BTreeMap<&str, i64>) -> String {
+    by_currency
+        .iter()
+        .map(|(cur, total)| fmt_money(*total, cur))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// The chat summary of one month.
+fn summary(expenses: &[Expense], previous: &[Expense], month: NaiveDate) -> String {
+    let name = month.format("%B %Y");
+    if expenses.is_empty() {
+        return format!("No expenses logged for {name}.");
+    }
+    let (by_currency, categories) = totals(expenses);
+    let mut out = format!(
+        "Spending, {name} ({} expense{}):",
+        expenses.len(),
+        if expenses.len() == 1 { "" } else { "s" }
+    );
+    for (cur, category, total) in &categories {
+        let share = by_currency[cur].max(1);
+        out.push_str(&format!(
+            "\n- {category}: {} ({}%)",
+            fmt_money(*total, cur),
+            total * 100 / share
+        ));
+    }
+    out.push_str(&format!("\nTotal: {}", total_line(&by_currency)));
+    if !previous.is_empty() {
+        let (prev, _) = totals(previous);
+        out.push_str(&format!(" (previous month: {})", total_line(&prev)));
+    }
+    out
+}
+
+/// The vault report of one month.
+fn report(expenses: &[Expense], month: NaiveDate) -> String {
+    let mut out = format!(
+        "# Expenses, {}\n\n_Updated by iCrab; edits here are overwritten._\n\n",
+        month.format("%B %Y")
+    );
+    if expenses.is_empty() {
+        out.push_str("No expenses logged.\n");
+        return out;
+    }
+    let cell = |s: &str| s.replace('|', "/").replace('\n', " ");
+    let (by_currency, categories) = totals(expenses);
+    out.push_str("| Category | Total |\n|---|---|\n");
+    for (cur, category, total) in &categories {
+        out.push_str(&format!(
+            "| {} | {} |\n",
+            cell(category),
+            fmt_money(*total, cur)
+        ));
+    }
+    for (cur, total) in &by_currency {
+        out.push_str(&format!("| **Total** | **{}** |\n", fmt_money(*total, cur)));
+    }
+    out.push_str("\n| Date | Amount | Category | Note |\n|---|---|---|---|\n");
+    for e in expenses {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            e.day,
+            fmt_money(e.amount, &e.currency),
+            cell(&e.category),
+            cell(&e.note)
+        ));
+    }
+    out
+}
+
+/// "#12 Fri 16 Oct: £14.50 Groceries — Tesco".
+fn describe(e: &Expense) -> String {
+    let day = NaiveDate::parse_from_str(&e.day, "%Y-%m-%d")
+        .map_or(e.day.clone(), |d| d.format("%a %-d %b").to_string());
+    let mut out = format!(
+        "#{} {day}: {} {}",
+        e.id,
+        fmt_money(e.amount, &e.currency),
+        e.category
+    );
+    if !e.note.is_empty() {
+        out.push_str(&format!(" — {}", e.note));
+    }
+    out
+}
+
+pub struct ExpenseTool {
+    db: Arc<BrainDb>,
+    dir: String,
+    currency: String,
+    timezone: Tz,
+}
+
+impl ExpenseTool {
+    pub fn new(db: Arc<BrainDb>, config: Option<&ExpenseConfig>, timezone: Tz) -> Self {
+        Self {
+            db,
+            dir: config
+                .and_then(|c| c.dir.as_deref())
+                .map(|d| d.trim_matches('/').to_string())
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| DEFAULT_DIR.to_string()),
+            currency: config
+                .and_then(|c| c.currency.as_deref())
+                .map(|c| c.trim().to_uppercase())
+                .filter(|c| !c.is_empty())
+                .unwrap_or_else(|| DEFAULT_CURRENCY.to_string()),
+            timezone,
+        }
+    }
+
+    async fn month(
+        &self,
+        (first, last): (NaiveDate, NaiveDate),
+        category: Option<String>,
+    ) -> Result<Vec<Expense>, String> {
+        let (from, to) = (first.to_string(), last.to_string());
+        run_blocking(&self.db, "expenses", move |db| {
+            db.expenses(&from, &to, category.as_deref())
+        })
+        .await
+    }
+
+    /// Rewrite the report of the month containing `day`; returns its vault path.
+    async fn refresh(&self, ctx: &ToolCtx, day: NaiveDate) -> Result<String, String> {
+        let range = month_range(&day.format("%Y-%m").to_string(), day).ok_or("invalid month")?;
+        let expenses = self.month(range, None).await?;
+        let rel = format!("{}/{}.md", self.dir, range.0.format("%Y-%m"));
+        let path = resolve_path(&rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("creating {} failed: {e}", self.dir))?;
+        }
+        tokio::fs::write(&path, report(&expenses, range.0))
+            .await
+            .map_err(|e| format!("writing {rel} failed: {e}"))?;
+        Ok(rel)
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let action = args.get("action").and_then(Value::as_str).unwrap_or("");
+        let today = Utc::now().with_timezone(&self.timezone).date_naive();
+        let category = args
+            .get("category")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string);
+        let month_arg = args.get("month").and_then(Value::as_str).unwrap_or("this");
+        let month = || {
+            month_range(month_arg, today)
+                .ok_or_else(|| format!("invalid month '{month_arg}' (YYYY-MM, this or last)"))
+        };
+
+        match action {
+            "add" => {
+                let (value, currency) =
+                    parse_amount(args.get("amount").ok_or("add needs 'amount'")?)?;
+                let currency = args
+                    .get("currency")
+                    .and_then(Value::as_str)
+                    .map(|c| c.trim().to_uppercase())
+                    .filter(|c| !c.is_empty())
+                    .or(currency)
+                    .unwrap_or_else(|| self.currency.clone());
+                let amount = (value * 10f64.powi(decimals(&currency) as i32)).round();
+                if !amount.is_finite() || amount == 0.0 || amount.abs() > 1e12 {
+                    return Err("'amount' must be a non-zero number".into());
+                }
+                let category = category.ok_or("add needs 'category', e.g. groceries")?;
+                let date_arg = args.get("date").and_then(Value::as_str).unwrap_or("today");
+                let day = parse_day(date_arg, today).ok_or_else(|| {
+                    format!("invalid date '{date_arg}' (today, yesterday or YYYY-MM-DD)")
+                })?;
+                let mut expense = Expense {
+                    id: 0,
+                    day: day.to_string(),
+                    amount: amount as i64,
+                    currency,
+                    category,
+                    note: args
+                        .get("note")
+                        .and_then(Value::as_str)
+                        .map(str::trim)
+                        .unwrap_or("")
+                        .to_string(),
+                };
+                let e = expense.clone();
+                expense.id =
+                    run_blocking(&self.db, "expenses", move |db| db.add_expense(&e)).await?;
+                let rel = self.refresh(ctx, day).await?;
+                Ok(format!("Logged {} (report: {rel}).", describe(&expense)))
+            }
+            "remove" => {
+                let id = args
+                    .get("id")
+                    .and_then(Value::as_i64)
+                    .ok_or("remove needs 'id' (from list)")?;
+                let Some(removed) =
+                    run_blocking(&self.db, "expenses", move |db| db.remove_expense(id)).await?
+                else {
+                    return Err(format!("no expense #{id}"));
+                };
+                if let Ok(day) = NaiveDate::parse_from_str(&removed.day, "%Y-%m-%d") {
+                    self.refresh(ctx, day).await?;
+                }
+                Ok(format!("Removed {}.", describe(&removed)))
+            }
+            "list" => {
+                let range = month()?;
+                let expenses = self.month(range, category.clone()).await?;
+                let name = range.0.format("%B %Y");
+                if expenses.is_empty() {
+                    return Ok(match category {
+                        Some(c) => format!("No '{c}' expenses in {name}."),
+                        None => format!("No expenses in {name}."),
+                    });
+                }
+                let mut out = format!("Expenses, {name}:");
+                let skipped = expenses.len().saturating_sub(MAX_LISTED);
+                for e in &expenses[skipped..] {
+                    out.push_str(&format!("\n- {}", describe(e)));
+                }
+                if skipped > 0 {
+                    out.push_str(&format!("\n({skipped} earlier expenses not shown)"));
+                }
+                let (by_currency, _) = totals(&expenses);
+                out.push_str(&format!("\nTotal: {}", total_line(&by_currency)));
+                Ok(out)
+            }
+            "summary" => {
+                let range = month()?;
+                let expenses = self.month(range, None).await?;
+                let prev_month = range.0.pred_opt().map(|d| d.format("%Y-%m").to_string());
+                let previous = match prev_month.and_then(|m| month_range(&m, today)) {
+                    Some(prev) => self.month(prev, None).await?,
+                    None => Vec::new(),
+                };
+                let mut out = summary(&expenses, &previous, range.0);
+                if !expenses.is_empty() {
+                    let rel = self.refresh(ctx, range.0).await?;
+                    out.push_str(&format!("\nReport: {rel}"));
+                }
+                Ok(out)
+            }
+            "" => Err("missing 'action' (add, list, summary or remove)".into()),
+            other => Err(format!(
+                "unknown action '{other}' (add, list, summary or remove)"
+            )),
+        }
+    }
+}
+
+impl Tool for ExpenseTool {
+    fn name(&self) -> &str {
+        "expense"
+    }
+
+    fn description(&self) -> &str {
+        "Expense tracker. add: log spending (\"£14.50 groceries\"; for a forwarded receipt, \
+         read the total and shop first). list: a month's expenses with ids. summary: a \
+         month's spending by category against the previous month; also writes the month's \
+         report note in the vault. remove: delete a wrong entry by id."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["add", "list", "summary", "remove"] },
+                "amount": { "type": ["number", "string"], "description": "Amount spent, e.g. 14.50 or \"£14.50\" (add); negative for a refund" },
+                "currency": { "type": "string", "description": format!("ISO code (add, default {})", self.currency) },
+                "category": { "type": "string", "description": "e.g. groceries, eating out, transport (add; filter for list)" },
+                "note": { "type": "string", "description": "Shop or details (add)" },
+                "date": { "type": "string", "description": "today (default), yesterday or YYYY-MM-DD (add)" },
+                "month": { "type": "string", "description": "YYYY-MM, this (default) or last (list, summary)" },
+                "id": { "type": "integer", "description": "Expense id from list (remove)" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    #[test]
+    fn money_formatting_and_parsing() {
+        assert_eq!(fmt_money(1450, "GBP"), "£14.50");
+        assert_eq!(fmt_money(-305, "USD"), "-$3.05");
+        assert_eq!(fmt_money(123_456_700, "CHF"), "1,234,567.00 CHF");
+        assert_eq!(fmt_money(1200, "JPY"), "¥1,200");
+
+        let p = |v: Value| parse_amount(&v);
+        assert_eq!(p(serde_json::json!(14.5)).unwrap(), (14.5, None));
+        assert_eq!(
+            p(serde_json::json!("£14.50")).unwrap(),
+            (14.5, Some("GBP".to_string()))
+        );
+        assert_eq!(
+            p(serde_json::json!("1,200 eur")).unwrap(),
+            (1200.0, Some("EUR".to_string()))
+        );
+        assert_eq!(
+            p(serde_json::json!("USD 3")).unwrap(),
+            (3.0, Some("USD".to_string()))
+        );
+        assert!(p(serde_json::json!("a lot")).is_err());
+    }
+
+    #[test]
+    fn months() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 16).unwrap();
+        let d = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            month_range("", today),
+            Some((d(2026, 3, 1), d(2026, 3, 31)))
+        );
+        assert_eq!(
+            month_range("last", today),
+            Some((d(2026, 2, 1), d(2026, 2, 28)))
+        );
+        assert_eq!(
+            month_range("2025-12", today),
+            Some((d(2025, 12, 1), d(2025, 12, 31)))
+        );
+        assert_eq!(month_range("March", today), None);
+    }
+
+    #[tokio::test]
+    async fn add_summary_report_and_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(BrainDb::open(dir.path()).unwrap());
+        let tool = ExpenseTool::new(db, None, chrono_tz::Europe::London);
+        let ctx = ctx(dir.path());
+        let add = |amount: Value, category: &str, date: &str| serde_json::json!({"action": "add", "amount": amount, "category": category, "date": date});
+
+        let res = tool
+            .execute(
+                &ctx,
+                &add(serde_json::json!("£14.50"), "Groceries", "2026-10-16"),
+            )
+            .await;
+        assert_eq!(
+            res.for_llm,
+            "Logged #1 Fri 16 Oct: £14.50 Groceries (report: Expenses/2026-10.md)."
+        );
+        tool.execute(
+            &ctx,
+            &add(serde_json::json!(5.5), "groceries", "2026-10-02"),
+        )
+        .await;
+        tool.execute(&ctx, &add(serde_json::json!(10), "Coffee", "2026-10-03"))
+            .await;
+        tool.execute(&ctx, &add(serde_json::json!(40), "Coffee", "2026-09-20"))
+            .await;
+
+        let args = serde_json::json!({"action": "summary", "month": "2026-10"});
+        let res = tool.execute(&ctx, &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Spending, October 2026 (3 expenses):\n\
+             - groceries: £20.00 (66%)\n\
+             - Coffee: £10.00 (33%)\n\
+             Total: £30.00 (previous month: £40.00)\n\
+             Report: Expenses/2026-10.md"
+        );
+        let report = std::fs::read_to_string(dir.path().join("Expenses/2026-10.md")).unwrap();
+        assert!(report.starts_with("# Expenses, October 2026\n"), "{report}");
+        assert!(report.contains("| **Total** | **£30.00** |"), "{report}");
+        assert!(
+            report.contains("| 2026-10-16 | £14.50 | Groceries |  |"),
+            "{report}"
+        );
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"action": "remove", "id": 1}))
+            .await;
+        assert_eq!(res.for_llm, "Removed #1 Fri 16 Oct: £14.50 Groceries.");
+        let args =
+            serde_json::json!({"action": "list", "month": "2026-10", "category": "groceries"});
+        let res = tool.execute(&ctx, &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Expenses, October 2026:\n- #2 Fri 2 Oct: £5.50 groceries\nTotal: £5.50"
+        );
+        let report = std::fs::read_to_string(dir.path().join("Expenses/2026-10.md")).unwrap();
+        assert!(!report.contains("£14.50"), "{report}");
+    }
+}
@@ -0,0 +1,385 @@
+//! `export_chat` tool: render a chat session (or a date range) from `chat_history` into a
+//! Markdown note under `Chats/`, so conversations worth keeping become part of the vault and
+//! are indexed and synced with it.
+//!
+//! Only user messages and assistant replies are exported; tool calls and their results are
+//! left out. Times are UTC, as stored.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::memory::db::{BrainDb, ChatRecord, ChatSearchFilter, DbError};
+use crate::telegram;
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::session::resolve_session;
+use crate::workspace;
+
+/// Most messages written to one export.
+const MAX_MESSAGES: usize = 2000;
+/// Characters of the first user message used as the default title.
+const TITLE_CHARS: usize = 60;
+/// Words of the title used in the file name.
+const SLUG_WORDS: usize = 6;
+
+pub struct ExportChatTool {
+    db: Arc<BrainDb>,
+}
+
+impl ExportChatTool {
+    pub fn new(db: Arc<BrainDb>) -> Self {
+        Self { db }
+    }
+}
+
+impl Tool for ExportChatTool {
+    fn name(&self) -> &str {
+        "export_chat"
+    }
+
+    fn description(&self) -> &str {
+        "Save a conversation of this chat as a Markdown note under Chats/ in the vault. \
+         Exports the current session by default, a past session by id (see the session tool), \
+         or every message between two dates."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session id or unique prefix to export (default: current session)"
+                },
+                "after": {
+                    "type": "string",
+                    "description": "Export messages on or after this date instead (YYYY-MM-DD, UTC)"
+                },
+                "before": {
+                    "type": "string",
+                    "description": "Export messages before this date (YYYY-MM-DD, UTC, exclusive)"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Note title (default: the first message)"
+                }
+            }
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let db = Arc::clone(&self.db);
+        let args = args.clone();
+        let workspace = ctx.workspace.clone();
+        let key = ctx
+            .chat_id
+            .map(|id| telegram::session_key(id, ctx.thread_id));
+
+        Box::pin(async move {
+            let Some(key) = key else {
+                return ToolResult::error("no chat_id (export_chat unavailable)");
+            };
+            let filter = match parse_range(&args) {
+                Ok(f) => f,
+                Err(e) => return ToolResult::error(e),
+            };
+            let session = text(&args, "session_id");
+            let title = text(&args, "title");
+
+            let result = tokio::task::spawn_blocking(move || {
+                export(&db, &workspace, &key, session.as_deref(), filter, title)
+            })
+            .await;
+            match result {
+                Ok(Ok(Ok(export))) => ToolResult::ok(format!(
+                    "Exported {} message(s) to {}.",
+                    export.messages, export.path
+                )),
+                Ok(Ok(Err(msg))) => ToolResult::error(msg),
+                Ok(Err(e)) => ToolResult::error(format!("export failed: {e}")),
+                Err(e) => ToolResult::error(format!("export task error: {e}")),
+            }
+        })
+    }
+}
+
+/// A written export: workspace-relative path and message count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exported {
+    pub path: String,
+    pub messages: usize,
+}
+
+/// Write the messages of `chat_id` selected by `session` (id or prefix) or by the date range
+/// in `filter` (current session when neither is given) to a new note in `Chats/`.
+/// The inner error is a user-facing message (nothing to export, unknown session).
+pub fn export(
+    db: &BrainDb,
+    workspace: &Path,
+    chat_id: &str,
+    session: Option<&str>,
+    mut filter: ChatSearchFilter,
+    title: Option<String>,
+) -> Result<Result<Exported, String>, DbError> {
+    let ranged = filter.after.is_some() || filter.before.is_some();
+    if let Some(wanted) = session {
+        let sessions = db.list_sessions(chat_id)?;
+        match resolve_session(&sessions, wanted) {
+            Ok(s) => filter.session_id = Some(s.session_id.clone()),
+            Err(e) => return Ok(Err(e)),
+        }
+    } else if !ranged {
+        filter.session_id = Some(db.get_or_create_session_id(chat_id)?);
+    }
+
+    let records: Vec<ChatRecord> = db
+        .chat_messages(chat_id, &filter, MAX_MESSAGES)?
+        .into_iter()
+        .filter(is_exported)
+        .collect();
+    if records.is_empty() {
+        return Ok(Err("no messages to export".to_string()));
+    }
+
+    let date = records[0]
+        .timestamp
+        .get(..10)
+        .unwrap_or("undated")
+        .to_string();
+    let title = title.unwrap_or_else(|| default_title(&records, &date));
+    let note = render(&records, &title, filter.session_id.as_deref());
+
+    let dir = workspace::chats_dir(workspace);
+    std::fs::create_dir_all(&dir).map_err(|e| DbError(format!("create {}: {e}", dir.display())))?;
+    let path = unique_path(&dir, &format!("{date}-{}", slug(&title)));
+    std::fs::write(&path, note).map_err(|e| DbError(format!("write {}: {e}", path.display())))?;
+
+    let rel = path
+        .strip_prefix(workspace)
+        .unwrap_or(&path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    Ok(Ok(Exported {
+        path: rel,
+        messages: records.len(),
+    }))
+}
+
+/// User messages and assistant replies with text (not tool calls or results).
+fn is_exported(record: &ChatRecord) -> bool {
+    let m = &record.message;
+    match m.role.as_str() {
+        "user" => true,
+        "assistant" => !m.content.trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// Markdown note: frontmatter, title, then one block per message.
+fn render(records: &[ChatRecord], title: &str, session_id: Option<&str>) -> String {
+    let first = &records[0].timestamp;
+    let last = &records[records.len() - 1].timestamp;
+    let mut out = String::from("---\n");
+    out.push_str(&format!("title: \"{}\"\n", title.replace('"', "'")));
+    out.push_str(&format!("date: {}\n", first.get(..10).unwrap_or(first)));
+    if let Some(id) = session_id {
+        out.push_str(&format!("session: {id}\n"));
+    }
+    out.push_str(&format!("messages: {}\n", records.len()));
+    out.push_str("tags: [chat]\n---\n\n");
+    out.push_str(&format!("# {title}\n\n"));
+    out.push_str(&format!("_{first} – {last} UTC_\n"));
+
+    let mut day = "";
+    for r in records {
+        let (date, time) = r
+            .timestamp
+            .split_once(' ')
+            .unwrap_or((r.timestamp.as_str(), ""));
+        if date != day {
+            out.push_str(&format!("\n## {date}\n"));
+            day = date;
+        }
+        let who = if r.message.role == "user" {
+            "You"
+        } else {
+            "iCrab"
+        };
+        let time = time.get(..5).unwrap_or(time);
+        out.push_str(&format!(
+            "\n**{who}** · {time}\n\n{}\n",
+            r.message.content.trim()
+        ));
+    }
+    out
+}
+
+/// First user message (shortened), or "Chat <date>".
+fn default_title(records: &[ChatRecord], date: &str) -> String {
+    let first = records
+        .iter()
+        .find(|r| r.message.role == "user")
+        .map(|r| {
+            r.message
+                .content
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    if first.is_empty() {
+        return format!("Chat {date}");
+    }
+    let mut title: String = first.chars().take(TITLE_CHARS).collect();
+    if first.chars().count() > TITLE_CHARS {
+        title.push('…');
+    }
+    title
+}
+
+/// Lowercase ASCII words of `title` joined by hyphens; "chat" when none.
+fn slug(title: &str) -> String {
+    let words: Vec<String> = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .take(SLUG_WORDS)
+        .map(str::to_ascii_lowercase)
+        .collect();
+    if words.is_empty() {
+        "chat".to_string()
+    } else {
+        words.join("-")
+    }
+}
+
+/// `dir/stem.md`, or `dir/stem-2.md`, `-3`, … if taken; earlier exports are never overwritten.
+fn unique_path(dir: &Path, stem: &str) -> PathBuf {
+    let mut path = dir.join(format!("{stem}.md"));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{stem}-{n}.md"));
+        n += 1;
+    }
+    path
+}
+
+fn text(args: &Value, key: &str) -> Option<String> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// Optional `after` / `before` dates.
+fn parse_range(args: &Value) -> Result<ChatSearchFilter, String> {
+    let date = |key: &str| match text(args, key) {
+        Some(d) => chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d")
+            .map(|d| Some(d.format("%Y-%m-%d").to_string()))
+            .map_err(|_| format!("'{key}' must be a date like 2026-02-01")),
+        None => Ok(None),
+    };
+    Ok(ChatSearchFilter {
+        after: date("after")?,
+        before: date("before")?,
+        ..ChatSearchFilter::default()
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::db::StoredMessage;
+    use tempfile::TempDir;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx {
+            chat_id: Some(42),
+            channel: Some("telegram".into()),
+            ..ToolCtx::for_tests(workspace)
+        }
+    }
+
+    fn msg(role: &str, content: &str) -> StoredMessage {
+        StoredMessage {
+            role: role.into(),
+            content: content.into(),
+            tool_call_id: None,
+            tool_calls: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn exports_current_session_without_tool_rows() {
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let sid = db.get_or_create_session_id("42").unwrap();
+        db.append_session(
+            "42",
+            &sid,
+            &[
+                msg("user", "Plan my Lisbon trip"),
+                msg("assistant", ""),
+                msg("tool", "{\"weather\": \"sunny\"}"),
+                msg("assistant", "Day 1: Alfama."),
+            ],
+            "",
+        )
+        .unwrap();
+
+        let tool = ExportChatTool::new(Arc::clone(&db));
+        let res = tool.execute(&ctx(tmp.path()), &serde_json::json!({})).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(res.for_llm.contains("2 message(s)"), "{}", res.for_llm);
+
+        let files: Vec<_> = std::fs::read_dir(workspace::chats_dir(tmp.path()))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let name = files[0].file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.ends_with("-plan-my-lisbon-trip.md"), "{name}");
+        let note = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(note.starts_with("---\ntitle: \"Plan my Lisbon trip\""));
+        assert!(note.contains("**You**") && note.contains("Day 1: Alfama."));
+        assert!(!note.contains("sunny"));
+
+        // A second export of the same session gets its own file.
+        let res = tool.execute(&ctx(tmp.path()), &serde_json::json!({})).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            workspace::chats_dir(tmp.path())
+                .join(name.replace(".md", "-2.md"))
+                .exists()
+        );
+    }
+
+    #[tokio::test]
+    async fn nothing_to_export_is_an_error() {
+        let tmp = TempDir::new().unwrap();
+        let tool = ExportChatTool::new(Arc::new(BrainDb::open(tmp.path()).unwrap()));
+        for args in [
+            serde_json::json!({}),
+            serde_json::json!({"session_id": "nope"}),
+            serde_json::json!({"after": "last week"}),
+        ] {
+            assert!(
+                tool.execute(&ctx(tmp.path()), &args).await.is_error,
+                "{args}"
+            );
+        }
+    }
+
+    #[test]
+    fn slug_keeps_ascii_words() {
+        assert_eq!(slug("Plan: my Lisbon trip!"), "plan-my-lisbon-trip");
+        assert_eq!(slug("¿¡!"), "chat");
+    }
+}
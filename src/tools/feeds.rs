@@ -0,0 +1,458 @@
+//! `feeds` tool: fetch RSS/Atom feeds from `[tools.feeds.urls]` (or a one-off URL) and return
+//! only items not returned before, for a daily news digest that never repeats itself.
+//!
+//! Seen item GUIDs are kept per feed URL in BrainDb (`feed_seen`). Parsing is deliberately
+//! small — items/entries with title, link, id, date and summary — not a full XML parser.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use regex_lite::{Captures, Regex};
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::config::FeedsConfig;
+use crate::memory::db::BrainDb;
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::web::{html_to_text, validate_fetch_url};
+
+const DEFAULT_MAX_ITEMS: usize = 10;
+const SUMMARY_CHARS: usize = 200;
+
+/// One item of a feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedItem {
+    /// `<guid>`/`<id>`, else the link, else the title.
+    pub guid: String,
+    pub title: String,
+    pub link: Option<String>,
+    /// "YYYY-MM-DD HH:MM" in the feed's own offset, or the raw date if unparseable.
+    pub published: Option<String>,
+    /// Plain-text start of the description or summary.
+    pub summary: Option<String>,
+}
+
+/// Decode XML/HTML entities (named basics and numeric).
+pub(crate) fn decode_entities(s: &str) -> String {
+    let re = Regex::new(r"&(#[xX][0-9a-fA-F]+|#[0-9]+|amp|lt|gt|quot|apos|nbsp);").unwrap();
+    re.replace_all(s, |caps: &Captures| {
+        let name = &caps[1];
+        let code = if let Some(hex) = name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok()
+        } else if let Some(dec) = name.strip_prefix('#') {
+            dec.parse().ok()
+        } else {
+            None
+        };
+        match (name, code.and_then(char::from_u32)) {
+            (_, Some(c)) => c.to_string(),
+            ("amp", _) => "&".to_string(),
+            ("lt", _) => "<".to_string(),
+            ("gt", _) => ">".to_string(),
+            ("quot", _) => "\"".to_string(),
+            ("apos", _) => "'".to_string(),
+            ("nbsp", _) => " ".to_string(),
+            _ => caps[0].to_string(),
+        }
+    })
+    .into_owned()
+}
+
+/// Text content of the first `<tag>` in `xml`: CDATA unwrapped, entities decoded, trimmed.
+fn tag_text(xml: &str, tag: &str) -> Option<String> {
+    let re = Regex::new(&format!(r"(?s)<{tag}(?:\s[^>]*)?>(.*?)</{tag}>")).ok()?;
+    let raw = re.captures(xml)?.get(1)?.as_str().trim();
+    let text = match raw
+        .strip_prefix("<![CDATA[")
+        .and_then(|r| r.strip_suffix("]]>"))
+    {
+        Some(cdata) => cdata.to_string(),
+        None => decode_entities(raw),
+    };
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Atom `<link href="…"/>`: the alternate (or rel-less) link.
+fn atom_link(xml: &str) -> Option<String> {
+    let link_re = Regex::new(r"<link\s[^>]*>").unwrap();
+    let attr = |tag: &str, name: &str| {
+        Regex::new(&format!(r#"\s{name}\s*=\s*["']([^"']*)["']"#))
+            .ok()?
+            .captures(tag)
+            .map(|c| decode_entities(&c[1]))
+    };
+    link_re.find_iter(xml).find_map(|m| {
+        let rel = attr(m.as_str(), "rel");
+        if rel.is_none() || rel.as_deref() == Some("alternate") {
+            attr(m.as_str(), "href")
+        } else {
+            None
+        }
+    })
+}
+
+fn format_date(raw: &str) -> String {
+    chrono::DateTime::parse_from_rfc2822(raw)
+        .or_else(|_| chrono::DateTime::parse_from_rfc3339(raw))
+        .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| raw.to_string())
+}
+
+fn summarize(html: &str) -> Option<String> {
+    let text = decode_entities(&html_to_text(html));
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(match text.char_indices().nth(SUMMARY_CHARS) {
+        Some((at, _)) => format!("{}…", text[..at].trim_end()),
+        None => text.to_string(),
+    })
+}
+
+/// Feed title and items of an RSS 2.0 / RSS 1.0 / Atom document, in document order.
+pub fn parse_feed(xml: &str) -> Result<(Option<String>, Vec<FeedItem>), String> {
+    let item_re = Regex::new(r"(?s)<(item|entry)(?:\s[^>]*)?>(.*?)</(?:item|entry)>").unwrap();
+    let first = item_re.find(xml).map_or(xml.len(), |m| m.start());
+    let feed_title = tag_text(&xml[..first], "title");
+    let mut items = Vec::new();
+    for caps in item_re.captures_iter(xml) {
+        let body = &caps[2];
+        let link = tag_text(body, "link").or_else(|| atom_link(body));
+        let title = tag_text(body, "title").map(|t| decode_entities(&html_to_text(&t)));
+        let guid = tag_text(body, "guid")
+            .or_else(|| tag_text(body, "id"))
+            .or_else(|| link.clone())
+            .or_else(|| title.clone());
+        let Some(guid) = guid else { continue };
+        let published = ["pubDate", "published", "updated", "dc:date"]
+            .iter()
+            .find_map(|t| tag_text(body, t))
+            .map(|d| format_date(&d));
+        let summary = ["description", "summary", "content:encoded", "content"]
+            .iter()
+            .find_map(|t| tag_text(body, t))
+            .and_then(|s| summarize(&s));
+        items.push(FeedItem {
+            guid,
+            title: title.unwrap_or_else(|| "(untitled)".to_string()),
+            link,
+            published,
+            summary,
+        });
+    }
+    if items.is_empty() && !xml.contains("<rss") && !xml.contains("<feed") && !xml.contains("<rdf")
+    {
+        return Err("not an RSS or Atom feed".to_string());
+    }
+    Ok((feed_title, items))
+}
+
+pub struct FeedsTool {
+    db: Arc<BrainDb>,
+    client: Client,
+    urls: BTreeMap<String, String>,
+    max_items: usize,
+}
+
+impl FeedsTool {
+    pub fn new(db: Arc<BrainDb>, client: Client, config: Option<&FeedsConfig>) -> Self {
+        Self {
+            db,
+            client,
+            urls: config.and_then(|c| c.urls.clone()).unwrap_or_default(),
+            max_items: config
+                .and_then(|c| c.max_items)
+                .unwrap_or(DEFAULT_MAX_ITEMS)
+                .max(1),
+        }
+    }
+
+    /// New items of one feed as a Markdown section.
+    async fn read_feed(&self, name: &str, url: &str, limit: usize, peek: bool) -> String {
+        let (title, items) = match self.fetch(url).await {
+            Ok(parsed) => parsed,
+            Err(e) => return format!("## {name}\nError: {e}"),
+        };
+        let heading = match title {
+            Some(t) if t != name => format!("## {name} — {t}"),
+            _ => format!("## {name}"),
+        };
+
+        let db = Arc::clone(&self.db);
+        let feed = url.to_string();
+        let guids: Vec<String> = items.iter().map(|i| i.guid.clone()).collect();
+        let unseen =
+            match tokio::task::spawn_blocking(move || db.feed_unseen(&feed, &guids, !peek)).await {
+                Ok(Ok(unseen)) => unseen,
+                Ok(Err(e)) => return format!("{heading}\nError: seen-items lookup failed: {e}"),
+                Err(e) => return format!("{heading}\nError: seen-items task error: {e}"),
+            };
+        if unseen.is_empty() {
+            return format!("{heading}\nNo new items.");
+        }
+
+        let mut out = format!("{heading} ({} new)", unseen.len());
+        let mut shown = 0;
+        for item in &items {
+            if shown == limit {
+                break;
+            }
+            if !unseen.contains(&item.guid) {
+                continue;
+            }
+            shown += 1;
+            out.push_str(&format!("\n- {}", item.title));
+            if let Some(date) = &item.published {
+                out.push_str(&format!(" ({date})"));
+            }
+            if let Some(link) = &item.link {
+                out.push_str(&format!("\n  {link}"));
+            }
+            if let Some(summary) = &item.summary {
+                out.push_str(&format!("\n  {summary}"));
+            }
+        }
+        if unseen.len() > shown {
+            let rest = unseen.len() - shown;
+            out.push_str(&if peek {
+                format!("\n… and {rest} more")
+            } else {
+                format!("\n… and {rest} more (also marked as seen)")
+            });
+        }
+        out
+    }
+
+    async fn fetch(&self, url: &str) -> Result<(Option<String>, Vec<FeedItem>), String> {
+        let url = validate_fetch_url(url)?;
+        let res = self
+            .client
+            .get(url)
+            .header(
+                "accept",
+                "application/rss+xml, application/atom+xml, application/xml;q=0.9, */*;q=0.5",
+            )
+            .send()
+            .await
+            .map_err(|e| format!("request failed: {e}"))?;
+        let status = res.status();
+        if !status.is_success() {
+            return Err(format!("HTTP {status}"));
+        }
+        let body = res
+            .bytes()
+            .await
+            .map_err(|e| format!("reading feed failed: {e}"))?;
+        parse_feed(&String::from_utf8_lossy(&body))
+    }
+}
+
+impl Tool for FeedsTool {
+    fn name(&self) -> &str {
+        "feeds"
+    }
+
+    fn description(&self) -> &str {
+        "Read RSS/Atom feeds and return only items not returned before (titles, links, dates, \
+         short summaries); returned items are remembered as seen. Reads all configured feeds \
+         unless 'feeds' or 'url' is given. Use for news digests; 'peek' leaves items unseen."
+    }
+
+    fn parameters(&self) -> Value {
+        let names: Vec<&str> = self.urls.keys().map(String::as_str).collect();
+        let feeds = if names.is_empty() {
+            "Configured feed names (none configured; use 'url')".to_string()
+        } else {
+            format!("Configured feed names: {} (default all)", names.join(", "))
+        };
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "feeds": { "type": "array", "items": { "type": "string" }, "description": feeds },
+                "url": { "type": "string", "description": "A feed URL to read instead of configured feeds" },
+                "limit": { "type": "integer", "description": format!("Max new items per feed (default {})", self.max_items) },
+                "peek": { "type": "boolean", "description": "Don't mark returned items as seen (default false)" }
+            }
+        })
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let limit = args
+                .get("limit")
+                .and_then(Value::as_u64)
+                .map_or(self.max_items, |n| n.max(1) as usize);
+            let peek = args.get("peek").and_then(Value::as_bool).unwrap_or(false);
+
+            let selected: Vec<(String, String)> = if let Some(url) =
+                args.get("url").and_then(Value::as_str)
+            {
+                vec![(url.to_string(), url.to_string())]
+            } else if let Some(names) = args.get("feeds").and_then(Value::as_array) {
+                let mut selected = Vec::new();
+                for name in names.iter().filter_map(Value::as_str) {
+                    match self.urls.get(name) {
+                        Some(url) => selected.push((name.to_string(), url.clone())),
+                        None => {
+                            let known: Vec<&str> = self.urls.keys().map(String::as_str).collect();
+                            return ToolResult::error(format!(
+                                "unknown feed '{name}' (configured: {})",
+                                if known.is_empty() {
+                                    "none".to_string()
+                                } else {
+                                    known.join(", ")
+                                }
+                            ));
+                        }
+                    }
+                }
+                selected
+            } else {
+                self.urls
+                    .iter()
+                    .map(|(n, u)| (n.clone(), u.clone()))
+                    .collect()
+            };
+            if selected.is_empty() {
+                return ToolResult::error(
+                    "no feeds configured: add [tools.feeds.urls] to the config or pass 'url'",
+                );
+            }
+
+            let sections = futures_util::future::join_all(
+                selected
+                    .iter()
+                    .map(|(name, url)| self.read_feed(name, url, limit, peek)),
+            )
+            .await;
+            ToolResult::ok(sections.join("\n\n"))
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::web::web_client;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+<channel>
+  <title>Example News</title>
+  <link>https://news.example/</link>
+  <item>
+    <title>Rates held &amp; markets calm</title>
+    <link>https://news.example/rates</link>
+    <guid isPermaLink="false">rates-1</guid>
+    <pubDate>Fri, 16 Oct 2026 07:30:00 +0100</pubDate>
+    <description><![CDATA[<p>The bank kept rates at <b>4%</b>.</p>]]></description>
+  </item>
+  <item>
+    <title>Second story</title>
+    <link>https://news.example/second</link>
+    <description>Plain &lt;i&gt;escaped&lt;/i&gt; text</description>
+  </item>
+</channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="text">Dev Blog</title>
+  <link rel="self" href="https://blog.example/feed.xml"/>
+  <entry>
+    <title>Release 2.0</title>
+    <link rel="alternate" href="https://blog.example/2-0"/>
+    <id>tag:blog.example,2026:2-0</id>
+    <updated>2026-10-15T18:00:00Z</updated>
+    <summary>Caf&#233; mode &#x2014; finally.</summary>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn parses_rss_and_atom() {
+        let (title, items) = parse_feed(RSS).unwrap();
+        assert_eq!(title.as_deref(), Some("Example News"));
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0],
+            FeedItem {
+                guid: "rates-1".to_string(),
+                title: "Rates held & markets calm".to_string(),
+                link: Some("https://news.example/rates".to_string()),
+                published: Some("2026-10-16 07:30".to_string()),
+                summary: Some("The bank kept rates at 4% .".to_string()),
+            }
+        );
+        assert_eq!(items[1].guid, "https://news.example/second");
+        assert_eq!(items[1].summary.as_deref(), Some("Plain escaped text"));
+
+        let (title, items) = parse_feed(ATOM).unwrap();
+        assert_eq!(title.as_deref(), Some("Dev Blog"));
+        assert_eq!(items[0].guid, "tag:blog.example,2026:2-0");
+        assert_eq!(items[0].link.as_deref(), Some("https://blog.example/2-0"));
+        assert_eq!(items[0].published.as_deref(), Some("2026-10-15 18:00"));
+        assert_eq!(items[0].summary.as_deref(), Some("Café mode — finally."));
+
+        assert!(parse_feed("<html><body>nope</body></html>").is_err());
+    }
+
+    #[tokio::test]
+    async fn returns_only_unseen_items() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rss.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(RSS))
+            .mount(&server)
+            .await;
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let mut urls = BTreeMap::new();
+        urls.insert("news".to_string(), format!("{}/rss.xml", server.uri()));
+        let config = FeedsConfig {
+            urls: Some(urls),
+            max_items: Some(1),
+        };
+        let tool = FeedsTool::new(db, web_client().unwrap(), Some(&config));
+        let ctx = ToolCtx::for_tests(PathBuf::from("/tmp"));
+
+        let res = tool.execute(&ctx, &serde_json::json!({"peek": true})).await;
+        assert!(res.for_llm.ends_with("\n… and 1 more"), "{}", res.for_llm);
+
+        let res = tool.execute(&ctx, &serde_json::json!({})).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "## news — Example News (2 new)\n\
+             - Rates held & markets calm (2026-10-16 07:30)\n  \
+             https://news.example/rates\n  \
+             The bank kept rates at 4% .\n\
+             … and 1 more (also marked as seen)"
+        );
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"feeds": ["news"]}))
+            .await;
+        assert_eq!(res.for_llm, "## news — Example News\nNo new items.");
+
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"feeds": ["sport"]}))
+            .await;
+        assert!(res.is_error);
+        assert!(
+            res.for_llm
+                .contains("unknown feed 'sport' (configured: news)")
+        );
+    }
+}
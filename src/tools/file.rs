@@ -1,11 +1,14 @@
-//! read_file, write_file, list_dir, edit_file, append_file — workspace-only, path restriction.
+//! read_file, write_file, list_dir, edit_file, multi_edit, append_file, copy_file, stat_file —
+//! workspace-only, path restriction.
 
 use std::path::{Component, Path, PathBuf};
 
 use serde_json::Value;
 use tokio::io::AsyncWriteExt;
 
+use crate::memory::backup::format_bytes;
 use crate::tools::context::ToolCtx;
+use crate::tools::patch::unified_diff;
 use crate::tools::registry::{BoxFuture, Tool};
 use crate::tools::result::ToolResult;
 
@@ -55,6 +58,70 @@ fn get_optional_string(args: &Value, key: &str) -> Option<String> {
     args.get(key).and_then(Value::as_str).map(String::from)
 }
 
+/// Lines per read_file page when no end_line is given.
+const READ_MAX_LINES: usize = 2000;
+/// Characters per read_file page, so a few huge lines cannot flood the context.
+const READ_MAX_CHARS: usize = 100_000;
+
+/// Lines `start..=end` (1-based) of `content`, capped at [`READ_MAX_LINES`] and
+/// [`READ_MAX_CHARS`]. A small file read without a range comes back unchanged; otherwise a
+/// closing note says which lines were shown and where to continue.
+pub fn read_lines(content: &str, start: Option<u64>, end: Option<u64>) -> Result<String, String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let total = lines.len();
+    let start = start.unwrap_or(1).max(1) as usize;
+    if start > total.max(1) {
+        return Err(format!(
+            "start_line {start} is past the end of the file ({total} lines)"
+        ));
+    }
+    if total == 0 {
+        return Ok(String::new());
+    }
+    let end = end.map_or(total, |e| (e as usize).min(total));
+    if end < start {
+        return Ok(format!(
+            "[No lines: end_line {end} is before start_line {start}]"
+        ));
+    }
+    let end = end.min(start + READ_MAX_LINES - 1);
+
+    let mut out = String::new();
+    let mut last = start - 1;
+    for line in &lines[start - 1..end] {
+        if out.len() + line.len() > READ_MAX_CHARS {
+            if last < start {
+                // A single line over the limit: show its beginning.
+                let cut = (0..=READ_MAX_CHARS)
+                    .rev()
+                    .find(|&i| line.is_char_boundary(i))
+                    .unwrap_or(0);
+                out.push_str(&line[..cut]);
+                out.push_str(&format!(
+                    "\n\n[Line {start} of {total} is {} bytes; showing the first {cut}.]",
+                    line.len()
+                ));
+                return Ok(out);
+            }
+            break;
+        }
+        out.push_str(line);
+        last += 1;
+    }
+    if start == 1 && last == total {
+        return Ok(out);
+    }
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&format!("\n[Showing lines {start}-{last} of {total}."));
+    if last < total {
+        out.push_str(&format!(" Continue with start_line={}.", last + 1));
+    }
+    out.push(']');
+    Ok(out)
+}
+
 /// read_file tool.
 pub struct ReadFile;
 
@@ -64,19 +131,27 @@ impl Tool for ReadFile {
     }
 
     fn description(&self) -> &str {
-        "Read the contents of a file in the workspace. Path is relative to workspace."
+        "Read the contents of a file in the workspace. Path is relative to workspace. Large \
+         files are returned in pages of up to 2000 lines; use start_line/end_line to read a \
+         range or the next page."
     }
 
     fn parameters(&self) -> Value {
         serde_json::json!({
             "type": "object",
             "properties": {
-                "path": { "type": "string", "description": "Path relative to workspace" }
+                "path": { "type": "string", "description": "Path relative to workspace" },
+                "start_line": { "type": "integer", "description": "First line to return, 1-based (default 1)" },
+                "end_line": { "type": "integer", "description": "Last line to return, inclusive (default: end of file, within the page limit)" }
             },
             "required": ["path"]
         })
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
         let args = args.clone();
         let ctx = ctx.clone();
@@ -90,8 +165,13 @@ impl Tool for ReadFile {
                     Ok(p) => p,
                     Err(e) => return ToolResult::error(e),
                 };
+            let start = args.get("start_line").and_then(Value::as_u64);
+            let end = args.get("end_line").and_then(Value::as_u64);
             match tokio::fs::read_to_string(&resolved).await {
-                Ok(content) => ToolResult::ok(content),
+                Ok(content) => match read_lines(&content, start, end) {
+                    Ok(page) => ToolResult::ok(page),
+                    Err(e) => ToolResult::error(e),
+                },
                 Err(e) => ToolResult::error(e.to_string()),
             }
         })
@@ -151,6 +231,141 @@ impl Tool for WriteFile {
     }
 }
 
+/// Depth of a `recursive` list_dir without max_depth.
+const TREE_MAX_DEPTH: usize = 10;
+/// Lines of list_dir output before the rest is summarised.
+const TREE_MAX_ENTRIES: usize = 500;
+
+/// One listed entry; `children` is None for files and for directories not descended into.
+struct TreeEntry {
+    name: String,
+    is_dir: bool,
+    /// File size in bytes, or number of entries for a directory.
+    size: u64,
+    children: Option<Vec<TreeEntry>>,
+}
+
+/// Entries of `dir` down to `depth` levels, sorted by name. With `glob` (matched against the
+/// name, or the path below the listed directory when it has a `/`) only matching files and
+/// the directories leading to them are kept. Hidden directories are listed but not entered.
+fn collect_tree(
+    dir: &Path,
+    rel: &str,
+    depth: usize,
+    glob: Option<&regex_lite::Regex>,
+    glob_is_path: bool,
+) -> std::io::Result<Vec<TreeEntry>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let path = entry.path();
+        let Ok(meta) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let rel_path = if rel.is_empty() {
+            name.clone()
+        } else {
+            format!("{rel}/{name}")
+        };
+        if meta.is_dir() {
+            let children = if depth > 1 && !name.starts_with('.') {
+                collect_tree(&path, &rel_path, depth - 1, glob, glob_is_path).ok()
+            } else {
+                None
+            };
+            if glob.is_some() && children.as_ref().is_none_or(|c| c.is_empty()) {
+                continue;
+            }
+            let size = match &children {
+                Some(c) if glob.is_none() => c.len() as u64,
+                _ => std::fs::read_dir(&path).map_or(0, |d| d.count() as u64),
+            };
+            entries.push(TreeEntry {
+                name,
+                is_dir: true,
+                size,
+                children,
+            });
+        } else {
+            if let Some(glob) = glob
+                && !glob.is_match(if glob_is_path { &rel_path } else { &name })
+            {
+                continue;
+            }
+            entries.push(TreeEntry {
+                name,
+                is_dir: false,
+                size: meta.len(),
+                children: None,
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+fn count_tree(entries: &[TreeEntry]) -> usize {
+    entries
+        .iter()
+        .map(|e| 1 + e.children.as_deref().map_or(0, count_tree))
+        .sum()
+}
+
+/// Indented listing: `name/ (N entries)` for directories (contents indented below when
+/// descended into), `name (size)` for files. Stops after `limit` lines.
+fn render_tree(entries: &[TreeEntry], indent: usize, limit: usize, out: &mut Vec<String>) {
+    for entry in entries {
+        if out.len() >= limit {
+            return;
+        }
+        let pad = "  ".repeat(indent);
+        if entry.is_dir {
+            let plural = if entry.size == 1 { "entry" } else { "entries" };
+            out.push(format!("{pad}{}/ ({} {plural})", entry.name, entry.size));
+            if let Some(children) = &entry.children {
+                render_tree(children, indent + 1, limit, out);
+            }
+        } else {
+            out.push(format!(
+                "{pad}{} ({})",
+                entry.name,
+                format_bytes(entry.size)
+            ));
+        }
+    }
+}
+
+/// list_dir output for `dir` (blocking; call from `spawn_blocking`).
+pub fn list_tree(dir: &Path, depth: usize, glob: Option<&str>) -> Result<String, String> {
+    let regex = match glob {
+        Some(g) => Some(
+            crate::memory::ignore::glob_regex(g).ok_or_else(|| format!("invalid glob '{g}'"))?,
+        ),
+        None => None,
+    };
+    let glob_is_path = glob.is_some_and(|g| g.contains('/'));
+    let entries = collect_tree(dir, "", depth.max(1), regex.as_ref(), glob_is_path)
+        .map_err(|e| e.to_string())?;
+    if entries.is_empty() {
+        return Ok(match glob {
+            Some(g) => format!("No files match '{g}'."),
+            None => "(empty directory)".to_string(),
+        });
+    }
+    let mut lines = Vec::new();
+    render_tree(&entries, 0, TREE_MAX_ENTRIES, &mut lines);
+    let total = count_tree(&entries);
+    if total > lines.len() {
+        lines.push(format!(
+            "[… {} more entries not shown; narrow it with path, max_depth or glob]",
+            total - lines.len()
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
 /// list_dir tool.
 pub struct ListDir;
 
@@ -160,40 +375,54 @@ impl Tool for ListDir {
     }
 
     fn description(&self) -> &str {
-        "List directory contents in the workspace. Path optional (default workspace root)."
+        "List directory contents in the workspace with sizes (directories end in / with their \
+         entry count). Path optional (default workspace root). Set recursive (or max_depth) \
+         to see a whole tree in one call, and glob (e.g. \"*.md\", \"Daily log/*.md\") to list \
+         only matching files."
     }
 
     fn parameters(&self) -> Value {
         serde_json::json!({
             "type": "object",
             "properties": {
-                "path": { "type": "string", "description": "Path relative to workspace (optional)" }
+                "path": { "type": "string", "description": "Path relative to workspace (optional)" },
+                "recursive": { "type": "boolean", "description": "Descend into subdirectories (default false)" },
+                "max_depth": { "type": "integer", "description": "Levels to show; 1 is the directory itself (default 1, or 10 when recursive)" },
+                "glob": { "type": "string", "description": "Only files matching this pattern (name, or path below 'path' if it contains /)" }
             }
         })
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
         let args = args.clone();
         let ctx = ctx.clone();
         Box::pin(async move {
             let path = get_optional_string(&args, "path").unwrap_or_else(|| ".".to_string());
+            let recursive = args
+                .get("recursive")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let depth = args
+                .get("max_depth")
+                .and_then(Value::as_u64)
+                .map(|d| (d as usize).clamp(1, TREE_MAX_DEPTH))
+                .unwrap_or(if recursive { TREE_MAX_DEPTH } else { 1 });
+            let glob = get_optional_string(&args, "glob").filter(|g| !g.trim().is_empty());
             let resolved =
                 match resolve_path(&path, &ctx.workspace, ctx.restrict_to_workspace).await {
                     Ok(p) => p,
                     Err(e) => return ToolResult::error(e),
                 };
-            match tokio::fs::read_dir(&resolved).await {
-                Ok(mut entries) => {
-                    let mut names = Vec::new();
-                    while let Ok(Some(entry)) = entries.next_entry().await {
-                        if let Ok(name) = entry.file_name().into_string() {
-                            names.push(name);
-                        }
-                    }
-                    names.sort();
-                    ToolResult::ok(names.join("\n"))
-                }
-                Err(e) => ToolResult::error(e.to_string()),
+            match tokio::task::spawn_blocking(move || list_tree(&resolved, depth, glob.as_deref()))
+                .await
+            {
+                Ok(Ok(listing)) => ToolResult::ok(listing),
+                Ok(Err(e)) => ToolResult::error(e),
+                Err(e) => ToolResult::error(format!("list task error: {e}")),
             }
         })
     }
@@ -260,6 +489,168 @@ impl Tool for EditFile {
     }
 }
 
+/// One `multi_edit` replacement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub old_text: String,
+    pub new_text: String,
+    /// 1-based occurrence to replace; 0 replaces all.
+    pub occurrence: usize,
+}
+
+fn parse_edits(args: &Value) -> Result<Vec<TextEdit>, String> {
+    let edits = args
+        .get("edits")
+        .and_then(Value::as_array)
+        .filter(|e| !e.is_empty())
+        .ok_or("missing or invalid 'edits' (non-empty array)")?;
+    edits
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            let field =
+                |key: &str| get_string(e, key).map_err(|err| format!("edit {}: {err}", i + 1));
+            let occurrence = match e.get("occurrence") {
+                None | Some(Value::Null) => 1,
+                Some(v) => v.as_u64().ok_or_else(|| {
+                    format!("edit {}: 'occurrence' must be an integer >= 0", i + 1)
+                })? as usize,
+            };
+            Ok(TextEdit {
+                old_text: field("old_text")?,
+                new_text: field("new_text")?,
+                occurrence,
+            })
+        })
+        .collect()
+}
+
+/// Apply `edits` in order, each to the result of the previous; returns the new content and
+/// the number of replacements. Fails on the first edit that does not match.
+pub fn apply_edits(content: &str, edits: &[TextEdit]) -> Result<(String, usize), String> {
+    let mut content = content.to_string();
+    let mut replaced = 0;
+    for (i, edit) in edits.iter().enumerate() {
+        if edit.old_text.is_empty() {
+            return Err(format!("edit {}: old_text is empty", i + 1));
+        }
+        let found = content.matches(edit.old_text.as_str()).count();
+        if found == 0 {
+            return Err(format!("edit {}: old_text not found in file", i + 1));
+        }
+        if edit.occurrence == 0 {
+            content = content.replace(&edit.old_text, &edit.new_text);
+            replaced += found;
+            continue;
+        }
+        let Some((at, _)) = content
+            .match_indices(edit.old_text.as_str())
+            .nth(edit.occurrence - 1)
+        else {
+            return Err(format!(
+                "edit {}: occurrence {} requested but old_text appears {found} time(s)",
+                i + 1,
+                edit.occurrence
+            ));
+        };
+        content.replace_range(at..at + edit.old_text.len(), &edit.new_text);
+        replaced += 1;
+    }
+    Ok((content, replaced))
+}
+
+/// multi_edit tool (several replacements in one file, all or nothing).
+pub struct MultiEdit;
+
+impl Tool for MultiEdit {
+    fn name(&self) -> &str {
+        "multi_edit"
+    }
+
+    fn description(&self) -> &str {
+        "Apply several text replacements to one file at once, in order. If any old_text is not \
+         found nothing is changed. Use dry_run to get the resulting diff without writing. \
+         Prefer this over repeated edit_file calls."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Path relative to workspace" },
+                "edits": {
+                    "type": "array",
+                    "description": "Replacements, applied in order (each sees the previous result)",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "old_text": { "type": "string", "description": "Exact text to replace" },
+                            "new_text": { "type": "string", "description": "Replacement text" },
+                            "occurrence": { "type": "integer", "description": "Which match to replace, 1-based (default 1); 0 replaces all" }
+                        },
+                        "required": ["old_text", "new_text"]
+                    }
+                },
+                "dry_run": { "type": "boolean", "description": "Return the diff without writing (default false)" }
+            },
+            "required": ["path", "edits"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let args = args.clone();
+        let ctx = ctx.clone();
+        Box::pin(async move {
+            let path = match get_string(&args, "path") {
+                Ok(p) => p,
+                Err(e) => return ToolResult::error(e),
+            };
+            let edits = match parse_edits(&args) {
+                Ok(e) => e,
+                Err(e) => return ToolResult::error(e),
+            };
+            let dry_run = args
+                .get("dry_run")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let resolved =
+                match resolve_path(&path, &ctx.workspace, ctx.restrict_to_workspace).await {
+                    Ok(p) => p,
+                    Err(e) => return ToolResult::error(e),
+                };
+            let content = match tokio::fs::read_to_string(&resolved).await {
+                Ok(c) => c,
+                Err(e) => return ToolResult::error(e.to_string()),
+            };
+            let (new_content, replaced) = match apply_edits(&content, &edits) {
+                Ok(r) => r,
+                Err(e) => return ToolResult::error(format!("{e}; no changes made")),
+            };
+            if dry_run {
+                let diff = unified_diff(
+                    &content,
+                    &new_content,
+                    &format!("a/{path}"),
+                    &format!("b/{path}"),
+                    3,
+                );
+                return ToolResult::ok(if diff.is_empty() {
+                    "No differences.".to_string()
+                } else {
+                    diff
+                });
+            }
+            match tokio::fs::write(&resolved, new_content).await {
+                Ok(()) => ToolResult::ok(format!(
+                    "edited ({} edit(s), {replaced} replacement(s))",
+                    edits.len()
+                )),
+                Err(e) => ToolResult::error(e.to_string()),
+            }
+        })
+    }
+}
+
 /// append_file tool.
 pub struct AppendFile;
 
@@ -325,6 +716,183 @@ impl Tool for AppendFile {
     }
 }
 
+/// copy_file tool (copy without passing the contents through the model, e.g. from a template).
+pub struct CopyFile;
+
+impl Tool for CopyFile {
+    fn name(&self) -> &str {
+        "copy_file"
+    }
+
+    fn description(&self) -> &str {
+        "Copy a file within the workspace (e.g. a template into a new note). Creates parent \
+         directories. Fails if the destination exists unless overwrite is true."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "source": { "type": "string", "description": "File to copy, relative to workspace" },
+                "destination": { "type": "string", "description": "New path, relative to workspace" },
+                "overwrite": { "type": "boolean", "description": "Replace an existing destination (default false)" }
+            },
+            "required": ["source", "destination"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let args = args.clone();
+        let ctx = ctx.clone();
+        Box::pin(async move {
+            let (source, destination) = match (
+                get_string(&args, "source"),
+                get_string(&args, "destination"),
+            ) {
+                (Ok(s), Ok(d)) => (s, d),
+                (Err(e), _) | (_, Err(e)) => return ToolResult::error(e),
+            };
+            let overwrite = args
+                .get("overwrite")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let from = match resolve_path(&source, &ctx.workspace, ctx.restrict_to_workspace).await
+            {
+                Ok(p) => p,
+                Err(e) => return ToolResult::error(e),
+            };
+            let to =
+                match resolve_path(&destination, &ctx.workspace, ctx.restrict_to_workspace).await {
+                    Ok(p) => p,
+                    Err(e) => return ToolResult::error(e),
+                };
+            match tokio::fs::metadata(&from).await {
+                Ok(m) if m.is_file() => {}
+                Ok(_) => return ToolResult::error(format!("{source} is not a file")),
+                Err(e) => return ToolResult::error(format!("{source}: {e}")),
+            }
+            if from == to {
+                return ToolResult::error("source and destination are the same file");
+            }
+            if !overwrite && tokio::fs::try_exists(&to).await.unwrap_or(false) {
+                return ToolResult::error(format!(
+                    "{destination} already exists (pass overwrite: true to replace it)"
+                ));
+            }
+            if let Some(parent) = to.parent()
+                && let Err(e) = tokio::fs::create_dir_all(parent).await
+            {
+                return ToolResult::error(e.to_string());
+            }
+            match tokio::fs::copy(&from, &to).await {
+                Ok(bytes) => {
+                    ToolResult::ok(format!("copied {source} to {destination} ({bytes} bytes)"))
+                }
+                Err(e) => ToolResult::error(e.to_string()),
+            }
+        })
+    }
+}
+
+/// Size, modification time and (for text) line count of `path`, without loading it whole
+/// (blocking; call from `spawn_blocking`).
+pub fn file_stats(path: &Path) -> std::io::Result<String> {
+    use std::io::Read;
+
+    let meta = std::fs::metadata(path)?;
+    let modified = meta
+        .modified()
+        .ok()
+        .map(chrono::DateTime::<chrono::Utc>::from)
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    if meta.is_dir() {
+        let entries = std::fs::read_dir(path)?.count();
+        return Ok(format!(
+            "type: directory\nentries: {entries}\nmodified: {modified}"
+        ));
+    }
+
+    let mut file = std::fs::File::open(path)?;
+    let mut buf = [0u8; 64 * 1024];
+    let (mut lines, mut binary, mut last) = (0u64, false, b'\n');
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        let chunk = &buf[..n];
+        if chunk.contains(&0) {
+            binary = true;
+            break;
+        }
+        lines += chunk.iter().filter(|&&b| b == b'\n').count() as u64;
+        last = chunk[n - 1];
+    }
+    let mut out = format!(
+        "type: {}\nsize: {} ({} bytes)\nmodified: {modified}",
+        if binary { "binary file" } else { "text file" },
+        format_bytes(meta.len()),
+        meta.len()
+    );
+    if !binary {
+        // A final line without a trailing newline still counts.
+        let lines = lines + u64::from(last != b'\n');
+        out.push_str(&format!("\nlines: {lines}"));
+    }
+    Ok(out)
+}
+
+/// stat_file tool (size, mtime and line count before deciding how to read a file).
+pub struct StatFile;
+
+impl Tool for StatFile {
+    fn name(&self) -> &str {
+        "stat_file"
+    }
+
+    fn description(&self) -> &str {
+        "Get a file's size, last modified time and line count without reading it. Use before \
+         read_file on notes that may be large, to decide whether to read it whole, read a line \
+         range, or grep it."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Path relative to workspace" }
+            },
+            "required": ["path"]
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let args = args.clone();
+        let ctx = ctx.clone();
+        Box::pin(async move {
+            let path = match get_string(&args, "path") {
+                Ok(p) => p,
+                Err(e) => return ToolResult::error(e),
+            };
+            let resolved =
+                match resolve_path(&path, &ctx.workspace, ctx.restrict_to_workspace).await {
+                    Ok(p) => p,
+                    Err(e) => return ToolResult::error(e),
+                };
+            match tokio::task::spawn_blocking(move || file_stats(&resolved)).await {
+                Ok(Ok(stats)) => ToolResult::ok(format!("path: {path}\n{stats}")),
+                Ok(Err(e)) => ToolResult::error(format!("{path}: {e}")),
+                Err(e) => ToolResult::error(format!("stat task error: {e}")),
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -341,14 +909,7 @@ mod tests {
         let dir = std::env::temp_dir();
         let f = dir.join("icrab_test_read_file.txt");
         let _ = tokio::fs::write(&f, "hello").await;
-        let ctx = ToolCtx {
-            workspace: dir.clone(),
-            restrict_to_workspace: true,
-            chat_id: None,
-            channel: None,
-            outbound_tx: None,
-            delivered: Default::default(),
-        };
+        let ctx = ToolCtx::for_tests(dir.clone());
         let rel = f.strip_prefix(&dir).unwrap().to_str().unwrap();
         let args = serde_json::json!({ "path": rel });
         let res = ReadFile.execute(&ctx, &args).await;
@@ -356,4 +917,132 @@ mod tests {
         assert_eq!(res.for_llm, "hello");
         let _ = tokio::fs::remove_file(&f).await;
     }
+
+    #[test]
+    fn file_stats_counts_lines_and_spots_binary() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let note = dir.path().join("note.md");
+        std::fs::write(&note, "one\ntwo\nthree").unwrap();
+        let stats = file_stats(&note).unwrap();
+        assert!(stats.starts_with("type: text file\nsize: 13 B (13 bytes)\nmodified: "));
+        assert!(stats.ends_with("\nlines: 3"), "{stats}");
+
+        std::fs::write(&note, "").unwrap();
+        assert!(file_stats(&note).unwrap().ends_with("\nlines: 0"));
+
+        let png = dir.path().join("chart.png");
+        std::fs::write(&png, [0x89, b'P', b'N', b'G', 0, 0, 1]).unwrap();
+        let stats = file_stats(&png).unwrap();
+        assert!(stats.starts_with("type: binary file"));
+        assert!(!stats.contains("lines"));
+
+        assert!(
+            file_stats(dir.path())
+                .unwrap()
+                .starts_with("type: directory\nentries: 2")
+        );
+    }
+
+    #[test]
+    fn apply_edits_in_order_or_not_at_all() {
+        let edit = |old: &str, new: &str, occurrence| TextEdit {
+            old_text: old.to_string(),
+            new_text: new.to_string(),
+            occurrence,
+        };
+        let note = "- [ ] milk\n- [ ] eggs\n- [ ] milk\n";
+        assert_eq!(
+            apply_edits(
+                note,
+                &[
+                    edit("milk", "oat milk", 2),
+                    edit("- [ ] eggs", "- [x] eggs", 1)
+                ]
+            )
+            .unwrap(),
+            ("- [ ] milk\n- [x] eggs\n- [ ] oat milk\n".to_string(), 2)
+        );
+        assert_eq!(
+            apply_edits(note, &[edit("- [ ]", "- [x]", 0)]).unwrap(),
+            ("- [x] milk\n- [x] eggs\n- [x] milk\n".to_string(), 3)
+        );
+        // Later edits see earlier results.
+        assert_eq!(
+            apply_edits(note, &[edit("eggs", "bread", 1), edit("bread", "rye", 1)])
+                .unwrap()
+                .0,
+            "- [ ] milk\n- [ ] rye\n- [ ] milk\n"
+        );
+        assert_eq!(
+            apply_edits(note, &[edit("milk", "x", 1), edit("tea", "y", 1)]).unwrap_err(),
+            "edit 2: old_text not found in file"
+        );
+        assert!(
+            apply_edits(note, &[edit("milk", "x", 3)])
+                .unwrap_err()
+                .contains("appears 2 time(s)")
+        );
+    }
+
+    #[test]
+    fn read_lines_pages_large_files() {
+        assert_eq!(read_lines("a\nb\n", None, None).unwrap(), "a\nb\n");
+        assert_eq!(read_lines("", None, None).unwrap(), "");
+        assert_eq!(
+            read_lines("a\nb\nc\nd", Some(2), Some(3)).unwrap(),
+            "b\nc\n\n[Showing lines 2-3 of 4. Continue with start_line=4.]"
+        );
+        assert_eq!(
+            read_lines("a\nb\nc\nd", Some(4), None).unwrap(),
+            "d\n\n[Showing lines 4-4 of 4.]"
+        );
+        assert!(read_lines("a\n", Some(5), None).is_err());
+
+        let log: String = (1..=5000).map(|i| format!("line {i}\n")).collect();
+        let page = read_lines(&log, None, None).unwrap();
+        assert!(page.starts_with("line 1\n"));
+        assert!(page.ends_with(
+            "line 2000\n\n[Showing lines 1-2000 of 5000. Continue with start_line=2001.]"
+        ));
+
+        let wide = format!("{}\nnext\n", "x".repeat(READ_MAX_CHARS + 10));
+        let page = read_lines(&wide, None, None).unwrap();
+        assert!(page.ends_with(&format!(
+            "[Line 1 of 2 is {} bytes; showing the first {READ_MAX_CHARS}.]",
+            READ_MAX_CHARS + 11
+        )));
+    }
+
+    #[test]
+    fn list_tree_annotates_and_filters() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("Daily log/2026")).unwrap();
+        std::fs::create_dir_all(root.join(".obsidian")).unwrap();
+        std::fs::write(root.join(".obsidian/app.json"), "{}").unwrap();
+        std::fs::write(root.join("Daily log/2026/10-16.md"), "today").unwrap();
+        std::fs::write(root.join("Daily log/notes.txt"), "x").unwrap();
+        std::fs::write(root.join("Index.md"), "# Index\n").unwrap();
+
+        assert_eq!(
+            list_tree(root, 1, None).unwrap(),
+            ".obsidian/ (1 entry)\nDaily log/ (2 entries)\nIndex.md (8 B)"
+        );
+        assert_eq!(
+            list_tree(root, 10, None).unwrap(),
+            ".obsidian/ (1 entry)\nDaily log/ (2 entries)\n  2026/ (1 entry)\n    10-16.md (5 B)\n  notes.txt (1 B)\nIndex.md (8 B)"
+        );
+        assert_eq!(
+            list_tree(root, 10, Some("*.md")).unwrap(),
+            "Daily log/ (2 entries)\n  2026/ (1 entry)\n    10-16.md (5 B)\nIndex.md (8 B)"
+        );
+        assert_eq!(
+            list_tree(root, 10, Some("Daily log/**/*.md")).unwrap(),
+            "Daily log/ (2 entries)\n  2026/ (1 entry)\n    10-16.md (5 B)"
+        );
+        assert_eq!(
+            list_tree(root, 1, Some("*.pdf")).unwrap(),
+            "No files match '*.pdf'."
+        );
+    }
 }
@@ -0,0 +1,192 @@
+//! `forget` tool and `/forget` command: delete the last N messages of a conversation from the
+//! Telegram chat (deleteMessages) and from `chat_history`, e.g. after pasting a password.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::memory::db::BrainDb;
+use crate::telegram::{self, MessageLog, OutboundKind, OutboundMsg};
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Default: the user's last message and the reply to it.
+pub const DEFAULT_FORGET_COUNT: usize = 2;
+const MAX_FORGET_COUNT: usize = 50;
+
+/// What a forget removed: Telegram messages queued for deletion and chat_history rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Forgotten {
+    pub telegram: usize,
+    pub history: usize,
+}
+
+/// Forget the last `count` messages of a chat/topic. `extra_ids` are deleted from Telegram as
+/// well (e.g. the `/forget` command message itself).
+#[allow(clippy::too_many_arguments)]
+pub async fn forget_recent(
+    db: &Arc<BrainDb>,
+    message_log: &MessageLog,
+    outbound_tx: Option<&mpsc::Sender<OutboundMsg>>,
+    channel: &str,
+    chat_id: i64,
+    thread_id: Option<i64>,
+    count: usize,
+    extra_ids: &[i64],
+) -> Result<Forgotten, String> {
+    let count = count.clamp(1, MAX_FORGET_COUNT);
+    let mut ids = message_log.take_recent(chat_id, thread_id, count);
+    ids.extend_from_slice(extra_ids);
+    let telegram = ids.len();
+    if let (Some(tx), false) = (outbound_tx, ids.is_empty()) {
+        tx.try_send(OutboundMsg {
+            chat_id,
+            text: String::new(),
+            channel: channel.to_string(),
+            kind: OutboundKind::Delete(ids),
+            thread_id,
+            disable_notification: false,
+        })
+        .map_err(|e| format!("queue delete: {e}"))?;
+    }
+
+    let db = Arc::clone(db);
+    let key = telegram::session_key(chat_id, thread_id);
+    let history = tokio::task::spawn_blocking(move || db.delete_recent_messages(&key, count))
+        .await
+        .map_err(|e| format!("forget task error: {e}"))?
+        .map_err(|e| e.to_string())?;
+    Ok(Forgotten { telegram, history })
+}
+
+/// Tool wrapper around [`forget_recent`] for the current chat.
+pub struct ForgetTool {
+    message_log: Arc<MessageLog>,
+    db: Arc<BrainDb>,
+}
+
+impl ForgetTool {
+    pub fn new(message_log: Arc<MessageLog>, db: Arc<BrainDb>) -> Self {
+        Self { message_log, db }
+    }
+}
+
+impl Tool for ForgetTool {
+    fn name(&self) -> &str {
+        "forget"
+    }
+
+    fn description(&self) -> &str {
+        "Delete the last N messages of this conversation (before the current request) from \
+         the Telegram chat and from stored chat history. Use when the user shared something \
+         sensitive (password, token) by mistake."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {
+                    "type": "integer",
+                    "description": "How many recent messages to delete (user and assistant, default 2, max 50)"
+                }
+            }
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let message_log = Arc::clone(&self.message_log);
+        let db = Arc::clone(&self.db);
+        let args = args.clone();
+        let ctx = ctx.clone();
+
+        Box::pin(async move {
+            let Some(chat_id) = ctx.chat_id else {
+                return ToolResult::error("no chat_id (forget unavailable)");
+            };
+            let count = args
+                .get("count")
+                .and_then(Value::as_u64)
+                .map(|n| n as usize)
+                .unwrap_or(DEFAULT_FORGET_COUNT);
+            let channel = ctx.channel.as_deref().unwrap_or("telegram");
+            match forget_recent(
+                &db,
+                &message_log,
+                ctx.outbound_tx.as_deref(),
+                channel,
+                chat_id,
+                ctx.thread_id,
+                count,
+                &[],
+            )
+            .await
+            {
+                Ok(f) => ToolResult::ok(format!(
+                    "Deleted {} Telegram message(s) and {} stored history row(s).",
+                    f.telegram, f.history
+                )),
+                Err(e) => ToolResult::error(format!("forget failed: {e}")),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::db::StoredMessage;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn forget_queues_delete_and_prunes_history() {
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let sid = db.get_or_create_session_id("7").unwrap();
+        let msg = |role: &str, content: &str| StoredMessage {
+            role: role.into(),
+            content: content.into(),
+            tool_call_id: None,
+            tool_calls: None,
+        };
+        db.append_session(
+            "7",
+            &sid,
+            &[
+                msg("user", "a"),
+                msg("user", "secret"),
+                msg("assistant", "ok"),
+            ],
+            "",
+        )
+        .unwrap();
+        let log = MessageLog::default();
+        for id in [10, 11, 12] {
+            log.record(7, None, id);
+        }
+        let (tx, mut rx) = mpsc::channel(4);
+
+        let f = forget_recent(&db, &log, Some(&tx), "telegram", 7, None, 2, &[13])
+            .await
+            .unwrap();
+        assert_eq!(
+            f,
+            Forgotten {
+                telegram: 3,
+                history: 2
+            }
+        );
+
+        let out = rx.try_recv().unwrap();
+        assert_eq!(out.kind, OutboundKind::Delete(vec![12, 11, 13]));
+        let (left, _) = db.load_session("7", &sid).unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(log.take_recent(7, None, 5), vec![10]);
+    }
+}
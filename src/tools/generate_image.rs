@@ -0,0 +1,499 @@
+//! generate_image tool: create an image from a prompt with the OpenAI Images API or Stability AI,
+//! save it under `Generated/` in the workspace and send it inline to the chat.
+//!
+//! Configured by `[tools.image]`; the tool is registered only when an API key is set.
+
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::config::ImageConfig;
+use crate::llm::base64_decode;
+use crate::telegram::{OutboundKind, OutboundMsg, multipart_fields};
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const OPENAI_API_BASE: &str = "https://api.openai.com/v1";
+const OPENAI_MODEL: &str = "gpt-image-1";
+const STABILITY_API_BASE: &str = "https://api.stability.ai";
+const STABILITY_MODEL: &str = "core";
+/// Folder for generated images, relative to the workspace.
+const OUTPUT_DIR: &str = "Generated";
+/// Generation often takes longer than the web client's timeout.
+const GENERATE_TIMEOUT_SECS: u64 = 180;
+const MAX_PROMPT_CHARS: usize = 4000;
+/// Words of the prompt used in the default file name.
+const NAME_WORDS: usize = 6;
+
+/// Image shape requested from the provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aspect {
+    Square,
+    Landscape,
+    Portrait,
+}
+
+impl Aspect {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "square" => Ok(Self::Square),
+            "landscape" => Ok(Self::Landscape),
+            "portrait" => Ok(Self::Portrait),
+            other => Err(format!(
+                "unknown aspect '{other}' (use square, landscape or portrait)"
+            )),
+        }
+    }
+
+    /// OpenAI `size`; DALL·E 3 has its own wide sizes and DALL·E 2 only squares.
+    fn openai_size(self, model: &str) -> &'static str {
+        match (self, model) {
+            (Self::Square, _) => "1024x1024",
+            (_, m) if m.starts_with("dall-e-2") => "1024x1024",
+            (Self::Landscape, m) if m.starts_with("dall-e-3") => "1792x1024",
+            (Self::Portrait, m) if m.starts_with("dall-e-3") => "1024x1792",
+            (Self::Landscape, _) => "1536x1024",
+            (Self::Portrait, _) => "1024x1536",
+        }
+    }
+
+    /// Stability `aspect_ratio`.
+    fn stability_ratio(self) -> &'static str {
+        match self {
+            Self::Square => "1:1",
+            Self::Landscape => "3:2",
+            Self::Portrait => "2:3",
+        }
+    }
+}
+
+/// Which image API to call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Provider {
+    OpenAi,
+    Stability,
+}
+
+/// A lowercase, hyphenated file stem from the first words of `prompt`.
+fn file_stem(prompt: &str) -> String {
+    let words: Vec<String> = prompt
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .take(NAME_WORDS)
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        "image".to_string()
+    } else {
+        words.join("-")
+    }
+}
+
+pub struct GenerateImageTool {
+    client: Client,
+    provider: Provider,
+    api_key: String,
+    api_base: String,
+    model: String,
+}
+
+impl GenerateImageTool {
+    /// None without `[tools.image]` or its API key.
+    pub fn from_config(client: Client, config: Option<&ImageConfig>) -> Option<Self> {
+        let config = config?;
+        let api_key = config
+            .api_key
+            .as_deref()
+            .map(str::trim)
+            .filter(|k| !k.is_empty())?;
+        let provider = match config.provider.as_deref().map(str::trim) {
+            Some("stability") => Provider::Stability,
+            _ => Provider::OpenAi,
+        };
+        let (default_base, default_model) = match provider {
+            Provider::OpenAi => (OPENAI_API_BASE, OPENAI_MODEL),
+            Provider::Stability => (STABILITY_API_BASE, STABILITY_MODEL),
+        };
+        let pick = |o: &Option<String>, default: &str| {
+            o.as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .unwrap_or(default)
+                .to_string()
+        };
+        Some(Self {
+            client,
+            provider,
+            api_key: api_key.to_string(),
+            api_base: pick(&config.api_base, default_base)
+                .trim_end_matches('/')
+                .to_string(),
+            model: pick(&config.model, default_model),
+        })
+    }
+
+    /// OpenAI Images API: returns the PNG and the revised prompt, if the model rewrote it.
+    async fn openai(
+        &self,
+        prompt: &str,
+        aspect: Aspect,
+    ) -> Result<(Vec<u8>, Option<String>), String> {
+        let mut payload = serde_json::json!({
+            "model": self.model,
+            "prompt": prompt,
+            "n": 1,
+            "size": aspect.openai_size(&self.model),
+        });
+        // GPT image models always answer in base64 and reject the parameter.
+        if self.model.starts_with("dall-e") {
+            payload["response_format"] = "b64_json".into();
+        }
+        let res = self
+            .client
+            .post(format!("{}/images/generations", self.api_base))
+            .bearer_auth(&self.api_key)
+            .timeout(Duration::from_secs(GENERATE_TIMEOUT_SECS))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| format!("image request failed: {e}"))?;
+        let status = res.status();
+        let body: Value = res.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = body
+                .pointer("/error/message")
+                .and_then(Value::as_str)
+                .unwrap_or("");
+            return Err(format!("image API error: HTTP {status}: {message}"));
+        }
+        let image = body
+            .pointer("/data/0")
+            .ok_or("image API returned no image")?;
+        let revised = image
+            .get("revised_prompt")
+            .and_then(Value::as_str)
+            .filter(|p| *p != prompt)
+            .map(String::from);
+        if let Some(b64) = image.get("b64_json").and_then(Value::as_str) {
+            return Ok((base64_decode(b64.as_bytes()), revised));
+        }
+        let url = image
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or("image API returned no image")?;
+        let bytes = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("image download failed: {e}"))?
+            .bytes()
+            .await
+            .map_err(|e| format!("image download failed: {e}"))?;
+        Ok((bytes.to_vec(), revised))
+    }
+
+    /// Stability AI Stable Image: the PNG comes back as the response body.
+    async fn stability(&self, prompt: &str, aspect: Aspect) -> Result<Vec<u8>, String> {
+        let (content_type, body) = multipart_fields(&[
+            ("prompt", prompt.to_string()),
+            ("aspect_ratio", aspect.stability_ratio().to_string()),
+            ("output_format", "png".to_string()),
+        ]);
+        let res = self
+            .client
+            .post(format!(
+                "{}/v2beta/stable-image/generate/{}",
+                self.api_base, self.model
+            ))
+            .bearer_auth(&self.api_key)
+            .header("Accept", "image/*")
+            .header("Content-Type", content_type)
+            .timeout(Duration::from_secs(GENERATE_TIMEOUT_SECS))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("image request failed: {e}"))?;
+        let status = res.status();
+        if !status.is_success() {
+            let body: Value = res.json().await.unwrap_or(Value::Null);
+            let message = body
+                .get("errors")
+                .and_then(Value::as_array)
+                .map(|errs| {
+                    errs.iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                })
+                .unwrap_or_default();
+            return Err(format!("image API error: HTTP {status}: {message}"));
+        }
+        if res
+            .headers()
+            .get("finish-reason")
+            .is_some_and(|r| r == "CONTENT_FILTERED")
+        {
+            return Err("the image was blocked by the provider's content filter".into());
+        }
+        res.bytes()
+            .await
+            .map(|b| b.to_vec())
+            .map_err(|e| format!("image download failed: {e}"))
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let prompt = args
+            .get("prompt")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .ok_or("prompt is required")?;
+        if prompt.chars().count() > MAX_PROMPT_CHARS {
+            return Err(format!(
+                "prompt is longer than {MAX_PROMPT_CHARS} characters"
+            ));
+        }
+        let aspect = Aspect::parse(
+            args.get("aspect")
+                .and_then(Value::as_str)
+                .unwrap_or("square"),
+        )?;
+
+        let (png, revised) = match self.provider {
+            Provider::OpenAi => self.openai(prompt, aspect).await?,
+            Provider::Stability => (self.stability(prompt, aspect).await?, None),
+        };
+        if png.is_empty() {
+            return Err("image API returned an empty image".into());
+        }
+
+        let stem = match args.get("name").and_then(Value::as_str) {
+            Some(name) if !name.trim().is_empty() => file_stem(name),
+            _ => file_stem(prompt),
+        };
+        let rel = format!(
+            "{OUTPUT_DIR}/{stem}-{}.png",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        );
+        let out = resolve_path(&rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        if let Some(dir) = out.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| format!("create {}: {e}", dir.display()))?;
+        }
+        tokio::fs::write(&out, &png)
+            .await
+            .map_err(|e| format!("write {rel}: {e}"))?;
+        let revised = revised
+            .map(|p| format!(" The model revised the prompt to: {p}"))
+            .unwrap_or_default();
+
+        let (Some(tx), Some(chat_id)) = (&ctx.outbound_tx, ctx.chat_id) else {
+            return Ok(format!(
+                "Saved {rel} (no chat to send it to; use send_photo later).{revised}"
+            ));
+        };
+        let caption = args
+            .get("caption")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        let msg = OutboundMsg {
+            chat_id,
+            text: caption,
+            channel: ctx
+                .channel
+                .clone()
+                .unwrap_or_else(|| "telegram".to_string()),
+            kind: OutboundKind::Photo(out),
+            thread_id: ctx.thread_id,
+            disable_notification: false,
+        };
+        tx.try_send(msg).map_err(|e| e.to_string())?;
+        ctx.delivered.store(true, Ordering::Relaxed);
+        Ok(format!("sent image (saved to {rel}).{revised}"))
+    }
+}
+
+impl Tool for GenerateImageTool {
+    fn name(&self) -> &str {
+        "generate_image"
+    }
+
+    fn description(&self) -> &str {
+        "Generate an image from a text prompt (illustrations, simple diagrams, fun pictures), save \
+         it under Generated/ in the workspace and send it inline to the current chat. Describe \
+         the picture in detail; image models render text poorly."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "prompt": { "type": "string", "description": "Description of the image" },
+                "aspect": { "type": "string", "enum": ["square", "landscape", "portrait"], "description": "Default square" },
+                "caption": { "type": "string", "description": "Optional caption sent with the image" },
+                "name": { "type": "string", "description": "Short file name (default from the prompt's first words)" }
+            },
+            "required": ["prompt"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) if ctx.delivered.load(Ordering::Relaxed) => ToolResult::silent(out),
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::Path;
+    use std::sync::Arc;
+    use wiremock::matchers::{body_json, body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::llm::base64_encode;
+    use crate::tools::web::web_client;
+
+    fn ctx(workspace: &Path, tx: Option<tokio::sync::mpsc::Sender<OutboundMsg>>) -> ToolCtx {
+        ToolCtx {
+            chat_id: tx.as_ref().map(|_| 42),
+            outbound_tx: tx.map(Arc::new),
+            ..ToolCtx::for_tests(workspace)
+        }
+    }
+
+    fn tool(provider: &str, api_base: &str, model: Option<&str>) -> GenerateImageTool {
+        let config = ImageConfig {
+            provider: Some(provider.into()),
+            api_key: Some("sk-test".into()),
+            api_base: Some(api_base.into()),
+            model: model.map(String::from),
+        };
+        GenerateImageTool::from_config(web_client().unwrap(), Some(&config)).unwrap()
+    }
+
+    fn saved_images(workspace: &Path) -> Vec<String> {
+        std::fs::read_dir(workspace.join(OUTPUT_DIR))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn config_and_names() {
+        assert!(GenerateImageTool::from_config(web_client().unwrap(), None).is_none());
+        let no_key = ImageConfig::default();
+        assert!(GenerateImageTool::from_config(web_client().unwrap(), Some(&no_key)).is_none());
+        let t = tool("stability", "https://api.stability.ai/", None);
+        assert_eq!(t.model, "core");
+        assert_eq!(t.api_base, "https://api.stability.ai");
+
+        assert_eq!(Aspect::Landscape.openai_size("gpt-image-1"), "1536x1024");
+        assert_eq!(Aspect::Portrait.openai_size("dall-e-3"), "1024x1792");
+        assert_eq!(Aspect::Portrait.openai_size("dall-e-2"), "1024x1024");
+        assert_eq!(
+            file_stem("A cosy cabin, in the snow at night with stars"),
+            "a-cosy-cabin-in-the-snow"
+        );
+        assert_eq!(file_stem("!!"), "image");
+    }
+
+    #[tokio::test]
+    async fn openai_image_is_saved_and_sent() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/images/generations"))
+            .and(header("authorization", "Bearer sk-test"))
+            .and(body_json(json!({
+                "model": "gpt-image-1",
+                "prompt": "a red fox",
+                "n": 1,
+                "size": "1536x1024"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": [{ "b64_json": base64_encode(b"\x89PNG fox") }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let ctx = ctx(dir.path(), Some(tx));
+
+        let res = tool("openai", &server.uri(), None)
+            .execute(&ctx, &json!({"prompt": "a red fox", "aspect": "landscape"}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm
+                .starts_with("sent image (saved to Generated/a-red-fox-")
+        );
+        let msg = rx.try_recv().unwrap();
+        let OutboundKind::Photo(path) = msg.kind else {
+            panic!("expected a photo");
+        };
+        assert_eq!(std::fs::read(path).unwrap(), b"\x89PNG fox");
+    }
+
+    #[tokio::test]
+    async fn stability_image_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2beta/stable-image/generate/core"))
+            .and(body_string_contains("name=\"aspect_ratio\"\r\n\r\n2:3"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"\x89PNG owl".to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v2beta/stable-image/generate/ultra"))
+            .respond_with(
+                ResponseTemplate::new(402).set_body_json(json!({ "errors": ["out of credits"] })),
+            )
+            .mount(&server)
+            .await;
+        let ctx = ctx(dir.path(), None);
+
+        let res = tool("stability", &server.uri(), None)
+            .execute(
+                &ctx,
+                &json!({"prompt": "an owl", "aspect": "portrait", "name": "Owl"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm.starts_with("Saved Generated/owl-"),
+            "{}",
+            res.for_llm
+        );
+        assert_eq!(saved_images(dir.path()).len(), 1);
+
+        let res = tool("stability", &server.uri(), Some("ultra"))
+            .execute(&ctx, &json!({"prompt": "an owl"}))
+            .await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("out of credits"), "{}", res.for_llm);
+        let res = tool("stability", &server.uri(), None)
+            .execute(&ctx, &json!({"prompt": "x", "aspect": "wide"}))
+            .await;
+        assert!(res.is_error);
+    }
+}
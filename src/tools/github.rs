@@ -0,0 +1,624 @@
+//! `github` tool: notifications, review requests, issues and pull-request status from the
+//! GitHub REST API with a personal access token from `[tools.github]` — enough for a morning
+//! briefing to nag about pending reviews.
+//!
+//! Issues may only be created in the configured `repos`; everything else is read-only.
+
+use reqwest::{Client, Method, RequestBuilder, Url};
+use serde_json::Value;
+
+use crate::config::GithubConfig;
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+pub(crate) const DEFAULT_API_URL: &str = "https://api.github.com";
+const WEB_URL: &str = "https://github.com";
+pub(crate) const API_VERSION: &str = "2022-11-28";
+const DEFAULT_LIMIT: u64 = 20;
+const MAX_LIMIT: u64 = 50;
+
+/// "owner/name" with GitHub's allowed characters.
+fn valid_repo(repo: &str) -> bool {
+    let mut parts = repo.split('/');
+    let ok = |p: Option<&str>| {
+        p.is_some_and(|p| {
+            !p.is_empty()
+                && p != "."
+                && p != ".."
+                && p.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+    };
+    ok(parts.next()) && ok(parts.next()) && parts.next().is_none()
+}
+
+fn str_at<'a>(value: &'a Value, pointer: &str) -> &'a str {
+    value.pointer(pointer).and_then(Value::as_str).unwrap_or("")
+}
+
+/// Date part of an ISO 8601 timestamp.
+fn date(timestamp: &str) -> &str {
+    timestamp.get(..10).unwrap_or(timestamp)
+}
+
+/// Browser link for an API resource URL (`…/repos/o/r/pulls/5` → `https://github.com/o/r/pull/5`).
+fn web_link(api_url: &str) -> Option<String> {
+    let (_, rest) = api_url.split_once("/repos/")?;
+    Some(format!(
+        "{WEB_URL}/{}",
+        rest.replacen("/pulls/", "/pull/", 1)
+    ))
+}
+
+/// "failing (lint, test)", "pending", "passing", or None without checks.
+fn checks_summary(body: &Value) -> Option<String> {
+    let runs = body.get("check_runs").and_then(Value::as_array)?;
+    if runs.is_empty() {
+        return None;
+    }
+    let failed: Vec<&str> = runs
+        .iter()
+        .filter(|r| {
+            matches!(
+                str_at(r, "/conclusion"),
+                "failure" | "timed_out" | "cancelled" | "action_required"
+            )
+        })
+        .map(|r| str_at(r, "/name"))
+        .collect();
+    Some(if !failed.is_empty() {
+        format!("failing ({})", failed.join(", "))
+    } else if runs.iter().any(|r| str_at(r, "/status") != "completed") {
+        "pending".to_string()
+    } else {
+        "passing".to_string()
+    })
+}
+
+pub struct GithubTool {
+    client: Client,
+    token: String,
+    repos: Vec<String>,
+    api_url: String,
+}
+
+impl GithubTool {
+    /// None when no token is configured.
+    pub fn from_config(client: Client, config: Option<&GithubConfig>) -> Option<Self> {
+        let config = config?;
+        let token = config.token.clone().filter(|t| !t.trim().is_empty())?;
+        Some(Self {
+            client,
+            token: token.trim().to_string(),
+            repos: config.repos.clone().unwrap_or_default(),
+            api_url: DEFAULT_API_URL.to_string(),
+        })
+    }
+
+    /// Use another API root with the same shape (e.g. GitHub Enterprise or a test server).
+    pub fn with_api_url(mut self, api_url: &str) -> Self {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<RequestBuilder, String> {
+        let url = Url::parse_with_params(&format!("{}{path}", self.api_url), query)
+            .map_err(|e| e.to_string())?;
+        Ok(self
+            .client
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", API_VERSION))
+    }
+
+    async fn send(&self, req: RequestBuilder) -> Result<Value, String> {
+        let res = req
+            .send()
+            .await
+            .map_err(|e| format!("GitHub request failed: {e}"))?;
+        let status = res.status();
+        let body: Value = res.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = str_at(&body, "/message");
+            return Err(if message.is_empty() {
+                format!("GitHub error: HTTP {status}")
+            } else {
+                format!("GitHub error: HTTP {status}: {message}")
+            });
+        }
+        Ok(body)
+    }
+
+    /// `repo` from the arguments, or every configured repo.
+    fn repos_for(&self, args: &Value) -> Result<Vec<String>, String> {
+        match args.get("repo").and_then(Value::as_str).map(str::trim) {
+            Some(repo) if !valid_repo(repo) => Err(format!("'{repo}' is not an owner/name repo")),
+            Some(repo) => Ok(vec![repo.to_string()]),
+            None if !self.repos.is_empty() => Ok(self.repos.clone()),
+            None => Err("repo is required (no repos configured)".to_string()),
+        }
+    }
+
+    async fn notifications(&self, limit: u64) -> Result<String, String> {
+        let req = self.request(
+            Method::GET,
+            "/notifications",
+            &[("per_page", limit.to_string())],
+        )?;
+        let body = self.send(req).await?;
+        let items = body.as_array().cloned().unwrap_or_default();
+        if items.is_empty() {
+            return Ok("No unread notifications.".to_string());
+        }
+        let mut out = format!("{} unread notifications:", items.len());
+        for n in &items {
+            let kind = match str_at(n, "/subject/type") {
+                "PullRequest" => "PR",
+                "Issue" => "issue",
+                other => other,
+            };
+            out.push_str(&format!(
+                "\n- {}: {} ({kind}, {})",
+                str_at(n, "/repository/full_name"),
+                str_at(n, "/subject/title"),
+                str_at(n, "/reason").replace('_', " ")
+            ));
+            let link = n
+                .pointer("/subject/url")
+                .and_then(Value::as_str)
+                .and_then(web_link)
+                .unwrap_or_else(|| str_at(n, "/repository/html_url").to_string());
+            if !link.is_empty() {
+                out.push_str(&format!("\n  {link}"));
+            }
+        }
+        Ok(out)
+    }
+
+    async fn reviews(&self, limit: u64) -> Result<String, String> {
+        let req = self.request(
+            Method::GET,
+            "/search/issues",
+            &[
+                (
+                    "q",
+                    "is:open is:pr review-requested:@me archived:false".to_string(),
+                ),
+                ("sort", "created".to_string()),
+                ("order", "asc".to_string()),
+                ("per_page", limit.to_string()),
+            ],
+        )?;
+        let body = self.send(req).await?;
+        let items = body
+            .get("items")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        if items.is_empty() {
+            return Ok("No pull requests are waiting for your review.".to_string());
+        }
+        let mut out = format!("{} pull requests waiting for your review:", items.len());
+        for pr in &items {
+            let repo = str_at(pr, "/repository_url")
+                .split_once("/repos/")
+                .map_or("", |(_, repo)| repo);
+            out.push_str(&format!(
+                "\n- {repo}#{} {} (by {}, opened {})\n  {}",
+                pr.get("number").and_then(Value::as_u64).unwrap_or(0),
+                str_at(pr, "/title"),
+                str_at(pr, "/user/login"),
+                date(str_at(pr, "/created_at")),
+                str_at(pr, "/html_url")
+            ));
+        }
+        Ok(out)
+    }
+
+    async fn issues(&self, args: &Value, limit: u64) -> Result<String, String> {
+        let mut sections = Vec::new();
+        for repo in self.repos_for(args)? {
+            let mut query = vec![
+                ("state", "open".to_string()),
+                ("sort", "updated".to_string()),
+                ("per_page", limit.to_string()),
+            ];
+            if let Some(labels) = args.get("labels").and_then(Value::as_str) {
+                query.push(("labels", labels.to_string()));
+            }
+            let req = self.request(Method::GET, &format!("/repos/{repo}/issues"), &query)?;
+            let body = self.send(req).await.map_err(|e| format!("{repo}: {e}"))?;
+            // The issues endpoint includes pull requests.
+            let issues: Vec<&Value> = body
+                .as_array()
+                .map(|a| {
+                    a.iter()
+                        .filter(|i| i.get("pull_request").is_none())
+                        .collect()
+                })
+                .unwrap_or_default();
+            if issues.is_empty() {
+                sections.push(format!("{repo}: no open issues."));
+                continue;
+            }
+            let mut out = format!("{repo} — {} open issues:", issues.len());
+            for issue in issues {
+                let labels: Vec<&str> = issue
+                    .get("labels")
+                    .and_then(Value::as_array)
+                    .map(|l| l.iter().map(|l| str_at(l, "/name")).collect())
+                    .unwrap_or_default();
+                let labels = if labels.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", labels.join(", "))
+                };
+                out.push_str(&format!(
+                    "\n- #{} {}{labels} (by {}, updated {})\n  {}",
+                    issue.get("number").and_then(Value::as_u64).unwrap_or(0),
+                    str_at(issue, "/title"),
+                    str_at(issue, "/user/login"),
+                    date(str_at(issue, "/updated_at")),
+                    str_at(issue, "/html_url")
+                ));
+            }
+            sections.push(out);
+        }
+        Ok(sections.join("\n\n"))
+    }
+
+    async fn create_issue(&self, args: &Value) -> Result<String, String> {
+        let repo = match args.get("repo").and_then(Value::as_str).map(str::trim) {
+            Some(repo) => repo.to_string(),
+            None if self.repos.len() == 1 => self.repos[0].clone(),
+            None => return Err("repo is required".to_string()),
+        };
+        if !self.repos.iter().any(|r| r.eq_ignore_ascii_case(&repo)) {
+            return Err(format!(
+                "issues can only be created in the configured repos ({})",
+                if self.repos.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.repos.join(", ")
+                }
+            ));
+        }
+        if !valid_repo(&repo) {
+            return Err(format!("'{repo}' is not an owner/name repo"));
+        }
+        let title = args
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or("create_issue needs 'title'")?;
+        let mut payload = serde_json::json!({ "title": title });
+        if let Some(body) = args.get("body").and_then(Value::as_str) {
+            payload["body"] = Value::from(body);
+        }
+        if let Some(labels) = args.get("labels").and_then(Value::as_str) {
+            let labels: Vec<&str> = labels
+                .split(',')
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .collect();
+            payload["labels"] = Value::from(labels);
+        }
+        let req = self
+            .request(Method::POST, &format!("/repos/{repo}/issues"), &[])?
+            .json(&payload);
+        let issue = self.send(req).await?;
+        Ok(format!(
+            "Created {repo}#{}: {title}\n{}",
+            issue.get("number").and_then(Value::as_u64).unwrap_or(0),
+            str_at(&issue, "/html_url")
+        ))
+    }
+
+    async fn pull_requests(&self, args: &Value, limit: u64) -> Result<String, String> {
+        let mut sections = Vec::new();
+        for repo in self.repos_for(args)? {
+            let req = self.request(
+                Method::GET,
+                &format!("/repos/{repo}/pulls"),
+                &[
+                    ("state", "open".to_string()),
+                    ("per_page", limit.to_string()),
+                ],
+            )?;
+            let body = self.send(req).await.map_err(|e| format!("{repo}: {e}"))?;
+            let pulls = body.as_array().cloned().unwrap_or_default();
+            if pulls.is_empty() {
+                sections.push(format!("{repo}: no open pull requests."));
+                continue;
+            }
+            let mut out = format!("{repo} — {} open pull requests:", pulls.len());
+            for pr in &pulls {
+                let mut notes = vec![format!("by {}", str_at(pr, "/user/login"))];
+                if pr.get("draft").and_then(Value::as_bool) == Some(true) {
+                    notes.push("draft".to_string());
+                }
+                let sha = str_at(pr, "/head/sha");
+                if !sha.is_empty() {
+                    let req = self.request(
+                        Method::GET,
+                        &format!("/repos/{repo}/commits/{sha}/check-runs"),
+                        &[],
+                    )?;
+                    // Missing check status shouldn't hide the PR list.
+                    if let Some(checks) =
+                        self.send(req).await.ok().as_ref().and_then(checks_summary)
+                    {
+                        notes.push(format!("checks {checks}"));
+                    }
+                }
+                let reviewers: Vec<&str> = ["/requested_reviewers", "/requested_teams"]
+                    .iter()
+                    .filter_map(|p| pr.pointer(p).and_then(Value::as_array))
+                    .flatten()
+                    .map(|r| {
+                        r.get("login")
+                            .or_else(|| r.get("slug"))
+                            .and_then(Value::as_str)
+                            .unwrap_or("")
+                    })
+                    .collect();
+                if !reviewers.is_empty() {
+                    notes.push(format!("review requested from {}", reviewers.join(", ")));
+                }
+                out.push_str(&format!(
+                    "\n- #{} {} ({})\n  {}",
+                    pr.get("number").and_then(Value::as_u64).unwrap_or(0),
+                    str_at(pr, "/title"),
+                    notes.join("; "),
+                    str_at(pr, "/html_url")
+                ));
+            }
+            sections.push(out);
+        }
+        Ok(sections.join("\n\n"))
+    }
+}
+
+impl Tool for GithubTool {
+    fn name(&self) -> &str {
+        "github"
+    }
+
+    fn description(&self) -> &str {
+        "GitHub: unread notifications, pull requests waiting for the user's review, open issues \
+         and pull requests (with CI checks and requested reviewers) of the configured repos, \
+         and creating issues."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["notifications", "reviews", "issues", "prs", "create_issue"], "description": "notifications (unread), reviews (PRs awaiting the user's review), issues, prs (open PRs with checks), create_issue" },
+                "repo": { "type": "string", "description": "owner/name; issues and prs default to all configured repos" },
+                "title": { "type": "string", "description": "create_issue: issue title" },
+                "body": { "type": "string", "description": "create_issue: issue body (markdown)" },
+                "labels": { "type": "string", "description": "Comma-separated labels (issues: filter; create_issue: apply)" },
+                "limit": { "type": "integer", "description": "Max items (default 20, max 50)" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let limit = args
+                .get("limit")
+                .and_then(Value::as_u64)
+                .unwrap_or(DEFAULT_LIMIT)
+                .clamp(1, MAX_LIMIT);
+            let result = match args.get("action").and_then(Value::as_str).unwrap_or("") {
+                "notifications" => self.notifications(limit).await,
+                "reviews" => self.reviews(limit).await,
+                "issues" => self.issues(args, limit).await,
+                "prs" => self.pull_requests(args, limit).await,
+                "create_issue" => self.create_issue(args).await,
+                other => Err(format!(
+                    "unknown action '{other}' (use notifications, reviews, issues, prs or create_issue)"
+                )),
+            };
+            match result {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::tools::web::web_client;
+
+    fn ctx() -> ToolCtx {
+        ToolCtx::for_tests(PathBuf::from("/tmp"))
+    }
+
+    fn tool(server: &MockServer, repos: &[&str]) -> GithubTool {
+        let config = GithubConfig {
+            token: Some("ghp_test".into()),
+            repos: Some(repos.iter().map(|r| r.to_string()).collect()),
+        };
+        GithubTool::from_config(web_client().unwrap(), Some(&config))
+            .unwrap()
+            .with_api_url(&server.uri())
+    }
+
+    #[test]
+    fn needs_a_token_and_valid_repos() {
+        let client = web_client().unwrap();
+        assert!(GithubTool::from_config(client.clone(), None).is_none());
+        let config = GithubConfig {
+            token: Some(" ".into()),
+            repos: None,
+        };
+        assert!(GithubTool::from_config(client, Some(&config)).is_none());
+        assert!(valid_repo("rust-lang/rust.vim"));
+        assert!(!valid_repo("rust-lang"));
+        assert!(!valid_repo("../x"));
+        assert!(!valid_repo("a/b/c"));
+    }
+
+    #[tokio::test]
+    async fn notifications_and_review_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/notifications"))
+            .and(header("authorization", "Bearer ghp_test"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "reason": "review_requested", "subject": { "title": "Add sync", "type": "PullRequest", "url": "https://api.github.com/repos/me/vault/pulls/7" }, "repository": { "full_name": "me/vault", "html_url": "https://github.com/me/vault" } },
+                { "reason": "ci_activity", "subject": { "title": "CI failed", "type": "CheckSuite", "url": null }, "repository": { "full_name": "me/vault", "html_url": "https://github.com/me/vault" } }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search/issues"))
+            .and(query_param(
+                "q",
+                "is:open is:pr review-requested:@me archived:false",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "items": [
+                { "number": 7, "title": "Add sync", "user": { "login": "alice" }, "created_at": "2026-10-12T08:00:00Z", "repository_url": "https://api.github.com/repos/me/vault", "html_url": "https://github.com/me/vault/pull/7" }
+            ]})))
+            .mount(&server)
+            .await;
+        let tool = tool(&server, &[]);
+
+        let res = tool
+            .execute(&ctx(), &json!({"action": "notifications"}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "2 unread notifications:\n\
+             - me/vault: Add sync (PR, review requested)\n  \
+             https://github.com/me/vault/pull/7\n\
+             - me/vault: CI failed (CheckSuite, ci activity)\n  \
+             https://github.com/me/vault"
+        );
+
+        let res = tool.execute(&ctx(), &json!({"action": "reviews"})).await;
+        assert_eq!(
+            res.for_llm,
+            "1 pull requests waiting for your review:\n\
+             - me/vault#7 Add sync (by alice, opened 2026-10-12)\n  \
+             https://github.com/me/vault/pull/7"
+        );
+    }
+
+    #[tokio::test]
+    async fn pull_requests_with_checks_and_issues() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/me/vault/pulls"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "number": 7, "title": "Add sync", "draft": false, "user": { "login": "alice" }, "head": { "sha": "abc" }, "requested_reviewers": [{ "login": "me" }], "requested_teams": [{ "slug": "core" }], "html_url": "https://github.com/me/vault/pull/7" },
+                { "number": 8, "title": "WIP", "draft": true, "user": { "login": "bob" }, "head": { "sha": "def" }, "html_url": "https://github.com/me/vault/pull/8" }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/me/vault/commits/abc/check-runs"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "check_runs": [
+                    { "name": "lint", "status": "completed", "conclusion": "success" },
+                    { "name": "test", "status": "completed", "conclusion": "failure" }
+                ]})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/me/vault/commits/def/check-runs"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "check_runs": [
+                    { "name": "test", "status": "in_progress", "conclusion": null }
+                ]})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/me/vault/issues"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "number": 3, "title": "Crash on empty note", "labels": [{ "name": "bug" }], "user": { "login": "carol" }, "updated_at": "2026-10-15T10:00:00Z", "html_url": "https://github.com/me/vault/issues/3" },
+                { "number": 7, "title": "Add sync", "pull_request": {}, "user": { "login": "alice" }, "updated_at": "2026-10-15T10:00:00Z", "html_url": "https://github.com/me/vault/pull/7" }
+            ])))
+            .mount(&server)
+            .await;
+        let tool = tool(&server, &["me/vault"]);
+
+        let res = tool.execute(&ctx(), &json!({"action": "prs"})).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "me/vault — 2 open pull requests:\n\
+             - #7 Add sync (by alice; checks failing (test); review requested from me, core)\n  \
+             https://github.com/me/vault/pull/7\n\
+             - #8 WIP (by bob; draft; checks pending)\n  \
+             https://github.com/me/vault/pull/8"
+        );
+
+        let res = tool.execute(&ctx(), &json!({"action": "issues"})).await;
+        assert_eq!(
+            res.for_llm,
+            "me/vault — 1 open issues:\n\
+             - #3 Crash on empty note [bug] (by carol, updated 2026-10-15)\n  \
+             https://github.com/me/vault/issues/3"
+        );
+    }
+
+    #[tokio::test]
+    async fn create_issue_only_in_configured_repos() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/repos/me/vault/issues"))
+            .and(body_json(
+                json!({ "title": "Renew domain", "labels": ["admin", "urgent"] }),
+            ))
+            .respond_with(ResponseTemplate::new(201).set_body_json(
+                json!({ "number": 12, "html_url": "https://github.com/me/vault/issues/12" }),
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let tool = tool(&server, &["me/vault"]);
+
+        let args =
+            json!({"action": "create_issue", "title": "Renew domain", "labels": "admin, urgent"});
+        let res = tool.execute(&ctx(), &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "Created me/vault#12: Renew domain\nhttps://github.com/me/vault/issues/12"
+        );
+
+        let args = json!({"action": "create_issue", "repo": "someone/else", "title": "Hi"});
+        let res = tool.execute(&ctx(), &args).await;
+        assert!(res.is_error);
+        assert_eq!(
+            res.for_llm,
+            "issues can only be created in the configured repos (me/vault)"
+        );
+    }
+}
@@ -2,12 +2,14 @@
 //!
 //! Avoids FTS5 overhead when a skill knows the exact folder and pattern it needs.
 //! Always restricted to the workspace — paths escaping via `..` are rejected.
+//! Files and folders listed in `.icrabignore` are never searched.
 
 use std::path::Path;
 
 use regex_lite::Regex;
 use serde_json::Value;
 
+use crate::memory::ignore::IgnoreRules;
 use crate::tools::context::ToolCtx;
 use crate::tools::file::resolve_path;
 use crate::tools::registry::{BoxFuture, Tool};
@@ -48,6 +50,10 @@ impl Tool for GrepDirTool {
         })
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
         let workspace = ctx.workspace.clone();
         let restrict = ctx.restrict_to_workspace;
@@ -120,8 +126,20 @@ fn grep_dir_blocking(
         return Err(format!("not a directory: {}", dir.display()));
     }
 
+    // `dir` is resolved against the canonical workspace; match ignore rules the same way.
+    let root = std::fs::canonicalize(workspace).unwrap_or_else(|_| workspace.to_path_buf());
+    let ignore = IgnoreRules::load(workspace);
+
     let mut matches = Vec::new();
-    walk_and_grep(dir, re, workspace, &mut matches, max_matches);
+    walk_and_grep(
+        dir,
+        re,
+        workspace,
+        &root,
+        &ignore,
+        &mut matches,
+        max_matches,
+    );
     Ok(matches)
 }
 
@@ -129,6 +147,8 @@ fn walk_and_grep(
     dir: &Path,
     re: &Regex,
     workspace: &Path,
+    root: &Path,
+    ignore: &IgnoreRules,
     matches: &mut Vec<GrepMatch>,
     max_matches: usize,
 ) {
@@ -158,13 +178,19 @@ fn walk_and_grep(
             Err(_) => continue,
         };
 
+        if let Ok(rel) = path.strip_prefix(root)
+            && ignore.is_ignored(&rel.to_string_lossy().replace('\\', "/"), meta.is_dir())
+        {
+            continue;
+        }
+
         if meta.is_dir() {
             let name = entry.file_name();
             let n = name.to_string_lossy();
             if n.starts_with('.') {
                 continue;
             }
-            walk_and_grep(&path, re, workspace, matches, max_matches);
+            walk_and_grep(&path, re, workspace, root, ignore, matches, max_matches);
         } else if meta.is_file() && path.extension().and_then(|e| e.to_str()) == Some("md") {
             let rel = path
                 .strip_prefix(workspace)
@@ -228,14 +254,7 @@ mod tests {
     use crate::tools::registry::Tool;
 
     fn tmp_ctx(ws: &Path) -> ToolCtx {
-        ToolCtx {
-            workspace: ws.to_path_buf(),
-            restrict_to_workspace: true,
-            chat_id: None,
-            channel: None,
-            outbound_tx: None,
-            delivered: Default::default(),
-        }
+        ToolCtx::for_tests(ws)
     }
 
     fn write_md(dir: &Path, name: &str, content: &str) {
@@ -372,6 +391,30 @@ mod tests {
         assert!(matches[0].line.contains("squats"));
     }
 
+    #[tokio::test]
+    async fn icrabignore_paths_are_skipped() {
+        let tmp = TempDir::new().unwrap();
+        write_md(tmp.path(), "note.md", "squats");
+        write_md(tmp.path(), "Private/diary.md", "squats");
+        write_md(tmp.path(), "Private/Gym/log.md", "squats");
+        write_md(tmp.path(), ".icrabignore", "Private/\n");
+
+        for dir in [".", "Private/Gym"] {
+            let res = GrepDirTool
+                .execute(
+                    &tmp_ctx(tmp.path()),
+                    &serde_json::json!({ "pattern": "squats", "dir_path": dir }),
+                )
+                .await;
+            assert!(!res.is_error, "{}", res.for_llm);
+            assert!(
+                !res.for_llm.contains("diary.md") && !res.for_llm.contains("log.md"),
+                "{}",
+                res.for_llm
+            );
+        }
+    }
+
     #[test]
     fn grep_blocking_nonexistent_dir_errors() {
         let tmp = TempDir::new().unwrap();
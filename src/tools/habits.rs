@@ -0,0 +1,432 @@
+//! `habits` tool: daily habits kept in BrainDb (`habits`, `habit_checkins`) — define, check
+//! in, streaks and completion rates — mirrored after every change into a Markdown dashboard
+//! in the vault (`[tools.habits] dashboard`, default `Habits.md`).
+//!
+//! `status` lists what is still undone today, so an evening cron job ("remind me of unchecked
+//! habits") only has to call it.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde_json::Value;
+
+use crate::config::HabitsConfig;
+use crate::memory::db::{BrainDb, Habit, run_blocking};
+use crate::tools::context::ToolCtx;
+use crate::tools::daily_note::parse_day;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const DEFAULT_DASHBOARD: &str = "Habits.md";
+/// Window of the completion rate.
+const RATE_DAYS: i64 = 30;
+/// Days shown as ✅/⬜ on the dashboard.
+const RECENT_DAYS: i64 = 7;
+
+/// Streaks and completion of one habit as of a day.
+#[derive(Debug, PartialEq)]
+struct Stats {
+    done_today: bool,
+    /// Consecutive days done up to today, or up to yesterday while today is still open.
+    streak: u32,
+    best: u32,
+    /// Days done and days tracked within the last [`RATE_DAYS`].
+    rate: (u32, u32),
+    /// Oldest first, ending today.
+    recent: Vec<bool>,
+}
+
+fn stats(habit: &Habit, today: NaiveDate, tz: Tz) -> Stats {
+    let done: BTreeSet<NaiveDate> = habit
+        .days
+        .iter()
+        .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .collect();
+    let done_today = done.contains(&today);
+
+    let mut streak = 0;
+    let mut day = if done_today {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    while done.contains(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+
+    let (mut best, mut run, mut prev) = (0, 0, None::<NaiveDate>);
+    for &d in &done {
+        run = if prev == d.pred_opt() { run + 1 } else { 1 };
+        best = best.max(run);
+        prev = Some(d);
+    }
+
+    let created = DateTime::<Utc>::from_timestamp(habit.created_at, 0)
+        .map(|t| t.with_timezone(&tz).date_naive())
+        .unwrap_or(today);
+    // Check-ins before the habit was defined (backfilled) count as tracked too.
+    let start = done.first().map_or(created, |&d| d.min(created));
+    let tracked = ((today - start).num_days() + 1).clamp(1, RATE_DAYS);
+    let window = today - Duration::days(tracked - 1);
+    let hits = done.range(window..=today).count() as u32;
+
+    let recent = (0..RECENT_DAYS)
+        .rev()
+        .map(|i| done.contains(&(today - Duration::days(i))))
+        .collect();
+    Stats {
+        done_today,
+        streak,
+        best,
+        rate: (hits, tracked as u32),
+        recent,
+    }
+}
+
+fn mark(done: bool) -> &'static str {
+    if done { "✅" } else { "⬜" }
+}
+
+fn days(n: u32) -> String {
+    if n == 1 {
+        "1 day".to_string()
+    } else {
+        format!("{n} days")
+    }
+}
+
+/// The chat summary for `day`.
+fn status(habits: &[Habit], day: NaiveDate, tz: Tz) -> String {
+    if habits.is_empty() {
+        return "No habits yet; define one first.".to_string();
+    }
+    let mut out = format!("Habits, {}:", day.format("%a %-d %b"));
+    let mut open = Vec::new();
+    for h in habits {
+        let s = stats(h, day, tz);
+        out.push_str(&format!(
+            "\n- {} {} — streak {} (best {}), {}/{} days",
+            mark(s.done_today),
+            h.name,
+            days(s.streak),
+            s.best,
+            s.rate.0,
+            s.rate.1
+        ));
+        if !s.done_today {
+            open.push(h.name.as_str());
+        }
+    }
+    if open.is_empty() {
+        out.push_str("\n\nAll done.");
+    } else {
+        out.push_str(&format!("\n\nNot done yet: {}", open.join(", ")));
+    }
+    out
+}
+
+/// The vault dashboard for `today`.
+fn dashboard(habits: &[Habit], today: NaiveDate, tz: Tz) -> String {
+    let mut out = format!(
+        "# Habits\n\n_Updated by iCrab on {}; edits here are overwritten._\n\n",
+        today.format("%Y-%m-%d")
+    );
+    if habits.is_empty() {
+        out.push_str("No habits tracked.\n");
+        return out;
+    }
+    out.push_str(&format!(
+        "| Habit | Today | Streak | Best | Last {RECENT_DAYS} days | {RATE_DAYS} days |\n\
+         |---|---|---|---|---|---|\n"
+    ));
+    for h in habits {
+        let s = stats(h, today, tz);
+        let recent: String = s.recent.iter().map(|&d| mark(d)).collect();
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {recent} | {}% |\n",
+            h.name.replace('|', "/"),
+            mark(s.done_today),
+            s.streak,
+            s.best,
+            s.rate.0 * 100 / s.rate.1.max(1)
+        ));
+    }
+    out
+}
+
+pub struct HabitsTool {
+    db: Arc<BrainDb>,
+    dashboard: String,
+    timezone: Tz,
+}
+
+impl HabitsTool {
+    pub fn new(db: Arc<BrainDb>, config: Option<&HabitsConfig>, timezone: Tz) -> Self {
+        Self {
+            db,
+            dashboard: config
+                .and_then(|c| c.dashboard.clone())
+                .unwrap_or_else(|| DEFAULT_DASHBOARD.to_string()),
+            timezone,
+        }
+    }
+
+    /// Rewrite the vault dashboard; returns the habits it was made from.
+    async fn refresh(&self, ctx: &ToolCtx, today: NaiveDate) -> Result<Vec<Habit>, String> {
+        let habits = run_blocking(&self.db, "habits", |db| db.habits()).await?;
+        let path = resolve_path(&self.dashboard, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("writing {} failed: {e}", self.dashboard))?;
+        }
+        tokio::fs::write(&path, dashboard(&habits, today, self.timezone))
+            .await
+            .map_err(|e| format!("writing {} failed: {e}", self.dashboard))?;
+        Ok(habits)
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let action = args.get("action").and_then(Value::as_str).unwrap_or("");
+        let today = Utc::now().with_timezone(&self.timezone).date_naive();
+        let date_arg = args.get("date").and_then(Value::as_str).unwrap_or("today");
+        let day = parse_day(date_arg, today)
+            .ok_or_else(|| format!("invalid date '{date_arg}' (today, yesterday or YYYY-MM-DD)"))?;
+        let name = args
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(str::to_string);
+        let need_name = || name.clone().ok_or(format!("{action} needs 'name'"));
+
+        let reply = match action {
+            "define" => {
+                let name = need_name()?;
+                let notes = args
+                    .get("notes")
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .unwrap_or("")
+                    .to_string();
+                let n = name.clone();
+                if run_blocking(&self.db, "habits", move |db| db.define_habit(&n, &notes)).await? {
+                    format!("Now tracking \"{name}\" daily.")
+                } else {
+                    format!("\"{name}\" is already tracked.")
+                }
+            }
+            "remove" => {
+                let name = need_name()?;
+                let n = name.clone();
+                if !run_blocking(&self.db, "habits", move |db| db.remove_habit(&n)).await? {
+                    return Err(format!("no habit called \"{name}\""));
+                }
+                format!("Stopped tracking \"{name}\" and deleted its history.")
+            }
+            "check" | "uncheck" => {
+                let name = need_name()?;
+                let done = action == "check";
+                if day > today {
+                    return Err("cannot check in on a future day".into());
+                }
+                let (n, d) = (name.clone(), day.format("%Y-%m-%d").to_string());
+                let changed = run_blocking(&self.db, "habits", move |db| {
+                    db.set_habit_done(&n, &d, done)
+                })
+                .await?;
+                let when = if day == today {
+                    "today".to_string()
+                } else {
+                    day.format("%a %-d %b").to_string()
+                };
+                match (changed, done) {
+                    (None, _) => {
+                        return Err(format!("no habit called \"{name}\"; define it first"));
+                    }
+                    (Some(false), true) => format!("\"{name}\" was already done {when}."),
+                    (Some(false), false) => format!("\"{name}\" was not marked done {when}."),
+                    (Some(true), true) => format!("Checked \"{name}\" for {when}."),
+                    (Some(true), false) => format!("Unchecked \"{name}\" for {when}."),
+                }
+            }
+            "status" => {
+                let habits = self.refresh(ctx, today).await?;
+                return Ok(status(&habits, day, self.timezone));
+            }
+            "" => return Err("missing 'action' (define, check, uncheck, status or remove)".into()),
+            other => {
+                return Err(format!(
+                    "unknown action '{other}' (define, check, uncheck, status or remove)"
+                ));
+            }
+        };
+        let habits = self.refresh(ctx, today).await?;
+        let checked = name
+            .filter(|_| action.ends_with("check"))
+            .and_then(|n| habits.into_iter().find(|h| h.name.eq_ignore_ascii_case(&n)));
+        if let Some(h) = checked {
+            let s = stats(&h, today, self.timezone);
+            return Ok(format!(
+                "{reply} Streak: {} (best {}).",
+                days(s.streak),
+                s.best
+            ));
+        }
+        Ok(reply)
+    }
+}
+
+impl Tool for HabitsTool {
+    fn name(&self) -> &str {
+        "habits"
+    }
+
+    fn description(&self) -> &str {
+        "Daily habit tracker with streaks, mirrored to a dashboard note in the vault. \
+         define: start tracking a habit. check / uncheck: mark it done (or not) today or on \
+         an earlier date. status: every habit with today's state, streaks and 30-day rate, \
+         and which are not done yet — use this for reminders about unchecked habits. \
+         remove: stop tracking a habit."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["define", "check", "uncheck", "status", "remove"] },
+                "name": { "type": "string", "description": "Habit name, e.g. \"Stretch\" (define, check, uncheck, remove)" },
+                "notes": { "type": "string", "description": "What counts as done, e.g. \"10 minutes\" (define)" },
+                "date": { "type": "string", "description": "today (default), yesterday or YYYY-MM-DD (check, uncheck, status)" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const LONDON: Tz = chrono_tz::Europe::London;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    fn habit(days: &[&str]) -> Habit {
+        Habit {
+            name: "Stretch".to_string(),
+            notes: String::new(),
+            // 2026-10-01 12:00 UTC
+            created_at: 1_790_856_000,
+            days: days.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, d).unwrap()
+    }
+
+    #[test]
+    fn streaks_and_rates() {
+        let h = habit(&[
+            "2026-10-02",
+            "2026-10-03",
+            "2026-10-04",
+            "2026-10-14",
+            "2026-10-15",
+        ]);
+        // Today still open: the streak up to yesterday counts.
+        let s = stats(&h, day(16), LONDON);
+        assert!(!s.done_today);
+        assert_eq!((s.streak, s.best), (2, 3));
+        assert_eq!(s.rate, (5, 16));
+        assert_eq!(
+            s.recent,
+            vec![false, false, false, false, true, true, false]
+        );
+        // A missed day breaks it.
+        assert_eq!(stats(&h, day(17), LONDON).streak, 0);
+        let s = stats(&habit(&["2026-10-16"]), day(16), LONDON);
+        assert_eq!((s.done_today, s.streak, s.best), (true, 1, 1));
+    }
+
+    #[test]
+    fn dashboard_table() {
+        let h = habit(&["2026-10-15", "2026-10-16"]);
+        assert_eq!(
+            dashboard(&[h], day(16), LONDON),
+            "# Habits\n\n_Updated by iCrab on 2026-10-16; edits here are overwritten._\n\n\
+             | Habit | Today | Streak | Best | Last 7 days | 30 days |\n\
+             |---|---|---|---|---|---|\n\
+             | Stretch | ✅ | 2 | 2 | ⬜⬜⬜⬜⬜✅✅ | 12% |\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn define_check_status_and_dashboard() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(BrainDb::open(dir.path()).unwrap());
+        let config = HabitsConfig {
+            dashboard: Some("Trackers/Habits.md".to_string()),
+        };
+        let tool = HabitsTool::new(db, Some(&config), LONDON);
+        let ctx = ctx(dir.path());
+        let run = |args: Value| {
+            let (tool, ctx) = (&tool, &ctx);
+            async move { tool.execute(ctx, &args).await }
+        };
+
+        let res = run(serde_json::json!({"action": "define", "name": "Stretch"})).await;
+        assert_eq!(res.for_llm, "Now tracking \"Stretch\" daily.");
+        run(serde_json::json!({"action": "define", "name": "Read"})).await;
+        let res = run(serde_json::json!({"action": "check", "name": "stretch"})).await;
+        assert_eq!(
+            res.for_llm,
+            "Checked \"stretch\" for today. Streak: 1 day (best 1)."
+        );
+        let res = run(serde_json::json!({"action": "check", "name": "Floss"})).await;
+        assert!(res.is_error);
+
+        let res = run(serde_json::json!({"action": "status"})).await;
+        assert!(
+            res.for_llm
+                .contains("\n- ✅ Stretch — streak 1 day (best 1), 1/1 days"),
+            "{}",
+            res.for_llm
+        );
+        assert!(
+            res.for_llm.ends_with("\n\nNot done yet: Read"),
+            "{}",
+            res.for_llm
+        );
+
+        let dash = std::fs::read_to_string(dir.path().join("Trackers/Habits.md")).unwrap();
+        assert!(dash.contains("| Stretch | ✅ | 1 | 1 |"), "{dash}");
+        assert!(dash.contains("| Read | ⬜ | 0 | 0 |"), "{dash}");
+
+        let res = run(serde_json::json!({"action": "remove", "name": "Read"})).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let dash = std::fs::read_to_string(dir.path().join("Trackers/Habits.md")).unwrap();
+        assert!(!dash.contains("Read"), "{dash}");
+    }
+}
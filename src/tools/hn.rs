@@ -0,0 +1,378 @@
+//! `hn` tool: Hacker News front page, newest stories or a story search via the Algolia HN API,
+//! and optionally a subreddit's listing, as numbered titles with scores and links — the
+//! material for a nightly tech digest cron job.
+
+use reqwest::{Client, Url};
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::feeds::decode_entities;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const DEFAULT_HN_URL: &str = "https://hn.algolia.com/api/v1";
+const DEFAULT_REDDIT_URL: &str = "https://www.reddit.com";
+const HN_ITEM_URL: &str = "https://news.ycombinator.com/item?id=";
+const DEFAULT_LIMIT: u64 = 10;
+const MAX_LIMIT: u64 = 30;
+
+/// A story from either site.
+#[derive(Debug, PartialEq)]
+struct Story {
+    title: String,
+    points: i64,
+    comments: i64,
+    /// The linked article; None for Ask HN and self posts.
+    url: Option<String>,
+    discussion: String,
+}
+
+fn format_stories(heading: &str, stories: &[Story]) -> String {
+    if stories.is_empty() {
+        return format!("{heading}: no stories.");
+    }
+    let mut out = format!("{heading}:");
+    for (i, story) in stories.iter().enumerate() {
+        out.push_str(&format!(
+            "\n{}. {} ({} points, {} comments)",
+            i + 1,
+            story.title,
+            story.points,
+            story.comments
+        ));
+        if let Some(url) = &story.url {
+            out.push_str(&format!("\n   {url}"));
+        }
+        out.push_str(&format!("\n   {}", story.discussion));
+    }
+    out
+}
+
+fn hn_stories(body: &Value) -> Vec<Story> {
+    let Some(hits) = body.get("hits").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    hits.iter()
+        .filter_map(|hit| {
+            let title = hit.get("title").and_then(Value::as_str)?.trim();
+            let id = hit.get("objectID").and_then(Value::as_str)?;
+            Some(Story {
+                title: title.to_string(),
+                points: hit.get("points").and_then(Value::as_i64).unwrap_or(0),
+                comments: hit.get("num_comments").and_then(Value::as_i64).unwrap_or(0),
+                url: hit
+                    .get("url")
+                    .and_then(Value::as_str)
+                    .filter(|u| !u.is_empty())
+                    .map(String::from),
+                discussion: format!("{HN_ITEM_URL}{id}"),
+            })
+        })
+        .collect()
+}
+
+fn reddit_stories(body: &Value, reddit_url: &str) -> Vec<Story> {
+    let Some(children) = body.pointer("/data/children").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    children
+        .iter()
+        .filter_map(|child| {
+            let post = child.get("data")?;
+            if post.get("stickied").and_then(Value::as_bool) == Some(true) {
+                return None;
+            }
+            let title = post.get("title").and_then(Value::as_str)?.trim();
+            let permalink = post.get("permalink").and_then(Value::as_str)?;
+            let is_self = post.get("is_self").and_then(Value::as_bool) == Some(true);
+            Some(Story {
+                title: decode_entities(title),
+                points: post.get("score").and_then(Value::as_i64).unwrap_or(0),
+                comments: post
+                    .get("num_comments")
+                    .and_then(Value::as_i64)
+                    .unwrap_or(0),
+                url: post
+                    .get("url")
+                    .and_then(Value::as_str)
+                    .filter(|_| !is_self)
+                    .map(decode_entities),
+                discussion: format!("{reddit_url}{permalink}"),
+            })
+        })
+        .collect()
+}
+
+pub struct HnTool {
+    client: Client,
+    hn_url: String,
+    reddit_url: String,
+}
+
+impl HnTool {
+    pub fn new(client: Client) -> Self {
+        Self::with_urls(client, DEFAULT_HN_URL, DEFAULT_REDDIT_URL)
+    }
+
+    /// Use other endpoints with the same API shapes (e.g. a test server).
+    pub fn with_urls(client: Client, hn_url: &str, reddit_url: &str) -> Self {
+        Self {
+            client,
+            hn_url: hn_url.trim_end_matches('/').to_string(),
+            reddit_url: reddit_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn get_json(&self, url: Url, site: &str) -> Result<Value, String> {
+        let res = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("{site} request failed: {e}"))?;
+        let status = res.status();
+        if !status.is_success() {
+            return Err(format!("{site} error: HTTP {status}"));
+        }
+        res.json()
+            .await
+            .map_err(|e| format!("{site} response unreadable: {e}"))
+    }
+
+    async fn hacker_news(&self, args: &Value, source: &str, limit: u64) -> Result<String, String> {
+        let hits = limit.to_string();
+        let mut params = vec![("hitsPerPage", hits)];
+        let (endpoint, heading) = match source {
+            "top" => {
+                params.push(("tags", "front_page".to_string()));
+                ("search", "Hacker News front page".to_string())
+            }
+            "new" => {
+                params.push(("tags", "story".to_string()));
+                ("search_by_date", "Newest Hacker News stories".to_string())
+            }
+            _ => {
+                let query = args
+                    .get("query")
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|q| !q.is_empty())
+                    .ok_or("search needs 'query'")?;
+                params.push(("query", query.to_string()));
+                params.push(("tags", "story".to_string()));
+                (
+                    "search",
+                    format!("Hacker News stories matching \"{query}\""),
+                )
+            }
+        };
+        let mut filters = Vec::new();
+        if let Some(hours) = args.get("hours").and_then(Value::as_u64) {
+            let since = chrono::Utc::now().timestamp() - (hours as i64) * 3600;
+            filters.push(format!("created_at_i>{since}"));
+        }
+        if let Some(points) = args.get("min_points").and_then(Value::as_u64) {
+            filters.push(format!("points>={points}"));
+        }
+        if !filters.is_empty() {
+            params.push(("numericFilters", filters.join(",")));
+        }
+        let url = Url::parse_with_params(&format!("{}/{endpoint}", self.hn_url), &params)
+            .map_err(|e| e.to_string())?;
+        let body = self.get_json(url, "Hacker News").await?;
+        Ok(format_stories(&heading, &hn_stories(&body)))
+    }
+
+    async fn reddit(&self, args: &Value, limit: u64) -> Result<String, String> {
+        let subreddit = args
+            .get("subreddit")
+            .and_then(Value::as_str)
+            .map(|s| s.trim().trim_start_matches("r/").trim_start_matches("/r/"))
+            .filter(|s| !s.is_empty())
+            .ok_or("reddit needs 'subreddit' (e.g. rust)")?;
+        if !subreddit
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+')
+        {
+            return Err(format!("'{subreddit}' is not a subreddit name"));
+        }
+        let sort = args.get("sort").and_then(Value::as_str).unwrap_or("hot");
+        if !matches!(sort, "hot" | "new" | "top") {
+            return Err(format!("unknown sort '{sort}' (use hot, new or top)"));
+        }
+        let url = Url::parse_with_params(
+            &format!("{}/r/{subreddit}/{sort}.json", self.reddit_url),
+            &[
+                ("limit", limit.to_string()),
+                ("t", "day".to_string()),
+                ("raw_json", "1".to_string()),
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        let body = self.get_json(url, "Reddit").await?;
+        let min_points = args.get("min_points").and_then(Value::as_i64).unwrap_or(0);
+        let stories: Vec<Story> = reddit_stories(&body, &self.reddit_url)
+            .into_iter()
+            .filter(|s| s.points >= min_points)
+            .collect();
+        let when = if sort == "top" { " (today)" } else { "" };
+        Ok(format_stories(
+            &format!("r/{subreddit} {sort}{when}"),
+            &stories,
+        ))
+    }
+}
+
+impl Tool for HnTool {
+    fn name(&self) -> &str {
+        "hn"
+    }
+
+    fn description(&self) -> &str {
+        "Hacker News stories (front page, newest, or a search) or a subreddit's posts, with \
+         scores, comment counts and links. Use for tech news digests."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "source": { "type": "string", "enum": ["top", "new", "search", "reddit"], "description": "top (HN front page, default), new, search (HN stories matching query) or reddit" },
+                "query": { "type": "string", "description": "search: search terms" },
+                "subreddit": { "type": "string", "description": "reddit: subreddit name, e.g. rust (several joined with +)" },
+                "sort": { "type": "string", "enum": ["hot", "new", "top"], "description": "reddit: listing (default hot; top is today's)" },
+                "hours": { "type": "integer", "description": "HN: only stories from the last N hours" },
+                "min_points": { "type": "integer", "description": "Only stories with at least this score" },
+                "limit": { "type": "integer", "description": "Max stories (default 10, max 30)" }
+            }
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let limit = args
+                .get("limit")
+                .and_then(Value::as_u64)
+                .unwrap_or(DEFAULT_LIMIT)
+                .clamp(1, MAX_LIMIT);
+            let result = match args.get("source").and_then(Value::as_str).unwrap_or("top") {
+                source @ ("top" | "new" | "search") => self.hacker_news(args, source, limit).await,
+                "reddit" => self.reddit(args, limit).await,
+                other => Err(format!(
+                    "unknown source '{other}' (use top, new, search or reddit)"
+                )),
+            };
+            match result {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::tools::web::web_client;
+
+    fn ctx() -> ToolCtx {
+        ToolCtx::for_tests(PathBuf::from("/tmp"))
+    }
+
+    async fn tool() -> (HnTool, MockServer) {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/hn/search"))
+            .and(query_param("tags", "front_page"))
+            .and(query_param("hitsPerPage", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "hits": [
+                { "objectID": "41", "title": "Show HN: A tiny agent", "url": "https://example.com/agent", "points": 312, "num_comments": 120 },
+                { "objectID": "42", "title": "Ask HN: Favourite editor?", "url": null, "points": 57, "num_comments": 301 }
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/hn/search"))
+            .and(query_param("query", "rust"))
+            .and(query_param("tags", "story"))
+            .and(query_param("numericFilters", "points>=100"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "hits": [] })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/r/rust/top.json"))
+            .and(query_param("t", "day"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": { "children": [
+                { "data": { "title": "Weekly thread", "stickied": true, "score": 5, "num_comments": 9, "permalink": "/r/rust/comments/a/", "is_self": true } },
+                { "data": { "title": "Rust 2.0 &amp; you", "score": 540, "num_comments": 80, "permalink": "/r/rust/comments/b/rust_20/", "url": "https://blog.rust-lang.org/x", "is_self": false } },
+                { "data": { "title": "Help with lifetimes", "score": 3, "num_comments": 4, "permalink": "/r/rust/comments/c/help/", "url": "https://www.reddit.com/r/rust/comments/c/help/", "is_self": true } }
+            ]}})))
+            .mount(&server)
+            .await;
+        let tool = HnTool::with_urls(
+            web_client().unwrap(),
+            &format!("{}/hn", server.uri()),
+            &server.uri(),
+        );
+        (tool, server)
+    }
+
+    #[tokio::test]
+    async fn hacker_news_front_page_and_search() {
+        let (tool, _server) = tool().await;
+        let res = tool.execute(&ctx(), &json!({"limit": 2})).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "Hacker News front page:\n\
+             1. Show HN: A tiny agent (312 points, 120 comments)\n   \
+             https://example.com/agent\n   \
+             https://news.ycombinator.com/item?id=41\n\
+             2. Ask HN: Favourite editor? (57 points, 301 comments)\n   \
+             https://news.ycombinator.com/item?id=42"
+        );
+
+        let args = json!({"source": "search", "query": "rust", "min_points": 100});
+        let res = tool.execute(&ctx(), &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Hacker News stories matching \"rust\": no stories."
+        );
+        let res = tool.execute(&ctx(), &json!({"source": "search"})).await;
+        assert_eq!(res.for_llm, "search needs 'query'");
+    }
+
+    #[tokio::test]
+    async fn subreddit_listing_skips_stickied_posts() {
+        let (tool, server) = tool().await;
+        let args = json!({"source": "reddit", "subreddit": "r/rust", "sort": "top"});
+        let res = tool.execute(&ctx(), &args).await;
+        assert_eq!(
+            res.for_llm,
+            format!(
+                "r/rust top (today):\n\
+                 1. Rust 2.0 & you (540 points, 80 comments)\n   \
+                 https://blog.rust-lang.org/x\n   \
+                 {0}/r/rust/comments/b/rust_20/\n\
+                 2. Help with lifetimes (3 points, 4 comments)\n   \
+                 {0}/r/rust/comments/c/help/",
+                server.uri()
+            )
+        );
+        let args = json!({"source": "reddit", "subreddit": "../api"});
+        assert!(tool.execute(&ctx(), &args).await.is_error);
+    }
+}
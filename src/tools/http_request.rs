@@ -0,0 +1,341 @@
+//! `http_request` tool: any-method HTTP call (headers, JSON/form/raw body) for APIs that
+//! `web_fetch` can't reach — home automation, todo apps and the like.
+//!
+//! Basic-auth logins live in `[tools.http.credentials.<name>]`; the agent passes only the
+//! name, so passwords never enter the conversation.
+
+use std::collections::BTreeMap;
+
+use reqwest::{Client, Method};
+use serde_json::Value;
+
+use crate::config::HttpCredential;
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::web::{body_text, validate_fetch_url};
+
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD"];
+
+/// `application/x-www-form-urlencoded` encoding of `fields` (strings as-is, other values as
+/// JSON).
+fn form_body(fields: &serde_json::Map<String, Value>) -> String {
+    // Url's query serializer is the form encoder; no separate dependency needed.
+    let mut url = reqwest::Url::parse("http://form.invalid/").expect("static URL");
+    {
+        let mut pairs = url.query_pairs_mut();
+        for (key, value) in fields {
+            match value {
+                Value::String(s) => pairs.append_pair(key, s),
+                other => pairs.append_pair(key, &other.to_string()),
+            };
+        }
+    }
+    url.query().unwrap_or("").to_string()
+}
+
+/// First `max_chars` characters of `text`, and whether it was cut.
+fn truncate_chars(text: String, max_chars: usize) -> (String, bool) {
+    match text.char_indices().nth(max_chars) {
+        Some((at, _)) => (text[..at].to_string(), true),
+        None => (text, false),
+    }
+}
+
+pub struct HttpRequestTool {
+    client: Client,
+    credentials: BTreeMap<String, HttpCredential>,
+    max_chars: u32,
+}
+
+impl HttpRequestTool {
+    pub fn new(
+        client: Client,
+        credentials: BTreeMap<String, HttpCredential>,
+        max_chars: u32,
+    ) -> Self {
+        Self {
+            client,
+            credentials,
+            max_chars,
+        }
+    }
+
+    /// Build the request from tool arguments.
+    fn request(&self, args: &Value) -> Result<reqwest::RequestBuilder, String> {
+        let url = args
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or("missing or invalid 'url'")?;
+        let url = validate_fetch_url(url)?;
+        let method = args
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or("GET")
+            .to_ascii_uppercase();
+        if !METHODS.contains(&method.as_str()) {
+            return Err(format!("method must be one of {}", METHODS.join(", ")));
+        }
+        let method = Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+
+        let mut req = self.client.request(method, url);
+        if let Some(headers) = args.get("headers") {
+            let headers = headers
+                .as_object()
+                .ok_or("'headers' must be an object of strings")?;
+            for (name, value) in headers {
+                let value = value
+                    .as_str()
+                    .ok_or_else(|| format!("header '{name}' must be a string"))?;
+                req = req.header(name.as_str(), value);
+            }
+        }
+
+        let bodies = ["json", "form", "body"]
+            .iter()
+            .filter(|k| args.get(**k).is_some_and(|v| !v.is_null()))
+            .count();
+        if bodies > 1 {
+            return Err("give at most one of 'json', 'form' and 'body'".to_string());
+        }
+        if let Some(json) = args.get("json").filter(|v| !v.is_null()) {
+            req = req.json(json);
+        } else if let Some(form) = args.get("form").filter(|v| !v.is_null()) {
+            let form = form.as_object().ok_or("'form' must be an object")?;
+            req = req
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(form_body(form));
+        } else if let Some(body) = args.get("body").filter(|v| !v.is_null()) {
+            let body = body.as_str().ok_or("'body' must be a string")?;
+            req = req.body(body.to_string());
+        }
+
+        if let Some(name) = args.get("credential").and_then(Value::as_str) {
+            let cred = self.credentials.get(name).ok_or_else(|| {
+                let known: Vec<&str> = self.credentials.keys().map(String::as_str).collect();
+                if known.is_empty() {
+                    format!("unknown credential '{name}' (none configured)")
+                } else {
+                    format!(
+                        "unknown credential '{name}' (configured: {})",
+                        known.join(", ")
+                    )
+                }
+            })?;
+            req = req.basic_auth(&cred.username, cred.password.as_deref());
+        }
+        Ok(req)
+    }
+}
+
+impl Tool for HttpRequestTool {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Make an HTTP request with any method (GET, POST, PUT, PATCH, DELETE, HEAD), optional \
+         headers and a JSON, form or raw body — for calling APIs. Use 'credential' to sign in \
+         with a named login from the config. Returns status, content type and the body \
+         (JSON pretty-printed)."
+    }
+
+    fn parameters(&self) -> Value {
+        let names: Vec<&str> = self.credentials.keys().map(String::as_str).collect();
+        let credential = if names.is_empty() {
+            "Named basic-auth credential from the config (none configured)".to_string()
+        } else {
+            format!(
+                "Named basic-auth credential from the config: {}",
+                names.join(", ")
+            )
+        };
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "URL (http or https)" },
+                "method": { "type": "string", "enum": METHODS, "description": "HTTP method (default GET)" },
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Extra request headers"
+                },
+                "json": { "description": "JSON body (sets Content-Type: application/json)" },
+                "form": {
+                    "type": "object",
+                    "description": "Form fields, sent as application/x-www-form-urlencoded"
+                },
+                "body": { "type": "string", "description": "Raw body (set Content-Type in headers)" },
+                "credential": { "type": "string", "description": credential },
+                "max_chars": { "type": "integer", "description": "Optional max characters of body to return" }
+            },
+            "required": ["url"]
+        })
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let max_chars = args
+            .get("max_chars")
+            .and_then(Value::as_u64)
+            .and_then(|n| u32::try_from(n).ok())
+            .unwrap_or(self.max_chars);
+        Box::pin(async move {
+            let req = match self.request(args) {
+                Ok(r) => r,
+                Err(e) => return ToolResult::error(e),
+            };
+            let res = match req.send().await {
+                Ok(r) => r,
+                Err(e) => return ToolResult::error(format!("request failed: {e}")),
+            };
+            let url = res.url().clone();
+            let status = res.status();
+            let headers = res.headers().clone();
+            let body = match res.bytes().await {
+                Ok(b) => b,
+                Err(e) => return ToolResult::error(format!("reading response failed: {e}")),
+            };
+            let (text, truncated) = truncate_chars(body_text(&headers, &body), max_chars as usize);
+
+            let content_type = headers
+                .get("content-type")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("none");
+            let mut out = format!(
+                "URL: {url}\nStatus: {status}\nContent-Type: {content_type}\nLength: {} bytes{}",
+                body.len(),
+                if truncated {
+                    format!(" (truncated to {max_chars} chars)")
+                } else {
+                    String::new()
+                }
+            );
+            if !text.trim().is_empty() {
+                out.push_str("\n\n");
+                out.push_str(&text);
+            }
+            if status.is_client_error() || status.is_server_error() {
+                ToolResult::error(out)
+            } else {
+                ToolResult::ok(out)
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::web::web_client;
+    use std::path::PathBuf;
+    use wiremock::matchers::{body_json, body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn ctx() -> ToolCtx {
+        ToolCtx::for_tests(PathBuf::from("/tmp"))
+    }
+
+    fn tool() -> HttpRequestTool {
+        let mut creds = BTreeMap::new();
+        creds.insert(
+            "home".to_string(),
+            HttpCredential {
+                username: "me".to_string(),
+                password: Some("hunter2".to_string()),
+            },
+        );
+        HttpRequestTool::new(web_client().unwrap(), creds, 1000)
+    }
+
+    #[tokio::test]
+    async fn sends_json_with_named_credential() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/lights"))
+            .and(header("authorization", "Basic bWU6aHVudGVyMg=="))
+            .and(header("x-room", "kitchen"))
+            .and(body_json(serde_json::json!({"on": true})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({"ok": 1})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let res = tool()
+            .execute(
+                &ctx(),
+                &serde_json::json!({
+                    "method": "post",
+                    "url": format!("{}/api/lights", server.uri()),
+                    "headers": {"X-Room": "kitchen"},
+                    "json": {"on": true},
+                    "credential": "home"
+                }),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(res.for_llm.contains("Status: 201 Created"));
+        assert!(
+            res.for_llm.ends_with("{\n  \"ok\": 1\n}"),
+            "{}",
+            res.for_llm
+        );
+        assert!(!res.for_llm.contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn sends_form_and_reports_http_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(header("content-type", "application/x-www-form-urlencoded"))
+            .and(body_string("priority=2&title=Buy+milk+%26+eggs"))
+            .respond_with(ResponseTemplate::new(422).set_body_string("bad due date"))
+            .mount(&server)
+            .await;
+
+        let res = tool()
+            .execute(
+                &ctx(),
+                &serde_json::json!({
+                    "method": "PUT",
+                    "url": server.uri(),
+                    "form": {"title": "Buy milk & eggs", "priority": 2}
+                }),
+            )
+            .await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("Status: 422"), "{}", res.for_llm);
+        assert!(res.for_llm.ends_with("bad due date"));
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments_are_errors() {
+        let t = tool();
+        for (args, expected) in [
+            (serde_json::json!({}), "missing or invalid 'url'"),
+            (
+                serde_json::json!({"url": "ftp://x.example"}),
+                "only http and https",
+            ),
+            (
+                serde_json::json!({"url": "http://x.example", "method": "TRACE"}),
+                "method must be one of",
+            ),
+            (
+                serde_json::json!({"url": "http://x.example", "json": {}, "body": "x"}),
+                "at most one of",
+            ),
+            (
+                serde_json::json!({"url": "http://x.example", "credential": "work"}),
+                "unknown credential 'work' (configured: home)",
+            ),
+        ] {
+            let res = t.execute(&ctx(), &args).await;
+            assert!(res.is_error);
+            assert!(res.for_llm.contains(expected), "{}", res.for_llm);
+        }
+    }
+}
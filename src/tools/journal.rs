@@ -0,0 +1,390 @@
+//! `journal` tool: guided journaling in the daily note. `prompts` offers a few questions that
+//! rotate day by day, `log` appends mood, gratitude, highlights and reflections under a
+//! "Journal" heading of the day's note (see `daily_note`), and `review` collects the past
+//! week's entries — read from the vault index — for a retrospective.
+//!
+//! Entries are written as Dataview-style inline fields (`- Mood:: 7/10`) so they stay easy to
+//! query in Obsidian.
+
+use std::sync::Arc;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde_json::Value;
+
+use crate::memory::db::{BrainDb, VaultFilter};
+use crate::tools::context::ToolCtx;
+use crate::tools::daily_note::{DailyNoteTool, parse_day, section};
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::search::search_with_fallback;
+
+/// Heading of the journal section in the daily note.
+const HEADING: &str = "Journal";
+/// Prompts offered per day.
+const PROMPTS_PER_DAY: usize = 3;
+const DEFAULT_REVIEW_DAYS: i64 = 7;
+const MAX_REVIEW_DAYS: i64 = 31;
+/// Keyword matches listed by `review` with a query.
+const MAX_MATCHES: usize = 10;
+
+const PROMPTS: &[&str] = &[
+    "What gave you energy today, and what drained it?",
+    "What are three things you are grateful for?",
+    "What was the best moment of the day?",
+    "What did you learn today?",
+    "What is on your mind that you have not said out loud?",
+    "What would make tomorrow a good day?",
+    "Who made a difference to your day, and how?",
+    "What did you avoid today, and why?",
+    "When did you feel most like yourself?",
+    "What are you looking forward to?",
+    "What would you do differently if you had today again?",
+    "What small win deserves to be noticed?",
+    "How did you look after your body today?",
+    "What worry can you let go of?",
+    "What surprised you today?",
+];
+
+/// The prompts for `day`; consecutive days get different ones and the cycle repeats every
+/// `PROMPTS.len() / PROMPTS_PER_DAY` days.
+fn prompts_for(day: NaiveDate) -> Vec<&'static str> {
+    let first = day.num_days_from_ce() as usize * PROMPTS_PER_DAY;
+    (0..PROMPTS_PER_DAY)
+        .map(|i| PROMPTS[(first + i) % PROMPTS.len()])
+        .collect()
+}
+
+/// Strings from a string or an array of strings, trimmed, empty ones dropped.
+fn string_list(v: Option<&Value>) -> Vec<String> {
+    let items: Vec<&str> = match v {
+        Some(Value::String(s)) => vec![s.as_str()],
+        Some(Value::Array(a)) => a.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    items
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The lines `log` appends for `args`; None when there is nothing to log.
+fn entry(args: &Value) -> Option<String> {
+    let text = |key: &str| {
+        args.get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    };
+    let mut lines = Vec::new();
+    if let Some(mood) = args.get("mood").filter(|m| !m.is_null()) {
+        let mood = match mood {
+            Value::String(s) => s.trim().to_string(),
+            other => other.to_string(),
+        };
+        if !mood.is_empty() {
+            lines.push(format!("- Mood:: {mood}"));
+        }
+    }
+    for g in string_list(args.get("gratitude")) {
+        lines.push(format!("- Gratitude:: {g}"));
+    }
+    for h in string_list(args.get("highlights")) {
+        lines.push(format!("- Highlight:: {h}"));
+    }
+    if let Some(reflection) = text("text") {
+        let reflection = reflection.lines().collect::<Vec<_>>().join(" ");
+        match text("prompt") {
+            Some(prompt) => lines.push(format!("- *{prompt}* {reflection}")),
+            None => lines.push(format!("- {reflection}")),
+        }
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+pub struct JournalTool {
+    notes: DailyNoteTool,
+    db: Arc<BrainDb>,
+}
+
+impl JournalTool {
+    /// `notes` decides where daily notes live and how new ones are made.
+    pub fn new(notes: DailyNoteTool, db: Arc<BrainDb>) -> Self {
+        Self { notes, db }
+    }
+
+    /// Journal sections of the `days` notes ending with `last`, then keyword matches for
+    /// `query` among all daily notes.
+    async fn review(
+        &self,
+        ctx: &ToolCtx,
+        last: NaiveDate,
+        days: i64,
+        query: Option<String>,
+    ) -> Result<String, String> {
+        let first = last - Duration::days(days - 1);
+        let paths: Vec<(NaiveDate, String)> = (0..days)
+            .map(|i| first + Duration::days(i))
+            .map(|d| (d, self.notes.note_path(d)))
+            .collect();
+        let db = Arc::clone(&self.db);
+        let lookup = paths.iter().map(|(_, p)| p.clone()).collect::<Vec<_>>();
+        let dir = format!("{}/", self.notes.dir());
+        let (indexed, matches) = tokio::task::spawn_blocking(move || {
+            let indexed = lookup
+                .iter()
+                .map(|p| db.get_vault_content(p).ok().flatten())
+                .collect::<Vec<_>>();
+            let matches = match &query {
+                Some(q) => search_with_fallback(&db, q, &VaultFilter::default(), 50)
+                    .map_err(|e| format!("searching the vault failed: {e}"))?
+                    .into_iter()
+                    .filter(|(p, _)| p.starts_with(&dir) && !lookup.contains(p))
+                    .take(MAX_MATCHES)
+                    .collect(),
+                None => Vec::new(),
+            };
+            Ok::<_, String>((indexed, matches))
+        })
+        .await
+        .map_err(|e| format!("journal task error: {e}"))??;
+
+        let mut entries = Vec::new();
+        for ((day, rel), content) in paths.iter().zip(indexed) {
+            // Today's note is often newer than the index.
+            let content = match content {
+                Some(c) if *day != last => Some(c),
+                _ => match resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await {
+                    Ok(path) => tokio::fs::read_to_string(path).await.ok(),
+                    Err(_) => None,
+                },
+            };
+            if let Some(body) = content
+                .and_then(|c| section(&c, HEADING))
+                .filter(|b| !b.is_empty())
+            {
+                entries.push(format!("### {}\n{body}", day.format("%a %-d %b")));
+            }
+        }
+
+        let mut out = format!(
+            "Journal, {} – {} ({} of {days} days with entries)",
+            first.format("%a %-d %b"),
+            last.format("%a %-d %b %Y"),
+            entries.len()
+        );
+        if entries.is_empty() {
+            out.push('.');
+        } else {
+            out.push_str(":\n\n");
+            out.push_str(&entries.join("\n\n"));
+        }
+        if !matches.is_empty() {
+            out.push_str("\n\nMatching entries from other days:");
+            for (path, snippet) in matches {
+                out.push_str(&format!("\n- {path}: {}", snippet.replace('\n', " ")));
+            }
+        }
+        Ok(out)
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let action = args.get("action").and_then(Value::as_str).unwrap_or("");
+        let date_arg = args.get("date").and_then(Value::as_str).unwrap_or("today");
+        let day = parse_day(date_arg, self.notes.today())
+            .ok_or_else(|| format!("invalid date '{date_arg}' (today, yesterday or YYYY-MM-DD)"))?;
+
+        match action {
+            "prompts" => {
+                let list: Vec<String> = prompts_for(day).iter().map(|p| format!("- {p}")).collect();
+                Ok(format!(
+                    "Journal prompts for {}:\n{}",
+                    day.format("%a %-d %b"),
+                    list.join("\n")
+                ))
+            }
+            "log" => {
+                let entry = entry(args)
+                    .ok_or("log needs at least one of mood, gratitude, highlights or text")?;
+                let (rel, created) = self.notes.append(ctx, day, HEADING, &entry).await?;
+                let how = if created {
+                    " (created from the template)"
+                } else {
+                    ""
+                };
+                Ok(format!("Added to the journal in {rel}{how}:\n{entry}"))
+            }
+            "review" => {
+                let days = args
+                    .get("days")
+                    .and_then(Value::as_i64)
+                    .unwrap_or(DEFAULT_REVIEW_DAYS)
+                    .clamp(1, MAX_REVIEW_DAYS);
+                let query = args
+                    .get("query")
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|q| !q.is_empty())
+                    .map(str::to_string);
+                self.review(ctx, day, days, query).await
+            }
+            "" => Err("missing 'action' (prompts, log or review)".into()),
+            other => Err(format!("unknown action '{other}' (prompts, log or review)")),
+        }
+    }
+}
+
+impl Tool for JournalTool {
+    fn name(&self) -> &str {
+        "journal"
+    }
+
+    fn description(&self) -> &str {
+        "Guided journaling in the user's daily note. prompts: today's reflection questions \
+         (ask one or two, not all at once). log: save mood, gratitude, highlights and a \
+         reflection under the note's Journal heading. review: the past week's journal \
+         entries (optionally also searching older ones for a query) for a weekly \
+         retrospective — summarise patterns in mood and highlights for the user."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["prompts", "log", "review"] },
+                "date": { "type": "string", "description": "Day: today (default), yesterday or YYYY-MM-DD; for review the last day of the range" },
+                "mood": { "type": "string", "description": "Mood, e.g. \"7/10, tired but content\" (log)" },
+                "gratitude": { "type": "array", "items": { "type": "string" }, "description": "Things the user is grateful for (log)" },
+                "highlights": { "type": "array", "items": { "type": "string" }, "description": "Highlights of the day (log)" },
+                "text": { "type": "string", "description": "Free reflection, in the user's words (log)" },
+                "prompt": { "type": "string", "description": "The prompt that text answers (log)" },
+                "days": { "type": "integer", "description": "Days to review, 1-31 (default 7)" },
+                "query": { "type": "string", "description": "Also search older daily notes for these words (review)" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    fn tool(workspace: &Path) -> (JournalTool, Arc<BrainDb>) {
+        let db = Arc::new(BrainDb::open(workspace).unwrap());
+        let notes = DailyNoteTool::new(None, chrono_tz::Europe::London, None);
+        (JournalTool::new(notes, Arc::clone(&db)), db)
+    }
+
+    #[test]
+    fn prompts_rotate_daily() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let today = prompts_for(day);
+        let tomorrow = prompts_for(day + Duration::days(1));
+        assert_eq!(today.len(), PROMPTS_PER_DAY);
+        assert!(today.iter().all(|p| !tomorrow.contains(p)));
+        assert_eq!(prompts_for(day + Duration::days(5)), today);
+    }
+
+    #[test]
+    fn entry_lines() {
+        let args = serde_json::json!({
+            "mood": 7,
+            "gratitude": ["sun", " "],
+            "highlights": "ran 5 km",
+            "prompt": "What surprised you today?",
+            "text": "How calm\nI was."
+        });
+        assert_eq!(
+            entry(&args).unwrap(),
+            "- Mood:: 7\n- Gratitude:: sun\n- Highlight:: ran 5 km\n\
+             - *What surprised you today?* How calm I was."
+        );
+        assert_eq!(entry(&serde_json::json!({"gratitude": []})), None);
+    }
+
+    #[tokio::test]
+    async fn log_then_review_the_week() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tool, db) = tool(dir.path());
+        let ctx = ctx(dir.path());
+
+        let args = serde_json::json!({"action": "log", "date": "2026-10-16", "mood": "good", "gratitude": ["coffee"]});
+        let res = tool.execute(&ctx, &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let note = std::fs::read_to_string(dir.path().join("Daily log/2026-10-16.md")).unwrap();
+        assert!(
+            note.contains("## Journal\n- Mood:: good\n- Gratitude:: coffee\n"),
+            "{note}"
+        );
+
+        // An earlier day known only to the index, and an older one matching the query.
+        db.upsert_vault_entry(
+            "Daily log/2026-10-12.md",
+            "# Mon\n## Journal\n- Mood:: tired\n## Log\n- x\n",
+            1,
+        )
+        .unwrap();
+        db.upsert_vault_entry(
+            "Daily log/2026-09-01.md",
+            "## Journal\n- Highlight:: climbing with Sam\n",
+            1,
+        )
+        .unwrap();
+
+        let args =
+            serde_json::json!({"action": "review", "date": "2026-10-16", "query": "climbing"});
+        let res = tool.execute(&ctx, &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm.starts_with(
+                "Journal, Sat 10 Oct – Fri 16 Oct 2026 (2 of 7 days with entries):\n\n\
+                 ### Mon 12 Oct\n- Mood:: tired\n\n### Fri 16 Oct\n- Mood:: good\n"
+            ),
+            "{}",
+            res.for_llm
+        );
+        assert!(
+            res.for_llm
+                .contains("Matching entries from other days:\n- Daily log/2026-09-01.md:"),
+            "{}",
+            res.for_llm
+        );
+    }
+
+    #[tokio::test]
+    async fn bad_arguments() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tool, _db) = tool(dir.path());
+        let ctx = ctx(dir.path());
+        let res = tool
+            .execute(&ctx, &serde_json::json!({"action": "log"}))
+            .await;
+        assert_eq!(
+            res.for_llm,
+            "log needs at least one of mood, gratitude, highlights or text"
+        );
+        let res = tool.execute(&ctx, &serde_json::json!({})).await;
+        assert!(res.is_error);
+    }
+}
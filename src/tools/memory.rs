@@ -0,0 +1,245 @@
+//! `memory` tool: long-term facts about the user, kept in BrainDb (`memories`).
+//!
+//! Facts survive `/clear` and new sessions. The agent saves them when the user states
+//! something lasting ("I'm vegetarian"), recalls or forgets them on request, and the most
+//! relevant ones are added to the system prompt every turn (see `agent::process_message`).
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::memory::db::{BrainDb, MemoryFact};
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Results returned by `recall` when no limit is given.
+const DEFAULT_LIMIT: usize = 10;
+
+pub struct MemoryTool {
+    db: Arc<BrainDb>,
+}
+
+impl MemoryTool {
+    pub fn new(db: Arc<BrainDb>) -> Self {
+        Self { db }
+    }
+}
+
+impl Tool for MemoryTool {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    fn description(&self) -> &str {
+        "Long-term memory about the user that persists across sessions. \
+         save: store a lasting fact or preference the user tells you (e.g. 'I'm vegetarian'). \
+         recall: search saved facts (omit query for the newest). \
+         forget: delete a fact by id when it is wrong or the user asks."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["save", "recall", "forget"],
+                    "description": "save a fact, recall facts, or forget one"
+                },
+                "fact": {
+                    "type": "string",
+                    "description": "The fact to save, as a short self-contained sentence (save)"
+                },
+                "category": {
+                    "type": "string",
+                    "description": "Optional grouping, e.g. preference, health, people, work (save)"
+                },
+                "query": {
+                    "type": "string",
+                    "description": "Words to search for (recall)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Max facts to return (recall, default 10)",
+                    "minimum": 1,
+                    "maximum": 50
+                },
+                "id": {
+                    "type": "integer",
+                    "description": "Fact id from recall (forget)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let db = Arc::clone(&self.db);
+        let args = args.clone();
+        let source = ctx
+            .chat_id
+            .map(|id| id.to_string())
+            .or_else(|| ctx.channel.clone())
+            .unwrap_or_default();
+
+        Box::pin(async move {
+            let action = args
+                .get("action")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            match action.as_str() {
+                "save" => {
+                    let fact = args
+                        .get("fact")
+                        .and_then(Value::as_str)
+                        .map(str::trim)
+                        .unwrap_or("")
+                        .to_string();
+                    if fact.is_empty() {
+                        return ToolResult::error("save requires non-empty 'fact'");
+                    }
+                    let category = args
+                        .get("category")
+                        .and_then(Value::as_str)
+                        .map(|c| c.trim().to_lowercase())
+                        .unwrap_or_default();
+                    let result = tokio::task::spawn_blocking(move || {
+                        db.save_memory(&fact, &category, &source)
+                    })
+                    .await;
+                    match result {
+                        Ok(Ok(id)) => ToolResult::ok(format!("Saved memory #{id}.")),
+                        Ok(Err(e)) => ToolResult::error(format!("memory save failed: {e}")),
+                        Err(e) => ToolResult::error(format!("memory task error: {e}")),
+                    }
+                }
+                "recall" => {
+                    let query = args
+                        .get("query")
+                        .and_then(Value::as_str)
+                        .unwrap_or("")
+                        .to_string();
+                    let limit = args
+                        .get("limit")
+                        .and_then(Value::as_u64)
+                        .map_or(DEFAULT_LIMIT, |v| (v as usize).clamp(1, 50));
+                    let result =
+                        tokio::task::spawn_blocking(move || db.search_memories(&query, limit))
+                            .await;
+                    match result {
+                        Ok(Ok(facts)) if facts.is_empty() => {
+                            ToolResult::ok("No matching memories.")
+                        }
+                        Ok(Ok(facts)) => ToolResult::ok(format_facts(&facts)),
+                        Ok(Err(e)) => ToolResult::error(format!("memory recall failed: {e}")),
+                        Err(e) => ToolResult::error(format!("memory task error: {e}")),
+                    }
+                }
+                "forget" => {
+                    let id = match args.get("id") {
+                        Some(Value::Number(n)) => n.as_i64(),
+                        Some(Value::String(s)) => s.trim().trim_start_matches('#').parse().ok(),
+                        _ => None,
+                    };
+                    let Some(id) = id else {
+                        return ToolResult::error("forget requires 'id' (from recall)");
+                    };
+                    match tokio::task::spawn_blocking(move || db.forget_memory(id)).await {
+                        Ok(Ok(true)) => ToolResult::ok(format!("Forgot memory #{id}.")),
+                        Ok(Ok(false)) => ToolResult::error(format!("no memory #{id}")),
+                        Ok(Err(e)) => ToolResult::error(format!("memory forget failed: {e}")),
+                        Err(e) => ToolResult::error(format!("memory task error: {e}")),
+                    }
+                }
+                other => ToolResult::error(format!(
+                    "unknown action '{other}' (use save, recall or forget)"
+                )),
+            }
+        })
+    }
+}
+
+/// One line per fact: `#id [category] fact`. Also used for the system prompt.
+pub fn format_facts(facts: &[MemoryFact]) -> String {
+    facts
+        .iter()
+        .map(|f| {
+            if f.category.is_empty() {
+                format!("#{} {}", f.id, f.fact)
+            } else {
+                format!("#{} [{}] {}", f.id, f.category, f.fact)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn ctx() -> ToolCtx {
+        ToolCtx {
+            chat_id: Some(42),
+            channel: Some("telegram".into()),
+            ..ToolCtx::for_tests(std::env::temp_dir())
+        }
+    }
+
+    #[tokio::test]
+    async fn save_recall_forget() {
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let tool = MemoryTool::new(Arc::clone(&db));
+
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"action": "save", "fact": "I'm vegetarian", "category": "Diet"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let saved = db.search_memories("vegetarian", 1).unwrap();
+        assert_eq!(saved[0].category, "diet");
+        assert_eq!(saved[0].source, "42");
+
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"action": "recall", "query": "vegetarian food"}),
+            )
+            .await;
+        let id = saved[0].id;
+        assert_eq!(res.for_llm, format!("#{id} [diet] I'm vegetarian"));
+
+        let res = tool
+            .execute(&ctx(), &serde_json::json!({"action": "forget", "id": id}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let res = tool
+            .execute(&ctx(), &serde_json::json!({"action": "recall"}))
+            .await;
+        assert_eq!(res.for_llm, "No matching memories.");
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments_are_errors() {
+        let tmp = TempDir::new().unwrap();
+        let tool = MemoryTool::new(Arc::new(BrainDb::open(tmp.path()).unwrap()));
+        for args in [
+            serde_json::json!({"action": "save", "fact": "  "}),
+            serde_json::json!({"action": "forget"}),
+            serde_json::json!({"action": "forget", "id": 999}),
+            serde_json::json!({"action": "dance"}),
+        ] {
+            assert!(tool.execute(&ctx(), &args).await.is_error, "{args}");
+        }
+    }
+}
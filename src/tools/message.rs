@@ -4,7 +4,7 @@ use std::sync::atomic::Ordering;
 
 use serde_json::Value;
 
-use crate::telegram::OutboundMsg;
+use crate::telegram::{OutboundKind, OutboundMsg};
 use crate::tools::context::ToolCtx;
 use crate::tools::registry::{BoxFuture, Tool};
 use crate::tools::result::ToolResult;
@@ -32,7 +32,8 @@ impl Tool for MessageTool {
         serde_json::json!({
             "type": "object",
             "properties": {
-                "text": { "type": "string", "description": "Message text to send to user" }
+                "text": { "type": "string", "description": "Message text to send to user" },
+                "silent": { "type": "boolean", "description": "Deliver without a notification sound (non-urgent updates)" }
             },
             "required": ["text"]
         })
@@ -53,6 +54,7 @@ impl Tool for MessageTool {
             let Some(chat_id) = ctx.chat_id else {
                 return ToolResult::error("no chat_id (message tool unavailable)");
             };
+            let silent = args.get("silent").and_then(Value::as_bool).unwrap_or(false);
             let channel = ctx
                 .channel
                 .clone()
@@ -61,6 +63,9 @@ impl Tool for MessageTool {
                 chat_id,
                 text,
                 channel,
+                kind: OutboundKind::Text,
+                thread_id: ctx.thread_id,
+                disable_notification: silent,
             };
             match tx.try_send(msg) {
                 Ok(()) => {
@@ -0,0 +1,359 @@
+//! `ocr` tool: text from an image in the workspace (a photographed receipt or whiteboard),
+//! via tesseract when installed or a vision LLM call. With `save` the text is written to a
+//! Markdown note beside the image, so the vault index makes it searchable.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::config::OcrConfig;
+use crate::llm::{GenParams, ImagePart, LlmProvider, Message, Role};
+use crate::shell::{self, escape_sh};
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Vision APIs take images up to about 20 MB; phone photos are well under.
+const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+const TESSERACT_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_LANGUAGES: &str = "eng";
+
+const VISION_PROMPT: &str = "Transcribe all text in this image exactly as written, in reading \
+    order, keeping line breaks. Keep each receipt or table row (item and amount) on one line. \
+    Output only the text, with no commentary; output nothing if there is no text.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    /// Tesseract when installed, otherwise vision.
+    Auto,
+    Tesseract,
+    Vision,
+}
+
+/// Media type of a supported image, from its extension.
+fn media_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        _ => return None,
+    })
+}
+
+/// Run `program` (tesseract) on `path` through [`shell::run`], like the `exec` tool:
+/// `std::process` does not work on iSH. None when the shell cannot find the program (exit
+/// code 127).
+fn run_tesseract(program: &str, path: &Path, languages: &str) -> Result<Option<String>, String> {
+    let script = format!(
+        "timeout -s KILL {TESSERACT_TIMEOUT_SECS} {} {} stdout -l {}",
+        escape_sh(program),
+        escape_sh(&path.to_string_lossy()),
+        escape_sh(languages),
+    );
+    let out = shell::run(&script, None)?;
+    match out.status.code() {
+        Some(0) => Ok(Some(String::from_utf8_lossy(&out.stdout).into_owned())),
+        Some(127) => Ok(None),
+        Some(137) | None => Err(format!(
+            "tesseract did not finish within {TESSERACT_TIMEOUT_SECS}s"
+        )),
+        Some(code) => Err(format!(
+            "tesseract failed (exit code {code}): {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )),
+    }
+}
+
+pub struct OcrTool {
+    llm: Arc<dyn LlmProvider>,
+    model: String,
+    backend: Backend,
+    languages: String,
+    tesseract: String,
+}
+
+impl OcrTool {
+    /// `model` is the vision model unless `[tools.ocr] model` overrides it.
+    pub fn new(llm: Arc<dyn LlmProvider>, model: &str, config: Option<&OcrConfig>) -> Self {
+        let backend = match config.and_then(|c| c.backend.as_deref()).map(str::trim) {
+            Some(b) if b.eq_ignore_ascii_case("tesseract") => Backend::Tesseract,
+            Some(b) if b.eq_ignore_ascii_case("vision") => Backend::Vision,
+            _ => Backend::Auto,
+        };
+        Self {
+            llm,
+            model: config
+                .and_then(|c| c.model.clone())
+                .unwrap_or_else(|| model.to_string()),
+            backend,
+            languages: config
+                .and_then(|c| c.languages.clone())
+                .unwrap_or_else(|| DEFAULT_LANGUAGES.to_string()),
+            tesseract: "tesseract".to_string(),
+        }
+    }
+
+    /// Tesseract's text for `path`; None when tesseract is not installed.
+    async fn tesseract(&self, path: &Path) -> Result<Option<String>, String> {
+        let (program, path, languages) = (
+            self.tesseract.clone(),
+            path.to_path_buf(),
+            self.languages.clone(),
+        );
+        let task = tokio::task::spawn_blocking(move || run_tesseract(&program, &path, &languages));
+        // `timeout` in the shell kills tesseract; this only guards against a hung shell.
+        let limit = Duration::from_secs(TESSERACT_TIMEOUT_SECS + 5);
+        match tokio::time::timeout(limit, task).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => Err(format!("tesseract task error: {e}")),
+            Err(_) => Err("tesseract did not finish in time".into()),
+        }
+    }
+
+    async fn vision(&self, media_type: &str, bytes: &[u8]) -> Result<String, String> {
+        let message = Message {
+            role: Role::User,
+            content: VISION_PROMPT.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            images: vec![ImagePart::from_bytes(media_type, bytes)],
+        };
+        let params = GenParams {
+            temperature: Some(0.0),
+            ..GenParams::default()
+        };
+        self.llm
+            .chat_with_params(&[message], &[], &self.model, &params)
+            .await
+            .map(|r| r.content)
+            .map_err(|e| format!("vision OCR failed: {e}"))
+    }
+
+    /// The image's text and the backend that read it.
+    async fn extract(
+        &self,
+        path: &Path,
+        media_type: &str,
+    ) -> Result<(String, &'static str), String> {
+        if self.backend != Backend::Vision {
+            match self.tesseract(path).await? {
+                Some(text) => return Ok((text, "tesseract")),
+                None if self.backend == Backend::Tesseract => {
+                    return Err(
+                        "tesseract is not installed (set [tools.ocr] backend = \"vision\" \
+                                to use the LLM instead)"
+                            .into(),
+                    );
+                }
+                None => {}
+            }
+        }
+        let bytes = tokio::fs::read(path)
+            .await
+            .map_err(|e| format!("reading image failed: {e}"))?;
+        Ok((self.vision(media_type, &bytes).await?, "vision"))
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let rel = args
+            .get("path")
+            .and_then(Value::as_str)
+            .ok_or("missing 'path' (an image in the workspace)")?;
+        let path = resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        let media_type = media_type(&path).ok_or("ocr reads png, jpg, webp or gif images")?;
+        let meta = tokio::fs::metadata(&path)
+            .await
+            .map_err(|e| format!("{rel}: {e}"))?;
+        if meta.len() > MAX_IMAGE_BYTES {
+            return Err(format!(
+                "{rel} is {} MB; the limit is {} MB",
+                meta.len() / (1024 * 1024),
+                MAX_IMAGE_BYTES / (1024 * 1024)
+            ));
+        }
+
+        let (text, backend) = self.extract(&path, media_type).await?;
+        let text = text.trim().replace('\u{c}', "");
+        if text.is_empty() {
+            return Ok(format!("No text found in {rel}."));
+        }
+        if !args.get("save").and_then(Value::as_bool).unwrap_or(false) {
+            return Ok(text);
+        }
+
+        // Beside the image, embedding it so the note shows what was read.
+        let note_rel = Path::new(rel).with_extension("md");
+        let note = path.with_extension("md");
+        if tokio::fs::try_exists(&note).await.unwrap_or(false) {
+            return Err(format!(
+                "{} already exists; read it or move it first",
+                note_rel.display()
+            ));
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        tokio::fs::write(&note, format!("![[{name}]]\n\n{text}\n"))
+            .await
+            .map_err(|e| format!("writing {} failed: {e}", note_rel.display()))?;
+        Ok(format!(
+            "Saved the text ({backend}) to {}:\n\n{text}",
+            note_rel.display()
+        ))
+    }
+}
+
+impl Tool for OcrTool {
+    fn name(&self) -> &str {
+        "ocr"
+    }
+
+    fn description(&self) -> &str {
+        "Read the text in an image file in the workspace (receipt, whiteboard, screenshot, \
+         document photo). With save=true the text is also written to a .md note beside the image \
+         so it becomes searchable."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Image path (png, jpg, webp, gif), e.g. Inbox/receipt.jpg" },
+                "save": { "type": "boolean", "description": "Also write the text to <image name>.md beside it (default false)" }
+            },
+            "required": ["path"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    use crate::llm::{LlmError, LlmResponse, ToolDef};
+
+    /// Answers with a fixed transcription and records the model and images it was sent.
+    #[derive(Default)]
+    struct FakeVision {
+        seen: Mutex<Vec<(String, Vec<ImagePart>)>>,
+    }
+
+    impl LlmProvider for FakeVision {
+        fn chat_with_params<'a>(
+            &'a self,
+            messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            let images = messages.iter().flat_map(|m| m.images.clone()).collect();
+            self.seen.lock().unwrap().push((model.to_string(), images));
+            Box::pin(async {
+                Ok(LlmResponse {
+                    content: "\nTESCO\nMilk 1.20\nTOTAL 1.20\n".to_string(),
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".to_string(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    fn tool(llm: Arc<FakeVision>, backend: &str) -> OcrTool {
+        let config = OcrConfig {
+            backend: Some(backend.to_string()),
+            model: Some("vision-model".to_string()),
+            languages: None,
+        };
+        let mut tool = OcrTool::new(llm, "chat-model", Some(&config));
+        tool.tesseract = "icrab-test-no-such-tesseract".to_string();
+        tool
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_vision_and_saves_a_note() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("Inbox")).unwrap();
+        std::fs::write(dir.path().join("Inbox/receipt.JPG"), b"\xff\xd8jpeg").unwrap();
+        let llm = Arc::new(FakeVision::default());
+        let tool = tool(Arc::clone(&llm), "auto");
+
+        let args = serde_json::json!({"path": "Inbox/receipt.JPG"});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(res.for_llm, "TESCO\nMilk 1.20\nTOTAL 1.20");
+        {
+            let seen = llm.seen.lock().unwrap();
+            assert_eq!(seen[0].0, "vision-model");
+            assert_eq!(
+                seen[0].1,
+                [ImagePart::from_bytes("image/jpeg", b"\xff\xd8jpeg")]
+            );
+        }
+
+        let args = serde_json::json!({"path": "Inbox/receipt.JPG", "save": true});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert!(
+            res.for_llm
+                .starts_with("Saved the text (vision) to Inbox/receipt.md:")
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("Inbox/receipt.md")).unwrap(),
+            "![[receipt.JPG]]\n\nTESCO\nMilk 1.20\nTOTAL 1.20\n"
+        );
+        // An existing note is never overwritten.
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("already exists"));
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_tesseract_and_non_images() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("board.png"), b"png").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"text").unwrap();
+        let llm = Arc::new(FakeVision::default());
+        let tool = tool(Arc::clone(&llm), "tesseract");
+
+        let res = tool
+            .execute(&ctx(dir.path()), &serde_json::json!({"path": "board.png"}))
+            .await;
+        assert!(res.is_error);
+        assert!(res.for_llm.starts_with("tesseract is not installed"));
+
+        let res = tool
+            .execute(&ctx(dir.path()), &serde_json::json!({"path": "notes.txt"}))
+            .await;
+        assert_eq!(res.for_llm, "ocr reads png, jpg, webp or gif images");
+        let res = tool
+            .execute(&ctx(dir.path()), &serde_json::json!({"path": "../x.png"}))
+            .await;
+        assert!(res.is_error);
+        assert!(llm.seen.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,822 @@
+//! `diff_files` and `apply_patch` tools: unified diffs, so the agent can show a change for
+//! review and then apply multi-hunk edits in one step.
+//!
+//! Diffs are line-based (Myers, with common prefix and suffix trimmed first; very different
+//! inputs fall back to one replace hunk). Patches are read leniently, since models rarely get
+//! hunk line counts right: counts are ignored, a hunk runs until the next header, and each
+//! hunk is placed where its context and removed lines match — nearest to the line its header
+//! names, after the previous hunk, falling back to ignoring trailing whitespace. Nothing is
+//! written unless every hunk of every file applies.
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const DEFAULT_CONTEXT: usize = 3;
+/// Past this many differing lines the diff is shown as a single replace hunk (bounds memory).
+const MAX_EDITS: isize = 1000;
+const NO_NEWLINE: &str = "\\ No newline at end of file";
+
+// ---------------------------------------------------------------------------
+// Diff
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// Line edits turning `a` into `b`.
+fn line_edits(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut edits = vec![Edit::Keep; prefix];
+    match myers(a_mid, b_mid) {
+        Some(mid) => edits.extend(mid),
+        None => {
+            edits.extend(std::iter::repeat_n(Edit::Delete, a_mid.len()));
+            edits.extend(std::iter::repeat_n(Edit::Insert, b_mid.len()));
+        }
+    }
+    edits.extend(std::iter::repeat_n(Edit::Keep, suffix));
+    edits
+}
+
+/// Shortest edit script (Myers 1986); None when it needs more than [`MAX_EDITS`] edits.
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Edit>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max + 1;
+    let mut v = vec![0isize; (2 * max + 3) as usize];
+    // v[-d..=d] before each round, for backtracking.
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    for d in 0..=max.min(MAX_EDITS) {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let (prev_x, prev_y) = if d == 0 {
+            (0, 0)
+        } else {
+            let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            (at(prev_k), at(prev_k) - prev_k)
+        };
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert);
+                y -= 1;
+            } else {
+                edits.push(Edit::Delete);
+                x -= 1;
+            }
+        }
+    }
+    edits.reverse();
+    edits
+}
+
+/// `-start,count` of a hunk header (GNU style: `,1` omitted, empty ranges name the line
+/// before).
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{count}", start + 1),
+    }
+}
+
+/// Unified diff from `old` to `new` with `context` lines around changes; empty when equal.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+) -> String {
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    let edits = line_edits(&a, &b);
+
+    // Entry ranges of hunks; changes closer than 2 * context share a hunk.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in edits.iter().enumerate().filter(|(_, e)| **e != Edit::Keep) {
+        let start = i.saturating_sub(context);
+        let end = (i + 1 + context).min(edits.len());
+        match hunks.last_mut() {
+            Some(h) if start <= h.1 => h.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    // Lines of a and b before each entry.
+    let mut pos = Vec::with_capacity(edits.len() + 1);
+    let (mut ai, mut bi) = (0, 0);
+    for e in &edits {
+        pos.push((ai, bi));
+        match e {
+            Edit::Keep => (ai, bi) = (ai + 1, bi + 1),
+            Edit::Delete => ai += 1,
+            Edit::Insert => bi += 1,
+        }
+    }
+    pos.push((ai, bi));
+
+    let mut out = format!("--- {old_label}\n+++ {new_label}\n");
+    for (start, end) in hunks {
+        let (a_start, b_start) = pos[start];
+        let (a_end, b_end) = pos[end];
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(a_start, a_end - a_start),
+            hunk_range(b_start, b_end - b_start)
+        ));
+        for i in start..end {
+            let (ai, bi) = pos[i];
+            let (sign, line) = match edits[i] {
+                Edit::Keep => (' ', a[ai]),
+                Edit::Delete => ('-', a[ai]),
+                Edit::Insert => ('+', b[bi]),
+            };
+            out.push(sign);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push('\n');
+                out.push_str(NO_NEWLINE);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Patch
+// ---------------------------------------------------------------------------
+
+/// One `@@` hunk: lines it expects (context and removed) and lines it leaves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hunk {
+    /// 1-based start line from the header (a hint for placement).
+    pub old_start: usize,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+    pub old_missing_newline: bool,
+    pub new_missing_newline: bool,
+}
+
+/// Hunks for one file. A `None` path is `/dev/null` (file created or deleted).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilePatch {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// Path the patch writes (or deletes).
+    pub fn target(&self) -> Option<&str> {
+        self.new_path.as_deref().or(self.old_path.as_deref())
+    }
+}
+
+/// Path from a `---`/`+++` line: tab-separated timestamp and git's `a/`/`b/` prefix removed.
+fn header_path(rest: &str) -> Option<String> {
+    let path = rest.split('\t').next().unwrap_or("").trim();
+    if path == "/dev/null" || path.is_empty() {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// Parse a unified diff. A patch with hunks but no `---`/`+++` header yields one
+/// [`FilePatch`] without paths.
+pub fn parse_patch(patch: &str) -> Result<Vec<FilePatch>, String> {
+    let lines: Vec<&str> = patch
+        .lines()
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some(old) = line.strip_prefix("--- ")
+            && let Some(new) = lines.get(i + 1).and_then(|l| l.strip_prefix("+++ "))
+        {
+            files.push(FilePatch {
+                old_path: header_path(old),
+                new_path: header_path(new),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+        let Some(header) = line.strip_prefix("@@") else {
+            // diff --git, index, mode lines and commentary.
+            i += 1;
+            continue;
+        };
+        let old_start = header
+            .trim_start()
+            .strip_prefix('-')
+            .and_then(|r| r.split([',', ' ']).next())
+            .and_then(|n| n.parse::<usize>().ok())
+            .ok_or_else(|| format!("malformed hunk header: {line}"))?;
+        if files.is_empty() {
+            files.push(FilePatch::default());
+        }
+        i += 1;
+
+        let start = i;
+        while i < lines.len() {
+            let l = lines[i];
+            let is_file_header =
+                l.starts_with("--- ") && lines.get(i + 1).is_some_and(|n| n.starts_with("+++ "));
+            if l.starts_with("@@") || l.starts_with("diff ") || is_file_header {
+                break;
+            }
+            if !(l.is_empty() || l.starts_with([' ', '-', '+', '\\'])) {
+                break;
+            }
+            i += 1;
+        }
+        let mut body = &lines[start..i];
+        // Blank lines after the last hunk line are separators, not empty context.
+        while body.last().is_some_and(|l| l.is_empty()) {
+            body = &body[..body.len() - 1];
+        }
+
+        let mut hunk = Hunk {
+            old_start,
+            ..Default::default()
+        };
+        // Which sides the previous line belonged to, for "\ No newline at end of file".
+        let mut last = (false, false);
+        for l in body {
+            match l.chars().next() {
+                Some('\\') => {
+                    hunk.old_missing_newline |= last.0;
+                    hunk.new_missing_newline |= last.1;
+                    continue;
+                }
+                Some('-') => hunk.old.push(l[1..].to_string()),
+                Some('+') => hunk.new.push(l[1..].to_string()),
+                // Context; editors often strip the space of blank context lines.
+                _ => {
+                    let text = l.get(1..).unwrap_or("").to_string();
+                    hunk.old.push(text.clone());
+                    hunk.new.push(text);
+                }
+            }
+            last = match l.chars().next() {
+                Some('-') => (true, false),
+                Some('+') => (false, true),
+                _ => (true, true),
+            };
+        }
+        if hunk.old.is_empty() && hunk.new.is_empty() {
+            return Err(format!("empty hunk: {line}"));
+        }
+        files.last_mut().expect("pushed above").hunks.push(hunk);
+    }
+    files.retain(|f| !f.hunks.is_empty());
+    if files.is_empty() {
+        return Err("no hunks found (expected a unified diff with @@ headers)".to_string());
+    }
+    Ok(files)
+}
+
+/// Where `block` matches `lines` at or after `min`, nearest to `expected`: exactly, else
+/// ignoring trailing whitespace.
+fn find_block(lines: &[String], block: &[String], expected: usize, min: usize) -> Option<usize> {
+    if block.len() > lines.len() {
+        return None;
+    }
+    let last = lines.len() - block.len();
+    if min > last {
+        return None;
+    }
+    let expected = expected.clamp(min, last);
+    let mut candidates: Vec<usize> = (min..=last).collect();
+    candidates.sort_by_key(|p| p.abs_diff(expected));
+    let exact = |p: &usize| lines[*p..*p + block.len()] == *block;
+    let loose = |p: &usize| {
+        lines[*p..*p + block.len()]
+            .iter()
+            .zip(block)
+            .all(|(l, b)| l.trim_end() == b.trim_end())
+    };
+    candidates
+        .iter()
+        .find(|p| exact(p))
+        .or_else(|| candidates.iter().find(|p| loose(p)))
+        .copied()
+}
+
+/// Apply `hunks` in order to `original`.
+pub fn apply_hunks(original: &str, hunks: &[Hunk]) -> Result<String, String> {
+    let mut lines: Vec<String> = original.split('\n').map(String::from).collect();
+    let mut trailing_newline = true;
+    if original.is_empty() {
+        lines.clear();
+    } else if original.ends_with('\n') {
+        lines.pop();
+    } else {
+        trailing_newline = false;
+    }
+
+    let (mut min, mut delta) = (0usize, 0isize);
+    for (n, hunk) in hunks.iter().enumerate() {
+        // Header line of the first old line (or, for pure insertions, the line before).
+        let header_pos = if hunk.old.is_empty() {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = (header_pos as isize + delta).max(0) as usize;
+        let at = if hunk.old.is_empty() {
+            expected.clamp(min, lines.len())
+        } else {
+            find_block(&lines, &hunk.old, expected, min).ok_or_else(|| {
+                format!(
+                    "hunk {} (@@ -{}) does not match: could not find the lines starting \"{}\"",
+                    n + 1,
+                    hunk.old_start,
+                    hunk.old[0]
+                )
+            })?
+        };
+        lines.splice(at..at + hunk.old.len(), hunk.new.iter().cloned());
+        min = at + hunk.new.len();
+        delta =
+            at as isize - header_pos as isize + hunk.new.len() as isize - hunk.old.len() as isize;
+        if hunk.new_missing_newline {
+            trailing_newline = false;
+        } else if hunk.old_missing_newline {
+            trailing_newline = true;
+        }
+    }
+
+    let mut out = lines.join("\n");
+    if trailing_newline && !lines.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Tools
+// ---------------------------------------------------------------------------
+
+/// One side of a diff: a workspace file (`<side>_path`) or literal text (`<side>`).
+async fn diff_side(ctx: &ToolCtx, args: &Value, side: &str) -> Result<(String, String), String> {
+    let path = args.get(format!("{side}_path")).and_then(Value::as_str);
+    let text = args.get(side).and_then(Value::as_str);
+    match (path, text) {
+        (Some(_), Some(_)) => Err(format!("give either '{side}_path' or '{side}', not both")),
+        (Some(path), None) => {
+            let resolved = resolve_path(path, &ctx.workspace, ctx.restrict_to_workspace).await?;
+            let content = tokio::fs::read_to_string(&resolved)
+                .await
+                .map_err(|e| format!("{path}: {e}"))?;
+            let prefix = if side == "old" { "a" } else { "b" };
+            Ok((format!("{prefix}/{path}"), content))
+        }
+        (None, Some(text)) => Ok((side.to_string(), text.to_string())),
+        (None, None) => Err(format!("missing '{side}_path' or '{side}'")),
+    }
+}
+
+/// diff_files tool.
+pub struct DiffFiles;
+
+impl Tool for DiffFiles {
+    fn name(&self) -> &str {
+        "diff_files"
+    }
+
+    fn description(&self) -> &str {
+        "Show a unified diff between two versions: workspace files (old_path / new_path) or \
+         text (old / new), in any combination — e.g. old_path with the proposed new text to \
+         preview a rewrite before applying it with apply_patch or write_file."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "old_path": { "type": "string", "description": "Original file, relative to workspace" },
+                "old": { "type": "string", "description": "Original text (instead of old_path)" },
+                "new_path": { "type": "string", "description": "Changed file, relative to workspace" },
+                "new": { "type": "string", "description": "Changed text (instead of new_path)" },
+                "context": { "type": "integer", "description": "Unchanged lines around each change (default 3)" }
+            }
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let (old_label, old) = match diff_side(ctx, args, "old").await {
+                Ok(s) => s,
+                Err(e) => return ToolResult::error(e),
+            };
+            let (new_label, new) = match diff_side(ctx, args, "new").await {
+                Ok(s) => s,
+                Err(e) => return ToolResult::error(e),
+            };
+            let context = args
+                .get("context")
+                .and_then(Value::as_u64)
+                .map(|n| n.min(100) as usize)
+                .unwrap_or(DEFAULT_CONTEXT);
+            let diff = unified_diff(&old, &new, &old_label, &new_label, context);
+            if diff.is_empty() {
+                ToolResult::ok("No differences.")
+            } else {
+                ToolResult::ok(diff)
+            }
+        })
+    }
+}
+
+/// A patched file, ready to write.
+struct Change {
+    display: String,
+    path: PathBuf,
+    /// None: delete the file.
+    content: Option<String>,
+    /// Old file removed after a rename.
+    remove: Option<PathBuf>,
+    summary: String,
+}
+
+/// Apply every file patch in memory; the first failure aborts all of them.
+async fn prepare(
+    ctx: &ToolCtx,
+    files: &[FilePatch],
+    path_override: Option<&str>,
+) -> Result<Vec<Change>, String> {
+    if path_override.is_some() && files.len() > 1 {
+        return Err("'path' can only be used with a single-file patch".to_string());
+    }
+    let mut changes = Vec::new();
+    for file in files {
+        let display = path_override
+            .or(file.target())
+            .ok_or("patch has no file name; pass 'path'")?
+            .to_string();
+        let path = resolve_path(&display, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        let source = match (path_override, &file.old_path) {
+            (None, Some(old)) if Some(old.as_str()) != file.new_path.as_deref() => {
+                resolve_path(old, &ctx.workspace, ctx.restrict_to_workspace).await?
+            }
+            _ => path.clone(),
+        };
+        let creating = path_override.is_none() && file.old_path.is_none();
+        let original = match tokio::fs::read_to_string(&source).await {
+            Ok(s) if creating && !s.is_empty() => {
+                return Err(format!("{display}: patch creates it but it already exists"));
+            }
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && creating => String::new(),
+            Err(e) => return Err(format!("{display}: {e}")),
+        };
+        let patched = apply_hunks(&original, &file.hunks).map_err(|e| format!("{display}: {e}"))?;
+
+        let (mut added, mut removed) = (0, 0);
+        for hunk in &file.hunks {
+            let old: Vec<&str> = hunk.old.iter().map(String::as_str).collect();
+            let new: Vec<&str> = hunk.new.iter().map(String::as_str).collect();
+            for edit in line_edits(&old, &new) {
+                match edit {
+                    Edit::Insert => added += 1,
+                    Edit::Delete => removed += 1,
+                    Edit::Keep => {}
+                }
+            }
+        }
+        let mut summary = format!(
+            "{display}: {} hunk(s), +{added} -{removed}",
+            file.hunks.len()
+        );
+        let deleting = path_override.is_none() && file.new_path.is_none();
+        if deleting && !patched.trim().is_empty() {
+            return Err(format!(
+                "{display}: patch deletes it but lines would remain"
+            ));
+        }
+        if creating {
+            summary.push_str(" (created)");
+        } else if deleting {
+            summary.push_str(" (deleted)");
+        }
+        let remove = (source != path).then_some(source);
+        if remove.is_some() {
+            summary.push_str(" (renamed)");
+        }
+        changes.push(Change {
+            display,
+            path,
+            content: (!deleting).then_some(patched),
+            remove,
+            summary,
+        });
+    }
+    Ok(changes)
+}
+
+async fn write_creating_dirs(path: &std::path::Path, content: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, content).await
+}
+
+/// apply_patch tool.
+pub struct ApplyPatch;
+
+impl Tool for ApplyPatch {
+    fn name(&self) -> &str {
+        "apply_patch"
+    }
+
+    fn description(&self) -> &str {
+        "Apply a unified diff (one or more files, any number of @@ hunks) to workspace files. \
+         Each hunk needs a few unchanged context lines; line numbers in @@ headers are only \
+         hints. Nothing is written unless every hunk applies. Prefer this over edit_file for \
+         multi-part or larger rewrites."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "patch": {
+                    "type": "string",
+                    "description": "Unified diff with ---/+++ file headers (paths relative to workspace) and @@ hunks"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "File to patch when the diff has no ---/+++ headers (single file only)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Only check that the patch applies (default false)"
+                }
+            },
+            "required": ["patch"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let Some(patch) = args.get("patch").and_then(Value::as_str) else {
+                return ToolResult::error("missing or invalid 'patch'");
+            };
+            let path = args.get("path").and_then(Value::as_str);
+            let dry_run = args
+                .get("dry_run")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let files = match parse_patch(patch) {
+                Ok(f) => f,
+                Err(e) => return ToolResult::error(e),
+            };
+            let changes = match prepare(ctx, &files, path).await {
+                Ok(c) => c,
+                Err(e) => return ToolResult::error(format!("patch not applied: {e}")),
+            };
+            let summary: Vec<&str> = changes.iter().map(|c| c.summary.as_str()).collect();
+            if dry_run {
+                return ToolResult::ok(format!("Patch applies cleanly:\n{}", summary.join("\n")));
+            }
+
+            for change in &changes {
+                let result = match &change.content {
+                    Some(content) => write_creating_dirs(&change.path, content).await,
+                    None => tokio::fs::remove_file(&change.path).await,
+                };
+                let result = match (&result, &change.remove) {
+                    (Ok(()), Some(old)) => tokio::fs::remove_file(old).await,
+                    _ => result,
+                };
+                if let Err(e) = result {
+                    return ToolResult::error(format!("writing {} failed: {e}", change.display));
+                }
+            }
+            ToolResult::ok(format!("Patched:\n{}", summary.join("\n")))
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(workspace: &std::path::Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    #[test]
+    fn unified_diff_groups_changes_into_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm";
+        assert_eq!(
+            unified_diff(old, new, "a/x.md", "b/x.md", 1),
+            "--- a/x.md\n+++ b/x.md\n\
+             @@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n\
+             @@ -12 +12,2 @@\n l\n+m\n\\ No newline at end of file\n"
+        );
+        assert_eq!(unified_diff(old, old, "a", "b", 3), "");
+        assert_eq!(
+            unified_diff("", "x\n", "old", "new", 3),
+            "--- old\n+++ new\n@@ -0,0 +1 @@\n+x\n"
+        );
+    }
+
+    #[test]
+    fn diffs_round_trip_through_apply() {
+        let cases = [
+            ("one\ntwo\nthree\n", "zero\none\n2\nthree\nfour\n"),
+            ("a\nb\nc\nd\ne\nf\ng\nh\n", "a\nc\nd\nX\ne\nf\nh\nY"),
+            ("same\nend", "same\nend\n"),
+            ("x\ny\n", ""),
+        ];
+        for (old, new) in cases {
+            for context in [0, 1, 3] {
+                let diff = unified_diff(old, new, "a/f", "b/f", context);
+                let files = parse_patch(&diff).unwrap();
+                assert_eq!(
+                    apply_hunks(old, &files[0].hunks).unwrap(),
+                    new,
+                    "context {context}:\n{diff}"
+                );
+            }
+        }
+        let old: String = (0..3000).map(|i| format!("{i}\n")).collect();
+        let new: String = (0..3000).map(|i| format!("{}\n", i * 7)).collect();
+        let diff = unified_diff(&old, &new, "a", "b", 3);
+        assert_eq!(
+            apply_hunks(&old, &parse_patch(&diff).unwrap()[0].hunks).unwrap(),
+            new
+        );
+    }
+
+    #[test]
+    fn hunks_are_placed_by_content_despite_wrong_numbers() {
+        let original = "# Plan\n\nintro\n\n## Monday\nrun\n\n## Friday\nrest\n";
+        // Wrong line numbers and counts, no space on the blank context line.
+        let patch = "@@ -40,9 +40,9 @@\n ## Monday\n-run\n+swim\n\n@@ -1,1 +1,1 @@\n ## Friday\n-rest\n+hike\n";
+        let files = parse_patch(patch).unwrap();
+        assert_eq!(files[0].old_path, None);
+        assert_eq!(
+            apply_hunks(original, &files[0].hunks).unwrap(),
+            "# Plan\n\nintro\n\n## Monday\nswim\n\n## Friday\nhike\n"
+        );
+
+        let stale = "@@ -1 +1 @@\n-walk\n+swim\n";
+        let err = apply_hunks(original, &parse_patch(stale).unwrap()[0].hunks).unwrap_err();
+        assert!(err.contains("hunk 1"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn apply_patch_writes_all_files_or_none() {
+        let ws = tempfile::TempDir::new().unwrap();
+        std::fs::write(ws.path().join("a.md"), "alpha\nbeta\n").unwrap();
+        std::fs::write(ws.path().join("b.md"), "gamma\n").unwrap();
+        let c = ctx(ws.path());
+
+        let bad = "--- a/a.md\n+++ b/a.md\n@@ -1 +1 @@\n-alpha\n+ALPHA\n\
+                   --- a/b.md\n+++ b/b.md\n@@ -1 +1 @@\n-delta\n+DELTA\n";
+        let res = ApplyPatch
+            .execute(&c, &serde_json::json!({"patch": bad}))
+            .await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("b.md: hunk 1"), "{}", res.for_llm);
+        assert_eq!(
+            std::fs::read_to_string(ws.path().join("a.md")).unwrap(),
+            "alpha\nbeta\n"
+        );
+
+        let good = "--- a/a.md\n+++ b/a.md\n@@ -1,2 +1,2 @@\n-alpha\n+ALPHA\n beta\n\
+                    --- /dev/null\n+++ b/Notes/new.md\n@@ -0,0 +1 @@\n+fresh\n";
+        let res = ApplyPatch
+            .execute(&c, &serde_json::json!({"patch": good, "dry_run": true}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(!ws.path().join("Notes/new.md").exists());
+
+        let res = ApplyPatch
+            .execute(&c, &serde_json::json!({"patch": good}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "Patched:\na.md: 1 hunk(s), +1 -1\nNotes/new.md: 1 hunk(s), +1 -0 (created)"
+        );
+        assert_eq!(
+            std::fs::read_to_string(ws.path().join("a.md")).unwrap(),
+            "ALPHA\nbeta\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(ws.path().join("Notes/new.md")).unwrap(),
+            "fresh\n"
+        );
+
+        let escape = "--- a/../x.md\n+++ b/../x.md\n@@ -0,0 +1 @@\n+x\n";
+        let res = ApplyPatch
+            .execute(&c, &serde_json::json!({"patch": escape}))
+            .await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("escape"), "{}", res.for_llm);
+    }
+
+    #[tokio::test]
+    async fn diff_files_compares_files_and_text() {
+        let ws = tempfile::TempDir::new().unwrap();
+        std::fs::write(ws.path().join("todo.md"), "- milk\n- eggs\n").unwrap();
+        let c = ctx(ws.path());
+        let res = DiffFiles
+            .execute(
+                &c,
+                &serde_json::json!({"old_path": "todo.md", "new": "- milk\n- bread\n"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "--- a/todo.md\n+++ new\n@@ -1,2 +1,2 @@\n - milk\n-- eggs\n+- bread\n"
+        );
+
+        let res = DiffFiles
+            .execute(&c, &serde_json::json!({"old": "x", "new": "x"}))
+            .await;
+        assert_eq!(res.for_llm, "No differences.");
+        let res = DiffFiles
+            .execute(&c, &serde_json::json!({"old": "x"}))
+            .await;
+        assert!(res.is_error);
+    }
+}
@@ -0,0 +1,318 @@
+//! qr: render text, a link or Wi-Fi credentials as a QR code PNG and send it to the chat.
+//!
+//! The matrix comes from the `qrcode` crate; the PNG (8-bit grayscale) is written here with
+//! the same chunk helper as send_photo's charts. Images land in `.icrab/charts/` unless a
+//! workspace `path` is given.
+
+use std::sync::atomic::Ordering;
+
+use qrcode::{Color, EcLevel, QrCode};
+use serde_json::Value;
+
+use crate::telegram::{OutboundKind, OutboundMsg};
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::send_photo::png_chunk;
+use crate::workspace;
+
+/// Modules of white border the spec asks for around the code.
+const QUIET_ZONE: usize = 4;
+/// Target image width; each module is scaled to a whole number of pixels near this.
+const TARGET_PX: usize = 512;
+const MIN_MODULE_PX: usize = 4;
+/// Telegram photo captions are limited to 1024 characters.
+const MAX_CAPTION_CHARS: usize = 1024;
+
+/// Escape `\ ; , : "` as the Wi-Fi QR format requires.
+fn wifi_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Payload and caption for a `wifi` object. The caption names the network but not the password.
+fn wifi_payload(v: &Value) -> Result<(String, String), String> {
+    let ssid = v
+        .get("ssid")
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .ok_or("wifi.ssid is required")?;
+    let password = v.get("password").and_then(Value::as_str).unwrap_or("");
+    let default_security = if password.is_empty() { "nopass" } else { "WPA" };
+    let security = v
+        .get("security")
+        .and_then(Value::as_str)
+        .unwrap_or(default_security);
+    let security = match security.to_ascii_uppercase().as_str() {
+        "WPA" | "WPA2" | "WPA3" => "WPA",
+        "WEP" => "WEP",
+        "NOPASS" | "NONE" | "OPEN" => "nopass",
+        _ => {
+            return Err(format!(
+                "unknown wifi.security '{security}' (use WPA, WEP or nopass)"
+            ));
+        }
+    };
+    if security != "nopass" && password.is_empty() {
+        return Err(format!("wifi.password is required for {security}"));
+    }
+    let mut payload = format!("WIFI:T:{security};S:{};", wifi_escape(ssid));
+    if security != "nopass" {
+        payload.push_str(&format!("P:{};", wifi_escape(password)));
+    }
+    if v.get("hidden").and_then(Value::as_bool).unwrap_or(false) {
+        payload.push_str("H:true;");
+    }
+    payload.push(';');
+    Ok((payload, format!("Wi-Fi: {ssid}")))
+}
+
+/// Render `data` as a grayscale PNG with a quiet zone, about `TARGET_PX` wide.
+pub fn render_qr(data: &str) -> Result<Vec<u8>, String> {
+    let code = QrCode::with_error_correction_level(data, EcLevel::M)
+        .map_err(|e| format!("cannot encode as a QR code: {e}"))?;
+    let modules = code.width();
+    let colors = code.to_colors();
+    let side = modules + 2 * QUIET_ZONE;
+    let scale = (TARGET_PX / side).max(MIN_MODULE_PX);
+    let px = side * scale;
+
+    let mut raw = Vec::with_capacity((px + 1) * px);
+    for y in 0..px {
+        raw.push(0); // filter: none
+        let my = (y / scale).checked_sub(QUIET_ZONE).filter(|&m| m < modules);
+        for x in 0..px {
+            let mx = (x / scale).checked_sub(QUIET_ZONE).filter(|&m| m < modules);
+            let is_dark = match (mx, my) {
+                (Some(mx), Some(my)) => colors[my * modules + mx] == Color::Dark,
+                _ => false,
+            };
+            raw.push(if is_dark { 0x00 } else { 0xff });
+        }
+    }
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(px as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(px as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 0, 0, 0, 0]); // 8-bit, grayscale
+
+    let idat = miniz_oxide::deflate::compress_to_vec_zlib(&raw, 6);
+    let mut out = Vec::with_capacity(idat.len() + 64);
+    out.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+    png_chunk(&mut out, b"IHDR", &ihdr);
+    png_chunk(&mut out, b"IDAT", &idat);
+    png_chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+/// qr tool: encode text or Wi-Fi credentials as a QR code and send it inline to the current chat.
+pub struct QrTool;
+
+impl QrTool {
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let (payload, default_caption) = match (
+            args.get("text").and_then(Value::as_str),
+            args.get("wifi").filter(|w| w.is_object()),
+        ) {
+            (Some(text), None) if !text.trim().is_empty() => (text.to_string(), text.to_string()),
+            (None, Some(wifi)) => wifi_payload(wifi)?,
+            _ => return Err("pass exactly one of 'text' or 'wifi'".into()),
+        };
+        let png = render_qr(&payload)?;
+
+        let rel = args.get("path").and_then(Value::as_str);
+        let out = match rel {
+            Some(rel) => {
+                if !rel.to_ascii_lowercase().ends_with(".png") {
+                    return Err(format!("{rel}: path must end in .png"));
+                }
+                resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await?
+            }
+            None => workspace::charts_dir(&ctx.workspace)
+                .join(format!("qr-{}.png", uuid::Uuid::new_v4())),
+        };
+        if let Some(dir) = out.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| format!("create {}: {e}", dir.display()))?;
+        }
+        tokio::fs::write(&out, png)
+            .await
+            .map_err(|e| format!("write {}: {e}", out.display()))?;
+
+        // Without a chat (e.g. a cron job) a saved file is still useful.
+        let (Some(tx), Some(chat_id)) = (&ctx.outbound_tx, ctx.chat_id) else {
+            return match rel {
+                Some(rel) => Ok(format!("saved QR code to {rel} (no chat to send it to)")),
+                None => Err("no outbound channel (pass 'path' to save the image instead)".into()),
+            };
+        };
+        let caption = args
+            .get("caption")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or(default_caption);
+        let msg = OutboundMsg {
+            chat_id,
+            text: caption.chars().take(MAX_CAPTION_CHARS).collect(),
+            channel: ctx
+                .channel
+                .clone()
+                .unwrap_or_else(|| "telegram".to_string()),
+            kind: OutboundKind::Photo(out),
+            thread_id: ctx.thread_id,
+            disable_notification: false,
+        };
+        tx.try_send(msg).map_err(|e| e.to_string())?;
+        ctx.delivered.store(true, Ordering::Relaxed);
+        Ok(match rel {
+            Some(rel) => format!("sent QR code (saved to {rel})"),
+            None => "sent QR code".to_string(),
+        })
+    }
+}
+
+impl Tool for QrTool {
+    fn name(&self) -> &str {
+        "qr"
+    }
+
+    fn description(&self) -> &str {
+        "Render a QR code and send it inline to the current chat. Pass `text` (a URL or any text) \
+         or `wifi` credentials so a phone can join the network by scanning. Optionally save the \
+         PNG in the workspace with `path`."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "text": { "type": "string", "description": "Text or URL to encode (or use wifi)" },
+                "wifi": {
+                    "type": "object",
+                    "description": "Wi-Fi network to encode instead of text",
+                    "properties": {
+                        "ssid": { "type": "string" },
+                        "password": { "type": "string" },
+                        "security": { "type": "string", "enum": ["WPA", "WEP", "nopass"], "description": "Default WPA with a password, else nopass" },
+                        "hidden": { "type": "boolean" }
+                    },
+                    "required": ["ssid"]
+                },
+                "caption": { "type": "string", "description": "Optional caption (defaults to the text, or the network name without the password)" },
+                "path": { "type": "string", "description": "Also save the PNG at this workspace path (must end in .png)" }
+            }
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) if ctx.delivered.load(Ordering::Relaxed) => ToolResult::silent(out),
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn ctx(workspace: &Path, tx: Option<tokio::sync::mpsc::Sender<OutboundMsg>>) -> ToolCtx {
+        ToolCtx {
+            chat_id: tx.as_ref().map(|_| 42),
+            outbound_tx: tx.map(Arc::new),
+            ..ToolCtx::for_tests(workspace)
+        }
+    }
+
+    #[test]
+    fn render_qr_produces_square_png() {
+        let png = render_qr("https://example.com").unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(&png[12..16], b"IHDR");
+        let w = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        let h = u32::from_be_bytes(png[20..24].try_into().unwrap());
+        assert_eq!(w, h);
+        // Version 2 (25 modules) + quiet zone = 33 modules at 15 px.
+        assert_eq!(w, 33 * 15);
+        assert_eq!(png[24..26], [8, 0]);
+        assert!(png.ends_with(&[0xAE, 0x42, 0x60, 0x82]));
+
+        assert!(render_qr(&"x".repeat(5000)).is_err());
+    }
+
+    #[test]
+    fn wifi_payload_escapes_and_hides_password() {
+        let (payload, caption) = wifi_payload(&json!({
+            "ssid": "Home;Net",
+            "password": "p:a\\ss",
+            "hidden": true
+        }))
+        .unwrap();
+        assert_eq!(payload, r"WIFI:T:WPA;S:Home\;Net;P:p\:a\\ss;H:true;;");
+        assert_eq!(caption, "Wi-Fi: Home;Net");
+
+        let (open, _) = wifi_payload(&json!({ "ssid": "Cafe" })).unwrap();
+        assert_eq!(open, "WIFI:T:nopass;S:Cafe;;");
+        assert!(wifi_payload(&json!({ "ssid": "x", "security": "WEP" })).is_err());
+        assert!(wifi_payload(&json!({ "password": "x" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn sends_photo_and_saves_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let ctx = ctx(dir.path(), Some(tx));
+
+        let res = QrTool
+            .execute(
+                &ctx,
+                &json!({ "text": "https://example.com", "path": "Attachments/link.png" }),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(ctx.delivered.load(Ordering::Relaxed));
+        let msg = rx.try_recv().unwrap();
+        assert_eq!(msg.chat_id, 42);
+        assert_eq!(msg.text, "https://example.com");
+        let OutboundKind::Photo(path) = msg.kind else {
+            panic!("expected a photo");
+        };
+        assert!(path.ends_with("Attachments/link.png"));
+        assert!(std::fs::read(&path).unwrap().starts_with(b"\x89PNG"));
+    }
+
+    #[tokio::test]
+    async fn without_chat_needs_a_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path(), None);
+
+        let res = QrTool.execute(&ctx, &json!({ "text": "hello" })).await;
+        assert!(res.is_error);
+        let res = QrTool
+            .execute(&ctx, &json!({ "text": "hello", "path": "qr.png" }))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(dir.path().join("qr.png").is_file());
+        let res = QrTool
+            .execute(&ctx, &json!({ "text": "a", "wifi": { "ssid": "b" } }))
+            .await;
+        assert!(res.is_error);
+    }
+}
@@ -9,10 +9,34 @@ use serde_json::Value;
 
 use crate::config::Config;
 use crate::llm::ToolDef;
+use crate::memory::db::BrainDb;
+use crate::tools::archive::ArchiveTool;
+use crate::tools::calc::CalcTool;
 use crate::tools::context::ToolCtx;
-use crate::tools::file::{AppendFile, EditFile, ListDir, ReadFile, WriteFile};
+use crate::tools::convert::ConvertTool;
+use crate::tools::csv::CsvTool;
+use crate::tools::download::DownloadFileTool;
+use crate::tools::email::EmailTool;
+use crate::tools::exec::ExecTool;
+use crate::tools::file::{
+    AppendFile, CopyFile, EditFile, ListDir, MultiEdit, ReadFile, StatFile, WriteFile,
+};
+use crate::tools::generate_image::GenerateImageTool;
+use crate::tools::github::GithubTool;
+use crate::tools::hn::HnTool;
+use crate::tools::http_request::HttpRequestTool;
+use crate::tools::patch::{ApplyPatch, DiffFiles};
 use crate::tools::result::ToolResult;
-use crate::tools::web::{WebFetchTool, WebSearchProvider, WebSearchTool, web_client};
+use crate::tools::share::ShareTextTool;
+use crate::tools::time::TimeTool;
+use crate::tools::todo::TodoTool;
+use crate::tools::tts::TtsTool;
+use crate::tools::weather::WeatherTool;
+use crate::tools::web::{
+    DEFAULT_WEB_FETCH_CACHE_TTL_SECS, WebFetchTool, WebSearchProvider, WebSearchTool, web_client,
+};
+use crate::tools::wikipedia::WikipediaTool;
+use crate::voice::VoiceClient;
 
 pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
@@ -21,6 +45,11 @@ pub trait Tool: Send + Sync {
     fn name(&self) -> &str;
     fn description(&self) -> &str;
     fn parameters(&self) -> Value;
+    /// True when the tool has no side effects, so its calls may run concurrently with other
+    /// read-only calls from the same LLM response. Default false (run in order).
+    fn read_only(&self) -> bool {
+        false
+    }
     fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult>;
 }
 
@@ -71,6 +100,12 @@ impl ToolRegistry {
         }
     }
 
+    /// Whether `name` is a registered read-only tool (see [`Tool::read_only`]).
+    pub fn is_read_only(&self, name: &str) -> bool {
+        let guard = self.inner.read().expect("registry lock");
+        guard.get(name).is_some_and(|t| t.read_only())
+    }
+
     /// All tool definitions for the LLM.
     pub fn to_tool_defs(&self) -> Vec<ToolDef> {
         let guard = self.inner.read().expect("registry lock");
@@ -103,20 +138,39 @@ impl ToolRegistry {
 const DEFAULT_BRAVE_MAX_RESULTS: u8 = 5;
 const DEFAULT_WEB_FETCH_MAX_CHARS: u32 = 50_000;
 
-/// Build the core registry (file + web).  Used as the base for both the
-/// main-agent registry and the subagent registry.
+/// Build the core registry (file + web, and exec when enabled).  Used as the base for both the
+/// main-agent registry and the subagent registry. `db` holds the web_fetch page cache.
 ///
 /// `MessageTool` is intentionally NOT included here. It is only added to
 /// subagent registries, where background tasks need to push results to the
 /// user. In the main agent the reply is returned as text content; offering
 /// `message` there causes the LLM to send duplicate replies.
-pub fn build_core_registry(config: &Config) -> ToolRegistry {
+pub fn build_core_registry(config: &Config, db: &Arc<BrainDb>) -> ToolRegistry {
     let reg = ToolRegistry::new();
     reg.register(ReadFile);
     reg.register(WriteFile);
     reg.register(ListDir);
     reg.register(EditFile);
+    reg.register(MultiEdit);
     reg.register(AppendFile);
+    reg.register(CopyFile);
+    reg.register(StatFile);
+    reg.register(DiffFiles);
+    reg.register(ApplyPatch);
+    reg.register(CsvTool);
+    reg.register(ArchiveTool);
+
+    let timezone = config
+        .timezone
+        .as_deref()
+        .and_then(|tz| tz.parse().ok())
+        .unwrap_or(chrono_tz::Europe::London);
+    reg.register(TodoTool::new(timezone));
+    reg.register(CalcTool::new(timezone));
+    reg.register(TimeTool::new(
+        timezone,
+        config.tools.as_ref().and_then(|t| t.time.as_ref()),
+    ));
 
     let web_cfg = config.tools.as_ref().and_then(|t| t.web.as_ref());
     let brave_max_results = web_cfg
@@ -126,20 +180,69 @@ pub fn build_core_registry(config: &Config) -> ToolRegistry {
     let fetch_max_chars = web_cfg
         .and_then(|w| w.web_fetch_max_chars)
         .unwrap_or(DEFAULT_WEB_FETCH_MAX_CHARS);
+    let fetch_cache_ttl = web_cfg
+        .and_then(|w| w.web_fetch_cache_ttl_secs)
+        .unwrap_or(DEFAULT_WEB_FETCH_CACHE_TTL_SECS);
 
     if let Ok(client) = web_client() {
-        let provider = web_cfg
-            .and_then(|w| w.brave_api_key.as_deref())
-            .filter(|k| !k.is_empty())
-            .map(|api_key| WebSearchProvider::Brave {
-                api_key: api_key.to_string(),
-                max_results: brave_max_results,
-            })
-            .unwrap_or(WebSearchProvider::DuckDuckGo {
-                max_results: brave_max_results,
-            });
+        let provider = WebSearchProvider::from_config(web_cfg, brave_max_results);
         reg.register(WebSearchTool::new(provider, client.clone()));
-        reg.register(WebFetchTool::new(client, fetch_max_chars));
+        reg.register(
+            WebFetchTool::new(client.clone(), fetch_max_chars)
+                .with_cache(Arc::clone(db), fetch_cache_ttl),
+        );
+        reg.register(DownloadFileTool::new(client.clone()));
+        reg.register(WikipediaTool::new(client.clone()));
+        reg.register(HnTool::new(client.clone()));
+        reg.register(ConvertTool::new(client.clone()));
+        reg.register(WeatherTool::new(client.clone()));
+        if let Some(github) = GithubTool::from_config(
+            client.clone(),
+            config.tools.as_ref().and_then(|t| t.github.as_ref()),
+        ) {
+            reg.register(github);
+        }
+        reg.register(ShareTextTool::new(
+            client.clone(),
+            config
+                .tools
+                .as_ref()
+                .and_then(|t| t.github.as_ref())
+                .and_then(|g| g.token.clone()),
+        ));
+        if let Some(image) = GenerateImageTool::from_config(
+            client.clone(),
+            config.tools.as_ref().and_then(|t| t.image.as_ref()),
+        ) {
+            reg.register(image);
+        }
+        let credentials = config
+            .tools
+            .as_ref()
+            .and_then(|t| t.http.as_ref())
+            .and_then(|h| h.credentials.clone())
+            .unwrap_or_default();
+        reg.register(HttpRequestTool::new(client, credentials, fetch_max_chars));
+    }
+
+    if let Some(exec_cfg) = config
+        .tools
+        .as_ref()
+        .and_then(|t| t.exec.as_ref())
+        .filter(|e| e.enabled.unwrap_or(false))
+    {
+        reg.register(ExecTool::from_config(exec_cfg));
+    }
+
+    if let Some(email) = EmailTool::from_config(
+        config.tools.as_ref().and_then(|t| t.email.as_ref()),
+        timezone,
+    ) {
+        reg.register(email);
+    }
+
+    if let Some(voice) = VoiceClient::from_config(config) {
+        reg.register(TtsTool::new(voice));
     }
 
     reg
@@ -148,8 +251,8 @@ pub fn build_core_registry(config: &Config) -> ToolRegistry {
 /// Build the default (main-agent) registry: core tools only.
 /// Caller adds spawn (and later cron) after constructing SubagentManager.
 #[inline]
-pub fn build_default_registry(config: &Config) -> ToolRegistry {
-    build_core_registry(config)
+pub fn build_default_registry(config: &Config, db: &Arc<BrainDb>) -> ToolRegistry {
+    build_core_registry(config, db)
 }
 
 #[cfg(test)]
@@ -169,14 +272,7 @@ mod tests {
         assert_eq!(summaries.len(), 1);
         assert!(summaries[0].starts_with("read_file - "));
 
-        let ctx = ToolCtx {
-            workspace: std::env::temp_dir(),
-            restrict_to_workspace: true,
-            chat_id: None,
-            channel: None,
-            outbound_tx: None,
-            delivered: Default::default(),
-        };
+        let ctx = ToolCtx::for_tests(std::env::temp_dir());
         let args = serde_json::json!({ "path": "." });
         let res = reg.execute(&ctx, "read_file", &args).await;
         assert!(res.is_error); // . is a dir, not a file
@@ -0,0 +1,548 @@
+//! `remind_me` tool: reminders from plain phrases ("tomorrow at 8", "in 45 min", "every
+//! weekday at 7:30"), so the model never computes unix timestamps. A small grammar turns the
+//! phrase into a cron store [`Schedule`] in the user's timezone; phrases it cannot read go to
+//! the model as structured output, and the result is validated like any other schedule.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::llm::{LlmProvider, Message, Role};
+use crate::tools::context::ToolCtx;
+use crate::tools::cron::{CronStore, JobAction, Schedule, parse_cron_expr};
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::time::{self, Zone, clock};
+use crate::tools::timer::{format_secs, parse_duration};
+
+/// Labels longer than this are cut at a word.
+const LABEL_CHARS: usize = 40;
+const DEFAULT_HOUR: u32 = 9;
+
+const SYSTEM_PROMPT: &str = "You turn the time part of a reminder into a schedule. kind is \
+    once, interval or cron. once: local_time is \"YYYY-MM-DD HH:MM\" in the user's timezone. \
+    interval: every_minutes. cron: a 5-field expression (minute hour day-of-month month \
+    day-of-week, Sunday = 0) in the user's local time. Leave unused fields out.";
+
+#[derive(Debug, Deserialize)]
+struct LlmSchedule {
+    kind: String,
+    local_time: Option<String>,
+    every_minutes: Option<u64>,
+    cron: Option<String>,
+}
+
+fn schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "kind": { "type": "string", "enum": ["once", "interval", "cron"] },
+            "local_time": { "type": "string" },
+            "every_minutes": { "type": "integer" },
+            "cron": { "type": "string" }
+        },
+        "required": ["kind"]
+    })
+}
+
+/// Cron day-of-week field of "weekday", "weekends", "mon and wed", "friday"; None when the
+/// words are not days.
+fn dow_field(days: &str) -> Option<String> {
+    let days = days.trim();
+    match days {
+        "day" | "days" => return Some("*".into()),
+        "weekday" | "weekdays" => return Some("1-5".into()),
+        "weekend" | "weekends" | "weekend day" => return Some("0,6".into()),
+        _ => {}
+    }
+    let mut nums: Vec<u32> = Vec::new();
+    for word in days
+        .split([',', '&', ' '])
+        .map(str::trim)
+        .filter(|w| !w.is_empty() && *w != "and")
+    {
+        let day = word
+            .strip_suffix('s')
+            .filter(|d| d.len() > 2)
+            .unwrap_or(word);
+        let n = match day {
+            "sun" | "sunday" => 0,
+            "mon" | "monday" => 1,
+            "tue" | "tues" | "tuesday" => 2,
+            "wed" | "wednesday" => 3,
+            "thu" | "thur" | "thurs" | "thursday" => 4,
+            "fri" | "friday" => 5,
+            "sat" | "saturday" => 6,
+            _ => return None,
+        };
+        if !nums.contains(&n) {
+            nums.push(n);
+        }
+    }
+    nums.sort_unstable();
+    (!nums.is_empty()).then(|| {
+        nums.iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    })
+}
+
+/// "1st", "15th", "2" as a day of the month.
+fn day_of_month(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    digits.parse().ok().filter(|d| (1..=31).contains(d))
+}
+
+/// Seconds in "45 min", "2 hours", "1h30m", "3 days", "a week", "half an hour".
+fn span_secs(text: &str) -> Option<u64> {
+    let text = text
+        .trim()
+        .replace("half an hour", "30 min")
+        .replace("an hour", "1 hour");
+    let text = text
+        .strip_prefix("a ")
+        .map(|rest| format!("1 {rest}"))
+        .unwrap_or(text);
+    if let Some(secs) = parse_duration(&text) {
+        return Some(secs);
+    }
+    let (n, unit) = text.split_once(' ').unwrap_or((&text, ""));
+    let (n, unit) = match unit {
+        "" => {
+            let split = n.find(|c: char| !c.is_ascii_digit())?;
+            n.split_at(split)
+        }
+        _ => (n, unit),
+    };
+    let n: u64 = n.trim().parse().ok()?;
+    let unit = match unit.trim() {
+        "d" | "day" | "days" => 86_400,
+        "w" | "week" | "weeks" => 604_800,
+        _ => return None,
+    };
+    n.checked_mul(unit)
+}
+
+/// A schedule from the grammar, or None for the model to try.
+fn parse_phrase(phrase: &str, tz: Tz, now: DateTime<Utc>) -> Option<Schedule> {
+    let p = phrase
+        .trim()
+        .to_lowercase()
+        .replace("a.m.", "am")
+        .replace("p.m.", "pm");
+    let p = p.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if let Some(rest) = p.strip_prefix("in ") {
+        return span_secs(rest).map(|secs| Schedule::Once {
+            at_unix: (now.timestamp().max(0) as u64).saturating_add(secs),
+        });
+    }
+
+    let recurring = if let Some(rest) = p.strip_prefix("every ").or(p.strip_prefix("each ")) {
+        Some(rest.to_string())
+    } else if let Some(rest) = p.strip_prefix("daily") {
+        Some(format!("day{rest}"))
+    } else if p == "hourly" {
+        Some("hour".to_string())
+    } else {
+        p.strip_prefix("weekly on ").map(str::to_string)
+    };
+    let Some(rest) = recurring else {
+        // A single time: "tomorrow at 8", "friday 5pm", "9am PST".
+        return time::next_at(&p, &Zone::Named(tz), now)
+            .ok()
+            .map(|at| Schedule::Once {
+                at_unix: at.timestamp().max(0) as u64,
+            });
+    };
+
+    let (days, at) = match rest.split_once(" at ") {
+        Some((days, at)) => (days.trim(), Some(at.trim())),
+        None => (rest.trim(), None),
+    };
+    if at.is_none() {
+        // "every hour", "every 15 minutes", "every 2 days".
+        let span = if days.starts_with(|c: char| c.is_ascii_digit()) {
+            span_secs(days)
+        } else {
+            match days {
+                "minute" => Some(60),
+                "hour" => Some(3600),
+                _ => None,
+            }
+        };
+        if let Some(every_seconds) = span {
+            return Some(Schedule::Interval {
+                every_seconds,
+                jitter_seconds: None,
+            });
+        }
+    }
+    let (minute, hour) = match at {
+        Some(at) => {
+            let t = clock(&at.replace(' ', ""))?;
+            (t.minute(), t.hour())
+        }
+        None => (0, DEFAULT_HOUR),
+    };
+    let (dom, dow) = if let Some(month) = days.strip_prefix("month") {
+        // "month", "month on the 15th".
+        let day = month
+            .trim()
+            .trim_start_matches("on ")
+            .trim_start_matches("the ")
+            .trim();
+        let dom = if day.is_empty() {
+            1
+        } else {
+            day_of_month(day)?
+        };
+        (dom.to_string(), "*".to_string())
+    } else {
+        ("*".to_string(), dow_field(days)?)
+    };
+    Some(Schedule::Cron {
+        expr: format!("{minute} {hour} {dom} * {dow}"),
+        tz: Some(tz.name().to_string()),
+    })
+}
+
+/// Check a schedule the model proposed and turn it into a store schedule.
+fn from_llm(s: LlmSchedule, tz: Tz) -> Result<Schedule, String> {
+    match s.kind.as_str() {
+        "once" => {
+            let local = s.local_time.ok_or("no local_time")?;
+            let naive = NaiveDateTime::parse_from_str(local.trim(), "%Y-%m-%d %H:%M")
+                .map_err(|_| format!("bad local_time '{local}'"))?;
+            let at = Zone::Named(tz)
+                .instant(naive)
+                .ok_or_else(|| format!("{local} does not exist in {}", tz.name()))?;
+            Ok(Schedule::Once {
+                at_unix: at.timestamp().max(0) as u64,
+            })
+        }
+        "interval" => {
+            let minutes = s.every_minutes.ok_or("no every_minutes")?;
+            Ok(Schedule::Interval {
+                every_seconds: minutes.saturating_mul(60),
+                jitter_seconds: None,
+            })
+        }
+        "cron" => {
+            let expr = s.cron.ok_or("no cron expression")?;
+            parse_cron_expr(&expr).map_err(|e| format!("bad cron expression '{expr}': {e}"))?;
+            Ok(Schedule::Cron {
+                expr: expr.trim().to_string(),
+                tz: Some(tz.name().to_string()),
+            })
+        }
+        other => Err(format!("unknown kind '{other}'")),
+    }
+}
+
+/// The message cut to a short label at a word boundary.
+fn label_for(message: &str) -> String {
+    let message = message.trim();
+    if message.chars().count() <= LABEL_CHARS {
+        return message.to_string();
+    }
+    let cut: String = message.chars().take(LABEL_CHARS).collect();
+    let cut = match cut.rfind(' ') {
+        Some(i) if i > LABEL_CHARS / 2 => &cut[..i],
+        _ => &cut,
+    };
+    format!("{}…", cut.trim_end_matches([',', '.', ';', ':']))
+}
+
+pub struct RemindMeTool {
+    store: Arc<CronStore>,
+    timezone: Tz,
+    llm: Arc<dyn LlmProvider>,
+    model: String,
+}
+
+impl RemindMeTool {
+    /// Phrases are read in `timezone`; `model` parses those the grammar cannot.
+    pub fn new(
+        store: Arc<CronStore>,
+        timezone: Tz,
+        llm: Arc<dyn LlmProvider>,
+        model: &str,
+    ) -> Self {
+        Self {
+            store,
+            timezone,
+            llm,
+            model: model.to_string(),
+        }
+    }
+
+    async fn ask_model(&self, phrase: &str, now: DateTime<Utc>) -> Result<Schedule, String> {
+        let local = now.with_timezone(&self.timezone);
+        let messages = [
+            Message {
+                role: Role::System,
+                content: SYSTEM_PROMPT.to_string(),
+                tool_call_id: None,
+                tool_calls: None,
+                images: Vec::new(),
+            },
+            Message {
+                role: Role::User,
+                content: format!(
+                    "Now: {} ({}).\nWhen: {phrase}",
+                    local.format("%A %Y-%m-%d %H:%M"),
+                    self.timezone.name()
+                ),
+                tool_call_id: None,
+                tool_calls: None,
+                images: Vec::new(),
+            },
+        ];
+        let parsed = self
+            .llm
+            .chat_structured::<LlmSchedule>(&messages, &self.model, &schema())
+            .await
+            .map_err(|e| format!("could not read '{phrase}': {e}"))?;
+        from_llm(parsed, self.timezone).map_err(|e| format!("could not read '{phrase}': {e}"))
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let str_arg = |key: &str| {
+            args.get(key)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+        };
+        let when = str_arg("when").ok_or("missing 'when' (e.g. \"tomorrow at 8\")")?;
+        let message = str_arg("message").ok_or("missing 'message' (what to remind about)")?;
+        let chat_id = ctx
+            .chat_id
+            .ok_or("remind_me needs a chat to remind (no current chat)")?;
+        let action = match str_arg("job_action") {
+            Some("agent") => JobAction::Agent,
+            _ => JobAction::Direct,
+        };
+
+        let now = Utc::now();
+        let schedule = match parse_phrase(when, self.timezone, now) {
+            Some(s) => s,
+            None => self.ask_model(when, now).await?,
+        };
+        let text = match action {
+            JobAction::Direct => format!("Reminder: {message}"),
+            JobAction::Agent => message.to_string(),
+        };
+        let label = str_arg("label")
+            .map(str::to_string)
+            .unwrap_or_else(|| label_for(message));
+        let job = self
+            .store
+            .add(Some(label.clone()), text, action, schedule.clone(), chat_id)
+            .map_err(|e| e.to_string())?;
+
+        let zone = Zone::Named(self.timezone);
+        let next = job
+            .next_run
+            .and_then(|t| DateTime::from_timestamp(t as i64, 0))
+            .map(|t| zone.show(t))
+            .unwrap_or_else(|| "never".to_string());
+        let repeat = match &schedule {
+            Schedule::Once { .. } => String::new(),
+            Schedule::Interval { every_seconds, .. } => {
+                format!(", then every {}", format_secs(*every_seconds))
+            }
+            Schedule::Cron { expr, .. } => format!(", repeating (cron {expr})"),
+        };
+        Ok(format!(
+            "Reminder {} '{label}' set: next {next}{repeat}.",
+            job.id
+        ))
+    }
+}
+
+impl Tool for RemindMeTool {
+    fn name(&self) -> &str {
+        "remind_me"
+    }
+
+    fn description(&self) -> &str {
+        "Set a reminder from a phrase — no timestamps to compute. when: \"in 45 min\", \
+         \"tomorrow at 8\", \"friday 5pm\", \"9am PST\", \"every weekday at 7:30\", \"every \
+         monday and thursday at 18:00\", \"every month on the 1st at 9\", \"every 2 hours\". \
+         Times are the user's local time. Manage reminders with the cron tool."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "when": { "type": "string", "description": "When, in words, e.g. \"tomorrow at 8\" or \"every weekday at 7:30\"" },
+                "message": { "type": "string", "description": "What to remind about (or, with job_action agent, the instruction to run)" },
+                "label": { "type": "string", "description": "Short name (default: the start of message)" },
+                "job_action": { "type": "string", "enum": ["direct", "agent"], "description": "direct (default) sends the reminder; agent runs message as a request, e.g. \"summarize my unread email\"" }
+            },
+            "required": ["when", "message"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::llm::{GenParams, LlmError, LlmResponse, ToolDef};
+
+    const LONDON: Tz = chrono_tz::Europe::London;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    fn parse(phrase: &str, now: DateTime<Utc>) -> Option<Schedule> {
+        parse_phrase(phrase, LONDON, now)
+    }
+
+    fn cron(phrase: &str) -> String {
+        match parse(phrase, utc("2026-10-16T12:00:00Z")) {
+            Some(Schedule::Cron { expr, tz }) => {
+                assert_eq!(tz.as_deref(), Some("Europe/London"));
+                expr
+            }
+            other => panic!("{phrase}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn one_shot_phrases() {
+        let now = utc("2026-10-16T12:00:00Z"); // Friday, 13:00 in London
+        let at = |phrase| match parse(phrase, now) {
+            Some(Schedule::Once { at_unix }) => at_unix as i64 - now.timestamp(),
+            other => panic!("{phrase}: {other:?}"),
+        };
+        assert_eq!(at("in 45 min"), 45 * 60);
+        assert_eq!(at("in an hour"), 3600);
+        assert_eq!(at("in 1h30m"), 5400);
+        assert_eq!(at("in 3 days"), 3 * 86_400);
+        // Tomorrow 08:00 BST is 07:00 UTC.
+        assert_eq!(at("tomorrow at 8"), 19 * 3600);
+        // 09:00 has passed today, so it is tomorrow's.
+        assert_eq!(at("9am"), 20 * 3600);
+        assert_eq!(at("at 5pm"), 4 * 3600);
+        assert!(parse("in a jiffy", now).is_none());
+        assert!(parse("when the bread is done", now).is_none());
+    }
+
+    #[test]
+    fn recurring_phrases() {
+        assert_eq!(cron("every weekday at 7:30"), "30 7 * * 1-5");
+        assert_eq!(cron("every day at 8pm"), "0 20 * * *");
+        assert_eq!(cron("daily at 6:15"), "15 6 * * *");
+        assert_eq!(cron("every monday and thursday at 18:00"), "0 18 * * 1,4");
+        assert_eq!(cron("every weekend at 10 am"), "0 10 * * 0,6");
+        assert_eq!(cron("every sunday"), "0 9 * * 0");
+        assert_eq!(cron("every month on the 15th at 9"), "0 9 15 * *");
+        assert_eq!(cron("weekly on fridays at 16:00"), "0 16 * * 5");
+        let now = utc("2026-10-16T12:00:00Z");
+        let every = |phrase| match parse(phrase, now) {
+            Some(Schedule::Interval { every_seconds, .. }) => every_seconds,
+            other => panic!("{phrase}: {other:?}"),
+        };
+        assert_eq!(every("every hour"), 3600);
+        assert_eq!(every("hourly"), 3600);
+        assert_eq!(every("every 15 minutes"), 900);
+        assert_eq!(every("every 2 days"), 2 * 86_400);
+        assert!(parse("every blue moon", now).is_none());
+    }
+
+    #[test]
+    fn labels_are_short() {
+        assert_eq!(label_for("take meds"), "take meds");
+        assert_eq!(
+            label_for("call the landlord about the boiler and the leaking kitchen tap"),
+            "call the landlord about the boiler and…"
+        );
+    }
+
+    /// Answers every request with one cron schedule.
+    struct Fake;
+
+    impl LlmProvider for Fake {
+        fn chat_with_params<'a>(
+            &'a self,
+            _messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            Box::pin(async move {
+                Ok(LlmResponse {
+                    content: r#"{"kind": "cron", "cron": "0 9 1-7 * 1"}"#.to_string(),
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".to_string(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    fn ctx() -> ToolCtx {
+        ToolCtx {
+            chat_id: Some(7),
+            ..ToolCtx::for_tests(std::path::PathBuf::from("/tmp"))
+        }
+    }
+
+    #[tokio::test]
+    async fn creates_jobs_with_grammar_or_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(CronStore::empty(dir.path()));
+        let tool = RemindMeTool::new(Arc::clone(&store), LONDON, Arc::new(Fake), "m");
+
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"when": "every weekday at 7:30", "message": "take meds"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm
+                .starts_with("Reminder job-1 'take meds' set: next ")
+        );
+        assert!(res.for_llm.ends_with(", repeating (cron 30 7 * * 1-5)."));
+        let job = store.get("job-1").unwrap();
+        assert_eq!(job.message, "Reminder: take meds");
+        assert_eq!(job.chat_id, 7);
+        assert_eq!(job.action, JobAction::Direct);
+
+        // Not in the grammar: the model's schedule is used.
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"when": "first monday of every month", "message": "rent", "job_action": "agent"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        let job = store.get("job-2").unwrap();
+        assert!(matches!(job.schedule, Schedule::Cron { ref expr, .. } if expr == "0 9 1-7 * 1"));
+        assert_eq!(job.message, "rent");
+    }
+}
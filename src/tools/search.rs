@@ -17,6 +17,26 @@
 //! query as syntactically invalid, the tool falls back to quoting each word
 //! individually joined by OR, which is always safe.
 //!
+//! # Tags and properties
+//!
+//! `tags` restricts results to notes carrying every given tag (frontmatter
+//! `tags:` or inline `#tag`, indexed into `vault_tags`); a tag also matches
+//! its nested tags.  `where` adds frontmatter conditions such as
+//! `type: workout` or `date >= 2026-01-01` (see [`FieldFilter::parse`]).
+//! With filters and no query, the matching notes are listed with their
+//! properties, most recently modified first.  `action: "list_tags"` lists
+//! every tag with its note count.
+//!
+//! # Hybrid ranking
+//!
+//! With an embedder ([`SearchVaultTool::with_embedder`], `[llm] embedding-model`)
+//! the BM25 candidates are merged with the notes closest to the query by
+//! embedding similarity, using reciprocal rank fusion: a note's score is
+//! `Σ 1 / (60 + rank)` over both rankings.  Notes found both ways rise to the
+//! top, exact keyword hits keep their place, and vague queries still find
+//! notes that share no words with them.  If embedding the query fails the
+//! BM25 order is used as is.
+//!
 //! # Registration
 //!
 //! ```ignore
@@ -29,13 +49,22 @@ use std::sync::Arc;
 
 use serde_json::Value;
 
-use crate::memory::db::{BrainDb, DbError};
+use crate::memory::db::{BrainDb, DbError, VaultFilter};
+use crate::memory::embeddings::{SemanticHit, VaultEmbedder};
+use crate::memory::frontmatter::FieldFilter;
+use crate::memory::tags;
 use crate::tools::context::ToolCtx;
 use crate::tools::registry::{BoxFuture, Tool};
 use crate::tools::result::ToolResult;
 
 /// Maximum number of vault search results returned to the LLM.
 const DEFAULT_LIMIT: usize = 5;
+/// Tags shown by `list_tags`.
+const MAX_LISTED_TAGS: usize = 100;
+/// Notes taken from each ranking before fusing them.
+const HYBRID_CANDIDATES: usize = 20;
+/// Reciprocal rank fusion constant; larger values flatten the lead of top ranks.
+const RRF_K: f32 = 60.0;
 
 // ---------------------------------------------------------------------------
 // SearchVaultTool
@@ -44,12 +73,19 @@ const DEFAULT_LIMIT: usize = 5;
 /// Search the indexed Obsidian vault using FTS5 BM25 ranking.
 pub struct SearchVaultTool {
     db: Arc<BrainDb>,
+    embedder: Option<VaultEmbedder>,
 }
 
 impl SearchVaultTool {
     /// Create a new search tool backed by `db`.
     pub fn new(db: Arc<BrainDb>) -> Self {
-        Self { db }
+        Self { db, embedder: None }
+    }
+
+    /// Rerank keyword results with embedding similarity when `embedder` is set.
+    pub fn with_embedder(mut self, embedder: Option<VaultEmbedder>) -> Self {
+        self.embedder = embedder;
+        self
     }
 }
 
@@ -60,7 +96,11 @@ impl Tool for SearchVaultTool {
 
     fn description(&self) -> &str {
         "Search the Obsidian vault notes for a keyword query. \
-         Returns BM25-ranked file paths and matching context snippets. \
+         Returns ranked file paths and matching context snippets \
+         (keyword BM25, combined with meaning when note embeddings exist). \
+         Filter by tags (e.g. [\"book\"]) and frontmatter properties \
+         (e.g. [\"type: workout\", \"date >= 2026-01-01\"]) with or without a query; \
+         action list_tags shows every tag in the vault. \
          Use this to find relevant notes before reading them in full with read_file."
     }
 
@@ -74,7 +114,24 @@ impl Tool for SearchVaultTool {
                         Supports multi-word queries ('bench press'), \
                         prefix wildcards ('squat*'), \
                         phrases ('\"bench press\"'), \
-                        and boolean operators (OR, NOT)."
+                        and boolean operators (OR, NOT). \
+                        Optional when tags or where are given."
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only notes with all of these tags ('book' also matches 'book/fiction')."
+                },
+                "where": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Frontmatter conditions, all must hold: 'type: workout', \
+                        'date >= 2026-01-01', 'status != done' (also <, <=, >)."
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["search", "list_tags"],
+                    "description": "search (default) or list_tags to list all tags with note counts."
                 },
                 "limit": {
                     "type": "integer",
@@ -82,22 +139,39 @@ impl Tool for SearchVaultTool {
                     "minimum": 1,
                     "maximum": 20
                 }
-            },
-            "required": ["query"]
+            }
         })
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
         let db = Arc::clone(&self.db);
         let args = args.clone();
 
         Box::pin(async move {
+            if args.get("action").and_then(Value::as_str) == Some("list_tags") {
+                return match tokio::task::spawn_blocking(move || db.vault_tag_counts()).await {
+                    Ok(Ok(counts)) => format_tags(&counts),
+                    Ok(Err(e)) => ToolResult::error(format!("list_tags failed: {e}")),
+                    Err(e) => ToolResult::error(format!("search task error: {e}")),
+                };
+            }
+
+            let filter = match parse_filter(&args) {
+                Ok(f) => f,
+                Err(e) => return ToolResult::error(e),
+            };
+
             let query = match args.get("query").and_then(Value::as_str) {
                 Some(q) => q.trim().to_string(),
+                None if !filter.is_empty() => String::new(),
                 None => return ToolResult::error("missing or invalid 'query'"),
             };
 
-            if query.is_empty() {
+            if query.is_empty() && filter.is_empty() {
                 return ToolResult::error("'query' must not be empty");
             }
 
@@ -106,10 +180,37 @@ impl Tool for SearchVaultTool {
                 .and_then(Value::as_u64)
                 .map_or(DEFAULT_LIMIT, |v| (v as usize).clamp(1, 20));
 
+            let hybrid = self.embedder.as_ref().filter(|_| !query.is_empty());
+            let candidates = if hybrid.is_some() {
+                limit.max(HYBRID_CANDIDATES)
+            } else {
+                limit
+            };
+
             // vault_fts_search is synchronous (rusqlite); run off the async
             // thread pool so we don't block the Tokio executor.
-            let result =
-                tokio::task::spawn_blocking(move || search_with_fallback(&db, &query, limit)).await;
+            let (keyword_query, keyword_filter) = (query.clone(), filter.clone());
+            let result = tokio::task::spawn_blocking(move || {
+                if keyword_query.is_empty() {
+                    db.vault_notes_matching(&keyword_filter, limit)
+                } else {
+                    search_with_fallback(&db, &keyword_query, &keyword_filter, candidates)
+                }
+            })
+            .await;
+
+            let result = match (result, hybrid) {
+                (Ok(Ok(rows)), Some(embedder)) => {
+                    match semantic_candidates(&self.db, embedder, &query, &filter).await {
+                        Ok(hits) => Ok(Ok(fuse(&rows, &hits, limit))),
+                        Err(e) => {
+                            eprintln!("search_vault: keyword ranking only, {e}");
+                            Ok(Ok(rows.into_iter().take(limit).collect()))
+                        }
+                    }
+                }
+                (other, _) => other,
+            };
 
             match result {
                 Ok(Ok(rows)) => format_results(&rows),
@@ -127,12 +228,13 @@ impl Tool for SearchVaultTool {
 /// Run an FTS5 search.  If the query string is syntactically invalid (FTS5
 /// returns an error), fall back to quoting each whitespace-separated word and
 /// joining with OR — this is always a valid FTS5 query.
-fn search_with_fallback(
+pub(crate) fn search_with_fallback(
     db: &BrainDb,
     query: &str,
+    filter: &VaultFilter,
     limit: usize,
 ) -> Result<Vec<(String, String)>, DbError> {
-    match db.vault_fts_search(query, limit) {
+    match db.vault_fts_search_filtered(query, filter, limit) {
         Ok(rows) => Ok(rows),
         Err(_) => {
             let safe: String = query
@@ -146,12 +248,71 @@ fn search_with_fallback(
             if safe.is_empty() {
                 Ok(Vec::new())
             } else {
-                db.vault_fts_search(&safe, limit)
+                db.vault_fts_search_filtered(&safe, filter, limit)
             }
         }
     }
 }
 
+/// The notes closest to `query` by embedding similarity that also match `filter`.
+async fn semantic_candidates(
+    db: &Arc<BrainDb>,
+    embedder: &VaultEmbedder,
+    query: &str,
+    filter: &VaultFilter,
+) -> Result<Vec<SemanticHit>, String> {
+    let mut hits = embedder
+        .search(query, HYBRID_CANDIDATES)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !filter.is_empty() {
+        let db = Arc::clone(db);
+        let filter = filter.clone();
+        let allowed = tokio::task::spawn_blocking(move || db.vault_paths_matching(&filter))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+        hits.retain(|h| allowed.contains(&h.filepath));
+    }
+    Ok(hits)
+}
+
+/// Reciprocal rank fusion of the keyword results and the semantic hits, best first.
+/// Keyword snippets are kept when a note appears in both; ties favour keyword rank.
+fn fuse(
+    keyword: &[(String, String)],
+    semantic: &[SemanticHit],
+    limit: usize,
+) -> Vec<(String, String)> {
+    let mut scored: Vec<(f32, usize, String, String)> = Vec::new();
+    for (rank, (filepath, snippet)) in keyword.iter().enumerate() {
+        scored.push((
+            1.0 / (RRF_K + rank as f32 + 1.0),
+            rank,
+            filepath.clone(),
+            snippet.clone(),
+        ));
+    }
+    for (rank, hit) in semantic.iter().enumerate() {
+        let score = 1.0 / (RRF_K + rank as f32 + 1.0);
+        match scored.iter_mut().find(|s| s.2 == hit.filepath) {
+            Some(existing) => existing.0 += score,
+            None => scored.push((
+                score,
+                keyword.len() + rank,
+                hit.filepath.clone(),
+                hit.snippet.clone(),
+            )),
+        }
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, filepath, snippet)| (filepath, snippet))
+        .collect()
+}
+
 /// Format `(filepath, snippet)` pairs into a concise string for the LLM.
 ///
 /// Output example:
@@ -176,6 +337,46 @@ fn format_results(rows: &[(String, String)]) -> ToolResult {
     ToolResult::ok(out)
 }
 
+/// `tags` (normalised) and `where` conditions from the arguments.
+fn parse_filter(args: &Value) -> Result<VaultFilter, String> {
+    let strings = |key: &str| -> Vec<String> {
+        args.get(key)
+            .and_then(Value::as_array)
+            .map(|a| {
+                a.iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let tags = strings("tags")
+        .iter()
+        .map(|t| tags::normalize(t))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let fields = strings("where")
+        .iter()
+        .map(|c| FieldFilter::parse(c))
+        .collect::<Result<_, _>>()?;
+    Ok(VaultFilter { tags, fields })
+}
+
+/// `#tag (n)` list for `list_tags`, most used first.
+fn format_tags(counts: &[(String, usize)]) -> ToolResult {
+    if counts.is_empty() {
+        return ToolResult::ok("No tags found in the vault.");
+    }
+    let mut out = format!("{} tag(s):\n", counts.len());
+    for (tag, n) in counts.iter().take(MAX_LISTED_TAGS) {
+        out.push_str(&format!("#{tag} ({n})\n"));
+    }
+    if counts.len() > MAX_LISTED_TAGS {
+        out.push_str(&format!("… and {} more\n", counts.len() - MAX_LISTED_TAGS));
+    }
+    ToolResult::ok(out)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -199,14 +400,7 @@ mod tests {
     }
 
     fn dummy_ctx() -> ToolCtx {
-        ToolCtx {
-            workspace: std::env::temp_dir(),
-            restrict_to_workspace: true,
-            chat_id: None,
-            channel: None,
-            outbound_tx: None,
-            delivered: Default::default(),
-        }
+        ToolCtx::for_tests(std::env::temp_dir())
     }
 
     fn index(db: &BrainDb, filepath: &str, content: &str) {
@@ -229,11 +423,11 @@ mod tests {
     }
 
     #[test]
-    fn tool_parameters_require_query() {
+    fn tool_parameters_offer_query_and_tags() {
         let (_tmp, db) = temp_db();
         let params = SearchVaultTool::new(db).parameters();
-        assert_eq!(params["required"][0], "query");
         assert!(params["properties"]["query"].is_object());
+        assert_eq!(params["properties"]["tags"]["type"], "array");
     }
 
     // ── Argument validation ───────────────────────────────────────────────────
@@ -438,7 +632,7 @@ mod tests {
     #[test]
     fn search_with_fallback_returns_empty_for_empty_vault() {
         let (_tmp, db) = temp_db();
-        let rows = search_with_fallback(&db, "anything", 5).unwrap();
+        let rows = search_with_fallback(&db, "anything", &VaultFilter::default(), 5).unwrap();
         assert!(rows.is_empty());
     }
 
@@ -448,11 +642,89 @@ mod tests {
         db.upsert_vault_entry("ideas.md", "Build a Rust AI assistant.", 0)
             .unwrap();
 
-        let rows = search_with_fallback(&db, "Rust", 5).unwrap();
+        let rows = search_with_fallback(&db, "Rust", &VaultFilter::default(), 5).unwrap();
         assert_eq!(rows.len(), 1);
         assert_eq!(rows[0].0, "ideas.md");
     }
 
+    // ── Tags ──────────────────────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn tags_filter_search_and_list() {
+        let (_tmp, db) = temp_db();
+        index(&db, "dune.md", "Dune, a desert planet.");
+        db.set_vault_tags("dune.md", &["book/fiction".into()])
+            .unwrap();
+        index(&db, "trip.md", "Desert trip to Morocco.");
+        db.set_vault_tags("trip.md", &["travel".into()]).unwrap();
+
+        let tool = SearchVaultTool::new(Arc::clone(&db));
+        let res = tool
+            .execute(
+                &dummy_ctx(),
+                &serde_json::json!({ "query": "desert", "tags": ["#Book"] }),
+            )
+            .await;
+        assert!(res.for_llm.contains("Found 1 result"), "{}", res.for_llm);
+        assert!(res.for_llm.contains("dune.md"));
+
+        let res = tool
+            .execute(&dummy_ctx(), &serde_json::json!({ "tags": ["travel"] }))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(res.for_llm.contains("trip.md") && !res.for_llm.contains("dune.md"));
+
+        let res = tool
+            .execute(&dummy_ctx(), &serde_json::json!({ "action": "list_tags" }))
+            .await;
+        assert!(res.for_llm.contains("#book/fiction (1)"), "{}", res.for_llm);
+        assert!(res.for_llm.contains("#travel (1)"));
+    }
+
+    #[tokio::test]
+    async fn where_filters_on_frontmatter() {
+        let (_tmp, db) = temp_db();
+        let note = |fp: &str, fm: Value| {
+            db.upsert_vault_note(fp, "squats and lunges", 0, &fm.to_string())
+                .unwrap();
+        };
+        note(
+            "jan.md",
+            serde_json::json!({"type": "workout", "date": "2026-01-05"}),
+        );
+        note(
+            "dec.md",
+            serde_json::json!({"type": ["Workout", "log"], "date": "2025-12-20"}),
+        );
+        note(
+            "plan.md",
+            serde_json::json!({"type": "plan", "date": "2026-02-01"}),
+        );
+
+        let tool = SearchVaultTool::new(Arc::clone(&db));
+        let res = tool
+            .execute(
+                &dummy_ctx(),
+                &serde_json::json!({ "query": "squats", "where": ["type: workout"] }),
+            )
+            .await;
+        assert!(res.for_llm.contains("Found 2 result"), "{}", res.for_llm);
+
+        let res = tool
+            .execute(
+                &dummy_ctx(),
+                &serde_json::json!({ "where": ["type = workout", "date >= 2026-01-01"] }),
+            )
+            .await;
+        assert!(res.for_llm.contains("Found 1 result"), "{}", res.for_llm);
+        assert!(res.for_llm.contains("jan.md") && res.for_llm.contains("date: 2026-01-05"));
+
+        let res = tool
+            .execute(&dummy_ctx(), &serde_json::json!({ "where": ["workout"] }))
+            .await;
+        assert!(res.is_error);
+    }
+
     // ── LLM-configurable limit ────────────────────────────────────────────────
 
     #[tokio::test]
@@ -585,4 +857,97 @@ mod tests {
         // Must not crash. May or may not match depending on FTS5 tokenizer.
         assert!(!res.is_error, "unexpected error: {}", res.for_llm);
     }
+
+    // ── Hybrid ranking ────────────────────────────────────────────────────────
+
+    /// Embeds text as counts of a few topic words, so related notes share a direction.
+    struct TopicEmbedder;
+
+    impl crate::llm::LlmProvider for TopicEmbedder {
+        fn chat_with_params<'a>(
+            &'a self,
+            _messages: &'a [crate::llm::Message],
+            _tools: &'a [crate::llm::ToolDef],
+            _model: &'a str,
+            _params: &'a crate::llm::GenParams,
+        ) -> BoxFuture<'a, Result<crate::llm::LlmResponse, crate::llm::LlmError>> {
+            Box::pin(async { Err(crate::llm::LlmError::Config("chat not scripted".into())) })
+        }
+
+        fn embed<'a>(
+            &'a self,
+            texts: &'a [String],
+            _model: &'a str,
+        ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, crate::llm::LlmError>> {
+            let topics = [&["leg", "squat"][..], &["bread", "recipe", "flour"][..]];
+            let vectors = texts
+                .iter()
+                .map(|t| {
+                    let t = t.to_lowercase();
+                    topics
+                        .iter()
+                        .map(|words| words.iter().filter(|w| t.contains(*w)).count() as f32)
+                        .collect()
+                })
+                .collect();
+            Box::pin(async move { Ok(vectors) })
+        }
+    }
+
+    #[tokio::test]
+    async fn hybrid_adds_semantic_matches_after_keyword_hits() {
+        let (_tmp, db) = temp_db();
+        index(&db, "Training/Leg day.md", "Plan for leg day: lunges.");
+        index(&db, "Training/Squat program.md", "Back squat 5x5.");
+        index(&db, "Kitchen/Sourdough.md", "Bread recipe: flour, water.");
+        let embedder = VaultEmbedder::new(Arc::clone(&db), Arc::new(TopicEmbedder), "topics");
+        embedder.run().await.unwrap();
+
+        let args = serde_json::json!({ "query": "leg day plan", "limit": 2 });
+        let keyword_only = SearchVaultTool::new(Arc::clone(&db))
+            .execute(&dummy_ctx(), &args)
+            .await;
+        assert!(
+            keyword_only.for_llm.contains("Found 1 result"),
+            "{}",
+            keyword_only.for_llm
+        );
+
+        let tool = SearchVaultTool::new(Arc::clone(&db)).with_embedder(Some(embedder));
+        let res = tool.execute(&dummy_ctx(), &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm.contains("1. Training/Leg day.md"),
+            "{}",
+            res.for_llm
+        );
+        assert!(
+            res.for_llm.contains("2. Training/Squat program.md"),
+            "{}",
+            res.for_llm
+        );
+        assert!(!res.for_llm.contains("Sourdough"), "{}", res.for_llm);
+    }
+
+    #[test]
+    fn fuse_prefers_notes_found_both_ways() {
+        let keyword = vec![
+            ("a.md".to_string(), "kw a".to_string()),
+            ("b.md".to_string(), "kw b".to_string()),
+        ];
+        let hit = |f: &str| SemanticHit {
+            filepath: f.into(),
+            score: 0.5,
+            snippet: format!("sem {f}"),
+        };
+        let fused = fuse(&keyword, &[hit("b.md"), hit("c.md")], 10);
+        assert_eq!(
+            fused,
+            vec![
+                ("b.md".to_string(), "kw b".to_string()),
+                ("a.md".to_string(), "kw a".to_string()),
+                ("c.md".to_string(), "sem c.md".to_string()),
+            ]
+        );
+    }
 }
@@ -1,15 +1,18 @@
 //! `search_chat` tool: BM25 keyword search over local chat history.
 //!
 //! Executes against the `chat_fts` FTS5 table (backed by `chat_history`),
-//! returning ranked (chat_id, role, snippet) triples.  Deliberately separate
-//! from `search_vault` so the agent can recall past conversations without
-//! touching the vault index.
+//! returning ranked hits (chat, session, role, date, snippet).  Deliberately
+//! separate from `search_vault` so the agent can recall past conversations
+//! without touching the vault index.
+//!
+//! Optional `after` / `before` dates, `role` and `session_id` narrow the search
+//! ("what did I tell you about rent last month").
 
 use std::sync::Arc;
 
 use serde_json::Value;
 
-use crate::memory::db::{BrainDb, DbError};
+use crate::memory::db::{BrainDb, ChatSearchFilter, ChatSearchHit, DbError};
 use crate::tools::context::ToolCtx;
 use crate::tools::registry::{BoxFuture, Tool};
 use crate::tools::result::ToolResult;
@@ -53,12 +56,33 @@ impl Tool for SearchChatTool {
                     "description": "Max results to return (default 5, max 20).",
                     "minimum": 1,
                     "maximum": 20
+                },
+                "after": {
+                    "type": "string",
+                    "description": "Only messages on or after this date (YYYY-MM-DD, UTC)."
+                },
+                "before": {
+                    "type": "string",
+                    "description": "Only messages before this date (YYYY-MM-DD, UTC, exclusive)."
+                },
+                "role": {
+                    "type": "string",
+                    "enum": ["user", "assistant"],
+                    "description": "Only messages from the user or only your replies."
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Only messages from this session (ids are shown in results)."
                 }
             },
             "required": ["query"]
         })
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
         let db = Arc::clone(&self.db);
         let args = args.clone();
@@ -78,9 +102,15 @@ impl Tool for SearchChatTool {
                 .and_then(Value::as_u64)
                 .map_or(DEFAULT_LIMIT, |v| (v as usize).clamp(1, 20));
 
-            let result =
-                tokio::task::spawn_blocking(move || chat_search_with_fallback(&db, &query, limit))
-                    .await;
+            let filter = match parse_filter(&args) {
+                Ok(f) => f,
+                Err(e) => return ToolResult::error(e),
+            };
+
+            let result = tokio::task::spawn_blocking(move || {
+                chat_search_with_fallback(&db, &query, &filter, limit)
+            })
+            .await;
 
             match result {
                 Ok(Ok(rows)) => format_results(&rows),
@@ -91,12 +121,43 @@ impl Tool for SearchChatTool {
     }
 }
 
+/// Filters from the optional `after`, `before`, `role` and `session_id` arguments.
+fn parse_filter(args: &Value) -> Result<ChatSearchFilter, String> {
+    let text = |key: &str| {
+        args.get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    let date = |key: &str| match text(key) {
+        Some(d) => chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d")
+            .map(|d| Some(d.format("%Y-%m-%d").to_string()))
+            .map_err(|_| format!("'{key}' must be a date like 2026-02-01")),
+        None => Ok(None),
+    };
+    let role = text("role").map(|r| r.to_lowercase());
+    if let Some(r) = &role
+        && r != "user"
+        && r != "assistant"
+    {
+        return Err("'role' must be \"user\" or \"assistant\"".to_string());
+    }
+    Ok(ChatSearchFilter {
+        after: date("after")?,
+        before: date("before")?,
+        role,
+        session_id: text("session_id"),
+    })
+}
+
 fn chat_search_with_fallback(
     db: &BrainDb,
     query: &str,
+    filter: &ChatSearchFilter,
     limit: usize,
-) -> Result<Vec<(String, String, String)>, DbError> {
-    match db.chat_fts_search(query, limit) {
+) -> Result<Vec<ChatSearchHit>, DbError> {
+    match db.chat_fts_search_filtered(query, filter, limit) {
         Ok(rows) => Ok(rows),
         Err(_) => {
             let safe: String = query
@@ -109,25 +170,27 @@ fn chat_search_with_fallback(
             if safe.is_empty() {
                 Ok(Vec::new())
             } else {
-                db.chat_fts_search(&safe, limit)
+                db.chat_fts_search_filtered(&safe, filter, limit)
             }
         }
     }
 }
 
-fn format_results(rows: &[(String, String, String)]) -> ToolResult {
+fn format_results(rows: &[ChatSearchHit]) -> ToolResult {
     if rows.is_empty() {
         return ToolResult::ok("No matching messages found in chat history.");
     }
 
     let mut out = format!("Found {} result(s) in chat history:\n", rows.len());
-    for (i, (chat_id, role, snippet)) in rows.iter().enumerate() {
+    for (i, hit) in rows.iter().enumerate() {
         out.push_str(&format!(
-            "\n{}. [{}] {}\n   {}\n",
+            "\n{}. [{}] {} {} (session {})\n   {}\n",
             i + 1,
-            role,
-            chat_id,
-            snippet
+            hit.role,
+            hit.chat_id,
+            hit.timestamp,
+            hit.session_id,
+            hit.snippet
         ));
     }
     ToolResult::ok(out)
@@ -154,14 +217,7 @@ mod tests {
     }
 
     fn dummy_ctx() -> ToolCtx {
-        ToolCtx {
-            workspace: std::env::temp_dir(),
-            restrict_to_workspace: true,
-            chat_id: None,
-            channel: None,
-            outbound_tx: None,
-            delivered: Default::default(),
-        }
+        ToolCtx::for_tests(std::env::temp_dir())
     }
 
     fn seed(db: &BrainDb, chat_id: &str, role: &str, content: &str) {
@@ -244,14 +300,50 @@ mod tests {
 
     #[test]
     fn format_results_single() {
-        let rows = vec![(
-            "chat123".to_string(),
-            "user".to_string(),
-            "...did **squats** today...".to_string(),
-        )];
+        let rows = vec![ChatSearchHit {
+            chat_id: "chat123".to_string(),
+            session_id: "sess-1".to_string(),
+            role: "user".to_string(),
+            timestamp: "2026-02-03 18:30:00".to_string(),
+            snippet: "...did **squats** today...".to_string(),
+        }];
         let r = format_results(&rows);
         assert!(r.for_llm.contains("1 result"));
         assert!(r.for_llm.contains("chat123"));
         assert!(r.for_llm.contains("user"));
+        assert!(r.for_llm.contains("2026-02-03"));
+        assert!(r.for_llm.contains("session sess-1"));
+    }
+
+    #[tokio::test]
+    async fn filters_narrow_results_and_are_validated() {
+        let (_tmp, db) = temp_db();
+        seed(&db, "c1", "user", "rent is due");
+        seed(&db, "c1", "assistant", "I will remind you about rent");
+        let tool = SearchChatTool::new(Arc::clone(&db));
+
+        let res = tool
+            .execute(
+                &dummy_ctx(),
+                &serde_json::json!({ "query": "rent", "role": "assistant" }),
+            )
+            .await;
+        assert!(res.for_llm.contains("Found 1 result"), "{}", res.for_llm);
+        assert!(res.for_llm.contains("[assistant]"));
+
+        let res = tool
+            .execute(
+                &dummy_ctx(),
+                &serde_json::json!({ "query": "rent", "before": "2000-01-01" }),
+            )
+            .await;
+        assert!(res.for_llm.contains("No matching"), "{}", res.for_llm);
+
+        for bad in [
+            serde_json::json!({ "query": "rent", "after": "last month" }),
+            serde_json::json!({ "query": "rent", "role": "robot" }),
+        ] {
+            assert!(tool.execute(&dummy_ctx(), &bad).await.is_error, "{bad}");
+        }
     }
 }
@@ -0,0 +1,198 @@
+//! `semantic_search_vault` tool: meaning-based search over the vault embeddings.
+//!
+//! Complements `search_vault` (BM25): the query is embedded with `[llm] embedding-model` and
+//! notes are ranked by the cosine similarity of their best chunk, so paraphrases match
+//! ("leg day plan" finds "Squat program"). Registered only when an embedding model is set.
+
+use serde_json::Value;
+
+use crate::memory::embeddings::{SemanticHit, VaultEmbedder};
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Maximum number of results returned to the LLM.
+const DEFAULT_LIMIT: usize = 5;
+
+/// Rank vault notes by embedding similarity to a natural-language query.
+pub struct SemanticSearchVaultTool {
+    embedder: VaultEmbedder,
+}
+
+impl SemanticSearchVaultTool {
+    pub fn new(embedder: VaultEmbedder) -> Self {
+        Self { embedder }
+    }
+}
+
+impl Tool for SemanticSearchVaultTool {
+    fn name(&self) -> &str {
+        "semantic_search_vault"
+    }
+
+    fn description(&self) -> &str {
+        "Search the Obsidian vault notes by meaning rather than exact words. \
+         Returns the most similar notes with a matching excerpt and a similarity score. \
+         Use this when search_vault finds nothing or the wording in the notes may differ."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "What to look for, in natural language (e.g. 'leg day plan')."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Max results to return (default 5, max 20).",
+                    "minimum": 1,
+                    "maximum": 20
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let query = match args.get("query").and_then(Value::as_str) {
+                Some(q) => q.trim(),
+                None => return ToolResult::error("missing or invalid 'query'"),
+            };
+            if query.is_empty() {
+                return ToolResult::error("'query' must not be empty");
+            }
+            let limit = args
+                .get("limit")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_LIMIT, |v| (v as usize).clamp(1, 20));
+
+            match self.embedder.search(query, limit).await {
+                Ok(hits) => format_results(&hits),
+                Err(e) => ToolResult::error(format!("semantic search failed: {e}")),
+            }
+        })
+    }
+}
+
+fn format_results(hits: &[SemanticHit]) -> ToolResult {
+    if hits.is_empty() {
+        return ToolResult::ok("No embedded notes yet; try search_vault.");
+    }
+    let mut out = format!("Found {} result(s):\n", hits.len());
+    for (i, hit) in hits.iter().enumerate() {
+        out.push_str(&format!(
+            "\n{}. {} (similarity {:.2})\n   {}\n",
+            i + 1,
+            hit.filepath,
+            hit.score,
+            hit.snippet
+        ));
+    }
+    ToolResult::ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    use crate::llm::{GenParams, LlmError, LlmProvider, LlmResponse, Message, ToolDef};
+    use crate::memory::db::BrainDb;
+
+    /// Embeds text as counts of a few topic words, so related notes share a direction.
+    struct TopicEmbedder;
+
+    impl LlmProvider for TopicEmbedder {
+        fn chat_with_params<'a>(
+            &'a self,
+            _messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            Box::pin(async { Err(LlmError::Config("chat not scripted".into())) })
+        }
+
+        fn embed<'a>(
+            &'a self,
+            texts: &'a [String],
+            _model: &'a str,
+        ) -> BoxFuture<'a, Result<Vec<Vec<f32>>, LlmError>> {
+            let topics = [&["leg", "squat"][..], &["bread", "recipe", "flour"][..]];
+            let vectors = texts
+                .iter()
+                .map(|t| {
+                    let t = t.to_lowercase();
+                    topics
+                        .iter()
+                        .map(|words| words.iter().filter(|w| t.contains(*w)).count() as f32)
+                        .collect()
+                })
+                .collect();
+            Box::pin(async move { Ok(vectors) })
+        }
+    }
+
+    fn dummy_ctx() -> ToolCtx {
+        ToolCtx::for_tests(std::env::temp_dir())
+    }
+
+    #[tokio::test]
+    async fn finds_paraphrased_note_after_embedding() {
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        db.upsert_vault_entry("Training/Squat program.md", "Back squat 5x5.", 1)
+            .unwrap();
+        db.upsert_vault_entry("Kitchen/Sourdough.md", "Bread recipe: flour, water.", 1)
+            .unwrap();
+        let embedder = VaultEmbedder::new(Arc::clone(&db), Arc::new(TopicEmbedder), "topics");
+        let tool = SemanticSearchVaultTool::new(embedder.clone());
+
+        let res = tool
+            .execute(
+                &dummy_ctx(),
+                &serde_json::json!({ "query": "leg day plan" }),
+            )
+            .await;
+        assert!(res.for_llm.contains("No embedded notes"), "{}", res.for_llm);
+
+        let stats = embedder.run().await.unwrap();
+        assert_eq!((stats.embedded, stats.chunks), (2, 2));
+        assert_eq!(embedder.run().await.unwrap().embedded, 0, "up to date");
+
+        let res = tool
+            .execute(
+                &dummy_ctx(),
+                &serde_json::json!({ "query": "leg day plan", "limit": 1 }),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(res.for_llm.contains("Found 1 result"), "{}", res.for_llm);
+        assert!(res.for_llm.contains("Squat program.md"), "{}", res.for_llm);
+        assert!(!res.for_llm.contains("Sourdough"), "{}", res.for_llm);
+    }
+
+    #[tokio::test]
+    async fn empty_query_returns_error() {
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let tool =
+            SemanticSearchVaultTool::new(VaultEmbedder::new(db, Arc::new(TopicEmbedder), "m"));
+        let res = tool
+            .execute(&dummy_ctx(), &serde_json::json!({ "query": " " }))
+            .await;
+        assert!(res.is_error);
+    }
+}
@@ -0,0 +1,105 @@
+//! send_file: deliver a workspace file to the current chat (Telegram sendDocument).
+
+use std::sync::atomic::Ordering;
+
+use serde_json::Value;
+
+use crate::telegram::{OutboundKind, OutboundMsg};
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Telegram bots may upload documents up to 50 MB.
+const MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+fn get_string(args: &Value, key: &str) -> Result<String, String> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| format!("missing or invalid '{key}'"))
+}
+
+/// send_file tool: queue a workspace file for upload to the current chat.
+pub struct SendFileTool;
+
+impl Tool for SendFileTool {
+    fn name(&self) -> &str {
+        "send_file"
+    }
+
+    fn description(&self) -> &str {
+        "Send a file from the workspace to the user in the current chat as a document. Path is relative to workspace."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Path relative to workspace" },
+                "caption": { "type": "string", "description": "Optional caption shown with the file" }
+            },
+            "required": ["path"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let args = args.clone();
+        let ctx = ctx.clone();
+
+        Box::pin(async move {
+            let path = match get_string(&args, "path") {
+                Ok(p) => p,
+                Err(e) => return ToolResult::error(e),
+            };
+            let caption = args
+                .get("caption")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            let resolved =
+                match resolve_path(&path, &ctx.workspace, ctx.restrict_to_workspace).await {
+                    Ok(p) => p,
+                    Err(e) => return ToolResult::error(e),
+                };
+            let meta = match tokio::fs::metadata(&resolved).await {
+                Ok(m) => m,
+                Err(e) => return ToolResult::error(format!("{path}: {e}")),
+            };
+            if !meta.is_file() {
+                return ToolResult::error(format!("{path}: not a file"));
+            }
+            if meta.len() > MAX_FILE_BYTES {
+                return ToolResult::error(format!(
+                    "{path}: file too large ({} bytes, max {MAX_FILE_BYTES})",
+                    meta.len()
+                ));
+            }
+            let Some(tx) = &ctx.outbound_tx else {
+                return ToolResult::error("no outbound channel (send_file unavailable)");
+            };
+            let Some(chat_id) = ctx.chat_id else {
+                return ToolResult::error("no chat_id (send_file unavailable)");
+            };
+            let channel = ctx
+                .channel
+                .clone()
+                .unwrap_or_else(|| "telegram".to_string());
+            let msg = OutboundMsg {
+                chat_id,
+                text: caption,
+                channel,
+                kind: OutboundKind::Document(resolved),
+                thread_id: ctx.thread_id,
+                disable_notification: false,
+            };
+            match tx.try_send(msg) {
+                Ok(()) => {
+                    ctx.delivered.store(true, Ordering::Relaxed);
+                    ToolResult::silent(format!("sent {path}"))
+                }
+                Err(e) => ToolResult::error(e.to_string()),
+            }
+        })
+    }
+}
@@ -0,0 +1,515 @@
+//! send_photo: deliver an image inline to the current chat (Telegram sendPhoto).
+//!
+//! Either sends an existing workspace image or renders a simple bar/line chart to PNG.
+//! The renderer is hand-rolled (palette PNG, stored deflate) to avoid pulling in a plotting crate;
+//! the image carries no text, so labels and values go into the caption.
+
+use std::path::Path;
+use std::sync::atomic::Ordering;
+
+use serde_json::Value;
+
+use crate::telegram::{OutboundKind, OutboundMsg};
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::workspace;
+
+/// Telegram bots may upload photos up to 10 MB.
+const MAX_PHOTO_BYTES: u64 = 10 * 1024 * 1024;
+const PHOTO_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+const MAX_CHART_POINTS: usize = 100;
+
+const CHART_WIDTH: usize = 640;
+const CHART_HEIGHT: usize = 400;
+const MARGIN_LEFT: usize = 40;
+const MARGIN_RIGHT: usize = 20;
+const MARGIN_TOP: usize = 20;
+const MARGIN_BOTTOM: usize = 30;
+
+// Palette indices.
+const WHITE: u8 = 0;
+const AXIS: u8 = 1;
+const GRID: u8 = 2;
+const BAR: u8 = 3;
+const LINE: u8 = 4;
+const PALETTE: [[u8; 3]; 5] = [
+    [0xff, 0xff, 0xff],
+    [0x33, 0x33, 0x33],
+    [0xdd, 0xdd, 0xdd],
+    [0x3a, 0x7b, 0xd5],
+    [0xe0, 0x6c, 0x3c],
+];
+
+// --- Chart spec ---
+
+/// Chart style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    Bar,
+    Line,
+}
+
+/// A parsed chart request: one series of labelled values.
+#[derive(Debug, Clone)]
+pub struct ChartSpec {
+    pub kind: ChartKind,
+    pub title: String,
+    pub labels: Vec<String>,
+    pub values: Vec<f64>,
+}
+
+impl ChartSpec {
+    /// Parse `{ "type": "bar"|"line", "title", "labels": [..], "values": [..] }`.
+    pub fn from_json(v: &Value) -> Result<Self, String> {
+        let kind = match v.get("type").and_then(Value::as_str).unwrap_or("bar") {
+            "bar" => ChartKind::Bar,
+            "line" => ChartKind::Line,
+            other => return Err(format!("unknown chart type '{other}' (use bar or line)")),
+        };
+        let title = v
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        let values: Vec<f64> = v
+            .get("values")
+            .and_then(Value::as_array)
+            .ok_or("chart.values must be an array of numbers")?
+            .iter()
+            .map(|x| x.as_f64().filter(|f| f.is_finite()))
+            .collect::<Option<_>>()
+            .ok_or("chart.values must be an array of numbers")?;
+        if values.is_empty() {
+            return Err("chart.values is empty".into());
+        }
+        if values.len() > MAX_CHART_POINTS {
+            return Err(format!("too many points (max {MAX_CHART_POINTS})"));
+        }
+        let labels: Vec<String> = match v.get("labels").and_then(Value::as_array) {
+            Some(arr) => arr
+                .iter()
+                .map(|l| match l {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect(),
+            None => (1..=values.len()).map(|i| i.to_string()).collect(),
+        };
+        if labels.len() != values.len() {
+            return Err("chart.labels and chart.values must have the same length".into());
+        }
+        Ok(Self {
+            kind,
+            title,
+            labels,
+            values,
+        })
+    }
+
+    /// Caption listing title and `label: value` pairs (the image itself has no text).
+    pub fn caption(&self) -> String {
+        let mut out = String::new();
+        if !self.title.is_empty() {
+            out.push_str(&self.title);
+            out.push('\n');
+        }
+        for (l, v) in self.labels.iter().zip(&self.values) {
+            out.push_str(&format!("{l}: {v}\n"));
+        }
+        out.trim_end().to_string()
+    }
+}
+
+// --- Rendering ---
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    px: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            px: vec![WHITE; width * height],
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64, c: u8) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.px[y as usize * self.width + x as usize] = c;
+        }
+    }
+
+    fn fill_rect(&mut self, x0: i64, y0: i64, x1: i64, y1: i64, c: u8) {
+        for y in y0.min(y1)..=y0.max(y1) {
+            for x in x0.min(x1)..=x0.max(x1) {
+                self.set(x, y, c);
+            }
+        }
+    }
+
+    /// Bresenham line, `thick` pixels wide (square brush).
+    fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), thick: i64, c: u8) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        loop {
+            self.fill_rect(x, y, x + thick - 1, y + thick - 1, c);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+}
+
+/// Render the chart to PNG bytes.
+pub fn render_chart(spec: &ChartSpec) -> Vec<u8> {
+    let mut c = Canvas::new(CHART_WIDTH, CHART_HEIGHT);
+    let plot_w = (CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT) as f64;
+    let plot_h = (CHART_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM) as f64;
+    let left = MARGIN_LEFT as f64;
+    let top = MARGIN_TOP as f64;
+
+    let lo = spec.values.iter().copied().fold(0.0_f64, f64::min);
+    let mut hi = spec.values.iter().copied().fold(0.0_f64, f64::max);
+    if hi <= lo {
+        hi = lo + 1.0;
+    }
+    let y_of = |v: f64| (top + (hi - v) / (hi - lo) * plot_h).round() as i64;
+    let right = (left + plot_w) as i64;
+
+    for i in 0..=4 {
+        let y = (top + plot_h * i as f64 / 4.0).round() as i64;
+        c.line((left as i64, y), (right, y), 1, GRID);
+    }
+
+    let n = spec.values.len();
+    let slot = plot_w / n as f64;
+    let zero = y_of(0.0);
+    match spec.kind {
+        ChartKind::Bar => {
+            for (i, &v) in spec.values.iter().enumerate() {
+                let x0 = (left + i as f64 * slot + slot * 0.2).round() as i64;
+                let x1 = (left + (i + 1) as f64 * slot - slot * 0.2).round() as i64;
+                c.fill_rect(x0, y_of(v), x1.max(x0), zero, BAR);
+            }
+        }
+        ChartKind::Line => {
+            let pts: Vec<(i64, i64)> = spec
+                .values
+                .iter()
+                .enumerate()
+                .map(|(i, &v)| ((left + (i as f64 + 0.5) * slot).round() as i64, y_of(v)))
+                .collect();
+            for w in pts.windows(2) {
+                c.line(w[0], w[1], 2, LINE);
+            }
+            for &(x, y) in &pts {
+                c.fill_rect(x - 2, y - 2, x + 2, y + 2, LINE);
+            }
+        }
+    }
+
+    let bottom = (top + plot_h).round() as i64;
+    c.line((left as i64, top as i64), (left as i64, bottom), 1, AXIS);
+    c.line((left as i64, zero), (right, zero), 1, AXIS);
+
+    encode_png(&c)
+}
+
+// --- PNG encoding (palette, stored deflate) ---
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &x in data {
+        a = (a + x as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// zlib stream using uncompressed (stored) deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 65535 * 5 + 16);
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut chunks = data.chunks(65535).peekable();
+    if chunks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+pub(crate) fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn encode_png(c: &Canvas) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((c.width + 1) * c.height);
+    for row in c.px.chunks(c.width) {
+        raw.push(0); // filter: none
+        raw.extend_from_slice(row);
+    }
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(c.width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(c.height as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 3, 0, 0, 0]); // 8-bit, palette
+    let plte: Vec<u8> = PALETTE.iter().flatten().copied().collect();
+
+    let mut out = Vec::with_capacity(raw.len() + 256);
+    out.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+    png_chunk(&mut out, b"IHDR", &ihdr);
+    png_chunk(&mut out, b"PLTE", &plte);
+    png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+    png_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+// --- Tool ---
+
+fn has_photo_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| PHOTO_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// send_photo tool: send a workspace image or a rendered chart inline to the current chat.
+pub struct SendPhotoTool;
+
+impl Tool for SendPhotoTool {
+    fn name(&self) -> &str {
+        "send_photo"
+    }
+
+    fn description(&self) -> &str {
+        "Send an image inline to the user in the current chat. Pass either `path` (PNG/JPEG/WebP in the workspace) or `chart` to render a simple bar or line chart (e.g. workout progress). Chart labels and values are sent as the caption."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Image path relative to workspace" },
+                "chart": {
+                    "type": "object",
+                    "description": "Chart to render instead of sending a file",
+                    "properties": {
+                        "type": { "type": "string", "enum": ["bar", "line"] },
+                        "title": { "type": "string" },
+                        "labels": { "type": "array", "items": { "type": "string" } },
+                        "values": { "type": "array", "items": { "type": "number" } }
+                    },
+                    "required": ["values"]
+                },
+                "caption": { "type": "string", "description": "Optional caption (defaults to chart title and values)" }
+            }
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let args = args.clone();
+        let ctx = ctx.clone();
+
+        Box::pin(async move {
+            let caption = args.get("caption").and_then(Value::as_str);
+            let (path, caption, label) = match (
+                args.get("path").and_then(Value::as_str),
+                args.get("chart").filter(|c| c.is_object()),
+            ) {
+                (Some(p), None) => {
+                    let resolved =
+                        match resolve_path(p, &ctx.workspace, ctx.restrict_to_workspace).await {
+                            Ok(r) => r,
+                            Err(e) => return ToolResult::error(e),
+                        };
+                    if !has_photo_extension(&resolved) {
+                        return ToolResult::error(format!(
+                            "{p}: not a supported image ({})",
+                            PHOTO_EXTENSIONS.join(", ")
+                        ));
+                    }
+                    let meta = match tokio::fs::metadata(&resolved).await {
+                        Ok(m) => m,
+                        Err(e) => return ToolResult::error(format!("{p}: {e}")),
+                    };
+                    if !meta.is_file() {
+                        return ToolResult::error(format!("{p}: not a file"));
+                    }
+                    if meta.len() > MAX_PHOTO_BYTES {
+                        return ToolResult::error(format!(
+                            "{p}: image too large ({} bytes, max {MAX_PHOTO_BYTES})",
+                            meta.len()
+                        ));
+                    }
+                    (resolved, caption.unwrap_or("").to_string(), p.to_string())
+                }
+                (None, Some(chart)) => {
+                    let spec = match ChartSpec::from_json(chart) {
+                        Ok(s) => s,
+                        Err(e) => return ToolResult::error(e),
+                    };
+                    let png = render_chart(&spec);
+                    let dir = workspace::charts_dir(&ctx.workspace);
+                    if let Err(e) = tokio::fs::create_dir_all(&dir).await {
+                        return ToolResult::error(format!("create {}: {e}", dir.display()));
+                    }
+                    let out = dir.join(format!("chart-{}.png", uuid::Uuid::new_v4()));
+                    if let Err(e) = tokio::fs::write(&out, png).await {
+                        return ToolResult::error(format!("write {}: {e}", out.display()));
+                    }
+                    let caption = caption.map(String::from).unwrap_or_else(|| spec.caption());
+                    (out, caption, "chart".to_string())
+                }
+                _ => return ToolResult::error("pass exactly one of 'path' or 'chart'"),
+            };
+
+            let Some(tx) = &ctx.outbound_tx else {
+                return ToolResult::error("no outbound channel (send_photo unavailable)");
+            };
+            let Some(chat_id) = ctx.chat_id else {
+                return ToolResult::error("no chat_id (send_photo unavailable)");
+            };
+            let channel = ctx
+                .channel
+                .clone()
+                .unwrap_or_else(|| "telegram".to_string());
+            let msg = OutboundMsg {
+                chat_id,
+                text: caption,
+                channel,
+                kind: OutboundKind::Photo(path),
+                thread_id: ctx.thread_id,
+                disable_notification: false,
+            };
+            match tx.try_send(msg) {
+                Ok(()) => {
+                    ctx.delivered.store(true, Ordering::Relaxed);
+                    ToolResult::silent(format!("sent {label}"))
+                }
+                Err(e) => ToolResult::error(e.to_string()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_known_value() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn adler32_known_value() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn zlib_stored_splits_large_input() {
+        let data = vec![7u8; 70_000];
+        let z = zlib_stored(&data);
+        // header + 2 blocks * 5 bytes + data + adler
+        assert_eq!(z.len(), 2 + 10 + 70_000 + 4);
+        assert_eq!(z[2], 0); // first block not final
+        assert_eq!(z[2 + 5 + 65535], 1); // second block final
+    }
+
+    #[test]
+    fn chart_spec_parses_and_validates() {
+        let spec = ChartSpec::from_json(&serde_json::json!({
+            "type": "line",
+            "title": "Squat",
+            "labels": ["Mon", "Wed"],
+            "values": [100, 102.5]
+        }))
+        .unwrap();
+        assert_eq!(spec.kind, ChartKind::Line);
+        assert_eq!(spec.caption(), "Squat\nMon: 100\nWed: 102.5");
+
+        let default_labels =
+            ChartSpec::from_json(&serde_json::json!({ "values": [1, 2, 3] })).unwrap();
+        assert_eq!(default_labels.kind, ChartKind::Bar);
+        assert_eq!(default_labels.labels, vec!["1", "2", "3"]);
+
+        assert!(ChartSpec::from_json(&serde_json::json!({ "values": [] })).is_err());
+        assert!(ChartSpec::from_json(&serde_json::json!({ "values": ["x"] })).is_err());
+        assert!(
+            ChartSpec::from_json(&serde_json::json!({ "labels": ["a"], "values": [1, 2] }))
+                .is_err()
+        );
+        assert!(
+            ChartSpec::from_json(&serde_json::json!({ "type": "pie", "values": [1] })).is_err()
+        );
+    }
+
+    #[test]
+    fn render_chart_produces_png() {
+        for kind in ["bar", "line"] {
+            let spec = ChartSpec::from_json(&serde_json::json!({
+                "type": kind,
+                "values": [3, -1, 4, 1, 5]
+            }))
+            .unwrap();
+            let png = render_chart(&spec);
+            assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+            assert_eq!(&png[12..16], b"IHDR");
+            assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 640);
+            assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 400);
+            assert!(png.ends_with(&[0xAE, 0x42, 0x60, 0x82]));
+        }
+    }
+
+    #[test]
+    fn photo_extension_check() {
+        assert!(has_photo_extension(Path::new("a/b.PNG")));
+        assert!(has_photo_extension(Path::new("x.jpeg")));
+        assert!(!has_photo_extension(Path::new("x.gif")));
+        assert!(!has_photo_extension(Path::new("x")));
+    }
+}
@@ -0,0 +1,334 @@
+//! `session` tool: archive the current conversation and start fresh, list past sessions,
+//! or resume one. Nothing is deleted; sessions are rows of `chat_history` grouped by
+//! `session_id` (see `BrainDb::start_new_session`).
+//!
+//! Switching takes effect from the next message: the rest of the current turn is still
+//! saved to the session it started in.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::memory::db::{ArchivedSession, BrainDb, DbError, SessionInfo};
+use crate::telegram;
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Sessions listed when no limit is given.
+const DEFAULT_LIMIT: usize = 10;
+/// Characters of the first user message shown per listed session.
+const PREVIEW_CHARS: usize = 80;
+/// Characters of a session id shown in listings (enough to resume by prefix).
+const SHORT_ID_CHARS: usize = 8;
+/// Characters of an earlier session's summary recalled in the system prompt.
+const RECALL_CHARS: usize = 300;
+
+pub struct SessionTool {
+    db: Arc<BrainDb>,
+}
+
+impl SessionTool {
+    pub fn new(db: Arc<BrainDb>) -> Self {
+        Self { db }
+    }
+}
+
+impl Tool for SessionTool {
+    fn name(&self) -> &str {
+        "session"
+    }
+
+    fn description(&self) -> &str {
+        "Manage conversation sessions of this chat without deleting anything. \
+         new: archive the current conversation and start fresh from the next message. \
+         list: show past sessions (id, dates, first message). \
+         resume: switch back to a past session by id (or id prefix) from list."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["new", "list", "resume"],
+                    "description": "new session, list sessions, or resume one"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Session id or unique prefix from list (resume)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Max sessions to list (list, default 10)",
+                    "minimum": 1,
+                    "maximum": 50
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let db = Arc::clone(&self.db);
+        let args = args.clone();
+        let key = ctx
+            .chat_id
+            .map(|id| telegram::session_key(id, ctx.thread_id));
+
+        Box::pin(async move {
+            let Some(key) = key else {
+                return ToolResult::error("no chat_id (session unavailable)");
+            };
+            let action = args
+                .get("action")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            match action.as_str() {
+                "new" => {
+                    match tokio::task::spawn_blocking(move || db.start_new_session(&key)).await {
+                        Ok(Ok(id)) => ToolResult::ok(format!(
+                            "Started new session {}; the previous one is archived.",
+                            short_id(&id)
+                        )),
+                        Ok(Err(e)) => ToolResult::error(format!("session new failed: {e}")),
+                        Err(e) => ToolResult::error(format!("session task error: {e}")),
+                    }
+                }
+                "list" => {
+                    let limit = args
+                        .get("limit")
+                        .and_then(Value::as_u64)
+                        .map_or(DEFAULT_LIMIT, |v| (v as usize).clamp(1, 50));
+                    match tokio::task::spawn_blocking(move || db.list_sessions(&key)).await {
+                        Ok(Ok(sessions)) if sessions.is_empty() => {
+                            ToolResult::ok("No stored sessions yet.")
+                        }
+                        Ok(Ok(sessions)) => ToolResult::ok(format_sessions(&sessions, limit)),
+                        Ok(Err(e)) => ToolResult::error(format!("session list failed: {e}")),
+                        Err(e) => ToolResult::error(format!("session task error: {e}")),
+                    }
+                }
+                "resume" => {
+                    let wanted = args
+                        .get("session_id")
+                        .and_then(Value::as_str)
+                        .map(str::trim)
+                        .unwrap_or("")
+                        .to_string();
+                    if wanted.is_empty() {
+                        return ToolResult::error("resume requires 'session_id' (from list)");
+                    }
+                    let result = tokio::task::spawn_blocking(
+                        move || -> Result<Result<String, String>, DbError> {
+                            let sessions = db.list_sessions(&key)?;
+                            let s = match resolve_session(&sessions, &wanted) {
+                                Ok(s) => s,
+                                Err(e) => return Ok(Err(e)),
+                            };
+                            if s.current {
+                                return Ok(Ok(format!(
+                                    "Session {} is already active.",
+                                    short_id(&s.session_id)
+                                )));
+                            }
+                            db.resume_session(&key, &s.session_id)?;
+                            Ok(Ok(format!(
+                                "Resumed session {} ({} messages, last active {}).",
+                                short_id(&s.session_id),
+                                s.messages,
+                                s.last_active
+                            )))
+                        },
+                    )
+                    .await;
+                    match result {
+                        Ok(Ok(Ok(msg))) => ToolResult::ok(msg),
+                        Ok(Ok(Err(msg))) => ToolResult::error(msg),
+                        Ok(Err(e)) => ToolResult::error(format!("session resume failed: {e}")),
+                        Err(e) => ToolResult::error(format!("session task error: {e}")),
+                    }
+                }
+                other => ToolResult::error(format!(
+                    "unknown action '{other}' (use new, list or resume)"
+                )),
+            }
+        })
+    }
+}
+
+/// The one session whose id starts with `wanted` (a full id or a prefix from a listing).
+pub fn resolve_session<'a>(
+    sessions: &'a [SessionInfo],
+    wanted: &str,
+) -> Result<&'a SessionInfo, String> {
+    let matches: Vec<&SessionInfo> = sessions
+        .iter()
+        .filter(|s| s.session_id.starts_with(wanted))
+        .collect();
+    match matches.as_slice() {
+        [] => Err(format!("no session matching '{wanted}'")),
+        [s] => Ok(s),
+        _ => Err(format!(
+            "'{wanted}' matches {} sessions; give more of the id",
+            matches.len()
+        )),
+    }
+}
+
+/// First characters of a session id, as shown in listings.
+pub fn short_id(id: &str) -> &str {
+    id.get(..SHORT_ID_CHARS).unwrap_or(id)
+}
+
+/// One line per session: `abcd1234 (current) 2026-02-01 → 2026-02-03, 12 msgs: "first…"`.
+fn format_sessions(sessions: &[SessionInfo], limit: usize) -> String {
+    let mut lines: Vec<String> = sessions
+        .iter()
+        .take(limit)
+        .map(|s| {
+            let marker = if s.current { " (current)" } else { "" };
+            let mut preview: String = s.first_message.chars().take(PREVIEW_CHARS).collect();
+            if s.first_message.chars().count() > PREVIEW_CHARS {
+                preview.push('…');
+            }
+            let preview = preview.replace('\n', " ");
+            format!(
+                "{}{marker} {} → {}, {} msgs: \"{preview}\"",
+                short_id(&s.session_id),
+                s.started_at,
+                s.last_active,
+                s.messages
+            )
+        })
+        .collect();
+    if sessions.len() > limit {
+        lines.push(format!("… and {} older session(s)", sessions.len() - limit));
+    }
+    lines.join("\n")
+}
+
+/// One line per earlier session for the system prompt:
+/// `- abcd1234 (2026-02-01): summary…`, or the first user message when never summarized.
+pub fn format_recall(sessions: &[ArchivedSession]) -> String {
+    sessions
+        .iter()
+        .map(|s| {
+            let (source, quote) = if s.summary.is_empty() {
+                (s.first_message.as_str(), "\"")
+            } else {
+                (s.summary.as_str(), "")
+            };
+            let mut text: String = source.chars().take(RECALL_CHARS).collect();
+            if source.chars().count() > RECALL_CHARS {
+                text.push('…');
+            }
+            let date = chrono::DateTime::from_timestamp(s.archived_at, 0)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            format!(
+                "- {} ({date}): {quote}{}{quote}",
+                short_id(&s.session_id),
+                text.replace('\n', " ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::db::StoredMessage;
+    use tempfile::TempDir;
+
+    fn ctx() -> ToolCtx {
+        ToolCtx {
+            chat_id: Some(42),
+            channel: Some("telegram".into()),
+            ..ToolCtx::for_tests(std::env::temp_dir())
+        }
+    }
+
+    fn say(db: &BrainDb, text: &str) {
+        let sid = db.get_or_create_session_id("42").unwrap();
+        let msg = StoredMessage {
+            role: "user".into(),
+            content: text.into(),
+            tool_call_id: None,
+            tool_calls: None,
+        };
+        db.append_session("42", &sid, &[msg], "").unwrap();
+    }
+
+    #[tokio::test]
+    async fn new_list_resume() {
+        let tmp = TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let tool = SessionTool::new(Arc::clone(&db));
+        say(&db, "planning the Lisbon trip");
+        let first = db.get_or_create_session_id("42").unwrap();
+
+        let res = tool
+            .execute(&ctx(), &serde_json::json!({"action": "new"}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        say(&db, "leg day");
+
+        let res = tool
+            .execute(&ctx(), &serde_json::json!({"action": "list"}))
+            .await;
+        let lines: Vec<&str> = res.for_llm.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", res.for_llm);
+        assert!(lines[0].contains("(current)") && lines[0].contains("leg day"));
+        assert!(lines[1].starts_with(&first[..8]) && lines[1].contains("Lisbon"));
+
+        let res = tool
+            .execute(
+                &ctx(),
+                &serde_json::json!({"action": "resume", "session_id": &first[..8]}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(db.get_or_create_session_id("42").unwrap(), first);
+    }
+
+    #[test]
+    fn recall_prefers_summaries_and_truncates() {
+        let session = |summary: &str, first: &str| ArchivedSession {
+            session_id: "abcdef1234567890".into(),
+            archived_at: 1_770_000_000,
+            summary: summary.into(),
+            first_message: first.into(),
+        };
+        let long = "x".repeat(RECALL_CHARS + 5);
+        let recall = format_recall(&[
+            session("Planned the Lisbon trip", "hi"),
+            session("", "leg day\nnotes"),
+            session(&long, ""),
+        ]);
+        let lines: Vec<&str> = recall.lines().collect();
+        assert_eq!(lines[0], "- abcdef12 (2026-02-02): Planned the Lisbon trip");
+        assert_eq!(lines[1], "- abcdef12 (2026-02-02): \"leg day notes\"");
+        assert!(lines[2].ends_with("x…"));
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments_are_errors() {
+        let tmp = TempDir::new().unwrap();
+        let tool = SessionTool::new(Arc::new(BrainDb::open(tmp.path()).unwrap()));
+        for args in [
+            serde_json::json!({"action": "resume"}),
+            serde_json::json!({"action": "resume", "session_id": "nope"}),
+            serde_json::json!({"action": "dance"}),
+        ] {
+            assert!(tool.execute(&ctx(), &args).await.is_error, "{args}");
+        }
+    }
+}
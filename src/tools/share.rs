@@ -0,0 +1,296 @@
+//! `share_text` tool: upload long content (a generated report, a vault note) to a GitHub Gist
+//! or paste.rs and return the link, instead of splitting it over many Telegram messages.
+//!
+//! Gists use the `[tools.github]` token (it needs the `gist` scope) and are secret unless
+//! `public`; paste.rs needs no account, and its pastes are readable by anyone with the link.
+
+use std::path::Path;
+
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::github::{API_VERSION, DEFAULT_API_URL};
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const DEFAULT_PASTE_URL: &str = "https://paste.rs";
+const DEFAULT_FILENAME: &str = "note.md";
+/// Largest text shared in one go (1 MB).
+const MAX_BYTES: usize = 1024 * 1024;
+
+/// A file name safe for a gist: no directories, something left.
+fn clean_filename(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or("").trim();
+    if name.is_empty() || name.chars().all(|c| c == '.') {
+        DEFAULT_FILENAME.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+pub struct ShareTextTool {
+    client: Client,
+    github_token: Option<String>,
+    gist_api_url: String,
+    paste_url: String,
+}
+
+impl ShareTextTool {
+    /// Gists are available when `github_token` is set.
+    pub fn new(client: Client, github_token: Option<String>) -> Self {
+        Self {
+            client,
+            github_token: github_token
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+            gist_api_url: DEFAULT_API_URL.to_string(),
+            paste_url: DEFAULT_PASTE_URL.to_string(),
+        }
+    }
+
+    /// Use other endpoints with the same APIs (e.g. a test server).
+    pub fn with_urls(mut self, gist_api_url: &str, paste_url: &str) -> Self {
+        self.gist_api_url = gist_api_url.trim_end_matches('/').to_string();
+        self.paste_url = paste_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    async fn gist(
+        &self,
+        token: &str,
+        filename: &str,
+        content: &str,
+        args: &Value,
+    ) -> Result<String, String> {
+        let public = args.get("public").and_then(Value::as_bool).unwrap_or(false);
+        let description = args
+            .get("description")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let payload = serde_json::json!({
+            "description": description,
+            "public": public,
+            "files": { filename: { "content": content } }
+        });
+        let res = self
+            .client
+            .post(format!("{}/gists", self.gist_api_url))
+            .bearer_auth(token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", API_VERSION)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| format!("GitHub request failed: {e}"))?;
+        let status = res.status();
+        let body: Value = res.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = body.get("message").and_then(Value::as_str).unwrap_or("");
+            let hint = if matches!(status, StatusCode::FORBIDDEN | StatusCode::NOT_FOUND) {
+                " (the token needs the gist scope)"
+            } else {
+                ""
+            };
+            return Err(format!("GitHub error: HTTP {status}: {message}{hint}"));
+        }
+        let url = body
+            .get("html_url")
+            .and_then(Value::as_str)
+            .ok_or("GitHub returned no gist URL")?;
+        let kind = if public { "public" } else { "secret" };
+        Ok(format!("Shared {filename} as a {kind} gist: {url}"))
+    }
+
+    async fn paste(&self, filename: &str, content: &str) -> Result<String, String> {
+        let res = self
+            .client
+            .post(format!("{}/", self.paste_url))
+            .body(content.to_string())
+            .send()
+            .await
+            .map_err(|e| format!("paste.rs request failed: {e}"))?;
+        let status = res.status();
+        let body = res.text().await.map_err(|e| e.to_string())?;
+        if status == StatusCode::PARTIAL_CONTENT {
+            return Err("paste.rs accepted only part of the text (too long); use a gist".into());
+        }
+        if !status.is_success() {
+            return Err(format!("paste.rs error: HTTP {status}: {}", body.trim()));
+        }
+        // The extension picks paste.rs's rendering (markdown, syntax highlighting).
+        let mut url = body.trim().to_string();
+        if let Some(ext) = Path::new(filename).extension().and_then(|e| e.to_str()) {
+            url = format!("{url}.{ext}");
+        }
+        Ok(format!(
+            "Shared {filename} on paste.rs (anyone with the link can read it): {url}"
+        ))
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let text = args.get("text").and_then(Value::as_str);
+        let rel = args.get("path").and_then(Value::as_str);
+        let (content, default_name) = match (text, rel) {
+            (Some(text), None) => (text.to_string(), DEFAULT_FILENAME.to_string()),
+            (None, Some(rel)) => {
+                let path = resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+                let content = tokio::fs::read_to_string(&path)
+                    .await
+                    .map_err(|e| format!("{rel}: {e}"))?;
+                (content, clean_filename(rel))
+            }
+            _ => return Err("give either 'text' or 'path'".into()),
+        };
+        if content.trim().is_empty() {
+            return Err("nothing to share".into());
+        }
+        if content.len() > MAX_BYTES {
+            return Err(format!(
+                "text is {} bytes; the limit is {MAX_BYTES}",
+                content.len()
+            ));
+        }
+        let filename = args
+            .get("filename")
+            .and_then(Value::as_str)
+            .map(clean_filename)
+            .unwrap_or(default_name);
+
+        let default_service = match self.github_token {
+            Some(_) => "gist",
+            None => "paste.rs",
+        };
+        let service = args
+            .get("service")
+            .and_then(Value::as_str)
+            .unwrap_or(default_service);
+        match service {
+            "gist" => {
+                let token = self
+                    .github_token
+                    .as_deref()
+                    .ok_or("gists need a token in [tools.github]; use service paste.rs")?;
+                self.gist(token, &filename, &content, args).await
+            }
+            "paste.rs" => self.paste(&filename, &content).await,
+            other => Err(format!("unknown service '{other}' (use gist or paste.rs)")),
+        }
+    }
+}
+
+impl Tool for ShareTextTool {
+    fn name(&self) -> &str {
+        "share_text"
+    }
+
+    fn description(&self) -> &str {
+        "Upload long text or a workspace file to a GitHub Gist (secret by default) or paste.rs \
+         and return the link. Use for reports and documents too long for one chat message."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "text": { "type": "string", "description": "Content to share (or use path)" },
+                "path": { "type": "string", "description": "Workspace file to share (or use text)" },
+                "filename": { "type": "string", "description": "Name shown with the content; its extension sets rendering (default note.md or the file's name)" },
+                "service": { "type": "string", "enum": ["gist", "paste.rs"], "description": "Default gist when a GitHub token is configured, else paste.rs" },
+                "description": { "type": "string", "description": "gist: description" },
+                "public": { "type": "boolean", "description": "gist: list it publicly (default false: secret, link only)" }
+            }
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::tools::web::web_client;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    #[tokio::test]
+    async fn shares_a_file_as_a_secret_gist() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("Reports")).unwrap();
+        std::fs::write(dir.path().join("Reports/Weekly.md"), "# Week 41\n").unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/gists"))
+            .and(header("authorization", "Bearer ghp_test"))
+            .and(body_json(json!({
+                "description": "",
+                "public": false,
+                "files": { "Weekly.md": { "content": "# Week 41\n" } }
+            })))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .set_body_json(json!({ "html_url": "https://gist.github.com/me/abc" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let tool = ShareTextTool::new(web_client().unwrap(), Some("ghp_test".into()))
+            .with_urls(&server.uri(), &server.uri());
+
+        let res = tool
+            .execute(&ctx(dir.path()), &json!({"path": "Reports/Weekly.md"}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "Shared Weekly.md as a secret gist: https://gist.github.com/me/abc"
+        );
+    }
+
+    #[tokio::test]
+    async fn paste_rs_without_a_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_string("long report"))
+            .respond_with(ResponseTemplate::new(201).set_body_string("https://paste.rs/Xy1\n"))
+            .mount(&server)
+            .await;
+        let tool =
+            ShareTextTool::new(web_client().unwrap(), None).with_urls(&server.uri(), &server.uri());
+
+        let res = tool
+            .execute(&ctx(dir.path()), &json!({"text": "long report"}))
+            .await;
+        assert_eq!(
+            res.for_llm,
+            "Shared note.md on paste.rs (anyone with the link can read it): https://paste.rs/Xy1.md"
+        );
+        let res = tool
+            .execute(&ctx(dir.path()), &json!({"text": "x", "service": "gist"}))
+            .await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("[tools.github]"), "{}", res.for_llm);
+        assert_eq!(clean_filename("../../etc/passwd"), "passwd");
+        assert_eq!(clean_filename(".."), "note.md");
+    }
+}
@@ -74,6 +74,7 @@ impl Tool for SpawnTool {
                 task,
                 label.clone(),
                 chat_id,
+                ctx.thread_id,
                 Arc::clone(outbound_tx),
                 channel,
             );
@@ -146,16 +147,24 @@ mod tests {
                 api_base: Some("http://localhost:1".into()),
                 api_key: Some("test".into()),
                 model: Some("test".into()),
+                ..Default::default()
             }),
             tools: None,
             heartbeat: None,
+            cron: None,
             timezone: None,
+            voice: None,
+            backup: None,
+            database: None,
+            retention: None,
+            index: None,
         };
         let llm = crate::llm::HttpProvider::from_config(&cfg).expect("stub");
         SubagentManager::new(
             Arc::new(llm),
             Arc::new(crate::tools::registry::ToolRegistry::new()),
             "test".into(),
+            Default::default(),
             std::path::PathBuf::from("/tmp"),
             true,
             5,
@@ -166,22 +175,13 @@ mod tests {
         if full {
             let (tx, _rx) = tokio::sync::mpsc::channel(4);
             ToolCtx {
-                workspace: std::path::PathBuf::from("/tmp"),
-                restrict_to_workspace: true,
                 chat_id: Some(123),
                 channel: Some("telegram".into()),
                 outbound_tx: Some(Arc::new(tx)),
-                delivered: Default::default(),
+                ..ToolCtx::for_tests(std::path::PathBuf::from("/tmp"))
             }
         } else {
-            ToolCtx {
-                workspace: std::path::PathBuf::from("/tmp"),
-                restrict_to_workspace: true,
-                chat_id: None,
-                channel: None,
-                outbound_tx: None,
-                delivered: Default::default(),
-            }
+            ToolCtx::for_tests(std::path::PathBuf::from("/tmp"))
         }
     }
 }
@@ -6,6 +6,7 @@ use serde_json::Value;
 
 use crate::agent::run_agent_loop;
 use crate::agent::subagent_manager::SubagentManager;
+use crate::llm::usage::{self, UsageScope};
 use crate::llm::{Message, Role};
 use crate::skills;
 use crate::tools::context::ToolCtx;
@@ -74,6 +75,8 @@ impl Tool for SubagentTool {
             channel: Some(channel),
             outbound_tx,
             delivered,
+            location: ctx.location,
+            thread_id: ctx.thread_id,
         };
 
         Box::pin(async move {
@@ -135,23 +138,35 @@ impl Tool for SubagentTool {
                     content: system,
                     tool_call_id: None,
                     tool_calls: None,
+                    images: Vec::new(),
                 },
                 Message {
                     role: Role::User,
                     content: task,
                     tool_call_id: None,
                     tool_calls: None,
+                    images: Vec::new(),
                 },
             ];
 
             // --- Run Agent Loop Synchronously ---
-            match run_agent_loop(
-                manager.llm(),
-                manager.registry(),
-                messages,
-                &sub_ctx,
-                manager.model(),
-                manager.max_iterations(),
+            // Bill the subagent's calls to the same chat, under its own source.
+            let usage_scope = UsageScope {
+                source: format!("subagent:{}", label.as_deref().unwrap_or("sync")),
+                ..usage::current_scope()
+            };
+            match usage::scoped(
+                usage_scope,
+                run_agent_loop(
+                    manager.llm(),
+                    manager.registry(),
+                    messages,
+                    &sub_ctx,
+                    manager.model(),
+                    manager.params(),
+                    manager.max_iterations(),
+                    None,
+                ),
             )
             .await
             {
@@ -209,10 +224,17 @@ mod tests {
                 api_base: Some("http://localhost:1".into()),
                 api_key: Some("test".into()),
                 model: Some("test".into()),
+                ..Default::default()
             }),
             tools: None,
             heartbeat: None,
+            cron: None,
             timezone: None,
+            voice: None,
+            backup: None,
+            database: None,
+            retention: None,
+            index: None,
         };
         // This might fail if Config::validate() checks paths, but here we just need types.
         // Actually HttpProvider::from_config might check stuff.
@@ -223,6 +245,7 @@ mod tests {
             Arc::new(llm),
             Arc::new(crate::tools::registry::ToolRegistry::new()),
             "test".into(),
+            Default::default(),
             std::path::PathBuf::from("/tmp"),
             true,
             5,
@@ -231,12 +254,9 @@ mod tests {
 
     fn test_ctx() -> ToolCtx {
         ToolCtx {
-            workspace: std::path::PathBuf::from("/tmp"),
-            restrict_to_workspace: true,
             chat_id: Some(123),
             channel: Some("telegram".into()),
-            outbound_tx: None,
-            delivered: Default::default(),
+            ..ToolCtx::for_tests(std::path::PathBuf::from("/tmp"))
         }
     }
 }
@@ -0,0 +1,351 @@
+//! `summarize_url` tool: fetch a page and summarize it map-reduce style — the text is split into
+//! chunks, each chunk is condensed into notes, and the notes are merged into one summary of
+//! bounded length. Uses its own model (`[tools.summarize] model`, default the chat model) so a
+//! cheap model does the reading, and `[llm.summarize]` sampling.
+
+use std::sync::Arc;
+
+use futures_util::future::join_all;
+use serde_json::Value;
+
+use crate::config::SummarizeConfig;
+use crate::llm::{GenParams, LlmProvider, Message, Role};
+use crate::memory::embeddings::chunk_text;
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::tools::web::WebFetchTool;
+
+/// About 3k tokens per chunk.
+const CHUNK_CHARS: usize = 12_000;
+/// Text beyond this is dropped (about 25 chunks).
+const MAX_INPUT_CHARS: usize = 300_000;
+/// Chunks summarized at once.
+const MAP_CONCURRENCY: usize = 4;
+const NOTES_MAX_TOKENS: usize = 600;
+const DEFAULT_WORDS: u64 = 200;
+const MAX_WORDS: u64 = 800;
+
+const MAP_PROMPT: &str = "You take notes on one part of a longer web page. List its key facts, \
+    arguments, numbers and names as terse bullet points. Skip navigation, ads, cookie banners \
+    and comments. Do not add anything that is not in the text.";
+
+const REDUCE_PROMPT: &str = "You write the final summary of a web page from its text or from \
+    notes taken on its parts, in order. Be accurate and specific; do not add anything that is \
+    not in the input. Write plain prose or short bullet points, no preamble.";
+
+fn message(role: Role, content: String) -> Message {
+    Message {
+        role,
+        content,
+        tool_call_id: None,
+        tool_calls: None,
+        images: Vec::new(),
+    }
+}
+
+pub struct SummarizeUrlTool {
+    fetcher: WebFetchTool,
+    llm: Arc<dyn LlmProvider>,
+    model: String,
+    params: GenParams,
+}
+
+impl SummarizeUrlTool {
+    /// `model` is used unless `[tools.summarize] model` overrides it; `params` are
+    /// `[llm.summarize]` (default temperature 0).
+    pub fn new(
+        fetcher: WebFetchTool,
+        llm: Arc<dyn LlmProvider>,
+        model: &str,
+        config: Option<&SummarizeConfig>,
+        params: &GenParams,
+    ) -> Self {
+        Self {
+            fetcher,
+            llm,
+            model: config
+                .and_then(|c| c.model.clone())
+                .unwrap_or_else(|| model.to_string()),
+            params: params.or(&GenParams {
+                temperature: Some(0.0),
+                ..GenParams::default()
+            }),
+        }
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        user: String,
+        max_tokens: usize,
+    ) -> Result<String, String> {
+        let messages = [
+            message(Role::System, system.to_string()),
+            message(Role::User, user),
+        ];
+        let params = GenParams {
+            max_tokens: Some(max_tokens),
+            ..self.params.clone()
+        };
+        let res = self
+            .llm
+            .chat_with_params(&messages, &[], &self.model, &params)
+            .await
+            .map_err(|e| format!("summarizing failed: {e}"))?;
+        let text = res.content.trim().to_string();
+        if text.is_empty() {
+            return Err("summarizing failed: the model returned nothing".into());
+        }
+        Ok(text)
+    }
+
+    /// Notes for each chunk, in order.
+    async fn map(&self, chunks: &[String], focus: &str) -> Result<Vec<String>, String> {
+        let total = chunks.len();
+        let mut notes = Vec::with_capacity(total);
+        for (batch_no, batch) in chunks.chunks(MAP_CONCURRENCY).enumerate() {
+            let calls = batch.iter().enumerate().map(|(i, chunk)| {
+                let part = batch_no * MAP_CONCURRENCY + i + 1;
+                self.complete(
+                    MAP_PROMPT,
+                    format!("Part {part} of {total}.{focus}\n\n{chunk}"),
+                    NOTES_MAX_TOKENS,
+                )
+            });
+            for result in join_all(calls).await {
+                notes.push(result?);
+            }
+        }
+        Ok(notes)
+    }
+
+    async fn run(&self, args: &Value) -> Result<String, String> {
+        let url = args
+            .get("url")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .ok_or("url is required")?;
+        let words = args
+            .get("max_words")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_WORDS)
+            .clamp(50, MAX_WORDS);
+        let focus = args
+            .get("focus")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(|f| format!(" Pay particular attention to: {f}."))
+            .unwrap_or_default();
+
+        let text = self.fetcher.fetch_text(url).await?;
+        let chars = text.chars().count();
+        let truncated = chars > MAX_INPUT_CHARS;
+        let text: String = text.chars().take(MAX_INPUT_CHARS).collect();
+        let mut parts = chunk_text(&text, CHUNK_CHARS);
+        if parts.is_empty() {
+            return Err(format!("{url}: the page has no text to summarize"));
+        }
+        let chunks = parts.len();
+
+        // Reduce the notes until they fit into one final call.
+        while parts.len() > 1 {
+            let notes = self.map(&parts, &focus).await?;
+            parts = chunk_text(&notes.join("\n\n"), CHUNK_CHARS);
+        }
+        let input = parts.pop().unwrap_or_default();
+        let summary = self
+            .complete(
+                REDUCE_PROMPT,
+                format!("Summarize in at most {words} words.{focus}\n\n{input}"),
+                words as usize * 2 + 100,
+            )
+            .await?;
+
+        let mut header = format!("Summary of {url} ({chars} chars");
+        if chunks > 1 {
+            header.push_str(&format!(", read in {chunks} parts"));
+        }
+        if truncated {
+            header.push_str(&format!(", only the first {MAX_INPUT_CHARS} read"));
+        }
+        Ok(format!("{header}):\n\n{summary}"))
+    }
+}
+
+impl Tool for SummarizeUrlTool {
+    fn name(&self) -> &str {
+        "summarize_url"
+    }
+
+    fn description(&self) -> &str {
+        "Fetch a web page and return a summary of the whole text, however long: it is read in \
+         chunks and the notes are merged. Prefer this to web_fetch for articles, papers and long \
+         posts when you need the gist rather than exact wording."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "Page to summarize (http or https)" },
+                "focus": { "type": "string", "description": "Optional question or topic to concentrate on" },
+                "max_words": { "type": "integer", "description": "Length of the summary in words (default 200, max 800)" }
+            },
+            "required": ["url"]
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::llm::{LlmError, LlmResponse, ToolDef};
+    use crate::tools::web::web_client;
+
+    /// Model, system prompt, user message and max_tokens of one request.
+    type Seen = (String, String, String, Option<usize>);
+
+    /// Answers "notes N" to chunk prompts and "final" to the merge, recording each request.
+    #[derive(Default)]
+    struct Fake {
+        seen: Mutex<Vec<Seen>>,
+    }
+
+    impl LlmProvider for Fake {
+        fn chat_with_params<'a>(
+            &'a self,
+            messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            model: &'a str,
+            params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            let mut seen = self.seen.lock().unwrap();
+            let system = messages[0].content.clone();
+            let reply = if system == MAP_PROMPT {
+                format!("notes {}", seen.len() + 1)
+            } else {
+                "final".to_string()
+            };
+            seen.push((
+                model.to_string(),
+                system,
+                messages[1].content.clone(),
+                params.max_tokens,
+            ));
+            Box::pin(async move {
+                Ok(LlmResponse {
+                    content: reply,
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".to_string(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    fn tool(fake: &Arc<Fake>) -> SummarizeUrlTool {
+        let config = SummarizeConfig {
+            model: Some("cheap-model".to_string()),
+        };
+        SummarizeUrlTool::new(
+            WebFetchTool::new(web_client().unwrap(), 0),
+            fake.clone(),
+            "chat-model",
+            Some(&config),
+            &GenParams::default(),
+        )
+    }
+
+    fn ctx() -> ToolCtx {
+        ToolCtx::for_tests(std::path::PathBuf::from("/tmp"))
+    }
+
+    async fn page(body: String) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/article"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body.into_bytes(), "text/html"))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn long_page_is_chunked_then_merged() {
+        let para = format!("<p>{}</p>\n\n", "word ".repeat(1_000));
+        let server = page(format!("<html><body>{}</body></html>", para.repeat(5))).await;
+        let fake = Arc::new(Fake::default());
+        let url = format!("{}/article", server.uri());
+
+        let res = tool(&fake)
+            .execute(
+                &ctx(),
+                &serde_json::json!({"url": url, "max_words": 100, "focus": "dates"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(res.for_llm.contains("read in 3 parts"), "{}", res.for_llm);
+        assert!(res.for_llm.ends_with("):\n\nfinal"), "{}", res.for_llm);
+
+        let seen = fake.seen.lock().unwrap();
+        assert_eq!(seen.len(), 4);
+        assert!(seen.iter().all(|(model, ..)| model == "cheap-model"));
+        assert!(
+            seen[0]
+                .2
+                .starts_with("Part 1 of 3. Pay particular attention to: dates.")
+        );
+        assert_eq!(seen[0].3, Some(NOTES_MAX_TOKENS));
+        let (_, system, user, max_tokens) = &seen[3];
+        assert_eq!(system, REDUCE_PROMPT);
+        assert!(user.starts_with("Summarize in at most 100 words."));
+        assert!(user.ends_with("notes 1\n\nnotes 2\n\nnotes 3"), "{user}");
+        assert_eq!(*max_tokens, Some(300));
+    }
+
+    #[tokio::test]
+    async fn short_page_takes_one_call_and_errors_surface() {
+        let server = page("<p>Short news item.</p>".to_string()).await;
+        let fake = Arc::new(Fake::default());
+        let tool = tool(&fake);
+
+        let url = format!("{}/article", server.uri());
+        let res = tool.execute(&ctx(), &serde_json::json!({"url": url})).await;
+        assert_eq!(
+            res.for_llm,
+            format!("Summary of {url} (16 chars):\n\nfinal")
+        );
+        assert_eq!(fake.seen.lock().unwrap().len(), 1);
+
+        let missing = format!("{}/missing", server.uri());
+        let res = tool
+            .execute(&ctx(), &serde_json::json!({"url": missing}))
+            .await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("404"), "{}", res.for_llm);
+    }
+}
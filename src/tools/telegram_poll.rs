@@ -0,0 +1,168 @@
+//! `telegram_poll` tool: post a native Telegram poll and read back the tally.
+//!
+//! Vote counts arrive as `poll` updates in the poller and are kept in the shared
+//! [`PollStore`]; `results` can wait for votes, `close` stops the poll for a final count.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::config::TelegramConfig;
+use crate::telegram::{self, NewPoll, PollStore};
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Telegram accepts 2–10 answer options.
+const MIN_OPTIONS: usize = 2;
+const MAX_OPTIONS: usize = 10;
+const MAX_WAIT_SECS: u64 = 300;
+const WAIT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+fn get_string(args: &Value, key: &str) -> Result<String, String> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| format!("missing or invalid '{key}'"))
+}
+
+pub struct TelegramPollTool {
+    polls: Arc<PollStore>,
+    telegram: TelegramConfig,
+}
+
+impl TelegramPollTool {
+    pub fn new(polls: Arc<PollStore>, telegram: TelegramConfig) -> Self {
+        Self { polls, telegram }
+    }
+}
+
+impl Tool for TelegramPollTool {
+    fn name(&self) -> &str {
+        "telegram_poll"
+    }
+
+    fn description(&self) -> &str {
+        "Post a native Telegram poll in the current chat and get the results. \
+         Actions: create (returns poll_id), results (current tally; optionally wait for votes), \
+         close (stop the poll and return the final tally)."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["create", "results", "close"]
+                },
+                "question": { "type": "string", "description": "Poll question (create)" },
+                "options": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "2-10 answer options (create)"
+                },
+                "multiple_answers": { "type": "boolean", "description": "Allow several answers (create, default false)" },
+                "anonymous": { "type": "boolean", "description": "Anonymous voting (create, default false)" },
+                "poll_id": { "type": "string", "description": "Poll id from create (results, close)" },
+                "wait_seconds": { "type": "integer", "description": "results: wait up to this long (max 300) for min_votes" },
+                "min_votes": { "type": "integer", "description": "results: stop waiting once this many people voted (default 1)" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let polls = Arc::clone(&self.polls);
+        let telegram = self.telegram.clone();
+        let args = args.clone();
+        let ctx = ctx.clone();
+
+        Box::pin(async move {
+            let action = args.get("action").and_then(Value::as_str).unwrap_or("");
+            match action {
+                "create" => {
+                    let Some(chat_id) = ctx.chat_id else {
+                        return ToolResult::error("no chat_id (telegram_poll unavailable)");
+                    };
+                    let question = match get_string(&args, "question") {
+                        Ok(q) if !q.trim().is_empty() => q,
+                        Ok(_) => return ToolResult::error("'question' must not be empty"),
+                        Err(e) => return ToolResult::error(e),
+                    };
+                    let options: Vec<String> = args
+                        .get("options")
+                        .and_then(Value::as_array)
+                        .map(|a| {
+                            a.iter()
+                                .filter_map(Value::as_str)
+                                .map(str::trim)
+                                .filter(|s| !s.is_empty())
+                                .map(String::from)
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    if !(MIN_OPTIONS..=MAX_OPTIONS).contains(&options.len()) {
+                        return ToolResult::error(format!(
+                            "'options' needs {MIN_OPTIONS}-{MAX_OPTIONS} non-empty strings"
+                        ));
+                    }
+                    let flag = |k: &str| args.get(k).and_then(Value::as_bool).unwrap_or(false);
+                    let poll = NewPoll {
+                        chat_id,
+                        thread_id: ctx.thread_id,
+                        question,
+                        options,
+                        anonymous: flag("anonymous"),
+                        multiple_answers: flag("multiple_answers"),
+                    };
+                    match telegram::send_poll(&telegram, &polls, &poll).await {
+                        Ok(poll_id) => ToolResult::ok(format!(
+                            "Poll posted (poll_id: {poll_id}). Use action=results to read votes."
+                        )),
+                        Err(e) => ToolResult::error(format!("sendPoll failed: {e}")),
+                    }
+                }
+                "results" => {
+                    let poll_id = match get_string(&args, "poll_id") {
+                        Ok(p) => p,
+                        Err(e) => return ToolResult::error(e),
+                    };
+                    let wait = args
+                        .get("wait_seconds")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(0)
+                        .min(MAX_WAIT_SECS);
+                    let min_votes = args.get("min_votes").and_then(Value::as_i64).unwrap_or(1);
+                    let deadline = tokio::time::Instant::now() + Duration::from_secs(wait);
+                    loop {
+                        let Some(state) = polls.get(&poll_id) else {
+                            return ToolResult::error(format!(
+                                "unknown poll '{poll_id}' (polls are forgotten on restart)"
+                            ));
+                        };
+                        let done = state.is_closed || state.total_voters >= min_votes;
+                        if done || tokio::time::Instant::now() >= deadline {
+                            return ToolResult::ok(state.tally());
+                        }
+                        tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+                    }
+                }
+                "close" => {
+                    let poll_id = match get_string(&args, "poll_id") {
+                        Ok(p) => p,
+                        Err(e) => return ToolResult::error(e),
+                    };
+                    match telegram::stop_poll(&telegram, &polls, &poll_id).await {
+                        Ok(state) => ToolResult::ok(state.tally()),
+                        Err(e) => ToolResult::error(format!("stopPoll failed: {e}")),
+                    }
+                }
+                other => ToolResult::error(format!(
+                    "unknown action '{other}' (use create, results or close)"
+                )),
+            }
+        })
+    }
+}
@@ -0,0 +1,734 @@
+//! `time` tool: world clock and timezone conversion, offline via chrono-tz. Places are IANA
+//! names ("Europe/Paris"), their city ("Tokyo", "New York"), common nicknames ("SF", "NYC"),
+//! abbreviations ("PST", "CET") or UTC offsets ("UTC+5:30").
+//!
+//! [`parse_when`] and [`next_at`] read times such as "9am PST" or "tomorrow 18:30 in
+//! Tokyo"; the cron tool uses them for one-shot reminders. Abbreviations name the region's
+//! wall clock, so "9am PST" in July is 9:00 Pacific daylight time — what people mean.
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::{TZ_VARIANTS, Tz};
+use serde_json::Value;
+
+use crate::config::TimeConfig;
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Abbreviations, regions and nicknames that are not the city of an IANA name.
+const ALIASES: &[(&str, &str)] = &[
+    ("utc", "UTC"),
+    ("gmt", "UTC"),
+    ("z", "UTC"),
+    ("pst", "America/Los_Angeles"),
+    ("pdt", "America/Los_Angeles"),
+    ("pt", "America/Los_Angeles"),
+    ("pacific", "America/Los_Angeles"),
+    ("mst", "America/Denver"),
+    ("mdt", "America/Denver"),
+    ("mt", "America/Denver"),
+    ("mountain", "America/Denver"),
+    ("cst", "America/Chicago"),
+    ("cdt", "America/Chicago"),
+    ("ct", "America/Chicago"),
+    ("central", "America/Chicago"),
+    ("est", "America/New_York"),
+    ("edt", "America/New_York"),
+    ("et", "America/New_York"),
+    ("eastern", "America/New_York"),
+    ("akst", "America/Anchorage"),
+    ("hst", "Pacific/Honolulu"),
+    ("bst", "Europe/London"),
+    ("wet", "Europe/Lisbon"),
+    ("cet", "Europe/Paris"),
+    ("cest", "Europe/Paris"),
+    ("eet", "Europe/Athens"),
+    ("eest", "Europe/Athens"),
+    ("msk", "Europe/Moscow"),
+    ("ist", "Asia/Kolkata"),
+    ("sgt", "Asia/Singapore"),
+    ("hkt", "Asia/Hong_Kong"),
+    ("kst", "Asia/Seoul"),
+    ("jst", "Asia/Tokyo"),
+    ("awst", "Australia/Perth"),
+    ("aest", "Australia/Sydney"),
+    ("aedt", "Australia/Sydney"),
+    ("nzst", "Pacific/Auckland"),
+    ("nzdt", "Pacific/Auckland"),
+    ("sf", "America/Los_Angeles"),
+    ("san francisco", "America/Los_Angeles"),
+    ("bay area", "America/Los_Angeles"),
+    ("silicon valley", "America/Los_Angeles"),
+    ("la", "America/Los_Angeles"),
+    ("seattle", "America/Los_Angeles"),
+    ("austin", "America/Chicago"),
+    ("dallas", "America/Chicago"),
+    ("houston", "America/Chicago"),
+    ("nyc", "America/New_York"),
+    ("boston", "America/New_York"),
+    ("washington", "America/New_York"),
+    ("dc", "America/New_York"),
+    ("atlanta", "America/New_York"),
+    ("miami", "America/New_York"),
+    ("uk", "Europe/London"),
+    ("manchester", "Europe/London"),
+    ("edinburgh", "Europe/London"),
+    ("munich", "Europe/Berlin"),
+    ("frankfurt", "Europe/Berlin"),
+    ("hamburg", "Europe/Berlin"),
+    ("barcelona", "Europe/Madrid"),
+    ("milan", "Europe/Rome"),
+    ("mumbai", "Asia/Kolkata"),
+    ("delhi", "Asia/Kolkata"),
+    ("new delhi", "Asia/Kolkata"),
+    ("bangalore", "Asia/Kolkata"),
+    ("bengaluru", "Asia/Kolkata"),
+    ("hyderabad", "Asia/Kolkata"),
+    ("india", "Asia/Kolkata"),
+    ("beijing", "Asia/Shanghai"),
+    ("shenzhen", "Asia/Shanghai"),
+    ("china", "Asia/Shanghai"),
+    ("japan", "Asia/Tokyo"),
+];
+
+/// A place's clock: an IANA zone, or a fixed UTC offset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    Named(Tz),
+    Offset(FixedOffset),
+}
+
+impl Zone {
+    /// "America/Los_Angeles", "UTC+05:30".
+    pub fn name(&self) -> String {
+        match self {
+            Zone::Named(tz) => tz.name().to_string(),
+            Zone::Offset(off) => format!("UTC{off}"),
+        }
+    }
+
+    /// Local date and time at `t`, with its offset.
+    pub fn local(&self, t: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Zone::Named(tz) => t.with_timezone(tz).fixed_offset(),
+            Zone::Offset(off) => t.with_timezone(off),
+        }
+    }
+
+    /// The instant a wall-clock time happens here; the earlier one when clocks go back, None
+    /// in the hour skipped when they go forward.
+    pub fn instant(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Named(tz) => tz
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|t| t.to_utc()),
+            Zone::Offset(off) => off.from_local_datetime(&local).single().map(|t| t.to_utc()),
+        }
+    }
+
+    /// "Thu 15 Oct 09:12 PDT (UTC-07:00)".
+    pub fn show(&self, t: DateTime<Utc>) -> String {
+        let offset = self.local(t).format("UTC%:z");
+        match self {
+            Zone::Named(tz) => {
+                let local = t.with_timezone(tz);
+                let abbrev = local.format("%Z").to_string();
+                if abbrev.starts_with(['+', '-']) || abbrev == "UTC" {
+                    format!("{} ({offset})", local.format("%a %-d %b %H:%M"))
+                } else {
+                    format!("{} {abbrev} ({offset})", local.format("%a %-d %b %H:%M"))
+                }
+            }
+            Zone::Offset(_) => format!("{} ({offset})", self.local(t).format("%a %-d %b %H:%M")),
+        }
+    }
+}
+
+/// "+5:30", "utc-3", "GMT+01:00" as an offset.
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let s = s.trim();
+    let lower = s.to_ascii_lowercase();
+    let rest = lower
+        .strip_prefix("utc")
+        .or_else(|| lower.strip_prefix("gmt"))
+        .unwrap_or(&lower)
+        .trim();
+    let (sign, digits) = match rest.chars().next()? {
+        '+' => (1, &rest[1..]),
+        '-' => (-1, &rest[1..]),
+        _ => return None,
+    };
+    let (h, m) = match digits.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if digits.len() == 4 => digits.split_at(2),
+        None => (digits, "0"),
+    };
+    let (h, m): (i32, i32) = (h.parse().ok()?, m.parse().ok()?);
+    if h > 14 || m >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (h * 3600 + m * 60))
+}
+
+/// The zone of a place name, abbreviation or offset; None when unknown.
+pub fn resolve_zone(name: &str) -> Option<Zone> {
+    let key = name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let key = key.trim_end_matches(" time");
+    if key.is_empty() {
+        return None;
+    }
+    if let Some(off) = parse_offset(key) {
+        return Some(Zone::Offset(off));
+    }
+    if let Some((_, tz)) = ALIASES.iter().find(|(alias, _)| *alias == key) {
+        return tz.parse().ok().map(Zone::Named);
+    }
+    let spaced = key.replace(' ', "_");
+    // The full IANA name first, then the city part of one ("new york" → America/New_York).
+    TZ_VARIANTS
+        .iter()
+        .find(|tz| tz.name().eq_ignore_ascii_case(&spaced))
+        .or_else(|| {
+            TZ_VARIANTS.iter().find(|tz| {
+                tz.name().contains('/')
+                    && tz
+                        .name()
+                        .rsplit('/')
+                        .next()
+                        .is_some_and(|city| city.eq_ignore_ascii_case(&spaced))
+            })
+        })
+        .map(|tz| Zone::Named(*tz))
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    let w = match word {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(w)
+}
+
+/// "9", "9am", "9:30", "21:05", "9.30pm", "noon".
+pub(crate) fn clock(word: &str) -> Option<NaiveTime> {
+    match word {
+        "noon" | "midday" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+    let (digits, half) = if let Some(d) = word.strip_suffix("am") {
+        (d, Some(false))
+    } else if let Some(d) = word.strip_suffix("pm") {
+        (d, Some(true))
+    } else {
+        (word, None)
+    };
+    let (h, m) = match digits.split_once([':', '.']) {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        Some(_) => return None,
+        None => (digits.parse::<u32>().ok()?, 0),
+    };
+    let h = match half {
+        Some(_) if h == 0 || h > 12 => return None,
+        Some(false) => h % 12,
+        Some(true) => h % 12 + 12,
+        None => h,
+    };
+    NaiveTime::from_hms_opt(h, m, 0)
+}
+
+/// What a time expression says: its day (when given), time of day (None for "now") and zone.
+struct Spec {
+    day: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    zone: Zone,
+}
+
+/// The zone at the end of `words` ("9am pst", "18:00 in new york", "noon tokyo time"):
+/// the zone and how many words it took.
+fn trailing_zone(words: &[String]) -> Option<(Zone, usize)> {
+    let mut words = words;
+    let mut taken = 0;
+    if words.last().is_some_and(|w| w == "time") {
+        words = &words[..words.len() - 1];
+        taken = 1;
+    }
+    (1..=words.len().min(3)).rev().find_map(|n| {
+        let candidate = words[words.len() - n..].join(" ");
+        resolve_zone(&candidate).map(|zone| (zone, taken + n))
+    })
+}
+
+fn parse_spec(input: &str, default: &Zone, now: DateTime<Utc>) -> Result<Spec, String> {
+    let lower = input
+        .trim()
+        .to_lowercase()
+        .replace("a.m.", "am")
+        .replace("p.m.", "pm")
+        .replace(',', " ");
+    // "9 am" reads as "9am".
+    let mut words: Vec<String> = Vec::new();
+    for w in lower.split_whitespace() {
+        match (w, words.last_mut()) {
+            ("am" | "pm", Some(prev))
+                if prev
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, ':' | '.')) =>
+            {
+                prev.push_str(w)
+            }
+            _ => words.push(w.to_string()),
+        }
+    }
+    let mut zone = *default;
+    if let Some((z, n)) = trailing_zone(&words) {
+        zone = z;
+        words.truncate(words.len() - n);
+    }
+    let today = zone.local(now).date_naive();
+    let mut day = None;
+    let mut time = None;
+    let mut now_given = false;
+    for w in &words {
+        let w = w.as_str();
+        let parsed_day = match w {
+            "at" | "on" | "in" | "the" | "next" | "this" => continue,
+            "now" => {
+                now_given = true;
+                continue;
+            }
+            "today" => Some(today),
+            "tomorrow" => today.succ_opt(),
+            "yesterday" => today.pred_opt(),
+            _ => weekday(w)
+                .map(|wd| {
+                    let ahead = (7 + wd.num_days_from_monday() as i64
+                        - today.weekday().num_days_from_monday() as i64)
+                        % 7;
+                    today + Duration::days(ahead)
+                })
+                .or_else(|| NaiveDate::parse_from_str(w, "%Y-%m-%d").ok()),
+        };
+        if let Some(d) = parsed_day {
+            if day.replace(d).is_some() {
+                return Err(format!("'{input}' names more than one day"));
+            }
+        } else if let Some(t) = clock(w) {
+            if time.replace(t).is_some() {
+                return Err(format!("'{input}' names more than one time"));
+            }
+        } else {
+            return Err(format!(
+                "could not read '{w}' in '{input}' (e.g. \"9am PST\", \"tomorrow 18:30 Tokyo\")"
+            ));
+        }
+    }
+    if time.is_none() && !now_given && day.is_none() {
+        return Err(format!("'{input}' has no time (e.g. 9am, 18:30 or now)"));
+    }
+    Ok(Spec { day, time, zone })
+}
+
+/// A resolved time expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct When {
+    pub at: DateTime<Utc>,
+    /// The zone the time was given in.
+    pub zone: Zone,
+    /// Whether a day was named; "9am" alone means today.
+    pub dated: bool,
+}
+
+fn resolve(
+    spec: &Spec,
+    day: NaiveDate,
+    now: DateTime<Utc>,
+    input: &str,
+) -> Result<DateTime<Utc>, String> {
+    let Some(time) = spec.time else {
+        return Ok(match spec.day {
+            // "tomorrow" alone: this time of day, tomorrow.
+            Some(_) => {
+                let local = spec.zone.local(now).time();
+                spec.zone
+                    .instant(day.and_time(local))
+                    .ok_or_else(|| format!("'{input}' does not exist in {}", spec.zone.name()))?
+            }
+            None => now,
+        });
+    };
+    spec.zone.instant(day.and_time(time)).ok_or_else(|| {
+        format!(
+            "{} {} does not exist in {} (the clocks go forward)",
+            day,
+            time.format("%H:%M"),
+            spec.zone.name()
+        )
+    })
+}
+
+/// Read a time such as "9am PST", "18:30", "tomorrow 9:00 in Tokyo", "2026-11-02 14:00
+/// Europe/Paris" or "now". Without a zone it is in `default`; without a day, today there.
+pub fn parse_when(input: &str, default: &Zone, now: DateTime<Utc>) -> Result<When, String> {
+    let spec = parse_spec(input, default, now)?;
+    let day = spec
+        .day
+        .unwrap_or_else(|| spec.zone.local(now).date_naive());
+    Ok(When {
+        at: resolve(&spec, day, now, input)?,
+        zone: spec.zone,
+        dated: spec.day.is_some(),
+    })
+}
+
+/// Like [`parse_when`], but a time of day without a day that has already passed means
+/// tomorrow: "9am PST" said in the afternoon is the next 9am.
+pub fn next_at(input: &str, default: &Zone, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let spec = parse_spec(input, default, now)?;
+    if spec.day.is_some() || spec.time.is_none() {
+        return parse_when(input, default, now).map(|w| w.at);
+    }
+    let today = spec.zone.local(now).date_naive();
+    let at = resolve(&spec, today, now, input)?;
+    if at > now {
+        return Ok(at);
+    }
+    let tomorrow = today.succ_opt().ok_or("date out of range")?;
+    resolve(&spec, tomorrow, now, input)
+}
+
+/// "Tokyo: Fri 16 Oct 01:12 JST (UTC+09:00)" for one place.
+fn clock_line(place: &str, zone: &Zone, t: DateTime<Utc>) -> String {
+    let name = zone.name();
+    if place.eq_ignore_ascii_case(&name) {
+        format!("{name}: {}", zone.show(t))
+    } else {
+        format!("{place} ({name}): {}", zone.show(t))
+    }
+}
+
+/// How far `to` is from the user's `home` at `t`: "9h ahead of you", "3h30m behind you".
+fn difference(home: &Zone, to: &Zone, t: DateTime<Utc>) -> String {
+    let secs = to.local(t).offset().local_minus_utc() - home.local(t).offset().local_minus_utc();
+    if secs == 0 {
+        return "same time as you".to_string();
+    }
+    let (h, m) = (secs.abs() / 3600, secs.abs() % 3600 / 60);
+    let span = if m == 0 {
+        format!("{h}h")
+    } else {
+        format!("{h}h{m:02}m")
+    };
+    format!(
+        "{span} {}",
+        if secs > 0 {
+            "ahead of you"
+        } else {
+            "behind you"
+        }
+    )
+}
+
+pub struct TimeTool {
+    timezone: Tz,
+    clocks: Vec<String>,
+}
+
+impl TimeTool {
+    /// `timezone` is the user's; `[tools.time] clocks` are the places `now` shows by default.
+    pub fn new(timezone: Tz, config: Option<&TimeConfig>) -> Self {
+        Self {
+            timezone,
+            clocks: config.and_then(|c| c.clocks.clone()).unwrap_or_default(),
+        }
+    }
+
+    fn places(&self, args: &Value, key: &str) -> Result<Vec<(String, Zone)>, String> {
+        let names: Vec<String> = match args.get(key) {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            Some(Value::String(s)) => s.split(',').map(str::to_string).collect(),
+            _ => Vec::new(),
+        };
+        names
+            .iter()
+            .map(|n| n.trim())
+            .filter(|n| !n.is_empty())
+            .map(|n| {
+                resolve_zone(n)
+                    .map(|z| (n.to_string(), z))
+                    .ok_or_else(|| format!("unknown place or timezone '{n}' (try a city or an IANA name like Europe/Paris)"))
+            })
+            .collect()
+    }
+
+    fn run(&self, args: &Value, now: DateTime<Utc>) -> Result<String, String> {
+        let home = Zone::Named(self.timezone);
+        let action = args.get("action").and_then(Value::as_str).unwrap_or("now");
+        match action {
+            "now" => {
+                let mut places = self.places(args, "places")?;
+                if places.is_empty() {
+                    places.push((home.name(), home));
+                    for name in &self.clocks {
+                        if let Some(zone) = resolve_zone(name) {
+                            places.push((name.clone(), zone));
+                        }
+                    }
+                }
+                let lines: Vec<String> = places
+                    .iter()
+                    .map(|(place, zone)| {
+                        let mut line = clock_line(place, zone, now);
+                        if *zone != home {
+                            line.push_str(&format!(", {}", difference(&home, zone, now)));
+                        }
+                        line
+                    })
+                    .collect();
+                Ok(lines.join("\n"))
+            }
+            "convert" => {
+                let input = args
+                    .get("time")
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .ok_or("convert needs 'time', e.g. \"9am PST\" or \"tomorrow 18:00\"")?;
+                let from = match args.get("from").and_then(Value::as_str).map(str::trim) {
+                    Some(f) if !f.is_empty() => {
+                        resolve_zone(f).ok_or_else(|| format!("unknown place or timezone '{f}'"))?
+                    }
+                    _ => home,
+                };
+                let when = parse_when(input, &from, now)?;
+                let mut targets = self.places(args, "to")?;
+                if targets.is_empty() {
+                    targets.push((home.name(), home));
+                }
+                let mut out = format!("{} = ", when.zone.show(when.at));
+                let converted: Vec<String> = targets
+                    .iter()
+                    .map(|(place, zone)| clock_line(place, zone, when.at))
+                    .collect();
+                if converted.len() == 1 {
+                    out.push_str(&converted[0]);
+                } else {
+                    out = out.trim_end_matches(" = ").to_string() + ":";
+                    for line in converted {
+                        out.push_str(&format!("\n- {line}"));
+                    }
+                }
+                Ok(out)
+            }
+            other => Err(format!("unknown action '{other}' (now or convert)")),
+        }
+    }
+}
+
+impl Tool for TimeTool {
+    fn name(&self) -> &str {
+        "time"
+    }
+
+    fn description(&self) -> &str {
+        "World clock and timezone converter (offline). now: the current time in places \
+         (\"what time is it in SF\"; default the user's zone and configured clocks). convert: \
+         a time given in one zone (\"9am PST\", \"tomorrow 18:00 in Tokyo\") in other places. \
+         Places are cities, IANA names, abbreviations like PST or offsets like UTC+5:30."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["now", "convert"], "description": "Default now" },
+                "places": { "type": "array", "items": { "type": "string" }, "description": "Places to show (now)" },
+                "time": { "type": "string", "description": "Time to convert, optionally with its zone: \"9am PST\", \"2026-11-02 14:00 Europe/Paris\" (convert)" },
+                "from": { "type": "string", "description": "Zone of 'time' when it names none (convert; default the user's)" },
+                "to": { "type": "array", "items": { "type": "string" }, "description": "Places to convert to (convert; default the user's zone)" }
+            }
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(args, Utc::now()) {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    fn zone(name: &str) -> Zone {
+        resolve_zone(name).unwrap()
+    }
+
+    #[test]
+    fn places_resolve() {
+        let la = Zone::Named(chrono_tz::America::Los_Angeles);
+        assert_eq!(zone("SF"), la);
+        assert_eq!(zone("pst"), la);
+        assert_eq!(zone("Pacific time"), la);
+        assert_eq!(zone("America/Los_Angeles"), la);
+        assert_eq!(zone("los angeles"), la);
+        assert_eq!(zone("New York"), Zone::Named(chrono_tz::America::New_York));
+        assert_eq!(zone("tokyo"), Zone::Named(chrono_tz::Asia::Tokyo));
+        assert_eq!(zone("UTC+5:30").name(), "UTC+05:30");
+        assert_eq!(zone("gmt-3").name(), "UTC-03:00");
+        assert_eq!(zone("gmt"), Zone::Named(chrono_tz::UTC));
+        assert_eq!(resolve_zone("Atlantis"), None);
+        assert_eq!(resolve_zone("9am"), None);
+    }
+
+    #[test]
+    fn clock_words() {
+        let t = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        assert_eq!(clock("9am"), t(9, 0));
+        assert_eq!(clock("12am"), t(0, 0));
+        assert_eq!(clock("12pm"), t(12, 0));
+        assert_eq!(clock("9:30pm"), t(21, 30));
+        assert_eq!(clock("21:05"), t(21, 5));
+        assert_eq!(clock("noon"), t(12, 0));
+        assert_eq!(clock("13pm"), None);
+        assert_eq!(clock("25"), None);
+    }
+
+    #[test]
+    fn pst_means_pacific_wall_time() {
+        let london = Zone::Named(chrono_tz::Europe::London);
+        // Summer: Pacific daylight time, UTC-7.
+        let now = utc("2026-07-01T12:00:00Z");
+        let w = parse_when("9am PST", &london, now).unwrap();
+        assert_eq!(w.at, utc("2026-07-01T16:00:00Z"));
+        assert!(!w.dated);
+        // Winter: UTC-8.
+        let now = utc("2026-12-01T12:00:00Z");
+        let w = parse_when("9 am pst", &london, now).unwrap();
+        assert_eq!(w.at, utc("2026-12-01T17:00:00Z"));
+        // No zone: the default one.
+        let w = parse_when("18:30", &london, now).unwrap();
+        assert_eq!(w.at, utc("2026-12-01T18:30:00Z"));
+    }
+
+    #[test]
+    fn days_and_zones_in_words() {
+        let london = Zone::Named(chrono_tz::Europe::London);
+        let now = utc("2026-10-16T12:00:00Z"); // a Friday
+        let at = |s| parse_when(s, &london, now).unwrap().at;
+        assert_eq!(at("tomorrow 9:00 in Tokyo"), utc("2026-10-17T00:00:00Z"));
+        assert_eq!(at("monday at noon"), utc("2026-10-19T11:00:00Z"));
+        assert_eq!(at("friday 8pm"), utc("2026-10-16T19:00:00Z"));
+        assert_eq!(
+            at("2026-11-02 14:00 Europe/Paris"),
+            utc("2026-11-02T13:00:00Z")
+        );
+        assert_eq!(at("noon new york time"), utc("2026-10-16T16:00:00Z"));
+        assert_eq!(at("9am UTC+5:30"), utc("2026-10-16T03:30:00Z"));
+        assert_eq!(at("now"), now);
+        assert!(parse_when("teatime", &london, now).is_err());
+        assert!(parse_when("9am 10am", &london, now).is_err());
+        assert_eq!(at("9.30 p.m."), utc("2026-10-16T20:30:00Z"));
+        // Skipped by the clocks going forward.
+        let spring = utc("2026-03-29T00:00:00Z");
+        assert!(parse_when("1:30", &london, spring).is_err());
+    }
+
+    #[test]
+    fn next_at_rolls_past_times_to_tomorrow() {
+        let london = Zone::Named(chrono_tz::Europe::London);
+        let now = utc("2026-10-16T18:00:00Z"); // 11:00 in San Francisco
+        assert_eq!(
+            next_at("9am PST", &london, now).unwrap(),
+            utc("2026-10-17T16:00:00Z")
+        );
+        assert_eq!(
+            next_at("noon PST", &london, now).unwrap(),
+            utc("2026-10-16T19:00:00Z")
+        );
+        // A named day is taken as given.
+        assert_eq!(
+            next_at("today 9am PST", &london, now).unwrap(),
+            utc("2026-10-16T16:00:00Z")
+        );
+    }
+
+    #[test]
+    fn now_and_convert() {
+        let tool = TimeTool::new(
+            chrono_tz::Europe::London,
+            Some(&TimeConfig {
+                clocks: Some(vec!["Tokyo".to_string()]),
+            }),
+        );
+        let now = utc("2026-10-16T08:30:00Z");
+        assert_eq!(
+            tool.run(&serde_json::json!({}), now).unwrap(),
+            "Europe/London: Fri 16 Oct 09:30 BST (UTC+01:00)\n\
+             Tokyo (Asia/Tokyo): Fri 16 Oct 17:30 JST (UTC+09:00), 8h ahead of you"
+        );
+        assert_eq!(
+            tool.run(&serde_json::json!({"places": ["SF"]}), now)
+                .unwrap(),
+            "SF (America/Los_Angeles): Fri 16 Oct 01:30 PDT (UTC-07:00), 8h behind you"
+        );
+        assert_eq!(
+            tool.run(
+                &serde_json::json!({"action": "convert", "time": "9am PST"}),
+                now
+            )
+            .unwrap(),
+            "Fri 16 Oct 09:00 PDT (UTC-07:00) = Europe/London: Fri 16 Oct 17:00 BST (UTC+01:00)"
+        );
+        let res = tool
+            .run(
+                &serde_json::json!({"action": "convert", "time": "17:00", "to": ["India", "NYC"]}),
+                now,
+            )
+            .unwrap();
+        assert_eq!(
+            res,
+            "Fri 16 Oct 17:00 BST (UTC+01:00):\n\
+             - India (Asia/Kolkata): Fri 16 Oct 21:30 IST (UTC+05:30)\n\
+             - NYC (America/New_York): Fri 16 Oct 12:00 EDT (UTC-04:00)"
+        );
+        assert!(
+            tool.run(&serde_json::json!({"places": ["Narnia"]}), now)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,412 @@
+//! `timer` tool: countdown timers ("20 minutes: tea") kept as one-shot jobs in the cron store,
+//! so they survive restarts and ring from the cron runner. Set, list, and cancel by name.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use regex_lite::Regex;
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::cron::{CronJob, CronStore};
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Longest timer; anything further out is a reminder for the `cron` tool.
+const MAX_TIMER_SECS: u64 = 86_400;
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Seconds in "20m", "1h30m", "1.5 hours", "2 min and 30 sec", "1:30" (m:ss) or "1:02:03";
+/// a bare number is minutes.
+pub fn parse_duration(input: &str) -> Option<u64> {
+    let s = input.trim().to_ascii_lowercase();
+    if s.contains(':') {
+        let parts: Vec<u64> = s
+            .split(':')
+            .map(|p| p.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        let secs = match parts[..] {
+            [m, sec] if sec < 60 => m * 60 + sec,
+            [h, m, sec] if m < 60 && sec < 60 => h * 3600 + m * 60 + sec,
+            _ => return None,
+        };
+        return (secs > 0).then_some(secs);
+    }
+    if let Ok(minutes) = s.parse::<f64>() {
+        let secs = (minutes * 60.0).round();
+        return (secs >= 1.0).then_some(secs as u64);
+    }
+
+    let re = Regex::new(r"(\d+(?:\.\d+)?)\s*([a-z]+)").unwrap();
+    let mut total = 0.0;
+    let mut last = 0;
+    for caps in re.captures_iter(&s) {
+        let whole = caps.get(0).unwrap();
+        let gap = s[last..whole.start()].trim().trim_matches(',').trim();
+        if !gap.is_empty() && gap != "and" {
+            return None;
+        }
+        let n: f64 = caps[1].parse().ok()?;
+        let unit = match &caps[2] {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3600.0,
+            _ => return None,
+        };
+        total += n * unit;
+        last = whole.end();
+    }
+    if last == 0 || !s[last..].trim().is_empty() {
+        return None;
+    }
+    let secs = total.round();
+    (secs >= 1.0).then_some(secs as u64)
+}
+
+/// "1h 5m", "20m", "2m 30s", "45s".
+pub fn format_secs(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    let parts: Vec<String> = [(h, "h"), (m, "m"), (s, "s")]
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, unit)| format!("{n}{unit}"))
+        .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+pub struct TimerTool {
+    store: Arc<CronStore>,
+    timezone: Tz,
+}
+
+impl TimerTool {
+    pub fn new(store: Arc<CronStore>, timezone: Tz) -> Self {
+        Self { store, timezone }
+    }
+
+    /// Running timers in this chat (all chats without one), soonest first.
+    fn running(&self, ctx: &ToolCtx) -> Vec<CronJob> {
+        let mut timers: Vec<CronJob> = self
+            .store
+            .list()
+            .into_iter()
+            .filter(|j| j.timer && j.enabled && ctx.chat_id.is_none_or(|c| c == j.chat_id))
+            .collect();
+        timers.sort_by_key(|j| j.next_run);
+        timers
+    }
+
+    fn rings_at(&self, at_unix: u64) -> String {
+        DateTime::<Utc>::from_timestamp(at_unix as i64, 0)
+            .map(|t| {
+                t.with_timezone(&self.timezone)
+                    .format("%H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+
+    fn describe(&self, job: &CronJob, now: u64) -> String {
+        let at = job.next_run.unwrap_or(now);
+        format!(
+            "{}: {} left (rings {})",
+            job.label.as_deref().unwrap_or("timer"),
+            format_secs(at.saturating_sub(now)),
+            self.rings_at(at)
+        )
+    }
+
+    fn set(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let input = args
+            .get("duration")
+            .and_then(Value::as_str)
+            .ok_or("set requires 'duration' (e.g. '20m', '1h30m', '90s')")?;
+        let secs = parse_duration(input).ok_or_else(|| {
+            format!("could not read duration '{input}' (e.g. '20m', '1h30m', '90s', '1:30')")
+        })?;
+        if secs > MAX_TIMER_SECS {
+            return Err("timers run for at most 24h; use the cron tool for later reminders".into());
+        }
+        let chat_id = ctx
+            .chat_id
+            .ok_or("timer needs a chat to ring in (no current chat)")?;
+
+        let length = format_secs(secs);
+        let base = args
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .unwrap_or(&length)
+            .to_string();
+        // Names stay unique among running timers so cancel can find them.
+        let taken: Vec<String> = self
+            .running(ctx)
+            .iter()
+            .filter_map(|j| j.label.as_deref().map(str::to_lowercase))
+            .collect();
+        let name = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{base} ({n})"),
+            })
+            .find(|candidate| !taken.contains(&candidate.to_lowercase()))
+            .expect("unbounded candidates");
+
+        let message = if name == length {
+            format!("Timer done: {name}")
+        } else {
+            format!("Timer done: {name} ({length})")
+        };
+        let at = unix_now() + secs;
+        self.store
+            .add_timer(name.clone(), message, at, chat_id)
+            .map_err(|e| e.to_string())?;
+        Ok(format!(
+            "Timer '{name}' set for {length} (rings at {}).",
+            self.rings_at(at)
+        ))
+    }
+
+    fn list(&self, ctx: &ToolCtx) -> String {
+        let timers = self.running(ctx);
+        if timers.is_empty() {
+            return "No timers running.".to_string();
+        }
+        let now = unix_now();
+        let lines: Vec<String> = timers
+            .iter()
+            .map(|j| format!("- {}", self.describe(j, now)))
+            .collect();
+        lines.join("\n")
+    }
+
+    fn cancel(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let timers = self.running(ctx);
+        if timers.is_empty() {
+            return Err("no timers running".into());
+        }
+        let names = || {
+            timers
+                .iter()
+                .filter_map(|j| j.label.clone())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let name = args
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|n| !n.is_empty());
+        let chosen: Vec<&CronJob> = match name {
+            Some(n) if n.eq_ignore_ascii_case("all") => timers.iter().collect(),
+            Some(n) => {
+                let label = |j: &CronJob| j.label.as_deref().unwrap_or("").to_lowercase();
+                let wanted = n.to_lowercase();
+                let exact: Vec<&CronJob> = timers.iter().filter(|j| label(j) == wanted).collect();
+                if exact.is_empty() {
+                    timers
+                        .iter()
+                        .filter(|j| label(j).contains(&wanted))
+                        .collect()
+                } else {
+                    exact
+                }
+            }
+            None if timers.len() == 1 => timers.iter().collect(),
+            None => return Err(format!("several timers running; name one of: {}", names())),
+        };
+        match chosen[..] {
+            [] => Err(format!(
+                "no timer named '{}' (running: {})",
+                name.unwrap_or_default(),
+                names()
+            )),
+            [_, _, ..] if !name.is_some_and(|n| n.eq_ignore_ascii_case("all")) => Err(format!(
+                "'{}' matches several timers; name one of: {}",
+                name.unwrap_or_default(),
+                names()
+            )),
+            _ => {
+                let now = unix_now();
+                let lines: Vec<String> = chosen
+                    .iter()
+                    .filter(|j| self.store.remove(&j.id))
+                    .map(|j| format!("Cancelled {}", self.describe(j, now)))
+                    .collect();
+                Ok(lines.join("\n"))
+            }
+        }
+    }
+}
+
+impl Tool for TimerTool {
+    fn name(&self) -> &str {
+        "timer"
+    }
+
+    fn description(&self) -> &str {
+        "Countdown timers (\"set a timer for 20 minutes: tea\"): set, list, cancel by name. \
+         Rings in this chat to the second. For reminders at a time of day use cron."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["set", "list", "cancel"] },
+                "duration": {
+                    "type": "string",
+                    "description": "set: how long, e.g. '20m', '1h30m', '90s', '1:30' (max 24h)"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "set: what it's for (default: the duration). cancel: which timer, or 'all'"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            let result = match args.get("action").and_then(Value::as_str) {
+                Some("set") => self.set(ctx, args),
+                Some("list") => Ok(self.list(ctx)),
+                Some("cancel") => self.cancel(ctx, args),
+                _ => Err("action must be set, list or cancel".to_string()),
+            };
+            match result {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(chat_id: i64) -> ToolCtx {
+        ToolCtx {
+            chat_id: Some(chat_id),
+            ..ToolCtx::for_tests(std::path::PathBuf::from("/tmp"))
+        }
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("20m"), Some(1200));
+        assert_eq!(parse_duration("20 minutes"), Some(1200));
+        assert_eq!(parse_duration("1h30m"), Some(5400));
+        assert_eq!(parse_duration("1.5 hours"), Some(5400));
+        assert_eq!(parse_duration("2 min and 30 sec"), Some(150));
+        assert_eq!(parse_duration("1 hour, 5 minutes"), Some(3900));
+        assert_eq!(parse_duration("45s"), Some(45));
+        assert_eq!(parse_duration("1:30"), Some(90));
+        assert_eq!(parse_duration("1:02:03"), Some(3723));
+        assert_eq!(parse_duration("5"), Some(300));
+        assert_eq!(parse_duration("0s"), None);
+        assert_eq!(parse_duration("tea"), None);
+        assert_eq!(parse_duration("5 parsecs"), None);
+        assert_eq!(parse_duration("1:75"), None);
+        assert_eq!(format_secs(5400), "1h 30m");
+        assert_eq!(format_secs(150), "2m 30s");
+        assert_eq!(format_secs(3600), "1h");
+    }
+
+    #[tokio::test]
+    async fn sets_lists_and_cancels_by_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = Arc::new(CronStore::empty(dir.path()));
+        let tool = TimerTool::new(Arc::clone(&store), chrono_tz::Europe::London);
+        let run = |chat, args: Value| {
+            let tool = &tool;
+            async move { tool.execute(&ctx(chat), &args).await }
+        };
+
+        let res = run(
+            1,
+            serde_json::json!({"action": "set", "duration": "20m", "name": "tea"}),
+        )
+        .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert!(
+            res.for_llm
+                .starts_with("Timer 'tea' set for 20m (rings at ")
+        );
+        run(
+            1,
+            serde_json::json!({"action": "set", "duration": "30s", "name": "Tea"}),
+        )
+        .await;
+        run(1, serde_json::json!({"action": "set", "duration": "90s"})).await;
+        run(2, serde_json::json!({"action": "set", "duration": "1h"})).await;
+
+        let jobs = store.list();
+        assert_eq!(jobs.len(), 4);
+        assert!(jobs.iter().all(|j| j.timer));
+        let second = jobs
+            .iter()
+            .find(|j| j.label.as_deref() == Some("Tea (2)"))
+            .unwrap();
+        assert_eq!(second.message, "Timer done: Tea (2) (30s)");
+        let unnamed = jobs
+            .iter()
+            .find(|j| j.label.as_deref() == Some("1m 30s"))
+            .unwrap();
+        assert_eq!(unnamed.message, "Timer done: 1m 30s");
+
+        let list = run(1, serde_json::json!({"action": "list"})).await.for_llm;
+        let names: Vec<&str> = list
+            .lines()
+            .map(|l| l.trim_start_matches("- ").split(':').next().unwrap())
+            .collect();
+        assert_eq!(names, ["Tea (2)", "1m 30s", "tea"]);
+
+        let res = run(1, serde_json::json!({"action": "cancel"})).await;
+        assert_eq!(
+            res.for_llm,
+            "several timers running; name one of: Tea (2), 1m 30s, tea"
+        );
+        let res = run(1, serde_json::json!({"action": "cancel", "name": "TEA"})).await;
+        assert!(
+            res.for_llm.starts_with("Cancelled tea: "),
+            "{}",
+            res.for_llm
+        );
+        let res = run(1, serde_json::json!({"action": "cancel", "name": "pasta"})).await;
+        assert!(res.is_error);
+        let res = run(1, serde_json::json!({"action": "cancel", "name": "all"})).await;
+        assert_eq!(res.for_llm.lines().count(), 2);
+        assert_eq!(
+            run(1, serde_json::json!({"action": "list"})).await.for_llm,
+            "No timers running."
+        );
+        // The other chat's timer is untouched.
+        assert_eq!(store.list().len(), 1);
+
+        let res = run(
+            1,
+            serde_json::json!({"action": "set", "duration": "2 days"}),
+        )
+        .await;
+        assert!(res.is_error);
+    }
+}
@@ -0,0 +1,494 @@
+//! `todo` tool: add, list, complete and defer checkbox items in the vault's `TODO.md`.
+//!
+//! Items stay plain Markdown in the format of Obsidian's Tasks plugin, so they still read and
+//! tick normally in Obsidian: `- [ ] Call the dentist 📅 2026-10-20 ^t4`. The trailing block
+//! id (`^t4`) is the item's stable id; items written by hand get one the next time the tool
+//! touches the file. Completing adds `✅ <date>`. Other lines of the file are left as they are.
+
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::workspace;
+
+const DUE: &str = "📅";
+const DONE: &str = "✅";
+
+/// One checkbox line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoItem {
+    /// Index of the line in the file.
+    line: usize,
+    /// Indentation and bullet, kept when the line is rewritten.
+    prefix: String,
+    pub done: bool,
+    pub text: String,
+    pub due: Option<NaiveDate>,
+    pub done_on: Option<NaiveDate>,
+    pub id: Option<u32>,
+}
+
+impl TodoItem {
+    /// Parse `- [ ] text 📅 date ✅ date ^tN` (any bullet, any indentation).
+    fn parse(line: usize, raw: &str) -> Option<Self> {
+        let indent = raw.len() - raw.trim_start().len();
+        let rest = &raw[indent..];
+        let bullet = rest
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '-' | '*' | '+'))?;
+        let rest = rest[1..].strip_prefix(" [")?;
+        let mut chars = rest.chars();
+        let done = match chars.next()? {
+            ' ' => false,
+            'x' | 'X' => true,
+            _ => return None,
+        };
+        let mut text = chars.as_str().strip_prefix(']')?.trim().to_string();
+
+        let mut id = None;
+        if let Some((before, block)) = text
+            .rsplit_once(" ^t")
+            .or_else(|| text.strip_prefix("^t").map(|b| ("", b)))
+            && let Ok(n) = block.trim().parse::<u32>()
+        {
+            id = Some(n);
+            text = before.trim_end().to_string();
+        }
+        let due = take_date(&mut text, DUE);
+        let done_on = take_date(&mut text, DONE);
+        Some(Self {
+            line,
+            prefix: format!("{}{bullet}", &raw[..indent]),
+            done,
+            text,
+            due,
+            done_on,
+            id,
+        })
+    }
+
+    fn render(&self) -> String {
+        let mut out = format!(
+            "{} [{}] {}",
+            self.prefix,
+            if self.done { 'x' } else { ' ' },
+            self.text
+        );
+        if let Some(due) = self.due {
+            out.push_str(&format!(" {DUE} {due}"));
+        }
+        if let Some(done) = self.done_on {
+            out.push_str(&format!(" {DONE} {done}"));
+        }
+        if let Some(id) = self.id {
+            out.push_str(&format!(" ^t{id}"));
+        }
+        out
+    }
+
+    /// `t4 Call the dentist (due 2026-10-20, overdue)`.
+    fn describe(&self, today: NaiveDate) -> String {
+        let mut out = format!("t{} {}", self.id.unwrap_or(0), self.text);
+        let mut notes = Vec::new();
+        if let Some(due) = self.due {
+            notes.push(match due {
+                d if d == today => "due today".to_string(),
+                d if d < today && !self.done => format!("due {d}, overdue"),
+                d => format!("due {d}"),
+            });
+        }
+        if let Some(done) = self.done_on {
+            notes.push(format!("done {done}"));
+        }
+        if !notes.is_empty() {
+            out.push_str(&format!(" ({})", notes.join(", ")));
+        }
+        out
+    }
+}
+
+/// Remove `<marker> YYYY-MM-DD` from `text` and return the date.
+fn take_date(text: &mut String, marker: &str) -> Option<NaiveDate> {
+    let at = text.find(marker)?;
+    let after = text[at + marker.len()..].trim_start();
+    let date = NaiveDate::parse_from_str(after.get(..10)?, "%Y-%m-%d").ok()?;
+    let end = text.len() - (after.len() - 10);
+    text.replace_range(at..end, "");
+    *text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(date)
+}
+
+/// `TODO.md` as lines plus the checkbox items among them.
+#[derive(Debug, Clone, Default)]
+pub struct TodoList {
+    lines: Vec<String>,
+    pub items: Vec<TodoItem>,
+}
+
+impl TodoList {
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<String> = text.lines().map(String::from).collect();
+        let items = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, l)| TodoItem::parse(i, l))
+            .collect();
+        Self { lines, items }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = self.lines.join("\n");
+        out.push('\n');
+        out
+    }
+
+    fn next_id(&self) -> u32 {
+        self.items.iter().filter_map(|i| i.id).max().unwrap_or(0) + 1
+    }
+
+    /// Give every item without an id (or with a duplicate) a new one; true if any changed.
+    pub fn assign_ids(&mut self) -> bool {
+        let mut next = self.next_id();
+        let mut seen = std::collections::HashSet::new();
+        let mut changed = false;
+        for item in &mut self.items {
+            if item.id.is_some_and(|id| seen.insert(id)) {
+                continue;
+            }
+            item.id = Some(next);
+            seen.insert(next);
+            next += 1;
+            self.lines[item.line] = item.render();
+            changed = true;
+        }
+        changed
+    }
+
+    /// Append an open item after the last item (or at the end); returns its id.
+    pub fn add(&mut self, text: &str, due: Option<NaiveDate>) -> u32 {
+        let id = self.next_id();
+        let line = self.items.last().map_or(self.lines.len(), |i| i.line + 1);
+        let prefix = self
+            .items
+            .last()
+            .map(|i| i.prefix.trim_start().to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let item = TodoItem {
+            line,
+            prefix,
+            done: false,
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+            due,
+            done_on: None,
+            id: Some(id),
+        };
+        self.lines.insert(line, item.render());
+        for later in self.items.iter_mut().filter(|i| i.line >= line) {
+            later.line += 1;
+        }
+        self.items.push(item);
+        self.items.sort_by_key(|i| i.line);
+        id
+    }
+
+    fn item_mut(&mut self, id: u32) -> Result<&mut TodoItem, String> {
+        self.items
+            .iter_mut()
+            .find(|i| i.id == Some(id))
+            .ok_or_else(|| format!("no todo with id t{id}"))
+    }
+
+    pub fn complete(&mut self, id: u32, today: NaiveDate) -> Result<TodoItem, String> {
+        let item = self.item_mut(id)?;
+        if item.done {
+            return Err(format!("t{id} is already done"));
+        }
+        item.done = true;
+        item.done_on = Some(today);
+        let item = item.clone();
+        self.lines[item.line] = item.render();
+        Ok(item)
+    }
+
+    pub fn defer(&mut self, id: u32, due: NaiveDate) -> Result<TodoItem, String> {
+        let item = self.item_mut(id)?;
+        if item.done {
+            return Err(format!("t{id} is already done"));
+        }
+        item.due = Some(due);
+        let item = item.clone();
+        self.lines[item.line] = item.render();
+        Ok(item)
+    }
+}
+
+/// `YYYY-MM-DD`, `today` or `tomorrow`.
+fn parse_day(s: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    match s.trim().to_lowercase().as_str() {
+        "today" => Ok(today),
+        "tomorrow" => Ok(today + chrono::Days::new(1)),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d")
+            .map_err(|_| format!("invalid date '{s}' (use YYYY-MM-DD, today or tomorrow)")),
+    }
+}
+
+/// `t4` or `4`.
+fn parse_id(args: &Value) -> Result<u32, String> {
+    let id = args.get("id").ok_or("missing 'id'")?;
+    id.as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .or_else(|| {
+            id.as_str()
+                .and_then(|s| s.trim().trim_start_matches(['t', 'T']).parse().ok())
+        })
+        .ok_or_else(|| format!("invalid id {id} (expected e.g. \"t4\")"))
+}
+
+/// Run one action against the list file (blocking; call from `spawn_blocking`).
+pub fn run_action(path: &Path, args: &Value, today: NaiveDate) -> Result<String, String> {
+    let action = args
+        .get("action")
+        .and_then(Value::as_str)
+        .ok_or("missing or invalid 'action'")?;
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "# TODO\n\n".to_string(),
+        Err(e) => return Err(format!("reading TODO.md failed: {e}")),
+    };
+    let mut list = TodoList::parse(&text);
+    let mut changed = list.assign_ids();
+
+    let reply = match action {
+        "add" => {
+            let item = args
+                .get("text")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .ok_or("missing 'text' for add")?;
+            let due = match args.get("due").and_then(Value::as_str) {
+                Some(d) => Some(parse_day(d, today)?),
+                None => None,
+            };
+            let id = list.add(item, due);
+            changed = true;
+            let added = list.items.iter().find(|i| i.id == Some(id)).expect("added");
+            format!("Added {}", added.describe(today))
+        }
+        "list" => {
+            let status = args.get("status").and_then(Value::as_str).unwrap_or("open");
+            let shown: Vec<&TodoItem> = list
+                .items
+                .iter()
+                .filter(|i| match status {
+                    "done" => i.done,
+                    "all" => true,
+                    _ => !i.done,
+                })
+                .collect();
+            if shown.is_empty() {
+                format!("No {status} todos.")
+            } else {
+                let overdue = shown
+                    .iter()
+                    .filter(|i| !i.done && i.due.is_some_and(|d| d < today))
+                    .count();
+                let mut out = format!("{} {status} todo(s)", shown.len());
+                if overdue > 0 {
+                    out.push_str(&format!(", {overdue} overdue"));
+                }
+                out.push(':');
+                for item in shown {
+                    let mark = if item.done { "[x]" } else { "[ ]" };
+                    out.push_str(&format!("\n- {mark} {}", item.describe(today)));
+                }
+                out
+            }
+        }
+        "complete" => {
+            let item = list.complete(parse_id(args)?, today)?;
+            changed = true;
+            format!("Completed {}", item.describe(today))
+        }
+        "defer" => {
+            let id = parse_id(args)?;
+            let due = match args.get("due").and_then(Value::as_str) {
+                Some(d) => parse_day(d, today)?,
+                None => {
+                    let days = args.get("days").and_then(Value::as_u64).unwrap_or(1);
+                    let current = list
+                        .items
+                        .iter()
+                        .find(|i| i.id == Some(id))
+                        .and_then(|i| i.due)
+                        .filter(|d| *d >= today)
+                        .unwrap_or(today);
+                    current + chrono::Days::new(days)
+                }
+            };
+            let item = list.defer(id, due)?;
+            changed = true;
+            format!("Deferred {}", item.describe(today))
+        }
+        other => return Err(format!("unknown action '{other}'")),
+    };
+
+    if changed {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, list.to_markdown())
+            .map_err(|e| format!("writing TODO.md failed: {e}"))?;
+    }
+    Ok(reply)
+}
+
+pub struct TodoTool {
+    timezone: Tz,
+}
+
+impl TodoTool {
+    pub fn new(timezone: Tz) -> Self {
+        Self { timezone }
+    }
+}
+
+impl Tool for TodoTool {
+    fn name(&self) -> &str {
+        "todo"
+    }
+
+    fn description(&self) -> &str {
+        "The user's to-do list (TODO.md in the vault). add: new item with optional due date. \
+         list: open items (or done/all) with ids like t4 and due dates. complete: tick off by \
+         id. defer: move the due date (due, or days from the current due date, default 1). Use \
+         this rather than editing TODO.md directly."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["add", "list", "complete", "defer"]
+                },
+                "text": { "type": "string", "description": "Item text (add)" },
+                "due": { "type": "string", "description": "Due date YYYY-MM-DD, today or tomorrow (add, defer)" },
+                "id": { "type": "string", "description": "Item id from list, e.g. t4 (complete, defer)" },
+                "days": { "type": "integer", "description": "Days to push the due date back (defer, default 1)" },
+                "status": {
+                    "type": "string",
+                    "enum": ["open", "done", "all"],
+                    "description": "Which items to list (default open)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let path: PathBuf = workspace::todo_file(&ctx.workspace);
+        let args = args.clone();
+        let today = chrono::Utc::now()
+            .with_timezone(&self.timezone)
+            .date_naive();
+        Box::pin(async move {
+            match tokio::task::spawn_blocking(move || run_action(&path, &args, today)).await {
+                Ok(Ok(reply)) => ToolResult::ok(reply),
+                Ok(Err(e)) => ToolResult::error(e),
+                Err(e) => ToolResult::error(format!("todo task error: {e}")),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn items_round_trip_and_get_stable_ids() {
+        let text = "# TODO\n\nSome notes\n- [ ] Call the dentist 📅 2026-10-20 ^t4\n  * [x] Buy milk ✅ 2026-10-01\n- plain bullet\n";
+        let mut list = TodoList::parse(text);
+        assert_eq!(list.items.len(), 2);
+        assert_eq!(list.items[0].text, "Call the dentist");
+        assert_eq!(list.items[0].due, Some(day("2026-10-20")));
+        assert_eq!(list.items[0].id, Some(4));
+        assert_eq!(list.items[1].done_on, Some(day("2026-10-01")));
+
+        assert!(list.assign_ids());
+        assert!(!list.assign_ids());
+        assert_eq!(
+            list.to_markdown(),
+            "# TODO\n\nSome notes\n- [ ] Call the dentist 📅 2026-10-20 ^t4\n  * [x] Buy milk ✅ 2026-10-01 ^t5\n- plain bullet\n"
+        );
+
+        assert_eq!(list.add("Renew  passport", Some(day("2026-11-01"))), 6);
+        list.complete(4, day("2026-10-16")).unwrap();
+        assert!(list.complete(4, day("2026-10-16")).is_err());
+        assert_eq!(
+            list.to_markdown(),
+            "# TODO\n\nSome notes\n- [x] Call the dentist 📅 2026-10-20 ✅ 2026-10-16 ^t4\n  * [x] Buy milk ✅ 2026-10-01 ^t5\n* [ ] Renew passport 📅 2026-11-01 ^t6\n- plain bullet\n"
+        );
+        let reparsed = TodoList::parse(&list.to_markdown());
+        assert_eq!(reparsed.items, list.items);
+    }
+
+    #[test]
+    fn actions_update_the_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = workspace::todo_file(tmp.path());
+        let today = day("2026-10-16");
+        let run = |args: Value| run_action(&path, &args, today);
+
+        assert_eq!(
+            run(serde_json::json!({"action": "list"})).unwrap(),
+            "No open todos."
+        );
+        assert_eq!(
+            run(serde_json::json!({"action": "add", "text": "Water plants", "due": "today"}))
+                .unwrap(),
+            "Added t1 Water plants (due today)"
+        );
+        run(serde_json::json!({"action": "add", "text": "Book flights", "due": "2026-10-10"}))
+            .unwrap();
+        assert_eq!(
+            run(serde_json::json!({"action": "list"})).unwrap(),
+            "2 open todo(s), 1 overdue:\n- [ ] t1 Water plants (due today)\n- [ ] t2 Book flights (due 2026-10-10, overdue)"
+        );
+        // Overdue items are deferred from today.
+        assert_eq!(
+            run(serde_json::json!({"action": "defer", "id": "t2", "days": 3})).unwrap(),
+            "Deferred t2 Book flights (due 2026-10-19)"
+        );
+        assert_eq!(
+            run(serde_json::json!({"action": "complete", "id": 1})).unwrap(),
+            "Completed t1 Water plants (due today, done 2026-10-16)"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# TODO\n\n- [x] Water plants 📅 2026-10-16 ✅ 2026-10-16 ^t1\n- [ ] Book flights 📅 2026-10-19 ^t2\n"
+        );
+        assert!(
+            run(serde_json::json!({"action": "complete", "id": "t9"}))
+                .unwrap_err()
+                .contains("no todo with id t9")
+        );
+        assert!(run(serde_json::json!({"action": "add", "text": "x", "due": "soon"})).is_err());
+    }
+}
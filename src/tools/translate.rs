@@ -0,0 +1,316 @@
+//! `translate` tool: detects the language of some text or a vault note and translates it with
+//! its own model (`[tools.translate] model`, default the chat model), so a cheap model can do
+//! the work. With `save` a translated note is written beside the original.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::TranslateConfig;
+use crate::llm::{LlmProvider, Message, Role};
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// About 10k tokens; longer notes should be translated a section at a time.
+const MAX_INPUT_CHARS: usize = 40_000;
+
+const SYSTEM_PROMPT: &str = "You are a translator. Detect the language of the user's text and \
+    translate it into the requested language. Keep the meaning, tone and formatting: Markdown, \
+    line breaks, [[wiki links]], URLs, code and YAML front matter keys stay as they are. Name \
+    the detected language in English (e.g. \"German\").";
+
+#[derive(Debug, Deserialize)]
+struct Translation {
+    source_language: String,
+    translation: String,
+}
+
+fn schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "source_language": { "type": "string" },
+            "translation": { "type": "string" }
+        },
+        "required": ["source_language", "translation"]
+    })
+}
+
+/// Note name for the translation of `path`: "Recipe.md" into French is "Recipe (French).md".
+fn translated_path(path: &Path, language: &str) -> Option<std::path::PathBuf> {
+    let stem = path.file_stem()?.to_str()?;
+    let language: String = language
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-'))
+        .collect();
+    let language = language.trim();
+    if language.is_empty() {
+        return None;
+    }
+    Some(path.with_file_name(format!("{stem} ({language}).md")))
+}
+
+pub struct TranslateTool {
+    llm: Arc<dyn LlmProvider>,
+    model: String,
+}
+
+impl TranslateTool {
+    /// `model` is used unless `[tools.translate] model` overrides it.
+    pub fn new(llm: Arc<dyn LlmProvider>, model: &str, config: Option<&TranslateConfig>) -> Self {
+        Self {
+            llm,
+            model: config
+                .and_then(|c| c.model.clone())
+                .unwrap_or_else(|| model.to_string()),
+        }
+    }
+
+    async fn translate(&self, text: &str, to: &str) -> Result<Translation, String> {
+        let messages = [
+            Message {
+                role: Role::System,
+                content: SYSTEM_PROMPT.to_string(),
+                tool_call_id: None,
+                tool_calls: None,
+                images: Vec::new(),
+            },
+            Message {
+                role: Role::User,
+                content: format!("Translate into {to}:\n\n{text}"),
+                tool_call_id: None,
+                tool_calls: None,
+                images: Vec::new(),
+            },
+        ];
+        self.llm
+            .chat_structured::<Translation>(&messages, &self.model, &schema())
+            .await
+            .map_err(|e| format!("translation failed: {e}"))
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let to = args
+            .get("to")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .ok_or("missing 'to' (target language, e.g. \"French\")")?;
+        let text = args.get("text").and_then(Value::as_str);
+        let rel = args.get("path").and_then(Value::as_str);
+        let save = args.get("save").and_then(Value::as_bool).unwrap_or(false);
+
+        let (input, path) = match (text, rel) {
+            (Some(text), None) => {
+                if save {
+                    return Err("save needs 'path' (a note to translate)".into());
+                }
+                (text.to_string(), None)
+            }
+            (None, Some(rel)) => {
+                let path = resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+                let content = tokio::fs::read_to_string(&path)
+                    .await
+                    .map_err(|e| format!("{rel}: {e}"))?;
+                (content, Some((rel, path)))
+            }
+            _ => return Err("give either 'text' or 'path'".into()),
+        };
+        if input.trim().is_empty() {
+            return Err("nothing to translate".into());
+        }
+        let chars = input.chars().count();
+        if chars > MAX_INPUT_CHARS {
+            return Err(format!(
+                "text is {chars} chars; the limit is {MAX_INPUT_CHARS} (translate it in parts)"
+            ));
+        }
+
+        let result = self.translate(&input, to).await?;
+        let source = result.source_language.trim();
+        if source.eq_ignore_ascii_case(to) {
+            return Ok(format!("The text is already in {source}."));
+        }
+        let translation = result.translation.trim();
+        let Some((rel, path)) = path.filter(|_| save) else {
+            return Ok(format!("Detected language: {source}\n\n{translation}"));
+        };
+
+        let note = translated_path(&path, to).ok_or("'to' is not a usable language name")?;
+        let note_rel = translated_path(Path::new(rel), to).ok_or("'path' has no file name")?;
+        if tokio::fs::try_exists(&note).await.unwrap_or(false) {
+            return Err(format!(
+                "{} already exists; read it or move it first",
+                note_rel.display()
+            ));
+        }
+        tokio::fs::write(&note, format!("{translation}\n"))
+            .await
+            .map_err(|e| format!("writing {} failed: {e}", note_rel.display()))?;
+        Ok(format!(
+            "Translated {rel} from {source} and saved it to {}.",
+            note_rel.display()
+        ))
+    }
+}
+
+impl Tool for TranslateTool {
+    fn name(&self) -> &str {
+        "translate"
+    }
+
+    fn description(&self) -> &str {
+        "Translate text or a vault note into another language, detecting the source language. \
+         With path and save=true the translation is saved beside the note as \"<name> (<language>).md\"."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "to": { "type": "string", "description": "Target language, e.g. \"French\"" },
+                "text": { "type": "string", "description": "Text to translate (or use path)" },
+                "path": { "type": "string", "description": "Note to translate, e.g. Notes/Rezept.md (or use text)" },
+                "save": { "type": "boolean", "description": "With path: save the translation beside the note (default false)" }
+            },
+            "required": ["to"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    use crate::llm::{GenParams, LlmError, LlmResponse, ToolDef};
+
+    /// Replies with `reply` and records the model and last user message it was sent.
+    struct Fake {
+        reply: &'static str,
+        seen: Mutex<Vec<(String, String)>>,
+    }
+
+    impl LlmProvider for Fake {
+        fn chat_with_params<'a>(
+            &'a self,
+            messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            let last = messages
+                .last()
+                .map(|m| m.content.clone())
+                .unwrap_or_default();
+            self.seen.lock().unwrap().push((model.to_string(), last));
+            Box::pin(async {
+                Ok(LlmResponse {
+                    content: self.reply.to_string(),
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".to_string(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    fn tool(reply: &'static str) -> (TranslateTool, Arc<Fake>) {
+        let fake = Arc::new(Fake {
+            reply,
+            seen: Mutex::new(Vec::new()),
+        });
+        let config = TranslateConfig {
+            model: Some("cheap-model".to_string()),
+        };
+        let tool = TranslateTool::new(fake.clone(), "chat-model", Some(&config));
+        (tool, fake)
+    }
+
+    const GERMAN: &str =
+        r##"{"source_language": "German", "translation": "# Pancakes\n\n- 200 g flour\n"}"##;
+
+    #[tokio::test]
+    async fn translates_text_with_the_configured_model() {
+        let dir = TempDir::new().unwrap();
+        let (tool, fake) = tool(GERMAN);
+        let args = serde_json::json!({"to": "English", "text": "# Pfannkuchen\n\n- 200 g Mehl"});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "Detected language: German\n\n# Pancakes\n\n- 200 g flour"
+        );
+        let seen = fake.seen.lock().unwrap();
+        assert_eq!(seen[0].0, "cheap-model");
+        assert!(
+            seen[0]
+                .1
+                .starts_with("Translate into English:\n\n# Pfannkuchen")
+        );
+    }
+
+    #[tokio::test]
+    async fn saves_a_note_beside_the_original() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("Recipes")).unwrap();
+        std::fs::write(dir.path().join("Recipes/Pfannkuchen.md"), "# Pfannkuchen\n").unwrap();
+        let (tool, _) = tool(GERMAN);
+        let args =
+            serde_json::json!({"to": "English", "path": "Recipes/Pfannkuchen.md", "save": true});
+
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Translated Recipes/Pfannkuchen.md from German and saved it to \
+             Recipes/Pfannkuchen (English).md."
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("Recipes/Pfannkuchen (English).md")).unwrap(),
+            "# Pancakes\n\n- 200 g flour\n"
+        );
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("already exists"));
+    }
+
+    #[tokio::test]
+    async fn same_language_and_bad_arguments() {
+        let dir = TempDir::new().unwrap();
+        let (tool, _) = tool(GERMAN);
+        let args = serde_json::json!({"to": "german", "text": "Guten Morgen"});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert_eq!(res.for_llm, "The text is already in German.");
+
+        let args = serde_json::json!({"to": "French", "text": "Hallo", "save": true});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert_eq!(res.for_llm, "save needs 'path' (a note to translate)");
+        let args = serde_json::json!({"to": "French"});
+        let res = tool.execute(&ctx(dir.path()), &args).await;
+        assert_eq!(res.for_llm, "give either 'text' or 'path'");
+        let args = serde_json::json!({"text": "Hallo"});
+        assert!(tool.execute(&ctx(dir.path()), &args).await.is_error);
+    }
+}
@@ -0,0 +1,212 @@
+//! tts tool: read text aloud into an audio file in the workspace via the `[voice]` speech API.
+//!
+//! Long text is split into request-sized pieces (`voice::split_for_speech`) and the audio is
+//! concatenated; MP3 frames and chained Ogg streams both play back as one file. The tool only
+//! writes the file — `send_file` delivers it.
+
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+use crate::voice::{self, VoiceClient};
+
+/// Default folder for generated audio, relative to the workspace.
+const DEFAULT_DIR: &str = "Audio";
+/// Longest text read in one call (about ten speech requests, roughly an hour of audio).
+const MAX_CHARS: usize = 40_000;
+
+pub struct TtsTool {
+    voice: VoiceClient,
+}
+
+impl TtsTool {
+    pub fn new(voice: VoiceClient) -> Self {
+        Self { voice }
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let text = match (
+            args.get("text").and_then(Value::as_str),
+            args.get("path").and_then(Value::as_str),
+        ) {
+            (Some(text), None) => text.to_string(),
+            (None, Some(rel)) => {
+                let path = resolve_path(rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+                tokio::fs::read_to_string(&path)
+                    .await
+                    .map_err(|e| format!("{rel}: {e}"))?
+            }
+            _ => return Err("give either 'text' or 'path'".into()),
+        };
+        let chars = text.chars().count();
+        if chars > MAX_CHARS {
+            return Err(format!(
+                "text is {chars} characters; the limit is {MAX_CHARS} (summarize it first)"
+            ));
+        }
+        let parts = voice::split_for_speech(&text, voice::MAX_SPEECH_CHARS);
+        if parts.is_empty() {
+            return Err("nothing to read".into());
+        }
+
+        let (format, ext) = match args.get("format").and_then(Value::as_str).unwrap_or("mp3") {
+            "mp3" => ("mp3", "mp3"),
+            "opus" | "ogg" => ("opus", "ogg"),
+            other => return Err(format!("unknown format '{other}' (use mp3 or opus)")),
+        };
+        let rel = match args.get("output").and_then(Value::as_str) {
+            Some(out) if !out.trim().is_empty() => out.trim().to_string(),
+            _ => format!(
+                "{DEFAULT_DIR}/speech-{}.{ext}",
+                chrono::Local::now().format("%Y%m%d-%H%M%S")
+            ),
+        };
+        let out = resolve_path(&rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+
+        let voice_name = args
+            .get("voice")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        let mut audio = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            let bytes = self
+                .voice
+                .synthesize_with(part, voice_name, format)
+                .await
+                .map_err(|e| format!("part {} of {}: {e}", i + 1, parts.len()))?;
+            audio.extend_from_slice(&bytes);
+        }
+
+        if let Some(dir) = out.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| format!("create {}: {e}", dir.display()))?;
+        }
+        tokio::fs::write(&out, &audio)
+            .await
+            .map_err(|e| format!("write {rel}: {e}"))?;
+        let pieces = match parts.len() {
+            1 => String::new(),
+            n => format!(", {n} parts"),
+        };
+        Ok(format!(
+            "Wrote {rel} ({} KB{pieces}). Deliver it with send_file.",
+            audio.len().div_ceil(1024)
+        ))
+    }
+}
+
+impl Tool for TtsTool {
+    fn name(&self) -> &str {
+        "tts"
+    }
+
+    fn description(&self) -> &str {
+        "Read text (or a workspace file) aloud into an MP3 or Opus audio file in the workspace and \
+         return its path, e.g. to listen to a long summary while commuting. Send the file with \
+         send_file afterwards."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "text": { "type": "string", "description": "Text to read aloud (or use path). Plain prose reads best; drop markdown symbols and links" },
+                "path": { "type": "string", "description": "Workspace file to read aloud (or use text)" },
+                "output": { "type": "string", "description": "Audio path relative to workspace (default Audio/speech-<timestamp>.mp3)" },
+                "format": { "type": "string", "enum": ["mp3", "opus"], "description": "Default mp3; opus writes an .ogg file" },
+                "voice": { "type": "string", "description": "Speech voice (default from [voice] tts-voice)" }
+            }
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, VoiceConfig};
+    use serde_json::json;
+    use std::path::Path;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    fn tool(api_base: &str) -> TtsTool {
+        let cfg = Config {
+            voice: Some(VoiceConfig {
+                api_base: Some(api_base.to_string()),
+                api_key: Some("k".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        TtsTool::new(VoiceClient::from_config(&cfg).unwrap())
+    }
+
+    #[tokio::test]
+    async fn long_text_is_synthesized_in_parts_and_joined() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/audio/speech"))
+            .and(body_partial_json(
+                json!({"voice": "nova", "response_format": "mp3"}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"ID3".to_vec()))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let text = "A sentence. ".repeat(400);
+
+        let res = tool(&server.uri())
+            .execute(
+                &ctx(dir.path()),
+                &json!({"text": text, "voice": "nova", "output": "Audio/brief.mp3"}),
+            )
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "Wrote Audio/brief.mp3 (1 KB, 2 parts). Deliver it with send_file."
+        );
+        assert_eq!(
+            std::fs::read(dir.path().join("Audio/brief.mp3")).unwrap(),
+            b"ID3ID3"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = tool("http://127.0.0.1:9");
+        for args in [
+            json!({}),
+            json!({"text": "   "}),
+            json!({"text": "hi", "format": "wav"}),
+            json!({"text": "hi", "output": "../out.mp3"}),
+            json!({"text": "x".repeat(MAX_CHARS + 1)}),
+        ] {
+            let res = tool.execute(&ctx(dir.path()), &args).await;
+            assert!(res.is_error, "{args}");
+        }
+    }
+}
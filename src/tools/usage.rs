@@ -0,0 +1,194 @@
+//! `usage` tool and `/usage` command: LLM token use and estimated spend from `llm_usage`,
+//! broken down by source (chat, cron, heartbeat, each subagent) and by chat.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::memory::db::{BrainDb, UsageGroupBy, UsageTotals};
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// Report window; "day" and "week" are the last 24 hours / 7 days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsagePeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl UsagePeriod {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "week" | "weekly" | "7d" => Some(Self::Week),
+            "day" | "daily" | "today" | "24h" => Some(Self::Day),
+            "month" | "monthly" | "30d" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    fn seconds(self) -> i64 {
+        match self {
+            Self::Day => 86_400,
+            Self::Week => 7 * 86_400,
+            Self::Month => 30 * 86_400,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Day => "last 24h",
+            Self::Week => "last 7 days",
+            Self::Month => "last 30 days",
+        }
+    }
+}
+
+fn format_cost(cost: Option<f64>) -> String {
+    match cost {
+        Some(c) => format!("${c:.4}"),
+        None => "n/a".to_string(),
+    }
+}
+
+fn format_rows(out: &mut String, title: &str, rows: &[UsageTotals]) {
+    out.push_str(&format!("\nBy {title}:\n"));
+    for r in rows {
+        let key = if r.key.is_empty() { "(other)" } else { &r.key };
+        out.push_str(&format!(
+            "- {}: {} calls, {} in / {} out tokens, {}\n",
+            key,
+            r.calls,
+            r.prompt_tokens,
+            r.completion_tokens,
+            format_cost(r.cost_usd)
+        ));
+    }
+}
+
+/// Build the usage report for `period` (blocking; call from `spawn_blocking`).
+pub fn usage_report(db: &BrainDb, period: UsagePeriod) -> Result<String, String> {
+    let since = chrono::Utc::now().timestamp() - period.seconds();
+    let by_source = db
+        .llm_usage_totals(since, UsageGroupBy::Source)
+        .map_err(|e| e.to_string())?;
+    if by_source.is_empty() {
+        return Ok(format!("No LLM usage recorded ({}).", period.label()));
+    }
+    let by_chat = db
+        .llm_usage_totals(since, UsageGroupBy::Chat)
+        .map_err(|e| e.to_string())?;
+    let calls: u64 = by_source.iter().map(|r| r.calls).sum();
+    let prompt: u64 = by_source.iter().map(|r| r.prompt_tokens).sum();
+    let completion: u64 = by_source.iter().map(|r| r.completion_tokens).sum();
+    let cost = by_source
+        .iter()
+        .filter_map(|r| r.cost_usd)
+        .reduce(|a, b| a + b);
+
+    let mut out = format!(
+        "LLM usage ({}): {} calls, {} in / {} out tokens, est. cost {}\n",
+        period.label(),
+        calls,
+        prompt,
+        completion,
+        format_cost(cost)
+    );
+    format_rows(&mut out, "source", &by_source);
+    format_rows(&mut out, "chat", &by_chat);
+    Ok(out.trim_end().to_string())
+}
+
+pub struct UsageTool {
+    db: Arc<BrainDb>,
+}
+
+impl UsageTool {
+    pub fn new(db: Arc<BrainDb>) -> Self {
+        Self { db }
+    }
+}
+
+impl Tool for UsageTool {
+    fn name(&self) -> &str {
+        "usage"
+    }
+
+    fn description(&self) -> &str {
+        "Summarize LLM token usage and estimated spend per source (chat, cron, heartbeat, \
+         subagents) and per chat over the last day, week or month."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "period": {
+                    "type": "string",
+                    "enum": ["day", "week", "month"],
+                    "description": "Time window (default week)"
+                }
+            }
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let db = Arc::clone(&self.db);
+        let period = args.get("period").and_then(Value::as_str).unwrap_or("");
+        let period = UsagePeriod::parse(period);
+
+        Box::pin(async move {
+            let Some(period) = period else {
+                return ToolResult::error("invalid 'period' (use day, week or month)");
+            };
+            match tokio::task::spawn_blocking(move || usage_report(&db, period)).await {
+                Ok(Ok(report)) => ToolResult::ok(report),
+                Ok(Err(e)) => ToolResult::error(format!("usage query failed: {e}")),
+                Err(e) => ToolResult::error(format!("usage task error: {e}")),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::db::LlmUsage;
+
+    #[test]
+    fn report_totals_and_breakdowns() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = BrainDb::open(tmp.path()).unwrap();
+        assert!(
+            usage_report(&db, UsagePeriod::Day)
+                .unwrap()
+                .starts_with("No LLM usage")
+        );
+        for (chat, source, cost) in [("7", "telegram", 0.01), ("7", "subagent:news", 0.02)] {
+            db.record_llm_usage(&LlmUsage {
+                chat_id: chat.into(),
+                source: source.into(),
+                model: "m".into(),
+                prompt_tokens: 100,
+                completion_tokens: 20,
+                cost_usd: Some(cost),
+            })
+            .unwrap();
+        }
+        let report = usage_report(&db, UsagePeriod::Week).unwrap();
+        assert!(report.contains("2 calls, 200 in / 40 out tokens, est. cost $0.0300"));
+        assert!(report.contains("- subagent:news: 1 calls"));
+        assert!(report.contains("- 7: 2 calls"));
+        assert_eq!(UsagePeriod::parse("daily"), Some(UsagePeriod::Day));
+        assert_eq!(UsagePeriod::parse("year"), None);
+    }
+}
@@ -0,0 +1,155 @@
+//! `vault_stats` tool: an overview of the indexed vault for weekly reviews — note count and
+//! size, what changed in the last N days, the largest notes and orphaned notes (no links in
+//! or out, see [`links`](crate::memory::links)).
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::memory::backup::format_bytes;
+use crate::memory::db::BrainDb;
+use crate::memory::links;
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const DEFAULT_DAYS: i64 = 7;
+const DEFAULT_LIMIT: usize = 5;
+const MAX_LIMIT: usize = 50;
+
+/// Build the vault report (blocking; call from `spawn_blocking`).
+pub fn vault_report(db: &BrainDb, days: i64, limit: usize) -> Result<String, String> {
+    let since = chrono::Utc::now().timestamp() - days * 86_400;
+    let stats = db.vault_stats(since, limit).map_err(|e| e.to_string())?;
+    if stats.notes == 0 {
+        return Ok("The vault index is empty.".to_string());
+    }
+    let notes = db.vault_contents().map_err(|e| e.to_string())?;
+    let orphans = links::orphans(&notes);
+
+    let mut out = format!(
+        "Vault: {} notes, {} indexed. {} modified in the last {} day(s).\n",
+        stats.notes,
+        format_bytes(stats.total_bytes),
+        stats.modified_since,
+        days
+    );
+    if !stats.recent.is_empty() {
+        out.push_str("\nRecently modified:\n");
+        for (fp, modified) in &stats.recent {
+            let date = chrono::DateTime::from_timestamp(*modified, 0)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            out.push_str(&format!("- {fp} ({date})\n"));
+        }
+    }
+    out.push_str("\nLargest:\n");
+    for (fp, bytes) in &stats.largest {
+        out.push_str(&format!("- {fp} ({})\n", format_bytes(*bytes)));
+    }
+    out.push_str(&format!("\nOrphaned notes (no links): {}\n", orphans.len()));
+    for fp in orphans.iter().take(limit) {
+        out.push_str(&format!("- {fp}\n"));
+    }
+    if orphans.len() > limit {
+        out.push_str(&format!("… {} more\n", orphans.len() - limit));
+    }
+    Ok(out.trim_end().to_string())
+}
+
+pub struct VaultStatsTool {
+    db: Arc<BrainDb>,
+}
+
+impl VaultStatsTool {
+    pub fn new(db: Arc<BrainDb>) -> Self {
+        Self { db }
+    }
+}
+
+impl Tool for VaultStatsTool {
+    fn name(&self) -> &str {
+        "vault_stats"
+    }
+
+    fn description(&self) -> &str {
+        "Overview of the Obsidian vault: note count and size, notes modified in the last N \
+         days, the largest notes and orphaned notes (no links in or out). Useful for weekly \
+         reviews."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "days": {
+                    "type": "integer",
+                    "description": "Window for recently modified notes, in days (default 7)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Notes listed per section (default 5)"
+                }
+            }
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        let db = Arc::clone(&self.db);
+        let days = args
+            .get("days")
+            .and_then(Value::as_i64)
+            .unwrap_or(DEFAULT_DAYS)
+            .max(1);
+        let limit = args
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map(|n| (n as usize).clamp(1, MAX_LIMIT))
+            .unwrap_or(DEFAULT_LIMIT);
+
+        Box::pin(async move {
+            match tokio::task::spawn_blocking(move || vault_report(&db, days, limit)).await {
+                Ok(Ok(report)) => ToolResult::ok(report),
+                Ok(Err(e)) => ToolResult::error(format!("vault stats query failed: {e}")),
+                Err(e) => ToolResult::error(format!("vault stats task error: {e}")),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_recent_largest_and_orphans() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = BrainDb::open(tmp.path()).unwrap();
+        assert_eq!(
+            vault_report(&db, 7, 5).unwrap(),
+            "The vault index is empty."
+        );
+        let now = chrono::Utc::now().timestamp();
+        db.upsert_vault_entry("Index.md", "See [[Lisbon]]", now)
+            .unwrap();
+        db.upsert_vault_entry("Trips/Lisbon.md", "Trams and pastel de nata", 0)
+            .unwrap();
+        db.upsert_vault_entry("Stray.md", "nothing links here", 0)
+            .unwrap();
+
+        let report = vault_report(&db, 7, 5).unwrap();
+        assert!(report.starts_with("Vault: 3 notes,"), "{report}");
+        assert!(report.contains("1 modified in the last 7 day(s)"));
+        assert!(report.contains("Recently modified:\n- Index.md ("));
+        assert!(report.contains("Largest:\n- Trips/Lisbon.md (24 B)"));
+        assert!(report.ends_with("Orphaned notes (no links): 1\n- Stray.md"));
+    }
+}
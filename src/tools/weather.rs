@@ -0,0 +1,481 @@
+//! `weather` tool: current conditions and a short daily forecast from Open-Meteo (no API key).
+//!
+//! Location is, in order: explicit `latitude`/`longitude`, a `place` name (Open-Meteo
+//! geocoding), or the last location shared in the chat ([`ToolCtx::location`]), which cron
+//! jobs also get, so a morning-briefing job needs no coordinates.
+
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+pub const DEFAULT_FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+pub const DEFAULT_GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+
+const DEFAULT_DAYS: u64 = 3;
+const MAX_DAYS: u64 = 7;
+
+/// WMO weather interpretation code as text.
+fn describe(code: i64) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Fog",
+        51 | 53 | 55 => "Drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 => "Light rain",
+        63 => "Rain",
+        65 => "Heavy rain",
+        66 | 67 => "Freezing rain",
+        71 => "Light snow",
+        73 => "Snow",
+        75 => "Heavy snow",
+        77 => "Snow grains",
+        80 => "Light showers",
+        81 => "Showers",
+        82 => "Violent showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown conditions",
+    }
+}
+
+/// Where the forecast is for.
+#[derive(Debug, Clone, PartialEq)]
+struct Place {
+    latitude: f64,
+    longitude: f64,
+    label: String,
+}
+
+fn num(v: &Value, key: &str) -> Option<f64> {
+    v.get(key).and_then(Value::as_f64)
+}
+
+/// One decimal, without a trailing `.0`.
+fn fmt1(n: f64) -> String {
+    let s = format!("{n:.1}");
+    s.strip_suffix(".0").unwrap_or(&s).to_string()
+}
+
+/// `HH:MM` from Open-Meteo's local `YYYY-MM-DDTHH:MM`.
+fn clock(v: Option<&Value>) -> &str {
+    v.and_then(Value::as_str)
+        .and_then(|t| t.split('T').nth(1))
+        .unwrap_or("?")
+}
+
+/// Render an Open-Meteo forecast response.
+fn format_forecast(place: &Place, body: &Value) -> Result<String, String> {
+    let current = body
+        .get("current")
+        .ok_or("forecast response has no current data")?;
+    let timezone = body
+        .get("timezone")
+        .and_then(Value::as_str)
+        .unwrap_or("GMT");
+    let mut out = format!("Weather for {} ({timezone})\n", place.label);
+
+    let mut now = format!(
+        "Now ({}): {}",
+        current
+            .get("time")
+            .and_then(Value::as_str)
+            .unwrap_or("?")
+            .replace('T', " "),
+        num(current, "temperature_2m")
+            .map(|t| format!("{}°C", fmt1(t)))
+            .unwrap_or_else(|| "?°C".to_string()),
+    );
+    if let Some(feels) = num(current, "apparent_temperature") {
+        now.push_str(&format!(" (feels like {}°C)", fmt1(feels)));
+    }
+    if let Some(code) = current.get("weather_code").and_then(Value::as_i64) {
+        now.push_str(&format!(", {}", describe(code)));
+    }
+    if let Some(h) = num(current, "relative_humidity_2m") {
+        now.push_str(&format!(", humidity {}%", fmt1(h)));
+    }
+    if let Some(w) = num(current, "wind_speed_10m") {
+        now.push_str(&format!(", wind {} km/h", fmt1(w)));
+    }
+    if let Some(p) = num(current, "precipitation").filter(|p| *p > 0.0) {
+        now.push_str(&format!(", precipitation {} mm", fmt1(p)));
+    }
+    out.push_str(&now);
+
+    let Some(daily) = body.get("daily") else {
+        return Ok(out);
+    };
+    let column = |key: &str, i: usize| daily.get(key).and_then(|c| c.get(i));
+    let days = daily
+        .get("time")
+        .and_then(Value::as_array)
+        .map(Vec::len)
+        .unwrap_or(0);
+    if days > 0 {
+        out.push_str("\n\nForecast:");
+    }
+    for i in 0..days {
+        let date = column("time", i).and_then(Value::as_str).unwrap_or("?");
+        let weekday = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(|d| d.format("%a ").to_string())
+            .unwrap_or_default();
+        let mut line = format!("\n- {weekday}{date}:");
+        if let Some(code) = column("weather_code", i).and_then(Value::as_i64) {
+            line.push_str(&format!(" {},", describe(code)));
+        }
+        let low = column("temperature_2m_min", i).and_then(Value::as_f64);
+        let high = column("temperature_2m_max", i).and_then(Value::as_f64);
+        if let (Some(low), Some(high)) = (low, high) {
+            line.push_str(&format!(" {}–{}°C", fmt1(low), fmt1(high)));
+        }
+        let chance = column("precipitation_probability_max", i).and_then(Value::as_f64);
+        let amount = column("precipitation_sum", i).and_then(Value::as_f64);
+        match (chance, amount) {
+            (Some(c), Some(a)) if a > 0.0 => {
+                line.push_str(&format!(", rain {}% ({} mm)", fmt1(c), fmt1(a)))
+            }
+            (Some(c), _) => line.push_str(&format!(", rain {}%", fmt1(c))),
+            (None, Some(a)) if a > 0.0 => line.push_str(&format!(", rain {} mm", fmt1(a))),
+            _ => {}
+        }
+        if daily.get("sunrise").is_some() {
+            line.push_str(&format!(
+                ", sunrise {}, sunset {}",
+                clock(column("sunrise", i)),
+                clock(column("sunset", i))
+            ));
+        }
+        out.push_str(&line);
+    }
+    Ok(out)
+}
+
+/// "3 min ago", "2 h ago", "4 days ago".
+fn age(updated_at: i64) -> String {
+    let secs = (chrono::Utc::now().timestamp() - updated_at).max(0);
+    match secs {
+        s if s < 3600 => format!("{} min ago", s / 60),
+        s if s < 86_400 => format!("{} h ago", s / 3600),
+        s => format!("{} days ago", s / 86_400),
+    }
+}
+
+pub struct WeatherTool {
+    client: Client,
+    forecast_url: String,
+    geocoding_url: String,
+}
+
+impl WeatherTool {
+    pub fn new(client: Client) -> Self {
+        Self::with_urls(client, DEFAULT_FORECAST_URL, DEFAULT_GEOCODING_URL)
+    }
+
+    /// Use other endpoints (same API shape as Open-Meteo), e.g. a self-hosted instance.
+    pub fn with_urls(client: Client, forecast_url: &str, geocoding_url: &str) -> Self {
+        Self {
+            client,
+            forecast_url: forecast_url.to_string(),
+            geocoding_url: geocoding_url.to_string(),
+        }
+    }
+
+    async fn get_json(&self, url: &str, query: &[(&str, String)]) -> Result<Value, String> {
+        let url = reqwest::Url::parse_with_params(url, query).map_err(|e| e.to_string())?;
+        let res = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("Open-Meteo request failed: {e}"))?;
+        let status = res.status();
+        let body: Value = res
+            .json()
+            .await
+            .map_err(|e| format!("Open-Meteo response unreadable: {e}"))?;
+        if !status.is_success() {
+            let reason = body
+                .get("reason")
+                .and_then(Value::as_str)
+                .unwrap_or(status.as_str());
+            return Err(format!("Open-Meteo error: {reason}"));
+        }
+        Ok(body)
+    }
+
+    async fn geocode(&self, name: &str) -> Result<Place, String> {
+        let body = self
+            .get_json(
+                &self.geocoding_url,
+                &[("name", name.to_string()), ("count", "1".to_string())],
+            )
+            .await?;
+        let hit = body
+            .get("results")
+            .and_then(|r| r.get(0))
+            .ok_or_else(|| format!("no place found for '{name}'"))?;
+        let (Some(latitude), Some(longitude)) = (num(hit, "latitude"), num(hit, "longitude"))
+        else {
+            return Err(format!("no coordinates for '{name}'"));
+        };
+        let label = ["name", "admin1", "country"]
+            .iter()
+            .filter_map(|k| hit.get(*k).and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Ok(Place {
+            latitude,
+            longitude,
+            label,
+        })
+    }
+
+    async fn place(&self, ctx: &ToolCtx, args: &Value) -> Result<Place, String> {
+        match (num(args, "latitude"), num(args, "longitude")) {
+            (Some(latitude), Some(longitude)) => {
+                if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                    return Err("latitude must be -90..90 and longitude -180..180".to_string());
+                }
+                return Ok(Place {
+                    latitude,
+                    longitude,
+                    label: format!("{latitude:.4}, {longitude:.4}"),
+                });
+            }
+            (None, None) => {}
+            _ => return Err("give both 'latitude' and 'longitude'".to_string()),
+        }
+        if let Some(name) = args
+            .get("place")
+            .and_then(Value::as_str)
+            .filter(|s| !s.trim().is_empty())
+        {
+            return self.geocode(name.trim()).await;
+        }
+        let loc = ctx.location.as_ref().ok_or(
+            "no location: pass 'place' or coordinates, or ask the user to share a location in Telegram",
+        )?;
+        Ok(Place {
+            latitude: loc.latitude,
+            longitude: loc.longitude,
+            label: format!(
+                "{:.4}, {:.4} (shared location, {})",
+                loc.latitude,
+                loc.longitude,
+                age(loc.updated_at)
+            ),
+        })
+    }
+
+    async fn forecast(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let place = self.place(ctx, args).await?;
+        let days = args
+            .get("days")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_DAYS)
+            .clamp(1, MAX_DAYS);
+        let body = self
+            .get_json(
+                &self.forecast_url,
+                &[
+                    ("latitude", place.latitude.to_string()),
+                    ("longitude", place.longitude.to_string()),
+                    (
+                        "current",
+                        "temperature_2m,apparent_temperature,relative_humidity_2m,precipitation,weather_code,wind_speed_10m".to_string(),
+                    ),
+                    (
+                        "daily",
+                        "weather_code,temperature_2m_max,temperature_2m_min,precipitation_probability_max,precipitation_sum,sunrise,sunset".to_string(),
+                    ),
+                    ("timezone", "auto".to_string()),
+                    ("forecast_days", days.to_string()),
+                ],
+            )
+            .await?;
+        format_forecast(&place, &body)
+    }
+}
+
+impl Tool for WeatherTool {
+    fn name(&self) -> &str {
+        "weather"
+    }
+
+    fn description(&self) -> &str {
+        "Current weather and a daily forecast (conditions, temperatures, rain chance, sunrise \
+         and sunset) from Open-Meteo. Uses coordinates or a place name if given, otherwise the \
+         location the user last shared in the chat."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "place": { "type": "string", "description": "Place name, e.g. 'Leeds' or 'Paris, France'" },
+                "latitude": { "type": "number", "description": "Latitude (with longitude)" },
+                "longitude": { "type": "number", "description": "Longitude (with latitude)" },
+                "days": { "type": "integer", "description": "Forecast days, 1-7 (default 3)" }
+            }
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.forecast(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::db::ChatLocation;
+    use crate::tools::web::web_client;
+    use std::path::PathBuf;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn ctx(location: Option<ChatLocation>) -> ToolCtx {
+        ToolCtx {
+            location,
+            ..ToolCtx::for_tests(PathBuf::from("/tmp"))
+        }
+    }
+
+    fn forecast_body() -> Value {
+        serde_json::json!({
+            "timezone": "Europe/London",
+            "current": {
+                "time": "2026-10-16T08:00",
+                "temperature_2m": 11.0,
+                "apparent_temperature": 9.4,
+                "relative_humidity_2m": 87,
+                "precipitation": 0.0,
+                "weather_code": 2,
+                "wind_speed_10m": 14.3
+            },
+            "daily": {
+                "time": ["2026-10-16", "2026-10-17"],
+                "weather_code": [61, 0],
+                "temperature_2m_min": [8.2, 6.0],
+                "temperature_2m_max": [14.1, 15.5],
+                "precipitation_probability_max": [80, 5],
+                "precipitation_sum": [3.2, 0.0],
+                "sunrise": ["2026-10-16T07:25", "2026-10-17T07:27"],
+                "sunset": ["2026-10-16T18:05", "2026-10-17T18:03"]
+            }
+        })
+    }
+
+    async fn tool(server: &MockServer) -> WeatherTool {
+        WeatherTool::with_urls(
+            web_client().unwrap(),
+            &format!("{}/v1/forecast", server.uri()),
+            &format!("{}/v1/search", server.uri()),
+        )
+    }
+
+    #[tokio::test]
+    async fn uses_shared_location_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/forecast"))
+            .and(query_param("latitude", "51.5"))
+            .and(query_param("longitude", "-0.12"))
+            .and(query_param("forecast_days", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(forecast_body()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let loc = ChatLocation {
+            latitude: 51.5,
+            longitude: -0.12,
+            updated_at: chrono::Utc::now().timestamp() - 7200,
+        };
+
+        let res = tool(&server)
+            .await
+            .execute(&ctx(Some(loc)), &serde_json::json!({"days": 2}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "Weather for 51.5000, -0.1200 (shared location, 2 h ago) (Europe/London)\n\
+             Now (2026-10-16 08:00): 11°C (feels like 9.4°C), Partly cloudy, humidity 87%, \
+             wind 14.3 km/h\n\n\
+             Forecast:\n\
+             - Fri 2026-10-16: Light rain, 8.2–14.1°C, rain 80% (3.2 mm), sunrise 07:25, sunset 18:05\n\
+             - Sat 2026-10-17: Clear sky, 6–15.5°C, rain 5%, sunrise 07:27, sunset 18:03"
+        );
+    }
+
+    #[tokio::test]
+    async fn geocodes_place_names() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/search"))
+            .and(query_param("name", "Leeds"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [{"name": "Leeds", "admin1": "England", "country": "United Kingdom",
+                             "latitude": 53.79648, "longitude": -1.54785}]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/forecast"))
+            .and(query_param("latitude", "53.79648"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(forecast_body()))
+            .mount(&server)
+            .await;
+
+        let res = tool(&server)
+            .await
+            .execute(&ctx(None), &serde_json::json!({"place": "Leeds"}))
+            .await;
+        assert!(
+            res.for_llm
+                .starts_with("Weather for Leeds, England, United Kingdom (Europe/London)"),
+            "{}",
+            res.for_llm
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_without_location() {
+        let server = MockServer::start().await;
+        let t = tool(&server).await;
+        let res = t.execute(&ctx(None), &serde_json::json!({})).await;
+        assert!(res.is_error);
+        assert!(res.for_llm.contains("share a location"));
+        let res = t
+            .execute(&ctx(None), &serde_json::json!({"latitude": 51.5}))
+            .await;
+        assert!(res.for_llm.contains("both 'latitude' and 'longitude'"));
+        let res = t
+            .execute(
+                &ctx(None),
+                &serde_json::json!({"latitude": 151.5, "longitude": 0}),
+            )
+            .await;
+        assert!(res.for_llm.contains("-90..90"));
+    }
+}
@@ -1,9 +1,13 @@
-//! web_search (Brave/DDG), web_fetch (GET URL, truncated body).
+//! web_search (Brave/SearXNG/Google/DDG), web_fetch (GET URL, truncated body; cached in BrainDb `web_cache`).
+
+use std::sync::Arc;
 
 use regex_lite::Regex;
 use reqwest::Client;
 use serde_json::Value;
 
+use crate::config::WebConfig;
+use crate::memory::db::{BrainDb, CachedPage, DbError};
 use crate::tools::context::ToolCtx;
 use crate::tools::registry::{BoxFuture, Tool};
 use crate::tools::result::ToolResult;
@@ -12,42 +16,159 @@ const USER_AGENT: &str = "iCrab/1.0 (https://github.com/Snehal-Reddy/iCrab)";
 const SEARCH_TIMEOUT_SECS: u64 = 15;
 const FETCH_TIMEOUT_SECS: u64 = 60;
 const MAX_REDIRECTS: u32 = 5;
+const GOOGLE_SEARCH_URL: &str = "https://www.googleapis.com/customsearch/v1";
 
-/// Search provider: Brave API or DuckDuckGo HTML fallback.
+/// Search provider: Brave API, a SearXNG instance, Google Custom Search, or DuckDuckGo HTML
+/// fallback.
 #[derive(Clone)]
 pub enum WebSearchProvider {
-    Brave { api_key: String, max_results: u8 },
-    DuckDuckGo { max_results: u8 },
+    Brave {
+        api_key: String,
+        max_results: u8,
+    },
+    /// Self-hosted metasearch; the instance must allow `format=json`.
+    SearXng {
+        base_url: String,
+        max_results: u8,
+    },
+    /// Custom Search JSON API with a Programmable Search Engine id (`cx`).
+    Google {
+        api_key: String,
+        engine_id: String,
+        max_results: u8,
+    },
+    DuckDuckGo {
+        max_results: u8,
+    },
 }
 
 impl WebSearchProvider {
+    /// From `[tools.web]`: `search-provider` when set, else Brave when it has an API key, else
+    /// DuckDuckGo. A selected provider missing its settings also falls back to DuckDuckGo
+    /// (config validation reports that case).
+    pub fn from_config(web: Option<&WebConfig>, max_results: u8) -> Self {
+        let setting = |get: fn(&WebConfig) -> Option<&String>| {
+            web.and_then(get)
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+        };
+        let brave_key = setting(|w| w.brave_api_key.as_ref());
+        let provider = setting(|w| w.search_provider.as_ref()).unwrap_or_else(|| {
+            if brave_key.is_some() {
+                "brave"
+            } else {
+                "duckduckgo"
+            }
+            .into()
+        });
+        let chosen = match provider.as_str() {
+            "brave" => brave_key.map(|api_key| Self::Brave {
+                api_key,
+                max_results,
+            }),
+            "searxng" => setting(|w| w.searxng_url.as_ref()).map(|base_url| Self::SearXng {
+                base_url,
+                max_results,
+            }),
+            "google" => setting(|w| w.google_api_key.as_ref())
+                .zip(setting(|w| w.google_cx.as_ref()))
+                .map(|(api_key, engine_id)| Self::Google {
+                    api_key,
+                    engine_id,
+                    max_results,
+                }),
+            _ => None,
+        };
+        chosen.unwrap_or(Self::DuckDuckGo { max_results })
+    }
+
     fn max_results(&self) -> u8 {
         match self {
-            Self::Brave { max_results, .. } => *max_results,
-            Self::DuckDuckGo { max_results } => *max_results,
+            Self::Brave { max_results, .. }
+            | Self::SearXng { max_results, .. }
+            | Self::Google { max_results, .. }
+            | Self::DuckDuckGo { max_results } => *max_results,
         }
     }
 
-    async fn search(&self, client: &Client, query: &str, count: u8) -> Result<String, String> {
-        let count = count.clamp(1, 10);
+    async fn search(
+        &self,
+        client: &Client,
+        query: &str,
+        freshness: Option<Freshness>,
+    ) -> Result<Vec<SearchHit>, String> {
         match self {
-            Self::Brave { api_key, .. } => brave_search(client, api_key, query, count).await,
-            Self::DuckDuckGo { .. } => duckduckgo_search(client, query, count).await,
+            Self::Brave { api_key, .. } => brave_search(client, api_key, query, freshness).await,
+            Self::SearXng { base_url, .. } => {
+                searxng_search(client, base_url, query, freshness).await
+            }
+            Self::Google {
+                api_key, engine_id, ..
+            } => google_search(client, api_key, engine_id, query, freshness).await,
+            Self::DuckDuckGo { .. } => duckduckgo_search(client, query, freshness).await,
         }
     }
 }
 
+/// Results asked of every provider; filtering and de-duplication then cut them to `count`.
+const FETCH_RESULTS: u8 = 10;
+
+/// How recent results must be (`freshness` parameter of web_search).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Freshness {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Freshness {
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "day" => Some(Self::Day),
+            "week" => Some(Self::Week),
+            "month" => Some(Self::Month),
+            "year" => Some(Self::Year),
+            _ => None,
+        }
+    }
+
+    /// The same window in a provider's own vocabulary: (Brave, SearXNG, Google, DuckDuckGo).
+    fn codes(self) -> (&'static str, &'static str, &'static str, &'static str) {
+        match self {
+            Self::Day => ("pd", "day", "d1", "d"),
+            Self::Week => ("pw", "week", "w1", "w"),
+            Self::Month => ("pm", "month", "m1", "m"),
+            Self::Year => ("py", "year", "y1", "y"),
+        }
+    }
+}
+
+/// One search result, whichever provider it came from.
+#[derive(Debug, Clone, PartialEq)]
+struct SearchHit {
+    title: String,
+    url: String,
+    snippet: String,
+}
+
 async fn brave_search(
     client: &Client,
     api_key: &str,
     query: &str,
-    count: u8,
-) -> Result<String, String> {
-    let url = reqwest::Url::parse_with_params(
-        "https://api.search.brave.com/res/v1/web/search",
-        &[("q", query), ("count", &count.to_string())],
-    )
-    .map_err(|e| e.to_string())?;
+    freshness: Option<Freshness>,
+) -> Result<Vec<SearchHit>, String> {
+    let mut params = vec![
+        ("q", query.to_string()),
+        ("count", FETCH_RESULTS.to_string()),
+    ];
+    if let Some(f) = freshness {
+        params.push(("freshness", f.codes().0.to_string()));
+    }
+    let url =
+        reqwest::Url::parse_with_params("https://api.search.brave.com/res/v1/web/search", &params)
+            .map_err(|e| e.to_string())?;
     let res = client
         .get(url)
         .header("X-Subscription-Token", api_key)
@@ -66,38 +187,166 @@ async fn brave_search(
         .and_then(Value::as_array)
         .map(|a| a.as_slice())
         .unwrap_or(&[]);
-    Ok(format_brave_results(results))
+    Ok(json_hits(results, "url", "description"))
 }
 
-/// Format Brave API web.results array for LLM. Used by brave_search and tests.
+/// Format Brave API web.results array for LLM. Used by tests.
+#[cfg(test)]
 fn format_brave_results(results: &[Value]) -> String {
-    let mut lines = Vec::with_capacity(results.len());
-    for r in results {
-        let title = r.get("title").and_then(Value::as_str).unwrap_or("");
-        let url = r.get("url").and_then(Value::as_str).unwrap_or("");
-        let desc = r.get("description").and_then(Value::as_str).unwrap_or("");
-        lines.push(format!("- **{}**\n  {}\n  {}", title, url, desc));
-    }
-    if lines.is_empty() {
-        "No results.".to_string()
-    } else {
-        lines.join("\n\n")
+    format_hits(&json_hits(results, "url", "description"))
+}
+
+/// JSON search results: `title` plus the given URL and snippet fields.
+fn json_hits(results: &[Value], url_key: &str, snippet_key: &str) -> Vec<SearchHit> {
+    let field = |r: &Value, key: &str| {
+        r.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    results
+        .iter()
+        .map(|r| SearchHit {
+            title: field(r, "title"),
+            url: field(r, url_key),
+            snippet: field(r, snippet_key),
+        })
+        .collect()
+}
+
+/// Results as shown to the LLM: bold title, URL, and the snippet when there is one.
+fn format_hits(hits: &[SearchHit]) -> String {
+    if hits.is_empty() {
+        return "No results.".to_string();
+    }
+    hits.iter()
+        .map(|h| {
+            if h.snippet.is_empty() {
+                format!("- **{}**\n  {}", h.title, h.url)
+            } else {
+                format!("- **{}**\n  {}\n  {}", h.title, h.url, h.snippet)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+async fn searxng_search(
+    client: &Client,
+    base_url: &str,
+    query: &str,
+    freshness: Option<Freshness>,
+) -> Result<Vec<SearchHit>, String> {
+    let mut params = vec![("q", query), ("format", "json")];
+    if let Some(f) = freshness {
+        params.push(("time_range", f.codes().1));
+    }
+    let url = reqwest::Url::parse_with_params(
+        &format!("{}/search", base_url.trim_end_matches('/')),
+        &params,
+    )
+    .map_err(|e| e.to_string())?;
+    let res = client.get(url).send().await.map_err(|e| e.to_string())?;
+    let status = res.status();
+    if status == reqwest::StatusCode::FORBIDDEN {
+        return Err(format!(
+            "SearXNG returned {status}; enable the json format (search.formats in settings.yml)"
+        ));
+    }
+    if !status.is_success() {
+        return Err(format!("SearXNG returned {}", status));
+    }
+    let v: Value = res.json().await.map_err(|e| e.to_string())?;
+    let results = v
+        .get("results")
+        .and_then(Value::as_array)
+        .map(|a| a.as_slice())
+        .unwrap_or(&[]);
+    Ok(json_hits(results, "url", "content"))
+}
+
+async fn google_search(
+    client: &Client,
+    api_key: &str,
+    engine_id: &str,
+    query: &str,
+    freshness: Option<Freshness>,
+) -> Result<Vec<SearchHit>, String> {
+    let mut params = vec![
+        ("key", api_key.to_string()),
+        ("cx", engine_id.to_string()),
+        ("q", query.to_string()),
+        ("num", FETCH_RESULTS.to_string()),
+    ];
+    if let Some(f) = freshness {
+        params.push(("dateRestrict", f.codes().2.to_string()));
     }
+    let url =
+        reqwest::Url::parse_with_params(GOOGLE_SEARCH_URL, &params).map_err(|e| e.to_string())?;
+    let res = client.get(url).send().await.map_err(|e| e.to_string())?;
+    let status = res.status();
+    let body = res.text().await.map_err(|e| e.to_string())?;
+    let v: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+    if !status.is_success() {
+        let message = v
+            .pointer("/error/message")
+            .and_then(Value::as_str)
+            .unwrap_or(body.trim());
+        return Err(format!(
+            "Google Custom Search error {}: {}",
+            status, message
+        ));
+    }
+    // No "items" at all when nothing matched.
+    let items = v
+        .get("items")
+        .and_then(Value::as_array)
+        .map(|a| a.as_slice())
+        .unwrap_or(&[]);
+    Ok(json_hits(items, "link", "snippet"))
 }
 
-async fn duckduckgo_search(client: &Client, query: &str, count: u8) -> Result<String, String> {
-    let url = reqwest::Url::parse_with_params("https://html.duckduckgo.com/html/", &[("q", query)])
+async fn duckduckgo_search(
+    client: &Client,
+    query: &str,
+    freshness: Option<Freshness>,
+) -> Result<Vec<SearchHit>, String> {
+    let mut params = vec![("q", query)];
+    if let Some(f) = freshness {
+        params.push(("df", f.codes().3));
+    }
+    let url = reqwest::Url::parse_with_params("https://html.duckduckgo.com/html/", &params)
         .map_err(|e| e.to_string())?;
     let res = client.get(url).send().await.map_err(|e| e.to_string())?;
     if !res.status().is_success() {
         return Err(format!("DuckDuckGo returned {}", res.status()));
     }
     let html = res.text().await.map_err(|e| e.to_string())?;
-    extract_ddg_results(&html, count)
+    ddg_hits(&html)
+}
+
+/// Target of a DDG result link: `//duckduckgo.com/l/?uddg=<url>` redirects are unwrapped.
+fn ddg_target(href: &str) -> String {
+    let absolute = if href.starts_with("//") {
+        format!("https:{href}")
+    } else {
+        href.to_string()
+    };
+    reqwest::Url::parse(&absolute)
+        .ok()
+        .filter(|u| u.host_str().is_some_and(|h| h.ends_with("duckduckgo.com")))
+        .and_then(|u| {
+            u.query_pairs()
+                .find(|(k, _)| k == "uddg")
+                .map(|(_, v)| v.into_owned())
+        })
+        .unwrap_or(absolute)
 }
 
-/// Extract result links and optional snippets from DDG HTML (regex-based).
-fn extract_ddg_results(html: &str, max: u8) -> Result<String, String> {
+/// Extract result links and optional snippets from DDG HTML (regex-based). Ads are skipped.
+fn ddg_hits(html: &str) -> Result<Vec<SearchHit>, String> {
     // DDG HTML: result links in <a class="result__a" href="...">title</a>, snippet in result__snippet.
     let link_re = Regex::new(r#"<a\s+class="result__a"[^>]*href="([^"]+)"[^>]*>([^<]*)</a>"#)
         .map_err(|e| e.to_string())?;
@@ -106,38 +355,126 @@ fn extract_ddg_results(html: &str, max: u8) -> Result<String, String> {
     )
     .map_err(|e| e.to_string())?;
 
-    let mut lines = Vec::new();
+    let mut hits = Vec::new();
     let mut seen = std::collections::HashSet::new();
-
-    // Prefer snippet matches (title + url + snippet)
-    for cap in snippet_re.captures_iter(html) {
-        if lines.len() >= max as usize {
-            break;
+    let mut push = |href: &str, title: &str, snippet: &str| {
+        let href = html_unescape(href);
+        if href.contains("duckduckgo.com/y.js") {
+            return;
         }
-        let url = html_unescape(&cap[1]);
-        let title = html_unescape(&cap[2]).trim().to_string();
-        let snippet = html_unescape(&cap[3]).trim().to_string();
+        let url = ddg_target(&href);
         if seen.insert(url.clone()) {
-            lines.push(format!("- **{}**\n  {}\n  {}", title, url, snippet));
+            hits.push(SearchHit {
+                title: html_unescape(title).trim().to_string(),
+                url,
+                snippet: html_unescape(snippet).trim().to_string(),
+            });
         }
+    };
+
+    // Prefer snippet matches (title + url + snippet), then links without snippet.
+    for cap in snippet_re.captures_iter(html) {
+        push(&cap[1], &cap[2], &cap[3]);
     }
-    // Then links without snippet
-    if lines.len() < max as usize {
-        for cap in link_re.captures_iter(html) {
-            if lines.len() >= max as usize {
-                break;
-            }
-            let url = html_unescape(&cap[1]);
-            let title = html_unescape(&cap[2]).trim().to_string();
-            if seen.insert(url.clone()) {
-                lines.push(format!("- **{}**\n  {}", title, url));
+    for cap in link_re.captures_iter(html) {
+        push(&cap[1], &cap[2], "");
+    }
+    Ok(hits)
+}
+
+/// A bare domain from "site:docs.rs", "https://www.docs.rs/foo" or "Docs.rs".
+fn clean_domain(s: &str) -> String {
+    let s = s.trim().trim_start_matches("site:");
+    let s = s.split_once("://").map_or(s, |(_, rest)| rest);
+    let s = s.split(['/', '?', '#']).next().unwrap_or("");
+    s.trim_start_matches("www.").to_ascii_lowercase()
+}
+
+/// Whether `url`'s host is `domain` or one of its subdomains.
+fn in_domain(url: &str, domain: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+        .is_some_and(|host| host == domain || host.ends_with(&format!(".{domain}")))
+}
+
+/// Key under which the same page found twice collapses: no scheme, `www.`, fragment,
+/// trailing slash or tracking parameters.
+fn dedup_key(url: &str) -> String {
+    let Ok(u) = reqwest::Url::parse(url) else {
+        return url.trim_end_matches('/').to_ascii_lowercase();
+    };
+    let host = u.host_str().unwrap_or("").trim_start_matches("www.");
+    let query: Vec<String> = u
+        .query_pairs()
+        .filter(|(k, _)| {
+            !k.starts_with("utm_") && !matches!(k.as_ref(), "ref" | "fbclid" | "gclid")
+        })
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect();
+    format!(
+        "{host}{}?{}",
+        u.path().trim_end_matches('/'),
+        query.join("&")
+    )
+}
+
+/// Domain filters and de-duplication applied to every provider's results.
+#[derive(Debug, Default)]
+struct ResultFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl ResultFilter {
+    fn from_args(args: &Value) -> Self {
+        let domains = |key: &str| -> Vec<String> {
+            let list = match args.get(key) {
+                Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+                Some(Value::String(s)) => s.split(',').collect(),
+                _ => Vec::new(),
+            };
+            let mut out: Vec<String> = list.into_iter().map(clean_domain).collect();
+            out.retain(|d| !d.is_empty());
+            out.dedup();
+            out
+        };
+        Self {
+            include: domains("include_domains"),
+            exclude: domains("exclude_domains"),
+        }
+    }
+
+    /// `query` with `site:` operators, so the provider already searches within the filters.
+    fn query(&self, query: &str) -> String {
+        let mut q = query.trim().to_string();
+        match self.include.as_slice() {
+            [] => {}
+            [one] => q.push_str(&format!(" site:{one}")),
+            many => {
+                let sites: Vec<String> = many.iter().map(|d| format!("site:{d}")).collect();
+                q.push_str(&format!(" ({})", sites.join(" OR ")));
             }
         }
+        for d in &self.exclude {
+            q.push_str(&format!(" -site:{d}"));
+        }
+        q
     }
-    if lines.is_empty() {
-        return Ok("No results.".to_string());
+
+    /// Up to `count` hits within the domain filters, each page once. Providers don't always
+    /// honour `site:`, so the filters are enforced here too.
+    fn apply(&self, hits: Vec<SearchHit>, count: usize) -> Vec<SearchHit> {
+        let mut seen = std::collections::HashSet::new();
+        hits.into_iter()
+            .filter(|h| {
+                self.include.is_empty() || self.include.iter().any(|d| in_domain(&h.url, d))
+            })
+            .filter(|h| !self.exclude.iter().any(|d| in_domain(&h.url, d)))
+            .filter(|h| seen.insert(dedup_key(&h.url)))
+            .take(count)
+            .collect()
     }
-    Ok(lines.join("\n\n"))
 }
 
 fn html_unescape(s: &str) -> String {
@@ -182,7 +519,7 @@ fn get_optional_u32(args: &Value, key: &str) -> Option<u32> {
         .and_then(|n| u32::try_from(n).ok())
 }
 
-/// web_search tool: Brave API or DuckDuckGo; returns titles, URLs, snippets.
+/// web_search tool: the configured [`WebSearchProvider`]; returns titles, URLs, snippets.
 pub struct WebSearchTool {
     pub provider: WebSearchProvider,
     pub client: Client,
@@ -200,7 +537,7 @@ impl Tool for WebSearchTool {
     }
 
     fn description(&self) -> &str {
-        "Search the web. Returns titles, URLs, and snippets. Use for finding current info or links."
+        "Search the web. Returns titles, URLs, and snippets. Use for finding current info or links. Duplicate pages are collapsed; narrow with include/exclude domains and freshness."
     }
 
     fn parameters(&self) -> Value {
@@ -208,12 +545,19 @@ impl Tool for WebSearchTool {
             "type": "object",
             "properties": {
                 "query": { "type": "string", "description": "Search query" },
-                "count": { "type": "integer", "description": "Max results 1-10", "minimum": 1, "maximum": 10 }
+                "count": { "type": "integer", "description": "Max results 1-10", "minimum": 1, "maximum": 10 },
+                "include_domains": { "type": "array", "items": { "type": "string" }, "description": "Only results from these domains or their subdomains, e.g. [\"docs.rs\"]" },
+                "exclude_domains": { "type": "array", "items": { "type": "string" }, "description": "Drop results from these domains, e.g. [\"pinterest.com\"]" },
+                "freshness": { "type": "string", "enum": ["day", "week", "month", "year"], "description": "Only pages from the past day, week, month or year" }
             },
             "required": ["query"]
         })
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
         let args = args.clone();
         let provider = self.provider.clone();
@@ -226,16 +570,53 @@ impl Tool for WebSearchTool {
             let count = get_optional_u8(&args, "count")
                 .unwrap_or_else(|| provider.max_results())
                 .clamp(1, 10);
-            match provider.search(&client, &query, count).await {
-                Ok(s) => ToolResult::ok(s),
+            let freshness = match args.get("freshness").and_then(Value::as_str) {
+                None => None,
+                Some(f) => match Freshness::parse(f) {
+                    Some(f) => Some(f),
+                    None => {
+                        return ToolResult::error(format!(
+                            "unknown freshness '{f}' (use day, week, month or year)"
+                        ));
+                    }
+                },
+            };
+            let filter = ResultFilter::from_args(&args);
+            match provider
+                .search(&client, &filter.query(&query), freshness)
+                .await
+            {
+                Ok(hits) => ToolResult::ok(format_hits(&filter.apply(hits, count as usize))),
                 Err(e) => ToolResult::error(e),
             }
         })
     }
 }
 
+/// Response body as text by content type: JSON pretty-printed, HTML converted to text.
+pub(crate) fn body_text(headers: &reqwest::header::HeaderMap, body: &[u8]) -> String {
+    let content_type = headers
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_lowercase();
+
+    if content_type.contains("application/json") {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(v) => serde_json::to_string_pretty(&v)
+                .unwrap_or_else(|_| String::from_utf8_lossy(body).into_owned()),
+            Err(_) => String::from_utf8_lossy(body).into_owned(),
+        }
+    } else if content_type.contains("text/html") || content_type.contains("application/xhtml") {
+        let raw = String::from_utf8_lossy(body).into_owned();
+        html_to_text(&raw)
+    } else {
+        String::from_utf8_lossy(body).into_owned()
+    }
+}
+
 /// Validate URL: http/https and has host.
-fn validate_fetch_url(s: &str) -> Result<reqwest::Url, String> {
+pub(crate) fn validate_fetch_url(s: &str) -> Result<reqwest::Url, String> {
     let url = reqwest::Url::parse(s).map_err(|e| e.to_string())?;
     if url.scheme() != "http" && url.scheme() != "https" {
         return Err("only http and https URLs are allowed".to_string());
@@ -246,15 +627,177 @@ fn validate_fetch_url(s: &str) -> Result<reqwest::Url, String> {
     Ok(url)
 }
 
+/// Default seconds a cached page is reused before revalidating.
+pub const DEFAULT_WEB_FETCH_CACHE_TTL_SECS: u64 = 900;
+/// Larger bodies are never cached.
+const MAX_CACHED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// "just now", "12 min ago", "3 h ago", "2 days ago".
+fn age(secs: i64) -> String {
+    if secs < 60 {
+        "just now".to_string()
+    } else if secs < 3_600 {
+        format!("{} min ago", secs / 60)
+    } else if secs < 86_400 {
+        format!("{} h ago", secs / 3_600)
+    } else {
+        format!("{} days ago", secs / 86_400)
+    }
+}
+
+/// A response body, from the network or from `web_cache`.
+struct Fetched {
+    status: reqwest::StatusCode,
+    headers: reqwest::header::HeaderMap,
+    body: Vec<u8>,
+    /// How the cache was used, for the result header.
+    cache_note: Option<String>,
+}
+
+impl Fetched {
+    fn from_cache(page: CachedPage, note: String) -> Self {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Ok(value) = page.content_type.parse() {
+            headers.insert(reqwest::header::CONTENT_TYPE, value);
+        }
+        Self {
+            status: reqwest::StatusCode::from_u16(page.status).unwrap_or(reqwest::StatusCode::OK),
+            headers,
+            body: page.body,
+            cache_note: Some(note),
+        }
+    }
+}
+
 /// web_fetch tool: GET URL, return body as text (JSON pretty, HTML stripped, truncated).
 pub struct WebFetchTool {
     pub client: Client,
     pub max_chars: u32,
+    /// Pages are cached here when set (see [`WebFetchTool::with_cache`]).
+    db: Option<Arc<BrainDb>>,
+    cache_ttl_secs: u64,
 }
 
 impl WebFetchTool {
     pub fn new(client: Client, max_chars: u32) -> Self {
-        Self { client, max_chars }
+        Self {
+            client,
+            max_chars,
+            db: None,
+            cache_ttl_secs: DEFAULT_WEB_FETCH_CACHE_TTL_SECS,
+        }
+    }
+
+    /// Keep fetched pages in `web_cache`: reuse them for `ttl_secs`, then revalidate with
+    /// If-None-Match / If-Modified-Since so an unchanged page is not downloaded again.
+    pub fn with_cache(mut self, db: Arc<BrainDb>, ttl_secs: u64) -> Self {
+        self.db = Some(db);
+        self.cache_ttl_secs = ttl_secs;
+        self
+    }
+
+    /// Cached page for `url`; DB failures count as a miss.
+    async fn cached(&self, url: &str) -> Option<CachedPage> {
+        let db = Arc::clone(self.db.as_ref()?);
+        let url = url.to_string();
+        match tokio::task::spawn_blocking(move || db.web_cache_get(&url)).await {
+            Ok(Ok(page)) => page,
+            Ok(Err(e)) => {
+                eprintln!("web cache: {}", e);
+                None
+            }
+            Err(e) => {
+                eprintln!("web cache task error: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Run a cache write off the async runtime. Failures are logged, never fatal.
+    async fn update_cache(
+        &self,
+        write: impl FnOnce(&BrainDb) -> Result<(), DbError> + Send + 'static,
+    ) {
+        let Some(db) = self.db.as_ref().map(Arc::clone) else {
+            return;
+        };
+        match tokio::task::spawn_blocking(move || write(&db)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("web cache: {}", e),
+            Err(e) => eprintln!("web cache task error: {}", e),
+        }
+    }
+
+    async fn fetch(&self, url: reqwest::Url) -> Result<Fetched, String> {
+        let now = chrono::Utc::now().timestamp();
+        let cached = self.cached(url.as_str()).await;
+        if let Some(page) = &cached {
+            let age_secs = now - page.fetched_at;
+            if age_secs >= 0 && (age_secs as u64) < self.cache_ttl_secs {
+                let note = format!("fetched {}, not re-downloaded", age(age_secs));
+                return Ok(Fetched::from_cache(page.clone(), note));
+            }
+        }
+
+        let mut req = self.client.get(url.clone());
+        if let Some(page) = &cached {
+            if let Some(etag) = &page.etag {
+                req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &page.last_modified {
+                req = req.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let res = req.send().await.map_err(|e| e.to_string())?;
+        let status = res.status();
+        if status == reqwest::StatusCode::NOT_MODIFIED
+            && let Some(page) = cached
+        {
+            let key = page.url.clone();
+            self.update_cache(move |db| db.web_cache_touch(&key, now))
+                .await;
+            let note = format!("not modified since {}", age(now - page.fetched_at));
+            return Ok(Fetched::from_cache(page, note));
+        }
+        let headers = res.headers().clone();
+        let body = res.bytes().await.map_err(|e| e.to_string())?.to_vec();
+
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        let no_store = header(reqwest::header::CACHE_CONTROL)
+            .is_some_and(|c| c.to_ascii_lowercase().contains("no-store"));
+        if status == reqwest::StatusCode::OK && !no_store && body.len() <= MAX_CACHED_BODY_BYTES {
+            let page = CachedPage {
+                url: url.to_string(),
+                status: status.as_u16(),
+                content_type: header(reqwest::header::CONTENT_TYPE).unwrap_or_default(),
+                etag: header(reqwest::header::ETAG),
+                last_modified: header(reqwest::header::LAST_MODIFIED),
+                body: body.clone(),
+                fetched_at: now,
+            };
+            self.update_cache(move |db| db.web_cache_put(&page)).await;
+        }
+        Ok(Fetched {
+            status,
+            headers,
+            body,
+            cache_note: None,
+        })
+    }
+
+    /// Fetch `url` (through the cache when set) as text, like `web_fetch` but untruncated.
+    /// Non-success statuses are errors.
+    pub(crate) async fn fetch_text(&self, url: &str) -> Result<String, String> {
+        let fetched = self.fetch(validate_fetch_url(url)?).await?;
+        if !fetched.status.is_success() {
+            return Err(format!("{url}: HTTP {}", fetched.status));
+        }
+        Ok(body_text(&fetched.headers, &fetched.body))
     }
 }
 
@@ -278,12 +821,13 @@ impl Tool for WebFetchTool {
         })
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
-        let args = args.clone();
-        let client = self.client.clone();
-        let max_chars = self.max_chars;
         Box::pin(async move {
-            let url_str = match get_string(&args, "url") {
+            let url_str = match get_string(args, "url") {
                 Ok(u) => u,
                 Err(e) => return ToolResult::error(e),
             };
@@ -291,39 +835,14 @@ impl Tool for WebFetchTool {
                 Ok(u) => u,
                 Err(e) => return ToolResult::error(e),
             };
-            let max_chars = get_optional_u32(&args, "max_chars").unwrap_or(max_chars);
+            let max_chars = get_optional_u32(args, "max_chars").unwrap_or(self.max_chars);
 
-            let res = match client.get(url.clone()).send().await {
-                Ok(r) => r,
-                Err(e) => return ToolResult::error(e.to_string()),
-            };
-            let status = res.status();
-            let headers = res.headers().clone();
-            let body = match res.bytes().await {
-                Ok(b) => b,
-                Err(e) => return ToolResult::error(e.to_string()),
+            let fetched = match self.fetch(url.clone()).await {
+                Ok(f) => f,
+                Err(e) => return ToolResult::error(e),
             };
 
-            let content_type = headers
-                .get("content-type")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("")
-                .to_lowercase();
-
-            let text = if content_type.contains("application/json") {
-                match serde_json::from_slice::<Value>(&body) {
-                    Ok(v) => serde_json::to_string_pretty(&v)
-                        .unwrap_or_else(|_| String::from_utf8_lossy(&body).into_owned()),
-                    Err(_) => String::from_utf8_lossy(&body).into_owned(),
-                }
-            } else if content_type.contains("text/html")
-                || content_type.contains("application/xhtml")
-            {
-                let raw = String::from_utf8_lossy(&body).into_owned();
-                html_to_text(&raw)
-            } else {
-                String::from_utf8_lossy(&body).into_owned()
-            };
+            let text = body_text(&fetched.headers, &fetched.body);
 
             let truncated = text.len() > max_chars as usize;
             let out = if truncated {
@@ -337,15 +856,19 @@ impl Tool for WebFetchTool {
             };
 
             let header = format!(
-                "URL: {}\nStatus: {}\nLength: {} bytes{}\n\n",
+                "URL: {}\nStatus: {}\nLength: {} bytes{}{}\n\n",
                 url,
-                status,
-                body.len(),
+                fetched.status,
+                fetched.body.len(),
                 if truncated {
                     format!(" (truncated to {} chars)", max_chars)
                 } else {
                     String::new()
-                }
+                },
+                fetched
+                    .cache_note
+                    .map(|note| format!("\nCached: {note}"))
+                    .unwrap_or_default()
             );
             ToolResult::ok(format!("{header}{out}"))
         })
@@ -369,14 +892,7 @@ mod tests {
     use std::path::PathBuf;
 
     fn dummy_ctx() -> ToolCtx {
-        ToolCtx {
-            workspace: PathBuf::from("/tmp"),
-            restrict_to_workspace: true,
-            chat_id: None,
-            channel: None,
-            outbound_tx: None,
-            delivered: Default::default(),
-        }
+        ToolCtx::for_tests(PathBuf::from("/tmp"))
     }
 
     #[test]
@@ -473,6 +989,154 @@ mod tests {
         assert!(s.contains("https://u.org"));
     }
 
+    #[test]
+    fn search_provider_from_config() {
+        let kind = |web: WebConfig| match WebSearchProvider::from_config(Some(&web), 5) {
+            WebSearchProvider::Brave { .. } => "brave",
+            WebSearchProvider::SearXng { .. } => "searxng",
+            WebSearchProvider::Google { .. } => "google",
+            WebSearchProvider::DuckDuckGo { .. } => "duckduckgo",
+        };
+        assert_eq!(kind(WebConfig::default()), "duckduckgo");
+        let brave = WebConfig {
+            brave_api_key: Some("k".into()),
+            ..WebConfig::default()
+        };
+        assert_eq!(kind(brave.clone()), "brave");
+        assert_eq!(
+            kind(WebConfig {
+                search_provider: Some("searxng".into()),
+                searxng_url: Some("https://searx.example.org".into()),
+                ..brave.clone()
+            }),
+            "searxng"
+        );
+        let google = WebConfig {
+            search_provider: Some("google".into()),
+            google_api_key: Some("g".into()),
+            ..WebConfig::default()
+        };
+        assert_eq!(kind(google.clone()), "duckduckgo", "cx missing");
+        assert_eq!(
+            kind(WebConfig {
+                google_cx: Some("cx".into()),
+                ..google
+            }),
+            "google"
+        );
+    }
+
+    #[test]
+    fn json_hits_google_shape() {
+        let items = [
+            serde_json::json!({ "title": "Rust", "link": "https://rust-lang.org", "snippet": "A language\nempowering everyone." }),
+            serde_json::json!({ "title": "Crates", "link": "https://crates.io", "snippet": "" }),
+        ];
+        assert_eq!(
+            format_hits(&json_hits(&items, "link", "snippet")),
+            "- **Rust**\n  https://rust-lang.org\n  A language empowering everyone.\n\n\
+             - **Crates**\n  https://crates.io"
+        );
+        assert_eq!(
+            format_hits(&json_hits(&[], "link", "snippet")),
+            "No results."
+        );
+    }
+
+    /// DDG results through the same post-processing web_search applies.
+    fn extract_ddg_results(html: &str, max: u8) -> Result<String, String> {
+        let hits = ResultFilter::default().apply(ddg_hits(html)?, max as usize);
+        Ok(format_hits(&hits))
+    }
+
+    #[test]
+    fn ddg_redirects_are_unwrapped_and_ads_skipped() {
+        let html = r#"<a class="result__a" href="https://duckduckgo.com/y.js?ad_domain=shop.com">Ad</a>
+            <a class="result__a" href="//duckduckgo.com/l/?uddg=https%3A%2F%2Fdocs.rs%2Ftokio&amp;rut=abc">tokio</a>"#;
+        let hits = ddg_hits(html).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].url, "https://docs.rs/tokio");
+    }
+
+    #[test]
+    fn result_filter_domains_dedup_and_query() {
+        let hit = |url: &str| SearchHit {
+            title: url.to_string(),
+            url: url.to_string(),
+            snippet: String::new(),
+        };
+        let args = serde_json::json!({
+            "include_domains": ["https://www.Rust-Lang.org/learn", "site:docs.rs"],
+            "exclude_domains": "blog.rust-lang.org"
+        });
+        let filter = ResultFilter::from_args(&args);
+        assert_eq!(filter.include, vec!["rust-lang.org", "docs.rs"]);
+        assert_eq!(
+            filter.query("async book"),
+            "async book (site:rust-lang.org OR site:docs.rs) -site:blog.rust-lang.org"
+        );
+        let hits = vec![
+            hit("https://rust-lang.github.io/async-book/"),
+            hit("https://doc.rust-lang.org/book/"),
+            hit("http://www.doc.rust-lang.org/book?utm_source=x#ch1"),
+            hit("https://blog.rust-lang.org/2026/01/01/async.html"),
+            hit("https://docs.rs/tokio"),
+            hit("https://notdocs.rs/tokio"),
+            hit("https://docs.rs/futures"),
+        ];
+        let urls: Vec<String> = filter.apply(hits, 2).into_iter().map(|h| h.url).collect();
+        assert_eq!(
+            urls,
+            vec!["https://doc.rust-lang.org/book/", "https://docs.rs/tokio"]
+        );
+        assert_eq!(
+            ResultFilter::default().query("  plain  "),
+            "plain",
+            "no filters, no operators"
+        );
+    }
+
+    #[tokio::test]
+    async fn searxng_search_parses_json_and_explains_403() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "ferris"))
+            .and(query_param("format", "json"))
+            .and(query_param("time_range", "week"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [
+                    { "title": "Ferris the crab", "url": "https://rustacean.net", "content": "The unofficial mascot." },
+                    { "title": "Second", "url": "https://example.com", "content": "" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+        let client = web_client().unwrap();
+        let base = format!("{}/", server.uri());
+
+        let hits = searxng_search(&client, &base, "ferris", Some(Freshness::Week))
+            .await
+            .unwrap();
+        assert_eq!(
+            format_hits(&hits[..1]),
+            "- **Ferris the crab**\n  https://rustacean.net\n  The unofficial mascot."
+        );
+        assert_eq!(hits.len(), 2);
+        let err = searxng_search(&client, &base, "other", None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("enable the json format"), "{err}");
+    }
+
     #[test]
     fn extract_ddg_results_empty() {
         let html = "<html><body>no results here</body></html>";
@@ -589,4 +1253,65 @@ mod tests {
                 .contains(&serde_json::json!("url"))
         );
     }
+
+    #[tokio::test]
+    async fn web_fetch_reuses_and_revalidates_cached_pages() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = Arc::new(BrainDb::open(tmp.path()).unwrap());
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_raw("<p>Opening hours</p>", "text/html"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let args = serde_json::json!({ "url": format!("{}/page", server.uri()) });
+
+        // Fresh for an hour: the second call doesn't touch the network.
+        let tool =
+            WebFetchTool::new(web_client().unwrap(), 1_000).with_cache(Arc::clone(&db), 3_600);
+        let first = tool.execute(&dummy_ctx(), &args).await;
+        assert!(!first.is_error, "{}", first.for_llm);
+        assert!(
+            first.for_llm.ends_with("Opening hours"),
+            "{}",
+            first.for_llm
+        );
+        assert!(!first.for_llm.contains("Cached:"));
+        let second = tool.execute(&dummy_ctx(), &args).await;
+        assert!(
+            second
+                .for_llm
+                .contains("Cached: fetched just now, not re-downloaded"),
+            "{}",
+            second.for_llm
+        );
+        assert!(second.for_llm.ends_with("Opening hours"));
+
+        // With no TTL it asks the server, which answers 304.
+        let tool = WebFetchTool::new(web_client().unwrap(), 1_000).with_cache(db, 0);
+        let third = tool.execute(&dummy_ctx(), &args).await;
+        assert!(
+            third
+                .for_llm
+                .contains("Status: 200 OK\nLength: 20 bytes\nCached: not modified since just now"),
+            "{}",
+            third.for_llm
+        );
+        assert!(third.for_llm.ends_with("Opening hours"));
+    }
 }
@@ -0,0 +1,668 @@
+//! `wikipedia` tool: the lead section and infobox facts of an article, straight from the
+//! Wikipedia APIs (REST summary, TextExtracts, wikitext of section 0) instead of a web search
+//! followed by fetching a heavy HTML page. Unknown titles fall back to a search.
+
+use regex_lite::{Captures, Regex};
+use reqwest::{Client, StatusCode, Url};
+use serde_json::Value;
+
+use crate::tools::context::ToolCtx;
+use crate::tools::feeds::decode_entities;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+/// `{lang}` is replaced by the language code.
+const DEFAULT_SITE: &str = "https://{lang}.wikipedia.org";
+const DEFAULT_LANG: &str = "en";
+const MAX_EXTRACT_CHARS: usize = 4_000;
+const MAX_FACTS: usize = 25;
+const MAX_FACT_CHARS: usize = 200;
+const SEARCH_RESULTS: usize = 5;
+
+/// Words of infobox parameter names (`image`, `map_caption`, `alt2`) that hold layout or
+/// media rather than facts.
+const SKIPPED_FACT_KEYS: &[&str] = &[
+    "image",
+    "caption",
+    "alt",
+    "logo",
+    "signature",
+    "map",
+    "pushpin",
+    "width",
+    "upright",
+    "module",
+    "embed",
+    "footnotes",
+    "coordinates",
+    "coord",
+    "size",
+];
+
+/// Templates whose positional parameters are list items.
+const LIST_TEMPLATES: &[&str] = &[
+    "plainlist",
+    "plain list",
+    "flatlist",
+    "flat list",
+    "ubl",
+    "ubil",
+    "unbulleted list",
+    "hlist",
+    "bulleted list",
+    "enum",
+];
+
+// ---------------------------------------------------------------------------
+// Wikitext
+// ---------------------------------------------------------------------------
+
+/// The body of the first `{{Infobox …}}` in `wikitext`, braces excluded.
+fn infobox_body(wikitext: &str) -> Option<&str> {
+    let start = wikitext.to_ascii_lowercase().find("{{infobox")?;
+    let bytes = wikitext.as_bytes();
+    let mut depth = 0usize;
+    let mut i = start;
+    while i + 1 < bytes.len() {
+        match &bytes[i..i + 2] {
+            b"{{" => {
+                depth += 1;
+                i += 2;
+            }
+            b"}}" => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return Some(&wikitext[start + 2..i - 2]);
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// A template's text without braces as plain text: dates joined with "-", list items with
+/// ", ", anything else its positional parameters joined with spaces.
+fn render_template(inner: &str) -> String {
+    let mut parts = inner.split('|');
+    let name = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+    let positional: Vec<&str> = parts
+        .map(str::trim)
+        .filter(|p| !p.is_empty() && !p.contains('='))
+        .collect();
+    if name.contains("date") {
+        let numbers: Vec<&str> = positional
+            .iter()
+            .copied()
+            .take_while(|p| p.chars().all(|c| c.is_ascii_digit()))
+            .take(3)
+            .collect();
+        if !numbers.is_empty() {
+            return numbers
+                .iter()
+                .enumerate()
+                .map(|(i, n)| {
+                    if i == 0 {
+                        n.to_string()
+                    } else {
+                        format!("{n:0>2}")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("-");
+        }
+    }
+    if LIST_TEMPLATES.contains(&name.as_str()) {
+        return positional.join(", ");
+    }
+    positional.join(" ")
+}
+
+/// Wikitext markup reduced to plain text: comments, references and tags dropped, links
+/// replaced by their labels, templates rendered.
+fn plain_text(wikitext: &str) -> String {
+    let comment = Regex::new(r"(?s)<!--.*?-->").expect("valid regex");
+    let reference = Regex::new(r"(?is)<ref[^>/]*/>|<ref[^>]*>.*?</ref>").expect("valid regex");
+    let br = Regex::new(r"(?i)<br\s*/?>").expect("valid regex");
+    let tag = Regex::new(r"<[^>]+>").expect("valid regex");
+    let template = Regex::new(r"\{\{([^{}]*)\}\}").expect("valid regex");
+    let link = Regex::new(r"\[\[(?:[^\]|]*\|)?([^\]|]*)\]\]").expect("valid regex");
+    let external = Regex::new(r"\[https?://\S+(?:\s+([^\]]*))?\]").expect("valid regex");
+
+    let mut s = comment.replace_all(wikitext, "").into_owned();
+    s = reference.replace_all(&s, "").into_owned();
+    s = br.replace_all(&s, ", ").into_owned();
+    s = tag.replace_all(&s, "").into_owned();
+    // Links first: their `|` would otherwise split template parameters.
+    s = link.replace_all(&s, "$1").into_owned();
+    // Innermost templates first, so nested ones render inside their parents.
+    for _ in 0..10 {
+        let next = template
+            .replace_all(&s, |c: &Captures| render_template(&c[1]))
+            .into_owned();
+        if next == s {
+            break;
+        }
+        s = next;
+    }
+    s = external.replace_all(&s, "$1").into_owned();
+    s = s.replace("'''", "").replace("''", "");
+    let items: Vec<&str> = s
+        .lines()
+        .map(|l| l.trim().trim_start_matches(['*', '#']).trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+    let s = decode_entities(&items.join(", "));
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Split at `|` outside `[[links]]` and `{{templates}}`.
+fn split_params(body: &str) -> Vec<&str> {
+    let bytes = body.as_bytes();
+    let mut parts = Vec::new();
+    let (mut depth, mut start, mut i) = (0i32, 0, 0);
+    while i < bytes.len() {
+        match bytes.get(i..i + 2) {
+            Some(b"[[") | Some(b"{{") => {
+                depth += 1;
+                i += 2;
+                continue;
+            }
+            Some(b"]]") | Some(b"}}") => {
+                depth -= 1;
+                i += 2;
+                continue;
+            }
+            _ => {}
+        }
+        if bytes[i] == b'|' && depth == 0 {
+            parts.push(&body[start..i]);
+            start = i + 1;
+        }
+        i += 1;
+    }
+    parts.push(&body[start..]);
+    parts
+}
+
+/// (label, value) pairs of the article's infobox, media and layout parameters left out.
+fn infobox_facts(wikitext: &str) -> Vec<(String, String)> {
+    let Some(body) = infobox_body(wikitext) else {
+        return Vec::new();
+    };
+    split_params(body)
+        .into_iter()
+        .skip(1)
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            let key = key.trim().to_ascii_lowercase();
+            let skipped = key.split(['_', ' ']).any(|w| {
+                SKIPPED_FACT_KEYS.contains(&w.trim_end_matches(|c: char| c.is_ascii_digit()))
+            });
+            if key.is_empty() || skipped {
+                return None;
+            }
+            let value = plain_text(value);
+            if value.is_empty() {
+                return None;
+            }
+            let value = if value.chars().count() > MAX_FACT_CHARS {
+                let cut: String = value.chars().take(MAX_FACT_CHARS).collect();
+                format!("{cut}…")
+            } else {
+                value
+            };
+            let mut label = key.replace('_', " ");
+            if let Some(first) = label.get(..1) {
+                label = first.to_uppercase() + &label[1..];
+            }
+            Some((label, value))
+        })
+        .take(MAX_FACTS)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+/// The REST summary of an article.
+struct Summary {
+    title: String,
+    description: Option<String>,
+    extract: String,
+    url: Option<String>,
+    disambiguation: bool,
+}
+
+pub struct WikipediaTool {
+    client: Client,
+    site: String,
+}
+
+impl WikipediaTool {
+    pub fn new(client: Client) -> Self {
+        Self::with_site(client, DEFAULT_SITE)
+    }
+
+    /// Use another MediaWiki site with the same APIs; `{lang}` in `site` is replaced by the
+    /// language code.
+    pub fn with_site(client: Client, site: &str) -> Self {
+        Self {
+            client,
+            site: site.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn base(&self, lang: &str) -> String {
+        self.site.replace("{lang}", lang)
+    }
+
+    async fn get_json(&self, url: Url) -> Result<Option<Value>, String> {
+        let res = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("Wikipedia request failed: {e}"))?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !res.status().is_success() {
+            return Err(format!("Wikipedia error: HTTP {}", res.status()));
+        }
+        res.json()
+            .await
+            .map(Some)
+            .map_err(|e| format!("Wikipedia response unreadable: {e}"))
+    }
+
+    async fn api(&self, lang: &str, params: &[(&str, &str)]) -> Result<Value, String> {
+        let mut query = vec![("format", "json"), ("formatversion", "2")];
+        query.extend_from_slice(params);
+        let url = Url::parse_with_params(&format!("{}/w/api.php", self.base(lang)), &query)
+            .map_err(|e| e.to_string())?;
+        Ok(self.get_json(url).await?.unwrap_or(Value::Null))
+    }
+
+    /// None when there is no article with this title.
+    async fn summary(&self, lang: &str, title: &str) -> Result<Option<Summary>, String> {
+        let mut url = Url::parse(&format!("{}/api/rest_v1/page/summary/", self.base(lang)))
+            .map_err(|e| e.to_string())?;
+        url.path_segments_mut()
+            .map_err(|_| "bad Wikipedia URL".to_string())?
+            .pop_if_empty()
+            .push(&title.replace(' ', "_"));
+        let Some(body) = self.get_json(url).await? else {
+            return Ok(None);
+        };
+        let text = |key: &str| body.get(key).and_then(Value::as_str).map(String::from);
+        Ok(Some(Summary {
+            title: text("title").unwrap_or_else(|| title.to_string()),
+            description: text("description"),
+            extract: text("extract").unwrap_or_default(),
+            url: body
+                .pointer("/content_urls/desktop/page")
+                .and_then(Value::as_str)
+                .map(String::from),
+            disambiguation: text("type").as_deref() == Some("disambiguation"),
+        }))
+    }
+
+    async fn search(&self, lang: &str, query: &str) -> Result<Vec<String>, String> {
+        let limit = SEARCH_RESULTS.to_string();
+        let body = self
+            .api(
+                lang,
+                &[
+                    ("action", "query"),
+                    ("list", "search"),
+                    ("srsearch", query),
+                    ("srlimit", &limit),
+                ],
+            )
+            .await?;
+        Ok(body
+            .pointer("/query/search")
+            .and_then(Value::as_array)
+            .map(|hits| {
+                hits.iter()
+                    .filter_map(|h| h.get("title").and_then(Value::as_str).map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// The plain-text lead section (all paragraphs before the first heading).
+    async fn lead(&self, lang: &str, title: &str) -> Result<Option<String>, String> {
+        let body = self
+            .api(
+                lang,
+                &[
+                    ("action", "query"),
+                    ("prop", "extracts"),
+                    ("exintro", "1"),
+                    ("explaintext", "1"),
+                    ("redirects", "1"),
+                    ("titles", title),
+                ],
+            )
+            .await?;
+        Ok(body
+            .pointer("/query/pages/0/extract")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from))
+    }
+
+    async fn facts(&self, lang: &str, title: &str) -> Result<Vec<(String, String)>, String> {
+        let body = self
+            .api(
+                lang,
+                &[
+                    ("action", "parse"),
+                    ("prop", "wikitext"),
+                    ("section", "0"),
+                    ("redirects", "1"),
+                    ("page", title),
+                ],
+            )
+            .await?;
+        Ok(body
+            .pointer("/parse/wikitext")
+            .and_then(Value::as_str)
+            .map(infobox_facts)
+            .unwrap_or_default())
+    }
+
+    async fn run(&self, args: &Value) -> Result<String, String> {
+        let query = args
+            .get("query")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .ok_or("missing 'query' (article title or search terms)")?;
+        let lang = args
+            .get("lang")
+            .and_then(Value::as_str)
+            .map(|l| l.trim().to_ascii_lowercase())
+            .unwrap_or_else(|| DEFAULT_LANG.to_string());
+        if !(2..=12).contains(&lang.len())
+            || !lang.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+        {
+            return Err(format!("'{lang}' is not a Wikipedia language code"));
+        }
+
+        let mut others = Vec::new();
+        let summary = match self.summary(&lang, query).await? {
+            Some(summary) => summary,
+            None => {
+                let mut hits = self.search(&lang, query).await?.into_iter();
+                let Some(best) = hits.next() else {
+                    return Ok(format!("No Wikipedia article found for \"{query}\"."));
+                };
+                others = hits.collect();
+                self.summary(&lang, &best)
+                    .await?
+                    .ok_or_else(|| format!("Wikipedia has no summary for \"{best}\""))?
+            }
+        };
+
+        if summary.disambiguation {
+            let hits = self.search(&lang, query).await?;
+            let mut out = format!(
+                "\"{}\" is ambiguous on Wikipedia. {}\n",
+                summary.title,
+                summary.extract.trim()
+            );
+            if !hits.is_empty() {
+                out.push_str("Top matches:\n");
+                for hit in hits {
+                    out.push_str(&format!("- {hit}\n"));
+                }
+            }
+            out.push_str("Ask again with a more specific title.");
+            return Ok(out);
+        }
+
+        let (lead, facts) = tokio::join!(
+            self.lead(&lang, &summary.title),
+            self.facts(&lang, &summary.title)
+        );
+        let lead = lead.ok().flatten().unwrap_or(summary.extract);
+        let lead = if lead.chars().count() > MAX_EXTRACT_CHARS {
+            let cut: String = lead.chars().take(MAX_EXTRACT_CHARS).collect();
+            format!("{cut}…")
+        } else {
+            lead
+        };
+
+        let mut out = format!("# {}\n", summary.title);
+        if let Some(description) = summary.description {
+            out.push_str(&format!("{description}\n"));
+        }
+        if let Some(url) = summary.url {
+            out.push_str(&format!("{url}\n"));
+        }
+        out.push_str(&format!("\n{lead}\n"));
+        let facts = facts.unwrap_or_default();
+        if !facts.is_empty() {
+            out.push_str("\nFacts:\n");
+            for (label, value) in facts {
+                out.push_str(&format!("- {label}: {value}\n"));
+            }
+        }
+        if !others.is_empty() {
+            out.push_str(&format!("\nOther matches: {}\n", others.join(", ")));
+        }
+        Ok(out.trim_end().to_string())
+    }
+}
+
+impl Tool for WikipediaTool {
+    fn name(&self) -> &str {
+        "wikipedia"
+    }
+
+    fn description(&self) -> &str {
+        "Look up a Wikipedia article: its lead section and infobox facts (dates, places, \
+         numbers). Faster and cleaner than web_search + web_fetch for encyclopedic questions. \
+         Give a title or search terms; lang picks the Wikipedia edition (default en)."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "description": "Article title or search terms, e.g. \"Ada Lovelace\"" },
+                "lang": { "type": "string", "description": "Language code of the Wikipedia edition, e.g. en, de, fr (default en)" }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(&'a self, _ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::tools::web::web_client;
+
+    const WIKITEXT: &str = "{{Short description|English mathematician}}\n\
+        {{Infobox person\n\
+        | name = Ada Lovelace\n\
+        | image = Ada Lovelace portrait.jpg\n\
+        | caption = Portrait, 1840\n\
+        | birth_name = Augusta Ada Byron\n\
+        | birth_date = {{birth date|1815|12|10|df=y}}\n\
+        | birth_place = [[London]], England<ref>{{cite web|url=x}}</ref>\n\
+        | spouse = {{marriage|[[William King-Noel, 1st Earl of Lovelace|William King]]|1835}}\n\
+        | known_for = {{hlist|[[Analytical Engine|Mathematics]]|'''Computing'''}}\n\
+        | relatives = <!-- none listed -->\n\
+        | image_size = 220px | altitude = 12 m\n\
+        }}\n\
+        '''Augusta Ada King, Countess of Lovelace''' was...";
+
+    fn ctx() -> ToolCtx {
+        ToolCtx::for_tests(PathBuf::from("/tmp"))
+    }
+
+    fn summary(title: &str, kind: &str, extract: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "type": kind,
+            "title": title,
+            "description": "English mathematician (1815–1852)",
+            "extract": extract,
+            "content_urls": { "desktop": { "page": format!("https://en.wikipedia.org/wiki/{}", title.replace(' ', "_")) } }
+        }))
+    }
+
+    async fn server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/rest_v1/page/summary/Ada_Lovelace"))
+            .respond_with(summary("Ada Lovelace", "standard", "Short extract."))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/rest_v1/page/summary/Mercury"))
+            .respond_with(summary(
+                "Mercury",
+                "disambiguation",
+                "Mercury may refer to:",
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/w/api.php"))
+            .and(query_param("prop", "extracts"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "query": { "pages": [{ "title": "Ada Lovelace", "extract": "Ada was a mathematician.\nShe wrote the first program." }] }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/w/api.php"))
+            .and(query_param("action", "parse"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "parse": { "title": "Ada Lovelace", "wikitext": WIKITEXT }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/w/api.php"))
+            .and(query_param("list", "search"))
+            .and(query_param("srsearch", "first computer programmer"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "query": { "search": [{ "title": "Ada Lovelace" }, { "title": "Charles Babbage" }] }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/w/api.php"))
+            .and(query_param("list", "search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "query": { "search": [{ "title": "Mercury (planet)" }, { "title": "Mercury (element)" }] }
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[test]
+    fn infobox_facts_are_plain_text() {
+        assert_eq!(
+            infobox_facts(WIKITEXT),
+            [
+                ("Name".to_string(), "Ada Lovelace".to_string()),
+                ("Birth name".to_string(), "Augusta Ada Byron".to_string()),
+                ("Birth date".to_string(), "1815-12-10".to_string()),
+                ("Birth place".to_string(), "London, England".to_string()),
+                ("Spouse".to_string(), "William King 1835".to_string()),
+                (
+                    "Known for".to_string(),
+                    "Mathematics, Computing".to_string()
+                ),
+                ("Altitude".to_string(), "12 m".to_string()),
+            ]
+        );
+        assert!(infobox_facts("no infobox here").is_empty());
+        assert_eq!(plain_text("a&nbsp;b<br/>c\n* d\n* e"), "a b, c, d, e");
+    }
+
+    #[tokio::test]
+    async fn article_with_lead_and_facts() {
+        let server = server().await;
+        let tool = WikipediaTool::with_site(web_client().unwrap(), &server.uri());
+        let res = tool
+            .execute(&ctx(), &json!({"query": "Ada Lovelace"}))
+            .await;
+        assert!(!res.is_error, "{}", res.for_llm);
+        assert_eq!(
+            res.for_llm,
+            "# Ada Lovelace\n\
+             English mathematician (1815–1852)\n\
+             https://en.wikipedia.org/wiki/Ada_Lovelace\n\
+             \n\
+             Ada was a mathematician.\nShe wrote the first program.\n\
+             \n\
+             Facts:\n\
+             - Name: Ada Lovelace\n\
+             - Birth name: Augusta Ada Byron\n\
+             - Birth date: 1815-12-10\n\
+             - Birth place: London, England\n\
+             - Spouse: William King 1835\n\
+             - Known for: Mathematics, Computing\n\
+             - Altitude: 12 m"
+        );
+    }
+
+    #[tokio::test]
+    async fn search_fallback_and_disambiguation() {
+        let server = server().await;
+        let tool = WikipediaTool::with_site(web_client().unwrap(), &server.uri());
+        let res = tool
+            .execute(&ctx(), &json!({"query": "first computer programmer"}))
+            .await;
+        assert!(res.for_llm.starts_with("# Ada Lovelace\n"));
+        assert!(res.for_llm.ends_with("\nOther matches: Charles Babbage"));
+
+        let res = tool.execute(&ctx(), &json!({"query": "Mercury"})).await;
+        assert_eq!(
+            res.for_llm,
+            "\"Mercury\" is ambiguous on Wikipedia. Mercury may refer to:\n\
+             Top matches:\n\
+             - Mercury (planet)\n\
+             - Mercury (element)\n\
+             Ask again with a more specific title."
+        );
+
+        let res = tool
+            .execute(&ctx(), &json!({"query": "x", "lang": "en/../"}))
+            .await;
+        assert!(res.is_error);
+    }
+}
@@ -0,0 +1,569 @@
+//! `workout` tool: sets, reps and weights in BrainDb (`workout_sets`), so "what did I squat
+//! last Monday" and "how is my bench going" have exact answers. Logged sets are also appended
+//! to the day's note in the vault (`[tools.workout] dir`, default `Workouts/<YYYY-MM-DD>.md`).
+
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde_json::Value;
+
+use crate::config::WorkoutConfig;
+use crate::memory::db::{BrainDb, WorkoutSet, run_blocking};
+use crate::tools::context::ToolCtx;
+use crate::tools::daily_note::parse_day;
+use crate::tools::file::resolve_path;
+use crate::tools::registry::{BoxFuture, Tool};
+use crate::tools::result::ToolResult;
+
+const DEFAULT_DIR: &str = "Workouts";
+const DEFAULT_PROGRESS_DAYS: i64 = 90;
+const MAX_PROGRESS_DAYS: i64 = 730;
+const MAX_REPS: u64 = 1_000;
+/// Identical sets one `sets` item may stand for.
+const MAX_COUNT: u64 = 20;
+const LB_PER_KG: f64 = 2.204_622_621_8;
+
+fn day_str(day: NaiveDate) -> String {
+    day.format("%Y-%m-%d").to_string()
+}
+
+fn parse_ymd(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+}
+
+/// "kg" or "lb".
+fn parse_unit(s: &str) -> Option<&'static str> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "" | "kg" | "kgs" => Some("kg"),
+        "lb" | "lbs" => Some("lb"),
+        _ => None,
+    }
+}
+
+fn convert(weight: f64, from: &str, to: &str) -> f64 {
+    match (from, to) {
+        ("kg", "lb") => weight * LB_PER_KG,
+        ("lb", "kg") => weight / LB_PER_KG,
+        _ => weight,
+    }
+}
+
+/// A weight without needless decimals: 100, 102.5.
+fn fmt_weight(w: f64) -> String {
+    let rounded = (w * 10.0).round() / 10.0;
+    format!("{rounded}")
+}
+
+/// Sets of one exercise, runs of identical sets grouped: "3×5 @ 100 kg, 3 @ 110 kg".
+fn describe_sets(sets: &[&WorkoutSet]) -> String {
+    let mut groups: Vec<(usize, &WorkoutSet)> = Vec::new();
+    for &set in sets {
+        match groups.last_mut() {
+            Some((n, last))
+                if last.reps == set.reps && last.weight == set.weight && last.unit == set.unit =>
+            {
+                *n += 1
+            }
+            _ => groups.push((1, set)),
+        }
+    }
+    groups
+        .iter()
+        .map(|(n, s)| {
+            let reps = if *n > 1 {
+                format!("{n}×{}", s.reps)
+            } else {
+                s.reps.to_string()
+            };
+            match s.weight {
+                Some(w) => format!("{reps} @ {} {}", fmt_weight(w), s.unit),
+                None => reps,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// "- Squat: 3×5 @ 100 kg" per exercise, in the order first logged.
+fn describe_day(sets: &[WorkoutSet]) -> Vec<String> {
+    let mut exercises: Vec<&str> = Vec::new();
+    for s in sets {
+        if !exercises
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(&s.exercise))
+        {
+            exercises.push(&s.exercise);
+        }
+    }
+    exercises
+        .iter()
+        .map(|e| {
+            let of: Vec<&WorkoutSet> = sets
+                .iter()
+                .filter(|s| s.exercise.eq_ignore_ascii_case(e))
+                .collect();
+            format!("- {e}: {}", describe_sets(&of))
+        })
+        .collect()
+}
+
+/// Estimated one-rep max (Epley).
+fn e1rm(weight: f64, reps: u32) -> f64 {
+    if reps <= 1 {
+        weight
+    } else {
+        weight * (1.0 + reps as f64 / 30.0)
+    }
+}
+
+/// One line per session of `sets` (one exercise), weights in `unit`, plus the change in
+/// estimated 1RM (or best reps for bodyweight work) from the first session to the last.
+fn progress(sets: &[WorkoutSet], unit: &str) -> Vec<String> {
+    let mut days: Vec<&str> = sets.iter().map(|s| s.day.as_str()).collect();
+    days.dedup();
+    let mut lines = Vec::new();
+    let mut first_last: Option<(f64, f64)> = None;
+    let weighted = sets.iter().any(|s| s.weight.is_some());
+    for day in days {
+        let session: Vec<&WorkoutSet> = sets.iter().filter(|s| s.day == day).collect();
+        let label = parse_ymd(day).map_or(day.to_string(), |d| d.format("%a %-d %b").to_string());
+        let measure = if weighted {
+            let loaded: Vec<(f64, u32)> = session
+                .iter()
+                .filter_map(|s| s.weight.map(|w| (convert(w, &s.unit, unit), s.reps)))
+                .collect();
+            let Some(&(top, top_reps)) = loaded
+                .iter()
+                .max_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+            else {
+                continue;
+            };
+            let best = loaded.iter().map(|&(w, r)| e1rm(w, r)).fold(0.0, f64::max);
+            let volume: f64 = loaded.iter().map(|&(w, r)| w * r as f64).sum();
+            lines.push(format!(
+                "- {label}: top {} {unit} × {top_reps}, e1RM {} {unit}, volume {} {unit}",
+                fmt_weight(top),
+                best.round(),
+                volume.round()
+            ));
+            best
+        } else {
+            let reps: u32 = session.iter().map(|s| s.reps).sum();
+            let best = session.iter().map(|s| s.reps).max().unwrap_or(0);
+            lines.push(format!(
+                "- {label}: {} sets, {reps} reps (best set {best})",
+                session.len()
+            ));
+            best as f64
+        };
+        first_last = Some(first_last.map_or((measure, measure), |(f, _)| (f, measure)));
+    }
+    if let Some((first, last)) = first_last.filter(|_| lines.len() > 1) {
+        let (what, suffix) = if weighted {
+            ("e1RM", format!(" {unit}"))
+        } else {
+            ("best set", " reps".to_string())
+        };
+        lines.push(format!(
+            "Change: {what} {} → {}{suffix} ({:+})",
+            first.round(),
+            last.round(),
+            (last - first).round()
+        ));
+    }
+    lines
+}
+
+/// The sets one `log` call stands for.
+fn parse_sets(
+    args: &Value,
+    day: NaiveDate,
+    exercise: &str,
+    unit: &str,
+) -> Result<Vec<WorkoutSet>, String> {
+    let items = args
+        .get("sets")
+        .and_then(Value::as_array)
+        .filter(|a| !a.is_empty())
+        .ok_or("log needs 'sets', e.g. [{\"reps\": 5, \"weight\": 100, \"count\": 3}]")?;
+    let mut sets = Vec::new();
+    for item in items {
+        let reps = item
+            .get("reps")
+            .and_then(Value::as_u64)
+            .filter(|r| (1..=MAX_REPS).contains(r))
+            .ok_or("each set needs 'reps' (a positive whole number)")?;
+        let weight = match item.get("weight") {
+            None | Some(Value::Null) => None,
+            Some(w) => Some(
+                w.as_f64()
+                    .filter(|w| w.is_finite() && *w >= 0.0)
+                    .ok_or("'weight' must be a number (omit it for bodyweight)")?,
+            ),
+        };
+        let count = item
+            .get("count")
+            .and_then(Value::as_u64)
+            .unwrap_or(1)
+            .clamp(1, MAX_COUNT);
+        for _ in 0..count {
+            sets.push(WorkoutSet {
+                day: day_str(day),
+                exercise: exercise.to_string(),
+                reps: reps as u32,
+                weight,
+                unit: unit.to_string(),
+            });
+        }
+    }
+    Ok(sets)
+}
+
+pub struct WorkoutTool {
+    db: Arc<BrainDb>,
+    dir: String,
+    timezone: Tz,
+}
+
+impl WorkoutTool {
+    pub fn new(db: Arc<BrainDb>, config: Option<&WorkoutConfig>, timezone: Tz) -> Self {
+        Self {
+            db,
+            dir: config
+                .and_then(|c| c.dir.as_deref())
+                .map(|d| d.trim_matches('/').to_string())
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| DEFAULT_DIR.to_string()),
+            timezone,
+        }
+    }
+
+    /// "no sets of 'x' logged (known: Squat, Bench press)".
+    async fn unknown(&self, exercise: &str) -> String {
+        let known = run_blocking(&self.db, "workout log", |db| db.workout_exercises())
+            .await
+            .unwrap_or_default();
+        if known.is_empty() {
+            return "no workouts logged yet".to_string();
+        }
+        let names: Vec<&str> = known.iter().take(20).map(|(e, _)| e.as_str()).collect();
+        format!(
+            "no sets of '{exercise}' logged (known exercises: {})",
+            names.join(", ")
+        )
+    }
+
+    /// Append `lines` to the day's note, creating it with a heading.
+    async fn append_note(
+        &self,
+        ctx: &ToolCtx,
+        day: NaiveDate,
+        lines: &[String],
+    ) -> Result<String, String> {
+        let rel = format!("{}/{}.md", self.dir, day_str(day));
+        let path = resolve_path(&rel, &ctx.workspace, ctx.restrict_to_workspace).await?;
+        let mut content = match tokio::fs::read_to_string(&path).await {
+            Ok(c) => c,
+            Err(_) => format!("# Workout, {}\n\n", day.format("%a %-d %b %Y")),
+        };
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        for line in lines {
+            content.push_str(line);
+            content.push('\n');
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("creating {} failed: {e}", self.dir))?;
+        }
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| format!("writing {rel} failed: {e}"))?;
+        Ok(rel)
+    }
+
+    async fn run(&self, ctx: &ToolCtx, args: &Value) -> Result<String, String> {
+        let action = args.get("action").and_then(Value::as_str).unwrap_or("");
+        let today = Utc::now().with_timezone(&self.timezone).date_naive();
+        let date_arg = args.get("date").and_then(Value::as_str).unwrap_or("today");
+        let day = parse_day(date_arg, today)
+            .ok_or_else(|| format!("invalid date '{date_arg}' (today, yesterday or YYYY-MM-DD)"))?;
+        let exercise = args
+            .get("exercise")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(str::to_string);
+
+        match action {
+            "log" => {
+                let exercise = exercise.ok_or("log needs 'exercise'")?;
+                let unit_arg = args.get("unit").and_then(Value::as_str).unwrap_or("kg");
+                let unit = parse_unit(unit_arg)
+                    .ok_or_else(|| format!("unknown unit '{unit_arg}' (kg or lb)"))?;
+                let sets = parse_sets(args, day, &exercise, unit)?;
+                let lines = describe_day(&sets);
+                let n = sets.len();
+                run_blocking(&self.db, "workout log", move |db| {
+                    db.log_workout_sets(&sets)
+                })
+                .await?;
+                let rel = self.append_note(ctx, day, &lines).await?;
+                Ok(format!(
+                    "Logged {n} set{} for {} and added to {rel}:\n{}",
+                    if n == 1 { "" } else { "s" },
+                    day.format("%a %-d %b"),
+                    lines.join("\n")
+                ))
+            }
+            "last" => {
+                let (ex, on) = (exercise.clone(), day_str(day));
+                let Some(found) = run_blocking(&self.db, "workout log", move |db| {
+                    db.last_workout_day(ex.as_deref(), &on)
+                })
+                .await?
+                else {
+                    return Err(match &exercise {
+                        Some(e) => self.unknown(e).await,
+                        None => "no workouts logged on or before that day".to_string(),
+                    });
+                };
+                let (ex, d) = (exercise.clone(), found.clone());
+                let sets = run_blocking(&self.db, "workout log", move |db| {
+                    db.workout_sets(ex.as_deref(), &d, &d)
+                })
+                .await?;
+                let label = parse_ymd(&found)
+                    .map_or(found.clone(), |d| d.format("%a %-d %b %Y").to_string());
+                Ok(format!("{label}:\n{}", describe_day(&sets).join("\n")))
+            }
+            "progress" => {
+                let exercise = exercise.ok_or("progress needs 'exercise'")?;
+                let days = args
+                    .get("days")
+                    .and_then(Value::as_i64)
+                    .unwrap_or(DEFAULT_PROGRESS_DAYS)
+                    .clamp(1, MAX_PROGRESS_DAYS);
+                let from = day_str(day - Duration::days(days - 1));
+                let (ex, to) = (exercise.clone(), day_str(day));
+                let sets = run_blocking(&self.db, "workout log", move |db| {
+                    db.workout_sets(Some(&ex), &from, &to)
+                })
+                .await?;
+                let Some(latest) = sets.last() else {
+                    let known = self.unknown(&exercise).await;
+                    return Err(format!("{known}, in the last {days} days"));
+                };
+                let unit = latest.unit.clone();
+                let lines = progress(&sets, &unit);
+                let sessions = lines.iter().filter(|l| l.starts_with("- ")).count();
+                Ok(format!(
+                    "{}, last {days} days ({sessions} session{}):\n{}",
+                    latest.exercise,
+                    if sessions == 1 { "" } else { "s" },
+                    lines.join("\n")
+                ))
+            }
+            "" => Err("missing 'action' (log, last or progress)".into()),
+            other => Err(format!("unknown action '{other}' (log, last or progress)")),
+        }
+    }
+}
+
+impl Tool for WorkoutTool {
+    fn name(&self) -> &str {
+        "workout"
+    }
+
+    fn description(&self) -> &str {
+        "Structured workout log. log: record the sets of one exercise (also added to the \
+         day's Workouts note). last: the sets done on a day — the latest session on or \
+         before 'date', e.g. \"what did I squat last Monday\". progress: one exercise's \
+         sessions over time with top set, estimated 1RM and volume. Prefer this to searching \
+         Workouts notes for exact numbers."
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": { "type": "string", "enum": ["log", "last", "progress"] },
+                "exercise": { "type": "string", "description": "Exercise name, kept consistent, e.g. \"Squat\" (required for log and progress)" },
+                "sets": {
+                    "type": "array",
+                    "description": "Sets to log; count repeats identical sets",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "reps": { "type": "integer" },
+                            "weight": { "type": "number", "description": "Omit for bodyweight" },
+                            "count": { "type": "integer", "description": "Number of identical sets (default 1)" }
+                        },
+                        "required": ["reps"]
+                    }
+                },
+                "unit": { "type": "string", "enum": ["kg", "lb"], "description": "Weight unit (log, default kg)" },
+                "date": { "type": "string", "description": "today (default), yesterday or YYYY-MM-DD; for last and progress the latest day to consider" },
+                "days": { "type": "integer", "description": "Days of history for progress (default 90)" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn execute<'a>(&'a self, ctx: &'a ToolCtx, args: &'a Value) -> BoxFuture<'a, ToolResult> {
+        Box::pin(async move {
+            match self.run(ctx, args).await {
+                Ok(out) => ToolResult::ok(out),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn ctx(workspace: &Path) -> ToolCtx {
+        ToolCtx::for_tests(workspace)
+    }
+
+    fn set(day: &str, reps: u32, weight: Option<f64>, unit: &str) -> WorkoutSet {
+        WorkoutSet {
+            day: day.to_string(),
+            exercise: "Squat".to_string(),
+            reps,
+            weight,
+            unit: unit.to_string(),
+        }
+    }
+
+    #[test]
+    fn sets_are_grouped() {
+        let sets = [
+            set("2026-10-12", 5, Some(100.0), "kg"),
+            set("2026-10-12", 5, Some(100.0), "kg"),
+            set("2026-10-12", 3, Some(102.5), "kg"),
+            set("2026-10-12", 8, None, "kg"),
+        ];
+        let refs: Vec<&WorkoutSet> = sets.iter().collect();
+        assert_eq!(describe_sets(&refs), "2×5 @ 100 kg, 3 @ 102.5 kg, 8");
+    }
+
+    #[test]
+    fn progress_converts_units_and_reports_change() {
+        let sets = [
+            set("2026-10-05", 5, Some(220.0), "lb"),
+            set("2026-10-12", 5, Some(100.0), "kg"),
+            set("2026-10-12", 3, Some(110.0), "kg"),
+        ];
+        assert_eq!(
+            progress(&sets, "kg"),
+            vec![
+                "- Mon 5 Oct: top 99.8 kg × 5, e1RM 116 kg, volume 499 kg",
+                "- Mon 12 Oct: top 110 kg × 3, e1RM 121 kg, volume 830 kg",
+                "Change: e1RM 116 → 121 kg (+5)",
+            ]
+        );
+        let pullups = [
+            set("2026-10-05", 6, None, "kg"),
+            set("2026-10-12", 8, None, "kg"),
+        ];
+        assert_eq!(
+            progress(&pullups, "kg").last().unwrap(),
+            "Change: best set 6 → 8 reps (+2)"
+        );
+    }
+
+    #[tokio::test]
+    async fn log_then_last_and_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(BrainDb::open(dir.path()).unwrap());
+        let tool = WorkoutTool::new(db, None, chrono_tz::Europe::London);
+        let ctx = ctx(dir.path());
+
+        let args = serde_json::json!({
+            "action": "log", "exercise": "Squat", "date": "2026-10-12",
+            "sets": [{"reps": 5, "weight": 100, "count": 3}, {"reps": 3, "weight": 110}]
+        });
+        let res = tool.execute(&ctx, &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Logged 4 sets for Mon 12 Oct and added to Workouts/2026-10-12.md:\n\
+             - Squat: 3×5 @ 100 kg, 3 @ 110 kg"
+        );
+        let args = serde_json::json!({
+            "action": "log", "exercise": "Pull-up", "date": "2026-10-12",
+            "sets": [{"reps": 8, "count": 2}]
+        });
+        tool.execute(&ctx, &args).await;
+        let note = std::fs::read_to_string(dir.path().join("Workouts/2026-10-12.md")).unwrap();
+        assert_eq!(
+            note,
+            "# Workout, Mon 12 Oct 2026\n\n- Squat: 3×5 @ 100 kg, 3 @ 110 kg\n- Pull-up: 2×8\n"
+        );
+
+        let args = serde_json::json!({"action": "last", "exercise": "squat", "date": "2026-10-16"});
+        let res = tool.execute(&ctx, &args).await;
+        assert_eq!(
+            res.for_llm,
+            "Mon 12 Oct 2026:\n- Squat: 3×5 @ 100 kg, 3 @ 110 kg"
+        );
+        let args = serde_json::json!({"action": "last", "date": "2026-10-16"});
+        let res = tool.execute(&ctx, &args).await;
+        assert!(res.for_llm.ends_with("\n- Pull-up: 2×8"), "{}", res.for_llm);
+
+        let args =
+            serde_json::json!({"action": "progress", "exercise": "Squat", "date": "2026-10-16"});
+        let res = tool.execute(&ctx, &args).await;
+        assert!(
+            res.for_llm
+                .starts_with("Squat, last 90 days (1 session):\n- Mon 12 Oct: top 110 kg × 3"),
+            "{}",
+            res.for_llm
+        );
+
+        let args = serde_json::json!({"action": "last", "exercise": "Deadlift"});
+        let res = tool.execute(&ctx, &args).await;
+        assert!(res.is_error);
+        assert!(
+            res.for_llm.contains("known exercises: Squat, Pull-up"),
+            "{}",
+            res.for_llm
+        );
+    }
+
+    #[tokio::test]
+    async fn bad_sets_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(BrainDb::open(dir.path()).unwrap());
+        let tool = WorkoutTool::new(db, None, chrono_tz::Europe::London);
+        let ctx = ctx(dir.path());
+        for (args, err) in [
+            (
+                serde_json::json!({"action": "log", "exercise": "Squat"}),
+                "log needs 'sets'",
+            ),
+            (
+                serde_json::json!({"action": "log", "exercise": "Squat", "sets": [{"reps": 0}]}),
+                "each set needs 'reps'",
+            ),
+            (
+                serde_json::json!({"action": "log", "exercise": "Squat", "unit": "stone", "sets": [{"reps": 5}]}),
+                "unknown unit 'stone'",
+            ),
+        ] {
+            let res = tool.execute(&ctx, &args).await;
+            assert!(res.is_error);
+            assert!(res.for_llm.starts_with(err), "{}", res.for_llm);
+        }
+    }
+}
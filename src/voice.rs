@@ -0,0 +1,294 @@
+//! Voice notes: speech-to-text for incoming voice messages, text-to-speech for replies.
+//!
+//! Talks to an OpenAI-compatible speech API (`/audio/transcriptions`, `/audio/speech`).
+//! Replies are requested as Opus in an OGG container, which Telegram shows as a voice note.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::telegram::multipart_body;
+
+const DEFAULT_API_BASE: &str = "https://api.openai.com/v1";
+const DEFAULT_STT_MODEL: &str = "whisper-1";
+const DEFAULT_TTS_MODEL: &str = "gpt-4o-mini-tts";
+const DEFAULT_TTS_VOICE: &str = "alloy";
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+/// Speech endpoints reject longer input; longer replies are sent as text instead.
+pub const MAX_SPEECH_CHARS: usize = 4096;
+
+/// Voice module errors.
+#[derive(Debug)]
+pub enum VoiceError {
+    Http(String),
+    Parse(String),
+    Io(String),
+}
+
+impl std::fmt::Display for VoiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoiceError::Http(s) => write!(f, "voice http: {}", s),
+            VoiceError::Parse(s) => write!(f, "voice parse: {}", s),
+            VoiceError::Io(s) => write!(f, "voice io: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for VoiceError {}
+
+#[derive(Serialize)]
+struct SpeechRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+    voice: &'a str,
+    response_format: &'a str,
+}
+
+#[derive(Deserialize)]
+struct TranscriptionResponse {
+    #[serde(default)]
+    text: String,
+}
+
+/// Speech API client built from `[voice]`.
+pub struct VoiceClient {
+    client: reqwest::Client,
+    api_base: String,
+    api_key: String,
+    stt_model: String,
+    tts_model: String,
+    tts_voice: String,
+    voice_replies: bool,
+}
+
+impl VoiceClient {
+    /// None when `[voice]` is absent (voice notes are then not understood).
+    pub fn from_config(cfg: &Config) -> Option<Self> {
+        let v = cfg.voice.as_ref()?;
+        let pick = |o: &Option<String>, default: &str| {
+            o.as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .unwrap_or(default)
+                .to_string()
+        };
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .expect("reqwest client");
+        Some(Self {
+            client,
+            api_base: pick(&v.api_base, DEFAULT_API_BASE)
+                .trim_end_matches('/')
+                .to_string(),
+            api_key: v.api_key.clone().unwrap_or_default(),
+            stt_model: pick(&v.stt_model, DEFAULT_STT_MODEL),
+            tts_model: pick(&v.tts_model, DEFAULT_TTS_MODEL),
+            tts_voice: pick(&v.tts_voice, DEFAULT_TTS_VOICE),
+            voice_replies: v.voice_replies.unwrap_or(true),
+        })
+    }
+
+    /// Whether voice notes should be answered with a voice note.
+    pub fn replies_enabled(&self) -> bool {
+        self.voice_replies
+    }
+
+    /// Transcribe an OGG/Opus voice note to text.
+    pub async fn transcribe(&self, audio: &[u8]) -> Result<String, VoiceError> {
+        let url = format!("{}/audio/transcriptions", self.api_base);
+        let (content_type, body) = multipart_body(
+            &[("model", self.stt_model.clone())],
+            "file",
+            "voice.ogg",
+            audio,
+        );
+        let res = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", content_type)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| VoiceError::Http(e.to_string()))?;
+        let status = res.status();
+        let text = res
+            .text()
+            .await
+            .map_err(|e| VoiceError::Http(e.to_string()))?;
+        if !status.is_success() {
+            return Err(VoiceError::Http(format!("{} {}", status, text)));
+        }
+        let parsed: TranscriptionResponse =
+            serde_json::from_str(&text).map_err(|e| VoiceError::Parse(e.to_string()))?;
+        Ok(parsed.text.trim().to_string())
+    }
+
+    /// Synthesize `text` to OGG/Opus bytes.
+    pub async fn synthesize(&self, text: &str) -> Result<Vec<u8>, VoiceError> {
+        self.synthesize_with(text, None, "opus").await
+    }
+
+    /// Synthesize `text` as `format` ("opus", "mp3", ...), optionally in another voice.
+    pub async fn synthesize_with(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        format: &str,
+    ) -> Result<Vec<u8>, VoiceError> {
+        let url = format!("{}/audio/speech", self.api_base);
+        let body = SpeechRequest {
+            model: &self.tts_model,
+            input: text,
+            voice: voice.unwrap_or(&self.tts_voice),
+            response_format: format,
+        };
+        let res = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| VoiceError::Http(e.to_string()))?;
+        let status = res.status();
+        if !status.is_success() {
+            let text = res.text().await.unwrap_or_default();
+            return Err(VoiceError::Http(format!("{} {}", status, text)));
+        }
+        res.bytes()
+            .await
+            .map(|b| b.to_vec())
+            .map_err(|e| VoiceError::Http(e.to_string()))
+    }
+
+    /// Synthesize `text` into a new `reply-<uuid>.ogg` under `dir`; returns its path.
+    pub async fn synthesize_to_file(&self, text: &str, dir: &Path) -> Result<PathBuf, VoiceError> {
+        let audio = self.synthesize(text).await?;
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| VoiceError::Io(format!("{}: {}", dir.display(), e)))?;
+        let path = dir.join(format!("reply-{}.ogg", uuid::Uuid::new_v4()));
+        tokio::fs::write(&path, audio)
+            .await
+            .map_err(|e| VoiceError::Io(format!("{}: {}", path.display(), e)))?;
+        Ok(path)
+    }
+}
+
+/// Split `text` into pieces of at most `max_chars` for separate speech requests,
+/// breaking at paragraphs, then sentences, then spaces.
+pub fn split_for_speech(text: &str, max_chars: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let Some((cut, _)) = rest.char_indices().nth(max_chars) else {
+            parts.push(rest.to_string());
+            break;
+        };
+        let head = &rest[..cut];
+        let split = head
+            .rfind("\n\n")
+            .or_else(|| head.rfind(['.', '!', '?', '\n']).map(|i| i + 1))
+            .or_else(|| head.rfind(' '))
+            .filter(|&i| i > 0)
+            .unwrap_or(cut);
+        parts.push(rest[..split].trim().to_string());
+        rest = rest[split..].trim_start();
+    }
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VoiceConfig;
+    use wiremock::matchers::{body_partial_json, body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(api_base: &str) -> VoiceClient {
+        let cfg = Config {
+            voice: Some(VoiceConfig {
+                api_base: Some(api_base.to_string()),
+                api_key: Some("k".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        VoiceClient::from_config(&cfg).unwrap()
+    }
+
+    #[test]
+    fn absent_section_disables_voice() {
+        assert!(VoiceClient::from_config(&Config::default()).is_none());
+    }
+
+    #[tokio::test]
+    async fn transcribe_posts_multipart_with_model() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/audio/transcriptions"))
+            .and(body_string_contains("whisper-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"text": " hello "})),
+            )
+            .mount(&server)
+            .await;
+        let text = client(&server.uri()).transcribe(b"OggS").await.unwrap();
+        assert_eq!(text, "hello");
+    }
+
+    #[tokio::test]
+    async fn synthesize_requests_opus_and_writes_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/audio/speech"))
+            .and(body_partial_json(
+                serde_json::json!({"input": "hi", "voice": "alloy", "response_format": "opus"}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"OggS-audio".to_vec()))
+            .mount(&server)
+            .await;
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = client(&server.uri())
+            .synthesize_to_file("hi", tmp.path())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"OggS-audio");
+        assert_eq!(path.extension().unwrap(), "ogg");
+    }
+
+    #[test]
+    fn split_for_speech_prefers_sentence_breaks() {
+        assert_eq!(split_for_speech("  short  ", 100), vec!["short"]);
+        assert_eq!(
+            split_for_speech("One two. Three four five.\n\nSix.", 20),
+            vec!["One two.", "Three four five.", "Six."]
+        );
+        assert_eq!(
+            split_for_speech("abcdefghij klm", 5),
+            vec!["abcde", "fghij", "klm"]
+        );
+        assert!(split_for_speech("", 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn synthesize_surfaces_api_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("bad key"))
+            .mount(&server)
+            .await;
+        let err = client(&server.uri()).synthesize("hi").await.unwrap_err();
+        assert!(err.to_string().contains("401"));
+    }
+}
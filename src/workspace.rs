@@ -71,6 +71,12 @@ pub fn daily_note_path(workspace: &Path, yyyymmdd: &str) -> PathBuf {
         .join(format!("{yyyymmdd}.md"))
 }
 
+/// Path to chat exports (Markdown notes in the vault): `workspace/Chats`.
+#[inline]
+pub fn chats_dir(workspace: &Path) -> PathBuf {
+    workspace.join("Chats")
+}
+
 /// Path to AGENT.md in workspace root.
 #[inline]
 pub fn agent_md(workspace: &Path) -> PathBuf {
@@ -89,12 +95,24 @@ pub fn identity_md(workspace: &Path) -> PathBuf {
     workspace.join("IDENTITY.md")
 }
 
+/// Path to the to-do list used by the `todo` tool: `workspace/TODO.md`.
+#[inline]
+pub fn todo_file(workspace: &Path) -> PathBuf {
+    workspace.join("TODO.md")
+}
+
 /// Path to cron jobs file: `workspace/cron/jobs.json`.
 #[inline]
 pub fn cron_jobs_file(workspace: &Path) -> PathBuf {
     workspace.join("cron").join("jobs.json")
 }
 
+/// Path to the vault ignore rules: `workspace/.icrabignore` (gitignore syntax).
+#[inline]
+pub fn icrabignore_file(workspace: &Path) -> PathBuf {
+    workspace.join(".icrabignore")
+}
+
 /// Path to the iCrab data directory: `workspace/.icrab/`.
 /// Contains SQLite database and other runtime state ignored by Git.
 #[inline]
@@ -108,6 +126,42 @@ pub fn brain_db_path(workspace: &Path) -> PathBuf {
     icrab_dir(workspace).join("brain.db")
 }
 
+/// Path to brain.db backups: `workspace/.icrab/backups/`.
+#[inline]
+pub fn backups_dir(workspace: &Path) -> PathBuf {
+    icrab_dir(workspace).join("backups")
+}
+
+/// Path to the outbound message spool: `workspace/.icrab/outbox.jsonl`.
+#[inline]
+pub fn outbox_file(workspace: &Path) -> PathBuf {
+    icrab_dir(workspace).join("outbox.jsonl")
+}
+
+/// Path to rendered charts: `workspace/.icrab/charts/`.
+#[inline]
+pub fn charts_dir(workspace: &Path) -> PathBuf {
+    icrab_dir(workspace).join("charts")
+}
+
+/// Path to synthesized voice replies awaiting upload: `workspace/.icrab/voice/`.
+#[inline]
+pub fn voice_dir(workspace: &Path) -> PathBuf {
+    icrab_dir(workspace).join("voice")
+}
+
+/// Path to LLM request/response debug logs: `workspace/.icrab/llm-log/`.
+#[inline]
+pub fn llm_log_dir(workspace: &Path) -> PathBuf {
+    icrab_dir(workspace).join("llm-log")
+}
+
+/// Path to recorded LLM exchanges for `[llm] replay`: `workspace/.icrab/llm-replay.jsonl`.
+#[inline]
+pub fn llm_replay_file(workspace: &Path) -> PathBuf {
+    icrab_dir(workspace).join("llm-replay.jsonl")
+}
+
 /// Parse "YYYYMMDD" into Date. Returns None if invalid.
 fn parse_yyyymmdd(s: &str) -> Option<NaiveDate> {
     if s.len() != 8 {
@@ -46,6 +46,8 @@ async fn test_agent_basic_flow() {
         channel: Some("telegram".into()),
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     let result = process_message(
@@ -156,6 +158,8 @@ async fn test_agent_tool_use_loop() {
         channel: Some("telegram".into()),
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     let result = process_message(
@@ -217,6 +221,8 @@ async fn test_agent_session_load_on_restart() {
         channel: Some("telegram".into()),
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     let r1 = process_message(
@@ -338,6 +344,8 @@ async fn test_agent_unknown_tool_completes_with_error_in_conversation() {
         channel: Some("telegram".into()),
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     let result = process_message(
@@ -413,6 +421,8 @@ async fn test_agent_invalid_tool_args_completes_with_error_in_conversation() {
         channel: Some("telegram".into()),
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     let result = process_message(
@@ -431,3 +441,279 @@ async fn test_agent_invalid_tool_args_completes_with_error_in_conversation() {
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), "I'll fix the format.");
 }
+
+#[tokio::test]
+async fn test_agent_streaming_forwards_deltas_and_tool_calls() {
+    use icrab::agent::process_message_streaming;
+    use wiremock::matchers::{body_partial_json, method, path};
+
+    let ws = TestWorkspace::new();
+    let mock_llm = MockLlm::new().await;
+    let config = create_test_config(&ws.root, &mock_llm.endpoint());
+    let provider = HttpProvider::from_config(&config).expect("provider");
+    let db = Arc::new(BrainDb::open(&ws.root).unwrap());
+    std::fs::write(ws.root.join("note.txt"), "squats").unwrap();
+
+    let registry = ToolRegistry::new();
+    registry.register(ReadFile);
+
+    // First streamed call: a tool call split across chunks. Second: the answer in pieces.
+    let tool_sse = concat!(
+        "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"type\":\"function\",\"function\":{\"name\":\"read_file\",\"arguments\":\"{\\\"path\\\":\"}}]}}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"\\\"note.txt\\\"}\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n",
+        "data: [DONE]\n\n",
+    );
+    let answer_sse = concat!(
+        "data: {\"choices\":[{\"delta\":{\"content\":\"Leg \"}}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{\"content\":\"day\"},\"finish_reason\":\"stop\"}]}\n\n",
+        "data: [DONE]\n\n",
+    );
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(json!({"stream": true})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(tool_sse),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_llm.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(json!({"stream": true})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(answer_sse),
+        )
+        .mount(&mock_llm.server)
+        .await;
+
+    let ctx = ToolCtx {
+        workspace: ws.root.clone(),
+        restrict_to_workspace: true,
+        chat_id: Some(123),
+        channel: Some("telegram".into()),
+        outbound_tx: None,
+        delivered: Default::default(),
+        location: None,
+        thread_id: None,
+    };
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let result = process_message_streaming(
+        &provider,
+        &registry,
+        &ws.root,
+        "gpt-4-test",
+        "Europe/London",
+        "chat_stream",
+        "What's in my note?",
+        &ctx,
+        &db,
+        &icrab::agent::TurnOptions::default(),
+        Some(&tx),
+    )
+    .await
+    .expect("streaming turn");
+    assert_eq!(result, "Leg day");
+
+    drop(tx);
+    let mut deltas = Vec::new();
+    while let Some(d) = rx.recv().await {
+        deltas.push(d);
+    }
+    assert_eq!(deltas, ["Leg ", "day"]);
+
+    // The assembled tool call ran: its result was sent back in the second request.
+    let requests = mock_llm.server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let second: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    let msgs = second["messages"].as_array().unwrap();
+    let tool_msg = msgs.iter().find(|m| m["role"] == "tool").unwrap();
+    assert_eq!(tool_msg["tool_call_id"], "call_1");
+    assert!(tool_msg["content"].as_str().unwrap().contains("squats"));
+}
+
+#[tokio::test]
+async fn test_agent_runs_read_only_tool_calls_concurrently() {
+    use icrab::agent::run_agent_loop;
+    use icrab::llm::{
+        GenParams, LlmError, LlmProvider, LlmResponse, Message, Role, ToolCall, ToolCallFunction,
+        ToolDef,
+    };
+    use icrab::tools::registry::{BoxFuture, Tool};
+    use icrab::tools::result::ToolResult;
+    use std::time::{Duration, Instant};
+
+    /// Read-only tool that takes 300ms and echoes its `id` argument.
+    struct SlowRead;
+
+    impl Tool for SlowRead {
+        fn name(&self) -> &str {
+            "slow_read"
+        }
+        fn description(&self) -> &str {
+            "slow"
+        }
+        fn parameters(&self) -> serde_json::Value {
+            json!({"type": "object", "properties": {"id": {"type": "string"}}})
+        }
+        fn read_only(&self) -> bool {
+            true
+        }
+        fn execute<'a>(
+            &'a self,
+            _ctx: &'a ToolCtx,
+            args: &'a serde_json::Value,
+        ) -> BoxFuture<'a, ToolResult> {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                ToolResult::ok(format!("r:{}", args["id"].as_str().unwrap_or("")))
+            })
+        }
+    }
+
+    /// First reply: three slow_read calls. Second: the tool results it was sent, in order.
+    struct Scripted;
+
+    impl LlmProvider for Scripted {
+        fn chat_with_params<'a>(
+            &'a self,
+            messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            let results: Vec<String> = messages
+                .iter()
+                .filter(|m| m.role == Role::Tool)
+                .map(|m| format!("{}={}", m.tool_call_id.as_deref().unwrap(), m.content))
+                .collect();
+            let tool_calls = if results.is_empty() {
+                ["a", "b", "c"]
+                    .iter()
+                    .map(|id| ToolCall {
+                        id: format!("call_{id}"),
+                        type_: "function".into(),
+                        function: ToolCallFunction {
+                            name: "slow_read".into(),
+                            arguments: json!({ "id": id }).to_string(),
+                        },
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            Box::pin(async move {
+                Ok(LlmResponse {
+                    content: results.join(","),
+                    tool_calls,
+                    finish_reason: "stop".into(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    let ws = TestWorkspace::new();
+    let registry = ToolRegistry::new();
+    registry.register(SlowRead);
+    let ctx = ToolCtx {
+        workspace: ws.root.clone(),
+        restrict_to_workspace: true,
+        chat_id: None,
+        channel: None,
+        outbound_tx: None,
+        delivered: Default::default(),
+        location: None,
+        thread_id: None,
+    };
+
+    let start = Instant::now();
+    let reply = run_agent_loop(
+        &Scripted,
+        &registry,
+        Vec::new(),
+        &ctx,
+        "m",
+        &GenParams::default(),
+        3,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(reply, "call_a=r:a,call_b=r:b,call_c=r:c");
+    assert!(
+        start.elapsed() < Duration::from_millis(800),
+        "three 300ms reads ran concurrently: {:?}",
+        start.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn test_agent_prompt_includes_saved_memories() {
+    use icrab::llm::{GenParams, LlmError, LlmProvider, LlmResponse, Message, Role, ToolDef};
+    use icrab::tools::registry::BoxFuture;
+
+    /// Replies with the per-request system message it was sent.
+    struct EchoSession;
+
+    impl LlmProvider for EchoSession {
+        fn chat_with_params<'a>(
+            &'a self,
+            messages: &'a [Message],
+            _tools: &'a [ToolDef],
+            _model: &'a str,
+            _params: &'a GenParams,
+        ) -> BoxFuture<'a, Result<LlmResponse, LlmError>> {
+            let session = messages
+                .iter()
+                .filter(|m| m.role == Role::System)
+                .nth(1)
+                .map(|m| m.content.clone())
+                .unwrap_or_default();
+            Box::pin(async move {
+                Ok(LlmResponse {
+                    content: session,
+                    tool_calls: Vec::new(),
+                    finish_reason: "stop".into(),
+                    usage: None,
+                })
+            })
+        }
+    }
+
+    let ws = TestWorkspace::new();
+    let db = Arc::new(BrainDb::open(&ws.root).unwrap());
+    let id = db.save_memory("I'm vegetarian", "diet", "123").unwrap();
+    let ctx = ToolCtx {
+        workspace: ws.root.clone(),
+        restrict_to_workspace: true,
+        chat_id: Some(123),
+        channel: Some("telegram".into()),
+        outbound_tx: None,
+        delivered: Default::default(),
+        location: None,
+        thread_id: None,
+    };
+
+    let reply = process_message(
+        &EchoSession,
+        &ToolRegistry::new(),
+        &ws.root,
+        "m",
+        "Europe/London",
+        "chat_memories",
+        "Any dinner ideas?",
+        &ctx,
+        &db,
+    )
+    .await
+    .unwrap();
+    assert!(
+        reply.contains(&format!("#{id} [diet] I'm vegetarian")),
+        "{reply}"
+    );
+}
@@ -99,22 +99,51 @@ pub fn create_test_config_with_telegram(
             bot_token: Some("test_token".to_string()),
             allowed_user_ids: Some(vec![12345]),
             api_base: telegram_api_base.map(|s| s.to_string()),
+            quiet_hours: None,
         }),
         llm: Some(LlmConfig {
             provider: Some("openai".to_string()), // or openrouter
             api_base: Some(llm_endpoint.to_string()),
             api_key: Some("test_key".to_string()),
             model: Some("gpt-4-test".to_string()),
+            ..Default::default()
         }),
         tools: Some(ToolsConfig {
             web: Some(WebConfig {
+                search_provider: None,
                 brave_api_key: Some("test_brave_key".to_string()),
                 brave_max_results: Some(5),
+                searxng_url: None,
+                google_api_key: None,
+                google_cx: None,
                 web_fetch_max_chars: Some(1000),
+                web_fetch_cache_ttl_secs: None,
             }),
+            exec: None,
+            http: None,
+            feeds: None,
+            calendar: None,
+            email: None,
+            ocr: None,
+            translate: None,
+            summarize: None,
+            daily_note: None,
+            habits: None,
+            workout: None,
+            expense: None,
+            bookmark: None,
+            time: None,
+            github: None,
+            image: None,
         }),
         heartbeat: None,
+        cron: None,
         restrict_to_workspace: Some(true),
         timezone: None,
+        voice: None,
+        backup: None,
+        database: None,
+        retention: None,
+        index: None,
     }
 }
@@ -77,6 +77,186 @@ model = "gpt-4"
     );
 }
 
+/// quiet-hours wraps past midnight; malformed values fail validation.
+#[test]
+fn test_config_quiet_hours() {
+    use chrono::NaiveTime;
+    use icrab::config::QuietHours;
+
+    let q = QuietHours::parse("23:00-07:00").expect("valid window");
+    let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+    assert!(q.contains(at(23, 30)));
+    assert!(q.contains(at(6, 59)));
+    assert!(!q.contains(at(7, 0)));
+    assert!(!q.contains(at(12, 0)));
+    assert!(
+        QuietHours::parse("13:00-14:00")
+            .unwrap()
+            .contains(at(13, 15))
+    );
+    assert!(QuietHours::parse("late-early").is_none());
+
+    let tmp = tempfile::TempDir::new().unwrap();
+    let config_path = tmp.path().join("config.toml");
+    let ws_str = tmp.path().to_string_lossy();
+    let config_content = format!(
+        r#"
+workspace = "{ws_str}"
+[telegram]
+bot-token = "t"
+quiet-hours = "11pm-7am"
+[llm]
+api-key = "k"
+model = "m"
+"#
+    );
+    std::fs::write(&config_path, config_content).unwrap();
+    match config::load(&config_path) {
+        Err(ConfigError::Validation(msg)) => {
+            assert!(msg.contains("[telegram] quiet-hours"), "{msg}")
+        }
+        other => panic!("expected Validation error, got {:?}", other),
+    }
+}
+
+/// Local Ollama needs no API key; other providers still do.
+#[test]
+fn test_config_ollama_without_api_key() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let config_path = tmp.path().join("config.toml");
+    let ws_str = tmp.path().to_string_lossy();
+    let write = |provider: &str| {
+        let content = format!(
+            r#"
+workspace = "{ws_str}"
+[telegram]
+bot-token = "t"
+[llm]
+provider = "{provider}"
+api-base = "http://192.168.1.20:11434"
+model = "llama3.1"
+"#
+        );
+        std::fs::write(&config_path, content).unwrap();
+    };
+
+    write("Ollama");
+    let cfg = config::load(&config_path).expect("ollama config is valid without a key");
+    assert!(!cfg.llm.as_ref().unwrap().requires_api_key());
+
+    write("openrouter");
+    match config::load(&config_path) {
+        Err(ConfigError::Validation(msg)) => assert!(msg.contains("api_key"), "{msg}"),
+        other => panic!("expected Validation error, got {:?}", other),
+    }
+}
+
+/// `[llm.agent]` / `[llm.summarize]` tables set per-call sampling.
+#[test]
+fn test_config_generation_params() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let config_path = tmp.path().join("config.toml");
+    let content = format!(
+        r#"
+workspace = "{}"
+[telegram]
+bot-token = "t"
+[llm]
+api-key = "k"
+model = "m"
+[llm.agent]
+temperature = 0.7
+top-p = 0.9
+[llm.summarize]
+max-tokens = 512
+stop = ["END"]
+"#,
+        tmp.path().to_string_lossy()
+    );
+    std::fs::write(&config_path, content).unwrap();
+    let cfg = config::load(&config_path).expect("valid config");
+    let llm = cfg.llm.unwrap();
+    let agent = llm.agent.unwrap();
+    assert_eq!(agent.temperature, Some(0.7));
+    assert_eq!(agent.top_p, Some(0.9));
+    let summarize = llm.summarize.unwrap();
+    assert_eq!(summarize.max_tokens, Some(512));
+    assert_eq!(summarize.stop, vec!["END".to_string()]);
+    assert!(llm.subagent.is_none());
+}
+
+/// `[llm.headers]` and `proxy` parse; auth-like header values count as secrets.
+#[test]
+fn test_config_llm_headers_and_proxy() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let config_path = tmp.path().join("config.toml");
+    let content = format!(
+        r#"
+workspace = "{}"
+[telegram]
+bot-token = "t"
+allowed-user-ids = [4242]
+[llm]
+api-key = "k"
+model = "m"
+proxy = "socks5://me:pw@127.0.0.1:1080"
+[llm.headers]
+X-Title = "iCrab"
+X-Gateway-Key = "gw-secret"
+"#,
+        tmp.path().to_string_lossy()
+    );
+    std::fs::write(&config_path, content).unwrap();
+    let cfg = config::load(&config_path).expect("valid config");
+    let llm = cfg.llm.as_ref().unwrap();
+    assert_eq!(llm.proxy.as_deref(), Some("socks5://me:pw@127.0.0.1:1080"));
+    let headers = llm.headers.as_ref().unwrap();
+    assert_eq!(headers["X-Title"], "iCrab");
+    let secrets = cfg.secrets();
+    for s in ["4242", "gw-secret", "me:pw"] {
+        assert!(secrets.iter().any(|x| x == s), "{s} in {secrets:?}");
+    }
+    assert!(!secrets.iter().any(|x| x == "iCrab"));
+}
+
+/// An explicit search provider needs its settings; unknown providers are rejected.
+#[test]
+fn test_config_search_provider() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let config_path = tmp.path().join("config.toml");
+    let ws_str = tmp.path().to_string_lossy();
+    let load = |web: &str| {
+        let content = format!(
+            r#"
+workspace = "{ws_str}"
+[telegram]
+bot-token = "t"
+[llm]
+api-key = "k"
+model = "m"
+[tools.web]
+{web}
+"#
+        );
+        std::fs::write(&config_path, content).unwrap();
+        config::load(&config_path)
+    };
+
+    let cfg = load("search-provider = \"google\"\ngoogle-api-key = \"gk\"\ngoogle-cx = \"cx\"")
+        .expect("valid config");
+    assert!(cfg.secrets().iter().any(|s| s == "gk"));
+    load("search-provider = \"searxng\"\nsearxng-url = \"https://searx.example.org\"")
+        .expect("valid config");
+    match load("search-provider = \"google\"\ngoogle-api-key = \"gk\"") {
+        Err(ConfigError::Validation(msg)) => assert!(msg.contains("google_cx"), "{msg}"),
+        other => panic!("expected Validation error, got {:?}", other),
+    }
+    match load("search-provider = \"bing\"") {
+        Err(ConfigError::Validation(msg)) => assert!(msg.contains("search_provider"), "{msg}"),
+        other => panic!("expected Validation error, got {:?}", other),
+    }
+}
+
 /// Restore an env var to its previous value (or remove if was unset).
 struct RestoreEnv {
     key: String,
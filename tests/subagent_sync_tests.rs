@@ -26,6 +26,7 @@ async fn subagent_tool_returns_result_synchronously() {
         provider.clone(),
         subagent_registry,
         "gpt-4-test".to_string(),
+        Default::default(),
         ws.root.clone(),
         true,
         5,
@@ -57,6 +58,8 @@ async fn subagent_tool_returns_result_synchronously() {
         channel: Some("telegram".to_string()),
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     let args = json!({
@@ -91,6 +94,7 @@ async fn subagent_tool_missing_task_returns_error() {
         provider,
         registry,
         "m".into(),
+        Default::default(),
         ws.root.clone(),
         true,
         5,
@@ -104,6 +108,8 @@ async fn subagent_tool_missing_task_returns_error() {
         channel: None,
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     let result = tool.execute(&ctx, &json!({})).await;
@@ -27,6 +27,7 @@ async fn test_subagent_spawn_and_completion() {
         provider,
         registry,
         "gpt-4-test".to_string(),
+        Default::default(),
         ws.root.clone(),
         true, // restrict to workspace
         5,    // max iterations
@@ -50,6 +51,7 @@ async fn test_subagent_spawn_and_completion() {
         "Analyze this text".to_string(),
         Some("analysis".to_string()),
         12345,
+        None,
         Arc::new(tx),
         "telegram".to_string(),
     );
@@ -85,6 +87,7 @@ async fn test_subagent_cancellation() {
         provider,
         registry,
         "gpt-4-test".to_string(),
+        Default::default(),
         ws.root.clone(),
         true,
         5,
@@ -114,6 +117,7 @@ async fn test_subagent_cancellation() {
         "Long running task".to_string(),
         None,
         12345,
+        None,
         Arc::new(tx),
         "telegram".to_string(),
     );
@@ -153,6 +157,7 @@ async fn test_subagent_parallel_execution() {
         provider,
         registry,
         "gpt-4-test".to_string(),
+        Default::default(),
         ws.root.clone(),
         true,
         5,
@@ -190,6 +195,7 @@ async fn test_subagent_parallel_execution() {
             format!("Task {}", i),
             None,
             12345,
+            None,
             Arc::new(tx.clone()),
             "telegram".to_string(),
         );
@@ -245,6 +251,7 @@ async fn test_subagent_message_tool_sends_to_outbound() {
         provider,
         registry,
         "gpt-4-test".to_string(),
+        Default::default(),
         ws.root.clone(),
         true,
         5,
@@ -297,6 +304,7 @@ async fn test_subagent_message_tool_sends_to_outbound() {
         "Analyze and report".to_string(),
         None,
         chat_id,
+        None,
         Arc::new(outbound_tx),
         "telegram".to_string(),
     );
@@ -338,6 +346,7 @@ async fn test_subagent_max_iterations_returns_clean_result() {
         provider,
         registry,
         "gpt-4-test".to_string(),
+        Default::default(),
         ws.root.clone(),
         true,
         2, // max_iterations = 2
@@ -374,6 +383,7 @@ async fn test_subagent_max_iterations_returns_clean_result() {
         "Loop task".to_string(),
         None,
         1,
+        None,
         Arc::new(tx),
         "telegram".to_string(),
     );
@@ -413,9 +423,10 @@ async fn test_main_agent_spawn_returns_before_subagent_completes() {
 
     let subagent_registry = Arc::new(ToolRegistry::new());
     let manager = Arc::new(SubagentManager::new(
-        Arc::clone(&provider),
+        provider.clone(),
         subagent_registry,
         "gpt-4-test".to_string(),
+        Default::default(),
         ws.root.clone(),
         true,
         5,
@@ -491,6 +502,8 @@ async fn test_main_agent_spawn_returns_before_subagent_completes() {
         channel: Some("telegram".into()),
         outbound_tx: Some(Arc::new(_out_tx)),
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     let db = std::sync::Arc::new(icrab::memory::db::BrainDb::open(&ws.root).unwrap());
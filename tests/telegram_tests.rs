@@ -6,7 +6,7 @@
 
 use serde_json::json;
 use tokio::time::{Duration, sleep};
-use wiremock::matchers::{method, query_param};
+use wiremock::matchers::{method, path, query_param};
 use wiremock::{Mock, ResponseTemplate};
 
 mod common;
@@ -367,6 +367,206 @@ async fn test_non_text_update_ignored_offset_advances() {
     sleep(Duration::from_millis(300)).await;
 }
 
+/// Location messages arrive with empty text and coordinates; live-location edits are flagged.
+#[tokio::test]
+async fn test_location_update_forwarded() {
+    let ws = TestWorkspace::new();
+    let mock_telegram = MockTelegramServer::new().await;
+    let config = create_test_config_with_telegram(
+        &ws.root,
+        "http://dummy-llm",
+        Some(&mock_telegram.api_base()),
+    );
+
+    Mock::given(method("GET"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ok": true,
+            "result": [{
+                "update_id": 30,
+                "message": {
+                    "from": {"id": 12345},
+                    "chat": {"id": 67890},
+                    "location": {"latitude": 51.5074, "longitude": -0.1278}
+                }
+            }, {
+                "update_id": 31,
+                "edited_message": {
+                    "from": {"id": 12345},
+                    "chat": {"id": 67890},
+                    "location": {"latitude": 51.5, "longitude": -0.13}
+                }
+            }, {
+                "update_id": 32,
+                "edited_message": {
+                    "from": {"id": 12345},
+                    "chat": {"id": 67890},
+                    "text": "edited text is ignored"
+                }
+            }]
+        })))
+        .up_to_n_times(1)
+        .mount(&mock_telegram.server)
+        .await;
+
+    let (inbound_tx, mut inbound_rx) = tokio::sync::mpsc::channel(64);
+    let _outbound_tx = icrab::telegram::spawn_telegram(&config, inbound_tx);
+
+    let first = tokio::time::timeout(Duration::from_secs(2), inbound_rx.recv())
+        .await
+        .expect("location message")
+        .unwrap();
+    assert!(first.text.is_empty());
+    let loc = first.location.expect("coordinates");
+    assert_eq!((loc.latitude, loc.longitude), (51.5074, -0.1278));
+    assert!(!loc.is_update);
+
+    let second = tokio::time::timeout(Duration::from_secs(2), inbound_rx.recv())
+        .await
+        .expect("live location update")
+        .unwrap();
+    assert!(second.location.expect("coordinates").is_update);
+
+    let none = tokio::time::timeout(Duration::from_millis(300), inbound_rx.recv()).await;
+    assert!(none.is_err(), "edited text must not be forwarded");
+}
+
+/// Voice notes arrive with empty text and a file_id that can be downloaded via getFile.
+#[tokio::test]
+async fn test_voice_note_forwarded_and_downloadable() {
+    let ws = TestWorkspace::new();
+    let mock_telegram = MockTelegramServer::new().await;
+    let config = create_test_config_with_telegram(
+        &ws.root,
+        "http://dummy-llm",
+        Some(&mock_telegram.api_base()),
+    );
+
+    Mock::given(method("GET"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ok": true,
+            "result": [{
+                "update_id": 40,
+                "message": {
+                    "from": {"id": 12345},
+                    "chat": {"id": 67890},
+                    "voice": {"file_id": "VOICE_1", "duration": 3}
+                }
+            }]
+        })))
+        .up_to_n_times(1)
+        .mount(&mock_telegram.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/bottest_token/getFile"))
+        .and(query_param("file_id", "VOICE_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ok": true,
+            "result": {"file_id": "VOICE_1", "file_path": "voice/file_1.oga"}
+        })))
+        .mount(&mock_telegram.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/file/bottest_token/voice/file_1.oga"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"OggS-data".to_vec()))
+        .mount(&mock_telegram.server)
+        .await;
+
+    let (inbound_tx, mut inbound_rx) = tokio::sync::mpsc::channel(64);
+    let _outbound_tx = icrab::telegram::spawn_telegram(&config, inbound_tx);
+
+    let msg = tokio::time::timeout(Duration::from_secs(2), inbound_rx.recv())
+        .await
+        .expect("voice message")
+        .unwrap();
+    assert!(msg.text.is_empty());
+    let file_id = msg.voice.expect("voice file_id");
+    assert_eq!(file_id, "VOICE_1");
+
+    let bytes = icrab::telegram::download_file(config.telegram.as_ref().unwrap(), &file_id)
+        .await
+        .unwrap();
+    assert_eq!(bytes, b"OggS-data");
+}
+
+/// sendPoll registers the poll; `poll` updates from getUpdates refresh the tally and
+/// advance the offset even though they carry no message.
+#[tokio::test]
+async fn test_poll_results_tracked_from_updates() {
+    use icrab::telegram::{NewPoll, TelegramShared};
+
+    let ws = TestWorkspace::new();
+    let mock_telegram = MockTelegramServer::new().await;
+    let config = create_test_config_with_telegram(
+        &ws.root,
+        "http://dummy-llm",
+        Some(&mock_telegram.api_base()),
+    );
+    let poll_json = |total: i64, a: i64, b: i64| {
+        json!({
+            "id": "P1",
+            "question": "Which workout?",
+            "options": [{"text": "A", "voter_count": a}, {"text": "B", "voter_count": b}],
+            "total_voter_count": total,
+            "is_closed": false
+        })
+    };
+
+    Mock::given(method("POST"))
+        .and(path("/bottest_token/sendPoll"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ok": true,
+            "result": {"message_id": 77, "chat": {"id": 67890}, "poll": poll_json(0, 0, 0)}
+        })))
+        .mount(&mock_telegram.server)
+        .await;
+    let shared = TelegramShared::default();
+    let poll_id = icrab::telegram::send_poll(
+        config.telegram.as_ref().unwrap(),
+        &shared.polls,
+        &NewPoll {
+            chat_id: 67890,
+            question: "Which workout?".into(),
+            options: vec!["A".into(), "B".into()],
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(poll_id, "P1");
+    assert_eq!(shared.polls.get("P1").unwrap().message_id, 77);
+
+    Mock::given(method("GET"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ok": true,
+            "result": [{"update_id": 50, "poll": poll_json(3, 2, 1)}]
+        })))
+        .up_to_n_times(1)
+        .mount(&mock_telegram.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(query_param("offset", "51"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true, "result": []})))
+        .expect(1..)
+        .mount(&mock_telegram.server)
+        .await;
+
+    let (inbound_tx, _inbound_rx) = tokio::sync::mpsc::channel(64);
+    let polls = std::sync::Arc::clone(&shared.polls);
+    let _outbound_tx = icrab::telegram::spawn_telegram_with_shared(&config, inbound_tx, shared);
+    sleep(Duration::from_millis(500)).await;
+
+    let state = polls.get("P1").unwrap();
+    assert_eq!(state.total_voters, 3);
+    assert_eq!(
+        state.options,
+        vec![("A".to_string(), 2), ("B".to_string(), 1)]
+    );
+    assert!(state.tally().contains("A: 2 (66%)"));
+}
+
 /// ok: false or empty result does not crash; empty result does not advance offset.
 #[tokio::test]
 async fn test_ok_false_does_not_crash_or_advance_offset() {
@@ -409,3 +609,134 @@ async fn test_ok_false_does_not_crash_or_advance_offset() {
 
     sleep(Duration::from_millis(200)).await;
 }
+
+// --- Outbound queue: retry on 429 honoring retry_after ---
+
+#[tokio::test]
+async fn test_send_retries_after_rate_limit() {
+    use icrab::telegram::{OutboundKind, OutboundMsg};
+    use wiremock::matchers::path_regex;
+
+    let ws = TestWorkspace::new();
+    let mock_telegram = MockTelegramServer::new().await;
+    let config = create_test_config_with_telegram(
+        &ws.root,
+        "http://dummy-llm",
+        Some(&mock_telegram.api_base()),
+    );
+    mock_telegram
+        .mock_get_updates(json!({ "ok": true, "result": [] }))
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"/bot[^/]+/sendMessage"))
+        .respond_with(ResponseTemplate::new(429).set_body_json(json!({
+            "ok": false,
+            "error_code": 429,
+            "description": "Too Many Requests: retry after 1",
+            "parameters": { "retry_after": 1 }
+        })))
+        .up_to_n_times(1)
+        .mount(&mock_telegram.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path_regex(r"/bot[^/]+/sendMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+        .mount(&mock_telegram.server)
+        .await;
+
+    let (inbound_tx, _inbound_rx) = tokio::sync::mpsc::channel(64);
+    let outbound_tx = icrab::telegram::spawn_telegram(&config, inbound_tx);
+    outbound_tx
+        .send(OutboundMsg {
+            chat_id: 67890,
+            text: "hello".into(),
+            channel: "telegram".into(),
+            kind: OutboundKind::Text,
+            thread_id: None,
+            disable_notification: false,
+        })
+        .await
+        .unwrap();
+
+    // While rate limited the message sits in the on-disk spool.
+    sleep(Duration::from_millis(300)).await;
+    let spool = icrab::workspace::outbox_file(&ws.root);
+    assert!(spool.exists(), "undelivered message should be spooled");
+
+    sleep(Duration::from_millis(1500)).await;
+    let sends = mock_telegram
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|r| r.url.path().ends_with("/sendMessage"))
+        .count();
+    assert_eq!(sends, 2, "one 429 then one successful retry");
+    assert!(!spool.exists(), "spool removed after delivery");
+}
+
+// --- Live (streamed) replies: first update is sent, later ones edit it in place ---
+
+#[tokio::test]
+async fn test_stream_updates_edit_the_same_message() {
+    use icrab::telegram::{OutboundKind, OutboundMsg};
+    use wiremock::matchers::path_regex;
+
+    let ws = TestWorkspace::new();
+    let mock_telegram = MockTelegramServer::new().await;
+    let config = create_test_config_with_telegram(
+        &ws.root,
+        "http://dummy-llm",
+        Some(&mock_telegram.api_base()),
+    );
+    mock_telegram
+        .mock_get_updates(json!({ "ok": true, "result": [] }))
+        .await;
+    Mock::given(method("POST"))
+        .and(path_regex(r"/bot[^/]+/sendMessage"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "ok": true, "result": { "message_id": 77 } })),
+        )
+        .mount(&mock_telegram.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path_regex(r"/bot[^/]+/editMessageText"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+        .mount(&mock_telegram.server)
+        .await;
+
+    let (inbound_tx, _inbound_rx) = tokio::sync::mpsc::channel(64);
+    let outbound_tx = icrab::telegram::spawn_telegram(&config, inbound_tx);
+    let update = |text: &str, done: bool| OutboundMsg {
+        chat_id: 67890,
+        text: text.into(),
+        channel: "telegram".into(),
+        kind: OutboundKind::Stream {
+            key: "reply-1".into(),
+            done,
+        },
+        thread_id: None,
+        disable_notification: false,
+    };
+    outbound_tx.send(update("Leg …", false)).await.unwrap();
+    sleep(Duration::from_millis(300)).await;
+    outbound_tx.send(update("Leg day", true)).await.unwrap();
+    sleep(Duration::from_millis(1500)).await;
+
+    let requests = mock_telegram.server.received_requests().await.unwrap();
+    let sends = requests
+        .iter()
+        .filter(|r| r.url.path().ends_with("/sendMessage"))
+        .count();
+    assert_eq!(sends, 1, "only the first update is a new message");
+    let edit = requests
+        .iter()
+        .find(|r| r.url.path().ends_with("/editMessageText"))
+        .expect("final text is an edit");
+    let body: serde_json::Value = serde_json::from_slice(&edit.body).unwrap();
+    assert_eq!(body["message_id"], 77);
+    assert_eq!(body["text"], "Leg day");
+}
@@ -2,7 +2,7 @@ use serde_json::json;
 use wiremock::{Mock, ResponseTemplate, matchers::method};
 
 use icrab::tools::context::ToolCtx;
-use icrab::tools::file::{AppendFile, EditFile, ListDir, ReadFile, WriteFile};
+use icrab::tools::file::{AppendFile, CopyFile, EditFile, ListDir, MultiEdit, ReadFile, WriteFile};
 use icrab::tools::message::MessageTool;
 use icrab::tools::registry::Tool;
 use icrab::tools::web::{WebFetchTool, web_client};
@@ -20,6 +20,8 @@ async fn test_file_ops() {
         channel: None,
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     // 1. Write file
@@ -64,6 +66,8 @@ async fn test_path_traversal() {
         channel: None,
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     let read_tool = ReadFile;
@@ -89,6 +93,8 @@ fn ctx_restricted(workspace: &std::path::Path) -> ToolCtx {
         channel: None,
         outbound_tx: None,
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     }
 }
 
@@ -164,6 +170,24 @@ async fn test_path_traversal_list_dir() {
     );
 }
 
+#[tokio::test]
+async fn test_path_traversal_copy_file() {
+    let ws = TestWorkspace::new();
+    std::fs::write(ws.root.join("note.md"), "x").unwrap();
+    for args in [
+        json!({ "source": "../../../etc/passwd", "destination": "passwd" }),
+        json!({ "source": "note.md", "destination": "../../../tmp/escape.md" }),
+    ] {
+        let res = CopyFile.execute(&ctx_restricted(&ws.root), &args).await;
+        assert!(res.is_error);
+        assert!(
+            res.for_llm.contains("escape") || res.for_llm.contains("restricted"),
+            "{}",
+            res.for_llm
+        );
+    }
+}
+
 // --- §3.3 edit_file / append_file on missing file ---
 
 #[tokio::test]
@@ -183,6 +207,61 @@ async fn test_edit_file_missing_file_returns_error() {
     assert!(!res.for_llm.is_empty());
 }
 
+#[tokio::test]
+async fn test_multi_edit_is_all_or_nothing() {
+    let ws = TestWorkspace::new();
+    let path = ws.root.join("plan.md");
+    std::fs::write(&path, "Mon: run\nWed: run\nFri: rest\n").unwrap();
+    let ctx = ctx_restricted(&ws.root);
+
+    let res = MultiEdit
+        .execute(
+            &ctx,
+            &json!({
+                "path": "plan.md",
+                "edits": [
+                    { "old_text": "run", "new_text": "swim", "occurrence": 2 },
+                    { "old_text": "Sun: rest", "new_text": "Sun: hike" }
+                ]
+            }),
+        )
+        .await;
+    assert!(res.is_error);
+    assert!(res.for_llm.contains("edit 2"), "{}", res.for_llm);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Mon: run\nWed: run\nFri: rest\n"
+    );
+
+    let edits = json!([
+        { "old_text": "run", "new_text": "swim", "occurrence": 2 },
+        { "old_text": "Fri: rest", "new_text": "Fri: hike" }
+    ]);
+    let res = MultiEdit
+        .execute(
+            &ctx,
+            &json!({ "path": "plan.md", "edits": edits, "dry_run": true }),
+        )
+        .await;
+    assert!(!res.is_error, "{}", res.for_llm);
+    assert!(
+        res.for_llm
+            .ends_with("-Wed: run\n-Fri: rest\n+Wed: swim\n+Fri: hike\n"),
+        "{}",
+        res.for_llm
+    );
+    assert!(std::fs::read_to_string(&path).unwrap().contains("Wed: run"));
+
+    let res = MultiEdit
+        .execute(&ctx, &json!({ "path": "plan.md", "edits": edits }))
+        .await;
+    assert!(!res.is_error, "{}", res.for_llm);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Mon: run\nWed: swim\nFri: hike\n"
+    );
+}
+
 #[tokio::test]
 async fn test_append_file_creates_missing_file() {
     let ws = TestWorkspace::new();
@@ -204,6 +283,38 @@ async fn test_append_file_creates_missing_file() {
     assert_eq!(content, "first line\n");
 }
 
+#[tokio::test]
+async fn test_copy_file_instantiates_template() {
+    let ws = TestWorkspace::new();
+    std::fs::create_dir_all(ws.root.join("Templates")).unwrap();
+    std::fs::write(ws.root.join("Templates/Daily.md"), "# Daily\n- [ ] \n").unwrap();
+    let ctx = ctx_restricted(&ws.root);
+    let args = json!({
+        "source": "Templates/Daily.md",
+        "destination": "Daily log/2026-10-16.md"
+    });
+
+    let res = CopyFile.execute(&ctx, &args).await;
+    assert!(!res.is_error, "{}", res.for_llm);
+    assert_eq!(
+        std::fs::read_to_string(ws.root.join("Daily log/2026-10-16.md")).unwrap(),
+        "# Daily\n- [ ] \n"
+    );
+
+    let res = CopyFile.execute(&ctx, &args).await;
+    assert!(res.is_error, "existing destination must not be overwritten");
+    assert!(res.for_llm.contains("already exists"), "{}", res.for_llm);
+
+    let mut args = args;
+    args["overwrite"] = json!(true);
+    assert!(!CopyFile.execute(&ctx, &args).await.is_error);
+
+    let res = CopyFile
+        .execute(&ctx, &json!({ "source": "Templates", "destination": "T2" }))
+        .await;
+    assert!(res.is_error, "directories are not copied");
+}
+
 // --- §3.3 message tool delivers to outbound with correct chat_id ---
 
 #[tokio::test]
@@ -233,6 +344,8 @@ async fn test_message_tool_sends_to_outbound() {
         channel: Some("telegram".into()),
         outbound_tx: Some(std::sync::Arc::new(outbound_tx)),
         delivered: Default::default(),
+        location: None,
+        thread_id: None,
     };
 
     // 1st call: LLM uses message tool
@@ -293,6 +406,50 @@ async fn test_message_tool_sends_to_outbound() {
     assert_eq!(out.text, "Hello from message tool");
 }
 
+// --- send_file tool queues a Document outbound message ---
+
+#[tokio::test]
+async fn test_send_file_tool_queues_document() {
+    use icrab::telegram::OutboundKind;
+    use icrab::tools::send_file::SendFileTool;
+    use tokio::sync::mpsc;
+
+    let ws = TestWorkspace::new();
+    std::fs::write(ws.root.join("report.md"), "# Report\n").unwrap();
+    let (outbound_tx, mut outbound_rx) = mpsc::channel(8);
+    let ctx = ToolCtx {
+        workspace: ws.root.clone(),
+        restrict_to_workspace: true,
+        chat_id: Some(7),
+        channel: Some("telegram".into()),
+        outbound_tx: Some(std::sync::Arc::new(outbound_tx)),
+        delivered: Default::default(),
+        location: None,
+        thread_id: None,
+    };
+
+    let res = SendFileTool
+        .execute(&ctx, &json!({ "path": "report.md", "caption": "weekly" }))
+        .await;
+    assert!(!res.is_error, "send_file failed: {}", res.for_llm);
+    let out = outbound_rx.try_recv().expect("one outbound message");
+    assert_eq!(out.chat_id, 7);
+    assert_eq!(out.text, "weekly");
+    match out.kind {
+        OutboundKind::Document(p) => assert!(p.ends_with("report.md")),
+        other => panic!("expected document, got {other:?}"),
+    }
+
+    let res = SendFileTool
+        .execute(&ctx, &json!({ "path": "../outside.md" }))
+        .await;
+    assert!(res.is_error);
+    let res = SendFileTool
+        .execute(&ctx, &json!({ "path": "missing.md" }))
+        .await;
+    assert!(res.is_error);
+}
+
 // --- §3.3 Web tools degrade gracefully (web_fetch with mock server) ---
 
 #[tokio::test]